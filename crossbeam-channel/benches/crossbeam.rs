@@ -5,7 +5,8 @@ extern crate crossbeam_utils;
 extern crate num_cpus;
 extern crate test;
 
-use crossbeam_channel::{bounded, unbounded};
+use crossbeam_channel::{bounded, bounded_pow2, spsc_bounded, unbounded};
+use crossbeam_channel::{Receiver, Select, SelectSet, Sender};
 use crossbeam_utils::thread::scope;
 use test::Bencher;
 
@@ -399,6 +400,137 @@ mod bounded_n {
     }
 }
 
+mod bounded_pow2 {
+    use super::*;
+
+    // A capacity just below a power of two, to compare against the rounded up capacity.
+    const CAP: usize = 1000;
+
+    fn fill_drain(s: &Sender<i32>, r: &Receiver<i32>) {
+        let cap = s.capacity().unwrap();
+        for i in 0..cap {
+            s.send(i as i32).unwrap();
+        }
+        for _ in 0..cap {
+            r.recv().unwrap();
+        }
+    }
+
+    #[bench]
+    fn exact_fill_drain(b: &mut Bencher) {
+        let (s, r) = bounded::<i32>(CAP);
+        b.iter(|| fill_drain(&s, &r));
+    }
+
+    #[bench]
+    fn pow2_fill_drain(b: &mut Bencher) {
+        let (s, r) = bounded_pow2::<i32>(CAP);
+        b.iter(|| fill_drain(&s, &r));
+    }
+
+    fn spsc(s: &Sender<i32>, r: &Receiver<i32>, b: &mut Bencher) {
+        let steps = TOTAL_STEPS;
+
+        let (s1, r1) = bounded(0);
+        let (s2, r2) = bounded(0);
+        scope(|scope| {
+            scope.spawn(|_| {
+                while r1.recv().is_ok() {
+                    for i in 0..steps {
+                        s.send(i as i32).unwrap();
+                    }
+                    s2.send(()).unwrap();
+                }
+            });
+
+            b.iter(|| {
+                s1.send(()).unwrap();
+                for _ in 0..steps {
+                    r.recv().unwrap();
+                }
+                r2.recv().unwrap();
+            });
+            drop(s1);
+        })
+        .unwrap();
+    }
+
+    #[bench]
+    fn exact_spsc(b: &mut Bencher) {
+        let (s, r) = bounded::<i32>(CAP);
+        spsc(&s, &r, b);
+    }
+
+    #[bench]
+    fn pow2_spsc(b: &mut Bencher) {
+        let (s, r) = bounded_pow2::<i32>(CAP);
+        spsc(&s, &r, b);
+    }
+}
+
+mod spsc_bounded {
+    use super::*;
+
+    const CAP: usize = 1000;
+
+    #[bench]
+    fn create(b: &mut Bencher) {
+        b.iter(|| spsc_bounded::<i32>(CAP));
+    }
+
+    #[bench]
+    fn inout(b: &mut Bencher) {
+        let (s, r) = spsc_bounded::<i32>(CAP);
+        b.iter(|| {
+            s.send(0).unwrap();
+            r.recv().unwrap();
+        });
+    }
+
+    #[bench]
+    fn fill_drain(b: &mut Bencher) {
+        let (s, r) = spsc_bounded::<i32>(CAP);
+        b.iter(|| {
+            for i in 0..CAP {
+                s.send(i as i32).unwrap();
+            }
+            for _ in 0..CAP {
+                r.recv().unwrap();
+            }
+        });
+    }
+
+    #[bench]
+    fn spsc(b: &mut Bencher) {
+        let steps = TOTAL_STEPS;
+        let (s, r) = spsc_bounded::<i32>(CAP);
+
+        let (s1, r1) = bounded(0);
+        let (s2, r2) = bounded(0);
+        scope(|scope| {
+            // The sender can't be shared between threads, so it is moved into the thread.
+            scope.spawn(move |_| {
+                while r1.recv().is_ok() {
+                    for i in 0..steps {
+                        s.send(i as i32).unwrap();
+                    }
+                    s2.send(()).unwrap();
+                }
+            });
+
+            b.iter(|| {
+                s1.send(()).unwrap();
+                for _ in 0..steps {
+                    r.recv().unwrap();
+                }
+                r2.recv().unwrap();
+            });
+            drop(s1);
+        })
+        .unwrap();
+    }
+}
+
 mod bounded_1 {
     use super::*;
 
@@ -713,3 +845,48 @@ mod bounded_0 {
         .unwrap();
     }
 }
+
+mod select_set {
+    use super::*;
+
+    const RECEIVERS: usize = 8;
+
+    fn channels() -> (Vec<Sender<i32>>, Vec<Receiver<i32>>) {
+        (0..RECEIVERS).map(|_| unbounded()).unzip()
+    }
+
+    #[bench]
+    fn select_loop(b: &mut Bencher) {
+        let (ss, rs) = channels();
+        b.iter(|| {
+            for s in &ss {
+                s.send(0).unwrap();
+            }
+            for _ in 0..RECEIVERS {
+                let mut sel = Select::new();
+                for r in &rs {
+                    sel.recv(r);
+                }
+                let oper = sel.select();
+                let i = oper.index();
+                oper.recv(&rs[i]).unwrap();
+            }
+        });
+    }
+
+    #[bench]
+    fn select_set(b: &mut Bencher) {
+        let (ss, rs) = channels();
+        let mut set = rs.iter().fold(SelectSet::new(), |set, r| set.add_recv(r));
+        b.iter(|| {
+            for s in &ss {
+                s.send(0).unwrap();
+            }
+            for _ in 0..RECEIVERS {
+                let oper = set.select();
+                let i = oper.index();
+                oper.recv(&rs[i]).unwrap();
+            }
+        });
+    }
+}
@@ -0,0 +1,460 @@
+//! Channel with acknowledged deliveries that are redelivered unless acknowledged.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use channel::{bounded, unbounded, Receiver, Sender};
+use err::{RecvError, RecvTimeoutError, TryRecvError};
+use select::Select;
+
+/// Where redelivered messages are placed relative to messages that haven't been received yet.
+///
+/// Used by [`ack_channel_with_redelivery`].
+///
+/// [`ack_channel_with_redelivery`]: fn.ack_channel_with_redelivery.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Redelivery {
+    /// Redelivered messages are received before any messages still in the channel.
+    ///
+    /// A message that failed is retried as soon as possible, so messages stay roughly in the order
+    /// they were sent. A message that keeps failing is retried over and over, though, and holds up
+    /// the rest of the channel.
+    Front,
+
+    /// Redelivered messages are received only when the channel has no other messages ready.
+    ///
+    /// A message that keeps failing doesn't hold up the rest of the channel, but under a steady
+    /// stream of new messages a redelivered message may wait for a long time.
+    Back,
+}
+
+/// State shared by all receivers and deliveries of an acknowledged channel.
+struct Shared<T> {
+    /// Where redelivered messages are placed.
+    redelivery: Redelivery,
+
+    /// Sends messages back into the redelivery queue.
+    requeue_s: Sender<T>,
+
+    /// Receives redelivered messages.
+    requeue_r: Receiver<T>,
+
+    /// The number of messages received from the channel but not acknowledged yet.
+    ///
+    /// This counts both messages held by deliveries and messages in the redelivery queue. Receivers
+    /// also count themselves here while they are taking a message out of the channel, so that
+    /// other receivers never see zero while a message is on its way to becoming a delivery.
+    outstanding: AtomicUsize,
+
+    /// Set once a receiver observes that the channel is empty and disconnected.
+    disconnected: AtomicBool,
+
+    /// Dropped once the channel is disconnected and all messages are acknowledged.
+    done_s: Mutex<Option<Sender<()>>>,
+
+    /// Becomes ready when `done_s` is dropped, waking up all blocked receivers.
+    done_r: Receiver<()>,
+}
+
+impl<T> Shared<T> {
+    /// Decrements the number of outstanding messages.
+    fn release(&self) {
+        if self.outstanding.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.is_done();
+        }
+    }
+
+    /// Returns `true` if the channel is disconnected and all messages are acknowledged.
+    ///
+    /// The first time this happens, blocked receivers are woken up.
+    fn is_done(&self) -> bool {
+        if self.disconnected.load(Ordering::SeqCst) && self.outstanding.load(Ordering::SeqCst) == 0
+        {
+            self.done_s.lock().unwrap().take();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Creates a bounded channel with at-least-once delivery.
+///
+/// Messages are sent through an ordinary [`Sender`], but received through an [`AckReceiver`],
+/// which hands out each message wrapped in a [`Delivery`]. A message stays in flight until
+/// [`Delivery::ack`] is called. If the delivery is dropped without being acknowledged, for
+/// example because the worker processing it panicked, the message is put back into the channel
+/// and received again by the same or another receiver.
+///
+/// Redelivered messages are received before messages still waiting in the channel. Use
+/// [`ack_channel_with_redelivery`] to have them received after those instead.
+///
+/// Receivers get disconnected only once all senders are dropped, the channel is empty, and every
+/// delivery has been acknowledged, since any delivery that is still in flight may come back.
+///
+/// [`Sender`]: struct.Sender.html
+/// [`AckReceiver`]: struct.AckReceiver.html
+/// [`Delivery`]: struct.Delivery.html
+/// [`Delivery::ack`]: struct.Delivery.html#method.ack
+/// [`ack_channel_with_redelivery`]: fn.ack_channel_with_redelivery.html
+///
+/// # Panics
+///
+/// Panics if the capacity is too large, just like [`bounded`].
+///
+/// [`bounded`]: fn.bounded.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::ack_channel;
+///
+/// let (s, r) = ack_channel(4);
+/// s.send("job").unwrap();
+///
+/// // The worker drops the delivery without acknowledging it.
+/// let d = r.recv().unwrap();
+/// assert_eq!(*d, "job");
+/// drop(d);
+///
+/// // The message is delivered again.
+/// let d = r.recv().unwrap();
+/// assert_eq!(d.ack(), "job");
+/// ```
+pub fn ack_channel<T>(cap: usize) -> (Sender<T>, AckReceiver<T>) {
+    ack_channel_with_redelivery(cap, Redelivery::Front)
+}
+
+/// Creates a bounded channel with at-least-once delivery and the given redelivery order.
+///
+/// This is just like [`ack_channel`], except the place of redelivered messages is decided by
+/// `redelivery`. See [`Redelivery`] for the trade-offs.
+///
+/// [`ack_channel`]: fn.ack_channel.html
+/// [`Redelivery`]: enum.Redelivery.html
+///
+/// # Panics
+///
+/// Panics if the capacity is too large, just like [`bounded`].
+///
+/// [`bounded`]: fn.bounded.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{ack_channel_with_redelivery, Redelivery};
+///
+/// let (s, r) = ack_channel_with_redelivery(4, Redelivery::Back);
+/// s.send(1).unwrap();
+/// s.send(2).unwrap();
+///
+/// // The first message fails and goes behind the second one.
+/// drop(r.recv().unwrap());
+///
+/// assert_eq!(r.recv().unwrap().ack(), 2);
+/// assert_eq!(r.recv().unwrap().ack(), 1);
+/// ```
+pub fn ack_channel_with_redelivery<T>(
+    cap: usize,
+    redelivery: Redelivery,
+) -> (Sender<T>, AckReceiver<T>) {
+    let (s, r) = bounded(cap);
+    let (requeue_s, requeue_r) = unbounded();
+    let (done_s, done_r) = bounded(0);
+
+    let receiver = AckReceiver {
+        receiver: r,
+        shared: Arc::new(Shared {
+            redelivery,
+            requeue_s,
+            requeue_r,
+            outstanding: AtomicUsize::new(0),
+            disconnected: AtomicBool::new(false),
+            done_s: Mutex::new(Some(done_s)),
+            done_r,
+        }),
+    };
+    (s, receiver)
+}
+
+/// The receiving side of a channel created by [`ack_channel`].
+///
+/// Receivers can be cloned to share work among several consumers. A message that isn't
+/// acknowledged by one of them can be redelivered to any of them.
+///
+/// [`ack_channel`]: fn.ack_channel.html
+pub struct AckReceiver<T> {
+    /// Receives messages that haven't been delivered yet.
+    receiver: Receiver<T>,
+
+    /// State shared with other receivers and with deliveries.
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> AckReceiver<T> {
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// This method will either receive a message from the channel immediately or return an error
+    /// if the channel is empty. An error is also returned if the channel is disconnected and every
+    /// delivery has been acknowledged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{ack_channel, TryRecvError};
+    ///
+    /// let (s, r) = ack_channel(1);
+    /// s.send(5).unwrap();
+    /// drop(s);
+    ///
+    /// let d = r.try_recv().unwrap();
+    ///
+    /// // The delivery might still be redelivered, so the channel isn't disconnected yet.
+    /// assert_eq!(r.try_recv().unwrap_err(), TryRecvError::Empty);
+    ///
+    /// assert_eq!(d.ack(), 5);
+    /// assert_eq!(r.try_recv().unwrap_err(), TryRecvError::Disconnected);
+    /// ```
+    pub fn try_recv(&self) -> Result<Delivery<T>, TryRecvError> {
+        let shared = &*self.shared;
+
+        if shared.redelivery == Redelivery::Front {
+            if let Ok(msg) = shared.requeue_r.try_recv() {
+                return Ok(self.deliver(msg));
+            }
+        }
+
+        // Count the message before taking it out so that no other receiver concludes that all
+        // messages have been acknowledged while this one is not a delivery yet.
+        shared.outstanding.fetch_add(1, Ordering::SeqCst);
+        match self.receiver.try_recv() {
+            Ok(msg) => return Ok(self.deliver(msg)),
+            Err(TryRecvError::Disconnected) => shared.disconnected.store(true, Ordering::SeqCst),
+            Err(TryRecvError::Empty) => {}
+        }
+        shared.release();
+
+        if shared.redelivery == Redelivery::Back {
+            if let Ok(msg) = shared.requeue_r.try_recv() {
+                return Ok(self.deliver(msg));
+            }
+        }
+
+        if shared.is_done() {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Blocks the current thread until a message is received or the channel is disconnected.
+    ///
+    /// If the channel is empty and not disconnected, this call will block until a message is sent
+    /// or redelivered. If the channel is empty and disconnected, this call will keep blocking
+    /// until every delivery has been acknowledged, and then return an error. A delivery that is
+    /// dropped in the meantime is received instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{ack_channel, RecvError};
+    ///
+    /// let (s, r) = ack_channel(1);
+    /// s.send(1).unwrap();
+    /// drop(s);
+    ///
+    /// let d = r.recv().unwrap();
+    ///
+    /// // A worker fails to process the message.
+    /// thread::spawn(move || drop(d));
+    ///
+    /// assert_eq!(r.recv().unwrap().ack(), 1);
+    /// assert_eq!(r.recv().unwrap_err(), RecvError);
+    /// ```
+    pub fn recv(&self) -> Result<Delivery<T>, RecvError> {
+        loop {
+            match self.try_recv() {
+                Ok(d) => return Ok(d),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                Err(TryRecvError::Empty) => {}
+            }
+            self.select().ready();
+        }
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    ///
+    /// This method behaves just like [`recv`], except it returns an error if no message arrives
+    /// within the specified duration.
+    ///
+    /// [`recv`]: struct.AckReceiver.html#method.recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{ack_channel, RecvTimeoutError};
+    ///
+    /// let (s, r) = ack_channel::<i32>(1);
+    ///
+    /// assert_eq!(
+    ///     r.recv_timeout(Duration::from_millis(100)).unwrap_err(),
+    ///     RecvTimeoutError::Timeout,
+    /// );
+    /// # drop(s);
+    /// ```
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Delivery<T>, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            match self.try_recv() {
+                Ok(d) => return Ok(d),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            let now = Instant::now();
+            if now >= deadline || self.select().ready_timeout(deadline - now).is_err() {
+                return Err(RecvTimeoutError::Timeout);
+            }
+        }
+    }
+
+    /// Returns the number of messages received but not acknowledged yet.
+    ///
+    /// This includes messages waiting to be redelivered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::ack_channel;
+    ///
+    /// let (s, r) = ack_channel(2);
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    ///
+    /// let d = r.recv().unwrap();
+    /// assert_eq!(r.outstanding(), 1);
+    ///
+    /// drop(d);
+    /// assert_eq!(r.outstanding(), 1);
+    ///
+    /// r.recv().unwrap().ack();
+    /// assert_eq!(r.outstanding(), 0);
+    /// ```
+    pub fn outstanding(&self) -> usize {
+        self.shared.outstanding.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of messages in the channel that haven't been received yet.
+    pub fn len(&self) -> usize {
+        self.receiver.len()
+    }
+
+    /// Returns `true` if the channel has no messages that haven't been received yet.
+    pub fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> usize {
+        self.receiver.capacity().unwrap()
+    }
+
+    /// Wraps a message that has just been counted as outstanding into a delivery.
+    fn deliver(&self, msg: T) -> Delivery<T> {
+        Delivery {
+            msg: Some(msg),
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns a `Select` that becomes ready when `try_recv` might succeed or report
+    /// disconnection.
+    fn select(&self) -> Select<'_> {
+        let shared = &*self.shared;
+        let mut sel = Select::new();
+        sel.recv(&shared.requeue_r);
+        sel.recv(&shared.done_r);
+        if !shared.disconnected.load(Ordering::SeqCst) {
+            sel.recv(&self.receiver);
+        }
+        sel
+    }
+}
+
+impl<T> Clone for AckReceiver<T> {
+    fn clone(&self) -> Self {
+        AckReceiver {
+            receiver: self.receiver.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for AckReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("AckReceiver { .. }")
+    }
+}
+
+/// A message received from an [`AckReceiver`] that is redelivered unless acknowledged.
+///
+/// The message can be accessed through `Deref` and `DerefMut`. Calling [`ack`] marks it as
+/// processed and takes it out. Dropping the delivery without acknowledging it puts the message
+/// back into the channel, including when the thread holding it panics.
+///
+/// [`AckReceiver`]: struct.AckReceiver.html
+/// [`ack`]: struct.Delivery.html#method.ack
+pub struct Delivery<T> {
+    /// The message, taken out by `ack`.
+    msg: Option<T>,
+
+    /// State of the channel the message was received from.
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Delivery<T> {
+    /// Acknowledges the message and returns it.
+    ///
+    /// The message will not be delivered again.
+    pub fn ack(mut self) -> T {
+        let msg = self.msg.take().unwrap();
+        self.shared.release();
+        msg
+    }
+}
+
+impl<T> Deref for Delivery<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.msg.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for Delivery<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.msg.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for Delivery<T> {
+    fn drop(&mut self) {
+        if let Some(msg) = self.msg.take() {
+            // This cannot fail because the redelivery queue is kept alive by `self.shared`. The
+            // message stays outstanding, so nothing has to be released.
+            let _ = self.shared.requeue_s.send(msg);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Delivery<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Delivery").field(&**self).finish()
+    }
+}
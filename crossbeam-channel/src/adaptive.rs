@@ -0,0 +1,208 @@
+//! Receiver adapter that waits with timeouts growing exponentially while the channel is idle.
+
+use std::cmp;
+use std::fmt;
+use std::time::Duration;
+
+use channel::Receiver;
+use err::{RecvError, RecvTimeoutError, TryRecvError};
+
+/// A receiver that waits for messages with timeouts that back off while the channel is idle.
+///
+/// Every wait is a call to [`Receiver::recv_timeout`]. The first wait uses the minimum timeout.
+/// Each time a wait times out with the channel still empty, the next timeout is multiplied by the
+/// backoff factor, up to the maximum timeout. As soon as a message is received, the timeout goes
+/// back to the minimum.
+///
+/// This suits pollers that wake up periodically to do other work: while messages keep arriving,
+/// they are noticed quickly, and while the channel is idle, there are fewer and fewer wakeups.
+///
+/// [`Receiver::recv_timeout`]: struct.Receiver.html#method.recv_timeout
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use crossbeam_channel::{unbounded, BackoffReceiver, RecvTimeoutError};
+///
+/// let (s, r) = unbounded();
+/// let mut r = BackoffReceiver::new(r, Duration::from_millis(1), Duration::from_millis(8), 2);
+///
+/// assert_eq!(r.recv_timeout_adaptive(), Err(RecvTimeoutError::Timeout));
+/// assert_eq!(r.recv_timeout_adaptive(), Err(RecvTimeoutError::Timeout));
+/// assert_eq!(r.current_timeout(), Duration::from_millis(4));
+///
+/// s.send(1).unwrap();
+/// assert_eq!(r.recv_timeout_adaptive(), Ok(1));
+/// assert_eq!(r.current_timeout(), Duration::from_millis(1));
+/// ```
+pub struct BackoffReceiver<T> {
+    /// The underlying receiver.
+    receiver: Receiver<T>,
+
+    /// The timeout after a message has been received.
+    min: Duration,
+
+    /// The longest timeout.
+    max: Duration,
+
+    /// The number the timeout is multiplied by after each timed out wait.
+    factor: u32,
+
+    /// The timeout of the next wait.
+    timeout: Duration,
+}
+
+impl<T> BackoffReceiver<T> {
+    /// Creates a backing off receiver reading messages from `receiver`.
+    ///
+    /// The parameters control the timeouts of the waits:
+    ///
+    /// * `min` is the timeout of the first wait, and of the first wait after a message has been
+    ///   received.
+    /// * `max` is the longest timeout. The timeout stays at `max` while the channel remains idle.
+    /// * `factor` is the number the timeout is multiplied by each time a wait times out. A factor
+    ///   of 2 doubles the timeout, while a factor of 1 keeps it at `min`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is zero or greater than `max`, or if `factor` is zero. A zero timeout could
+    /// never grow by multiplication.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, BackoffReceiver};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// let r = BackoffReceiver::new(r, Duration::from_millis(10), Duration::from_secs(1), 2);
+    ///
+    /// assert_eq!(r.current_timeout(), Duration::from_millis(10));
+    /// ```
+    pub fn new(
+        receiver: Receiver<T>,
+        min: Duration,
+        max: Duration,
+        factor: u32,
+    ) -> BackoffReceiver<T> {
+        assert!(
+            min > Duration::from_secs(0),
+            "minimum timeout must be positive"
+        );
+        assert!(
+            min <= max,
+            "minimum timeout is greater than the maximum timeout"
+        );
+        assert!(factor > 0, "backoff factor must be positive");
+
+        BackoffReceiver {
+            receiver,
+            min,
+            max,
+            factor,
+            timeout: min,
+        }
+    }
+
+    /// Blocks the current thread until a message is received or the channel is empty and
+    /// disconnected.
+    ///
+    /// Internally, this waits with [`recv_timeout_adaptive`] until it succeeds, so the timeout
+    /// keeps backing off while the channel is idle and is reset once a message is received.
+    ///
+    /// [`recv_timeout_adaptive`]: struct.BackoffReceiver.html#method.recv_timeout_adaptive
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, BackoffReceiver, RecvError};
+    ///
+    /// let (s, r) = unbounded();
+    /// let mut r = BackoffReceiver::new(r, Duration::from_millis(1), Duration::from_millis(50), 2);
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     s.send(5).unwrap();
+    /// });
+    ///
+    /// assert_eq!(r.recv_adaptive(), Ok(5));
+    /// assert_eq!(r.recv_adaptive(), Err(RecvError));
+    /// ```
+    pub fn recv_adaptive(&mut self) -> Result<T, RecvError> {
+        loop {
+            match self.recv_timeout_adaptive() {
+                Ok(msg) => return Ok(msg),
+                Err(RecvTimeoutError::Disconnected) => return Err(RecvError),
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+        }
+    }
+
+    /// Waits for a message to be received from the channel for the current timeout.
+    ///
+    /// If the wait times out, the timeout of the next wait is backed off. If a message is
+    /// received, the timeout goes back to the minimum.
+    pub fn recv_timeout_adaptive(&mut self) -> Result<T, RecvTimeoutError> {
+        let res = self.receiver.recv_timeout(self.timeout);
+        match res {
+            Ok(_) => self.reset(),
+            Err(RecvTimeoutError::Timeout) => self.back_off(),
+            Err(RecvTimeoutError::Disconnected) => {}
+        }
+        res
+    }
+
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// A received message resets the timeout to the minimum, but an empty channel doesn't back it
+    /// off.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let res = self.receiver.try_recv();
+        if res.is_ok() {
+            self.reset();
+        }
+        res
+    }
+
+    /// Returns the timeout of the next wait.
+    pub fn current_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Resets the timeout of the next wait to the minimum.
+    pub fn reset(&mut self) {
+        self.timeout = self.min;
+    }
+
+    /// Multiplies the timeout by the backoff factor, capping it at the maximum.
+    fn back_off(&mut self) {
+        self.timeout = match self.timeout.checked_mul(self.factor) {
+            Some(t) => cmp::min(t, self.max),
+            None => self.max,
+        };
+    }
+
+    /// Returns a reference to the underlying receiver.
+    pub fn get_ref(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+
+    /// Returns the underlying receiver.
+    pub fn into_inner(self) -> Receiver<T> {
+        self.receiver
+    }
+}
+
+impl<T> fmt::Debug for BackoffReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BackoffReceiver")
+            .field("min", &self.min)
+            .field("max", &self.max)
+            .field("factor", &self.factor)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
@@ -0,0 +1,159 @@
+//! Bridges between these channels and `futures` streams and channels.
+//!
+//! Threads blocked on a channel are woken up by parking and unparking, while asynchronous tasks
+//! are woken up through their task handles, and neither kind of waiting can be registered with the
+//! other. Each bridge therefore runs a small forwarding thread, which blocks on one side and feeds
+//! the other, so that neither side has to know about the other kind of waiting.
+
+use std::thread;
+
+use futures::sync::mpsc;
+use futures::{executor, Future, Sink, Stream};
+
+use channel::{bounded, Receiver};
+use err::RecvError;
+
+/// Spawns a thread forwarding messages from `rx` into the asynchronous channel `tx`.
+///
+/// The thread blocks on `rx` until a message arrives, and then waits until `tx` has room for it,
+/// so a slow asynchronous consumer applies backpressure. It exits once `rx` is empty and
+/// disconnected, or once the asynchronous receiver is dropped. In the latter case, the message
+/// being forwarded is dropped, and the thread only notices when the next message arrives or `rx`
+/// gets disconnected.
+///
+/// # Examples
+///
+/// ```
+/// extern crate crossbeam_channel;
+/// extern crate futures;
+///
+/// use crossbeam_channel::{spawn_forwarder, unbounded};
+/// use futures::sync::mpsc;
+/// use futures::Stream;
+///
+/// # fn main() {
+/// let (s, r) = unbounded();
+/// let (tx, rx) = mpsc::channel(4);
+/// let forwarder = spawn_forwarder(r, tx);
+///
+/// s.send(1).unwrap();
+/// s.send(2).unwrap();
+/// drop(s);
+///
+/// assert_eq!(rx.wait().collect::<Result<Vec<_>, _>>(), Ok(vec![1, 2]));
+/// forwarder.join().unwrap();
+/// # }
+/// ```
+pub fn spawn_forwarder<T>(rx: Receiver<T>, tx: mpsc::Sender<T>) -> thread::JoinHandle<()>
+where
+    T: Send + 'static,
+{
+    thread::Builder::new()
+        .name("crossbeam-async-bridge".to_string())
+        .spawn(move || {
+            let mut tx = tx;
+            for msg in rx.iter() {
+                match tx.send(msg).wait() {
+                    Ok(t) => tx = t,
+                    Err(_) => return,
+                }
+            }
+        })
+        .unwrap()
+}
+
+/// Blocks the current thread until the next item of an asynchronous stream is ready.
+///
+/// This is the counterpart of [`Receiver::recv`] for a stream such as an asynchronous channel's
+/// receiver. If the stream has ended or failed, an error is returned.
+///
+/// [`Receiver::recv`]: struct.Receiver.html#method.recv
+///
+/// # Examples
+///
+/// ```
+/// extern crate crossbeam_channel;
+/// extern crate futures;
+///
+/// use std::thread;
+/// use crossbeam_channel::{block_on_recv, RecvError};
+/// use futures::sync::mpsc;
+/// use futures::{Future, Sink};
+///
+/// # fn main() {
+/// let (tx, mut rx) = mpsc::channel(0);
+///
+/// thread::spawn(move || {
+///     tx.send(5).wait().unwrap();
+/// });
+///
+/// assert_eq!(block_on_recv(&mut rx), Ok(5));
+/// assert_eq!(block_on_recv(&mut rx), Err(RecvError));
+/// # }
+/// ```
+pub fn block_on_recv<S: Stream>(stream: &mut S) -> Result<S::Item, RecvError> {
+    match executor::spawn(stream).wait_stream() {
+        Some(Ok(item)) => Ok(item),
+        Some(Err(_)) | None => Err(RecvError),
+    }
+}
+
+/// Spawns a thread forwarding the items of an asynchronous stream into a new channel.
+///
+/// The returned receiver can be used like any other, in particular in a [`Select`] or [`select!`]
+/// alongside ordinary channels, which makes the readiness of a stream selectable. The receiver
+/// gets disconnected once the stream ends. An error also ends the stream, and the error itself is
+/// discarded, so map errors into items first if they matter.
+///
+/// The channel has zero capacity, so the next item is only pulled from the stream once the
+/// previous one has been received. The thread exits once the stream ends or the receiver is
+/// dropped.
+///
+/// [`Select`]: struct.Select.html
+/// [`select!`]: macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// extern crate crossbeam_channel;
+/// extern crate futures;
+///
+/// use crossbeam_channel::{receiver_from_stream, unbounded};
+/// use futures::stream;
+///
+/// # fn main() {
+/// let (_s, sync_r) = unbounded::<i32>();
+/// let async_r = receiver_from_stream(stream::iter_ok::<_, ()>(vec![1, 2, 3]));
+///
+/// select! {
+///     recv(sync_r) -> _ => panic!(),
+///     recv(async_r) -> msg => assert_eq!(msg, Ok(1)),
+/// }
+/// # }
+/// ```
+pub fn receiver_from_stream<S>(stream: S) -> Receiver<S::Item>
+where
+    S: Stream + Send + 'static,
+    S::Item: Send + 'static,
+{
+    let (s, r) = bounded(0);
+
+    thread::Builder::new()
+        .name("crossbeam-async-bridge".to_string())
+        .spawn(move || {
+            for item in stream.wait() {
+                match item {
+                    Ok(item) => {
+                        if s.send(item).is_err() {
+                            return;
+                        }
+                    }
+                    Err(_) => return,
+                }
+            }
+        })
+        .unwrap();
+
+    r
+}
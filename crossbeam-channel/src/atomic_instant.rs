@@ -0,0 +1,113 @@
+//! Deadline that can be moved by one thread while others are waiting for it.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crossbeam_utils::atomic::AtomicCell;
+
+use context::Context;
+use select::Operation;
+use waker::SyncWaker;
+
+/// A deadline shared between threads, which can be moved while receive operations wait for it.
+///
+/// The deadline is encoded as a `u64` number of nanoseconds since the instant the `AtomicInstant`
+/// was created, so it can be loaded and stored atomically. Deadlines earlier than that instant
+/// are stored as the instant itself, which makes no difference because both have already passed.
+///
+/// This is used with [`Receiver::recv_with_shared_deadline`], which lets a controller thread
+/// extend or shorten the deadline of a receive operation while it is blocked.
+///
+/// [`Receiver::recv_with_shared_deadline`]: struct.Receiver.html#method.recv_with_shared_deadline
+pub struct AtomicInstant {
+    /// The instant deadlines are measured from.
+    base: Instant,
+
+    /// The deadline, in nanoseconds since `base`.
+    nanos: AtomicCell<u64>,
+
+    /// Receive operations waiting for the deadline.
+    waiters: SyncWaker,
+}
+
+unsafe impl Send for AtomicInstant {}
+unsafe impl Sync for AtomicInstant {}
+
+impl AtomicInstant {
+    /// Creates a new shared deadline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::AtomicInstant;
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(1);
+    /// let shared = AtomicInstant::new(deadline);
+    /// assert_eq!(shared.load(), deadline);
+    /// ```
+    pub fn new(deadline: Instant) -> AtomicInstant {
+        let base = Instant::now();
+        AtomicInstant {
+            base,
+            nanos: AtomicCell::new(encode(base, deadline)),
+            waiters: SyncWaker::new(),
+        }
+    }
+
+    /// Returns the current deadline.
+    pub fn load(&self) -> Instant {
+        let nanos = self.nanos.load();
+        self.base + Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
+    }
+
+    /// Moves the deadline.
+    ///
+    /// If the deadline moves earlier, receive operations waiting for it are woken up so that they
+    /// notice right away. If it moves later, nobody is woken up: a waiting operation notices the
+    /// change once the previous deadline is reached, and then keeps waiting.
+    pub fn store(&self, deadline: Instant) {
+        let nanos = encode(self.base, deadline);
+        if nanos < self.nanos.swap(nanos) {
+            self.waiters.notify();
+        }
+    }
+
+    /// Wakes up all receive operations waiting for the deadline.
+    ///
+    /// Each of them loads the deadline again and keeps waiting for the new value. This is how a
+    /// controller makes an extended deadline take effect before the previous one is reached.
+    pub fn wake(&self) {
+        self.waiters.notify();
+    }
+
+    /// Registers an operation to be woken up when the deadline moves earlier.
+    pub(crate) fn watch(&self, oper: Operation, cx: &Context) {
+        self.waiters.watch(oper, cx);
+    }
+
+    /// Unregisters an operation registered with `watch`.
+    pub(crate) fn unwatch(&self, oper: Operation) {
+        self.waiters.unwatch(oper);
+    }
+}
+
+impl fmt::Debug for AtomicInstant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AtomicInstant")
+            .field("deadline", &self.load())
+            .finish()
+    }
+}
+
+/// Encodes `deadline` as the number of nanoseconds since `base`, saturating at both ends.
+fn encode(base: Instant, deadline: Instant) -> u64 {
+    if deadline <= base {
+        return 0;
+    }
+    let d = deadline - base;
+    d.as_secs()
+        .checked_mul(1_000_000_000)
+        .and_then(|n| n.checked_add(u64::from(d.subsec_nanos())))
+        .unwrap_or(u64::max_value())
+}
@@ -0,0 +1,149 @@
+//! Cancellation of blocking receive operations.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use context::Context;
+use select::Operation;
+use waker::SyncWaker;
+
+/// Creates a cancellation token together with the canceller that triggers it.
+///
+/// Receive operations started with [`Receiver::recv_cancellable`] block until a message arrives,
+/// the channel gets disconnected, or [`Canceller::cancel`] is called. Cancelling wakes up all
+/// receive operations waiting on the token right away, without them having to poll a flag.
+///
+/// Both halves can be cloned, and one token can be shared by any number of receive operations on
+/// any number of channels and threads.
+///
+/// A token cannot be re-armed: once cancelled, it stays cancelled, and every later receive
+/// operation using it fails unless a message is ready. Create a new pair for each piece of work
+/// that needs to be cancelled separately.
+///
+/// [`Receiver::recv_cancellable`]: struct.Receiver.html#method.recv_cancellable
+/// [`Canceller::cancel`]: struct.Canceller.html#method.cancel
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{cancel_token, unbounded, RecvCancelError};
+///
+/// let (_s, r) = unbounded::<i32>();
+/// let (token, canceller) = cancel_token();
+///
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(100));
+///     canceller.cancel();
+/// });
+///
+/// assert_eq!(r.recv_cancellable(&token), Err(RecvCancelError::Cancelled));
+/// assert!(token.is_cancelled());
+/// ```
+pub fn cancel_token() -> (CancelToken, Canceller) {
+    let inner = Arc::new(Inner {
+        is_cancelled: AtomicBool::new(false),
+        waiters: SyncWaker::new(),
+    });
+    let token = CancelToken {
+        inner: inner.clone(),
+    };
+    (token, Canceller { inner })
+}
+
+/// State shared by a token and its canceller.
+struct Inner {
+    /// Equals `true` once the token has been cancelled.
+    is_cancelled: AtomicBool,
+
+    /// Receive operations waiting on the token.
+    waiters: SyncWaker,
+}
+
+unsafe impl Send for Inner {}
+unsafe impl Sync for Inner {}
+
+/// The waiting side of a pair created by [`cancel_token`].
+///
+/// [`cancel_token`]: fn.cancel_token.html
+pub struct CancelToken {
+    /// The shared state.
+    inner: Arc<Inner>,
+}
+
+impl CancelToken {
+    /// Returns `true` if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Registers an operation to be woken up when the token is cancelled.
+    pub(crate) fn watch(&self, oper: Operation, cx: &Context) {
+        self.inner.waiters.watch(oper, cx);
+    }
+
+    /// Unregisters an operation registered with `watch`.
+    pub(crate) fn unwatch(&self, oper: Operation) {
+        self.inner.waiters.unwatch(oper);
+    }
+}
+
+impl Clone for CancelToken {
+    fn clone(&self) -> Self {
+        CancelToken {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("is_cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// The cancelling side of a pair created by [`cancel_token`].
+///
+/// Dropping all cancellers does not cancel the token.
+///
+/// [`cancel_token`]: fn.cancel_token.html
+pub struct Canceller {
+    /// The shared state.
+    inner: Arc<Inner>,
+}
+
+impl Canceller {
+    /// Cancels the token and wakes up all receive operations waiting on it.
+    ///
+    /// Cancelling an already cancelled token does nothing.
+    pub fn cancel(&self) {
+        if !self.inner.is_cancelled.swap(true, Ordering::SeqCst) {
+            self.inner.waiters.notify();
+        }
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Clone for Canceller {
+    fn clone(&self) -> Self {
+        Canceller {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for Canceller {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Canceller")
+            .field("is_cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
@@ -1,21 +1,49 @@
 //! The channel interface.
 
 use std::fmt;
+use std::hash::Hash;
 use std::iter::FusedIterator;
 use std::mem;
+use std::ops;
+#[cfg(all(unix, feature = "os_poll"))]
+use std::os::unix::io::RawFd;
 use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::ptr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+#[cfg(feature = "arena")]
+use bumpalo::Bump;
+use crossbeam_utils::Backoff;
+#[cfg(feature = "async-bridge")]
+use futures::sync::mpsc;
+
+#[cfg(feature = "async-bridge")]
+use async_bridge;
+use atomic_instant::AtomicInstant;
+use cancel::CancelToken;
 use context::Context;
 use counter;
-use err::{RecvError, RecvTimeoutError, SendError, SendTimeoutError, TryRecvError, TrySendError};
+use err::{CapacityError, PeekError, TryPeekError};
+use err::{RecvCancelError, RecvError, RecvSpinError, RecvTimeoutError};
+use err::{ReserveSlotError, TryReserveSlotError};
+use err::{SendError, SendOnceError, SendTimeoutError, TryRecvError, TrySendError};
 use flavors;
-use select::{Operation, SelectHandle, Token};
+use flavors::array::Slot as ArraySlot;
+use pump::Pump;
+#[cfg(feature = "replay")]
+use replay::{self, ReplayOp};
+use select::{Operation, SelectHandle, Selected, Token};
+use throttled::ThrottledSender;
+use utils::DropHandler;
+#[cfg(all(unix, feature = "os_poll"))]
+use waker;
 
 /// Creates a channel of unbounded capacity.
 ///
-/// This channel has a growable buffer that can hold any number of messages at a time.
+/// This channel has a growable buffer that can hold any number of messages at a time. The buffer
+/// is allocated lazily when the first message is sent, so a channel that never carries a message
+/// uses very little memory.
 ///
 /// # Examples
 ///
@@ -41,7 +69,35 @@ use select::{Operation, SelectHandle, Token};
 /// println!("{}", r.recv().unwrap());
 /// ```
 pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
-    let (s, r) = counter::new(flavors::list::Channel::new());
+    unbounded_with_name(None)
+}
+
+/// Creates a named channel of unbounded capacity.
+///
+/// This works just like [`unbounded`], except that the channel is given a name, which is returned
+/// by [`Sender::name`] and [`Receiver::name`] and shown in their `Debug` output. This makes it
+/// easier to tell channels apart when debugging.
+///
+/// [`unbounded`]: fn.unbounded.html
+/// [`Sender::name`]: struct.Sender.html#method.name
+/// [`Receiver::name`]: struct.Receiver.html#method.name
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::unbounded_named;
+///
+/// let (s, r) = unbounded_named::<i32>("events");
+/// assert_eq!(s.name(), Some("events"));
+/// assert_eq!(r.name(), Some("events"));
+/// ```
+pub fn unbounded_named<T>(name: &str) -> (Sender<T>, Receiver<T>) {
+    unbounded_with_name(Some(Arc::from(name)))
+}
+
+/// Creates a channel of unbounded capacity with an optional name.
+fn unbounded_with_name<T>(name: Option<Arc<str>>) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::with_name(flavors::list::Channel::new(), name);
     let s = Sender {
         flavor: SenderFlavor::List(s),
     };
@@ -58,6 +114,13 @@ pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
 /// A special case is zero-capacity channel, which cannot hold any messages. Instead, send and
 /// receive operations must appear at the same time in order to pair up and pass the message over.
 ///
+/// # Panics
+///
+/// Panics if the buffer for `cap` messages is too large to ever be allocated. Use
+/// [`bounded_checked`] to handle that case.
+///
+/// [`bounded_checked`]: fn.bounded_checked.html
+///
 /// # Examples
 ///
 /// A channel of capacity 1:
@@ -102,627 +165,3160 @@ pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
 /// assert_eq!(r.recv(), Ok(1));
 /// ```
 pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
-    if cap == 0 {
-        let (s, r) = counter::new(flavors::zero::Channel::new());
-        let s = Sender {
-            flavor: SenderFlavor::Zero(s),
-        };
-        let r = Receiver {
-            flavor: ReceiverFlavor::Zero(r),
-        };
-        (s, r)
-    } else {
-        let (s, r) = counter::new(flavors::array::Channel::with_capacity(cap));
-        let s = Sender {
-            flavor: SenderFlavor::Array(s),
-        };
-        let r = Receiver {
-            flavor: ReceiverFlavor::Array(r),
-        };
-        (s, r)
-    }
+    BoundedBuilder::new(cap).build()
 }
 
-/// Creates a receiver that delivers a message after a certain duration of time.
+/// Creates a named channel of bounded capacity.
 ///
-/// The channel is bounded with capacity of 1 and never gets disconnected. Exactly one message will
-/// be sent into the channel after `duration` elapses. The message is the instant at which it is
-/// sent.
+/// This works just like [`bounded`], except that the channel is given a name, which is returned
+/// by [`Sender::name`] and [`Receiver::name`] and shown in their `Debug` output. This makes it
+/// easier to tell channels apart when debugging.
+///
+/// [`bounded`]: fn.bounded.html
+/// [`Sender::name`]: struct.Sender.html#method.name
+/// [`Receiver::name`]: struct.Receiver.html#method.name
 ///
 /// # Examples
 ///
-/// Using an `after` channel for timeouts:
+/// ```
+/// use crossbeam_channel::bounded_named;
 ///
+/// let (s, r) = bounded_named::<i32>(10, "requests");
+/// assert_eq!(s.name(), Some("requests"));
+/// assert_eq!(format!("{:?}", r), "Receiver { name: \"requests\", .. }");
 /// ```
-/// # #[macro_use]
-/// # extern crate crossbeam_channel;
-/// # fn main() {
-/// use std::time::Duration;
-/// use crossbeam_channel::{after, unbounded};
+pub fn bounded_named<T>(cap: usize, name: &str) -> (Sender<T>, Receiver<T>) {
+    BoundedBuilder::new(cap).name(name).build()
+}
+
+/// Creates a channel of bounded capacity whose blocking operations wait using `strategy`.
 ///
-/// let (s, r) = unbounded::<i32>();
-/// let timeout = Duration::from_millis(100);
+/// This works just like [`bounded`], except that threads blocked in send and receive operations
+/// on the channel wait as described by [`ParkStrategy`] instead of always being parked. See its
+/// documentation for the tradeoffs between the strategies.
 ///
-/// select! {
-///     recv(r) -> msg => println!("received {:?}", msg),
-///     recv(after(timeout)) -> _ => println!("timed out"),
-/// }
-/// # }
-/// ```
+/// To combine the strategy with a name or other options, use [`BoundedBuilder`].
 ///
-/// When the message gets sent:
+/// Only send and receive operations, including those with timeouts, follow the strategy.
+/// Selection with [`Select`] or [`select!`] always parks the thread. Zero-capacity channels
+/// ignore the strategy and always park, since a send operation can only complete once it pairs up
+/// with a receive operation waiting on the other side, and vice versa.
+///
+/// [`bounded`]: fn.bounded.html
+/// [`BoundedBuilder`]: struct.BoundedBuilder.html
+/// [`ParkStrategy`]: enum.ParkStrategy.html
+/// [`Select`]: struct.Select.html
+/// [`select!`]: macro.select.html
+///
+/// # Panics
+///
+/// Panics if the capacity is too large, just like [`bounded`].
+///
+/// # Examples
 ///
 /// ```
 /// use std::thread;
-/// use std::time::{Duration, Instant};
-/// use crossbeam_channel::after;
-///
-/// // Converts a number of milliseconds into a `Duration`.
-/// let ms = |ms| Duration::from_millis(ms);
+/// use crossbeam_channel::{bounded_with_strategy, ParkStrategy};
 ///
-/// // Returns `true` if `a` and `b` are very close `Instant`s.
-/// let eq = |a, b| a + ms(50) > b && b + ms(50) > a;
+/// let (s, r) = bounded_with_strategy(16, ParkStrategy::Spin);
 ///
-/// let start = Instant::now();
-/// let r = after(ms(100));
+/// // The receiving thread busy-waits and never makes a system call to go to sleep.
+/// let consumer = thread::spawn(move || r.iter().sum::<i32>());
 ///
-/// thread::sleep(ms(500));
+/// for i in 0..100 {
+///     s.send(i).unwrap();
+/// }
+/// drop(s);
 ///
-/// // This message was sent 100 ms from the start and received 500 ms from the start.
-/// assert!(eq(r.recv().unwrap(), start + ms(100)));
-/// assert!(eq(Instant::now(), start + ms(500)));
+/// assert_eq!(consumer.join().unwrap(), 4950);
 /// ```
-pub fn after(duration: Duration) -> Receiver<Instant> {
-    Receiver {
-        flavor: ReceiverFlavor::After(Arc::new(flavors::after::Channel::new(duration))),
-    }
+pub fn bounded_with_strategy<T>(cap: usize, strategy: ParkStrategy) -> (Sender<T>, Receiver<T>) {
+    BoundedBuilder::new(cap).strategy(strategy).build()
 }
 
-/// Creates a receiver that never delivers messages.
+/// Creates a channel of unbounded capacity that hands messages left in it to `handler` once the
+/// last receiver is dropped.
 ///
-/// The channel is bounded with capacity of 0 and never gets disconnected.
+/// This works just like [`unbounded`], except for what happens to messages that were sent but
+/// never received. Normally, they are dropped together with the channel. Here, the thread that
+/// drops the last [`Receiver`] first disconnects the channel and then calls `handler` with each
+/// remaining message in FIFO order, before its `drop` returns. This can be used to persist
+/// undelivered messages instead of losing them.
 ///
-/// # Examples
+/// Only messages that were already in the channel at the moment of disconnection are handed over.
+/// Send operations that start later fail and return their message in a [`SendError`] as usual,
+/// and so do senders blocked on a full channel, which are woken up by the disconnection.
 ///
-/// Using a `never` channel to optionally add a timeout to [`select!`]:
+/// The handler runs at most once per message and is dropped afterwards, even if the channel was
+/// already disconnected by dropping all senders. It runs inside `drop`, so it must not panic.
 ///
-/// ```
-/// # #[macro_use]
-/// # extern crate crossbeam_channel;
-/// # fn main() {
-/// use std::thread;
-/// use std::time::{Duration, Instant};
-/// use crossbeam_channel::{after, never, unbounded};
+/// [`unbounded`]: fn.unbounded.html
+/// [`Receiver`]: struct.Receiver.html
+/// [`SendError`]: struct.SendError.html
 ///
-/// let (s, r) = unbounded();
+/// # Examples
 ///
-/// thread::spawn(move || {
-///     thread::sleep(Duration::from_secs(1));
-///     s.send(1).unwrap();
-/// });
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use crossbeam_channel::unbounded_with_drop_handler;
 ///
-/// // Suppose this duration can be a `Some` or a `None`.
-/// let duration = Some(Duration::from_millis(100));
+/// let persisted = Arc::new(Mutex::new(Vec::new()));
+/// let p = persisted.clone();
 ///
-/// // Create a channel that times out after the specified duration.
-/// let timeout = duration
-///     .map(|d| after(d))
-///     .unwrap_or(never());
+/// let (s, r) = unbounded_with_drop_handler(move |msg| p.lock().unwrap().push(msg));
+/// s.send(1).unwrap();
+/// s.send(2).unwrap();
+/// s.send(3).unwrap();
 ///
-/// select! {
-///     recv(r) -> msg => assert_eq!(msg, Ok(1)),
-///     recv(timeout) -> _ => println!("timed out"),
-/// }
-/// # }
-/// ```
+/// assert_eq!(r.recv(), Ok(1));
+/// drop(r);
 ///
-/// [`select!`]: macro.select.html
-pub fn never<T>() -> Receiver<T> {
-    Receiver {
-        flavor: ReceiverFlavor::Never(flavors::never::Channel::new()),
-    }
+/// assert_eq!(*persisted.lock().unwrap(), [2, 3]);
+/// assert!(s.send(4).is_err());
+/// ```
+pub fn unbounded_with_drop_handler<T, F>(handler: F) -> (Sender<T>, Receiver<T>)
+where
+    F: FnMut(T) + Send + 'static,
+{
+    let chan = flavors::list::Channel::new().with_drop_handler(Box::new(handler));
+    let (s, r) = counter::new(chan);
+    let s = Sender {
+        flavor: SenderFlavor::List(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::List(r),
+    };
+    (s, r)
 }
 
-/// Creates a receiver that delivers messages periodically.
+/// Creates a channel of bounded capacity that hands messages left in it to `handler` once the
+/// last receiver is dropped.
 ///
-/// The channel is bounded with capacity of 1 and never gets disconnected. Messages will be
-/// sent into the channel in intervals of `duration`. Each message is the instant at which it is
-/// sent.
+/// This works just like [`bounded`], with messages left in the channel handled as described in
+/// [`unbounded_with_drop_handler`]. A zero-capacity channel never holds messages, so the handler
+/// is never called for it.
 ///
-/// # Examples
+/// Slots reserved with [`Sender::try_reserve`] that are not filled yet are skipped, because the
+/// sender holding them may never fill them. A message sent into such a slot later is dropped along
+/// with the channel instead of being handed to `handler`.
 ///
-/// Using a `tick` channel to periodically print elapsed time:
+/// To combine the handler with a name or other options, use [`BoundedBuilder`].
 ///
-/// ```
-/// use std::time::{Duration, Instant};
-/// use crossbeam_channel::tick;
+/// [`bounded`]: fn.bounded.html
+/// [`BoundedBuilder`]: struct.BoundedBuilder.html
+/// [`unbounded_with_drop_handler`]: fn.unbounded_with_drop_handler.html
+/// [`Sender::try_reserve`]: struct.Sender.html#method.try_reserve
 ///
-/// let start = Instant::now();
-/// let ticker = tick(Duration::from_millis(100));
+/// # Panics
+///
+/// Panics if the capacity is too large, just like [`bounded`].
+///
+/// # Examples
 ///
-/// for _ in 0..5 {
-///     ticker.recv().unwrap();
-///     println!("elapsed: {:?}", start.elapsed());
-/// }
 /// ```
+/// use std::sync::{Arc, Mutex};
+/// use crossbeam_channel::bounded_with_drop_handler;
 ///
-/// When messages get sent:
+/// let persisted = Arc::new(Mutex::new(Vec::new()));
+/// let p = persisted.clone();
 ///
+/// let (s, r) = bounded_with_drop_handler(2, move |msg| p.lock().unwrap().push(msg));
+/// s.send("a").unwrap();
+/// s.send("b").unwrap();
+/// drop(r);
+///
+/// assert_eq!(*persisted.lock().unwrap(), ["a", "b"]);
 /// ```
-/// use std::thread;
-/// use std::time::{Duration, Instant};
-/// use crossbeam_channel::tick;
+pub fn bounded_with_drop_handler<T, F>(cap: usize, handler: F) -> (Sender<T>, Receiver<T>)
+where
+    F: FnMut(T) + Send + 'static,
+{
+    BoundedBuilder::new(cap).drop_handler(handler).build()
+}
+
+/// Creates a channel of bounded capacity, or returns an error if the capacity is invalid.
 ///
-/// // Converts a number of milliseconds into a `Duration`.
-/// let ms = |ms| Duration::from_millis(ms);
+/// This works just like [`bounded`], except that the capacity is validated first and an error is
+/// returned instead of panicking when the buffer for `cap` messages could never be allocated.
+/// This is useful when the capacity comes from an untrusted source.
 ///
-/// // Returns `true` if `a` and `b` are very close `Instant`s.
-/// let eq = |a, b| a + ms(50) > b && b + ms(50) > a;
+/// The capacity is invalid if the buffer size in bytes would exceed `isize::MAX`. This is only a
+/// check on the capacity and doesn't make the allocation itself fallible: a valid capacity may
+/// still exceed the available memory, in which case the allocation fails just like any other and
+/// the process is aborted.
 ///
-/// let start = Instant::now();
-/// let r = tick(ms(100));
+/// To validate the capacity of a channel with a name or other options, use
+/// [`BoundedBuilder::try_build`].
 ///
-/// // This message was sent 100 ms from the start and received 100 ms from the start.
-/// assert!(eq(r.recv().unwrap(), start + ms(100)));
-/// assert!(eq(Instant::now(), start + ms(100)));
+/// [`bounded`]: fn.bounded.html
+/// [`BoundedBuilder::try_build`]: struct.BoundedBuilder.html#method.try_build
 ///
-/// thread::sleep(ms(500));
+/// # Examples
 ///
-/// // This message was sent 200 ms from the start and received 600 ms from the start.
-/// assert!(eq(r.recv().unwrap(), start + ms(200)));
-/// assert!(eq(Instant::now(), start + ms(600)));
+/// ```
+/// use crossbeam_channel::{bounded_checked, CapacityError};
 ///
-/// // This message was sent 700 ms from the start and received 700 ms from the start.
-/// assert!(eq(r.recv().unwrap(), start + ms(700)));
-/// assert!(eq(Instant::now(), start + ms(700)));
+/// let (s, r) = bounded_checked(10).unwrap();
+/// s.send(1).unwrap();
+/// assert_eq!(r.recv(), Ok(1));
+///
+/// assert_eq!(bounded_checked::<u64>(usize::max_value()).err(), Some(CapacityError));
 /// ```
-pub fn tick(duration: Duration) -> Receiver<Instant> {
-    Receiver {
-        flavor: ReceiverFlavor::Tick(Arc::new(flavors::tick::Channel::new(duration))),
-    }
+pub fn bounded_checked<T>(cap: usize) -> Result<(Sender<T>, Receiver<T>), CapacityError> {
+    BoundedBuilder::new(cap).try_build()
 }
 
-/// The sending side of a channel.
+/// Configures a channel of bounded capacity before creating it.
+///
+/// The options of [`bounded_named`], [`bounded_with_strategy`] and [`bounded_with_drop_handler`]
+/// can be combined here, and [`try_build`] validates the capacity like [`bounded_checked`]. Each
+/// option behaves as described for the corresponding function. In particular, a zero-capacity
+/// channel ignores the strategy and never calls the drop handler.
+///
+/// [`bounded_named`]: fn.bounded_named.html
+/// [`bounded_with_strategy`]: fn.bounded_with_strategy.html
+/// [`bounded_with_drop_handler`]: fn.bounded_with_drop_handler.html
+/// [`bounded_checked`]: fn.bounded_checked.html
+/// [`try_build`]: struct.BoundedBuilder.html#method.try_build
 ///
 /// # Examples
 ///
 /// ```
-/// use std::thread;
-/// use crossbeam_channel::unbounded;
-///
-/// let (s1, r) = unbounded();
-/// let s2 = s1.clone();
+/// use std::sync::{Arc, Mutex};
+/// use crossbeam_channel::{BoundedBuilder, ParkStrategy};
 ///
-/// thread::spawn(move || s1.send(1).unwrap());
-/// thread::spawn(move || s2.send(2).unwrap());
+/// let persisted = Arc::new(Mutex::new(Vec::new()));
+/// let p = persisted.clone();
 ///
-/// let msg1 = r.recv().unwrap();
-/// let msg2 = r.recv().unwrap();
+/// let (s, r) = BoundedBuilder::new(4)
+///     .name("jobs")
+///     .strategy(ParkStrategy::Yield)
+///     .drop_handler(move |msg| p.lock().unwrap().push(msg))
+///     .build();
+/// assert_eq!(s.name(), Some("jobs"));
 ///
-/// assert_eq!(msg1 + msg2, 3);
+/// s.send(1).unwrap();
+/// drop(r);
+/// assert_eq!(*persisted.lock().unwrap(), [1]);
 /// ```
-pub struct Sender<T> {
-    flavor: SenderFlavor<T>,
-}
+pub struct BoundedBuilder<T> {
+    /// The capacity of the channel.
+    cap: usize,
 
-/// Sender flavors.
-enum SenderFlavor<T> {
-    /// Bounded channel based on a preallocated array.
-    Array(counter::Sender<flavors::array::Channel<T>>),
+    /// The name of the channel.
+    name: Option<Arc<str>>,
 
-    /// Unbounded channel implemented as a linked list.
-    List(counter::Sender<flavors::list::Channel<T>>),
+    /// How threads wait in blocking operations.
+    strategy: ParkStrategy,
 
-    /// Zero-capacity channel.
-    Zero(counter::Sender<flavors::zero::Channel<T>>),
+    /// Receives the messages left in the channel when the last receiver is dropped.
+    drop_handler: Option<DropHandler<T>>,
 }
 
-unsafe impl<T: Send> Send for Sender<T> {}
-unsafe impl<T: Send> Sync for Sender<T> {}
-
-impl<T> UnwindSafe for Sender<T> {}
-impl<T> RefUnwindSafe for Sender<T> {}
-
-impl<T> Sender<T> {
-    /// Attempts to send a message into the channel without blocking.
-    ///
-    /// This method will either send a message into the channel immediately or return an error if
-    /// the channel is full or disconnected. The returned error contains the original message.
-    ///
-    /// If called on a zero-capacity channel, this method will send the message only if there
-    /// happens to be a receive operation on the other side of the channel at the same time.
+impl<T> BoundedBuilder<T> {
+    /// Starts configuring a channel that can hold at most `cap` messages at a time.
     ///
-    /// # Examples
-    ///
-    /// ```
-    /// use crossbeam_channel::{bounded, TrySendError};
-    ///
-    /// let (s, r) = bounded(1);
-    ///
-    /// assert_eq!(s.try_send(1), Ok(()));
-    /// assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    /// Without further options, the channel is just like one created by [`bounded`].
     ///
-    /// drop(r);
-    /// assert_eq!(s.try_send(3), Err(TrySendError::Disconnected(3)));
-    /// ```
-    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
-        match &self.flavor {
-            SenderFlavor::Array(chan) => chan.try_send(msg),
-            SenderFlavor::List(chan) => chan.try_send(msg),
-            SenderFlavor::Zero(chan) => chan.try_send(msg),
+    /// [`bounded`]: fn.bounded.html
+    pub fn new(cap: usize) -> BoundedBuilder<T> {
+        BoundedBuilder {
+            cap,
+            name: None,
+            strategy: ParkStrategy::Block,
+            drop_handler: None,
         }
     }
 
-    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    /// Gives the channel a name, as with [`bounded_named`].
     ///
-    /// If the channel is full and not disconnected, this call will block until the send operation
-    /// can proceed. If the channel becomes disconnected, this call will wake up and return an
-    /// error. The returned error contains the original message.
+    /// [`bounded_named`]: fn.bounded_named.html
+    pub fn name(mut self, name: &str) -> BoundedBuilder<T> {
+        self.name = Some(Arc::from(name));
+        self
+    }
+
+    /// Sets how threads wait in blocking operations, as with [`bounded_with_strategy`].
     ///
-    /// If called on a zero-capacity channel, this method will wait for a receive operation to
-    /// appear on the other side of the channel.
+    /// [`bounded_with_strategy`]: fn.bounded_with_strategy.html
+    pub fn strategy(mut self, strategy: ParkStrategy) -> BoundedBuilder<T> {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets a handler for messages left in the channel, as with [`bounded_with_drop_handler`].
     ///
-    /// # Examples
+    /// [`bounded_with_drop_handler`]: fn.bounded_with_drop_handler.html
+    pub fn drop_handler<F>(mut self, handler: F) -> BoundedBuilder<T>
+    where
+        F: FnMut(T) + Send + 'static,
+    {
+        self.drop_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Creates the channel.
     ///
-    /// ```
-    /// use std::thread;
-    /// use std::time::Duration;
-    /// use crossbeam_channel::{bounded, SendError};
+    /// # Panics
     ///
-    /// let (s, r) = bounded(1);
-    /// assert_eq!(s.send(1), Ok(()));
+    /// Panics if the capacity is too large, just like [`bounded`].
     ///
-    /// thread::spawn(move || {
-    ///     assert_eq!(r.recv(), Ok(1));
-    ///     thread::sleep(Duration::from_secs(1));
-    ///     drop(r);
-    /// });
+    /// [`bounded`]: fn.bounded.html
+    pub fn build(self) -> (Sender<T>, Receiver<T>) {
+        self.try_build().expect("capacity overflow")
+    }
+
+    /// Creates the channel, or returns an error if the capacity is invalid.
     ///
-    /// assert_eq!(s.send(2), Ok(()));
-    /// assert_eq!(s.send(3), Err(SendError(3)));
-    /// ```
-    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
-        match &self.flavor {
+    /// The capacity is validated just like in [`bounded_checked`].
+    ///
+    /// [`bounded_checked`]: fn.bounded_checked.html
+    pub fn try_build(self) -> Result<(Sender<T>, Receiver<T>), CapacityError> {
+        if self.cap == 0 {
+            let (s, r) = counter::with_name(flavors::zero::Channel::new(), self.name);
+            let s = Sender {
+                flavor: SenderFlavor::Zero(s),
+            };
+            let r = Receiver {
+                flavor: ReceiverFlavor::Zero(r),
+            };
+            return Ok((s, r));
+        }
+
+        let mut chan = flavors::array::Channel::checked_with_capacity(self.cap)
+            .ok_or(CapacityError)?
+            .with_strategy(self.strategy);
+        if let Some(handler) = self.drop_handler {
+            chan = chan.with_drop_handler(handler);
+        }
+
+        let (s, r) = counter::with_name(chan, self.name);
+        let s = Sender {
+            flavor: SenderFlavor::Array(s),
+        };
+        let r = Receiver {
+            flavor: ReceiverFlavor::Array(r),
+        };
+        Ok((s, r))
+    }
+}
+
+impl<T> fmt::Debug for BoundedBuilder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("BoundedBuilder { .. }")
+    }
+}
+
+/// Creates a channel of bounded capacity that stores messages in the provided buffer.
+///
+/// This works just like [`bounded`] with a capacity of `buf.len()`, except that the channel does
+/// not allocate a buffer of its own. This is useful when memory has to be set aside up front, for
+/// example in real-time code that must not allocate once it is running. A small header with the
+/// channel state, independent of the capacity, is still allocated on the heap.
+///
+/// The buffer is borrowed for the `'static` lifetime because it must outlive every sender and
+/// receiver, and the channel never gives it back. Messages still in the buffer are dropped when
+/// the channel is dropped, but the buffer itself is not deallocated. Such a buffer is typically a
+/// `static` or obtained once from [`Box::leak`].
+///
+/// The buffer is made of [`ArraySlot`]s rather than uninitialized memory because each slot pairs
+/// the message with the stamp the channel uses to synchronize access to it. Slots start out with
+/// a default message, which is why the message type must implement `Default`.
+///
+/// [`bounded`]: fn.bounded.html
+/// [`Box::leak`]: https://doc.rust-lang.org/std/boxed/struct.Box.html#method.leak
+/// [`ArraySlot`]: struct.ArraySlot.html
+///
+/// # Panics
+///
+/// Panics if the buffer is empty.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{bounded_in_slice, ArraySlot};
+///
+/// let buf = (0..4).map(|_| ArraySlot::new()).collect::<Vec<_>>();
+/// let buf: &'static mut [ArraySlot<i32>] = Box::leak(buf.into_boxed_slice());
+///
+/// let (s, r) = bounded_in_slice(buf);
+/// assert_eq!(s.capacity(), Some(4));
+///
+/// s.send(1).unwrap();
+/// s.send(2).unwrap();
+/// assert_eq!(r.recv(), Ok(1));
+/// assert_eq!(r.recv(), Ok(2));
+/// ```
+pub fn bounded_in_slice<T>(buf: &'static mut [ArraySlot<T>]) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::array::Channel::with_buffer(buf));
+    let s = Sender {
+        flavor: SenderFlavor::Array(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Array(r),
+    };
+    (s, r)
+}
+
+/// Creates a channel of bounded capacity, rounded up to a power of two.
+///
+/// This works just like [`bounded`], except that the capacity is `min_cap` rounded up to the next
+/// power of two, which is at least 1. The actual capacity is reported by [`Sender::capacity`] and
+/// [`Receiver::capacity`].
+///
+/// Note that channels created by [`bounded`] already wrap indices into their buffer using a bit
+/// mask rather than division, so an exact capacity carries no performance penalty. This
+/// constructor is only a convenience for code that wants power-of-two capacities.
+///
+/// # Panics
+///
+/// Panics if the next power of two does not fit in a `usize`.
+///
+/// [`bounded`]: fn.bounded.html
+/// [`Sender::capacity`]: struct.Sender.html#method.capacity
+/// [`Receiver::capacity`]: struct.Receiver.html#method.capacity
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::bounded_pow2;
+///
+/// let (s, r) = bounded_pow2::<i32>(100);
+/// assert_eq!(s.capacity(), Some(128));
+/// assert_eq!(r.capacity(), Some(128));
+///
+/// let (s, _) = bounded_pow2::<i32>(0);
+/// assert_eq!(s.capacity(), Some(1));
+/// ```
+pub fn bounded_pow2<T>(min_cap: usize) -> (Sender<T>, Receiver<T>) {
+    let cap = min_cap
+        .checked_next_power_of_two()
+        .expect("capacity overflow");
+    bounded(cap)
+}
+
+/// Creates a single-use channel that carries at most one message.
+///
+/// This channel has a capacity of one, but unlike a channel created by [`bounded`], it can be
+/// used only once and is cheaper to create and operate on. It is a good fit for request/response
+/// patterns where a single reply is expected.
+///
+/// Only the first send operation can succeed. Every later send operation fails with an error, as
+/// if the channel was disconnected, even if the message has not been received yet. Use
+/// [`Sender::send_once`] to tell a reused channel apart from a disconnected one. After the
+/// message has been received, the channel is disconnected and further receive operations fail as
+/// well. Send operations on this channel never block.
+///
+/// [`bounded`]: fn.bounded.html
+/// [`Sender::send_once`]: struct.Sender.html#method.send_once
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::oneshot;
+///
+/// let (s, r) = oneshot();
+///
+/// thread::spawn(move || {
+///     s.send(1).unwrap();
+///
+///     // The channel can be used only once.
+///     assert!(s.send(2).is_err());
+/// });
+///
+/// assert_eq!(r.recv(), Ok(1));
+/// assert!(r.recv().is_err());
+/// ```
+pub fn oneshot<T>() -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::oneshot::Channel::new());
+    let s = Sender {
+        flavor: SenderFlavor::Oneshot(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Oneshot(r),
+    };
+    (s, r)
+}
+
+/// Creates a channel of bounded capacity with a priority lane for urgent messages.
+///
+/// Messages sent with [`PrioritySender::send`] are received in FIFO order, just like in a channel
+/// created by [`bounded`]. Messages sent with [`PrioritySender::send_urgent`] cut the line: they
+/// are received before any message sent normally, and in FIFO order among themselves. Both kinds
+/// of messages share the capacity of the channel, so urgent send operations block when the
+/// channel is full, too.
+///
+/// The returned [`Receiver`] is an ordinary receiver and can be used in [`select!`]. However,
+/// [`Receiver::make_sender`] returns `None` because the sending side is a [`PrioritySender`].
+///
+/// # Panics
+///
+/// Panics if the capacity is zero.
+///
+/// [`bounded`]: fn.bounded.html
+/// [`PrioritySender`]: struct.PrioritySender.html
+/// [`PrioritySender::send`]: struct.PrioritySender.html#method.send
+/// [`PrioritySender::send_urgent`]: struct.PrioritySender.html#method.send_urgent
+/// [`Receiver`]: struct.Receiver.html
+/// [`Receiver::make_sender`]: struct.Receiver.html#method.make_sender
+/// [`select!`]: macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::bounded_priority;
+///
+/// let (s, r) = bounded_priority(10);
+///
+/// s.send("data 1").unwrap();
+/// s.send("data 2").unwrap();
+/// s.send_urgent("stop").unwrap();
+///
+/// assert_eq!(r.recv(), Ok("stop"));
+/// assert_eq!(r.recv(), Ok("data 1"));
+/// assert_eq!(r.recv(), Ok("data 2"));
+/// ```
+pub fn bounded_priority<T>(cap: usize) -> (PrioritySender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::priority::Channel::with_capacity(cap));
+    let s = PrioritySender { chan: s };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Priority(r),
+    };
+    (s, r)
+}
+
+/// Creates a channel of bounded capacity that receives messages in priority order.
+///
+/// Receive operations always take the greatest message in the channel, as ordered by [`Ord`],
+/// regardless of the order in which messages were sent. Equal messages may be received in any
+/// order. This suits schedulers that always pick the most important or most urgent task, e.g.
+/// with tasks ordered by [`Reverse`] deadlines.
+///
+/// Unlike [`bounded_priority`], which only has a single lane for urgent messages, this channel
+/// orders all messages. Messages are kept in a binary heap protected by a lock, so sending and
+/// receiving take logarithmic time and don't scale as well as [`bounded`] under heavy contention.
+///
+/// Both sides are an ordinary [`Sender`] and [`Receiver`], and can be used in [`select!`].
+///
+/// # Panics
+///
+/// Panics if the capacity is zero.
+///
+/// [`Ord`]: https://doc.rust-lang.org/std/cmp/trait.Ord.html
+/// [`Reverse`]: https://doc.rust-lang.org/std/cmp/struct.Reverse.html
+/// [`bounded_priority`]: fn.bounded_priority.html
+/// [`bounded`]: fn.bounded.html
+/// [`Sender`]: struct.Sender.html
+/// [`Receiver`]: struct.Receiver.html
+/// [`select!`]: macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// use std::cmp::Reverse;
+/// use crossbeam_channel::priority_channel;
+///
+/// let (s, r) = priority_channel(10);
+///
+/// // Tasks with earlier deadlines come first.
+/// s.send((Reverse(30), "later")).unwrap();
+/// s.send((Reverse(10), "sooner")).unwrap();
+/// s.send((Reverse(20), "soon")).unwrap();
+///
+/// assert_eq!(r.recv(), Ok((Reverse(10), "sooner")));
+/// assert_eq!(r.recv(), Ok((Reverse(20), "soon")));
+/// assert_eq!(r.recv(), Ok((Reverse(30), "later")));
+/// ```
+pub fn priority_channel<T: Ord>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::heap::Channel::with_capacity(cap));
+    let s = Sender {
+        flavor: SenderFlavor::Heap(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Heap(r),
+    };
+    (s, r)
+}
+
+/// Creates a channel of unbounded capacity that keeps only the latest value for each key.
+///
+/// Messages are `(key, value)` pairs. Sending a message while another one with the same key is
+/// still in the channel overwrites that message in place instead of appending a new one, so
+/// receivers never see stale values that have since been replaced. This suits state replication,
+/// where only the latest state of each entity matters and falling behind should not mean
+/// processing every intermediate update.
+///
+/// Messages are received roughly in FIFO order of when their keys were first sent: an
+/// overwritten message keeps its place in the queue and only its value changes. Once a message
+/// has been received, sending its key again appends a new message at the back.
+///
+/// The channel is indexed by key hashes, and sending or receiving takes constant time on average.
+/// The queue is protected by a lock, so this channel doesn't scale as well as [`unbounded`] under
+/// heavy contention.
+///
+/// Both sides are an ordinary [`Sender`] and [`Receiver`], and can be used in [`select!`].
+///
+/// [`unbounded`]: fn.unbounded.html
+/// [`Sender`]: struct.Sender.html
+/// [`Receiver`]: struct.Receiver.html
+/// [`select!`]: macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::coalescing_channel;
+///
+/// let (s, r) = coalescing_channel();
+///
+/// s.send(("cursor", 1)).unwrap();
+/// s.send(("title", 10)).unwrap();
+/// s.send(("cursor", 2)).unwrap();
+/// s.send(("cursor", 3)).unwrap();
+///
+/// // Only the latest cursor position is delivered, in the place of the first one.
+/// assert_eq!(r.len(), 2);
+/// assert_eq!(r.recv(), Ok(("cursor", 3)));
+/// assert_eq!(r.recv(), Ok(("title", 10)));
+/// ```
+pub fn coalescing_channel<K: Eq + Hash, V>() -> (Sender<(K, V)>, Receiver<(K, V)>) {
+    let (s, r) = counter::new(flavors::coalesce::Channel::new());
+    let s = Sender {
+        flavor: SenderFlavor::Coalesce(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Coalesce(r),
+    };
+    (s, r)
+}
+
+/// Creates a receiver that delivers a message after a certain duration of time.
+///
+/// The channel is bounded with capacity of 1 and never gets disconnected. Exactly one message will
+/// be sent into the channel after `duration` elapses. The message is the instant at which it is
+/// sent.
+///
+/// # Examples
+///
+/// Using an `after` channel for timeouts:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::time::Duration;
+/// use crossbeam_channel::{after, unbounded};
+///
+/// let (s, r) = unbounded::<i32>();
+/// let timeout = Duration::from_millis(100);
+///
+/// select! {
+///     recv(r) -> msg => println!("received {:?}", msg),
+///     recv(after(timeout)) -> _ => println!("timed out"),
+/// }
+/// # }
+/// ```
+///
+/// When the message gets sent:
+///
+/// ```
+/// use std::thread;
+/// use std::time::{Duration, Instant};
+/// use crossbeam_channel::after;
+///
+/// // Converts a number of milliseconds into a `Duration`.
+/// let ms = |ms| Duration::from_millis(ms);
+///
+/// // Returns `true` if `a` and `b` are very close `Instant`s.
+/// let eq = |a, b| a + ms(50) > b && b + ms(50) > a;
+///
+/// let start = Instant::now();
+/// let r = after(ms(100));
+///
+/// thread::sleep(ms(500));
+///
+/// // This message was sent 100 ms from the start and received 500 ms from the start.
+/// assert!(eq(r.recv().unwrap(), start + ms(100)));
+/// assert!(eq(Instant::now(), start + ms(500)));
+/// ```
+pub fn after(duration: Duration) -> Receiver<Instant> {
+    Receiver {
+        flavor: ReceiverFlavor::After(Arc::new(flavors::after::Channel::new(duration))),
+    }
+}
+
+/// Creates a receiver that never delivers messages.
+///
+/// The channel is bounded with capacity of 0 and never gets disconnected.
+///
+/// # Examples
+///
+/// Using a `never` channel to optionally add a timeout to [`select!`]:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::thread;
+/// use std::time::{Duration, Instant};
+/// use crossbeam_channel::{after, never, unbounded};
+///
+/// let (s, r) = unbounded();
+///
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_secs(1));
+///     s.send(1).unwrap();
+/// });
+///
+/// // Suppose this duration can be a `Some` or a `None`.
+/// let duration = Some(Duration::from_millis(100));
+///
+/// // Create a channel that times out after the specified duration.
+/// let timeout = duration
+///     .map(|d| after(d))
+///     .unwrap_or(never());
+///
+/// select! {
+///     recv(r) -> msg => assert_eq!(msg, Ok(1)),
+///     recv(timeout) -> _ => println!("timed out"),
+/// }
+/// # }
+/// ```
+///
+/// [`select!`]: macro.select.html
+pub fn never<T>() -> Receiver<T> {
+    Receiver {
+        flavor: ReceiverFlavor::Never(flavors::never::Channel::new()),
+    }
+}
+
+/// Creates a receiver that always delivers a clone of `msg`.
+///
+/// The channel is bounded with capacity of 1, always holds a message, and never gets
+/// disconnected. Every receive operation succeeds immediately and returns a clone of `msg`.
+///
+/// Receivers sharing the channel clone the message one at a time, so `T` only needs to be `Send`.
+///
+/// # Selection
+///
+/// In a [`select!`], a receive operation on this channel is always ready, and it competes fairly
+/// with other ready operations: selection picks randomly among them.
+///
+/// To use it as a lowest-priority fallback, list it last in a [`select_biased!`] or
+/// [`Select::new_biased`]. Biased selection always picks the first ready operation, so the `ready`
+/// channel is then chosen only when none of the operations before it are ready.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::ready;
+///
+/// let r = ready("hello");
+///
+/// assert_eq!(r.recv(), Ok("hello"));
+/// assert_eq!(r.try_recv(), Ok("hello"));
+/// assert_eq!(r.len(), 1);
+/// ```
+///
+/// Fall back to a default message when no work is pending:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use crossbeam_channel::{ready, unbounded};
+///
+/// let (s, r) = unbounded();
+/// let idle = ready(0);
+///
+/// s.send(1).unwrap();
+///
+/// // `r` is ready and listed first, so it wins.
+/// select_biased! {
+///     recv(r) -> msg => assert_eq!(msg, Ok(1)),
+///     recv(idle) -> _ => panic!(),
+/// }
+///
+/// // `r` is empty now, so the fallback is chosen.
+/// select_biased! {
+///     recv(r) -> _ => panic!(),
+///     recv(idle) -> msg => assert_eq!(msg, Ok(0)),
+/// }
+/// # }
+/// ```
+///
+/// [`select!`]: macro.select.html
+/// [`select_biased!`]: macro.select_biased.html
+/// [`Select::new_biased`]: struct.Select.html#method.new_biased
+pub fn ready<T: Clone + Send>(msg: T) -> Receiver<T> {
+    Receiver {
+        flavor: ReceiverFlavor::Ready(Arc::new(flavors::ready::Channel::new(msg))),
+    }
+}
+
+/// Creates a receiver that receives messages from both `a` and `b`.
+///
+/// Messages are taken from whichever input has one, alternating between the inputs when both do,
+/// so neither of them gets starved. The merged receiver is disconnected only when both inputs are
+/// empty and disconnected. Messages cannot be sent into the merged channel directly.
+///
+/// No thread is spawned: receive operations on the merged receiver, including those in
+/// [`select!`], wait on both inputs directly. Messages from zero-capacity inputs are only taken
+/// from senders that are blocked, so a [`try_send`] into such an input never pairs up with the
+/// merged receiver.
+///
+/// [`select!`]: macro.select.html
+/// [`try_send`]: struct.Sender.html#method.try_send
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{merge, unbounded};
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+/// let r = merge(r1, r2);
+///
+/// thread::spawn(move || s1.send(1).unwrap());
+/// thread::spawn(move || s2.send(2).unwrap());
+///
+/// let mut msgs = vec![r.recv().unwrap(), r.recv().unwrap()];
+/// msgs.sort();
+/// assert_eq!(msgs, [1, 2]);
+///
+/// // Both senders are gone now.
+/// assert!(r.recv().is_err());
+/// ```
+pub fn merge<T>(a: Receiver<T>, b: Receiver<T>) -> Receiver<T> {
+    Receiver {
+        flavor: ReceiverFlavor::Merge(Arc::new(flavors::merge::Channel::new(a, b))),
+    }
+}
+
+/// Creates a receiver that delivers messages periodically.
+///
+/// The channel is bounded with capacity of 1 and never gets disconnected. Messages will be
+/// sent into the channel in intervals of `duration`. Each message is the instant at which it is
+/// sent.
+///
+/// # Examples
+///
+/// Using a `tick` channel to periodically print elapsed time:
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use crossbeam_channel::tick;
+///
+/// let start = Instant::now();
+/// let ticker = tick(Duration::from_millis(100));
+///
+/// for _ in 0..5 {
+///     ticker.recv().unwrap();
+///     println!("elapsed: {:?}", start.elapsed());
+/// }
+/// ```
+///
+/// When messages get sent:
+///
+/// ```
+/// use std::thread;
+/// use std::time::{Duration, Instant};
+/// use crossbeam_channel::tick;
+///
+/// // Converts a number of milliseconds into a `Duration`.
+/// let ms = |ms| Duration::from_millis(ms);
+///
+/// // Returns `true` if `a` and `b` are very close `Instant`s.
+/// let eq = |a, b| a + ms(50) > b && b + ms(50) > a;
+///
+/// let start = Instant::now();
+/// let r = tick(ms(100));
+///
+/// // This message was sent 100 ms from the start and received 100 ms from the start.
+/// assert!(eq(r.recv().unwrap(), start + ms(100)));
+/// assert!(eq(Instant::now(), start + ms(100)));
+///
+/// thread::sleep(ms(500));
+///
+/// // This message was sent 200 ms from the start and received 600 ms from the start.
+/// assert!(eq(r.recv().unwrap(), start + ms(200)));
+/// assert!(eq(Instant::now(), start + ms(600)));
+///
+/// // This message was sent 700 ms from the start and received 700 ms from the start.
+/// assert!(eq(r.recv().unwrap(), start + ms(700)));
+/// assert!(eq(Instant::now(), start + ms(700)));
+/// ```
+pub fn tick(duration: Duration) -> Receiver<Instant> {
+    Receiver {
+        flavor: ReceiverFlavor::Tick(Arc::new(flavors::tick::Channel::new(duration))),
+    }
+}
+
+/// Creates a receiver that delivers messages periodically, with random variation in the period.
+///
+/// This is just like [`tick`], except that each interval between messages is `duration` plus a
+/// random offset drawn uniformly from `-jitter` to `+jitter`, independently for every interval.
+/// On average, messages are still delivered once per `duration`. This keeps many tickers started
+/// with the same period, such as heartbeats or cache refreshes in different services, from
+/// firing in lockstep and causing synchronized load spikes.
+///
+/// Just like with [`tick`], each interval starts when the previous message is received. The
+/// jitter has a resolution of one microsecond. It is limited to `duration` so that intervals are
+/// never negative, and to about 35 minutes.
+///
+/// The random offsets come from the same fast thread-local generator used for random selection,
+/// which is not suitable for cryptographic purposes.
+///
+/// [`tick`]: fn.tick.html
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use crossbeam_channel::tick_jittered;
+///
+/// let start = Instant::now();
+/// let heartbeat = tick_jittered(Duration::from_millis(100), Duration::from_millis(20));
+///
+/// // The first message arrives between 80 and 120 ms from the start.
+/// let msg = heartbeat.recv().unwrap();
+/// assert!(msg >= start + Duration::from_millis(80));
+/// assert!(msg <= Instant::now());
+/// ```
+pub fn tick_jittered(duration: Duration, jitter: Duration) -> Receiver<Instant> {
+    Receiver {
+        flavor: ReceiverFlavor::Tick(Arc::new(flavors::tick::Channel::with_jitter(
+            duration, jitter,
+        ))),
+    }
+}
+
+/// The kind of a channel.
+///
+/// This is a coarse classification of a channel by its capacity, returned by [`Sender::kind`] and
+/// [`Receiver::kind`].
+///
+/// [`Sender::kind`]: struct.Sender.html#method.kind
+/// [`Receiver::kind`]: struct.Receiver.html#method.kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelKind {
+    /// A channel that can hold a limited, positive number of messages at a time.
+    Bounded,
+
+    /// A channel that can hold any number of messages at a time.
+    Unbounded,
+
+    /// A zero-capacity channel, where send and receive operations must pair up.
+    Rendezvous,
+}
+
+/// How threads wait in blocking operations on a channel created by [`bounded_with_strategy`].
+///
+/// The strategy trades CPU time for latency. A parked thread uses no CPU time, but waking it up
+/// takes a system call on both sides and it may take a while until the operating system schedules
+/// it again. A spinning thread notices a ready channel almost immediately, but keeps a core busy
+/// for as long as it waits.
+///
+/// [`bounded_with_strategy`]: fn.bounded_with_strategy.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParkStrategy {
+    /// Busy-wait without ever yielding or parking the thread.
+    ///
+    /// This gives the lowest latency, but burns a whole core while waiting and can starve other
+    /// threads running on it. Use it only when the waiting threads are pinned to dedicated cores.
+    Spin,
+
+    /// Spin for a short while, then yield the time slice to other threads and try again.
+    ///
+    /// Waiting threads never go to sleep, so they keep using CPU time, but they let other threads
+    /// run on the same core. Latency depends on how busy the system is.
+    Yield,
+
+    /// Spin for a short while, then park the thread until it is woken up.
+    ///
+    /// This is what all other channels do. Waiting threads use no CPU time, which suits many
+    /// channels that are idle most of the time.
+    Block,
+}
+
+/// What to do with a message inspected by [`Receiver::recv_peek_decide`].
+///
+/// [`Receiver::recv_peek_decide`]: struct.Receiver.html#method.recv_peek_decide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Decision {
+    /// Remove the message from the channel and return it.
+    Take,
+
+    /// Leave the message at the front of the channel for the next receive operation.
+    Leave,
+}
+
+/// The result of [`Receiver::poll_recv`].
+///
+/// This mirrors `std::task::Poll`, which is not available in all supported Rust versions.
+///
+/// [`Receiver::poll_recv`]: struct.Receiver.html#method.poll_recv
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Poll<T> {
+    /// The value is ready.
+    Ready(T),
+
+    /// The value is not ready yet, and a wakeup has been registered.
+    Pending,
+}
+
+/// The outcome of [`Receiver::poll`].
+///
+/// This carries the same information as `Result<T, TryRecvError>`, flattened into a single enum.
+///
+/// [`Receiver::poll`]: struct.Receiver.html#method.poll
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecvState<T> {
+    /// A message was received.
+    Ready(T),
+
+    /// The channel is empty, but not disconnected.
+    Empty,
+
+    /// The channel is empty and disconnected.
+    Disconnected,
+}
+
+/// The outcome of [`Receiver::steal`].
+///
+/// [`Receiver::steal`]: struct.Receiver.html#method.steal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Steal<T> {
+    /// A message was stolen.
+    Success(T),
+
+    /// The channel was empty.
+    Empty,
+
+    /// Another thread got in the way, either by winning the race for the message, by being in the
+    /// middle of writing it, or by inspecting it, and the operation should be retried.
+    Retry,
+}
+
+/// The outcome of [`Sender::offer_state`].
+///
+/// This carries the same information as `Result<(), TrySendError<T>>`, flattened into a single
+/// enum.
+///
+/// [`Sender::offer_state`]: struct.Sender.html#method.offer_state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SendState<T> {
+    /// The message was sent.
+    Sent,
+
+    /// The channel is full, and the message is given back.
+    Full(T),
+
+    /// The channel is disconnected, and the message is given back.
+    Disconnected(T),
+}
+
+/// The sending side of a channel.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::unbounded;
+///
+/// let (s1, r) = unbounded();
+/// let s2 = s1.clone();
+///
+/// thread::spawn(move || s1.send(1).unwrap());
+/// thread::spawn(move || s2.send(2).unwrap());
+///
+/// let msg1 = r.recv().unwrap();
+/// let msg2 = r.recv().unwrap();
+///
+/// assert_eq!(msg1 + msg2, 3);
+/// ```
+pub struct Sender<T> {
+    flavor: SenderFlavor<T>,
+}
+
+/// Sender flavors.
+enum SenderFlavor<T> {
+    /// Bounded channel based on a preallocated array.
+    Array(counter::Sender<flavors::array::Channel<T>>),
+
+    /// Unbounded channel implemented as a linked list.
+    List(counter::Sender<flavors::list::Channel<T>>),
+
+    /// Zero-capacity channel.
+    Zero(counter::Sender<flavors::zero::Channel<T>>),
+
+    /// Single-use channel that carries at most one message.
+    Oneshot(counter::Sender<flavors::oneshot::Channel<T>>),
+
+    /// Bounded channel that receives messages in priority order.
+    Heap(counter::Sender<flavors::heap::Channel<T>>),
+
+    /// Unbounded channel that keeps only the latest message for each key.
+    Coalesce(counter::Sender<flavors::coalesce::Channel<T>>),
+}
+
+unsafe impl<T: Send> Send for Sender<T> {}
+unsafe impl<T: Send> Sync for Sender<T> {}
+
+impl<T> UnwindSafe for Sender<T> {}
+impl<T> RefUnwindSafe for Sender<T> {}
+
+impl<T> Sender<T> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// This method will either send a message into the channel immediately or return an error if
+    /// the channel is full or disconnected. The returned error contains the original message.
+    ///
+    /// If called on a zero-capacity channel, this method will send the message only if there
+    /// happens to be a receive operation on the other side of the channel at the same time.
+    ///
+    /// If called on an unbounded channel, this method never returns [`TrySendError::Full`]. Memory
+    /// for new messages is allocated on demand, and a failed allocation aborts the process.
+    ///
+    /// [`TrySendError::Full`]: enum.TrySendError.html#variant.Full
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, TrySendError};
+    ///
+    /// let (s, r) = bounded(1);
+    ///
+    /// assert_eq!(s.try_send(1), Ok(()));
+    /// assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.try_send(3), Err(TrySendError::Disconnected(3)));
+    /// ```
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        if !self.replay_turn(|| Some(Instant::now())) {
+            return Err(TrySendError::Full(msg));
+        }
+        let res = match &self.flavor {
+            SenderFlavor::Array(chan) => chan.try_send(msg),
+            SenderFlavor::List(chan) => chan.try_send(msg),
+            SenderFlavor::Zero(chan) => chan.try_send(msg),
+            SenderFlavor::Oneshot(chan) => chan.try_send(msg),
+            SenderFlavor::Heap(chan) => chan.try_send(msg),
+            SenderFlavor::Coalesce(chan) => chan.try_send(msg),
+        };
+        self.replay_complete(res.is_ok());
+        res.map(|()| self.observe_len())
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// If the channel is full and not disconnected, this call will block until the send operation
+    /// can proceed. If the channel becomes disconnected, this call will wake up and return an
+    /// error. The returned error contains the original message.
+    ///
+    /// If called on a zero-capacity channel, this method will wait for a receive operation to
+    /// appear on the other side of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{bounded, SendError};
+    ///
+    /// let (s, r) = bounded(1);
+    /// assert_eq!(s.send(1), Ok(()));
+    ///
+    /// thread::spawn(move || {
+    ///     assert_eq!(r.recv(), Ok(1));
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     drop(r);
+    /// });
+    ///
+    /// assert_eq!(s.send(2), Ok(()));
+    /// assert_eq!(s.send(3), Err(SendError(3)));
+    /// ```
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        trace_span!("send", name = self.name().unwrap_or(""), len = self.len());
+        self.replay_turn(|| None);
+        let res = match &self.flavor {
             SenderFlavor::Array(chan) => chan.send(msg, None),
             SenderFlavor::List(chan) => chan.send(msg, None),
             SenderFlavor::Zero(chan) => chan.send(msg, None),
+            SenderFlavor::Oneshot(chan) => chan.send(msg, None),
+            SenderFlavor::Heap(chan) => chan.send(msg, None),
+            SenderFlavor::Coalesce(chan) => chan.send(msg, None),
+        };
+        self.replay_complete(res.is_ok());
+        res.map(|()| self.observe_len()).map_err(|err| match err {
+            SendTimeoutError::Disconnected(msg) => SendError(msg),
+            SendTimeoutError::Timeout(_) => unreachable!(),
+        })
+    }
+
+    /// Sends a message, telling apart a used-up [`oneshot`] channel from a disconnected one.
+    ///
+    /// On a channel created by [`oneshot`], [`send`] and [`try_send`] report a second send as if
+    /// the channel was disconnected. This method returns [`SendOnceError::AlreadySent`] instead, so
+    /// callers can detect that the channel was reused. If the message has been sent and the
+    /// receiver is gone as well, the error is still `AlreadySent`.
+    ///
+    /// On other channels, this method behaves just like [`send`], and the only error it returns is
+    /// [`SendOnceError::Disconnected`].
+    ///
+    /// [`oneshot`]: fn.oneshot.html
+    /// [`send`]: struct.Sender.html#method.send
+    /// [`try_send`]: struct.Sender.html#method.try_send
+    /// [`SendOnceError::AlreadySent`]: enum.SendOnceError.html#variant.AlreadySent
+    /// [`SendOnceError::Disconnected`]: enum.SendOnceError.html#variant.Disconnected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{oneshot, SendOnceError};
+    ///
+    /// let (s, r) = oneshot();
+    /// assert_eq!(s.send_once(1), Ok(()));
+    /// assert_eq!(s.send_once(2), Err(SendOnceError::AlreadySent(2)));
+    /// assert_eq!(r.recv(), Ok(1));
+    ///
+    /// let (s, r) = oneshot();
+    /// drop(r);
+    /// assert_eq!(s.send_once(3), Err(SendOnceError::Disconnected(3)));
+    /// ```
+    pub fn send_once(&self, msg: T) -> Result<(), SendOnceError<T>> {
+        match &self.flavor {
+            SenderFlavor::Oneshot(chan) => {
+                self.replay_turn(|| None);
+                let res = chan.send_once(msg);
+                self.replay_complete(res.is_ok());
+                res.map(|()| self.observe_len())
+            }
+            _ => self
+                .send(msg)
+                .map_err(|SendError(msg)| SendOnceError::Disconnected(msg)),
+        }
+    }
+
+    /// Blocks the current thread until a message is sent, and then returns the number of messages
+    /// in the channel.
+    ///
+    /// This method goes through [`send`], so it is traced and replayed like one, and on success
+    /// it returns the length of the channel observed right after the message was sent. A producer
+    /// can use it to slow down when receivers are falling behind. The length is only a snapshot
+    /// and other threads may change it at any time. On a zero-capacity channel, the returned length is always 0.
+    ///
+    /// [`send`]: struct.Sender.html#method.send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, SendError};
+    ///
+    /// let (s, r) = unbounded();
+    /// assert_eq!(s.send_and_len(1), Ok(1));
+    /// assert_eq!(s.send_and_len(2), Ok(2));
+    ///
+    /// assert_eq!(r.recv(), Ok(1));
+    /// assert_eq!(s.send_and_len(3), Ok(2));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.send_and_len(4), Err(SendError(4)));
+    /// ```
+    pub fn send_and_len(&self, msg: T) -> Result<usize, SendError<T>> {
+        self.send(msg).map(|()| self.len())
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    ///
+    /// If the channel is full and not disconnected, this call will block until the send operation
+    /// can proceed or the operation times out. If the channel becomes disconnected, this call will
+    /// wake up and return an error. The returned error contains the original message.
+    ///
+    /// If called on a zero-capacity channel, this method will wait for a receive operation to
+    /// appear on the other side of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{bounded, SendTimeoutError};
+    ///
+    /// let (s, r) = bounded(0);
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     assert_eq!(r.recv(), Ok(2));
+    ///     drop(r);
+    /// });
+    ///
+    /// assert_eq!(
+    ///     s.send_timeout(1, Duration::from_millis(500)),
+    ///     Err(SendTimeoutError::Timeout(1)),
+    /// );
+    /// assert_eq!(
+    ///     s.send_timeout(2, Duration::from_secs(1)),
+    ///     Ok(()),
+    /// );
+    /// assert_eq!(
+    ///     s.send_timeout(3, Duration::from_millis(500)),
+    ///     Err(SendTimeoutError::Disconnected(3)),
+    /// );
+    /// ```
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        trace_span!(
+            "send_timeout",
+            name = self.name().unwrap_or(""),
+            len = self.len(),
+            ?timeout
+        );
+        let deadline = Instant::now() + timeout;
+        if !self.replay_turn(|| Some(deadline)) {
+            return Err(SendTimeoutError::Timeout(msg));
+        }
+
+        let res = match &self.flavor {
+            SenderFlavor::Array(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::List(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Zero(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Oneshot(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Heap(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Coalesce(chan) => chan.send(msg, Some(deadline)),
+        };
+        self.replay_complete(res.is_ok());
+        res.map(|()| self.observe_len())
+    }
+
+    /// Attempts to send a message, spinning briefly if the channel is full, but never parking.
+    ///
+    /// This sits between [`try_send`], which makes a single attempt, and [`send_timeout`], which
+    /// parks the thread until the timeout. It makes one attempt like [`try_send`], followed by a
+    /// few retries with exponentially growing busy-wait spins in between. The whole budget is a
+    /// fixed, small number of spin loop iterations, typically a few microseconds. The thread never
+    /// yields or parks, so no system call is made, which suits best-effort sends from real-time
+    /// loops.
+    ///
+    /// If the channel is still full after that, [`TrySendError::Full`] is returned. If it is
+    /// disconnected, [`TrySendError::Disconnected`] is returned right away. Either way, the error
+    /// contains the original message.
+    ///
+    /// [`try_send`]: struct.Sender.html#method.try_send
+    /// [`send_timeout`]: struct.Sender.html#method.send_timeout
+    /// [`TrySendError::Full`]: enum.TrySendError.html#variant.Full
+    /// [`TrySendError::Disconnected`]: enum.TrySendError.html#variant.Disconnected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, TrySendError};
+    ///
+    /// let (s, r) = bounded(1);
+    ///
+    /// assert_eq!(s.offer(1), Ok(()));
+    /// assert_eq!(s.offer(2), Err(TrySendError::Full(2)));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.offer(3), Err(TrySendError::Disconnected(3)));
+    /// ```
+    pub fn offer(&self, msg: T) -> Result<(), TrySendError<T>> {
+        // `Backoff::spin` doubles the spin loop on each call until it reaches its cap on the
+        // seventh one, so this many retries spin for the full exponential range and no longer.
+        const RETRIES: usize = 7;
+
+        let backoff = Backoff::new();
+        let mut msg = msg;
+
+        for _ in 0..RETRIES {
+            match self.try_send(msg) {
+                Err(TrySendError::Full(m)) => msg = m,
+                res => return res,
+            }
+            backoff.spin();
+        }
+        self.try_send(msg)
+    }
+
+    /// Attempts to send a message without blocking, returning the outcome as a [`SendState`].
+    ///
+    /// This is the same as [`try_send`], but with the error flattened into the returned enum,
+    /// which makes for shorter match arms in polling loops. Unlike [`offer`], it makes a single
+    /// attempt.
+    ///
+    /// [`SendState`]: enum.SendState.html
+    /// [`try_send`]: struct.Sender.html#method.try_send
+    /// [`offer`]: struct.Sender.html#method.offer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, SendState};
+    ///
+    /// let (s, r) = bounded(1);
+    ///
+    /// assert_eq!(s.offer_state(1), SendState::Sent);
+    /// assert_eq!(s.offer_state(2), SendState::Full(2));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.offer_state(3), SendState::Disconnected(3));
+    /// ```
+    pub fn offer_state(&self, msg: T) -> SendState<T> {
+        match self.try_send(msg) {
+            Ok(()) => SendState::Sent,
+            Err(TrySendError::Full(msg)) => SendState::Full(msg),
+            Err(TrySendError::Disconnected(msg)) => SendState::Disconnected(msg),
+        }
+    }
+
+    /// Attempts to reserve a slot in the channel for a message that is sent later, without
+    /// blocking.
+    ///
+    /// The returned [`Slot`] holds a place at the back of the channel. Filling it with
+    /// [`Slot::send`] never blocks and never fails, so capacity can be reserved before the message
+    /// is computed. Receivers never see a reserved slot: a receive operation that reaches it waits
+    /// until it is filled, or skips it if the [`Slot`] is dropped without sending, which abandons
+    /// the reservation. Messages sent after the reservation wait behind it, so a slot should be
+    /// filled or dropped promptly.
+    ///
+    /// While a reserved slot is at the front of the channel, receive operations see the channel
+    /// as empty: non-blocking ones such as [`Receiver::try_recv`] fail, and blocking ones wait
+    /// without spinning. In particular, a thread holding a slot must not block on receiving from
+    /// the same channel before it fills or drops the slot, or it waits forever.
+    ///
+    /// If the channel is full, this method returns [`TryReserveSlotError::Full`]. If all receivers
+    /// have been dropped, it returns [`TryReserveSlotError::Disconnected`]. If the channel was not
+    /// created by [`bounded`] with a positive capacity, it returns
+    /// [`TryReserveSlotError::Unsupported`], because other channels don't keep their messages in a
+    /// ring buffer.
+    ///
+    /// A reserved slot counts toward the length of the channel until it is received or skipped.
+    ///
+    /// [`Slot`]: struct.Slot.html
+    /// [`Slot::send`]: struct.Slot.html#method.send
+    /// [`Receiver::try_recv`]: struct.Receiver.html#method.try_recv
+    /// [`TryReserveSlotError::Full`]: enum.TryReserveSlotError.html#variant.Full
+    /// [`TryReserveSlotError::Disconnected`]: enum.TryReserveSlotError.html#variant.Disconnected
+    /// [`TryReserveSlotError::Unsupported`]: enum.TryReserveSlotError.html#variant.Unsupported
+    /// [`bounded`]: fn.bounded.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, TryReserveSlotError};
+    ///
+    /// let (s, r) = bounded(2);
+    ///
+    /// let first = s.try_reserve().unwrap();
+    /// s.send(2).unwrap();
+    /// assert_eq!(s.try_reserve().err(), Some(TryReserveSlotError::Full));
+    ///
+    /// // The second message waits behind the reserved slot.
+    /// assert!(r.try_recv().is_err());
+    /// first.send(1);
+    /// assert_eq!(r.try_recv(), Ok(1));
+    /// assert_eq!(r.try_recv(), Ok(2));
+    /// ```
+    pub fn try_reserve(&self) -> Result<Slot<'_, T>, TryReserveSlotError> {
+        let reservation = match &self.flavor {
+            SenderFlavor::Array(chan) => chan.try_reserve().map_err(|err| match err {
+                TrySendError::Full(()) => TryReserveSlotError::Full,
+                TrySendError::Disconnected(()) => TryReserveSlotError::Disconnected,
+            })?,
+            _ => return Err(TryReserveSlotError::Unsupported),
+        };
+        Ok(Slot {
+            sender: self,
+            reservation,
+        })
+    }
+
+    /// Reserves a slot in the channel for a message that is sent later, blocking until there is
+    /// room or the channel is disconnected.
+    ///
+    /// This method behaves just like [`try_reserve`], except that it waits for room when the
+    /// channel is full. If all receivers have been dropped, or if the channel was not created by
+    /// [`bounded`] with a positive capacity, it returns an error.
+    ///
+    /// [`try_reserve`]: struct.Sender.html#method.try_reserve
+    /// [`bounded`]: fn.bounded.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::bounded;
+    ///
+    /// let (s, r) = bounded(1);
+    ///
+    /// let slot = s.reserve().unwrap();
+    /// let h = thread::spawn(move || r.recv());
+    ///
+    /// // The receiver waits until the slot is filled.
+    /// slot.send("computed later");
+    /// assert_eq!(h.join().unwrap(), Ok("computed later"));
+    /// ```
+    pub fn reserve(&self) -> Result<Slot<'_, T>, ReserveSlotError> {
+        let reservation = match &self.flavor {
+            SenderFlavor::Array(chan) => chan.reserve(None).map_err(|err| match err {
+                SendTimeoutError::Disconnected(()) => ReserveSlotError::Disconnected,
+                SendTimeoutError::Timeout(()) => unreachable!(),
+            })?,
+            _ => return Err(ReserveSlotError::Unsupported),
+        };
+        Ok(Slot {
+            sender: self,
+            reservation,
+        })
+    }
+
+    /// Returns `true` if the channel is empty.
+    ///
+    /// Note: Zero-capacity channels are always empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// assert!(s.is_empty());
+    ///
+    /// s.send(0).unwrap();
+    /// assert!(!s.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.is_empty(),
+            SenderFlavor::List(chan) => chan.is_empty(),
+            SenderFlavor::Zero(chan) => chan.is_empty(),
+            SenderFlavor::Oneshot(chan) => chan.is_empty(),
+            SenderFlavor::Heap(chan) => chan.is_empty(),
+            SenderFlavor::Coalesce(chan) => chan.is_empty(),
+        }
+    }
+
+    /// Returns `true` if the channel is full.
+    ///
+    /// Note: Zero-capacity channels are always full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::bounded;
+    ///
+    /// let (s, r) = bounded(1);
+    ///
+    /// assert!(!s.is_full());
+    /// s.send(0).unwrap();
+    /// assert!(s.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.is_full(),
+            SenderFlavor::List(chan) => chan.is_full(),
+            SenderFlavor::Zero(chan) => chan.is_full(),
+            SenderFlavor::Oneshot(chan) => chan.is_full(),
+            SenderFlavor::Heap(chan) => chan.is_full(),
+            SenderFlavor::Coalesce(chan) => chan.is_full(),
+        }
+    }
+
+    /// Returns the number of messages in the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// assert_eq!(s.len(), 0);
+    ///
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// assert_eq!(s.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.len(),
+            SenderFlavor::List(chan) => chan.len(),
+            SenderFlavor::Zero(chan) => chan.len(),
+            SenderFlavor::Oneshot(chan) => chan.len(),
+            SenderFlavor::Heap(chan) => chan.len(),
+            SenderFlavor::Coalesce(chan) => chan.len(),
+        }
+    }
+
+    /// If the channel is bounded, returns its capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, unbounded};
+    ///
+    /// let (s, _) = unbounded::<i32>();
+    /// assert_eq!(s.capacity(), None);
+    ///
+    /// let (s, _) = bounded::<i32>(5);
+    /// assert_eq!(s.capacity(), Some(5));
+    ///
+    /// let (s, _) = bounded::<i32>(0);
+    /// assert_eq!(s.capacity(), Some(0));
+    /// ```
+    pub fn capacity(&self) -> Option<usize> {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.capacity(),
+            SenderFlavor::List(chan) => chan.capacity(),
+            SenderFlavor::Zero(chan) => chan.capacity(),
+            SenderFlavor::Oneshot(chan) => chan.capacity(),
+            SenderFlavor::Heap(chan) => chan.capacity(),
+            SenderFlavor::Coalesce(chan) => chan.capacity(),
+        }
+    }
+
+    /// Returns the kind of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, unbounded, ChannelKind};
+    ///
+    /// let (s, _) = unbounded::<i32>();
+    /// assert_eq!(s.kind(), ChannelKind::Unbounded);
+    ///
+    /// let (s, _) = bounded::<i32>(5);
+    /// assert_eq!(s.kind(), ChannelKind::Bounded);
+    ///
+    /// let (s, _) = bounded::<i32>(0);
+    /// assert_eq!(s.kind(), ChannelKind::Rendezvous);
+    /// ```
+    pub fn kind(&self) -> ChannelKind {
+        match &self.flavor {
+            SenderFlavor::Array(_) => ChannelKind::Bounded,
+            SenderFlavor::List(_) => ChannelKind::Unbounded,
+            SenderFlavor::Zero(_) => ChannelKind::Rendezvous,
+            SenderFlavor::Oneshot(_) => ChannelKind::Bounded,
+            SenderFlavor::Heap(_) => ChannelKind::Bounded,
+            SenderFlavor::Coalesce(_) => ChannelKind::Unbounded,
+        }
+    }
+
+    /// Returns the name of the channel, if it was given one.
+    ///
+    /// Channels are named by constructors such as [`bounded_named`] and [`unbounded_named`]. The
+    /// name is shared by all senders and receivers of the channel.
+    ///
+    /// [`bounded_named`]: fn.bounded_named.html
+    /// [`unbounded_named`]: fn.unbounded_named.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, unbounded_named};
+    ///
+    /// let (s, _) = unbounded_named::<i32>("jobs");
+    /// assert_eq!(s.clone().name(), Some("jobs"));
+    ///
+    /// let (s, _) = unbounded::<i32>();
+    /// assert_eq!(s.name(), None);
+    /// ```
+    pub fn name(&self) -> Option<&str> {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.name(),
+            SenderFlavor::List(chan) => chan.name(),
+            SenderFlavor::Zero(chan) => chan.name(),
+            SenderFlavor::Oneshot(chan) => chan.name(),
+            SenderFlavor::Heap(chan) => chan.name(),
+            SenderFlavor::Coalesce(chan) => chan.name(),
+        }
+    }
+
+    /// Returns a pointer identifying the channel.
+    ///
+    /// The pointer is the same for all senders and receivers of the channel, and differs between
+    /// channels that exist at the same time. Unlike [`identical_to`], it doesn't depend on the
+    /// message type, so handles stored as trait objects can still be compared, deduplicated, or
+    /// used as map keys.
+    ///
+    /// The pointer is only meaningful for comparison and must never be dereferenced. Once the
+    /// channel is dropped, a new channel may get the same pointer.
+    ///
+    /// [`identical_to`]: struct.Sender.html#method.identical_to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::any::Any;
+    /// use crossbeam_channel::{bounded, unbounded, Sender};
+    ///
+    /// let (s1, r1) = unbounded::<i32>();
+    /// let (s2, _) = bounded::<String>(1);
+    /// assert_eq!(s1.channel_ptr(), r1.channel_ptr());
+    /// assert_eq!(s1.channel_ptr(), s1.clone().channel_ptr());
+    ///
+    /// let handles: Vec<(*const (), Box<Any>)> = vec![
+    ///     (s1.channel_ptr(), Box::new(s1.clone())),
+    ///     (s2.channel_ptr(), Box::new(s2)),
+    /// ];
+    /// assert!(handles.iter().any(|h| h.0 == r1.channel_ptr()));
+    /// assert!(handles[0].1.downcast_ref::<Sender<i32>>().is_some());
+    /// ```
+    pub fn channel_ptr(&self) -> *const () {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.as_ptr(),
+            SenderFlavor::List(chan) => chan.as_ptr(),
+            SenderFlavor::Zero(chan) => chan.as_ptr(),
+            SenderFlavor::Oneshot(chan) => chan.as_ptr(),
+            SenderFlavor::Heap(chan) => chan.as_ptr(),
+            SenderFlavor::Coalesce(chan) => chan.as_ptr(),
+        }
+    }
+
+    /// Returns true if senders send to the same channel.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, _) = unbounded::<usize>();
+    ///
+    /// let s2 = s.clone();
+    /// assert!(s.identical_to(&s2));
+    ///
+    /// let (s3, _) = unbounded();
+    /// assert!(!s.identical_to(&s3));
+    /// ```
+    pub fn identical_to(&self, other: &Sender<T>) -> bool {
+        use self::SenderFlavor::*;
+        match (&self.flavor, &other.flavor) {
+            (Array(ref self_counter), Array(ref other_counter)) => self_counter == other_counter,
+            (List(ref self_counter), List(ref other_counter)) => self_counter == other_counter,
+            (Zero(ref self_counter), Zero(ref other_counter)) => self_counter == other_counter,
+            (Oneshot(ref self_counter), Oneshot(ref other_counter)) => {
+                self_counter == other_counter
+            }
+            (Heap(ref self_counter), Heap(ref other_counter)) => self_counter == other_counter,
+            (Coalesce(ref self_counter), Coalesce(ref other_counter)) => {
+                self_counter == other_counter
+            }
+            // Channels of different flavours are never equal.
+            _ => false,
+        }
+    }
+
+    /// Blocks the current thread until all receivers of the channel are dropped.
+    ///
+    /// This is useful for a producer that would rather stop early than spend time computing the
+    /// next message when nobody is going to receive it. The thread is parked while waiting and
+    /// woken up by the last receiver as it gets dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     drop(r);
+    /// });
+    ///
+    /// s.wait_closed();
+    /// assert!(s.send(1).is_err());
+    /// ```
+    pub fn wait_closed(&self) {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.wait_closed(None),
+            SenderFlavor::List(chan) => chan.wait_closed(None),
+            SenderFlavor::Zero(chan) => chan.wait_closed(None),
+            SenderFlavor::Oneshot(chan) => chan.wait_closed(None),
+            SenderFlavor::Heap(chan) => chan.wait_closed(None),
+            SenderFlavor::Coalesce(chan) => chan.wait_closed(None),
+        };
+    }
+
+    /// Waits until all receivers of the channel are dropped, but only until the given deadline.
+    ///
+    /// Returns `true` if all receivers have been dropped, and `false` if the deadline was reached
+    /// first. If the deadline has already passed, this method doesn't block and simply checks
+    /// whether the receivers are gone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    ///
+    /// assert!(!s.closed_deadline(Instant::now()));
+    /// assert!(!s.closed_deadline(Instant::now() + Duration::from_millis(100)));
+    ///
+    /// drop(r);
+    /// assert!(s.closed_deadline(Instant::now()));
+    /// ```
+    pub fn closed_deadline(&self, deadline: Instant) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.wait_closed(Some(deadline)),
+            SenderFlavor::List(chan) => chan.wait_closed(Some(deadline)),
+            SenderFlavor::Zero(chan) => chan.wait_closed(Some(deadline)),
+            SenderFlavor::Oneshot(chan) => chan.wait_closed(Some(deadline)),
+            SenderFlavor::Heap(chan) => chan.wait_closed(Some(deadline)),
+            SenderFlavor::Coalesce(chan) => chan.wait_closed(Some(deadline)),
+        }
+    }
+
+    /// Blocks the current thread until the channel is empty.
+    ///
+    /// Returns `true` once every message sent so far has been received. Note that this only
+    /// guarantees the channel was momentarily empty: another sender may send a new message right
+    /// after this method returns, so it doesn't guarantee the channel stays empty.
+    ///
+    /// If all receivers get dropped while messages are still in the channel, those messages will
+    /// never be received and `false` is returned. Zero-capacity channels never hold messages, so
+    /// for them this method returns `true` immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     for _ in r.iter() {}
+    /// });
+    ///
+    /// assert!(s.wait_drained());
+    /// assert!(s.is_empty());
+    /// ```
+    pub fn wait_drained(&self) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.wait_empty(),
+            SenderFlavor::List(chan) => chan.wait_empty(),
+            SenderFlavor::Zero(_) => true,
+            SenderFlavor::Oneshot(chan) => chan.wait_empty(),
+            SenderFlavor::Heap(chan) => chan.wait_empty(),
+            SenderFlavor::Coalesce(chan) => chan.wait_empty(),
+        }
+    }
+
+    /// Creates a new receiver for the same channel.
+    ///
+    /// This is just like cloning a [`Receiver`] of the channel. If all receivers have already been
+    /// dropped, the channel is disconnected for good and `None` is returned.
+    ///
+    /// [`Receiver`]: struct.Receiver.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// let r2 = s.make_receiver().unwrap();
+    /// s.send(1).unwrap();
+    /// assert_eq!(r2.recv(), Ok(1));
+    ///
+    /// drop(r);
+    /// drop(r2);
+    /// assert!(s.make_receiver().is_none());
+    /// ```
+    pub fn make_receiver(&self) -> Option<Receiver<T>> {
+        let flavor = match &self.flavor {
+            SenderFlavor::Array(chan) => ReceiverFlavor::Array(chan.acquire_receiver()?),
+            SenderFlavor::List(chan) => ReceiverFlavor::List(chan.acquire_receiver()?),
+            SenderFlavor::Zero(chan) => ReceiverFlavor::Zero(chan.acquire_receiver()?),
+            SenderFlavor::Oneshot(chan) => ReceiverFlavor::Oneshot(chan.acquire_receiver()?),
+            SenderFlavor::Heap(chan) => ReceiverFlavor::Heap(chan.acquire_receiver()?),
+            SenderFlavor::Coalesce(chan) => ReceiverFlavor::Coalesce(chan.acquire_receiver()?),
+        };
+        Some(Receiver { flavor })
+    }
+
+    /// Returns a sender that slows down as the channel fills up.
+    ///
+    /// Whenever the channel holds more than `high_watermark` messages, each blocking send
+    /// operation on the returned sender first sleeps for a short duration proportional to the
+    /// number of messages above the watermark. This smooths out bursts and gives a slow consumer
+    /// a chance to catch up before the channel becomes full. The delay is capped at 10
+    /// milliseconds by default, which can be changed with [`ThrottledSender::with_max_delay`].
+    ///
+    /// This is a cooperative, best-effort soft limit, not a guarantee. The number of messages is
+    /// only sampled before sleeping, other senders are not throttled, and a bounded channel still
+    /// blocks senders once it is full.
+    ///
+    /// [`ThrottledSender::with_max_delay`]: struct.ThrottledSender.html#method.with_max_delay
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::bounded;
+    ///
+    /// let (s, r) = bounded(100);
+    /// let s = s.throttled(50);
+    ///
+    /// // The first 50 messages are sent without delay.
+    /// for i in 0..50 {
+    ///     s.send(i).unwrap();
+    /// }
+    ///
+    /// // From now on, every send sleeps a little longer than the previous one.
+    /// s.send(50).unwrap();
+    /// assert_eq!(r.len(), 51);
+    /// ```
+    pub fn throttled(&self, high_watermark: usize) -> ThrottledSender<T> {
+        ThrottledSender::new(self.clone(), high_watermark)
+    }
+
+    /// Checks the length of the channel against the watermark set by
+    /// [`Receiver::set_high_watermark`] after a successful send operation.
+    ///
+    /// [`Receiver::set_high_watermark`]: struct.Receiver.html#method.set_high_watermark
+    fn observe_len(&self) {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.observe_watermark(|| chan.len()),
+            SenderFlavor::List(chan) => chan.observe_watermark(|| chan.len()),
+            SenderFlavor::Zero(chan) => chan.observe_watermark(|| chan.len()),
+            SenderFlavor::Oneshot(chan) => chan.observe_watermark(|| chan.len()),
+            SenderFlavor::Heap(chan) => chan.observe_watermark(|| chan.len()),
+            SenderFlavor::Coalesce(chan) => chan.observe_watermark(|| chan.len()),
+        }
+    }
+
+    /// Waits until it is the turn of a send operation in a replayed schedule.
+    ///
+    /// Returns `false` if the deadline is reached first. The deadline is only computed if the
+    /// operation has to wait, so that operations don't read the clock otherwise.
+    #[cfg(feature = "replay")]
+    fn replay_turn<F>(&self, deadline: F) -> bool
+    where
+        F: FnOnce() -> Option<Instant>,
+    {
+        replay::wait_turn(self.replay_id(), ReplayOp::Send, deadline)
+    }
+
+    /// Waits until it is the turn of a send operation in a replayed schedule.
+    #[cfg(not(feature = "replay"))]
+    fn replay_turn<F>(&self, _deadline: F) -> bool
+    where
+        F: FnOnce() -> Option<Instant>,
+    {
+        true
+    }
+
+    /// Completes a send operation in a recording or replay, at the point where it succeeds or fails.
+    #[cfg(feature = "replay")]
+    fn replay_complete(&self, success: bool) {
+        replay::complete(self.replay_id(), ReplayOp::Send, success)
+    }
+
+    /// Completes a send operation in a recording or replay, at the point where it succeeds or fails.
+    #[cfg(not(feature = "replay"))]
+    fn replay_complete(&self, _success: bool) {}
+
+    /// Returns the identifier of the channel used for recording and replaying operations.
+    #[cfg(feature = "replay")]
+    fn replay_id(&self) -> Option<usize> {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.replay_id(),
+            SenderFlavor::List(chan) => chan.replay_id(),
+            SenderFlavor::Zero(chan) => chan.replay_id(),
+            SenderFlavor::Oneshot(chan) => chan.replay_id(),
+            SenderFlavor::Heap(chan) => chan.replay_id(),
+            SenderFlavor::Coalesce(chan) => chan.replay_id(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            match &self.flavor {
+                SenderFlavor::Array(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::List(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::Zero(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::Oneshot(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::Heap(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::Coalesce(chan) => chan.release(|c| c.disconnect()),
+            }
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        let flavor = match &self.flavor {
+            SenderFlavor::Array(chan) => SenderFlavor::Array(chan.acquire()),
+            SenderFlavor::List(chan) => SenderFlavor::List(chan.acquire()),
+            SenderFlavor::Zero(chan) => SenderFlavor::Zero(chan.acquire()),
+            SenderFlavor::Oneshot(chan) => SenderFlavor::Oneshot(chan.acquire()),
+            SenderFlavor::Heap(chan) => SenderFlavor::Heap(chan.acquire()),
+            SenderFlavor::Coalesce(chan) => SenderFlavor::Coalesce(chan.acquire()),
+        };
+
+        Sender { flavor }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "Sender {{ name: {:?}, .. }}", name),
+            None => f.pad("Sender { .. }"),
+        }
+    }
+}
+
+/// The sending side of a channel created by [`bounded_priority`].
+///
+/// Messages can be sent either normally or as urgent messages, which are received before any
+/// message sent normally.
+///
+/// [`bounded_priority`]: fn.bounded_priority.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::bounded_priority;
+///
+/// let (s, r) = bounded_priority(100);
+/// let s2 = s.clone();
+///
+/// for i in 0..3 {
+///     s.send(i).unwrap();
+/// }
+///
+/// thread::spawn(move || s2.send_urgent(-1).unwrap()).join().unwrap();
+///
+/// assert_eq!(r.iter().take(4).collect::<Vec<_>>(), [-1, 0, 1, 2]);
+/// ```
+pub struct PrioritySender<T> {
+    chan: counter::Sender<flavors::priority::Channel<T>>,
+}
+
+unsafe impl<T: Send> Send for PrioritySender<T> {}
+unsafe impl<T: Send> Sync for PrioritySender<T> {}
+
+impl<T> PrioritySender<T> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// The message is queued after all messages already in the channel. This method will either
+    /// send a message into the channel immediately or return an error if the channel is full or
+    /// disconnected. The returned error contains the original message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded_priority, TrySendError};
+    ///
+    /// let (s, r) = bounded_priority(1);
+    ///
+    /// assert_eq!(s.try_send(1), Ok(()));
+    /// assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.try_send(3), Err(TrySendError::Disconnected(3)));
+    /// ```
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.chan.try_send(msg, false).map(|()| self.observe_len())
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// The message is queued after all messages already in the channel. If the channel is full
+    /// and not disconnected, this call will block until the send operation can proceed. If the
+    /// channel becomes disconnected, this call will wake up and return an error. The returned
+    /// error contains the original message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded_priority, SendError};
+    ///
+    /// let (s, r) = bounded_priority(1);
+    /// assert_eq!(s.send(1), Ok(()));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.send(2), Err(SendError(2)));
+    /// ```
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.chan
+            .send(msg, false, None)
+            .map(|()| self.observe_len())
+            .map_err(|err| match err {
+                SendTimeoutError::Disconnected(msg) => SendError(msg),
+                SendTimeoutError::Timeout(_) => unreachable!(),
+            })
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    ///
+    /// This is like [`send`], except the call gives up with a timeout error once `timeout` has
+    /// elapsed.
+    ///
+    /// [`send`]: struct.PrioritySender.html#method.send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{bounded_priority, SendTimeoutError};
+    ///
+    /// let (s, _r) = bounded_priority(1);
+    /// s.send(1).unwrap();
+    ///
+    /// assert_eq!(
+    ///     s.send_timeout(2, Duration::from_millis(100)),
+    ///     Err(SendTimeoutError::Timeout(2)),
+    /// );
+    /// ```
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        let deadline = Instant::now() + timeout;
+        self.chan
+            .send(msg, false, Some(deadline))
+            .map(|()| self.observe_len())
+    }
+
+    /// Attempts to send an urgent message into the channel without blocking.
+    ///
+    /// The message is queued before all normal messages in the channel, but after urgent
+    /// messages that were sent earlier. Errors are reported just like in [`try_send`].
+    ///
+    /// [`try_send`]: struct.PrioritySender.html#method.try_send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded_priority, TrySendError};
+    ///
+    /// let (s, r) = bounded_priority(2);
+    ///
+    /// assert_eq!(s.try_send(1), Ok(()));
+    /// assert_eq!(s.try_send_urgent(2), Ok(()));
+    /// assert_eq!(s.try_send_urgent(3), Err(TrySendError::Full(3)));
+    ///
+    /// assert_eq!(r.try_recv(), Ok(2));
+    /// assert_eq!(r.try_recv(), Ok(1));
+    /// ```
+    pub fn try_send_urgent(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.chan.try_send(msg, true).map(|()| self.observe_len())
+    }
+
+    /// Blocks the current thread until an urgent message is sent or the channel is disconnected.
+    ///
+    /// The message is queued before all normal messages in the channel, but after urgent
+    /// messages that were sent earlier. Blocking and errors work just like in [`send`].
+    ///
+    /// [`send`]: struct.PrioritySender.html#method.send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::bounded_priority;
+    ///
+    /// let (s, r) = bounded_priority(10);
+    ///
+    /// s.send(1).unwrap();
+    /// s.send_urgent(2).unwrap();
+    /// s.send_urgent(3).unwrap();
+    ///
+    /// assert_eq!(r.try_iter().collect::<Vec<_>>(), [2, 3, 1]);
+    /// ```
+    pub fn send_urgent(&self, msg: T) -> Result<(), SendError<T>> {
+        self.chan
+            .send(msg, true, None)
+            .map(|()| self.observe_len())
+            .map_err(|err| match err {
+                SendTimeoutError::Disconnected(msg) => SendError(msg),
+                SendTimeoutError::Timeout(_) => unreachable!(),
+            })
+    }
+
+    /// Waits for an urgent message to be sent into the channel, but only for a limited time.
+    ///
+    /// This is like [`send_urgent`], except the call gives up with a timeout error once
+    /// `timeout` has elapsed.
+    ///
+    /// [`send_urgent`]: struct.PrioritySender.html#method.send_urgent
+    pub fn send_urgent_timeout(
+        &self,
+        msg: T,
+        timeout: Duration,
+    ) -> Result<(), SendTimeoutError<T>> {
+        let deadline = Instant::now() + timeout;
+        self.chan
+            .send(msg, true, Some(deadline))
+            .map(|()| self.observe_len())
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.chan.is_empty()
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.chan.is_full()
+    }
+
+    /// Returns the number of messages in the channel, both urgent and normal.
+    pub fn len(&self) -> usize {
+        self.chan.len()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        self.chan.capacity()
+    }
+
+    /// Checks the length of the channel against the watermark after a successful send operation.
+    fn observe_len(&self) {
+        self.chan.observe_watermark(|| self.chan.len())
+    }
+}
+
+impl<T> Drop for PrioritySender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.chan.release(|c| c.disconnect());
+        }
+    }
+}
+
+impl<T> Clone for PrioritySender<T> {
+    fn clone(&self) -> Self {
+        PrioritySender {
+            chan: self.chan.acquire(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for PrioritySender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("PrioritySender { .. }")
+    }
+}
+
+/// The receiving side of a channel.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::unbounded;
+///
+/// let (s, r) = unbounded();
+///
+/// thread::spawn(move || {
+///     s.send(1);
+///     thread::sleep(Duration::from_secs(1));
+///     s.send(2);
+/// });
+///
+/// assert_eq!(r.recv(), Ok(1)); // Received immediately.
+/// assert_eq!(r.recv(), Ok(2)); // Received after 1 second.
+/// ```
+pub struct Receiver<T> {
+    flavor: ReceiverFlavor<T>,
+}
+
+/// Receiver flavors.
+enum ReceiverFlavor<T> {
+    /// Bounded channel based on a preallocated array.
+    Array(counter::Receiver<flavors::array::Channel<T>>),
+
+    /// Unbounded channel implemented as a linked list.
+    List(counter::Receiver<flavors::list::Channel<T>>),
+
+    /// Zero-capacity channel.
+    Zero(counter::Receiver<flavors::zero::Channel<T>>),
+
+    /// Single-use channel that carries at most one message.
+    Oneshot(counter::Receiver<flavors::oneshot::Channel<T>>),
+
+    /// Bounded channel with a priority lane for urgent messages.
+    Priority(counter::Receiver<flavors::priority::Channel<T>>),
+
+    /// Bounded channel that receives messages in priority order.
+    Heap(counter::Receiver<flavors::heap::Channel<T>>),
+
+    /// Unbounded channel that keeps only the latest message for each key.
+    Coalesce(counter::Receiver<flavors::coalesce::Channel<T>>),
+
+    /// The after flavor.
+    After(Arc<flavors::after::Channel>),
+
+    /// The tick flavor.
+    Tick(Arc<flavors::tick::Channel>),
+
+    /// The never flavor.
+    Never(flavors::never::Channel<T>),
+
+    /// The ready flavor.
+    Ready(Arc<flavors::ready::Channel<T>>),
+
+    /// The merge flavor.
+    Merge(Arc<flavors::merge::Channel<T>>),
+}
+
+unsafe impl<T: Send> Send for Receiver<T> {}
+unsafe impl<T: Send> Sync for Receiver<T> {}
+
+impl<T> UnwindSafe for Receiver<T> {}
+impl<T> RefUnwindSafe for Receiver<T> {}
+
+impl<T> Receiver<T> {
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// This method will either receive a message from the channel immediately or return an error
+    /// if the channel is empty.
+    ///
+    /// If called on a zero-capacity channel, this method will receive a message only if there
+    /// happens to be a send operation on the other side of the channel at the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, TryRecvError};
+    ///
+    /// let (s, r) = unbounded();
+    /// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// s.send(5).unwrap();
+    /// drop(s);
+    ///
+    /// assert_eq!(r.try_recv(), Ok(5));
+    /// assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    /// ```
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        if !self.replay_turn(|| Some(Instant::now())) {
+            return Err(TryRecvError::Empty);
         }
-        .map_err(|err| match err {
-            SendTimeoutError::Disconnected(msg) => SendError(msg),
-            SendTimeoutError::Timeout(_) => unreachable!(),
-        })
+        let res = match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.try_recv(),
+            ReceiverFlavor::List(chan) => chan.try_recv(),
+            ReceiverFlavor::Zero(chan) => chan.try_recv(),
+            ReceiverFlavor::Oneshot(chan) => chan.try_recv(),
+            ReceiverFlavor::Heap(chan) => chan.try_recv(),
+            ReceiverFlavor::Coalesce(chan) => chan.try_recv(),
+            ReceiverFlavor::Priority(chan) => chan.try_recv(),
+            ReceiverFlavor::After(chan) => {
+                let msg = chan.try_recv();
+                unsafe {
+                    mem::transmute_copy::<Result<Instant, TryRecvError>, Result<T, TryRecvError>>(
+                        &msg,
+                    )
+                }
+            }
+            ReceiverFlavor::Tick(chan) => {
+                let msg = chan.try_recv();
+                unsafe {
+                    mem::transmute_copy::<Result<Instant, TryRecvError>, Result<T, TryRecvError>>(
+                        &msg,
+                    )
+                }
+            }
+            ReceiverFlavor::Never(chan) => chan.try_recv(),
+            ReceiverFlavor::Ready(chan) => chan.try_recv(),
+            ReceiverFlavor::Merge(chan) => chan.try_recv(),
+        };
+        if res.is_ok() {
+            self.mark_received();
+        }
+        #[cfg(all(unix, feature = "os_poll"))]
+        {
+            if let Err(TryRecvError::Empty) = res {
+                self.rearm_ready_fds();
+            }
+        }
+        self.replay_complete(res.is_ok());
+        res
     }
 
-    /// Waits for a message to be sent into the channel, but only for a limited time.
+    /// Attempts to receive a message without blocking, returning the outcome as a [`RecvState`].
     ///
-    /// If the channel is full and not disconnected, this call will block until the send operation
-    /// can proceed or the operation times out. If the channel becomes disconnected, this call will
-    /// wake up and return an error. The returned error contains the original message.
+    /// This is the same as [`try_recv`], but with the error flattened into the returned enum,
+    /// which makes for shorter match arms in polling loops.
     ///
-    /// If called on a zero-capacity channel, this method will wait for a receive operation to
-    /// appear on the other side of the channel.
+    /// [`RecvState`]: enum.RecvState.html
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, RecvState};
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// drop(s);
+    ///
+    /// let mut sum = 0;
+    /// loop {
+    ///     match r.poll() {
+    ///         RecvState::Ready(x) => sum += x,
+    ///         RecvState::Empty => continue,
+    ///         RecvState::Disconnected => break,
+    ///     }
+    /// }
+    /// assert_eq!(sum, 3);
+    /// ```
+    pub fn poll(&self) -> RecvState<T> {
+        match self.try_recv() {
+            Ok(msg) => RecvState::Ready(msg),
+            Err(TryRecvError::Empty) => RecvState::Empty,
+            Err(TryRecvError::Disconnected) => RecvState::Disconnected,
+        }
+    }
+
+    /// Makes a single attempt at taking a message, for work-stealing schedulers.
+    ///
+    /// This is like [`try_recv`], except that it gives up as soon as it runs into another thread
+    /// instead of waiting for it: if it loses the race for a message to another receiver, a
+    /// sender is still writing the next message, or another receiver has locked the front of the
+    /// channel to inspect it, [`Steal::Retry`] is returned. An idle worker can then move on to
+    /// another worker's channel rather than contend for this one. [`Steal::Empty`] is returned if
+    /// the channel is empty, whether it is disconnected or not, if the front is locked and the
+    /// channel is disconnected, or if the next message is in a slot that is reserved but not
+    /// filled yet.
+    ///
+    /// Messages are stolen from the front of the channel, in the same order [`try_recv`] takes
+    /// them, not from the back as in a Chase-Lev deque. Any number of threads may send into and
+    /// receive from a channel at either end, and a receiver claims a slot before reading it, so
+    /// taking the newest message would race with receivers catching up to it.
+    ///
+    /// Only bounded channels implement stealing with a single attempt. For other channels, this
+    /// is the same as [`try_recv`] and never returns [`Steal::Retry`].
+    ///
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    /// [`Steal::Retry`]: enum.Steal.html#variant.Retry
+    /// [`Steal::Empty`]: enum.Steal.html#variant.Empty
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, Steal};
+    ///
+    /// let (s, r) = bounded(4);
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    ///
+    /// let mut stolen = Vec::new();
+    /// loop {
+    ///     match r.steal() {
+    ///         Steal::Success(x) => stolen.push(x),
+    ///         Steal::Empty => break,
+    ///         Steal::Retry => {}
+    ///     }
+    /// }
+    /// assert_eq!(stolen, [1, 2]);
+    /// ```
+    pub fn steal(&self) -> Steal<T> {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => {
+                if !self.replay_turn(|| Some(Instant::now())) {
+                    return Steal::Empty;
+                }
+                let res = chan.steal();
+                if let Steal::Success(_) = res {
+                    self.mark_received();
+                    self.replay_complete(true);
+                } else {
+                    self.replay_complete(false);
+                }
+                res
+            }
+            _ => match self.try_recv() {
+                Ok(msg) => Steal::Success(msg),
+                Err(_) => Steal::Empty,
+            },
+        }
+    }
+
+    /// Blocks the current thread until a message is received or the channel is empty and
+    /// disconnected.
+    ///
+    /// If the channel is empty and not disconnected, this call will block until the receive
+    /// operation can proceed. If the channel is empty and becomes disconnected, this call will
+    /// wake up and return an error.
+    ///
+    /// If called on a zero-capacity channel, this method will wait for a send operation to appear
+    /// on the other side of the channel.
     ///
     /// # Examples
     ///
     /// ```
     /// use std::thread;
     /// use std::time::Duration;
-    /// use crossbeam_channel::{bounded, SendTimeoutError};
+    /// use crossbeam_channel::{unbounded, RecvError};
     ///
-    /// let (s, r) = bounded(0);
+    /// let (s, r) = unbounded();
     ///
     /// thread::spawn(move || {
     ///     thread::sleep(Duration::from_secs(1));
-    ///     assert_eq!(r.recv(), Ok(2));
-    ///     drop(r);
+    ///     s.send(5).unwrap();
+    ///     drop(s);
+    /// });
+    ///
+    /// assert_eq!(r.recv(), Ok(5));
+    /// assert_eq!(r.recv(), Err(RecvError));
+    /// ```
+    pub fn recv(&self) -> Result<T, RecvError> {
+        trace_span!("recv", name = self.name().unwrap_or(""), len = self.len());
+        self.replay_turn(|| None);
+        let res = match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.recv(None),
+            ReceiverFlavor::List(chan) => chan.recv(None),
+            ReceiverFlavor::Zero(chan) => chan.recv(None),
+            ReceiverFlavor::Oneshot(chan) => chan.recv(None),
+            ReceiverFlavor::Heap(chan) => chan.recv(None),
+            ReceiverFlavor::Coalesce(chan) => chan.recv(None),
+            ReceiverFlavor::Priority(chan) => chan.recv(None),
+            ReceiverFlavor::After(chan) => {
+                let msg = chan.recv(None);
+                unsafe {
+                    mem::transmute_copy::<
+                        Result<Instant, RecvTimeoutError>,
+                        Result<T, RecvTimeoutError>,
+                    >(&msg)
+                }
+            }
+            ReceiverFlavor::Tick(chan) => {
+                let msg = chan.recv(None);
+                unsafe {
+                    mem::transmute_copy::<
+                        Result<Instant, RecvTimeoutError>,
+                        Result<T, RecvTimeoutError>,
+                    >(&msg)
+                }
+            }
+            ReceiverFlavor::Never(chan) => chan.recv(None),
+            ReceiverFlavor::Ready(chan) => chan.recv(None),
+            ReceiverFlavor::Merge(chan) => chan.recv(None),
+        };
+        if res.is_ok() {
+            self.mark_received();
+        }
+        self.replay_complete(res.is_ok());
+        res.map_err(|_| RecvError)
+    }
+
+    /// Blocks the current thread until a message is received or the channel is empty and
+    /// disconnected, and reports whether the thread had to wait.
+    ///
+    /// This method behaves just like [`recv`], except that the received message is paired with a
+    /// flag that is `true` if the current thread had to block waiting for a message, and `false`
+    /// if a message was available right away or after a short spin. Frequent waiting means the
+    /// consumer is starved, which a scheduler can use to balance producers and consumers.
+    ///
+    /// [`recv`]: struct.Receiver.html#method.recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(r.recv_reporting(), Ok((1, false)));
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     s.send(2).unwrap();
+    /// });
+    ///
+    /// assert_eq!(r.recv_reporting(), Ok((2, true)));
+    /// ```
+    pub fn recv_reporting(&self) -> Result<(T, bool), RecvError> {
+        let waits = Context::wait_count();
+        let msg = self.recv()?;
+        Ok((msg, Context::wait_count() != waits))
+    }
+
+    /// Blocks the current thread until a message is received or the channel is empty and
+    /// disconnected, and reports whether the received message was the last one in the channel.
+    ///
+    /// This method behaves just like [`recv`], except that the received message is paired with a
+    /// flag that is `true` if the channel was empty right after the message was taken out. A
+    /// consumer can use it to process messages in batches and go idle exactly when it has drained
+    /// the channel, without a separate call to [`is_empty`] that other receivers could race with.
+    ///
+    /// The flag is only a snapshot: a message sent concurrently may arrive right after it is
+    /// taken. For bounded channels, it is checked right when the message is claimed, at the cost of
+    /// a single extra load. For other flavors, it is equivalent to calling [`is_empty`] right after
+    /// [`recv`].
+    ///
+    /// [`recv`]: struct.Receiver.html#method.recv
+    /// [`is_empty`]: struct.Receiver.html#method.is_empty
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::bounded;
+    ///
+    /// let (s, r) = bounded(4);
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    ///
+    /// assert_eq!(r.recv_with_empty(), Ok((1, false)));
+    /// assert_eq!(r.recv_with_empty(), Ok((2, true)));
+    /// ```
+    pub fn recv_with_empty(&self) -> Result<(T, bool), RecvError> {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => {
+                self.replay_turn(|| None);
+                let res = chan.recv_with_empty(None).map_err(|_| RecvError);
+                if res.is_ok() {
+                    self.mark_received();
+                }
+                self.replay_complete(res.is_ok());
+                res
+            }
+            _ => {
+                let msg = self.recv()?;
+                Ok((msg, self.is_empty()))
+            }
+        }
+    }
+
+    /// Blocks the current thread until a message is received, and moves it into `arena`.
+    ///
+    /// This method behaves just like [`recv`], except that the received message is placed into a
+    /// [`bumpalo`] arena and a mutable reference to it is returned. The message lives as long as
+    /// the arena, and all messages received this way are freed together when the arena is reset
+    /// or dropped. Combined with [`try_iter`] or [`drain`], this makes it cheap to process a batch
+    /// of messages without moving each one around.
+    ///
+    /// Note that `bumpalo` never runs destructors, so messages that own resources, such as heap
+    /// allocations or file handles, leak them unless the caller drops them in place.
+    ///
+    /// This method is available with the `arena` feature.
+    ///
+    /// [`recv`]: struct.Receiver.html#method.recv
+    /// [`try_iter`]: struct.Receiver.html#method.try_iter
+    /// [`drain`]: struct.Receiver.html#method.drain
+    /// [`bumpalo`]: https://docs.rs/bumpalo
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate bumpalo;
+    /// # extern crate crossbeam_channel;
+    /// # fn main() {
+    /// use bumpalo::Bump;
+    /// use crossbeam_channel::bounded;
+    ///
+    /// let (s, r) = bounded(4);
+    /// let arena = Bump::new();
+    ///
+    /// s.send([7u8; 64]).unwrap();
+    /// let msg = r.recv_into_arena(&arena).unwrap();
+    /// msg[0] = 1;
+    /// assert_eq!(msg[..2], [1, 7]);
+    /// # }
+    /// ```
+    #[cfg(feature = "arena")]
+    pub fn recv_into_arena<'b>(&self, arena: &'b Bump) -> Result<&'b mut T, RecvError> {
+        self.recv().map(|msg| arena.alloc(msg))
+    }
+
+    /// Attempts to receive a message, retrying a limited number of times without blocking.
+    ///
+    /// This is an advanced method for systems that need a bound on the latency of each receive
+    /// operation. It never parks the thread. Instead, it makes one attempt to receive, like
+    /// [`try_recv`], followed by at most `max_cycles` retries, backing off between attempts by
+    /// spinning and then yielding. If no message was received after that,
+    /// [`RecvSpinError::Exhausted`] is returned to signal that the operation would block.
+    ///
+    /// With `max_cycles` of zero, this method makes a single attempt, just like [`try_recv`].
+    ///
+    /// Under contention, the budget may run out while a message is in the channel but other
+    /// receivers keep grabbing it first, so an `Exhausted` error doesn't mean the channel was
+    /// empty.
+    ///
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    /// [`RecvSpinError::Exhausted`]: enum.RecvSpinError.html#variant.Exhausted
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, RecvSpinError};
+    ///
+    /// let (s, r) = unbounded();
+    /// assert_eq!(r.recv_bounded_spins(10), Err(RecvSpinError::Exhausted));
+    ///
+    /// s.send(5).unwrap();
+    /// assert_eq!(r.recv_bounded_spins(10), Ok(5));
+    ///
+    /// drop(s);
+    /// assert_eq!(r.recv_bounded_spins(10), Err(RecvSpinError::Disconnected));
+    /// ```
+    pub fn recv_bounded_spins(&self, max_cycles: usize) -> Result<T, RecvSpinError> {
+        let backoff = Backoff::new();
+        let mut cycles = 0;
+
+        loop {
+            match self.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Disconnected) => return Err(RecvSpinError::Disconnected),
+                Err(TryRecvError::Empty) if cycles < max_cycles => {
+                    cycles += 1;
+                    backoff.snooze();
+                }
+                Err(TryRecvError::Empty) => return Err(RecvSpinError::Exhausted),
+            }
+        }
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    ///
+    /// If the channel is empty and not disconnected, this call will block until the receive
+    /// operation can proceed or the operation times out. If the channel is empty and becomes
+    /// disconnected, this call will wake up and return an error.
+    ///
+    /// If called on a zero-capacity channel, this method will wait for a send operation to appear
+    /// on the other side of the channel.
+    ///
+    /// If a message is ready, it is received right away, without reading the current time, unless
+    /// [`idle_duration`] has been called on the channel.
+    ///
+    /// A zero timeout makes this method behave just like [`try_recv`]: it makes exactly one
+    /// attempt to receive a message and returns a timeout error if none is ready, without spinning
+    /// or blocking. In particular, it never waits for a send operation on a zero-capacity channel.
+    ///
+    /// [`idle_duration`]: struct.Receiver.html#method.idle_duration
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, RecvTimeoutError};
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     s.send(5).unwrap();
+    ///     drop(s);
     /// });
     ///
     /// assert_eq!(
-    ///     s.send_timeout(1, Duration::from_millis(500)),
-    ///     Err(SendTimeoutError::Timeout(1)),
+    ///     r.recv_timeout(Duration::from_millis(500)),
+    ///     Err(RecvTimeoutError::Timeout),
     /// );
     /// assert_eq!(
-    ///     s.send_timeout(2, Duration::from_secs(1)),
-    ///     Ok(()),
+    ///     r.recv_timeout(Duration::from_secs(1)),
+    ///     Ok(5),
     /// );
     /// assert_eq!(
-    ///     s.send_timeout(3, Duration::from_millis(500)),
-    ///     Err(SendTimeoutError::Disconnected(3)),
+    ///     r.recv_timeout(Duration::from_secs(1)),
+    ///     Err(RecvTimeoutError::Disconnected),
     /// );
     /// ```
-    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
-        let deadline = Instant::now() + timeout;
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        trace_span!(
+            "recv_timeout",
+            name = self.name().unwrap_or(""),
+            len = self.len(),
+            ?timeout
+        );
+        // A zero timeout has expired before we could wait, so make a single attempt.
+        if timeout == Duration::from_secs(0) {
+            return self.try_recv().map_err(|err| match err {
+                TryRecvError::Empty => RecvTimeoutError::Timeout,
+                TryRecvError::Disconnected => RecvTimeoutError::Disconnected,
+            });
+        }
 
-        match &self.flavor {
-            SenderFlavor::Array(chan) => chan.send(msg, Some(deadline)),
-            SenderFlavor::List(chan) => chan.send(msg, Some(deadline)),
-            SenderFlavor::Zero(chan) => chan.send(msg, Some(deadline)),
+        if !self.replay_turn(|| Some(Instant::now() + timeout)) {
+            return Err(RecvTimeoutError::Timeout);
+        }
+
+        // Try receiving a ready message first so that the clock is read only if we have to wait.
+        let res = match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.try_recv(),
+            ReceiverFlavor::List(chan) => chan.try_recv(),
+            ReceiverFlavor::Zero(chan) => chan.try_recv(),
+            ReceiverFlavor::Oneshot(chan) => chan.try_recv(),
+            ReceiverFlavor::Heap(chan) => chan.try_recv(),
+            ReceiverFlavor::Coalesce(chan) => chan.try_recv(),
+            ReceiverFlavor::Priority(chan) => chan.try_recv(),
+            _ => Err(TryRecvError::Empty),
+        };
+        match res {
+            Ok(msg) => {
+                self.mark_received();
+                self.replay_complete(true);
+                return Ok(msg);
+            }
+            Err(TryRecvError::Disconnected) => {
+                self.replay_complete(false);
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            Err(TryRecvError::Empty) => {}
         }
+
+        let res = self.recv_before(Instant::now() + timeout);
+        self.replay_complete(res.is_ok());
+        res
     }
 
-    /// Returns `true` if the channel is empty.
+    /// Waits for a message to be received from the channel, but only for a limited time, and
+    /// reports how late a timeout was.
     ///
-    /// Note: Zero-capacity channels are always empty.
+    /// This method behaves just like [`recv_timeout`], except that an error is paired with a
+    /// duration. On a timeout, it is the overshoot: the time between the deadline, which is the
+    /// moment of the call plus `timeout`, and the moment the thread actually returned. A parked
+    /// thread is only woken after the deadline when the OS scheduler gets around to it, so a
+    /// large overshoot points at scheduling delays rather than at the channel. On disconnection,
+    /// the duration is zero.
+    ///
+    /// [`recv_timeout`]: struct.Receiver.html#method.recv_timeout
     ///
     /// # Examples
     ///
     /// ```
-    /// use crossbeam_channel::unbounded;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, RecvTimeoutError};
     ///
-    /// let (s, r) = unbounded();
-    /// assert!(s.is_empty());
+    /// let (s, r) = unbounded::<i32>();
     ///
-    /// s.send(0).unwrap();
-    /// assert!(!s.is_empty());
+    /// match r.recv_timeout_detailed(Duration::from_millis(100)) {
+    ///     Err((RecvTimeoutError::Timeout, overshoot)) => {
+    ///         println!("woken up {:?} after the deadline", overshoot);
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    ///
+    /// drop(s);
+    /// assert_eq!(
+    ///     r.recv_timeout_detailed(Duration::from_millis(100)),
+    ///     Err((RecvTimeoutError::Disconnected, Duration::from_secs(0))),
+    /// );
     /// ```
-    pub fn is_empty(&self) -> bool {
-        match &self.flavor {
-            SenderFlavor::Array(chan) => chan.is_empty(),
-            SenderFlavor::List(chan) => chan.is_empty(),
-            SenderFlavor::Zero(chan) => chan.is_empty(),
-        }
+    pub fn recv_timeout_detailed(
+        &self,
+        timeout: Duration,
+    ) -> Result<T, (RecvTimeoutError, Duration)> {
+        let deadline = Instant::now() + timeout;
+
+        self.recv_timeout(timeout).map_err(|err| match err {
+            RecvTimeoutError::Timeout => {
+                let now = Instant::now();
+                let overshoot = if now > deadline {
+                    now - deadline
+                } else {
+                    Duration::from_secs(0)
+                };
+                (err, overshoot)
+            }
+            RecvTimeoutError::Disconnected => (err, Duration::from_secs(0)),
+        })
     }
 
-    /// Returns `true` if the channel is full.
+    /// Attempts to receive a message without blocking, registering a wakeup if there is none.
     ///
-    /// Note: Zero-capacity channels are always full.
+    /// This method is meant for driving the channel from an event loop instead of a blocked
+    /// thread. If a message is ready, it is returned as `Poll::Ready(Ok(msg))`. If the channel is
+    /// empty and disconnected, `Poll::Ready(Err(RecvError))` is returned. Otherwise, `register` is
+    /// stored in the channel and `Poll::Pending` is returned. The callback is invoked once, at the
+    /// point where a blocked receiver would have been unparked: when a message is sent into the
+    /// channel, a sender blocks on a zero-capacity channel, or the channel becomes disconnected. If
+    /// the channel becomes ready while the callback is being registered, it is invoked right away.
+    /// Wakeups may be spurious, so `poll_recv` should be called again after each one.
     ///
-    /// # Examples
+    /// The callback runs on the thread that made the channel ready, possibly while a lock inside
+    /// the channel is held. It must not use the channel and should only wake up the event loop,
+    /// e.g. by setting a flag or unparking a thread.
     ///
-    /// ```
-    /// use crossbeam_channel::bounded;
+    /// Only one registration should be pending at a time: call `poll_recv` again only after the
+    /// previous callback has been invoked, or after a message has been received. Every call that
+    /// returns `Poll::Pending` stores another callback, and all of them stay in the channel until
+    /// it is notified.
     ///
-    /// let (s, r) = bounded(1);
+    /// Channels created by [`after`], [`tick`], and [`never`] cannot notify callbacks, so
+    /// `register` is only invoked if they are ready at the time of the call.
     ///
-    /// assert!(!s.is_full());
-    /// s.send(0).unwrap();
-    /// assert!(s.is_full());
-    /// ```
-    pub fn is_full(&self) -> bool {
-        match &self.flavor {
-            SenderFlavor::Array(chan) => chan.is_full(),
-            SenderFlavor::List(chan) => chan.is_full(),
-            SenderFlavor::Zero(chan) => chan.is_full(),
-        }
-    }
-
-    /// Returns the number of messages in the channel.
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
     ///
     /// # Examples
     ///
     /// ```
-    /// use crossbeam_channel::unbounded;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    /// use crossbeam_channel::{unbounded, Poll};
     ///
     /// let (s, r) = unbounded();
-    /// assert_eq!(s.len(), 0);
+    /// let woken = Arc::new(AtomicBool::new(false));
+    ///
+    /// let w = woken.clone();
+    /// assert_eq!(r.poll_recv(move || w.store(true, Ordering::SeqCst)), Poll::Pending);
+    /// assert!(!woken.load(Ordering::SeqCst));
     ///
     /// s.send(1).unwrap();
-    /// s.send(2).unwrap();
-    /// assert_eq!(s.len(), 2);
+    /// assert!(woken.load(Ordering::SeqCst));
+    /// assert_eq!(r.poll_recv(|| {}), Poll::Ready(Ok(1)));
     /// ```
-    pub fn len(&self) -> usize {
-        match &self.flavor {
-            SenderFlavor::Array(chan) => chan.len(),
-            SenderFlavor::List(chan) => chan.len(),
-            SenderFlavor::Zero(chan) => chan.len(),
+    pub fn poll_recv<F>(&self, register: F) -> Poll<Result<T, RecvError>>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        match self.try_recv() {
+            Ok(msg) => return Poll::Ready(Ok(msg)),
+            Err(TryRecvError::Disconnected) => return Poll::Ready(Err(RecvError)),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        // The context is kept alive by the channel's waker until it is notified.
+        let cx = Context::with_callback(register);
+        let oper = Operation::from_context(&cx);
+
+        if SelectHandle::watch(self, oper, &cx) {
+            // The channel became ready in the meantime. Invoke the callback now unless a
+            // notification has already done so.
+            SelectHandle::unwatch(self, oper);
+            if cx.try_select(Selected::Aborted).is_ok() {
+                cx.unpark();
+            }
         }
+
+        Poll::Pending
     }
 
-    /// If the channel is bounded, returns its capacity.
+    /// Waits for a message to be received from the channel until the given deadline.
     ///
-    /// # Examples
+    /// Unlike [`recv_timeout`], the deadline is absolute, so it doesn't move forward when messages
+    /// keep arriving. Once the deadline is reached, this call returns a timeout error even if the
+    /// channel still has messages ready. This makes it easy to interleave receiving with periodic
+    /// work that must run at least once in a given period, no matter how busy the channel is.
     ///
-    /// ```
-    /// use crossbeam_channel::{bounded, unbounded};
+    /// If the channel is empty and not disconnected, this call will block until the receive
+    /// operation can proceed or the deadline is reached. If the channel is empty and becomes
+    /// disconnected, this call will wake up and return an error.
     ///
-    /// let (s, _) = unbounded::<i32>();
-    /// assert_eq!(s.capacity(), None);
+    /// [`recv_timeout`]: struct.Receiver.html#method.recv_timeout
     ///
-    /// let (s, _) = bounded::<i32>(5);
-    /// assert_eq!(s.capacity(), Some(5));
+    /// # Examples
     ///
-    /// let (s, _) = bounded::<i32>(0);
-    /// assert_eq!(s.capacity(), Some(0));
     /// ```
-    pub fn capacity(&self) -> Option<usize> {
-        match &self.flavor {
-            SenderFlavor::Array(chan) => chan.capacity(),
-            SenderFlavor::List(chan) => chan.capacity(),
-            SenderFlavor::Zero(chan) => chan.capacity(),
-        }
-    }
-
-    /// Returns true if senders send to the same channel.
-    ///
-    /// # Examples
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{unbounded, RecvTimeoutError};
     ///
-    /// ```rust
-    /// use crossbeam_channel::unbounded;
+    /// let (s, r) = unbounded();
     ///
-    /// let (s, _) = unbounded::<usize>();
+    /// // A steady stream of messages.
+    /// thread::spawn(move || {
+    ///     while s.send(()).is_ok() {
+    ///         thread::sleep(Duration::from_millis(1));
+    ///     }
+    /// });
     ///
-    /// let s2 = s.clone();
-    /// assert!(s.identical_to(&s2));
+    /// let period = Duration::from_millis(50);
+    /// let mut deadline = Instant::now() + period;
+    /// let mut ticks = 0;
     ///
-    /// let (s3, _) = unbounded();
-    /// assert!(!s.identical_to(&s3));
+    /// while ticks < 3 {
+    ///     match r.recv_until_deadline(deadline) {
+    ///         Ok(()) => {}
+    ///         Err(RecvTimeoutError::Timeout) => {
+    ///             // Periodic work runs even though messages keep arriving.
+    ///             ticks += 1;
+    ///             deadline += period;
+    ///         }
+    ///         Err(RecvTimeoutError::Disconnected) => unreachable!(),
+    ///     }
+    /// }
     /// ```
-    pub fn identical_to(&self, other: &Sender<T>) -> bool {
-        use self::SenderFlavor::*;
-        match (&self.flavor, &other.flavor) {
-            (Array(ref self_counter), Array(ref other_counter)) => self_counter == other_counter,
-            (List(ref self_counter), List(ref other_counter)) => self_counter == other_counter,
-            (Zero(ref self_counter), Zero(ref other_counter)) => self_counter == other_counter,
-            // Channels of different flavours are never equal.
-            _ => false,
-        }
-    }
-}
-
-impl<T> Drop for Sender<T> {
-    fn drop(&mut self) {
-        unsafe {
-            match &self.flavor {
-                SenderFlavor::Array(chan) => chan.release(|c| c.disconnect()),
-                SenderFlavor::List(chan) => chan.release(|c| c.disconnect()),
-                SenderFlavor::Zero(chan) => chan.release(|c| c.disconnect()),
-            }
+    pub fn recv_until_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        if Instant::now() >= deadline {
+            return Err(RecvTimeoutError::Timeout);
         }
+        self.recv_before(deadline)
     }
-}
-
-impl<T> Clone for Sender<T> {
-    fn clone(&self) -> Self {
-        let flavor = match &self.flavor {
-            SenderFlavor::Array(chan) => SenderFlavor::Array(chan.acquire()),
-            SenderFlavor::List(chan) => SenderFlavor::List(chan.acquire()),
-            SenderFlavor::Zero(chan) => SenderFlavor::Zero(chan.acquire()),
-        };
-
-        Sender { flavor }
-    }
-}
-
-impl<T> fmt::Debug for Sender<T> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.pad("Sender { .. }")
-    }
-}
-
-/// The receiving side of a channel.
-///
-/// # Examples
-///
-/// ```
-/// use std::thread;
-/// use std::time::Duration;
-/// use crossbeam_channel::unbounded;
-///
-/// let (s, r) = unbounded();
-///
-/// thread::spawn(move || {
-///     s.send(1);
-///     thread::sleep(Duration::from_secs(1));
-///     s.send(2);
-/// });
-///
-/// assert_eq!(r.recv(), Ok(1)); // Received immediately.
-/// assert_eq!(r.recv(), Ok(2)); // Received after 1 second.
-/// ```
-pub struct Receiver<T> {
-    flavor: ReceiverFlavor<T>,
-}
-
-/// Receiver flavors.
-enum ReceiverFlavor<T> {
-    /// Bounded channel based on a preallocated array.
-    Array(counter::Receiver<flavors::array::Channel<T>>),
-
-    /// Unbounded channel implemented as a linked list.
-    List(counter::Receiver<flavors::list::Channel<T>>),
-
-    /// Zero-capacity channel.
-    Zero(counter::Receiver<flavors::zero::Channel<T>>),
-
-    /// The after flavor.
-    After(Arc<flavors::after::Channel>),
 
-    /// The tick flavor.
-    Tick(Arc<flavors::tick::Channel>),
-
-    /// The never flavor.
-    Never(flavors::never::Channel<T>),
-}
-
-unsafe impl<T: Send> Send for Receiver<T> {}
-unsafe impl<T: Send> Sync for Receiver<T> {}
-
-impl<T> UnwindSafe for Receiver<T> {}
-impl<T> RefUnwindSafe for Receiver<T> {}
-
-impl<T> Receiver<T> {
-    /// Attempts to receive a message from the channel without blocking.
+    /// Waits for a message to be received from the channel until a deadline shared with other
+    /// threads.
     ///
-    /// This method will either receive a message from the channel immediately or return an error
-    /// if the channel is empty.
+    /// This is like [`recv_until_deadline`], except that a controller thread can move the deadline
+    /// through the [`AtomicInstant`] while this call is blocked. The deadline is loaded again every
+    /// time the thread wakes up, and the call returns a timeout error once the current deadline has
+    /// passed.
     ///
-    /// If called on a zero-capacity channel, this method will receive a message only if there
-    /// happens to be a send operation on the other side of the channel at the same time.
+    /// Changes to the deadline take effect with different latency:
+    ///
+    /// * Moving the deadline earlier with [`AtomicInstant::store`] wakes up the blocked thread, so
+    ///   a deadline moved into the past makes this call return promptly.
+    /// * Moving the deadline later doesn't wake anyone up. The blocked thread notices the new
+    ///   deadline when the previous one is reached, and then keeps waiting. Calling
+    ///   [`AtomicInstant::wake`] makes it notice right away.
+    ///
+    /// If the channel is empty and becomes disconnected, this call will wake up and return an
+    /// error.
+    ///
+    /// [`recv_until_deadline`]: struct.Receiver.html#method.recv_until_deadline
+    /// [`AtomicInstant`]: struct.AtomicInstant.html
+    /// [`AtomicInstant::store`]: struct.AtomicInstant.html#method.store
+    /// [`AtomicInstant::wake`]: struct.AtomicInstant.html#method.wake
     ///
     /// # Examples
     ///
     /// ```
-    /// use crossbeam_channel::{unbounded, TryRecvError};
+    /// use std::sync::Arc;
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{unbounded, AtomicInstant, RecvTimeoutError};
     ///
-    /// let (s, r) = unbounded();
-    /// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    /// let (_s, r) = unbounded::<i32>();
+    /// let deadline = Arc::new(AtomicInstant::new(Instant::now() + Duration::from_secs(60)));
     ///
-    /// s.send(5).unwrap();
-    /// drop(s);
+    /// let controller = deadline.clone();
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     // Cancel the wait by moving the deadline into the past.
+    ///     controller.store(Instant::now());
+    /// });
     ///
-    /// assert_eq!(r.try_recv(), Ok(5));
-    /// assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    /// assert_eq!(
+    ///     r.recv_with_shared_deadline(&deadline),
+    ///     Err(RecvTimeoutError::Timeout),
+    /// );
     /// ```
-    pub fn try_recv(&self) -> Result<T, TryRecvError> {
-        match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.try_recv(),
-            ReceiverFlavor::List(chan) => chan.try_recv(),
-            ReceiverFlavor::Zero(chan) => chan.try_recv(),
-            ReceiverFlavor::After(chan) => {
-                let msg = chan.try_recv();
-                unsafe {
-                    mem::transmute_copy::<Result<Instant, TryRecvError>, Result<T, TryRecvError>>(
-                        &msg,
-                    )
-                }
+    pub fn recv_with_shared_deadline(
+        &self,
+        deadline: &AtomicInstant,
+    ) -> Result<T, RecvTimeoutError> {
+        loop {
+            match self.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {}
             }
-            ReceiverFlavor::Tick(chan) => {
-                let msg = chan.try_recv();
-                unsafe {
-                    mem::transmute_copy::<Result<Instant, TryRecvError>, Result<T, TryRecvError>>(
-                        &msg,
-                    )
-                }
+
+            let current = deadline.load();
+            if Instant::now() >= current {
+                return Err(RecvTimeoutError::Timeout);
             }
-            ReceiverFlavor::Never(chan) => chan.try_recv(),
+
+            let mut token = Token::default();
+            Context::with(|cx| {
+                let oper = Operation::hook(&mut token);
+                let ready = SelectHandle::watch(self, oper, cx);
+                deadline.watch(oper, cx);
+
+                // Recheck after watching so that no message or deadline change is missed.
+                if ready || deadline.load() != current {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                let wake_at = match SelectHandle::deadline(self) {
+                    Some(d) if d < current => d,
+                    _ => current,
+                };
+                cx.wait_until(Some(wake_at));
+
+                deadline.unwatch(oper);
+                SelectHandle::unwatch(self, oper);
+            });
         }
     }
-
-    /// Blocks the current thread until a message is received or the channel is empty and
-    /// disconnected.
+
+    /// Blocks the current thread until a message is received or the token is cancelled.
     ///
-    /// If the channel is empty and not disconnected, this call will block until the receive
-    /// operation can proceed. If the channel is empty and becomes disconnected, this call will
+    /// If the channel is empty and not disconnected, this call will block until a message arrives
+    /// or [`Canceller::cancel`] is called on the token's canceller from another thread, which wakes
+    /// this call up right away. If the channel is empty and becomes disconnected, this call will
     /// wake up and return an error.
     ///
-    /// If called on a zero-capacity channel, this method will wait for a send operation to appear
-    /// on the other side of the channel.
+    /// A ready message is always received, even if the token has already been cancelled. Many
+    /// receive operations, on any channels, can wait on the same token.
+    ///
+    /// [`Canceller::cancel`]: struct.Canceller.html#method.cancel
     ///
     /// # Examples
     ///
     /// ```
     /// use std::thread;
     /// use std::time::Duration;
-    /// use crossbeam_channel::{unbounded, RecvError};
+    /// use crossbeam_channel::{cancel_token, unbounded, RecvCancelError};
     ///
     /// let (s, r) = unbounded();
+    /// let (token, canceller) = cancel_token();
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(r.recv_cancellable(&token), Ok(1));
     ///
     /// thread::spawn(move || {
-    ///     thread::sleep(Duration::from_secs(1));
-    ///     s.send(5).unwrap();
-    ///     drop(s);
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     canceller.cancel();
     /// });
     ///
-    /// assert_eq!(r.recv(), Ok(5));
-    /// assert_eq!(r.recv(), Err(RecvError));
+    /// assert_eq!(r.recv_cancellable(&token), Err(RecvCancelError::Cancelled));
     /// ```
-    pub fn recv(&self) -> Result<T, RecvError> {
-        match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.recv(None),
-            ReceiverFlavor::List(chan) => chan.recv(None),
-            ReceiverFlavor::Zero(chan) => chan.recv(None),
+    pub fn recv_cancellable(&self, token: &CancelToken) -> Result<T, RecvCancelError> {
+        loop {
+            match self.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Disconnected) => return Err(RecvCancelError::Disconnected),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            if token.is_cancelled() {
+                return Err(RecvCancelError::Cancelled);
+            }
+
+            let mut tok = Token::default();
+            Context::with(|cx| {
+                let oper = Operation::hook(&mut tok);
+                let ready = SelectHandle::watch(self, oper, cx);
+                token.watch(oper, cx);
+
+                // Recheck after watching so that no message or cancellation is missed.
+                if ready || token.is_cancelled() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                cx.wait_until(SelectHandle::deadline(self));
+
+                token.unwatch(oper);
+                SelectHandle::unwatch(self, oper);
+            });
+        }
+    }
+
+    /// Blocks until a message is received or the deadline is reached.
+    fn recv_before(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        let res = match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::List(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Zero(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Oneshot(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Heap(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Coalesce(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Priority(chan) => chan.recv(Some(deadline)),
             ReceiverFlavor::After(chan) => {
-                let msg = chan.recv(None);
+                let msg = chan.recv(Some(deadline));
                 unsafe {
                     mem::transmute_copy::<
                         Result<Instant, RecvTimeoutError>,
@@ -731,7 +3327,7 @@ impl<T> Receiver<T> {
                 }
             }
             ReceiverFlavor::Tick(chan) => {
-                let msg = chan.recv(None);
+                let msg = chan.recv(Some(deadline));
                 unsafe {
                     mem::transmute_copy::<
                         Result<Instant, RecvTimeoutError>,
@@ -739,75 +3335,182 @@ impl<T> Receiver<T> {
                     >(&msg)
                 }
             }
-            ReceiverFlavor::Never(chan) => chan.recv(None),
+            ReceiverFlavor::Never(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Ready(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Merge(chan) => chan.recv(Some(deadline)),
+        };
+        if res.is_ok() {
+            self.mark_received();
         }
-        .map_err(|_| RecvError)
+        res
     }
 
-    /// Waits for a message to be received from the channel, but only for a limited time.
+    /// Blocks the current thread until a message is received and passes a reference to it to `f`.
     ///
-    /// If the channel is empty and not disconnected, this call will block until the receive
-    /// operation can proceed or the operation times out. If the channel is empty and becomes
-    /// disconnected, this call will wake up and return an error.
+    /// This works just like [`recv`], except the message is not returned. Instead, `f` is called
+    /// with a reference to the message and its result is returned. The message is dropped after
+    /// `f` returns.
     ///
-    /// If called on a zero-capacity channel, this method will wait for a send operation to appear
-    /// on the other side of the channel.
+    /// If the channel is bounded, `f` inspects the message in place inside the channel's buffer,
+    /// which avoids moving large messages around. For other kinds of channels the message is
+    /// received first and then passed to `f`.
+    ///
+    /// If `f` panics, the message is still removed from the channel and dropped.
+    ///
+    /// [`recv`]: struct.Receiver.html#method.recv
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::thread;
-    /// use std::time::Duration;
-    /// use crossbeam_channel::{unbounded, RecvTimeoutError};
+    /// use crossbeam_channel::{bounded, RecvError};
     ///
-    /// let (s, r) = unbounded();
+    /// let (s, r) = bounded(1);
     ///
-    /// thread::spawn(move || {
-    ///     thread::sleep(Duration::from_secs(1));
-    ///     s.send(5).unwrap();
-    ///     drop(s);
-    /// });
+    /// s.send(vec![1, 2, 3]).unwrap();
+    /// assert_eq!(r.recv_with(|v| v.len()), Ok(3));
     ///
-    /// assert_eq!(
-    ///     r.recv_timeout(Duration::from_millis(500)),
-    ///     Err(RecvTimeoutError::Timeout),
-    /// );
-    /// assert_eq!(
-    ///     r.recv_timeout(Duration::from_secs(1)),
-    ///     Ok(5),
-    /// );
-    /// assert_eq!(
-    ///     r.recv_timeout(Duration::from_secs(1)),
-    ///     Err(RecvTimeoutError::Disconnected),
-    /// );
+    /// drop(s);
+    /// assert_eq!(r.recv_with(|v| v.len()), Err(RecvError));
     /// ```
-    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
-        let deadline = Instant::now() + timeout;
-
+    pub fn recv_with<F, R>(&self, f: F) -> Result<R, RecvError>
+    where
+        F: FnOnce(&T) -> R,
+    {
         match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.recv(Some(deadline)),
-            ReceiverFlavor::List(chan) => chan.recv(Some(deadline)),
-            ReceiverFlavor::Zero(chan) => chan.recv(Some(deadline)),
-            ReceiverFlavor::After(chan) => {
-                let msg = chan.recv(Some(deadline));
-                unsafe {
-                    mem::transmute_copy::<
-                        Result<Instant, RecvTimeoutError>,
-                        Result<T, RecvTimeoutError>,
-                    >(&msg)
-                }
-            }
-            ReceiverFlavor::Tick(chan) => {
-                let msg = chan.recv(Some(deadline));
-                unsafe {
-                    mem::transmute_copy::<
-                        Result<Instant, RecvTimeoutError>,
-                        Result<T, RecvTimeoutError>,
-                    >(&msg)
+            ReceiverFlavor::Array(chan) => {
+                let res = chan.recv_with(None, f).map_err(|_| RecvError);
+                if res.is_ok() {
+                    self.mark_received();
                 }
+                res
             }
-            ReceiverFlavor::Never(chan) => chan.recv(Some(deadline)),
+            _ => self.recv().map(|msg| f(&msg)),
+        }
+    }
+
+    /// Blocks the current thread until a message is available, then lets `f` decide whether to
+    /// receive it.
+    ///
+    /// `f` is called with a reference to the message at the front of the channel. If it returns
+    /// [`Decision::Take`], the message is removed from the channel and `Some` message is returned.
+    /// If it returns [`Decision::Leave`], the message stays at the front of the channel for the
+    /// next receive operation and `None` is returned. Unlike receiving a message and sending it
+    /// back, leaving it never reorders messages or races with senders.
+    ///
+    /// While `f` runs, the front of the channel is locked. Other receive operations see the
+    /// channel as empty until the lock is released: non-blocking ones such as [`try_recv`] fail,
+    /// and blocking ones wait without spinning. If the channel is disconnected, they return an
+    /// error instead, just like on an empty and disconnected channel. `f` should therefore be
+    /// quick. The lock is released even if `f` panics, in which case the message stays in the
+    /// channel.
+    ///
+    /// If the channel is empty and disconnected, this call will wake up and return an error. If
+    /// the channel was not created by [`bounded`] with a positive capacity or by [`unbounded`],
+    /// an error is returned right away, because other channels have no front to inspect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` makes a blocking receive operation on this channel, or blocks in a selection
+    /// with a receive operation on it, which would otherwise never complete.
+    ///
+    /// [`Decision::Take`]: enum.Decision.html#variant.Take
+    /// [`Decision::Leave`]: enum.Decision.html#variant.Leave
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    /// [`bounded`]: fn.bounded.html
+    /// [`unbounded`]: fn.unbounded.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Decision, PeekError};
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    ///
+    /// let even = |x: &i32| if x % 2 == 0 { Decision::Take } else { Decision::Leave };
+    /// assert_eq!(r.recv_peek_decide(even), Ok(None));
+    /// assert_eq!(r.recv_peek_decide(|_| Decision::Take), Ok(Some(1)));
+    /// assert_eq!(r.recv_peek_decide(even), Ok(Some(2)));
+    ///
+    /// drop(s);
+    /// assert_eq!(r.recv_peek_decide(even), Err(PeekError::Disconnected));
+    /// ```
+    pub fn recv_peek_decide<F>(&self, f: F) -> Result<Option<T>, PeekError>
+    where
+        F: FnOnce(&T) -> Decision,
+    {
+        let f = |msg: &T| f(msg) == Decision::Take;
+        let res = match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.recv_peek_decide(None, f),
+            ReceiverFlavor::List(chan) => chan.recv_peek_decide(None, f),
+            _ => return Err(PeekError::Unsupported),
+        };
+        if let Ok(Some(_)) = res {
+            self.mark_received();
         }
+        res.map_err(|_| PeekError::Disconnected)
+    }
+
+    /// Attempts to lock the front of the channel and inspect the messages buffered there in bulk.
+    ///
+    /// The returned [`Contiguous`] gives access to the run of messages at the front of the
+    /// channel, up to the point where the channel's ring buffer wraps around. A consumer can
+    /// process the whole run in a tight loop and then call [`Contiguous::consume`] to remove the
+    /// first `n` messages at once, which avoids the overhead of receiving messages one by one.
+    /// Call this method again to get the rest of the messages after the buffer wraps around.
+    ///
+    /// While the run exists, the front of the channel is locked. Other receive operations see the
+    /// channel as empty until the lock is released: non-blocking ones such as [`try_recv`] fail,
+    /// and blocking ones wait without spinning. If the channel is disconnected, they return an
+    /// error instead, just like on an empty and disconnected channel. The run should therefore be
+    /// consumed or dropped quickly, and it cannot be sent to another thread. Dropping it without
+    /// calling [`Contiguous::consume`] leaves all messages in the channel. A blocking receive
+    /// operation on this channel, including a blocking selection, panics while the current thread
+    /// holds the run, since it would never complete.
+    ///
+    /// Since there is only one front, a run that is held for long stalls every other receiver for
+    /// that long, and a run that is leaked with [`mem::forget`] stalls them until the channel is
+    /// disconnected. Messages behind the run can't be received in the meantime either.
+    ///
+    /// If there are no messages in the channel, or another receiver has locked the front of it,
+    /// this method returns an error, just like [`try_recv`]. If the channel was not created by
+    /// [`bounded`] with a positive capacity, an error is returned too, because other channels
+    /// don't keep their messages in a ring buffer.
+    ///
+    /// [`Contiguous`]: struct.Contiguous.html
+    /// [`Contiguous::consume`]: struct.Contiguous.html#method.consume
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    /// [`mem::forget`]: https://doc.rust-lang.org/std/mem/fn.forget.html
+    /// [`bounded`]: fn.bounded.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, TryPeekError};
+    ///
+    /// let (s, r) = bounded(4);
+    /// for i in 1..=3 {
+    ///     s.send(i).unwrap();
+    /// }
+    ///
+    /// let run = r.peek_contiguous().unwrap();
+    /// assert_eq!(run.len(), 3);
+    /// assert_eq!(run[0] + run[1] + run[2], 6);
+    /// run.consume(2);
+    ///
+    /// assert_eq!(r.try_recv(), Ok(3));
+    /// assert_eq!(r.peek_contiguous().err(), Some(TryPeekError::Empty));
+    /// ```
+    pub fn peek_contiguous(&self) -> Result<Contiguous<'_, T>, TryPeekError> {
+        let run = match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.lock_run()?,
+            _ => return Err(TryPeekError::Unsupported),
+        };
+        Ok(Contiguous {
+            receiver: self,
+            run,
+        })
     }
 
     /// Returns `true` if the channel is empty.
@@ -830,9 +3533,15 @@ impl<T> Receiver<T> {
             ReceiverFlavor::Array(chan) => chan.is_empty(),
             ReceiverFlavor::List(chan) => chan.is_empty(),
             ReceiverFlavor::Zero(chan) => chan.is_empty(),
+            ReceiverFlavor::Oneshot(chan) => chan.is_empty(),
+            ReceiverFlavor::Heap(chan) => chan.is_empty(),
+            ReceiverFlavor::Coalesce(chan) => chan.is_empty(),
+            ReceiverFlavor::Priority(chan) => chan.is_empty(),
             ReceiverFlavor::After(chan) => chan.is_empty(),
             ReceiverFlavor::Tick(chan) => chan.is_empty(),
             ReceiverFlavor::Never(chan) => chan.is_empty(),
+            ReceiverFlavor::Ready(chan) => chan.is_empty(),
+            ReceiverFlavor::Merge(chan) => chan.is_empty(),
         }
     }
 
@@ -856,9 +3565,15 @@ impl<T> Receiver<T> {
             ReceiverFlavor::Array(chan) => chan.is_full(),
             ReceiverFlavor::List(chan) => chan.is_full(),
             ReceiverFlavor::Zero(chan) => chan.is_full(),
+            ReceiverFlavor::Oneshot(chan) => chan.is_full(),
+            ReceiverFlavor::Heap(chan) => chan.is_full(),
+            ReceiverFlavor::Coalesce(chan) => chan.is_full(),
+            ReceiverFlavor::Priority(chan) => chan.is_full(),
             ReceiverFlavor::After(chan) => chan.is_full(),
             ReceiverFlavor::Tick(chan) => chan.is_full(),
             ReceiverFlavor::Never(chan) => chan.is_full(),
+            ReceiverFlavor::Ready(chan) => chan.is_full(),
+            ReceiverFlavor::Merge(chan) => chan.is_full(),
         }
     }
 
@@ -881,9 +3596,63 @@ impl<T> Receiver<T> {
             ReceiverFlavor::Array(chan) => chan.len(),
             ReceiverFlavor::List(chan) => chan.len(),
             ReceiverFlavor::Zero(chan) => chan.len(),
+            ReceiverFlavor::Oneshot(chan) => chan.len(),
+            ReceiverFlavor::Heap(chan) => chan.len(),
+            ReceiverFlavor::Coalesce(chan) => chan.len(),
+            ReceiverFlavor::Priority(chan) => chan.len(),
             ReceiverFlavor::After(chan) => chan.len(),
             ReceiverFlavor::Tick(chan) => chan.len(),
             ReceiverFlavor::Never(chan) => chan.len(),
+            ReceiverFlavor::Ready(chan) => chan.len(),
+            ReceiverFlavor::Merge(chan) => chan.len(),
+        }
+    }
+
+    /// Returns the number of messages in the channel, counted consistently at a single instant.
+    ///
+    /// [`len`] is cheap but racy: while messages are being sent and received concurrently, it may
+    /// count a message whose send operation is still writing it, or a message that is being taken
+    /// out right now. This method is meant for decisions that must neither over- nor undercount,
+    /// such as state transitions in flow control, while [`len`] stays the right choice for
+    /// monitoring. Likewise, `len_exact() == 0` is an exact check for emptiness, except under the
+    /// constant traffic described below.
+    ///
+    /// For bounded channels, this method counts only messages that are fully sent and not yet
+    /// claimed by a receiver, and the result is the exact length at some instant during the call.
+    /// It does so without blocking other threads: it scans the occupied part of the buffer and
+    /// starts over whenever a send or receive operation completes during the scan. The cost is
+    /// therefore linear in the number of messages, and under heavy contention the scan may be
+    /// retried many times, so avoid calling it in a hot loop. The number of scans is capped: if
+    /// operations keep completing during every scan, this method gives up and returns the result
+    /// of [`len`] instead, which may count messages that are still being written.
+    ///
+    /// For unbounded channels, the count is only best-effort: it is retried until it is based on a
+    /// consistent view of the channel, with the same cap, but operations in progress may still be
+    /// counted. For the
+    /// remaining flavors, which either have no concurrent operations in progress or take a lock,
+    /// this method is the same as [`len`]. A merged receiver adds up the exact lengths of its
+    /// inputs, which are not taken at the same instant.
+    ///
+    /// [`len`]: struct.Receiver.html#method.len
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::bounded;
+    ///
+    /// let (s, r) = bounded(4);
+    /// assert_eq!(r.len_exact(), 0);
+    ///
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// assert_eq!(r.len_exact(), 2);
+    /// ```
+    pub fn len_exact(&self) -> usize {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.len_exact(),
+            ReceiverFlavor::List(chan) => chan.len_exact(),
+            ReceiverFlavor::Merge(chan) => chan.len_exact(),
+            _ => self.len(),
         }
     }
 
@@ -894,23 +3663,327 @@ impl<T> Receiver<T> {
     /// ```
     /// use crossbeam_channel::{bounded, unbounded};
     ///
-    /// let (_, r) = unbounded::<i32>();
-    /// assert_eq!(r.capacity(), None);
+    /// let (_, r) = unbounded::<i32>();
+    /// assert_eq!(r.capacity(), None);
+    ///
+    /// let (_, r) = bounded::<i32>(5);
+    /// assert_eq!(r.capacity(), Some(5));
+    ///
+    /// let (_, r) = bounded::<i32>(0);
+    /// assert_eq!(r.capacity(), Some(0));
+    /// ```
+    pub fn capacity(&self) -> Option<usize> {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.capacity(),
+            ReceiverFlavor::List(chan) => chan.capacity(),
+            ReceiverFlavor::Zero(chan) => chan.capacity(),
+            ReceiverFlavor::Oneshot(chan) => chan.capacity(),
+            ReceiverFlavor::Heap(chan) => chan.capacity(),
+            ReceiverFlavor::Coalesce(chan) => chan.capacity(),
+            ReceiverFlavor::Priority(chan) => chan.capacity(),
+            ReceiverFlavor::After(chan) => chan.capacity(),
+            ReceiverFlavor::Tick(chan) => chan.capacity(),
+            ReceiverFlavor::Never(chan) => chan.capacity(),
+            ReceiverFlavor::Ready(chan) => chan.capacity(),
+            ReceiverFlavor::Merge(chan) => chan.capacity(),
+        }
+    }
+
+    /// Returns the kind of the channel.
+    ///
+    /// Channels created by [`after`], [`tick`], and [`ready`] are bounded, channels created by
+    /// [`never`] are rendezvous channels, and receivers created by [`merge`] are unbounded, in
+    /// agreement with their capacities.
+    ///
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`ready`]: fn.ready.html
+    /// [`never`]: fn.never.html
+    /// [`merge`]: fn.merge.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, unbounded, ChannelKind};
+    ///
+    /// let (_, r) = unbounded::<i32>();
+    /// assert_eq!(r.kind(), ChannelKind::Unbounded);
+    ///
+    /// let (_, r) = bounded::<i32>(5);
+    /// assert_eq!(r.kind(), ChannelKind::Bounded);
+    ///
+    /// let (_, r) = bounded::<i32>(0);
+    /// assert_eq!(r.kind(), ChannelKind::Rendezvous);
+    /// ```
+    pub fn kind(&self) -> ChannelKind {
+        match &self.flavor {
+            ReceiverFlavor::Array(_) => ChannelKind::Bounded,
+            ReceiverFlavor::List(_) => ChannelKind::Unbounded,
+            ReceiverFlavor::Zero(_) => ChannelKind::Rendezvous,
+            ReceiverFlavor::Oneshot(_) => ChannelKind::Bounded,
+            ReceiverFlavor::Heap(_) => ChannelKind::Bounded,
+            ReceiverFlavor::Coalesce(_) => ChannelKind::Unbounded,
+            ReceiverFlavor::Priority(_) => ChannelKind::Bounded,
+            ReceiverFlavor::After(_) => ChannelKind::Bounded,
+            ReceiverFlavor::Tick(_) => ChannelKind::Bounded,
+            ReceiverFlavor::Never(_) => ChannelKind::Rendezvous,
+            ReceiverFlavor::Ready(_) => ChannelKind::Bounded,
+            ReceiverFlavor::Merge(_) => ChannelKind::Unbounded,
+        }
+    }
+
+    /// Returns the name of the channel, if it was given one.
+    ///
+    /// Channels are named by constructors such as [`bounded_named`] and [`unbounded_named`]. The
+    /// name is shared by all senders and receivers of the channel. Channels created by [`after`],
+    /// [`tick`], [`never`], and [`ready`] have no name.
+    ///
+    /// [`bounded_named`]: fn.bounded_named.html
+    /// [`unbounded_named`]: fn.unbounded_named.html
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
+    /// [`ready`]: fn.ready.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, unbounded_named};
+    ///
+    /// let (_, r) = unbounded_named::<i32>("jobs");
+    /// assert_eq!(r.clone().name(), Some("jobs"));
+    ///
+    /// let (_, r) = unbounded::<i32>();
+    /// assert_eq!(r.name(), None);
+    /// ```
+    pub fn name(&self) -> Option<&str> {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.name(),
+            ReceiverFlavor::List(chan) => chan.name(),
+            ReceiverFlavor::Zero(chan) => chan.name(),
+            ReceiverFlavor::Oneshot(chan) => chan.name(),
+            ReceiverFlavor::Heap(chan) => chan.name(),
+            ReceiverFlavor::Coalesce(chan) => chan.name(),
+            ReceiverFlavor::Priority(chan) => chan.name(),
+            ReceiverFlavor::After(_) => None,
+            ReceiverFlavor::Tick(_) => None,
+            ReceiverFlavor::Never(_) => None,
+            ReceiverFlavor::Ready(_) => None,
+            ReceiverFlavor::Merge(_) => None,
+        }
+    }
+
+    /// Returns how long it has been since a message was last received from the channel.
+    ///
+    /// The time is shared by all receivers of the channel: it measures the time since the most
+    /// recent successful receive operation through any receiver, not just this one. This can be
+    /// used by a watchdog to detect a consumer that has stopped making progress.
+    ///
+    /// Tracking is opt-in, so that receive operations on channels nobody watches don't read the
+    /// clock. It starts with the first call to this method through any receiver, which returns
+    /// zero. From then on, if no message has been received since that call, the time since the
+    /// call is returned.
+    ///
+    /// The time is tracked with millisecond precision. Channels created by [`after`], [`tick`],
+    /// [`never`], and [`ready`] don't track receive operations, so zero is always returned for
+    /// them. A receiver created by [`merge`] reports the shorter idle time of its inputs.
+    ///
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
+    /// [`ready`]: fn.ready.html
+    /// [`merge`]: fn.merge.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    ///
+    /// // Start tracking.
+    /// assert_eq!(r.idle_duration(), Duration::from_millis(0));
+    ///
+    /// thread::sleep(Duration::from_millis(100));
+    /// assert!(r.idle_duration() >= Duration::from_millis(100));
+    ///
+    /// r.recv().unwrap();
+    /// assert!(r.idle_duration() < Duration::from_millis(100));
+    /// ```
+    pub fn idle_duration(&self) -> Duration {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.idle_duration(),
+            ReceiverFlavor::List(chan) => chan.idle_duration(),
+            ReceiverFlavor::Zero(chan) => chan.idle_duration(),
+            ReceiverFlavor::Oneshot(chan) => chan.idle_duration(),
+            ReceiverFlavor::Heap(chan) => chan.idle_duration(),
+            ReceiverFlavor::Coalesce(chan) => chan.idle_duration(),
+            ReceiverFlavor::Priority(chan) => chan.idle_duration(),
+            ReceiverFlavor::After(_) => Duration::from_secs(0),
+            ReceiverFlavor::Tick(_) => Duration::from_secs(0),
+            ReceiverFlavor::Never(_) => Duration::from_secs(0),
+            ReceiverFlavor::Ready(_) => Duration::from_secs(0),
+            ReceiverFlavor::Merge(chan) => chan.idle_duration(),
+        }
+    }
+
+    /// Registers a callback to run exactly once, when the channel becomes disconnected.
+    ///
+    /// The channel becomes disconnected when the last [`Sender`] or the last `Receiver` is
+    /// dropped, and the callback runs on the stack of the thread that drops it, right after any
+    /// blocked operations have been woken up. If the channel is already disconnected, the
+    /// callback runs immediately on the current thread instead. The callback runs even if messages
+    /// are still left in the channel.
+    ///
+    /// Any number of callbacks can be registered, and each of them runs once even if several
+    /// threads race to disconnect the channel. Since a callback may run inside `drop`, it should
+    /// be short and must not panic. It shouldn't own a [`Sender`] of this channel either, or else
+    /// the channel never becomes disconnected on the sending side.
+    ///
+    /// A channel created by [`merge`] is disconnected once both of its inputs are. Channels created
+    /// by [`after`], [`tick`], [`never`], and [`ready`] are never disconnected, so the callback is
+    /// dropped without running.
+    ///
+    /// [`Sender`]: struct.Sender.html
+    /// [`merge`]: fn.merge.html
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
+    /// [`ready`]: fn.ready.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// let (done_s, done_r) = unbounded();
+    ///
+    /// r.on_disconnect_once(move || done_s.send("disconnected").unwrap());
+    /// assert!(done_r.try_recv().is_err());
+    ///
+    /// thread::spawn(move || drop(s)).join().unwrap();
+    /// assert_eq!(done_r.try_recv(), Ok("disconnected"));
+    /// ```
+    pub fn on_disconnect_once<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.on_disconnect_once(f),
+            ReceiverFlavor::List(chan) => chan.on_disconnect_once(f),
+            ReceiverFlavor::Zero(chan) => chan.on_disconnect_once(f),
+            ReceiverFlavor::Oneshot(chan) => chan.on_disconnect_once(f),
+            ReceiverFlavor::Heap(chan) => chan.on_disconnect_once(f),
+            ReceiverFlavor::Coalesce(chan) => chan.on_disconnect_once(f),
+            ReceiverFlavor::Priority(chan) => chan.on_disconnect_once(f),
+            ReceiverFlavor::After(_) => {}
+            ReceiverFlavor::Tick(_) => {}
+            ReceiverFlavor::Never(_) => {}
+            ReceiverFlavor::Ready(_) => {}
+            ReceiverFlavor::Merge(chan) => {
+                let mut f = Some(f);
+                chan.on_disconnect_once(Box::new(move || {
+                    if let Some(f) = f.take() {
+                        f();
+                    }
+                }))
+            }
+        }
+    }
+
+    /// Sets a callback to be called whenever the number of messages in the channel grows to
+    /// `threshold` or more.
+    ///
+    /// Send operations check the length of the channel after sending. The callback is called once
+    /// each time the length is found to have reached `threshold` from below, and not again until a
+    /// send operation finds the length below `threshold`. This is useful for scaling the number of
+    /// consumers with the backlog.
+    ///
+    /// The callback is called on a dedicated notifier thread, so producers never run it or wait
+    /// for it. If the threshold is reached again while the callback is still running, only one
+    /// more call is queued up. The callback may use the channel freely. However, it should not own
+    /// a [`Sender`] or a `Receiver` of this channel, or else the channel and the notifier thread
+    /// are kept alive forever. If the callback panics, the notifier thread stops and no further
+    /// calls are made, even to callbacks set later.
+    ///
+    /// Setting a watermark replaces the previous one. Only one notifier thread is started per
+    /// channel, the first time a watermark is set. The watermark applies to the channel, so it is
+    /// shared by all senders and receivers. Channels created by [`after`], [`tick`], [`never`],
+    /// [`ready`], and [`merge`] ignore the watermark.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the notifier thread can't be spawned.
+    ///
+    /// [`Sender`]: struct.Sender.html
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
+    /// [`ready`]: fn.ready.html
+    /// [`merge`]: fn.merge.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{bounded, unbounded};
+    ///
+    /// let (s, r) = unbounded();
+    /// let (scale_s, scale_r) = bounded(10);
     ///
-    /// let (_, r) = bounded::<i32>(5);
-    /// assert_eq!(r.capacity(), Some(5));
+    /// r.set_high_watermark(3, move || {
+    ///     let _ = scale_s.try_send("spawn another consumer");
+    /// });
     ///
-    /// let (_, r) = bounded::<i32>(0);
-    /// assert_eq!(r.capacity(), Some(0));
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// assert!(scale_r.recv_timeout(Duration::from_millis(100)).is_err());
+    ///
+    /// s.send(3).unwrap();
+    /// assert_eq!(scale_r.recv(), Ok("spawn another consumer"));
     /// ```
-    pub fn capacity(&self) -> Option<usize> {
+    pub fn set_high_watermark<F>(&self, threshold: usize, cb: F)
+    where
+        F: Fn() + Send + 'static,
+    {
         match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.capacity(),
-            ReceiverFlavor::List(chan) => chan.capacity(),
-            ReceiverFlavor::Zero(chan) => chan.capacity(),
-            ReceiverFlavor::After(chan) => chan.capacity(),
-            ReceiverFlavor::Tick(chan) => chan.capacity(),
-            ReceiverFlavor::Never(chan) => chan.capacity(),
+            ReceiverFlavor::Array(chan) => chan.watermark().set(threshold, cb),
+            ReceiverFlavor::List(chan) => chan.watermark().set(threshold, cb),
+            ReceiverFlavor::Zero(chan) => chan.watermark().set(threshold, cb),
+            ReceiverFlavor::Oneshot(chan) => chan.watermark().set(threshold, cb),
+            ReceiverFlavor::Heap(chan) => chan.watermark().set(threshold, cb),
+            ReceiverFlavor::Coalesce(chan) => chan.watermark().set(threshold, cb),
+            ReceiverFlavor::Priority(chan) => chan.watermark().set(threshold, cb),
+            ReceiverFlavor::After(_) => {}
+            ReceiverFlavor::Tick(_) => {}
+            ReceiverFlavor::Never(_) => {}
+            ReceiverFlavor::Ready(_) => {}
+            ReceiverFlavor::Merge(_) => {}
+        }
+    }
+
+    /// Removes the callback set by [`set_high_watermark`].
+    ///
+    /// [`set_high_watermark`]: struct.Receiver.html#method.set_high_watermark
+    pub fn clear_high_watermark(&self) {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.clear_watermark(),
+            ReceiverFlavor::List(chan) => chan.clear_watermark(),
+            ReceiverFlavor::Zero(chan) => chan.clear_watermark(),
+            ReceiverFlavor::Oneshot(chan) => chan.clear_watermark(),
+            ReceiverFlavor::Heap(chan) => chan.clear_watermark(),
+            ReceiverFlavor::Coalesce(chan) => chan.clear_watermark(),
+            ReceiverFlavor::Priority(chan) => chan.clear_watermark(),
+            ReceiverFlavor::After(_) => {}
+            ReceiverFlavor::Tick(_) => {}
+            ReceiverFlavor::Never(_) => {}
+            ReceiverFlavor::Ready(_) => {}
+            ReceiverFlavor::Merge(_) => {}
         }
     }
 
@@ -943,7 +4016,7 @@ impl<T> Receiver<T> {
     ///
     /// assert_eq!(v, [1, 2, 3]);
     /// ```
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter { receiver: self }
     }
 
@@ -979,10 +4052,129 @@ impl<T> Receiver<T> {
     ///
     /// assert_eq!(v, [1, 2]);
     /// ```
-    pub fn try_iter(&self) -> TryIter<T> {
+    pub fn try_iter(&self) -> TryIter<'_, T> {
         TryIter { receiver: self }
     }
 
+    /// A non-blocking iterator over messages that are in the channel right now.
+    ///
+    /// The number of messages in the channel is taken once, when this method is called, and the
+    /// iterator yields at most that many messages. Unlike [`try_iter`], it does not pick up
+    /// messages sent afterwards, and its exact length is known up front, which is useful for
+    /// preallocating space for a batch.
+    ///
+    /// If other receivers take messages from the channel in the meantime, the iterator stops early
+    /// when the channel becomes empty, so its length is only exact if there are no competing
+    /// receivers. Zero-capacity channels never hold messages, so the iterator is always empty.
+    ///
+    /// [`try_iter`]: struct.Receiver.html#method.try_iter
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    ///
+    /// let mut drain = r.drain();
+    /// assert_eq!(drain.len(), 2);
+    ///
+    /// // Messages sent after the call to `drain` are not included.
+    /// s.send(3).unwrap();
+    ///
+    /// let mut batch = Vec::with_capacity(drain.len());
+    /// batch.extend(drain);
+    /// assert_eq!(batch, [1, 2]);
+    /// assert_eq!(r.recv(), Ok(3));
+    /// ```
+    pub fn drain(&self) -> Drain<'_, T> {
+        Drain {
+            receiver: self,
+            remaining: self.len(),
+        }
+    }
+
+    /// Consumes the receiver and returns all messages currently buffered in the channel.
+    ///
+    /// Messages are returned in the order they would have been received. This is a shorthand for
+    /// collecting [`drain`] into a vector and dropping the receiver afterwards, which is handy
+    /// for final processing when the channel is no longer needed.
+    ///
+    /// Only messages buffered at the time of the call are taken, so this never blocks even if
+    /// senders are still alive. If this is the last receiver, the channel gets disconnected and
+    /// later sends fail. Zero-capacity channels never hold messages, so the vector is always
+    /// empty.
+    ///
+    /// [`drain`]: struct.Receiver.html#method.drain
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// drop(s);
+    ///
+    /// assert_eq!(r.drain_all(), [1, 2]);
+    /// ```
+    pub fn drain_all(self) -> Vec<T> {
+        let drain = self.drain();
+        let mut msgs = Vec::with_capacity(drain.len());
+        msgs.extend(drain);
+        msgs
+    }
+
+    /// Returns a pointer identifying the channel.
+    ///
+    /// The pointer is the same for all senders and receivers of the channel, and differs between
+    /// channels that exist at the same time. Unlike [`identical_to`], it doesn't depend on the
+    /// message type, so handles stored as trait objects can still be compared, deduplicated, or
+    /// used as map keys. All [`never`] channels are identical and share the same pointer.
+    ///
+    /// The pointer is only meaningful for comparison and must never be dereferenced. Once the
+    /// channel is dropped, a new channel may get the same pointer.
+    ///
+    /// [`identical_to`]: struct.Receiver.html#method.identical_to
+    /// [`never`]: fn.never.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r1) = unbounded::<i32>();
+    /// let r2 = r1.clone();
+    /// let (_, r3) = unbounded::<i32>();
+    ///
+    /// assert_eq!(r1.channel_ptr(), r2.channel_ptr());
+    /// assert_eq!(r1.channel_ptr(), s.channel_ptr());
+    /// assert_ne!(r1.channel_ptr(), r3.channel_ptr());
+    /// ```
+    pub fn channel_ptr(&self) -> *const () {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.as_ptr(),
+            ReceiverFlavor::List(chan) => chan.as_ptr(),
+            ReceiverFlavor::Zero(chan) => chan.as_ptr(),
+            ReceiverFlavor::Oneshot(chan) => chan.as_ptr(),
+            ReceiverFlavor::Heap(chan) => chan.as_ptr(),
+            ReceiverFlavor::Coalesce(chan) => chan.as_ptr(),
+            ReceiverFlavor::Priority(chan) => chan.as_ptr(),
+            ReceiverFlavor::After(chan) => &**chan as *const flavors::after::Channel as *const (),
+            ReceiverFlavor::Tick(chan) => &**chan as *const flavors::tick::Channel as *const (),
+            ReceiverFlavor::Never(_) => ptr::null(),
+            ReceiverFlavor::Ready(chan) => {
+                &**chan as *const flavors::ready::Channel<T> as *const ()
+            }
+            ReceiverFlavor::Merge(chan) => {
+                &**chan as *const flavors::merge::Channel<T> as *const ()
+            }
+        }
+    }
+
     /// Returns true if the receiver receive from the same channel.
     ///
     /// # Examples
@@ -1020,6 +4212,16 @@ impl<T> Receiver<T> {
             (Array(ref self_counter), Array(ref other_counter)) => self_counter == other_counter,
             (List(ref self_counter), List(ref other_counter)) => self_counter == other_counter,
             (Zero(ref self_counter), Zero(ref other_counter)) => self_counter == other_counter,
+            (Oneshot(ref self_counter), Oneshot(ref other_counter)) => {
+                self_counter == other_counter
+            }
+            (Heap(ref self_counter), Heap(ref other_counter)) => self_counter == other_counter,
+            (Coalesce(ref self_counter), Coalesce(ref other_counter)) => {
+                self_counter == other_counter
+            }
+            (Priority(ref self_counter), Priority(ref other_counter)) => {
+                self_counter == other_counter
+            }
             (After(ref self_channel), After(ref other_channel)) => {
                 Arc::ptr_eq(self_channel, other_channel)
             }
@@ -1027,22 +4229,389 @@ impl<T> Receiver<T> {
                 Arc::ptr_eq(self_channel, other_channel)
             }
             (Never(_), Never(_)) => true,
+            (Ready(ref self_channel), Ready(ref other_channel)) => {
+                Arc::ptr_eq(self_channel, other_channel)
+            }
+            (Merge(ref self_channel), Merge(ref other_channel)) => {
+                Arc::ptr_eq(self_channel, other_channel)
+            }
             // Channels of different flavours are never equal.
             _ => false,
         }
     }
+
+    /// Registers a file descriptor to be signalled when the channel may have become ready for
+    /// receiving.
+    ///
+    /// This allows waiting on channels with an external poller such as `epoll`, together with
+    /// other I/O sources. When a message is sent into an empty channel, a sender blocks on a
+    /// zero-capacity channel, or the channel becomes disconnected, an 8-byte integer equal to 1
+    /// is written into `fd`. This is the format expected by an `eventfd`, but a pipe or a socket
+    /// works as well. If the channel is ready at the time of registration, `fd` is signalled right
+    /// away.
+    ///
+    /// To keep send operations cheap, `fd` is signalled only once until [`try_recv`] reports that
+    /// the channel is empty. After each wakeup, the receiver should therefore drain the channel
+    /// with [`try_recv`] until it returns [`TryRecvError::Empty`], or else it may not be woken up
+    /// again. Signals may still be spurious. The descriptor should be non-blocking, so that a send
+    /// operation never blocks on a full descriptor, which is readable anyway. The descriptor is not owned by the channel: it must stay
+    /// open until it is unregistered with [`unregister_ready_fd`] or the channel is dropped.
+    ///
+    /// Channels created by [`after`], [`tick`], and [`never`] cannot signal descriptors, in which
+    /// case `false` is returned. A channel created by [`ready`] is always ready, so `fd` is
+    /// signalled right away.
+    ///
+    /// This method is available with the `os_poll` feature on Unix platforms.
+    ///
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    /// [`TryRecvError::Empty`]: enum.TryRecvError.html#variant.Empty
+    /// [`unregister_ready_fd`]: struct.Receiver.html#method.unregister_ready_fd
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
+    /// [`ready`]: fn.ready.html
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn register_ready_fd(&self, fd: RawFd) -> bool {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.watch_fd(fd),
+            ReceiverFlavor::List(chan) => chan.watch_fd(fd),
+            ReceiverFlavor::Zero(chan) => chan.watch_fd(fd),
+            ReceiverFlavor::Oneshot(chan) => chan.watch_fd(fd),
+            ReceiverFlavor::Heap(chan) => chan.watch_fd(fd),
+            ReceiverFlavor::Coalesce(chan) => chan.watch_fd(fd),
+            ReceiverFlavor::Priority(chan) => chan.watch_fd(fd),
+            ReceiverFlavor::After(_) => return false,
+            ReceiverFlavor::Tick(_) => return false,
+            ReceiverFlavor::Never(_) => return false,
+            ReceiverFlavor::Ready(_) => {}
+            ReceiverFlavor::Merge(chan) => {
+                for r in chan.inputs() {
+                    r.register_ready_fd(fd);
+                }
+            }
+        }
+
+        // Make sure a message sent before registration is not missed.
+        if SelectHandle::is_ready(self) {
+            waker::signal_fd(fd);
+        }
+        true
+    }
+
+    /// Unregisters a file descriptor previously registered with [`register_ready_fd`].
+    ///
+    /// This method is available with the `os_poll` feature on Unix platforms.
+    ///
+    /// [`register_ready_fd`]: struct.Receiver.html#method.register_ready_fd
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn unregister_ready_fd(&self, fd: RawFd) {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.unwatch_fd(fd),
+            ReceiverFlavor::List(chan) => chan.unwatch_fd(fd),
+            ReceiverFlavor::Zero(chan) => chan.unwatch_fd(fd),
+            ReceiverFlavor::Oneshot(chan) => chan.unwatch_fd(fd),
+            ReceiverFlavor::Heap(chan) => chan.unwatch_fd(fd),
+            ReceiverFlavor::Coalesce(chan) => chan.unwatch_fd(fd),
+            ReceiverFlavor::Priority(chan) => chan.unwatch_fd(fd),
+            ReceiverFlavor::After(_) => {}
+            ReceiverFlavor::Tick(_) => {}
+            ReceiverFlavor::Never(_) => {}
+            ReceiverFlavor::Ready(_) => {}
+            ReceiverFlavor::Merge(chan) => {
+                for r in chan.inputs() {
+                    r.unregister_ready_fd(fd);
+                }
+            }
+        }
+    }
+
+    /// Lets the next message signal the registered file descriptors again, after a receive
+    /// operation has found the channel empty.
+    #[cfg(all(unix, feature = "os_poll"))]
+    fn rearm_ready_fds(&self) {
+        let is_ready = || SelectHandle::is_ready(self);
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.rearm_fds(is_ready),
+            ReceiverFlavor::List(chan) => chan.rearm_fds(is_ready),
+            ReceiverFlavor::Zero(chan) => chan.rearm_fds(is_ready),
+            ReceiverFlavor::Oneshot(chan) => chan.rearm_fds(is_ready),
+            ReceiverFlavor::Heap(chan) => chan.rearm_fds(is_ready),
+            ReceiverFlavor::Coalesce(chan) => chan.rearm_fds(is_ready),
+            ReceiverFlavor::Priority(chan) => chan.rearm_fds(is_ready),
+            ReceiverFlavor::After(_) => {}
+            ReceiverFlavor::Tick(_) => {}
+            ReceiverFlavor::Never(_) => {}
+            ReceiverFlavor::Ready(_) => {}
+            // The inputs are rearmed when trying to receive from them.
+            ReceiverFlavor::Merge(_) => {}
+        }
+    }
+
+    /// Creates a new sender for the same channel.
+    ///
+    /// This is just like cloning a [`Sender`] of the channel. If all senders have already been
+    /// dropped, the channel is disconnected for good and `None` is returned. Channels created by
+    /// [`after`], [`tick`], [`never`], [`ready`], and [`merge`] have no senders, so `None` is
+    /// returned for them too.
+    ///
+    /// [`Sender`]: struct.Sender.html
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
+    /// [`ready`]: fn.ready.html
+    /// [`merge`]: fn.merge.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// let s2 = r.make_sender().unwrap();
+    /// s2.send(1).unwrap();
+    /// assert_eq!(r.recv(), Ok(1));
+    ///
+    /// drop(s);
+    /// drop(s2);
+    /// assert!(r.make_sender().is_none());
+    /// ```
+    pub fn make_sender(&self) -> Option<Sender<T>> {
+        let flavor = match &self.flavor {
+            ReceiverFlavor::Array(chan) => SenderFlavor::Array(chan.acquire_sender()?),
+            ReceiverFlavor::List(chan) => SenderFlavor::List(chan.acquire_sender()?),
+            ReceiverFlavor::Zero(chan) => SenderFlavor::Zero(chan.acquire_sender()?),
+            ReceiverFlavor::Oneshot(chan) => SenderFlavor::Oneshot(chan.acquire_sender()?),
+            ReceiverFlavor::Heap(chan) => SenderFlavor::Heap(chan.acquire_sender()?),
+            ReceiverFlavor::Coalesce(chan) => SenderFlavor::Coalesce(chan.acquire_sender()?),
+            ReceiverFlavor::Priority(_) => return None,
+            ReceiverFlavor::After(_) => return None,
+            ReceiverFlavor::Tick(_) => return None,
+            ReceiverFlavor::Never(_) => return None,
+            ReceiverFlavor::Ready(_) => return None,
+            ReceiverFlavor::Merge(_) => return None,
+        };
+        Some(Sender { flavor })
+    }
+
+    /// Converts the receiver into the receiving side of an asynchronous `futures` channel.
+    ///
+    /// The returned receiver is a `Stream` of messages received from this channel. Messages are
+    /// moved into it by a forwarding thread spawned with [`spawn_forwarder`], which exits once
+    /// this channel is empty and disconnected, ending the stream.
+    ///
+    /// This requires the `async-bridge` feature.
+    ///
+    /// [`spawn_forwarder`]: fn.spawn_forwarder.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate crossbeam_channel;
+    /// extern crate futures;
+    ///
+    /// use crossbeam_channel::unbounded;
+    /// use futures::Stream;
+    ///
+    /// # fn main() {
+    /// let (s, r) = unbounded();
+    /// let stream = r.bridge_to_async();
+    ///
+    /// s.send("hello").unwrap();
+    /// drop(s);
+    ///
+    /// assert_eq!(stream.wait().collect::<Result<Vec<_>, _>>(), Ok(vec!["hello"]));
+    /// # }
+    /// ```
+    #[cfg(feature = "async-bridge")]
+    pub fn bridge_to_async(self) -> mpsc::Receiver<T>
+    where
+        T: Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(0);
+        async_bridge::spawn_forwarder(self, tx);
+        rx
+    }
+
+    /// Creates a pump forwarding every message from this receiver into `tx`, transformed by `f`.
+    ///
+    /// This codifies a pipeline stage: a loop that receives a message, transforms it, and sends
+    /// it into the next channel. The returned [`Pump`] doesn't spawn a thread. Instead, the caller
+    /// drives it with [`Pump::step`] or [`Pump::run`] on a thread of its choice.
+    ///
+    /// The pump finishes once this receiver is empty and disconnected, at which point it drops
+    /// `tx` so that the next stage sees the disconnection after receiving all messages. It also
+    /// finishes if `tx` turns out to be disconnected while forwarding a message, at which point it
+    /// drops the receiver so that the previous stage sees the disconnection too. That message is
+    /// dropped.
+    ///
+    /// [`Pump`]: struct.Pump.html
+    /// [`Pump::step`]: struct.Pump.html#method.step
+    /// [`Pump::run`]: struct.Pump.html#method.run
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// let (s3, r3) = unbounded();
+    ///
+    /// let mut parse = r1.pipe_to(s2, |line: &str| line.parse::<i32>().unwrap());
+    /// let mut double = r2.pipe_to(s3, |x| x * 2);
+    /// thread::spawn(move || parse.run());
+    /// thread::spawn(move || double.run());
+    ///
+    /// s1.send("1").unwrap();
+    /// s1.send("2").unwrap();
+    /// drop(s1);
+    ///
+    /// // Disconnection propagates through both stages.
+    /// assert_eq!(r3.iter().collect::<Vec<_>>(), [2, 4]);
+    /// ```
+    pub fn pipe_to<U, F>(self, tx: Sender<U>, f: F) -> Pump<T, U, F>
+    where
+        F: FnMut(T) -> U,
+    {
+        Pump::new(self, tx, f)
+    }
+
+    /// Records a successful receive operation for [`idle_duration`].
+    ///
+    /// [`idle_duration`]: struct.Receiver.html#method.idle_duration
+    fn mark_received(&self) {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.mark_received(),
+            ReceiverFlavor::List(chan) => chan.mark_received(),
+            ReceiverFlavor::Zero(chan) => chan.mark_received(),
+            ReceiverFlavor::Oneshot(chan) => chan.mark_received(),
+            ReceiverFlavor::Heap(chan) => chan.mark_received(),
+            ReceiverFlavor::Coalesce(chan) => chan.mark_received(),
+            ReceiverFlavor::Priority(chan) => chan.mark_received(),
+            ReceiverFlavor::After(_) => {}
+            ReceiverFlavor::Tick(_) => {}
+            ReceiverFlavor::Never(_) => {}
+            ReceiverFlavor::Ready(_) => {}
+            ReceiverFlavor::Merge(_) => {}
+        }
+    }
+
+    /// Waits until it is the turn of a receive operation in a replayed schedule.
+    ///
+    /// Returns `false` if the deadline is reached first. The deadline is only computed if the
+    /// operation has to wait, so that operations don't read the clock otherwise.
+    #[cfg(feature = "replay")]
+    fn replay_turn<F>(&self, deadline: F) -> bool
+    where
+        F: FnOnce() -> Option<Instant>,
+    {
+        replay::wait_turn(self.replay_id(), ReplayOp::Recv, deadline)
+    }
+
+    /// Waits until it is the turn of a receive operation in a replayed schedule.
+    #[cfg(not(feature = "replay"))]
+    fn replay_turn<F>(&self, _deadline: F) -> bool
+    where
+        F: FnOnce() -> Option<Instant>,
+    {
+        true
+    }
+
+    /// Completes a receive operation in a recording or replay, at the point where it succeeds or fails.
+    #[cfg(feature = "replay")]
+    fn replay_complete(&self, success: bool) {
+        replay::complete(self.replay_id(), ReplayOp::Recv, success)
+    }
+
+    /// Completes a receive operation in a recording or replay, at the point where it succeeds or fails.
+    #[cfg(not(feature = "replay"))]
+    fn replay_complete(&self, _success: bool) {}
+
+    /// Returns the identifier of the channel used for recording and replaying operations.
+    #[cfg(feature = "replay")]
+    fn replay_id(&self) -> Option<usize> {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.replay_id(),
+            ReceiverFlavor::List(chan) => chan.replay_id(),
+            ReceiverFlavor::Zero(chan) => chan.replay_id(),
+            ReceiverFlavor::Oneshot(chan) => chan.replay_id(),
+            ReceiverFlavor::Heap(chan) => chan.replay_id(),
+            ReceiverFlavor::Coalesce(chan) => chan.replay_id(),
+            ReceiverFlavor::Priority(chan) => chan.replay_id(),
+            ReceiverFlavor::After(_) => None,
+            ReceiverFlavor::Tick(_) => None,
+            ReceiverFlavor::Never(_) => None,
+            ReceiverFlavor::Ready(_) => None,
+            ReceiverFlavor::Merge(_) => None,
+        }
+    }
+
+    /// Registers an observer that is notified every time a message is taken out of the channel.
+    ///
+    /// Zero-capacity channels never hold messages, and the messages of the other special flavors
+    /// are not taken out of a buffer, so nothing is registered for them.
+    pub(crate) fn watch_drain(&self, oper: Operation, cx: &Context) {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.watch_drain(oper, cx),
+            ReceiverFlavor::List(chan) => chan.watch_drain(oper, cx),
+            ReceiverFlavor::Oneshot(chan) => chan.watch_drain(oper, cx),
+            ReceiverFlavor::Heap(chan) => chan.watch_drain(oper, cx),
+            ReceiverFlavor::Coalesce(chan) => chan.watch_drain(oper, cx),
+            ReceiverFlavor::Priority(chan) => chan.watch_drain(oper, cx),
+            ReceiverFlavor::Merge(chan) => chan.watch_drain(oper, cx),
+            ReceiverFlavor::Zero(_)
+            | ReceiverFlavor::After(_)
+            | ReceiverFlavor::Tick(_)
+            | ReceiverFlavor::Never(_)
+            | ReceiverFlavor::Ready(_) => {}
+        }
+    }
+
+    /// Unregisters an observer registered by `watch_drain`.
+    pub(crate) fn unwatch_drain(&self, oper: Operation) {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.unwatch_drain(oper),
+            ReceiverFlavor::List(chan) => chan.unwatch_drain(oper),
+            ReceiverFlavor::Oneshot(chan) => chan.unwatch_drain(oper),
+            ReceiverFlavor::Heap(chan) => chan.unwatch_drain(oper),
+            ReceiverFlavor::Coalesce(chan) => chan.unwatch_drain(oper),
+            ReceiverFlavor::Priority(chan) => chan.unwatch_drain(oper),
+            ReceiverFlavor::Merge(chan) => chan.unwatch_drain(oper),
+            ReceiverFlavor::Zero(_)
+            | ReceiverFlavor::After(_)
+            | ReceiverFlavor::Tick(_)
+            | ReceiverFlavor::Never(_)
+            | ReceiverFlavor::Ready(_) => {}
+        }
+    }
 }
 
 impl<T> Drop for Receiver<T> {
     fn drop(&mut self) {
         unsafe {
             match &self.flavor {
-                ReceiverFlavor::Array(chan) => chan.release(|c| c.disconnect()),
-                ReceiverFlavor::List(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Array(chan) => chan.release(|c| {
+                    let disconnected = c.disconnect();
+                    c.hand_off_remaining();
+                    disconnected
+                }),
+                ReceiverFlavor::List(chan) => chan.release(|c| {
+                    let disconnected = c.disconnect();
+                    c.hand_off_remaining();
+                    disconnected
+                }),
                 ReceiverFlavor::Zero(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Oneshot(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Heap(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Coalesce(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Priority(chan) => chan.release(|c| c.disconnect()),
                 ReceiverFlavor::After(_) => {}
                 ReceiverFlavor::Tick(_) => {}
                 ReceiverFlavor::Never(_) => {}
+                ReceiverFlavor::Ready(_) => {}
+                ReceiverFlavor::Merge(_) => {}
             }
         }
     }
@@ -1054,9 +4623,15 @@ impl<T> Clone for Receiver<T> {
             ReceiverFlavor::Array(chan) => ReceiverFlavor::Array(chan.acquire()),
             ReceiverFlavor::List(chan) => ReceiverFlavor::List(chan.acquire()),
             ReceiverFlavor::Zero(chan) => ReceiverFlavor::Zero(chan.acquire()),
+            ReceiverFlavor::Oneshot(chan) => ReceiverFlavor::Oneshot(chan.acquire()),
+            ReceiverFlavor::Heap(chan) => ReceiverFlavor::Heap(chan.acquire()),
+            ReceiverFlavor::Coalesce(chan) => ReceiverFlavor::Coalesce(chan.acquire()),
+            ReceiverFlavor::Priority(chan) => ReceiverFlavor::Priority(chan.acquire()),
             ReceiverFlavor::After(chan) => ReceiverFlavor::After(chan.clone()),
             ReceiverFlavor::Tick(chan) => ReceiverFlavor::Tick(chan.clone()),
             ReceiverFlavor::Never(_) => ReceiverFlavor::Never(flavors::never::Channel::new()),
+            ReceiverFlavor::Ready(chan) => ReceiverFlavor::Ready(chan.clone()),
+            ReceiverFlavor::Merge(chan) => ReceiverFlavor::Merge(chan.clone()),
         };
 
         Receiver { flavor }
@@ -1065,7 +4640,10 @@ impl<T> Clone for Receiver<T> {
 
 impl<T> fmt::Debug for Receiver<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.pad("Receiver { .. }")
+        match self.name() {
+            Some(name) => write!(f, "Receiver {{ name: {:?}, .. }}", name),
+            None => f.pad("Receiver { .. }"),
+        }
     }
 }
 
@@ -1087,6 +4665,41 @@ impl<T> IntoIterator for Receiver<T> {
     }
 }
 
+/// A slot reserved in a channel for a message that is sent later.
+///
+/// This guard is created by [`Sender::reserve`] and [`Sender::try_reserve`]. See their
+/// documentation for more.
+///
+/// Dropping the guard without calling [`send`] abandons the reservation: the slot stays in the
+/// channel, but receivers skip it instead of waiting for it, and its capacity is freed once they
+/// do. This also happens if the thread panics while holding the guard.
+///
+/// [`Sender::reserve`]: struct.Sender.html#method.reserve
+/// [`Sender::try_reserve`]: struct.Sender.html#method.try_reserve
+/// [`send`]: struct.Slot.html#method.send
+pub struct Slot<'a, T: 'a> {
+    sender: &'a Sender<T>,
+    reservation: flavors::array::Reservation<'a, T>,
+}
+
+impl<'a, T> Slot<'a, T> {
+    /// Sends a message into the reserved slot.
+    ///
+    /// This never blocks and never fails. If all receivers have been dropped in the meantime, the
+    /// message stays in the channel until it is dropped.
+    pub fn send(self, msg: T) {
+        self.reservation.fill(msg);
+        self.sender.observe_len();
+        self.sender.replay_complete(true);
+    }
+}
+
+impl<'a, T> fmt::Debug for Slot<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Slot { .. }")
+    }
+}
+
 /// A blocking iterator over messages in a channel.
 ///
 /// Each call to [`next`] blocks waiting for the next message and then returns it. However, if the
@@ -1186,6 +4799,118 @@ impl<'a, T> fmt::Debug for TryIter<'a, T> {
     }
 }
 
+/// A non-blocking iterator over messages that were in a channel when it was created.
+///
+/// This iterator is created by [`Receiver::drain`]. See its documentation for more.
+///
+/// [`Receiver::drain`]: struct.Receiver.html#method.drain
+pub struct Drain<'a, T: 'a> {
+    receiver: &'a Receiver<T>,
+    remaining: usize,
+}
+
+impl<'a, T> FusedIterator for Drain<'a, T> {}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        match self.receiver.try_recv() {
+            Ok(msg) => {
+                self.remaining -= 1;
+                Some(msg)
+            }
+            Err(_) => {
+                // Other receivers took the remaining messages.
+                self.remaining = 0;
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> {}
+
+impl<'a, T> fmt::Debug for Drain<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Drain { .. }")
+    }
+}
+
+/// A run of messages at the front of a channel, locked for bulk consumption.
+///
+/// This guard is created by [`Receiver::peek_contiguous`]. See its documentation for more.
+///
+/// Messages in the run can be accessed by index. The channel stores a stamp next to each message,
+/// so the run cannot be borrowed as a slice.
+///
+/// [`Receiver::peek_contiguous`]: struct.Receiver.html#method.peek_contiguous
+pub struct Contiguous<'a, T: 'a> {
+    receiver: &'a Receiver<T>,
+    run: flavors::array::Run<'a, T>,
+}
+
+impl<'a, T> Contiguous<'a, T> {
+    /// Returns the number of messages in the run.
+    ///
+    /// The run always holds at least one message.
+    pub fn len(&self) -> usize {
+        self.run.len()
+    }
+
+    /// Returns `true` if the run holds no messages.
+    ///
+    /// This is never the case for a run returned by [`Receiver::peek_contiguous`].
+    ///
+    /// [`Receiver::peek_contiguous`]: struct.Receiver.html#method.peek_contiguous
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the `i`-th message of the run, or `None` if it is out of bounds.
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.run.get(i)
+    }
+
+    /// Removes the first `n` messages of the run from the channel and unlocks the front of it.
+    ///
+    /// The removed messages are dropped. The remaining ones stay in the channel in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length of the run.
+    pub fn consume(mut self, n: usize) {
+        self.run.consume(n);
+        if n > 0 {
+            self.receiver.mark_received();
+        }
+    }
+}
+
+impl<'a, T> ops::Index<usize> for Contiguous<'a, T> {
+    type Output = T;
+
+    fn index(&self, i: usize) -> &T {
+        self.get(i).expect("index out of bounds")
+    }
+}
+
+impl<'a, T> fmt::Debug for Contiguous<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Contiguous")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
 /// A blocking iterator over messages in a channel.
 ///
 /// Each call to [`next`] blocks waiting for the next message and then returns it. However, if the
@@ -1241,6 +4966,9 @@ impl<T> SelectHandle for Sender<T> {
             SenderFlavor::Array(chan) => chan.sender().try_select(token),
             SenderFlavor::List(chan) => chan.sender().try_select(token),
             SenderFlavor::Zero(chan) => chan.sender().try_select(token),
+            SenderFlavor::Oneshot(chan) => chan.sender().try_select(token),
+            SenderFlavor::Heap(chan) => chan.sender().try_select(token),
+            SenderFlavor::Coalesce(chan) => chan.sender().try_select(token),
         }
     }
 
@@ -1253,6 +4981,9 @@ impl<T> SelectHandle for Sender<T> {
             SenderFlavor::Array(chan) => chan.sender().register(oper, cx),
             SenderFlavor::List(chan) => chan.sender().register(oper, cx),
             SenderFlavor::Zero(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Oneshot(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Heap(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Coalesce(chan) => chan.sender().register(oper, cx),
         }
     }
 
@@ -1261,6 +4992,9 @@ impl<T> SelectHandle for Sender<T> {
             SenderFlavor::Array(chan) => chan.sender().unregister(oper),
             SenderFlavor::List(chan) => chan.sender().unregister(oper),
             SenderFlavor::Zero(chan) => chan.sender().unregister(oper),
+            SenderFlavor::Oneshot(chan) => chan.sender().unregister(oper),
+            SenderFlavor::Heap(chan) => chan.sender().unregister(oper),
+            SenderFlavor::Coalesce(chan) => chan.sender().unregister(oper),
         }
     }
 
@@ -1269,6 +5003,9 @@ impl<T> SelectHandle for Sender<T> {
             SenderFlavor::Array(chan) => chan.sender().accept(token, cx),
             SenderFlavor::List(chan) => chan.sender().accept(token, cx),
             SenderFlavor::Zero(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Oneshot(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Heap(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Coalesce(chan) => chan.sender().accept(token, cx),
         }
     }
 
@@ -1277,6 +5014,9 @@ impl<T> SelectHandle for Sender<T> {
             SenderFlavor::Array(chan) => chan.sender().is_ready(),
             SenderFlavor::List(chan) => chan.sender().is_ready(),
             SenderFlavor::Zero(chan) => chan.sender().is_ready(),
+            SenderFlavor::Oneshot(chan) => chan.sender().is_ready(),
+            SenderFlavor::Heap(chan) => chan.sender().is_ready(),
+            SenderFlavor::Coalesce(chan) => chan.sender().is_ready(),
         }
     }
 
@@ -1285,6 +5025,9 @@ impl<T> SelectHandle for Sender<T> {
             SenderFlavor::Array(chan) => chan.sender().watch(oper, cx),
             SenderFlavor::List(chan) => chan.sender().watch(oper, cx),
             SenderFlavor::Zero(chan) => chan.sender().watch(oper, cx),
+            SenderFlavor::Oneshot(chan) => chan.sender().watch(oper, cx),
+            SenderFlavor::Heap(chan) => chan.sender().watch(oper, cx),
+            SenderFlavor::Coalesce(chan) => chan.sender().watch(oper, cx),
         }
     }
 
@@ -1293,6 +5036,9 @@ impl<T> SelectHandle for Sender<T> {
             SenderFlavor::Array(chan) => chan.sender().unwatch(oper),
             SenderFlavor::List(chan) => chan.sender().unwatch(oper),
             SenderFlavor::Zero(chan) => chan.sender().unwatch(oper),
+            SenderFlavor::Oneshot(chan) => chan.sender().unwatch(oper),
+            SenderFlavor::Heap(chan) => chan.sender().unwatch(oper),
+            SenderFlavor::Coalesce(chan) => chan.sender().unwatch(oper),
         }
     }
 }
@@ -1303,9 +5049,15 @@ impl<T> SelectHandle for Receiver<T> {
             ReceiverFlavor::Array(chan) => chan.receiver().try_select(token),
             ReceiverFlavor::List(chan) => chan.receiver().try_select(token),
             ReceiverFlavor::Zero(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Heap(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Priority(chan) => chan.receiver().try_select(token),
             ReceiverFlavor::After(chan) => chan.try_select(token),
             ReceiverFlavor::Tick(chan) => chan.try_select(token),
             ReceiverFlavor::Never(chan) => chan.try_select(token),
+            ReceiverFlavor::Ready(chan) => chan.try_select(token),
+            ReceiverFlavor::Merge(chan) => chan.try_select(token),
         }
     }
 
@@ -1314,9 +5066,15 @@ impl<T> SelectHandle for Receiver<T> {
             ReceiverFlavor::Array(_) => None,
             ReceiverFlavor::List(_) => None,
             ReceiverFlavor::Zero(_) => None,
+            ReceiverFlavor::Oneshot(_) => None,
+            ReceiverFlavor::Heap(_) => None,
+            ReceiverFlavor::Coalesce(_) => None,
+            ReceiverFlavor::Priority(_) => None,
             ReceiverFlavor::After(chan) => chan.deadline(),
             ReceiverFlavor::Tick(chan) => chan.deadline(),
             ReceiverFlavor::Never(chan) => chan.deadline(),
+            ReceiverFlavor::Ready(chan) => chan.deadline(),
+            ReceiverFlavor::Merge(chan) => chan.deadline(),
         }
     }
 
@@ -1325,9 +5083,15 @@ impl<T> SelectHandle for Receiver<T> {
             ReceiverFlavor::Array(chan) => chan.receiver().register(oper, cx),
             ReceiverFlavor::List(chan) => chan.receiver().register(oper, cx),
             ReceiverFlavor::Zero(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Heap(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Priority(chan) => chan.receiver().register(oper, cx),
             ReceiverFlavor::After(chan) => chan.register(oper, cx),
             ReceiverFlavor::Tick(chan) => chan.register(oper, cx),
             ReceiverFlavor::Never(chan) => chan.register(oper, cx),
+            ReceiverFlavor::Ready(chan) => chan.register(oper, cx),
+            ReceiverFlavor::Merge(chan) => chan.register(oper, cx),
         }
     }
 
@@ -1336,9 +5100,15 @@ impl<T> SelectHandle for Receiver<T> {
             ReceiverFlavor::Array(chan) => chan.receiver().unregister(oper),
             ReceiverFlavor::List(chan) => chan.receiver().unregister(oper),
             ReceiverFlavor::Zero(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Heap(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Priority(chan) => chan.receiver().unregister(oper),
             ReceiverFlavor::After(chan) => chan.unregister(oper),
             ReceiverFlavor::Tick(chan) => chan.unregister(oper),
             ReceiverFlavor::Never(chan) => chan.unregister(oper),
+            ReceiverFlavor::Ready(chan) => chan.unregister(oper),
+            ReceiverFlavor::Merge(chan) => chan.unregister(oper),
         }
     }
 
@@ -1347,9 +5117,15 @@ impl<T> SelectHandle for Receiver<T> {
             ReceiverFlavor::Array(chan) => chan.receiver().accept(token, cx),
             ReceiverFlavor::List(chan) => chan.receiver().accept(token, cx),
             ReceiverFlavor::Zero(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Heap(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Priority(chan) => chan.receiver().accept(token, cx),
             ReceiverFlavor::After(chan) => chan.accept(token, cx),
             ReceiverFlavor::Tick(chan) => chan.accept(token, cx),
             ReceiverFlavor::Never(chan) => chan.accept(token, cx),
+            ReceiverFlavor::Ready(chan) => chan.accept(token, cx),
+            ReceiverFlavor::Merge(chan) => chan.accept(token, cx),
         }
     }
 
@@ -1358,9 +5134,15 @@ impl<T> SelectHandle for Receiver<T> {
             ReceiverFlavor::Array(chan) => chan.receiver().is_ready(),
             ReceiverFlavor::List(chan) => chan.receiver().is_ready(),
             ReceiverFlavor::Zero(chan) => chan.receiver().is_ready(),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().is_ready(),
+            ReceiverFlavor::Heap(chan) => chan.receiver().is_ready(),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().is_ready(),
+            ReceiverFlavor::Priority(chan) => chan.receiver().is_ready(),
             ReceiverFlavor::After(chan) => chan.is_ready(),
             ReceiverFlavor::Tick(chan) => chan.is_ready(),
             ReceiverFlavor::Never(chan) => chan.is_ready(),
+            ReceiverFlavor::Ready(chan) => chan.is_ready(),
+            ReceiverFlavor::Merge(chan) => chan.is_ready(),
         }
     }
 
@@ -1369,9 +5151,15 @@ impl<T> SelectHandle for Receiver<T> {
             ReceiverFlavor::Array(chan) => chan.receiver().watch(oper, cx),
             ReceiverFlavor::List(chan) => chan.receiver().watch(oper, cx),
             ReceiverFlavor::Zero(chan) => chan.receiver().watch(oper, cx),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().watch(oper, cx),
+            ReceiverFlavor::Heap(chan) => chan.receiver().watch(oper, cx),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().watch(oper, cx),
+            ReceiverFlavor::Priority(chan) => chan.receiver().watch(oper, cx),
             ReceiverFlavor::After(chan) => chan.watch(oper, cx),
             ReceiverFlavor::Tick(chan) => chan.watch(oper, cx),
             ReceiverFlavor::Never(chan) => chan.watch(oper, cx),
+            ReceiverFlavor::Ready(chan) => chan.watch(oper, cx),
+            ReceiverFlavor::Merge(chan) => chan.watch(oper, cx),
         }
     }
 
@@ -1380,9 +5168,15 @@ impl<T> SelectHandle for Receiver<T> {
             ReceiverFlavor::Array(chan) => chan.receiver().unwatch(oper),
             ReceiverFlavor::List(chan) => chan.receiver().unwatch(oper),
             ReceiverFlavor::Zero(chan) => chan.receiver().unwatch(oper),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().unwatch(oper),
+            ReceiverFlavor::Heap(chan) => chan.receiver().unwatch(oper),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().unwatch(oper),
+            ReceiverFlavor::Priority(chan) => chan.receiver().unwatch(oper),
             ReceiverFlavor::After(chan) => chan.unwatch(oper),
             ReceiverFlavor::Tick(chan) => chan.unwatch(oper),
             ReceiverFlavor::Never(chan) => chan.unwatch(oper),
+            ReceiverFlavor::Ready(chan) => chan.unwatch(oper),
+            ReceiverFlavor::Merge(chan) => chan.unwatch(oper),
         }
     }
 }
@@ -1393,15 +5187,26 @@ pub unsafe fn write<T>(s: &Sender<T>, token: &mut Token, msg: T) -> Result<(), T
         SenderFlavor::Array(chan) => chan.write(token, msg),
         SenderFlavor::List(chan) => chan.write(token, msg),
         SenderFlavor::Zero(chan) => chan.write(token, msg),
+        SenderFlavor::Oneshot(chan) => chan.write(token, msg),
+        SenderFlavor::Heap(chan) => chan.write(token, msg),
+        SenderFlavor::Coalesce(chan) => chan.write(token, msg),
     }
+    .map(|()| {
+        s.observe_len();
+        s.replay_complete(true);
+    })
 }
 
 /// Reads a message from the channel.
 pub unsafe fn read<T>(r: &Receiver<T>, token: &mut Token) -> Result<T, ()> {
-    match &r.flavor {
+    let res = match &r.flavor {
         ReceiverFlavor::Array(chan) => chan.read(token),
         ReceiverFlavor::List(chan) => chan.read(token),
         ReceiverFlavor::Zero(chan) => chan.read(token),
+        ReceiverFlavor::Oneshot(chan) => chan.read(token),
+        ReceiverFlavor::Heap(chan) => chan.read(token),
+        ReceiverFlavor::Coalesce(chan) => chan.read(token),
+        ReceiverFlavor::Priority(chan) => chan.read(token),
         ReceiverFlavor::After(chan) => {
             mem::transmute_copy::<Result<Instant, ()>, Result<T, ()>>(&chan.read(token))
         }
@@ -1409,5 +5214,12 @@ pub unsafe fn read<T>(r: &Receiver<T>, token: &mut Token) -> Result<T, ()> {
             mem::transmute_copy::<Result<Instant, ()>, Result<T, ()>>(&chan.read(token))
         }
         ReceiverFlavor::Never(chan) => chan.read(token),
+        ReceiverFlavor::Ready(chan) => chan.read(token),
+        ReceiverFlavor::Merge(chan) => chan.read(token),
+    };
+    if res.is_ok() {
+        r.mark_received();
+        r.replay_complete(true);
     }
+    res
 }
@@ -0,0 +1,209 @@
+//! Scopes whose channels interrupt blocked receive operations when the scope ends.
+
+use std::fmt;
+use std::ops;
+use std::thread;
+
+use crossbeam_utils::thread::{scope, Scope};
+
+use cancel::{cancel_token, CancelToken, Canceller};
+use channel::{bounded, unbounded, Receiver, Sender};
+use err::{RecvCancelError, TryRecvError};
+
+/// Creates a scope for spawning threads, with channels that are cancelled when the scope ends.
+///
+/// This works just like `crossbeam_utils::thread::scope`, and threads are spawned the same way.
+/// In addition, channels can be created with [`ChannelScope::channel_bounded`] and
+/// [`ChannelScope::channel_unbounded`]. Once the closure `f` returns or panics, the scope begins
+/// its teardown: every receive operation on those channels that is blocked, or that starts later
+/// while the channel is empty, fails with [`RecvCancelError::Cancelled`]. Only then are the
+/// spawned threads joined. If `f` has panicked, the panic is propagated after that.
+///
+/// This breaks the classic deadlock where the scope waits for a worker that is blocked in a
+/// receive operation, while a sender is still alive somewhere in the scope. Messages that are
+/// already in a channel are still received during teardown, so workers can finish them first.
+///
+/// Send operations are not interrupted. A sender blocked on a full channel is woken up once all
+/// receivers have been dropped, which happens when the receiving threads exit.
+///
+/// [`ChannelScope::channel_bounded`]: struct.ChannelScope.html#method.channel_bounded
+/// [`ChannelScope::channel_unbounded`]: struct.ChannelScope.html#method.channel_unbounded
+/// [`RecvCancelError::Cancelled`]: enum.RecvCancelError.html#variant.Cancelled
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use crossbeam_channel::channel_scope;
+///
+/// let sum = AtomicUsize::new(0);
+///
+/// channel_scope(|scope| {
+///     let (s, r) = scope.channel_bounded(10);
+///     let sum = &sum;
+///
+///     scope.spawn(move |_| {
+///         while let Ok(x) = r.recv() {
+///             sum.fetch_add(x, Ordering::SeqCst);
+///         }
+///     });
+///
+///     for x in 1..4 {
+///         s.send(x).unwrap();
+///     }
+///
+///     // The sender is still alive here, but the scope doesn't wait for the worker forever.
+/// })
+/// .unwrap();
+///
+/// assert_eq!(sum.load(Ordering::SeqCst), 6);
+/// ```
+pub fn channel_scope<'env, F, R>(f: F) -> thread::Result<R>
+where
+    F: for<'scope> FnOnce(&ChannelScope<'scope, 'env>) -> R,
+{
+    let (token, canceller) = cancel_token();
+
+    scope(|scope| {
+        // Dropped when `f` returns or panics, before the threads are joined.
+        let _guard = CancelOnDrop(canceller);
+        f(&ChannelScope { scope, token })
+    })
+}
+
+/// Cancels the token on drop.
+struct CancelOnDrop(Canceller);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// A scope created by [`channel_scope`].
+///
+/// It dereferences to the underlying `crossbeam_utils::thread::Scope`, which spawns threads.
+///
+/// [`channel_scope`]: fn.channel_scope.html
+pub struct ChannelScope<'scope, 'env: 'scope> {
+    /// The underlying scope.
+    scope: &'scope Scope<'env>,
+
+    /// Cancelled when the scope begins its teardown.
+    token: CancelToken,
+}
+
+impl<'scope, 'env> ChannelScope<'scope, 'env> {
+    /// Creates a channel of bounded capacity whose receive operations are cancelled when the
+    /// scope ends.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is too large, just like [`bounded`].
+    ///
+    /// [`bounded`]: fn.bounded.html
+    pub fn channel_bounded<T>(&self, cap: usize) -> (Sender<T>, ScopeReceiver<T>) {
+        let (s, r) = bounded(cap);
+        (s, self.receiver(r))
+    }
+
+    /// Creates a channel of unbounded capacity whose receive operations are cancelled when the
+    /// scope ends.
+    pub fn channel_unbounded<T>(&self) -> (Sender<T>, ScopeReceiver<T>) {
+        let (s, r) = unbounded();
+        (s, self.receiver(r))
+    }
+
+    /// Ties an existing receiver to the scope.
+    pub fn receiver<T>(&self, receiver: Receiver<T>) -> ScopeReceiver<T> {
+        ScopeReceiver {
+            receiver,
+            token: self.token.clone(),
+        }
+    }
+
+    /// Returns `true` if the scope has begun its teardown.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+impl<'scope, 'env> ops::Deref for ChannelScope<'scope, 'env> {
+    type Target = Scope<'env>;
+
+    fn deref(&self) -> &Scope<'env> {
+        self.scope
+    }
+}
+
+impl<'scope, 'env> fmt::Debug for ChannelScope<'scope, 'env> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ChannelScope { .. }")
+    }
+}
+
+/// The receiving side of a channel tied to a [`ChannelScope`].
+///
+/// [`ChannelScope`]: struct.ChannelScope.html
+pub struct ScopeReceiver<T> {
+    /// The underlying receiver.
+    receiver: Receiver<T>,
+
+    /// Cancelled when the scope begins its teardown.
+    token: CancelToken,
+}
+
+impl<T> ScopeReceiver<T> {
+    /// Blocks the current thread until a message is received, the channel is empty and
+    /// disconnected, or the scope begins its teardown.
+    ///
+    /// A ready message is always received, even during teardown. See
+    /// [`Receiver::recv_cancellable`] for more.
+    ///
+    /// [`Receiver::recv_cancellable`]: struct.Receiver.html#method.recv_cancellable
+    pub fn recv(&self) -> Result<T, RecvCancelError> {
+        self.receiver.recv_cancellable(&self.token)
+    }
+
+    /// Attempts to receive a message from the channel without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// Returns `true` if the scope has begun its teardown.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Returns the underlying receiver.
+    ///
+    /// Blocking operations on it are not cancelled when the scope ends.
+    pub fn receiver(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.receiver.len()
+    }
+}
+
+impl<T> Clone for ScopeReceiver<T> {
+    fn clone(&self) -> Self {
+        ScopeReceiver {
+            receiver: self.receiver.clone(),
+            token: self.token.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for ScopeReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ScopeReceiver { .. }")
+    }
+}
@@ -1,14 +1,16 @@
 //! Thread-local context used in select.
 
 use std::cell::Cell;
+use std::fmt;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread::{self, Thread, ThreadId};
 use std::time::Instant;
 
 use crossbeam_utils::Backoff;
 
 use select::Selected;
+use utils::BoxedCallback;
 
 /// Thread-local context used in select.
 #[derive(Debug, Clone)]
@@ -30,9 +32,52 @@ struct Inner {
 
     /// Thread id.
     thread_id: ThreadId,
+
+    /// Called instead of unparking the thread, for contexts created by `with_callback`.
+    callback: Option<Callback>,
+}
+
+/// A callback invoked at most once when a context is unparked.
+struct Callback(Mutex<Option<BoxedCallback>>);
+
+impl Callback {
+    /// Takes the callback out and invokes it, unless it has already been invoked.
+    fn call(&self) {
+        // Take the callback out first so that it doesn't run while the lock is held.
+        let f = self.0.lock().unwrap().take();
+        if let Some(mut f) = f {
+            f();
+        }
+    }
+
+    /// Takes the callback out and drops it without invoking it.
+    fn cancel(&self) {
+        // Drop the callback after the lock is released, for the same reason as in `call`.
+        let f = self.0.lock().unwrap().take();
+        drop(f);
+    }
+}
+
+impl fmt::Debug for Callback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Callback { .. }")
+    }
+}
+
+thread_local! {
+    /// The number of times the current thread has waited in `Context::wait_until`.
+    static WAITS: Cell<usize> = Cell::new(0);
 }
 
 impl Context {
+    /// Returns the number of times the current thread has waited for an operation to be selected.
+    ///
+    /// Comparing the values before and after an operation tells whether it had to block.
+    #[inline]
+    pub fn wait_count() -> usize {
+        WAITS.try_with(|w| w.get()).unwrap_or(0)
+    }
+
     /// Creates a new context for the duration of the closure.
     #[inline]
     pub fn with<F, R>(f: F) -> R
@@ -72,10 +117,46 @@ impl Context {
                 packet: AtomicUsize::new(0),
                 thread: thread::current(),
                 thread_id: thread::current().id(),
+                callback: None,
+            }),
+        }
+    }
+
+    /// Creates a new `Context` that invokes `f` instead of unparking a thread.
+    ///
+    /// Such a context is never waited on. It can be registered with `SelectHandle::watch` so that
+    /// `f` is called by whichever thread notifies the watched channel first.
+    ///
+    /// Note that `f` runs inside `unpark`, which channels call while holding the lock of their
+    /// waker. It must not use the channel it was registered with, or it will deadlock.
+    pub fn with_callback<F>(f: F) -> Context
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let mut f = Some(f);
+        let f = move || {
+            if let Some(f) = f.take() {
+                f();
+            }
+        };
+
+        Context {
+            inner: Arc::new(Inner {
+                select: AtomicUsize::new(Selected::Waiting.into()),
+                packet: AtomicUsize::new(0),
+                thread: thread::current(),
+                thread_id: thread::current().id(),
+                callback: Some(Callback(Mutex::new(Some(Box::new(f))))),
             }),
         }
     }
 
+    /// Returns the address of the shared state, which is unique for as long as the context lives.
+    #[inline]
+    pub fn addr(&self) -> usize {
+        &*self.inner as *const Inner as usize
+    }
+
     /// Resets `select` and `packet`.
     #[inline]
     fn reset(&self) {
@@ -136,6 +217,8 @@ impl Context {
     /// If the deadline is reached, `Selected::Aborted` will be selected.
     #[inline]
     pub fn wait_until(&self, deadline: Option<Instant>) -> Selected {
+        let _ = WAITS.try_with(|w| w.set(w.get().wrapping_add(1)));
+
         // Spin for a short time, waiting until an operation is selected.
         let backoff = Backoff::new();
         loop {
@@ -151,6 +234,24 @@ impl Context {
             }
         }
 
+        // Mark the current thread as blocked until it returns, unless there is a deadline.
+        #[cfg(feature = "deadlock_detection")]
+        let _blocked = match deadline {
+            None => Some(::deadlock::Blocked::new()),
+            Some(_) => None,
+        };
+
+        trace_event!(?deadline, "parking");
+        #[cfg(feature = "tracing")]
+        let parked_at = Instant::now();
+
+        let sel = self.park_until(deadline);
+        trace_event!(waited = ?parked_at.elapsed(), selected = ?sel, "woke up");
+        sel
+    }
+
+    /// Parks the current thread until an operation is selected or the deadline is reached.
+    fn park_until(&self, deadline: Option<Instant>) -> Selected {
         loop {
             // Check whether an operation has been selected.
             let sel = Selected::from(self.inner.select.load(Ordering::Acquire));
@@ -178,9 +279,22 @@ impl Context {
     }
 
     /// Unparks the thread this context belongs to.
+    ///
+    /// If the context was created by `with_callback`, the callback is invoked instead.
     #[inline]
     pub fn unpark(&self) {
-        self.inner.thread.unpark();
+        match self.inner.callback {
+            None => self.inner.thread.unpark(),
+            Some(ref callback) => callback.call(),
+        }
+    }
+
+    /// Drops the callback without invoking it, if the context was created by `with_callback`.
+    #[inline]
+    pub fn drop_callback(&self) {
+        if let Some(ref callback) = self.inner.callback {
+            callback.cancel();
+        }
     }
 
     /// Returns the id of the thread this context belongs to.
@@ -1,9 +1,19 @@
 //! Reference counter for channels.
 
+use std::cmp;
 use std::isize;
 use std::ops;
 use std::process;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::ptr;
+use std::sync::atomic::{self, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use context::Context;
+use select::{Operation, Selected, Token};
+use utils::{BoxedCallback, Spinlock};
+use waker::SyncWaker;
+use watermark::Watermark;
 
 /// Reference counter internals.
 struct Counter<C> {
@@ -16,16 +26,150 @@ struct Counter<C> {
     /// Set to `true` if the last sender or the last receiver reference deallocates the channel.
     destroy: AtomicBool,
 
+    /// The name of the channel, if it has one.
+    name: Option<Arc<str>>,
+
+    /// The identifier used for recording and replaying operations, if the channel has one.
+    #[cfg(feature = "replay")]
+    replay_id: Option<usize>,
+
+    /// State used by diagnostics and callbacks, or null until it is first needed.
+    extras: AtomicPtr<Extras>,
+
     /// The internal channel.
     chan: C,
 }
 
+/// State that most channels never use, allocated the first time it is needed.
+///
+/// Keeping it out of line keeps the header of a channel that doesn't use it down to a word.
+struct Extras {
+    /// The instant at which the extras were allocated.
+    created: Instant,
+
+    /// Milliseconds between `created` and the last successful receive operation.
+    last_recv: AtomicUsize,
+
+    /// Equals `true` once `idle_duration` has been called, after which receive operations update
+    /// `last_recv`.
+    track_idle: AtomicBool,
+
+    /// The high watermark set by the receiving side.
+    watermark: Watermark,
+
+    /// Senders waiting for the last receiver to be released.
+    closed: SyncWaker,
+
+    /// Callbacks to run once the channel is disconnected, or `None` if they have already run.
+    on_disconnect: Spinlock<Option<Vec<BoxedCallback>>>,
+}
+
+impl Extras {
+    /// Stores the current time as the time of the last successful receive operation.
+    fn store_last_recv(&self) {
+        let elapsed = self.created.elapsed();
+        let ms = elapsed.as_secs() * 1000 + u64::from(elapsed.subsec_nanos() / 1_000_000);
+
+        // Saturate rather than wrap around if `usize` is too small.
+        let ms = cmp::min(ms, usize::max_value() as u64) as usize;
+        self.last_recv.store(ms, Ordering::Relaxed);
+    }
+}
+
+impl<C> Counter<C> {
+    /// Returns the extras, unless they haven't been allocated yet.
+    #[inline]
+    fn extras(&self) -> Option<&Extras> {
+        unsafe { self.extras.load(Ordering::Acquire).as_ref() }
+    }
+
+    /// Returns the extras, allocating them if this is the first time they are needed.
+    fn extras_or_init(&self) -> &Extras {
+        if let Some(extras) = self.extras() {
+            return extras;
+        }
+
+        let new = Box::into_raw(Box::new(Extras {
+            created: Instant::now(),
+            last_recv: AtomicUsize::new(0),
+            track_idle: AtomicBool::new(false),
+            watermark: Watermark::new(),
+            closed: SyncWaker::new(),
+            on_disconnect: Spinlock::new(Some(Vec::new())),
+        }));
+
+        // This is `SeqCst` to pair with the fence in `disconnected`.
+        match self.extras.compare_exchange(
+            ptr::null_mut(),
+            new,
+            Ordering::SeqCst,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => unsafe { &*new },
+            Err(current) => unsafe {
+                // Another thread got there first.
+                drop(Box::from_raw(new));
+                &*current
+            },
+        }
+    }
+
+    /// Returns `true` if all sender or all receiver references have been released.
+    fn is_disconnected(&self) -> bool {
+        self.senders.load(Ordering::SeqCst) == 0 || self.receivers.load(Ordering::SeqCst) == 0
+    }
+
+    /// Wakes up the senders waiting for the last receiver and runs the disconnect callbacks,
+    /// unless they have already run.
+    ///
+    /// The callbacks are taken out first so that they don't run while the lock is held.
+    fn disconnected(&self) {
+        // Either this sees the extras, or whoever allocates them sees the released reference.
+        atomic::fence(Ordering::SeqCst);
+        let extras = match self.extras() {
+            None => return,
+            Some(extras) => extras,
+        };
+
+        if self.receivers.load(Ordering::SeqCst) == 0 {
+            extras.closed.disconnect();
+        }
+
+        let callbacks = extras.on_disconnect.lock().take();
+        if let Some(callbacks) = callbacks {
+            for mut f in callbacks {
+                f();
+            }
+        }
+    }
+}
+
+impl<C> Drop for Counter<C> {
+    fn drop(&mut self) {
+        let extras = *self.extras.get_mut();
+        if !extras.is_null() {
+            unsafe {
+                drop(Box::from_raw(extras));
+            }
+        }
+    }
+}
+
 /// Wraps a channel into the reference counter.
 pub fn new<C>(chan: C) -> (Sender<C>, Receiver<C>) {
+    with_name(chan, None)
+}
+
+/// Wraps a channel with an optional name into the reference counter.
+pub fn with_name<C>(chan: C, name: Option<Arc<str>>) -> (Sender<C>, Receiver<C>) {
     let counter = Box::into_raw(Box::new(Counter {
         senders: AtomicUsize::new(1),
         receivers: AtomicUsize::new(1),
         destroy: AtomicBool::new(false),
+        name,
+        #[cfg(feature = "replay")]
+        replay_id: ::replay::next_channel_id(),
+        extras: AtomicPtr::new(ptr::null_mut()),
         chan,
     }));
     let s = Sender { counter };
@@ -44,7 +188,36 @@ impl<C> Sender<C> {
         unsafe { &*self.counter }
     }
 
+    /// Returns the name of the channel.
+    pub fn name(&self) -> Option<&str> {
+        self.counter().name.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Returns the identifier used for recording and replaying operations.
+    #[cfg(feature = "replay")]
+    pub fn replay_id(&self) -> Option<usize> {
+        self.counter().replay_id
+    }
+
+    /// Returns the address of the shared counter, which identifies the channel.
+    pub fn as_ptr(&self) -> *const () {
+        self.counter as *const ()
+    }
+
+    /// Checks the length of the channel against the high watermark, if one has ever been set.
+    ///
+    /// The length is only computed if there is a watermark.
+    #[inline]
+    pub fn observe_watermark<F: FnOnce() -> usize>(&self, len: F) {
+        if let Some(extras) = self.counter().extras() {
+            extras.watermark.observe(len);
+        }
+    }
+
     /// Acquires another sender reference.
+    ///
+    /// The count cannot drop to zero while this runs because `self` is a live sender reference and
+    /// keeps it positive, so a new reference is never created after the last one was released.
     pub fn acquire(&self) -> Sender<C> {
         let count = self.counter().senders.fetch_add(1, Ordering::Relaxed);
 
@@ -60,12 +233,84 @@ impl<C> Sender<C> {
         }
     }
 
+    /// Acquires a receiver reference, unless all receiver references have been released.
+    pub fn acquire_receiver(&self) -> Option<Receiver<C>> {
+        let receivers = &self.counter().receivers;
+        let mut count = receivers.load(Ordering::Relaxed);
+
+        loop {
+            // Once the last receiver is released, the channel is disconnected and may be
+            // deallocated by the last sender, so it must not be revived.
+            if count == 0 {
+                return None;
+            }
+
+            // See `acquire` for why we abort.
+            if count > isize::MAX as usize {
+                process::abort();
+            }
+
+            match receivers.compare_exchange_weak(
+                count,
+                count + 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    return Some(Receiver {
+                        counter: self.counter,
+                    })
+                }
+                Err(c) => count = c,
+            }
+        }
+    }
+
+    /// Returns `true` if all receiver references have been released.
+    pub fn is_closed(&self) -> bool {
+        self.counter().receivers.load(Ordering::SeqCst) == 0
+    }
+
+    /// Blocks until all receiver references are released or the deadline is reached.
+    ///
+    /// Returns `true` if all receiver references have been released.
+    pub fn wait_closed(&self, deadline: Option<Instant>) -> bool {
+        if self.is_closed() {
+            return true;
+        }
+
+        let closed = &self.counter().extras_or_init().closed;
+        Context::with(|cx| {
+            // Prepare for blocking until the last receiver wakes us up.
+            let mut token = Token::default();
+            let oper = Operation::hook(&mut token);
+            closed.register(oper, cx);
+
+            // Has the last receiver been released just now?
+            if self.is_closed() {
+                let _ = cx.try_select(Selected::Aborted);
+            }
+
+            // Block the current thread.
+            cx.wait_until(deadline);
+            closed.unregister(oper);
+        });
+
+        self.is_closed()
+    }
+
     /// Releases the sender reference.
     ///
     /// Function `disconnect` will be called if this is the last sender reference.
     pub unsafe fn release<F: FnOnce(&C) -> bool>(&self, disconnect: F) {
         if self.counter().senders.fetch_sub(1, Ordering::AcqRel) == 1 {
-            disconnect(&self.counter().chan);
+            if disconnect(&self.counter().chan) {
+                trace_event!(
+                    name = self.name().unwrap_or(""),
+                    "all senders dropped, disconnected"
+                );
+            }
+            self.counter().disconnected();
 
             if self.counter().destroy.swap(true, Ordering::AcqRel) {
                 drop(Box::from_raw(self.counter));
@@ -99,7 +344,100 @@ impl<C> Receiver<C> {
         unsafe { &*self.counter }
     }
 
+    /// Returns the name of the channel.
+    pub fn name(&self) -> Option<&str> {
+        self.counter().name.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Returns the identifier used for recording and replaying operations.
+    #[cfg(feature = "replay")]
+    pub fn replay_id(&self) -> Option<usize> {
+        self.counter().replay_id
+    }
+
+    /// Returns the address of the shared counter, which identifies the channel.
+    pub fn as_ptr(&self) -> *const () {
+        self.counter as *const ()
+    }
+
+    /// Records that a message has just been received from the channel.
+    ///
+    /// This doesn't read the clock unless `idle_duration` has been called before.
+    #[inline]
+    pub fn mark_received(&self) {
+        if let Some(extras) = self.counter().extras() {
+            if extras.track_idle.load(Ordering::Relaxed) {
+                extras.store_last_recv();
+            }
+        }
+    }
+
+    /// Returns the time elapsed since the last successful receive operation, or since the first
+    /// call to this method if nothing has been received since.
+    pub fn idle_duration(&self) -> Duration {
+        let extras = self.counter().extras_or_init();
+
+        // Receive operations are only tracked from the first call on.
+        if !extras.track_idle.swap(true, Ordering::Relaxed) {
+            extras.store_last_recv();
+            return Duration::from_secs(0);
+        }
+
+        let last = Duration::from_millis(extras.last_recv.load(Ordering::Relaxed) as u64);
+        extras
+            .created
+            .elapsed()
+            .checked_sub(last)
+            .unwrap_or_else(|| Duration::from_secs(0))
+    }
+
+    /// Returns the high watermark of the channel, allocating it if it has never been set.
+    pub fn watermark(&self) -> &Watermark {
+        &self.counter().extras_or_init().watermark
+    }
+
+    /// Removes the high watermark of the channel, if one has ever been set.
+    pub fn clear_watermark(&self) {
+        if let Some(extras) = self.counter().extras() {
+            extras.watermark.clear();
+        }
+    }
+
+    /// Registers a callback to run once the channel is disconnected.
+    ///
+    /// If the channel is already disconnected, the callback runs immediately.
+    pub fn on_disconnect_once<F: FnOnce() + Send + 'static>(&self, f: F) {
+        let mut f = Some(f);
+        let mut f = move || {
+            if let Some(f) = f.take() {
+                f();
+            }
+        };
+
+        {
+            let mut callbacks = self.counter().extras_or_init().on_disconnect.lock();
+            match *callbacks {
+                Some(ref mut callbacks) => callbacks.push(Box::new(f)),
+                None => {
+                    drop(callbacks);
+                    f();
+                    return;
+                }
+            }
+        }
+
+        // The extras may have been allocated just now, after the last reference was released
+        // without finding them, so check whether the callback has to run right away.
+        atomic::fence(Ordering::SeqCst);
+        if self.counter().is_disconnected() {
+            self.counter().disconnected();
+        }
+    }
+
     /// Acquires another receiver reference.
+    ///
+    /// The count cannot drop to zero while this runs because `self` is a live receiver reference and
+    /// keeps it positive, so a new reference is never created after the last one was released.
     pub fn acquire(&self) -> Receiver<C> {
         let count = self.counter().receivers.fetch_add(1, Ordering::Relaxed);
 
@@ -115,12 +453,51 @@ impl<C> Receiver<C> {
         }
     }
 
+    /// Acquires a sender reference, unless all sender references have been released.
+    pub fn acquire_sender(&self) -> Option<Sender<C>> {
+        let senders = &self.counter().senders;
+        let mut count = senders.load(Ordering::Relaxed);
+
+        loop {
+            // Once the last sender is released, the channel is disconnected and may be
+            // deallocated by the last receiver, so it must not be revived.
+            if count == 0 {
+                return None;
+            }
+
+            // See `acquire` for why we abort.
+            if count > isize::MAX as usize {
+                process::abort();
+            }
+
+            match senders.compare_exchange_weak(
+                count,
+                count + 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    return Some(Sender {
+                        counter: self.counter,
+                    })
+                }
+                Err(c) => count = c,
+            }
+        }
+    }
+
     /// Releases the receiver reference.
     ///
     /// Function `disconnect` will be called if this is the last receiver reference.
     pub unsafe fn release<F: FnOnce(&C) -> bool>(&self, disconnect: F) {
         if self.counter().receivers.fetch_sub(1, Ordering::AcqRel) == 1 {
-            disconnect(&self.counter().chan);
+            if disconnect(&self.counter().chan) {
+                trace_event!(
+                    name = self.name().unwrap_or(""),
+                    "all receivers dropped, disconnected"
+                );
+            }
+            self.counter().disconnected();
 
             if self.counter().destroy.swap(true, Ordering::AcqRel) {
                 drop(Box::from_raw(self.counter));
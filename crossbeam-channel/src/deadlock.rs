@@ -0,0 +1,137 @@
+//! Registry of threads blocked on channel operations, for debugging deadlocks.
+//!
+//! This module is only compiled with the `deadlock_detection` feature.
+
+use std::collections::HashMap;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, Thread, ThreadId};
+use std::time::Instant;
+
+/// Information about a thread that is blocked on a channel operation without a timeout.
+///
+/// Returned by [`blocked_threads`].
+///
+/// [`blocked_threads`]: fn.blocked_threads.html
+#[derive(Debug, Clone)]
+pub struct BlockedThread {
+    /// The blocked thread.
+    thread: Thread,
+
+    /// The instant at which the thread blocked.
+    since: Instant,
+}
+
+impl BlockedThread {
+    /// Returns a handle to the blocked thread.
+    pub fn thread(&self) -> &Thread {
+        &self.thread
+    }
+
+    /// Returns the instant at which the thread blocked.
+    pub fn since(&self) -> Instant {
+        self.since
+    }
+}
+
+/// Returns the global registry of blocked threads, initializing it if necessary.
+fn registry() -> &'static Mutex<HashMap<ThreadId, BlockedThread>> {
+    static REGISTRY: AtomicPtr<Mutex<HashMap<ThreadId, BlockedThread>>> =
+        AtomicPtr::new(ptr::null_mut());
+
+    let mut reg = REGISTRY.load(Ordering::Acquire);
+
+    if reg.is_null() {
+        let new = Box::into_raw(Box::new(Mutex::new(HashMap::new())));
+
+        // Install the new registry, unless another thread has beaten us to it.
+        reg = match REGISTRY.compare_exchange(reg, new, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => new,
+            Err(r) => {
+                unsafe { drop(Box::from_raw(new)) };
+                r
+            }
+        };
+    }
+
+    // The registry is never deallocated.
+    unsafe { &*reg }
+}
+
+/// Marks the current thread as blocked for as long as this guard is alive.
+pub struct Blocked {
+    thread_id: ThreadId,
+}
+
+impl Blocked {
+    /// Registers the current thread as blocked.
+    pub fn new() -> Blocked {
+        let thread = thread::current();
+        let thread_id = thread.id();
+        let info = BlockedThread {
+            thread,
+            since: Instant::now(),
+        };
+
+        if let Ok(mut reg) = registry().lock() {
+            reg.insert(thread_id, info);
+        }
+        Blocked { thread_id }
+    }
+}
+
+impl Drop for Blocked {
+    fn drop(&mut self) {
+        if let Ok(mut reg) = registry().lock() {
+            reg.remove(&self.thread_id);
+        }
+    }
+}
+
+/// Returns all threads that are currently blocked on a channel operation without a timeout.
+///
+/// This is a debugging aid, available with the `deadlock_detection` feature. A thread is listed
+/// while it is parked inside a blocking operation without a deadline, such as [`Sender::send`],
+/// [`Receiver::recv`], or [`Select::select`]. Operations with a timeout are not tracked because
+/// they always make progress eventually.
+///
+/// This is a dump of blocked threads, not a deadlock detector. Neither the channels the threads
+/// wait on nor the threads holding the other ends of those channels are tracked, because channel
+/// handles move between threads freely. Hence no wait-for graph is built and no cycles are
+/// detected: a listed thread may simply be waiting for a message that will arrive later. It is up
+/// to the caller to decide whether the program is stuck, for example by checking that every
+/// thread that could wake the listed threads is listed as well, and that they stay listed.
+///
+/// [`Sender::send`]: struct.Sender.html#method.send
+/// [`Receiver::recv`]: struct.Receiver.html#method.recv
+/// [`Select::select`]: struct.Select.html#method.select
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{blocked_threads, unbounded};
+///
+/// let (s, r) = unbounded::<i32>();
+///
+/// let handle = thread::Builder::new()
+///     .name("waiter".to_string())
+///     .spawn(move || r.recv())
+///     .unwrap();
+///
+/// thread::sleep(Duration::from_secs(1));
+/// let blocked = blocked_threads();
+/// assert_eq!(blocked.len(), 1);
+/// assert_eq!(blocked[0].thread().name(), Some("waiter"));
+///
+/// drop(s);
+/// handle.join().unwrap().unwrap_err();
+/// ```
+pub fn blocked_threads() -> Vec<BlockedThread> {
+    match registry().lock() {
+        Ok(reg) => reg.values().cloned().collect(),
+        Err(_) => Vec::new(),
+    }
+}
@@ -45,6 +45,56 @@ pub enum SendTimeoutError<T> {
     Disconnected(T),
 }
 
+/// An error returned from the [`send_once`] method.
+///
+/// The error contains the message being sent so it can be recovered.
+///
+/// [`send_once`]: struct.Sender.html#method.send_once
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SendOnceError<T> {
+    /// The message could not be sent because a message has already been sent into this
+    /// single-use channel.
+    AlreadySent(T),
+
+    /// The message could not be sent because the channel is disconnected.
+    Disconnected(T),
+}
+
+/// An error returned from the [`reserve`] method.
+///
+/// [`reserve`]: struct.Sender.html#method.reserve
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ReserveSlotError {
+    /// The slot could not be reserved because the channel doesn't keep messages in a ring buffer.
+    ///
+    /// Only channels created by [`bounded`] with a positive capacity support this.
+    ///
+    /// [`bounded`]: fn.bounded.html
+    Unsupported,
+
+    /// The slot could not be reserved because the channel is disconnected.
+    Disconnected,
+}
+
+/// An error returned from the [`try_reserve`] method.
+///
+/// [`try_reserve`]: struct.Sender.html#method.try_reserve
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TryReserveSlotError {
+    /// The slot could not be reserved because the channel doesn't keep messages in a ring buffer.
+    ///
+    /// Only channels created by [`bounded`] with a positive capacity support this.
+    ///
+    /// [`bounded`]: fn.bounded.html
+    Unsupported,
+
+    /// The slot could not be reserved because the channel is full.
+    Full,
+
+    /// The slot could not be reserved because the channel is disconnected.
+    Disconnected,
+}
+
 /// An error returned from the [`recv`] method.
 ///
 /// A message could not be received because the channel is empty and disconnected.
@@ -83,6 +133,72 @@ pub enum RecvTimeoutError {
     Disconnected,
 }
 
+/// An error returned from the [`recv_cancellable`] method.
+///
+/// [`recv_cancellable`]: struct.Receiver.html#method.recv_cancellable
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvCancelError {
+    /// A message could not be received because the channel is empty and the operation was
+    /// cancelled.
+    Cancelled,
+
+    /// The message could not be received because the channel is empty and disconnected.
+    Disconnected,
+}
+
+/// An error returned from the [`recv_bounded_spins`] method.
+///
+/// [`recv_bounded_spins`]: struct.Receiver.html#method.recv_bounded_spins
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvSpinError {
+    /// A message could not be received because the retry budget ran out.
+    ///
+    /// The channel may still hold messages if other receivers kept taking them first.
+    Exhausted,
+
+    /// The message could not be received because the channel is empty and disconnected.
+    Disconnected,
+}
+
+/// An error returned from the [`recv_peek_decide`] method.
+///
+/// [`recv_peek_decide`]: struct.Receiver.html#method.recv_peek_decide
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PeekError {
+    /// The message could not be inspected because the channel has no front to inspect.
+    ///
+    /// Only channels created by [`bounded`] with a positive capacity or by [`unbounded`] support
+    /// this.
+    ///
+    /// [`bounded`]: fn.bounded.html
+    /// [`unbounded`]: fn.unbounded.html
+    Unsupported,
+
+    /// The message could not be received because the channel is empty and disconnected.
+    Disconnected,
+}
+
+/// An error returned from the [`peek_contiguous`] method.
+///
+/// [`peek_contiguous`]: struct.Receiver.html#method.peek_contiguous
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TryPeekError {
+    /// The messages could not be inspected because the channel doesn't keep them in a ring buffer.
+    ///
+    /// Only channels created by [`bounded`] with a positive capacity support this.
+    ///
+    /// [`bounded`]: fn.bounded.html
+    Unsupported,
+
+    /// The messages could not be inspected because the channel is empty, or because another
+    /// receiver is inspecting them.
+    Empty,
+
+    /// The messages could not be inspected because the channel is empty and disconnected, or
+    /// because it is disconnected while another receiver is inspecting them.
+    Disconnected,
+}
+
 /// An error returned from the [`try_select`] method.
 ///
 /// Failed because none of the channel operations were ready.
@@ -115,6 +231,28 @@ pub struct TryReadyError;
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub struct ReadyTimeoutError;
 
+/// An error returned from the [`bounded_checked`] function.
+///
+/// Failed because the buffer for the requested capacity is too large to ever be allocated.
+///
+/// [`bounded_checked`]: fn.bounded_checked.html
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct CapacityError;
+
+/// An error returned from the [`call`] method.
+///
+/// [`call`]: struct.RpcClient.html#method.call
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum CallError<T> {
+    /// The request could not be sent because all servers have been dropped.
+    ///
+    /// The error contains the request so it can be recovered.
+    Disconnected(T),
+
+    /// A server received the request, but dropped its responder without replying.
+    NoReply,
+}
+
 impl<T> fmt::Debug for SendError<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         "SendError(..)".fmt(f)
@@ -219,17 +357,17 @@ impl<T> TrySendError<T> {
 
     /// Returns `true` if the send operation failed because the channel is full.
     pub fn is_full(&self) -> bool {
-        match self {
-            TrySendError::Full(_) => true,
-            _ => false,
+        match *self {
+            TrySendError::Full(..) => true,
+            TrySendError::Disconnected(..) => false,
         }
     }
 
     /// Returns `true` if the send operation failed because the channel is disconnected.
     pub fn is_disconnected(&self) -> bool {
-        match self {
-            TrySendError::Disconnected(_) => true,
-            _ => false,
+        match *self {
+            TrySendError::Disconnected(..) => true,
+            TrySendError::Full(..) => false,
         }
     }
 }
@@ -291,21 +429,166 @@ impl<T> SendTimeoutError<T> {
 
     /// Returns `true` if the send operation timed out.
     pub fn is_timeout(&self) -> bool {
-        match self {
-            SendTimeoutError::Timeout(_) => true,
-            _ => false,
+        match *self {
+            SendTimeoutError::Timeout(..) => true,
+            SendTimeoutError::Disconnected(..) => false,
         }
     }
 
     /// Returns `true` if the send operation failed because the channel is disconnected.
     pub fn is_disconnected(&self) -> bool {
+        match *self {
+            SendTimeoutError::Disconnected(..) => true,
+            SendTimeoutError::Timeout(..) => false,
+        }
+    }
+}
+
+impl<T> fmt::Debug for SendOnceError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SendOnceError::AlreadySent(..) => "AlreadySent(..)".fmt(f),
+            SendOnceError::Disconnected(..) => "Disconnected(..)".fmt(f),
+        }
+    }
+}
+
+impl<T> fmt::Display for SendOnceError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SendOnceError::AlreadySent(..) => "sending on a used-up channel".fmt(f),
+            SendOnceError::Disconnected(..) => "sending on a disconnected channel".fmt(f),
+        }
+    }
+}
+
+impl<T: Send> error::Error for SendOnceError<T> {
+    fn description(&self) -> &str {
+        match *self {
+            SendOnceError::AlreadySent(..) => "sending on a used-up channel",
+            SendOnceError::Disconnected(..) => "sending on a disconnected channel",
+        }
+    }
+}
+
+impl<T> SendOnceError<T> {
+    /// Unwraps the message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::oneshot;
+    ///
+    /// let (s, r) = oneshot();
+    /// s.send(1).unwrap();
+    ///
+    /// if let Err(err) = s.send_once(2) {
+    ///     assert_eq!(err.into_inner(), 2);
+    /// }
+    /// # drop(r);
+    /// ```
+    pub fn into_inner(self) -> T {
         match self {
-            SendTimeoutError::Disconnected(_) => true,
-            _ => false,
+            SendOnceError::AlreadySent(v) => v,
+            SendOnceError::Disconnected(v) => v,
+        }
+    }
+
+    /// Returns `true` if the send operation failed because a message has already been sent.
+    pub fn is_already_sent(&self) -> bool {
+        match *self {
+            SendOnceError::AlreadySent(..) => true,
+            SendOnceError::Disconnected(..) => false,
+        }
+    }
+
+    /// Returns `true` if the send operation failed because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        match *self {
+            SendOnceError::Disconnected(..) => true,
+            SendOnceError::AlreadySent(..) => false,
         }
     }
 }
 
+impl fmt::Display for ReserveSlotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReserveSlotError::Unsupported => "reserving in a channel without a ring buffer".fmt(f),
+            ReserveSlotError::Disconnected => "reserving in a disconnected channel".fmt(f),
+        }
+    }
+}
+
+impl error::Error for ReserveSlotError {
+    fn description(&self) -> &str {
+        match *self {
+            ReserveSlotError::Unsupported => "reserving in a channel without a ring buffer",
+            ReserveSlotError::Disconnected => "reserving in a disconnected channel",
+        }
+    }
+}
+
+impl ReserveSlotError {
+    /// Returns `true` if the channel doesn't support reserving slots.
+    pub fn is_unsupported(&self) -> bool {
+        *self == ReserveSlotError::Unsupported
+    }
+
+    /// Returns `true` if the slot could not be reserved because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        *self == ReserveSlotError::Disconnected
+    }
+}
+
+impl fmt::Display for TryReserveSlotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TryReserveSlotError::Unsupported => {
+                "reserving in a channel without a ring buffer".fmt(f)
+            }
+            TryReserveSlotError::Full => "reserving in a full channel".fmt(f),
+            TryReserveSlotError::Disconnected => "reserving in a disconnected channel".fmt(f),
+        }
+    }
+}
+
+impl error::Error for TryReserveSlotError {
+    fn description(&self) -> &str {
+        match *self {
+            TryReserveSlotError::Unsupported => "reserving in a channel without a ring buffer",
+            TryReserveSlotError::Full => "reserving in a full channel",
+            TryReserveSlotError::Disconnected => "reserving in a disconnected channel",
+        }
+    }
+}
+
+impl From<ReserveSlotError> for TryReserveSlotError {
+    fn from(err: ReserveSlotError) -> TryReserveSlotError {
+        match err {
+            ReserveSlotError::Unsupported => TryReserveSlotError::Unsupported,
+            ReserveSlotError::Disconnected => TryReserveSlotError::Disconnected,
+        }
+    }
+}
+
+impl TryReserveSlotError {
+    /// Returns `true` if the channel doesn't support reserving slots.
+    pub fn is_unsupported(&self) -> bool {
+        *self == TryReserveSlotError::Unsupported
+    }
+
+    /// Returns `true` if the slot could not be reserved because the channel is full.
+    pub fn is_full(&self) -> bool {
+        *self == TryReserveSlotError::Full
+    }
+
+    /// Returns `true` if the slot could not be reserved because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        *self == TryReserveSlotError::Disconnected
+    }
+}
+
 impl fmt::Display for RecvError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         "receiving on an empty and disconnected channel".fmt(f)
@@ -355,17 +638,17 @@ impl From<RecvError> for TryRecvError {
 impl TryRecvError {
     /// Returns `true` if the receive operation failed because the channel is empty.
     pub fn is_empty(&self) -> bool {
-        match self {
+        match *self {
             TryRecvError::Empty => true,
-            _ => false,
+            TryRecvError::Disconnected => false,
         }
     }
 
     /// Returns `true` if the receive operation failed because the channel is disconnected.
     pub fn is_disconnected(&self) -> bool {
-        match self {
+        match *self {
             TryRecvError::Disconnected => true,
-            _ => false,
+            TryRecvError::Empty => false,
         }
     }
 }
@@ -403,21 +686,181 @@ impl From<RecvError> for RecvTimeoutError {
 impl RecvTimeoutError {
     /// Returns `true` if the receive operation timed out.
     pub fn is_timeout(&self) -> bool {
-        match self {
+        match *self {
             RecvTimeoutError::Timeout => true,
-            _ => false,
+            RecvTimeoutError::Disconnected => false,
         }
     }
 
     /// Returns `true` if the receive operation failed because the channel is disconnected.
     pub fn is_disconnected(&self) -> bool {
-        match self {
+        match *self {
             RecvTimeoutError::Disconnected => true,
-            _ => false,
+            RecvTimeoutError::Timeout => false,
+        }
+    }
+}
+
+impl fmt::Display for RecvCancelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecvCancelError::Cancelled => "receive operation was cancelled".fmt(f),
+            RecvCancelError::Disconnected => "channel is empty and disconnected".fmt(f),
+        }
+    }
+}
+
+impl error::Error for RecvCancelError {
+    fn description(&self) -> &str {
+        match *self {
+            RecvCancelError::Cancelled => "receive operation was cancelled",
+            RecvCancelError::Disconnected => "channel is empty and disconnected",
+        }
+    }
+}
+
+impl From<RecvError> for RecvCancelError {
+    fn from(err: RecvError) -> RecvCancelError {
+        match err {
+            RecvError => RecvCancelError::Disconnected,
+        }
+    }
+}
+
+impl RecvCancelError {
+    /// Returns `true` if the receive operation was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        *self == RecvCancelError::Cancelled
+    }
+
+    /// Returns `true` if the receive operation failed because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        *self == RecvCancelError::Disconnected
+    }
+}
+
+impl fmt::Display for RecvSpinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecvSpinError::Exhausted => "receive operation ran out of retries".fmt(f),
+            RecvSpinError::Disconnected => "channel is empty and disconnected".fmt(f),
+        }
+    }
+}
+
+impl error::Error for RecvSpinError {
+    fn description(&self) -> &str {
+        match *self {
+            RecvSpinError::Exhausted => "receive operation ran out of retries",
+            RecvSpinError::Disconnected => "channel is empty and disconnected",
+        }
+    }
+}
+
+impl From<RecvError> for RecvSpinError {
+    fn from(err: RecvError) -> RecvSpinError {
+        match err {
+            RecvError => RecvSpinError::Disconnected,
+        }
+    }
+}
+
+impl RecvSpinError {
+    /// Returns `true` if the receive operation ran out of retries.
+    pub fn is_exhausted(&self) -> bool {
+        *self == RecvSpinError::Exhausted
+    }
+
+    /// Returns `true` if the receive operation failed because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        *self == RecvSpinError::Disconnected
+    }
+}
+
+impl fmt::Display for PeekError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PeekError::Unsupported => "peeking into a channel without a front".fmt(f),
+            PeekError::Disconnected => "receiving on an empty and disconnected channel".fmt(f),
+        }
+    }
+}
+
+impl error::Error for PeekError {
+    fn description(&self) -> &str {
+        match *self {
+            PeekError::Unsupported => "peeking into a channel without a front",
+            PeekError::Disconnected => "receiving on an empty and disconnected channel",
+        }
+    }
+}
+
+impl From<RecvError> for PeekError {
+    fn from(err: RecvError) -> PeekError {
+        match err {
+            RecvError => PeekError::Disconnected,
         }
     }
 }
 
+impl PeekError {
+    /// Returns `true` if the channel doesn't support inspecting its front.
+    pub fn is_unsupported(&self) -> bool {
+        *self == PeekError::Unsupported
+    }
+
+    /// Returns `true` if the receive operation failed because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        *self == PeekError::Disconnected
+    }
+}
+
+impl fmt::Display for TryPeekError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TryPeekError::Unsupported => "peeking into a channel without a ring buffer".fmt(f),
+            TryPeekError::Empty => "receiving on an empty channel".fmt(f),
+            TryPeekError::Disconnected => "receiving on an empty and disconnected channel".fmt(f),
+        }
+    }
+}
+
+impl error::Error for TryPeekError {
+    fn description(&self) -> &str {
+        match *self {
+            TryPeekError::Unsupported => "peeking into a channel without a ring buffer",
+            TryPeekError::Empty => "receiving on an empty channel",
+            TryPeekError::Disconnected => "receiving on an empty and disconnected channel",
+        }
+    }
+}
+
+impl From<TryRecvError> for TryPeekError {
+    fn from(err: TryRecvError) -> TryPeekError {
+        match err {
+            TryRecvError::Empty => TryPeekError::Empty,
+            TryRecvError::Disconnected => TryPeekError::Disconnected,
+        }
+    }
+}
+
+impl TryPeekError {
+    /// Returns `true` if the channel doesn't support inspecting messages in bulk.
+    pub fn is_unsupported(&self) -> bool {
+        *self == TryPeekError::Unsupported
+    }
+
+    /// Returns `true` if the messages could not be inspected because the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        *self == TryPeekError::Empty
+    }
+
+    /// Returns `true` if the messages could not be inspected because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        *self == TryPeekError::Disconnected
+    }
+}
+
 impl fmt::Display for TrySelectError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         "all operations in select would block".fmt(f)
@@ -449,3 +892,81 @@ impl error::Error for SelectTimeoutError {
         None
     }
 }
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        "channel capacity is too large".fmt(f)
+    }
+}
+
+impl error::Error for CapacityError {
+    fn description(&self) -> &str {
+        "channel capacity is too large"
+    }
+}
+
+impl<T> fmt::Debug for CallError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CallError::Disconnected(..) => "Disconnected(..)".fmt(f),
+            CallError::NoReply => "NoReply".fmt(f),
+        }
+    }
+}
+
+impl<T> fmt::Display for CallError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CallError::Disconnected(..) => "calling a disconnected server".fmt(f),
+            CallError::NoReply => "server dropped the request without replying".fmt(f),
+        }
+    }
+}
+
+impl<T: Send> error::Error for CallError<T> {
+    fn description(&self) -> &str {
+        match *self {
+            CallError::Disconnected(..) => "calling a disconnected server",
+            CallError::NoReply => "server dropped the request without replying",
+        }
+    }
+}
+
+impl<T> CallError<T> {
+    /// Unwraps the request, if it was never received by a server.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::rpc;
+    ///
+    /// let (client, server) = rpc::<&str, ()>();
+    /// drop(server);
+    ///
+    /// if let Err(err) = client.call("foo") {
+    ///     assert_eq!(err.into_inner(), Some("foo"));
+    /// }
+    /// ```
+    pub fn into_inner(self) -> Option<T> {
+        match self {
+            CallError::Disconnected(v) => Some(v),
+            CallError::NoReply => None,
+        }
+    }
+
+    /// Returns `true` if the call failed because all servers have been dropped.
+    pub fn is_disconnected(&self) -> bool {
+        match *self {
+            CallError::Disconnected(..) => true,
+            CallError::NoReply => false,
+        }
+    }
+
+    /// Returns `true` if the call failed because the server didn't reply.
+    pub fn is_no_reply(&self) -> bool {
+        match *self {
+            CallError::NoReply => true,
+            CallError::Disconnected(..) => false,
+        }
+    }
+}
@@ -12,23 +12,62 @@
 //!   - Copyright (c) 2010-2011 Dmitry Vyukov
 //!   - Simplified BSD License and Apache License, Version 2.0
 //!   - http://www.1024cores.net/home/code-license
+//!
+//! Memory orderings:
+//!   - A message is published by storing the slot stamp with `Release` and taken after loading the
+//!     stamp with `Acquire`. This is all the synchronization the message itself needs, so the head
+//!     and the tail are otherwise loaded with `Relaxed` on the fast path.
+//!   - Moving the head or the tail is `SeqCst`, and so is the fence before a sender loads the head
+//!     to check whether the channel is full, or a receiver loads the tail to check whether it is
+//!     empty. This puts all moves and all full/empty checks into a single total order, so that a
+//!     failed `try_send` or `try_recv` is consistent with `len`, `is_empty`, and `is_full`, and a
+//!     blocked operation cannot miss a move that happened before it went to sleep.
+//!   - Wake-ups go through `SyncWaker`, which pairs its own `SeqCst` flag with the readiness check
+//!     done after registering.
+//!
+//! Reserved slots:
+//!   - A sender can move the tail and fill the slot later. Until then, the slot stamp keeps the
+//!     lap of the tail but has the mark bit set, so receivers know to wait for it instead of
+//!     spinning. If the reservation is abandoned, the stamp becomes that of a written message with
+//!     the mark bit set, and the first receiver to reach the slot skips it.
+//!   - The mark bit is never set in the stamp of a slot otherwise, because indices are always
+//!     smaller than the mark bit.
+//!
+//! Locked head:
+//!   - A receiver can lock the head in place by setting the mark bit in it, so that it can inspect
+//!     messages before deciding whether to take them. Other receivers treat the channel as empty
+//!     until the head is unlocked, which wakes up all of them, rather than spin on the lock.
+//!   - The thread holding the lock is recorded so that it can't block on the channel itself.
 
 use std::cell::UnsafeCell;
+use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
+#[cfg(all(unix, feature = "os_poll"))]
+use std::os::unix::io::RawFd;
 use std::ptr;
 use std::sync::atomic::{self, AtomicUsize, Ordering};
 use std::time::Instant;
 
 use crossbeam_utils::{Backoff, CachePadded};
 
+use channel::{ParkStrategy, Steal};
 use context::Context;
 use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
 use select::{Operation, SelectHandle, Selected, Token};
+use utils::{self, DropHandler, Spinlock};
 use waker::SyncWaker;
 
-/// A slot in a channel.
-struct Slot<T> {
+/// The number of times `len_exact` scans the buffer before it gives up and falls back to `len`.
+const LEN_EXACT_ATTEMPTS: usize = 64;
+
+/// A slot in the buffer of a bounded channel.
+///
+/// A buffer of slots can be given to [`bounded_in_slice`] so that the channel doesn't allocate one.
+/// Slots are created holding a default message, so the message type must implement `Default`.
+///
+/// [`bounded_in_slice`]: fn.bounded_in_slice.html
+pub struct Slot<T> {
     /// The current stamp.
     stamp: AtomicUsize,
 
@@ -36,6 +75,33 @@ struct Slot<T> {
     msg: UnsafeCell<T>,
 }
 
+impl<T: Default> Slot<T> {
+    /// Creates an empty slot.
+    ///
+    /// The slot holds a default message until a channel takes it over and drops the message. It
+    /// can't be left uninitialized instead because `MaybeUninit` is not available on Rust 1.26,
+    /// the oldest version this crate supports.
+    pub fn new() -> Slot<T> {
+        Slot {
+            // The stamp is set by the channel that takes over the slot.
+            stamp: AtomicUsize::new(0),
+            msg: UnsafeCell::new(T::default()),
+        }
+    }
+}
+
+impl<T: Default> Default for Slot<T> {
+    fn default() -> Slot<T> {
+        Slot::new()
+    }
+}
+
+impl<T> fmt::Debug for Slot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ArraySlot { .. }")
+    }
+}
+
 /// The token type for the array flavor.
 #[derive(Debug)]
 pub struct ArrayToken {
@@ -62,7 +128,8 @@ pub struct Channel<T> {
     ///
     /// This value is a "stamp" consisting of an index into the buffer, a mark bit, and a lap, but
     /// packed into a single `usize`. The lower bits represent the index, while the upper bits
-    /// represent the lap. The mark bit in the head is always zero.
+    /// represent the lap. The mark bit in the head indicates that a receiver has locked the head
+    /// in place in order to inspect the message in it.
     ///
     /// Messages are popped from the head of the channel.
     head: CachePadded<AtomicUsize>,
@@ -79,6 +146,9 @@ pub struct Channel<T> {
     /// The buffer holding slots.
     buffer: *mut Slot<T>,
 
+    /// Whether the buffer was allocated by the channel, rather than provided by the user.
+    owned: bool,
+
     /// The channel capacity.
     cap: usize,
 
@@ -94,23 +164,37 @@ pub struct Channel<T> {
     /// Receivers waiting while the channel is empty and not disconnected.
     receivers: SyncWaker,
 
+    /// The thread that has locked the head, as returned by `utils::thread_token`, or zero.
+    head_owner: AtomicUsize,
+
+    /// How threads wait in blocking send and receive operations.
+    strategy: ParkStrategy,
+
+    /// Receives the messages left in the channel when the last receiver is dropped.
+    drop_handler: Spinlock<Option<DropHandler<T>>>,
+
     /// Indicates that dropping a `Channel<T>` may drop values of type `T`.
     _marker: PhantomData<T>,
 }
 
 impl<T> Channel<T> {
-    /// Creates a bounded channel of capacity `cap`.
-    pub fn with_capacity(cap: usize) -> Self {
+    /// Creates a bounded channel of capacity `cap`, or returns `None` if the capacity is too large.
+    ///
+    /// The capacity is too large if the buffer would take more than `isize::MAX` bytes, or if
+    /// stamps for it cannot be represented. Allocating the buffer is not fallible.
+    pub fn checked_with_capacity(cap: usize) -> Option<Self> {
         assert!(cap > 0, "capacity must be positive");
 
-        // Compute constants `mark_bit` and `one_lap`.
-        let mark_bit = (cap + 1).next_power_of_two();
-        let one_lap = mark_bit * 2;
+        // Stamps for the buffer must be representable.
+        cap.checked_add(1)?
+            .checked_next_power_of_two()?
+            .checked_mul(2)?;
 
-        // Head is initialized to `{ lap: 0, mark: 0, index: 0 }`.
-        let head = 0;
-        // Tail is initialized to `{ lap: 0, mark: 0, index: 0 }`.
-        let tail = 0;
+        // The buffer must fit in the address space.
+        let size = cap.checked_mul(mem::size_of::<Slot<T>>())?;
+        if size > isize::MAX as usize {
+            return None;
+        }
 
         // Allocate a buffer of `cap` slots.
         let buffer = {
@@ -120,6 +204,38 @@ impl<T> Channel<T> {
             ptr
         };
 
+        Some(Self::from_buffer(buffer, cap, true))
+    }
+
+    /// Creates a bounded channel that stores messages in the provided buffer.
+    ///
+    /// The buffer is never deallocated by the channel.
+    pub fn with_buffer(buffer: &'static mut [Slot<T>]) -> Self {
+        assert!(!buffer.is_empty(), "buffer must not be empty");
+
+        // Slots are created holding a default message. Drop it so that the slots are empty.
+        for slot in buffer.iter_mut() {
+            unsafe {
+                ptr::drop_in_place(slot.msg.get());
+            }
+        }
+
+        // A slot is larger than a byte, so the length is far below `usize::MAX / 2` and stamps for
+        // the buffer are always representable.
+        Self::from_buffer(buffer.as_mut_ptr(), buffer.len(), false)
+    }
+
+    /// Creates a bounded channel over a buffer of `cap` slots.
+    fn from_buffer(buffer: *mut Slot<T>, cap: usize, owned: bool) -> Self {
+        // Compute constants `mark_bit` and `one_lap`.
+        let mark_bit = (cap + 1).next_power_of_two();
+        let one_lap = mark_bit * 2;
+
+        // Head is initialized to `{ lap: 0, mark: 0, index: 0 }`.
+        let head = 0;
+        // Tail is initialized to `{ lap: 0, mark: 0, index: 0 }`.
+        let tail = 0;
+
         // Initialize stamps in the slots.
         for i in 0..cap {
             unsafe {
@@ -131,6 +247,7 @@ impl<T> Channel<T> {
 
         Channel {
             buffer,
+            owned,
             cap,
             one_lap,
             mark_bit,
@@ -138,17 +255,105 @@ impl<T> Channel<T> {
             tail: CachePadded::new(AtomicUsize::new(tail)),
             senders: SyncWaker::new(),
             receivers: SyncWaker::new(),
+            head_owner: AtomicUsize::new(0),
+            strategy: ParkStrategy::Block,
+            drop_handler: Spinlock::new(None),
             _marker: PhantomData,
         }
     }
 
+    /// Sets how threads wait in blocking send and receive operations.
+    pub fn with_strategy(mut self, strategy: ParkStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets a handler that receives the messages left in the channel when the last receiver is
+    /// dropped.
+    pub fn with_drop_handler(mut self, handler: DropHandler<T>) -> Self {
+        self.drop_handler = Spinlock::new(Some(handler));
+        self
+    }
+
+    /// Hands the remaining messages over to the drop handler, if there is one.
+    ///
+    /// This is called by the last receiver after disconnecting the channel, so no new messages
+    /// can arrive, and messages that are still being written are waited for. Reserved slots are
+    /// skipped, since the sender may never fill them.
+    pub fn hand_off_remaining(&self) {
+        let handler = self.drop_handler.lock().take();
+        if let Some(mut handler) = handler {
+            while let Ok(msg) = self.try_recv() {
+                handler(msg);
+            }
+
+            // Receiving stops at a reserved slot, so hand off the messages behind it as well.
+            self.hand_off_behind_head(&mut handler);
+        }
+    }
+
+    /// Hands the messages between the head and the tail over to `handler`, without moving the
+    /// head.
+    ///
+    /// The slots are marked as abandoned reservations after their messages are taken out, so that
+    /// they are skipped when the channel is dropped. Reserved slots are left alone. This must only
+    /// be called once there are no receivers left and the channel is disconnected.
+    fn hand_off_behind_head(&self, handler: &mut DropHandler<T>) {
+        let backoff = Backoff::new();
+        let tail = self.tail.load(Ordering::SeqCst) & !self.mark_bit;
+        let mut pos = self.head.load(Ordering::SeqCst);
+
+        while pos != tail {
+            // Deconstruct the position.
+            let index = pos & (self.mark_bit - 1);
+            let lap = pos & !(self.one_lap - 1);
+
+            let slot = unsafe { &*self.buffer.add(index) };
+
+            // Wait until a sender that has moved the tail past the slot has written or reserved it.
+            let mut stamp = slot.stamp.load(Ordering::Acquire);
+            while stamp == pos {
+                backoff.snooze();
+                stamp = slot.stamp.load(Ordering::Acquire);
+            }
+
+            if stamp == pos + 1 {
+                let msg = unsafe { slot.msg.get().read() };
+                slot.stamp.store(stamp | self.mark_bit, Ordering::Release);
+                handler(msg);
+            }
+
+            pos = if index + 1 < self.cap {
+                pos + 1
+            } else {
+                lap.wrapping_add(self.one_lap)
+            };
+        }
+    }
+
+    /// Waits a little before retrying a blocking operation, unless the thread should be parked.
+    ///
+    /// This is called once backing off hasn't helped. Returns `false` if the thread should be
+    /// parked until another thread wakes it up.
+    fn retry_without_parking(&self, backoff: &Backoff) -> bool {
+        match self.strategy {
+            ParkStrategy::Spin => {
+                backoff.spin();
+                true
+            }
+            // Backing off has just yielded the thread a few times.
+            ParkStrategy::Yield => true,
+            ParkStrategy::Block => false,
+        }
+    }
+
     /// Returns a receiver handle to the channel.
-    pub fn receiver(&self) -> Receiver<T> {
+    pub fn receiver(&self) -> Receiver<'_, T> {
         Receiver(self)
     }
 
     /// Returns a sender handle to the channel.
-    pub fn sender(&self) -> Sender<T> {
+    pub fn sender(&self) -> Sender<'_, T> {
         Sender(self)
     }
 
@@ -185,7 +390,7 @@ impl<T> Channel<T> {
                     lap.wrapping_add(self.one_lap)
                 };
 
-                // Try moving the tail.
+                // Try moving the tail. This is `SeqCst` to pair with the fence in `start_recv`.
                 match self.tail.compare_exchange_weak(
                     tail,
                     new_tail,
@@ -203,12 +408,12 @@ impl<T> Channel<T> {
                         backoff.spin();
                     }
                 }
-            } else if stamp.wrapping_add(self.one_lap) == tail + 1 {
+            } else if self.is_previous_lap(stamp, tail) {
                 atomic::fence(Ordering::SeqCst);
                 let head = self.head.load(Ordering::Relaxed);
 
                 // If the head lags one lap behind the tail as well...
-                if head.wrapping_add(self.one_lap) == tail {
+                if (head & !self.mark_bit).wrapping_add(self.one_lap) == tail {
                     // ...then the channel is full.
                     return false;
                 }
@@ -223,6 +428,19 @@ impl<T> Channel<T> {
         }
     }
 
+    /// Returns `true` if the stamp of the slot at the tail shows that the slot is still occupied
+    /// from the previous lap.
+    ///
+    /// The slot holds a message, a reservation, or an abandoned reservation.
+    fn is_previous_lap(&self, stamp: usize, tail: usize) -> bool {
+        let unmarked = stamp & !self.mark_bit;
+
+        // A message or an abandoned reservation.
+        unmarked.wrapping_add(self.one_lap) == tail + 1
+            // A reservation that has not been filled yet.
+            || (stamp != unmarked && unmarked.wrapping_add(self.one_lap) == tail)
+    }
+
     /// Writes a message into the channel.
     pub unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
         // If there is no slot, the channel is disconnected.
@@ -243,10 +461,30 @@ impl<T> Channel<T> {
 
     /// Attempts to reserve a slot for receiving a message.
     fn start_recv(&self, token: &mut Token) -> bool {
+        self.claim_head(token, false)
+    }
+
+    /// Attempts to claim the slot at the head of the channel.
+    ///
+    /// If `peek` is `false`, the head is moved forward. Otherwise, the head is locked in place so
+    /// that the message can be inspected before deciding whether to receive it.
+    fn claim_head(&self, token: &mut Token, peek: bool) -> bool {
         let backoff = Backoff::new();
         let mut head = self.head.load(Ordering::Relaxed);
 
         loop {
+            // If a receiver has locked the head, the receive operation is not ready until the head
+            // is unlocked. Other receivers see the channel as empty meanwhile, so if it is
+            // disconnected, they receive an error.
+            if head & self.mark_bit != 0 {
+                if self.is_disconnected() {
+                    token.array.slot = ptr::null();
+                    token.array.stamp = 0;
+                    return true;
+                }
+                return false;
+            }
+
             // Deconstruct the head.
             let index = head & (self.mark_bit - 1);
             let lap = head & !(self.one_lap - 1);
@@ -255,9 +493,46 @@ impl<T> Channel<T> {
             let slot = unsafe { &*self.buffer.add(index) };
             let stamp = slot.stamp.load(Ordering::Acquire);
 
+            // If the slot holds an abandoned reservation, skip it.
+            if stamp == (head + 1) | self.mark_bit {
+                let new = if index + 1 < self.cap {
+                    head + 1
+                } else {
+                    lap.wrapping_add(self.one_lap)
+                };
+
+                // Moving the head past the slot releases it for the next lap, just like reading.
+                match self.head.compare_exchange_weak(
+                    head,
+                    new,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        slot.stamp
+                            .store(head.wrapping_add(self.one_lap), Ordering::Release);
+                        self.senders.notify();
+                        head = new;
+                    }
+                    Err(h) => {
+                        head = h;
+                        backoff.spin();
+                    }
+                }
+                continue;
+            }
+
+            // If the slot is reserved but not filled yet, the receive operation is not ready.
+            if stamp == head | self.mark_bit {
+                return false;
+            }
+
             // If the the stamp is ahead of the head by 1, we may attempt to pop.
             if head + 1 == stamp {
-                let new = if index + 1 < self.cap {
+                let new = if peek {
+                    // Lock the head by setting the mark bit.
+                    head | self.mark_bit
+                } else if index + 1 < self.cap {
                     // Same lap, incremented index.
                     // Set to `{ lap: lap, mark: 0, index: index + 1 }`.
                     head + 1
@@ -267,7 +542,7 @@ impl<T> Channel<T> {
                     lap.wrapping_add(self.one_lap)
                 };
 
-                // Try moving the head.
+                // Try moving the head. This is `SeqCst` to pair with the fence in `start_send`.
                 match self.head.compare_exchange_weak(
                     head,
                     new,
@@ -275,6 +550,11 @@ impl<T> Channel<T> {
                     Ordering::Relaxed,
                 ) {
                     Ok(_) => {
+                        if peek {
+                            self.head_owner
+                                .store(utils::thread_token(), Ordering::Relaxed);
+                        }
+
                         // Prepare the token for the follow-up call to `read`.
                         token.array.slot = slot as *const Slot<T> as *const u8;
                         token.array.stamp = head.wrapping_add(self.one_lap);
@@ -331,6 +611,164 @@ impl<T> Channel<T> {
         Ok(msg)
     }
 
+    /// Passes a reference to the message in the slot to `f`, then removes the message.
+    ///
+    /// The slot is released even if `f` panics.
+    pub unsafe fn read_with<F, R>(&self, token: &mut Token, f: F) -> Result<R, ()>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        if token.array.slot.is_null() {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        /// Reads the message out of the slot and updates the stamp when dropped.
+        struct Guard<'a, T: 'a> {
+            chan: &'a Channel<T>,
+            slot: &'a Slot<T>,
+            stamp: usize,
+        }
+
+        impl<'a, T> Drop for Guard<'a, T> {
+            fn drop(&mut self) {
+                // Move the message out before updating the stamp so that the slot is released
+                // even if the destructor of the message panics.
+                let _msg = unsafe { self.slot.msg.get().read() };
+                self.slot.stamp.store(self.stamp, Ordering::Release);
+
+                // Wake a sleeping sender.
+                self.chan.senders.notify();
+            }
+        }
+
+        let slot: &Slot<T> = &*(token.array.slot as *const Slot<T>);
+        let _guard = Guard {
+            chan: self,
+            slot,
+            stamp: token.array.stamp,
+        };
+
+        Ok(f(&*slot.msg.get()))
+    }
+
+    /// Passes a reference to the message at the locked head to `f`, then either removes the
+    /// message if `f` returns `true`, or leaves it in the channel and unlocks the head otherwise.
+    ///
+    /// The head is unlocked even if `f` panics.
+    pub unsafe fn peek_decide<F>(&self, token: &mut Token, f: F) -> Result<Option<T>, ()>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        if token.array.slot.is_null() {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        /// Stores a new value into the head when dropped, which unlocks it.
+        struct Unlock<'a, T: 'a> {
+            chan: &'a Channel<T>,
+            value: usize,
+        }
+
+        impl<'a, T> Drop for Unlock<'a, T> {
+            fn drop(&mut self) {
+                self.chan.unlock_head(self.value);
+            }
+        }
+
+        // Nobody else can move the head while it is locked.
+        let head = self.head.load(Ordering::Relaxed) & !self.mark_bit;
+        let mut unlock = Unlock {
+            chan: self,
+            value: head,
+        };
+
+        let slot: &Slot<T> = &*(token.array.slot as *const Slot<T>);
+        if !f(&*slot.msg.get()) {
+            // Leave the message in the channel.
+            return Ok(None);
+        }
+
+        // Deconstruct the head.
+        let index = head & (self.mark_bit - 1);
+        let lap = head & !(self.one_lap - 1);
+
+        // Unlock the head and move it forward at the same time.
+        unlock.value = if index + 1 < self.cap {
+            head + 1
+        } else {
+            lap.wrapping_add(self.one_lap)
+        };
+        drop(unlock);
+
+        token.array.stamp = head.wrapping_add(self.one_lap);
+        self.read(token).map(Some)
+    }
+
+    /// Unlocks the head by storing `head` into it, and wakes up receivers waiting for the lock.
+    fn unlock_head(&self, head: usize) {
+        self.head_owner.store(0, Ordering::Relaxed);
+
+        // This moves the head just like the CAS in `start_recv`, so it is `SeqCst` too.
+        self.head.store(head, Ordering::SeqCst);
+
+        // Any number of messages may be ready now, so all waiting receivers are woken up.
+        self.receivers.notify_all();
+    }
+
+    /// Returns `true` if a receiver has locked the head.
+    fn is_head_locked(&self) -> bool {
+        self.head.load(Ordering::SeqCst) & self.mark_bit != 0
+    }
+
+    /// Panics if the current thread has locked the head, since the lock would never be released
+    /// while it blocks on the channel.
+    fn assert_not_head_owner(&self) {
+        assert!(
+            !(self.is_head_locked()
+                && self.head_owner.load(Ordering::Relaxed) == utils::thread_token()),
+            "cannot block on a channel while inspecting its front"
+        );
+    }
+
+    /// Locks the head in place and returns the run of messages starting at it.
+    ///
+    /// The run ends at the first slot that holds no message yet, or at the end of the buffer,
+    /// whichever comes first. It always holds at least one message.
+    pub fn lock_run(&self) -> Result<Run<'_, T>, TryRecvError> {
+        let token = &mut Token::default();
+        if !self.claim_head(token, true) {
+            return Err(TryRecvError::Empty);
+        }
+        if token.array.slot.is_null() {
+            // The channel is disconnected.
+            return Err(TryRecvError::Disconnected);
+        }
+
+        // Nobody else can move the head while it is locked.
+        let head = self.head.load(Ordering::Relaxed) & !self.mark_bit;
+        let index = head & (self.mark_bit - 1);
+
+        // Count the messages that follow the head in the same lap.
+        let mut len = 1;
+        while index + len < self.cap {
+            let slot = unsafe { &*self.buffer.add(index + len) };
+            if slot.stamp.load(Ordering::Acquire) != head + len + 1 {
+                break;
+            }
+            len += 1;
+        }
+
+        Ok(Run {
+            chan: self,
+            head,
+            len,
+            consumed: 0,
+            _marker: PhantomData,
+        })
+    }
+
     /// Attempts to send a message into the channel.
     pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
         let token = &mut Token::default();
@@ -344,16 +782,28 @@ impl<T> Channel<T> {
     /// Sends a message into the channel.
     pub fn send(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
         let token = &mut Token::default();
+        if self.wait_send(token, deadline).is_err() {
+            return Err(SendTimeoutError::Timeout(msg));
+        }
+        let res = unsafe { self.write(token, msg) };
+        res.map_err(SendTimeoutError::Disconnected)
+    }
+
+    /// Blocks until a slot for sending a message is reserved or the deadline is reached.
+    fn wait_send(&self, token: &mut Token, deadline: Option<Instant>) -> Result<(), ()> {
+        // Set to `true` once a receiver has woken us up to take a freed slot.
+        let mut woken = false;
+
         loop {
             // Try sending a message several times.
             let backoff = Backoff::new();
             loop {
                 if self.start_send(token) {
-                    let res = unsafe { self.write(token, msg) };
-                    return res.map_err(SendTimeoutError::Disconnected);
+                    return Ok(());
                 }
 
-                if backoff.is_completed() {
+                // Spinning never yields, so the clock is checked after every attempt.
+                if backoff.is_completed() || self.strategy == ParkStrategy::Spin {
                     break;
                 } else {
                     backoff.snooze();
@@ -362,16 +812,33 @@ impl<T> Channel<T> {
 
             if let Some(d) = deadline {
                 if Instant::now() >= d {
-                    return Err(SendTimeoutError::Timeout(msg));
+                    return Err(());
                 }
             }
 
+            if self.retry_without_parking(&backoff) {
+                continue;
+            }
+
             Context::with(|cx| {
                 // Prepare for blocking until a receiver wakes us up.
+                //
+                // If we were woken up but another sender took the slot first, we go back to the
+                // front of the queue rather than the back. Blocked senders are thus woken up in
+                // roughly the order they started waiting, so none of them gets starved.
                 let oper = Operation::hook(token);
-                self.senders.register(oper, cx);
+                if woken {
+                    self.senders.register_front(oper, cx);
+                } else {
+                    self.senders.register(oper, cx);
+                }
 
                 // Has the channel become ready just now?
+                //
+                // Registration publishes the operation with a `SeqCst` store, and `is_full` loads
+                // the head with `SeqCst`. A receiver moves the head with a `SeqCst` CAS and then
+                // checks for registered senders with a `SeqCst` load. Therefore, either we see the
+                // freed slot here, or the receiver sees our registration and wakes us up.
                 if !self.is_full() || self.is_disconnected() {
                     let _ = cx.try_select(Selected::Aborted);
                 }
@@ -384,12 +851,78 @@ impl<T> Channel<T> {
                     Selected::Aborted | Selected::Disconnected => {
                         self.senders.unregister(oper).unwrap();
                     }
-                    Selected::Operation(_) => {}
+                    Selected::Operation(_) => woken = true,
                 }
             });
         }
     }
 
+    /// Attempts to reserve a slot for a message that is sent later.
+    pub fn try_reserve(&self) -> Result<Reservation<'_, T>, TrySendError<()>> {
+        let token = &mut Token::default();
+        if self.start_send(token) {
+            self.mark_reserved(token)
+                .ok_or(TrySendError::Disconnected(()))
+        } else {
+            Err(TrySendError::Full(()))
+        }
+    }
+
+    /// Reserves a slot for a message that is sent later, blocking until one is free.
+    pub fn reserve(
+        &self,
+        deadline: Option<Instant>,
+    ) -> Result<Reservation<'_, T>, SendTimeoutError<()>> {
+        let token = &mut Token::default();
+        if self.wait_send(token, deadline).is_err() {
+            return Err(SendTimeoutError::Timeout(()));
+        }
+        self.mark_reserved(token)
+            .ok_or(SendTimeoutError::Disconnected(()))
+    }
+
+    /// Marks the slot taken by `start_send` as reserved.
+    ///
+    /// Returns `None` if the channel is disconnected.
+    fn mark_reserved(&self, token: &mut Token) -> Option<Reservation<'_, T>> {
+        if token.array.slot.is_null() {
+            return None;
+        }
+
+        // The stamp still equals the tail the slot was taken at, which lets receivers tell a
+        // reserved slot apart from one that is being written right now.
+        let slot = unsafe { &*(token.array.slot as *const Slot<T>) };
+        slot.stamp
+            .store((token.array.stamp - 1) | self.mark_bit, Ordering::SeqCst);
+
+        Some(Reservation {
+            chan: self,
+            slot,
+            stamp: token.array.stamp,
+        })
+    }
+
+    /// Returns `true` if the slot at the head is reserved but not filled yet.
+    fn is_head_reserved(&self) -> bool {
+        let head = self.head.load(Ordering::SeqCst) & !self.mark_bit;
+        let index = head & (self.mark_bit - 1);
+        let slot = unsafe { &*self.buffer.add(index) };
+        slot.stamp.load(Ordering::SeqCst) == head | self.mark_bit
+    }
+
+    /// Returns `true` if a receive operation might not have to block.
+    ///
+    /// The head must hold a message, an abandoned reservation that can be skipped, or the channel
+    /// must be disconnected. A reserved slot at the head blocks receivers until it is filled or
+    /// abandoned, and a locked head until it is unlocked, both of which notify them. A locked head
+    /// doesn't block receivers once the channel is disconnected.
+    fn is_recv_ready(&self) -> bool {
+        if self.is_head_locked() {
+            return self.is_disconnected();
+        }
+        (!self.is_empty() || self.is_disconnected()) && !self.is_head_reserved()
+    }
+
     /// Attempts to receive a message without blocking.
     pub fn try_recv(&self) -> Result<T, TryRecvError> {
         let token = &mut Token::default();
@@ -401,24 +934,181 @@ impl<T> Channel<T> {
         }
     }
 
+    /// Makes a single attempt at receiving a message, without waiting for other threads.
+    ///
+    /// Unlike `try_recv`, this never spins or snoozes. Whenever another thread is in the middle
+    /// of an operation that affects the head, `Steal::Retry` is returned right away. Abandoned
+    /// reservations at the head are skipped, since nobody else may ever skip them.
+    pub fn steal(&self) -> Steal<T> {
+        let mut head = self.head.load(Ordering::Relaxed);
+
+        loop {
+            // Another receiver has locked the head to inspect the message there. Unless the
+            // channel is disconnected, the message may be left for others to take.
+            if head & self.mark_bit != 0 {
+                return if self.is_disconnected() {
+                    Steal::Empty
+                } else {
+                    Steal::Retry
+                };
+            }
+
+            // Deconstruct the head.
+            let index = head & (self.mark_bit - 1);
+            let lap = head & !(self.one_lap - 1);
+
+            // Inspect the corresponding slot.
+            let slot = unsafe { &*self.buffer.add(index) };
+            let stamp = slot.stamp.load(Ordering::Acquire);
+
+            let new = if index + 1 < self.cap {
+                head + 1
+            } else {
+                lap.wrapping_add(self.one_lap)
+            };
+
+            return if head + 1 == stamp {
+                // Try moving the head once. This is `SeqCst` to pair with the fence in `start_send`.
+                match self
+                    .head
+                    .compare_exchange(head, new, Ordering::SeqCst, Ordering::Relaxed)
+                {
+                    Ok(_) => {
+                        let token = &mut Token::default();
+                        token.array.slot = slot as *const Slot<T> as *const u8;
+                        token.array.stamp = head.wrapping_add(self.one_lap);
+                        match unsafe { self.read(token) } {
+                            Ok(msg) => Steal::Success(msg),
+                            Err(()) => unreachable!(),
+                        }
+                    }
+                    Err(_) => Steal::Retry,
+                }
+            } else if stamp == (head + 1) | self.mark_bit {
+                // The slot holds an abandoned reservation. Skip it just like `claim_head` does and
+                // move on to the next slot, since skipping it is progress rather than contention.
+                match self
+                    .head
+                    .compare_exchange(head, new, Ordering::SeqCst, Ordering::Relaxed)
+                {
+                    Ok(_) => {
+                        slot.stamp
+                            .store(head.wrapping_add(self.one_lap), Ordering::Release);
+                        self.senders.notify();
+                        head = new;
+                        continue;
+                    }
+                    Err(_) => Steal::Retry,
+                }
+            } else if stamp == head | self.mark_bit {
+                // The slot is reserved but not filled yet.
+                Steal::Empty
+            } else if stamp == head {
+                atomic::fence(Ordering::SeqCst);
+                let tail = self.tail.load(Ordering::Relaxed);
+
+                // If the tail equals the head, the channel is empty. Otherwise, the head is stale.
+                if (tail & !self.mark_bit) == head {
+                    Steal::Empty
+                } else {
+                    Steal::Retry
+                }
+            } else {
+                // The head is stale or the slot is being written.
+                Steal::Retry
+            };
+        }
+    }
+
     /// Receives a message from the channel.
     pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
         let token = &mut Token::default();
+        self.wait_recv(token, deadline, false)?;
+        let res = unsafe { self.read(token) };
+        res.map_err(|_| RecvTimeoutError::Disconnected)
+    }
+
+    /// Receives a message from the channel, along with whether it was the last one.
+    ///
+    /// The message was the last one if, right after it was claimed, the tail was just behind it.
+    pub fn recv_with_empty(
+        &self,
+        deadline: Option<Instant>,
+    ) -> Result<(T, bool), RecvTimeoutError> {
+        let token = &mut Token::default();
+        self.wait_recv(token, deadline, false)?;
+
+        let empty = !token.array.slot.is_null() && {
+            // Reconstruct the claimed head from the stamp to be stored into the slot.
+            let head = token.array.stamp.wrapping_sub(self.one_lap);
+            let index = head & (self.mark_bit - 1);
+            let lap = head & !(self.one_lap - 1);
+            let next = if index + 1 < self.cap {
+                head + 1
+            } else {
+                lap.wrapping_add(self.one_lap)
+            };
+
+            self.tail.load(Ordering::SeqCst) & !self.mark_bit == next
+        };
+
+        let res = unsafe { self.read(token) };
+        res.map(|msg| (msg, empty))
+            .map_err(|_| RecvTimeoutError::Disconnected)
+    }
+
+    /// Receives a message from the channel and passes a reference to it to `f`.
+    pub fn recv_with<F, R>(&self, deadline: Option<Instant>, f: F) -> Result<R, RecvTimeoutError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let token = &mut Token::default();
+        self.wait_recv(token, deadline, false)?;
+        let res = unsafe { self.read_with(token, f) };
+        res.map_err(|_| RecvTimeoutError::Disconnected)
+    }
+
+    /// Waits for a message and passes a reference to it to `f`, which decides whether to receive
+    /// it or leave it in the channel.
+    pub fn recv_peek_decide<F>(
+        &self,
+        deadline: Option<Instant>,
+        f: F,
+    ) -> Result<Option<T>, RecvTimeoutError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        let token = &mut Token::default();
+        self.wait_recv(token, deadline, true)?;
+        let res = unsafe { self.peek_decide(token, f) };
+        res.map_err(|_| RecvTimeoutError::Disconnected)
+    }
+
+    /// Blocks until a slot for receiving a message is reserved or the deadline is reached.
+    ///
+    /// If `peek` is `true`, the head is locked in place instead of moved forward.
+    fn wait_recv(
+        &self,
+        token: &mut Token,
+        deadline: Option<Instant>,
+        peek: bool,
+    ) -> Result<(), RecvTimeoutError> {
         loop {
             // Try receiving a message several times.
             let backoff = Backoff::new();
             loop {
-                if self.start_recv(token) {
-                    let res = unsafe { self.read(token) };
-                    return res.map_err(|_| RecvTimeoutError::Disconnected);
+                if self.claim_head(token, peek) {
+                    return Ok(());
                 }
 
-                if backoff.is_completed() {
+                // Spinning never yields, so the clock is checked after every attempt.
+                if backoff.is_completed() || self.strategy == ParkStrategy::Spin {
                     break;
                 } else {
                     backoff.snooze();
                 }
             }
+            self.assert_not_head_owner();
 
             if let Some(d) = deadline {
                 if Instant::now() >= d {
@@ -426,13 +1116,17 @@ impl<T> Channel<T> {
                 }
             }
 
+            if self.retry_without_parking(&backoff) {
+                continue;
+            }
+
             Context::with(|cx| {
                 // Prepare for blocking until a sender wakes us up.
                 let oper = Operation::hook(token);
                 self.receivers.register(oper, cx);
 
                 // Has the channel become ready just now?
-                if !self.is_empty() || self.is_disconnected() {
+                if self.is_recv_ready() {
                     let _ = cx.try_select(Selected::Aborted);
                 }
 
@@ -468,7 +1162,7 @@ impl<T> Channel<T> {
                     tix - hix
                 } else if hix > tix {
                     self.cap - hix + tix
-                } else if (tail & !self.mark_bit) == head {
+                } else if (tail & !self.mark_bit) == (head & !self.mark_bit) {
                     0
                 } else {
                     self.cap
@@ -477,11 +1171,87 @@ impl<T> Channel<T> {
         }
     }
 
+    /// Returns the number of messages inside the channel at some instant during the call.
+    ///
+    /// Unlike `len`, this counts only slots whose message has been written and not yet claimed by
+    /// a receiver, so reserved slots that are still being filled and abandoned reservations are
+    /// not counted. The slots between the head and the tail are scanned one by one, and the scan is
+    /// retried if the head or the tail moved in the meantime. While they stay put, slots can only
+    /// go from reserved to written, so the count lies between the number of messages at the start
+    /// and at the end of the scan, and must have been exact at some instant in between.
+    ///
+    /// If the head or the tail moves during every one of `LEN_EXACT_ATTEMPTS` scans, the result
+    /// of `len` is returned instead, so that the call finishes even under constant traffic.
+    pub fn len_exact(&self) -> usize {
+        let backoff = Backoff::new();
+
+        for _ in 0..LEN_EXACT_ATTEMPTS {
+            // Load the tail, then load the head. The mark bits don't affect the count.
+            let tail = self.tail.load(Ordering::SeqCst) & !self.mark_bit;
+            let head = self.head.load(Ordering::SeqCst) & !self.mark_bit;
+
+            // If the tail changed, the head might already be past it, so start over.
+            if self.tail.load(Ordering::SeqCst) & !self.mark_bit != tail {
+                backoff.spin();
+                continue;
+            }
+
+            // Count the written slots from the head up to the tail.
+            let mut count = 0;
+            let mut pos = head;
+            while pos != tail {
+                let index = pos & (self.mark_bit - 1);
+                let lap = pos & !(self.one_lap - 1);
+
+                let slot = unsafe { &*self.buffer.add(index) };
+                if slot.stamp.load(Ordering::Acquire) == pos + 1 {
+                    count += 1;
+                }
+
+                pos = if index + 1 < self.cap {
+                    pos + 1
+                } else {
+                    lap.wrapping_add(self.one_lap)
+                };
+            }
+
+            // If neither the tail nor the head moved, the count is consistent.
+            if self.tail.load(Ordering::SeqCst) & !self.mark_bit == tail
+                && self.head.load(Ordering::SeqCst) & !self.mark_bit == head
+            {
+                return count;
+            }
+            backoff.spin();
+        }
+
+        self.len()
+    }
+
     /// Returns the capacity of the channel.
     pub fn capacity(&self) -> Option<usize> {
         Some(self.cap)
     }
 
+    /// Registers a file descriptor to be signalled whenever a receive operation may have become
+    /// ready.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn watch_fd(&self, fd: RawFd) {
+        self.receivers.watch_fd(fd);
+    }
+
+    /// Unregisters a file descriptor.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn unwatch_fd(&self, fd: RawFd) {
+        self.receivers.unwatch_fd(fd);
+    }
+
+    /// Lets the next notification signal the file descriptors again, after a receive operation
+    /// has found the channel empty.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn rearm_fds<F: FnOnce() -> bool>(&self, is_ready: F) {
+        self.receivers.rearm_fds(is_ready);
+    }
+
     /// Disconnects the channel and wakes up all blocked senders and receivers.
     ///
     /// Returns `true` if this call disconnected the channel.
@@ -502,6 +1272,49 @@ impl<T> Channel<T> {
         self.tail.load(Ordering::SeqCst) & self.mark_bit != 0
     }
 
+    /// Registers an observer that is notified every time a message is taken out of the channel.
+    pub fn watch_drain(&self, oper: Operation, cx: &Context) {
+        self.senders.watch(oper, cx);
+    }
+
+    /// Unregisters an observer registered by `watch_drain`.
+    pub fn unwatch_drain(&self, oper: Operation) {
+        self.senders.unwatch(oper);
+    }
+
+    /// Blocks the current thread until the channel is empty.
+    ///
+    /// Returns `false` if the channel got disconnected while it still held messages, in which case
+    /// they will never be received.
+    pub fn wait_empty(&self) -> bool {
+        let token = &mut Token::default();
+
+        loop {
+            if self.is_empty() {
+                return true;
+            }
+            if self.is_disconnected() {
+                return false;
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a receiver takes a message out. Receivers notify
+                // observers among the senders every time they free a slot.
+                let oper = Operation::hook(token);
+                self.senders.watch(oper, cx);
+
+                // Has the channel become empty or disconnected just now?
+                if self.is_empty() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                cx.wait_until(None);
+                self.senders.unwatch(oper);
+            });
+        }
+    }
+
     /// Returns `true` if the channel is empty.
     pub fn is_empty(&self) -> bool {
         let head = self.head.load(Ordering::SeqCst);
@@ -511,7 +1324,7 @@ impl<T> Channel<T> {
         //
         // Note: If the head changes just before we load the tail, that means there was a moment
         // when the channel was not empty, so it is safe to just return `false`.
-        (tail & !self.mark_bit) == head
+        (tail & !self.mark_bit) == (head & !self.mark_bit)
     }
 
     /// Returns `true` if the channel is full.
@@ -523,7 +1336,7 @@ impl<T> Channel<T> {
         //
         // Note: If the tail changes just before we load the head, that means there was a moment
         // when the channel was not full, so it is safe to just return `false`.
-        head.wrapping_add(self.one_lap) == tail & !self.mark_bit
+        (head & !self.mark_bit).wrapping_add(self.one_lap) == tail & !self.mark_bit
     }
 }
 
@@ -541,16 +1354,159 @@ impl<T> Drop for Channel<T> {
                 hix + i - self.cap
             };
 
+            // Skip reservations that were never filled.
+            let slot = unsafe { &mut *self.buffer.add(index) };
+            if *slot.stamp.get_mut() & self.mark_bit == 0 {
+                unsafe {
+                    ptr::drop_in_place(slot.msg.get());
+                }
+            }
+        }
+
+        // Finally, deallocate the buffer, but don't run any destructors. A buffer provided by the
+        // user is left alone.
+        if self.owned {
             unsafe {
-                self.buffer.add(index).drop_in_place();
+                Vec::from_raw_parts(self.buffer, 0, self.cap);
             }
         }
+    }
+}
+
+/// A run of consecutive messages at the locked head of the channel.
+///
+/// The head stays locked until the run is dropped, at which point the consumed messages are
+/// removed from the channel and the head is unlocked past them.
+pub struct Run<'a, T: 'a> {
+    /// The channel.
+    chan: &'a Channel<T>,
+
+    /// The head of the channel, without the mark bit.
+    head: usize,
 
-        // Finally, deallocate the buffer, but don't run any destructors.
-        unsafe {
-            Vec::from_raw_parts(self.buffer, 0, self.cap);
+    /// The number of messages in the run.
+    len: usize,
+
+    /// The number of messages to remove from the channel when the run is dropped.
+    consumed: usize,
+
+    /// Keeps the run on the thread that has locked the head, like a `MutexGuard`.
+    _marker: PhantomData<*mut ()>,
+}
+
+impl<'a, T> Run<'a, T> {
+    /// Returns the number of messages in the run.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns a reference to the `i`-th message of the run.
+    pub fn get(&self, i: usize) -> Option<&T> {
+        if i < self.len {
+            let index = self.head & (self.chan.mark_bit - 1);
+            let slot = unsafe { &*self.chan.buffer.add(index + i) };
+            Some(unsafe { &*slot.msg.get() })
+        } else {
+            None
         }
     }
+
+    /// Marks the first `n` messages of the run to be removed from the channel.
+    pub fn consume(&mut self, n: usize) {
+        assert!(
+            n <= self.len,
+            "cannot consume more messages than the run holds"
+        );
+        self.consumed = n;
+    }
+}
+
+impl<'a, T> Drop for Run<'a, T> {
+    fn drop(&mut self) {
+        /// Stores the head moved past the released slots when dropped, which unlocks it.
+        struct Unlock<'a, T: 'a> {
+            chan: &'a Channel<T>,
+            head: usize,
+            released: usize,
+        }
+
+        impl<'a, T> Drop for Unlock<'a, T> {
+            fn drop(&mut self) {
+                let index = self.head & (self.chan.mark_bit - 1);
+                let lap = self.head & !(self.chan.one_lap - 1);
+
+                let new = if index + self.released < self.chan.cap {
+                    self.head + self.released
+                } else {
+                    lap.wrapping_add(self.chan.one_lap)
+                };
+
+                self.chan.unlock_head(new);
+            }
+        }
+
+        let mut unlock = Unlock {
+            chan: self.chan,
+            head: self.head,
+            released: 0,
+        };
+        let index = self.head & (self.chan.mark_bit - 1);
+
+        // Release the slots one by one so that the head is unlocked past the released ones even if
+        // the destructor of a message panics.
+        while unlock.released < self.consumed {
+            let i = unlock.released;
+            let slot = unsafe { &*self.chan.buffer.add(index + i) };
+
+            let msg = unsafe { slot.msg.get().read() };
+            let stamp = (self.head + i).wrapping_add(self.chan.one_lap);
+            slot.stamp.store(stamp, Ordering::Release);
+            unlock.released += 1;
+
+            // Wake a sleeping sender.
+            self.chan.senders.notify();
+            drop(msg);
+        }
+    }
+}
+
+/// A reserved slot that a message is written into later.
+///
+/// If the reservation is dropped without being filled, the slot is abandoned and receivers skip
+/// it.
+pub struct Reservation<'a, T: 'a> {
+    /// The channel.
+    chan: &'a Channel<T>,
+
+    /// The reserved slot.
+    slot: &'a Slot<T>,
+
+    /// The stamp of the slot once it holds a message.
+    stamp: usize,
+}
+
+impl<'a, T> Reservation<'a, T> {
+    /// Writes the message into the reserved slot.
+    pub fn fill(self, msg: T) {
+        unsafe { self.slot.msg.get().write(msg) };
+        self.publish(self.stamp);
+        mem::forget(self);
+    }
+
+    /// Stores the stamp into the slot and wakes a sleeping receiver.
+    fn publish(&self, stamp: usize) {
+        // This is `SeqCst` to pair with the check in `is_head_reserved`, so that a receiver either
+        // sees the new stamp or gets woken up.
+        self.slot.stamp.store(stamp, Ordering::SeqCst);
+        self.chan.receivers.notify();
+    }
+}
+
+impl<'a, T> Drop for Reservation<'a, T> {
+    fn drop(&mut self) {
+        // Abandon the slot.
+        self.publish(self.stamp | self.chan.mark_bit);
+    }
 }
 
 /// Receiver handle to a channel.
@@ -569,6 +1525,7 @@ impl<'a, T> SelectHandle for Receiver<'a, T> {
     }
 
     fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.assert_not_head_owner();
         self.0.receivers.register(oper, cx);
         self.is_ready()
     }
@@ -582,10 +1539,11 @@ impl<'a, T> SelectHandle for Receiver<'a, T> {
     }
 
     fn is_ready(&self) -> bool {
-        !self.0.is_empty() || self.0.is_disconnected()
+        self.0.is_recv_ready()
     }
 
     fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.assert_not_head_owner();
         self.0.receivers.watch(oper, cx);
         self.is_ready()
     }
@@ -605,6 +1563,8 @@ impl<'a, T> SelectHandle for Sender<'a, T> {
     }
 
     fn register(&self, oper: Operation, cx: &Context) -> bool {
+        // Readiness must be checked after registration so that a slot freed in between is never
+        // missed. See `Channel::send` for details.
         self.0.senders.register(oper, cx);
         self.is_ready()
     }
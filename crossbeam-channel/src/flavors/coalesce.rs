@@ -0,0 +1,470 @@
+//! Unbounded channel that keeps only the latest message for each key.
+//!
+//! Messages are `(key, value)` pairs kept in a queue protected by a lock, together with an index
+//! from key hashes to positions in the queue. When a message is sent while another one with the
+//! same key is still in the queue, its value is overwritten in place instead of appending a new
+//! message, so the queue never holds more than one message per key.
+//!
+//! Positions are tracked as sequence numbers: the message at the head of the queue has sequence
+//! number `head`, and every appended message gets the next one. Receiving a message removes it
+//! from the head of the queue and from the index.
+//!
+//! The hash and key comparison functions are captured when the channel is created, which is the
+//! only place where the key type is known to implement `Eq` and `Hash`.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+#[cfg(all(unix, feature = "os_poll"))]
+use std::os::unix::io::RawFd;
+use std::time::Instant;
+
+use crossbeam_utils::Backoff;
+
+use context::Context;
+use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use select::{Operation, SelectHandle, Selected, Token};
+use utils::Spinlock;
+use waker::SyncWaker;
+
+/// The token type for the coalescing flavor.
+///
+/// It is `true` if a message was reserved or can be sent, and `false` if the channel is
+/// disconnected.
+pub type CoalesceToken = bool;
+
+/// Inner representation of a coalescing channel.
+struct Inner<T> {
+    /// Messages in the order their keys were first sent.
+    queue: VecDeque<T>,
+
+    /// Sequence number of the message at the head of the queue.
+    head: u64,
+
+    /// Sequence numbers of the messages in the queue, grouped by the hash of their keys.
+    index: HashMap<u64, Vec<u64>>,
+
+    /// The number of messages reserved by receive operations but not read yet.
+    recv_reserved: usize,
+
+    /// Equals `true` when the channel is disconnected.
+    is_disconnected: bool,
+}
+
+impl<T> Inner<T> {
+    /// Inserts a message, overwriting the queued message with the same key if there is one.
+    ///
+    /// Returns the overwritten message, or `None` if the message was appended.
+    fn insert(&mut self, msg: T, hash: u64, same_key: fn(&T, &T) -> bool) -> Option<T> {
+        if let Some(seqs) = self.index.get(&hash) {
+            for &seq in seqs {
+                let slot = &mut self.queue[(seq - self.head) as usize];
+                if same_key(slot, &msg) {
+                    return Some(::std::mem::replace(slot, msg));
+                }
+            }
+        }
+
+        let seq = self.head + self.queue.len() as u64;
+        self.queue.push_back(msg);
+        self.index.entry(hash).or_insert_with(Vec::new).push(seq);
+        None
+    }
+
+    /// Removes the message at the head of the queue.
+    fn pop(&mut self, hash: fn(&T) -> u64) -> Option<T> {
+        let msg = self.queue.pop_front()?;
+        let h = hash(&msg);
+        let head = self.head;
+
+        let now_empty = {
+            let seqs = self.index.get_mut(&h).unwrap();
+            let pos = seqs.iter().position(|&seq| seq == head).unwrap();
+            seqs.swap_remove(pos);
+            seqs.is_empty()
+        };
+        if now_empty {
+            self.index.remove(&h);
+        }
+
+        self.head = head + 1;
+        Some(msg)
+    }
+}
+
+/// Unbounded channel that keeps only the latest message for each key.
+pub struct Channel<T> {
+    /// Inner representation of the channel.
+    inner: Spinlock<Inner<T>>,
+
+    /// Hashes the key of a message.
+    hash: fn(&T) -> u64,
+
+    /// Returns `true` if two messages have equal keys.
+    same_key: fn(&T, &T) -> bool,
+
+    /// Senders waiting for the channel to become empty.
+    senders: SyncWaker,
+
+    /// Receivers waiting while the channel is empty and not disconnected.
+    receivers: SyncWaker,
+}
+
+impl<K, V> Channel<(K, V)> {
+    /// Creates a coalescing channel whose messages are keyed by their first element.
+    pub fn new() -> Self
+    where
+        K: Eq + Hash,
+    {
+        Channel {
+            inner: Spinlock::new(Inner {
+                queue: VecDeque::new(),
+                head: 0,
+                index: HashMap::new(),
+                recv_reserved: 0,
+                is_disconnected: false,
+            }),
+            hash: hash_key::<K, V>,
+            same_key: same_key::<K, V>,
+            senders: SyncWaker::new(),
+            receivers: SyncWaker::new(),
+        }
+    }
+}
+
+/// Hashes the key of a `(key, value)` message.
+fn hash_key<K: Hash, V>(msg: &(K, V)) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    msg.0.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns `true` if two `(key, value)` messages have equal keys.
+fn same_key<K: Eq, V>(a: &(K, V), b: &(K, V)) -> bool {
+    a.0 == b.0
+}
+
+impl<T> Channel<T> {
+    /// Returns a receiver handle to the channel.
+    pub fn receiver(&self) -> Receiver<'_, T> {
+        Receiver(self)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub fn sender(&self) -> Sender<'_, T> {
+        Sender(self)
+    }
+
+    /// Attempts to reserve a slot for sending a message.
+    fn start_send(&self, token: &mut Token) -> bool {
+        token.coalesce = !self.inner.lock().is_disconnected;
+        true
+    }
+
+    /// Writes a message into the channel.
+    pub unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        if !token.coalesce {
+            // The channel is disconnected.
+            return Err(msg);
+        }
+
+        let hash = (self.hash)(&msg);
+        let old = self.inner.lock().insert(msg, hash, self.same_key);
+
+        // The overwritten message is dropped outside the lock. If the message was appended instead,
+        // wake a sleeping receiver.
+        if old.is_none() {
+            self.receivers.notify();
+        }
+        drop(old);
+        Ok(())
+    }
+
+    /// Attempts to reserve a message for receiving.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        let mut inner = self.inner.lock();
+
+        if inner.queue.len() > inner.recv_reserved {
+            inner.recv_reserved += 1;
+            token.coalesce = true;
+            true
+        } else if inner.is_disconnected {
+            token.coalesce = false;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reads the message at the head of the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if !token.coalesce {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        let msg = {
+            let mut inner = self.inner.lock();
+            inner.recv_reserved -= 1;
+            inner.pop(self.hash).unwrap()
+        };
+
+        // Wake senders waiting for the channel to become empty.
+        self.senders.notify();
+        Ok(msg)
+    }
+
+    /// Attempts to send a message into the channel.
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.send(msg, None).map_err(|err| match err {
+            SendTimeoutError::Disconnected(msg) => TrySendError::Disconnected(msg),
+            SendTimeoutError::Timeout(_) => unreachable!(),
+        })
+    }
+
+    /// Sends a message into the channel.
+    pub fn send(&self, msg: T, _deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        let token = &mut Token::default();
+        assert!(self.start_send(token));
+        unsafe {
+            self.write(token, msg)
+                .map_err(SendTimeoutError::Disconnected)
+        }
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let token = &mut Token::default();
+
+        if self.start_recv(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            // Try receiving a message several times.
+            let backoff = Backoff::new();
+            loop {
+                if self.start_recv(token) {
+                    let res = unsafe { self.read(token) };
+                    return res.map_err(|_| RecvTimeoutError::Disconnected);
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a sender wakes us up.
+                let oper = Operation::hook(token);
+                self.receivers.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if self.receiver().is_ready() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.receivers.unregister(oper).unwrap();
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Returns the current number of messages inside the channel.
+    pub fn len(&self) -> usize {
+        self.inner.lock().queue.len()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Registers a file descriptor to be signalled whenever a receive operation may have become
+    /// ready.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn watch_fd(&self, fd: RawFd) {
+        self.receivers.watch_fd(fd);
+    }
+
+    /// Unregisters a file descriptor.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn unwatch_fd(&self, fd: RawFd) {
+        self.receivers.unwatch_fd(fd);
+    }
+
+    /// Lets the next notification signal the file descriptors again, after a receive operation
+    /// has found the channel empty.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn rearm_fds<F: FnOnce() -> bool>(&self, is_ready: F) {
+        self.receivers.rearm_fds(is_ready);
+    }
+
+    /// Disconnects the channel and wakes up all blocked senders and receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        let was_connected = {
+            let mut inner = self.inner.lock();
+            !::std::mem::replace(&mut inner.is_disconnected, true)
+        };
+
+        if was_connected {
+            self.senders.disconnect();
+            self.receivers.disconnect();
+        }
+        was_connected
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.lock().is_disconnected
+    }
+
+    /// Registers an observer that is notified every time a message is taken out of the channel.
+    pub fn watch_drain(&self, oper: Operation, cx: &Context) {
+        self.senders.watch(oper, cx);
+    }
+
+    /// Unregisters an observer registered by `watch_drain`.
+    pub fn unwatch_drain(&self, oper: Operation) {
+        self.senders.unwatch(oper);
+    }
+
+    /// Blocks the current thread until the channel is empty.
+    ///
+    /// Returns `false` if the channel got disconnected while it still held messages, in which case
+    /// they will never be received.
+    pub fn wait_empty(&self) -> bool {
+        let token = &mut Token::default();
+
+        loop {
+            if self.is_empty() {
+                return true;
+            }
+            if self.is_disconnected() {
+                return false;
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a receiver takes a message out.
+                let oper = Operation::hook(token);
+                self.senders.watch(oper, cx);
+
+                // Has the channel become empty or disconnected just now?
+                if self.is_empty() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                cx.wait_until(None);
+                self.senders.unwatch(oper);
+            });
+        }
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        false
+    }
+}
+
+/// Receiver handle to a channel.
+pub struct Receiver<'a, T: 'a>(&'a Channel<T>);
+
+/// Sender handle to a channel.
+pub struct Sender<'a, T: 'a>(&'a Channel<T>);
+
+impl<'a, T> SelectHandle for Receiver<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        let inner = self.0.inner.lock();
+        inner.queue.len() > inner.recv_reserved || inner.is_disconnected
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.receivers.unwatch(oper);
+    }
+}
+
+impl<'a, T> SelectHandle for Sender<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_send(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    fn unregister(&self, _oper: Operation) {}
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn watch(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    fn unwatch(&self, _oper: Operation) {}
+}
@@ -0,0 +1,512 @@
+//! Bounded channel that receives messages in priority order.
+//!
+//! Messages are kept in a binary max-heap protected by a lock. Receive operations always take the
+//! greatest message in the channel, so the order in which messages are received depends on how
+//! they compare rather than on the order in which they were sent. Lock-free heaps are hard to get
+//! right, and the lock is only held for a push or a pop, which take a logarithmic number of steps.
+//!
+//! The comparison function is captured when the channel is created, which is the only place where
+//! the message type is known to implement `Ord`.
+
+use std::cmp::Ordering;
+#[cfg(all(unix, feature = "os_poll"))]
+use std::os::unix::io::RawFd;
+use std::time::Instant;
+
+use crossbeam_utils::Backoff;
+
+use context::Context;
+use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use select::{Operation, SelectHandle, Selected, Token};
+use utils::Spinlock;
+use waker::SyncWaker;
+
+/// The token type for the heap flavor.
+///
+/// It is `true` if a slot or a message was reserved and `false` if the channel is disconnected.
+pub type HeapToken = bool;
+
+/// Inner representation of a heap channel.
+struct Inner<T> {
+    /// Messages ordered as a binary max-heap.
+    heap: Vec<T>,
+
+    /// The number of slots reserved by send operations but not written yet.
+    send_reserved: usize,
+
+    /// The number of messages reserved by receive operations but not read yet.
+    recv_reserved: usize,
+
+    /// Equals `true` when the channel is disconnected.
+    is_disconnected: bool,
+}
+
+impl<T> Inner<T> {
+    /// Pushes a message into the heap.
+    fn push(&mut self, msg: T, cmp: fn(&T, &T) -> Ordering) {
+        self.heap.push(msg);
+
+        // Sift the message up until its parent is not less than it.
+        let mut i = self.heap.len() - 1;
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if cmp(&self.heap[i], &self.heap[parent]) != Ordering::Greater {
+                break;
+            }
+            self.heap.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    /// Pops the greatest message from the heap.
+    fn pop(&mut self, cmp: fn(&T, &T) -> Ordering) -> Option<T> {
+        let len = self.heap.len();
+        if len == 0 {
+            return None;
+        }
+        self.heap.swap(0, len - 1);
+        let msg = self.heap.pop();
+
+        // Sift the new root down until none of its children is greater than it.
+        let len = len - 1;
+        let mut i = 0;
+        loop {
+            let mut greatest = i;
+            for &child in &[2 * i + 1, 2 * i + 2] {
+                if child < len && cmp(&self.heap[child], &self.heap[greatest]) == Ordering::Greater
+                {
+                    greatest = child;
+                }
+            }
+            if greatest == i {
+                break;
+            }
+            self.heap.swap(i, greatest);
+            i = greatest;
+        }
+
+        msg
+    }
+}
+
+/// Bounded channel that receives messages in priority order.
+pub struct Channel<T> {
+    /// Inner representation of the channel.
+    inner: Spinlock<Inner<T>>,
+
+    /// Compares two messages.
+    cmp: fn(&T, &T) -> Ordering,
+
+    /// The channel capacity.
+    cap: usize,
+
+    /// Senders waiting while the channel is full.
+    senders: SyncWaker,
+
+    /// Receivers waiting while the channel is empty and not disconnected.
+    receivers: SyncWaker,
+}
+
+impl<T> Channel<T> {
+    /// Creates a heap channel of capacity `cap` whose messages are ordered by `Ord`.
+    pub fn with_capacity(cap: usize) -> Self
+    where
+        T: Ord,
+    {
+        assert!(cap > 0, "capacity must be positive");
+
+        Channel {
+            inner: Spinlock::new(Inner {
+                heap: Vec::with_capacity(cap),
+                send_reserved: 0,
+                recv_reserved: 0,
+                is_disconnected: false,
+            }),
+            cmp: T::cmp,
+            cap,
+            senders: SyncWaker::new(),
+            receivers: SyncWaker::new(),
+        }
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub fn receiver(&self) -> Receiver<'_, T> {
+        Receiver(self)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub fn sender(&self) -> Sender<'_, T> {
+        Sender(self)
+    }
+
+    /// Attempts to reserve a slot for sending a message.
+    fn start_send(&self, token: &mut Token) -> bool {
+        let mut inner = self.inner.lock();
+
+        if inner.is_disconnected {
+            token.heap = false;
+            true
+        } else if inner.heap.len() + inner.send_reserved < self.cap {
+            inner.send_reserved += 1;
+            token.heap = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Writes a message into the reserved slot.
+    pub unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        if !token.heap {
+            // The channel is disconnected.
+            return Err(msg);
+        }
+
+        {
+            let mut inner = self.inner.lock();
+            inner.send_reserved -= 1;
+            inner.push(msg, self.cmp);
+        }
+
+        // Wake a sleeping receiver.
+        self.receivers.notify();
+        Ok(())
+    }
+
+    /// Attempts to reserve a message for receiving.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        let mut inner = self.inner.lock();
+
+        if inner.heap.len() > inner.recv_reserved {
+            inner.recv_reserved += 1;
+            token.heap = true;
+            true
+        } else if inner.is_disconnected {
+            token.heap = false;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reads the greatest message from the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if !token.heap {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        let msg = {
+            let mut inner = self.inner.lock();
+            inner.recv_reserved -= 1;
+            inner.pop(self.cmp).unwrap()
+        };
+
+        // Wake a sleeping sender.
+        self.senders.notify();
+        Ok(msg)
+    }
+
+    /// Attempts to send a message into the channel.
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        let token = &mut Token::default();
+        if self.start_send(token) {
+            unsafe { self.write(token, msg).map_err(TrySendError::Disconnected) }
+        } else {
+            Err(TrySendError::Full(msg))
+        }
+    }
+
+    /// Sends a message into the channel.
+    pub fn send(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        let token = &mut Token::default();
+        loop {
+            // Try sending a message several times.
+            let backoff = Backoff::new();
+            loop {
+                if self.start_send(token) {
+                    let res = unsafe { self.write(token, msg) };
+                    return res.map_err(SendTimeoutError::Disconnected);
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(SendTimeoutError::Timeout(msg));
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a receiver wakes us up.
+                let oper = Operation::hook(token);
+                self.senders.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if self.sender().is_ready() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.senders.unregister(oper).unwrap();
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let token = &mut Token::default();
+
+        if self.start_recv(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            // Try receiving a message several times.
+            let backoff = Backoff::new();
+            loop {
+                if self.start_recv(token) {
+                    let res = unsafe { self.read(token) };
+                    return res.map_err(|_| RecvTimeoutError::Disconnected);
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a sender wakes us up.
+                let oper = Operation::hook(token);
+                self.receivers.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if self.receiver().is_ready() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.receivers.unregister(oper).unwrap();
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Returns the current number of messages inside the channel.
+    pub fn len(&self) -> usize {
+        self.inner.lock().heap.len()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        Some(self.cap)
+    }
+
+    /// Registers a file descriptor to be signalled whenever a receive operation may have become
+    /// ready.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn watch_fd(&self, fd: RawFd) {
+        self.receivers.watch_fd(fd);
+    }
+
+    /// Unregisters a file descriptor.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn unwatch_fd(&self, fd: RawFd) {
+        self.receivers.unwatch_fd(fd);
+    }
+
+    /// Lets the next notification signal the file descriptors again, after a receive operation
+    /// has found the channel empty.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn rearm_fds<F: FnOnce() -> bool>(&self, is_ready: F) {
+        self.receivers.rearm_fds(is_ready);
+    }
+
+    /// Disconnects the channel and wakes up all blocked senders and receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        let was_connected = {
+            let mut inner = self.inner.lock();
+            !::std::mem::replace(&mut inner.is_disconnected, true)
+        };
+
+        if was_connected {
+            self.senders.disconnect();
+            self.receivers.disconnect();
+        }
+        was_connected
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.lock().is_disconnected
+    }
+
+    /// Registers an observer that is notified every time a message is taken out of the channel.
+    pub fn watch_drain(&self, oper: Operation, cx: &Context) {
+        self.senders.watch(oper, cx);
+    }
+
+    /// Unregisters an observer registered by `watch_drain`.
+    pub fn unwatch_drain(&self, oper: Operation) {
+        self.senders.unwatch(oper);
+    }
+
+    /// Blocks the current thread until the channel is empty.
+    ///
+    /// Returns `false` if the channel got disconnected while it still held messages, in which case
+    /// they will never be received.
+    pub fn wait_empty(&self) -> bool {
+        let token = &mut Token::default();
+
+        loop {
+            if self.is_empty() {
+                return true;
+            }
+            if self.is_disconnected() {
+                return false;
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a receiver takes a message out. Receivers notify
+                // observers among the senders every time they free a slot.
+                let oper = Operation::hook(token);
+                self.senders.watch(oper, cx);
+
+                // Has the channel become empty or disconnected just now?
+                if self.is_empty() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                cx.wait_until(None);
+                self.senders.unwatch(oper);
+            });
+        }
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.cap
+    }
+}
+
+/// Receiver handle to a channel.
+pub struct Receiver<'a, T: 'a>(&'a Channel<T>);
+
+/// Sender handle to a channel.
+pub struct Sender<'a, T: 'a>(&'a Channel<T>);
+
+impl<'a, T> SelectHandle for Receiver<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        let inner = self.0.inner.lock();
+        inner.heap.len() > inner.recv_reserved || inner.is_disconnected
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.receivers.unwatch(oper);
+    }
+}
+
+impl<'a, T> SelectHandle for Sender<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_send(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.senders.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.senders.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        let inner = self.0.inner.lock();
+        inner.heap.len() + inner.send_reserved < self.0.cap || inner.is_disconnected
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.senders.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.senders.unwatch(oper);
+    }
+}
@@ -1,8 +1,27 @@
 //! Unbounded channel implemented as a linked list.
+//!
+//! Memory orderings:
+//!   - A message is published by setting `WRITE` in the slot state with `Release` and taken after
+//!     loading the state with `Acquire`. Block pointers are published with `Release` and loaded
+//!     with `Acquire` so that a block is fully initialized before anyone follows a pointer to it.
+//!   - Moving the head or the tail index is `SeqCst`, and so is the fence before a receiver loads
+//!     the tail to check whether the channel is empty. This puts all moves and all emptiness
+//!     checks into a single total order, so that a failed `try_recv` is consistent with `len` and
+//!     `is_empty`, and a blocked receiver cannot miss a message sent before it went to sleep.
+//!   - Destroying a block is coordinated by `READ` and `DESTROY` with `AcqRel`, so that the last
+//!     reader of a block and the thread freeing it see each other's accesses.
+//!
+//! Locked head:
+//!   - A receiver can lock the head in place by setting `LOCK_BIT` in it, so that it can inspect
+//!     the message there before deciding whether to take it. Other receivers treat the channel as
+//!     empty until the head is unlocked, which wakes up all of them, rather than spin on the lock.
+//!   - The thread holding the lock is recorded so that it can't block on the channel itself.
 
 use std::cell::UnsafeCell;
 use std::marker::PhantomData;
 use std::mem::{self, ManuallyDrop};
+#[cfg(all(unix, feature = "os_poll"))]
+use std::os::unix::io::RawFd;
 use std::ptr;
 use std::sync::atomic::{self, AtomicPtr, AtomicUsize, Ordering};
 use std::time::Instant;
@@ -12,6 +31,7 @@ use crossbeam_utils::{Backoff, CachePadded};
 use context::Context;
 use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
 use select::{Operation, SelectHandle, Selected, Token};
+use utils::{self, DropHandler, Spinlock};
 use waker::SyncWaker;
 
 // TODO(stjepang): Once we bump the minimum required Rust version to 1.28 or newer, re-apply the
@@ -33,11 +53,17 @@ const LAP: usize = 32;
 // The maximum number of messages a block can hold.
 const BLOCK_CAP: usize = LAP - 1;
 // How many lower bits are reserved for metadata.
-const SHIFT: usize = 1;
+const SHIFT: usize = 2;
 // Has two different purposes:
 // * If set in head, indicates that the block is not the last one.
 // * If set in tail, indicates that the channel is disconnected.
 const MARK_BIT: usize = 1;
+// If set in head, indicates that a receiver has locked the head in place in order to inspect the
+// message in it.
+const LOCK_BIT: usize = 2;
+
+// The number of times `len_exact` retries before it gives up and falls back to `len`.
+const LEN_EXACT_ATTEMPTS: usize = 64;
 
 /// A slot in a block.
 struct Slot<T> {
@@ -155,12 +181,23 @@ pub struct Channel<T> {
     /// Receivers waiting while the channel is empty and not disconnected.
     receivers: SyncWaker,
 
+    /// Senders waiting for the channel to become empty.
+    drainers: SyncWaker,
+
+    /// The thread that has locked the head, as returned by `utils::thread_token`, or zero.
+    head_owner: AtomicUsize,
+
+    /// Receives the messages left in the channel when the last receiver is dropped.
+    drop_handler: Spinlock<Option<DropHandler<T>>>,
+
     /// Indicates that dropping a `Channel<T>` may drop messages of type `T`.
     _marker: PhantomData<T>,
 }
 
 impl<T> Channel<T> {
     /// Creates a new unbounded channel.
+    ///
+    /// No blocks are allocated here. The first block is installed by the first send operation.
     pub fn new() -> Self {
         Channel {
             head: CachePadded::new(Position {
@@ -172,21 +209,47 @@ impl<T> Channel<T> {
                 index: AtomicUsize::new(0),
             }),
             receivers: SyncWaker::new(),
+            drainers: SyncWaker::new(),
+            head_owner: AtomicUsize::new(0),
+            drop_handler: Spinlock::new(None),
             _marker: PhantomData,
         }
     }
 
+    /// Sets a handler that receives the messages left in the channel when the last receiver is
+    /// dropped.
+    pub fn with_drop_handler(mut self, handler: DropHandler<T>) -> Self {
+        self.drop_handler = Spinlock::new(Some(handler));
+        self
+    }
+
+    /// Hands the remaining messages over to the drop handler, if there is one.
+    ///
+    /// This is called by the last receiver after disconnecting the channel, so no new messages
+    /// can arrive, and messages that are still being written are waited for.
+    pub fn hand_off_remaining(&self) {
+        let handler = self.drop_handler.lock().take();
+        if let Some(mut handler) = handler {
+            while let Ok(msg) = self.try_recv() {
+                handler(msg);
+            }
+        }
+    }
+
     /// Returns a receiver handle to the channel.
-    pub fn receiver(&self) -> Receiver<T> {
+    pub fn receiver(&self) -> Receiver<'_, T> {
         Receiver(self)
     }
 
     /// Returns a sender handle to the channel.
-    pub fn sender(&self) -> Sender<T> {
+    pub fn sender(&self) -> Sender<'_, T> {
         Sender(self)
     }
 
     /// Attempts to reserve a slot for sending a message.
+    ///
+    /// This always succeeds, either by reserving a slot or by finding the channel disconnected,
+    /// in which case the token records a null block.
     fn start_send(&self, token: &mut Token) -> bool {
         let backoff = Backoff::new();
         let mut tail = self.tail.index.load(Ordering::Acquire);
@@ -225,8 +288,8 @@ impl<T> Channel<T> {
                 if self
                     .tail
                     .block
-                    .compare_and_swap(block, new, Ordering::Release)
-                    == block
+                    .compare_exchange(block, new, Ordering::Release, Ordering::Relaxed)
+                    .is_ok()
                 {
                     self.head.block.store(new, Ordering::Release);
                     block = new;
@@ -240,7 +303,8 @@ impl<T> Channel<T> {
 
             let new_tail = tail + (1 << SHIFT);
 
-            // Try advancing the tail forward.
+            // Try advancing the tail forward. This is `SeqCst` to pair with the fence in
+            // `start_recv`.
             match self.tail.index.compare_exchange_weak(
                 tail,
                 new_tail,
@@ -290,11 +354,30 @@ impl<T> Channel<T> {
 
     /// Attempts to reserve a slot for receiving a message.
     fn start_recv(&self, token: &mut Token) -> bool {
+        self.claim_head(token, false)
+    }
+
+    /// Attempts to claim the slot at the head of the channel.
+    ///
+    /// If `peek` is `false`, the head is moved forward. Otherwise, the head is locked in place so
+    /// that the message can be inspected before deciding whether to receive it.
+    fn claim_head(&self, token: &mut Token, peek: bool) -> bool {
         let backoff = Backoff::new();
         let mut head = self.head.index.load(Ordering::Acquire);
         let mut block = self.head.block.load(Ordering::Acquire);
 
         loop {
+            // If a receiver has locked the head, the receive operation is not ready until the head
+            // is unlocked. Other receivers see the channel as empty meanwhile, so if it is
+            // disconnected, they receive an error.
+            if head & LOCK_BIT != 0 {
+                if self.is_disconnected() {
+                    token.list.block = ptr::null();
+                    return true;
+                }
+                return false;
+            }
+
             // Calculate the offset of the index into the block.
             let offset = (head >> SHIFT) % LAP;
 
@@ -340,7 +423,12 @@ impl<T> Channel<T> {
                 continue;
             }
 
-            // Try moving the head index forward.
+            // When peeking, lock the head in place instead of moving it forward.
+            if peek {
+                new_head = head | LOCK_BIT;
+            }
+
+            // Try moving the head index forward. This is `SeqCst` for the same reason as the tail.
             match self.head.index.compare_exchange_weak(
                 head,
                 new_head,
@@ -348,16 +436,12 @@ impl<T> Channel<T> {
                 Ordering::Acquire,
             ) {
                 Ok(_) => unsafe {
-                    // If we've reached the end of the block, move to the next one.
-                    if offset + 1 == BLOCK_CAP {
-                        let next = (*block).wait_next();
-                        let mut next_index = (new_head & !MARK_BIT).wrapping_add(1 << SHIFT);
-                        if !(*next).next.load(Ordering::Relaxed).is_null() {
-                            next_index |= MARK_BIT;
-                        }
-
-                        self.head.block.store(next, Ordering::Release);
-                        self.head.index.store(next_index, Ordering::Release);
+                    if peek {
+                        self.head_owner
+                            .store(utils::thread_token(), Ordering::Relaxed);
+                    } else if offset + 1 == BLOCK_CAP {
+                        // We've reached the end of the block, so move to the next one.
+                        self.next_head_block(block, new_head);
                     }
 
                     token.list.block = block as *const u8;
@@ -373,6 +457,122 @@ impl<T> Channel<T> {
         }
     }
 
+    /// Moves the head into the block following `block`, once its last slot has been claimed.
+    unsafe fn next_head_block(&self, block: *const Block<T>, new_head: usize) {
+        let next = (*block).wait_next();
+        let mut next_index = (new_head & !MARK_BIT).wrapping_add(1 << SHIFT);
+        if !(*next).next.load(Ordering::Relaxed).is_null() {
+            next_index |= MARK_BIT;
+        }
+
+        self.head.block.store(next, Ordering::Release);
+        self.head.index.store(next_index, Ordering::Release);
+    }
+
+    /// Passes a reference to the message at the locked head to `f`, then either removes the
+    /// message if `f` returns `true`, or leaves it in the channel and unlocks the head otherwise.
+    ///
+    /// The head is unlocked even if `f` panics.
+    pub unsafe fn peek_decide<F>(&self, token: &mut Token, f: F) -> Result<Option<T>, ()>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        if token.list.block.is_null() {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        /// Stores a new value into the head index when dropped, which unlocks it.
+        struct Unlock<'a, T: 'a> {
+            chan: &'a Channel<T>,
+            value: usize,
+        }
+
+        impl<'a, T> Drop for Unlock<'a, T> {
+            fn drop(&mut self) {
+                self.chan.unlock_head(self.value);
+            }
+        }
+
+        // Nobody else can move the head while it is locked.
+        let head = self.head.index.load(Ordering::Relaxed) & !LOCK_BIT;
+        let mut unlock = Unlock {
+            chan: self,
+            value: head,
+        };
+
+        // Wait for the message to be written, then inspect it.
+        let block = token.list.block as *mut Block<T>;
+        let offset = token.list.offset;
+        let slot = (*block).slots.get_unchecked(offset);
+        slot.wait_write();
+        if !f(&*slot.msg.get()) {
+            // Leave the message in the channel.
+            return Ok(None);
+        }
+
+        let mut new_head = head + (1 << SHIFT);
+
+        // If head and tail are not in the same block, set `MARK_BIT` in head.
+        if new_head & MARK_BIT == 0 {
+            atomic::fence(Ordering::SeqCst);
+            let tail = self.tail.index.load(Ordering::Relaxed);
+
+            if (head >> SHIFT) / LAP != (tail >> SHIFT) / LAP {
+                new_head |= MARK_BIT;
+            }
+        }
+
+        // Unlock the head and move it forward at the same time.
+        unlock.value = new_head;
+        drop(unlock);
+
+        // If we've reached the end of the block, move to the next one.
+        if offset + 1 == BLOCK_CAP {
+            self.next_head_block(block, new_head);
+        }
+
+        self.read(token).map(Some)
+    }
+
+    /// Unlocks the head by storing `index` into it, and wakes up receivers waiting for the lock.
+    fn unlock_head(&self, index: usize) {
+        self.head_owner.store(0, Ordering::Relaxed);
+
+        // This moves the head just like the CAS in `start_recv`, so it is `SeqCst` too.
+        self.head.index.store(index, Ordering::SeqCst);
+
+        // The message at the head may be ready now, and so may be the ones after it if it was
+        // taken, so all waiting receivers are woken up.
+        self.receivers.notify_all();
+    }
+
+    /// Returns `true` if a receiver has locked the head.
+    fn is_head_locked(&self) -> bool {
+        self.head.index.load(Ordering::SeqCst) & LOCK_BIT != 0
+    }
+
+    /// Panics if the current thread has locked the head, since the lock would never be released
+    /// while it blocks on the channel.
+    fn assert_not_head_owner(&self) {
+        assert!(
+            !(self.is_head_locked()
+                && self.head_owner.load(Ordering::Relaxed) == utils::thread_token()),
+            "cannot block on a channel while inspecting its front"
+        );
+    }
+
+    /// Returns `true` if a receive operation might not have to block.
+    ///
+    /// A locked head blocks receivers until it is unlocked, which notifies them, or until the
+    /// channel is disconnected.
+    fn is_recv_ready(&self) -> bool {
+        if self.is_head_locked() {
+            return self.is_disconnected();
+        }
+        !self.is_empty() || self.is_disconnected()
+    }
+
     /// Reads a message from the channel.
     pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
         if token.list.block.is_null() {
@@ -396,10 +596,15 @@ impl<T> Channel<T> {
             Block::destroy(block, offset + 1);
         }
 
+        // Wake senders waiting for the channel to become empty.
+        self.drainers.notify();
         Ok(msg)
     }
 
     /// Attempts to send a message into the channel.
+    ///
+    /// The channel is never full: a new block is allocated when needed, and allocation failure
+    /// aborts rather than being reported as an error.
     pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
         self.send(msg, None).map_err(|err| match err {
             SendTimeoutError::Disconnected(msg) => TrySendError::Disconnected(msg),
@@ -431,14 +636,42 @@ impl<T> Channel<T> {
     /// Receives a message from the channel.
     pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
         let token = &mut Token::default();
+        self.wait_recv(token, deadline, false)?;
+        let res = unsafe { self.read(token) };
+        res.map_err(|_| RecvTimeoutError::Disconnected)
+    }
+
+    /// Waits for a message and passes a reference to it to `f`, which decides whether to receive
+    /// it or leave it in the channel.
+    pub fn recv_peek_decide<F>(
+        &self,
+        deadline: Option<Instant>,
+        f: F,
+    ) -> Result<Option<T>, RecvTimeoutError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        let token = &mut Token::default();
+        self.wait_recv(token, deadline, true)?;
+        let res = unsafe { self.peek_decide(token, f) };
+        res.map_err(|_| RecvTimeoutError::Disconnected)
+    }
+
+    /// Blocks until a slot for receiving a message is reserved or the deadline is reached.
+    ///
+    /// If `peek` is `true`, the head is locked in place instead of moved forward.
+    fn wait_recv(
+        &self,
+        token: &mut Token,
+        deadline: Option<Instant>,
+        peek: bool,
+    ) -> Result<(), RecvTimeoutError> {
         loop {
             // Try receiving a message several times.
             let backoff = Backoff::new();
             loop {
-                if self.start_recv(token) {
-                    unsafe {
-                        return self.read(token).map_err(|_| RecvTimeoutError::Disconnected);
-                    }
+                if self.claim_head(token, peek) {
+                    return Ok(());
                 }
 
                 if backoff.is_completed() {
@@ -447,6 +680,7 @@ impl<T> Channel<T> {
                     backoff.snooze();
                 }
             }
+            self.assert_not_head_owner();
 
             if let Some(d) = deadline {
                 if Instant::now() >= d {
@@ -460,7 +694,7 @@ impl<T> Channel<T> {
                 self.receivers.register(oper, cx);
 
                 // Has the channel become ready just now?
-                if !self.is_empty() || self.is_disconnected() {
+                if self.is_recv_ready() {
                     let _ = cx.try_select(Selected::Aborted);
                 }
 
@@ -517,12 +751,55 @@ impl<T> Channel<T> {
         }
     }
 
+    /// Returns the number of messages in the channel, retrying until the head stays put too.
+    ///
+    /// This is best-effort only: slots reserved by senders that are still writing are counted, and
+    /// so are slots claimed by receivers that are still reading.
+    pub fn len_exact(&self) -> usize {
+        let backoff = Backoff::new();
+
+        for _ in 0..LEN_EXACT_ATTEMPTS {
+            // `len` loads the head between two loads of the tail, so if the head didn't change
+            // around it either, both indices were consistent at a single instant.
+            let head = self.head.index.load(Ordering::SeqCst);
+            let len = self.len();
+
+            if self.head.index.load(Ordering::SeqCst) == head {
+                return len;
+            }
+            backoff.spin();
+        }
+
+        // The head kept moving, so settle for a count that may be inconsistent.
+        self.len()
+    }
+
     /// Returns the capacity of the channel.
     pub fn capacity(&self) -> Option<usize> {
         None
     }
 
-    /// Disconnects the channel and wakes up all blocked receivers.
+    /// Registers a file descriptor to be signalled whenever a receive operation may have become
+    /// ready.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn watch_fd(&self, fd: RawFd) {
+        self.receivers.watch_fd(fd);
+    }
+
+    /// Unregisters a file descriptor.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn unwatch_fd(&self, fd: RawFd) {
+        self.receivers.unwatch_fd(fd);
+    }
+
+    /// Lets the next notification signal the file descriptors again, after a receive operation
+    /// has found the channel empty.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn rearm_fds<F: FnOnce() -> bool>(&self, is_ready: F) {
+        self.receivers.rearm_fds(is_ready);
+    }
+
+    /// Disconnects the channel and wakes up all blocked receivers and waiting senders.
     ///
     /// Returns `true` if this call disconnected the channel.
     pub fn disconnect(&self) -> bool {
@@ -530,6 +807,7 @@ impl<T> Channel<T> {
 
         if tail & MARK_BIT == 0 {
             self.receivers.disconnect();
+            self.drainers.disconnect();
             true
         } else {
             false
@@ -541,6 +819,48 @@ impl<T> Channel<T> {
         self.tail.index.load(Ordering::SeqCst) & MARK_BIT != 0
     }
 
+    /// Registers an observer that is notified every time a message is taken out of the channel.
+    pub fn watch_drain(&self, oper: Operation, cx: &Context) {
+        self.drainers.watch(oper, cx);
+    }
+
+    /// Unregisters an observer registered by `watch_drain`.
+    pub fn unwatch_drain(&self, oper: Operation) {
+        self.drainers.unwatch(oper);
+    }
+
+    /// Blocks the current thread until the channel is empty.
+    ///
+    /// Returns `false` if the channel got disconnected while it still held messages, in which case
+    /// they will never be received.
+    pub fn wait_empty(&self) -> bool {
+        let token = &mut Token::default();
+
+        loop {
+            if self.is_empty() {
+                return true;
+            }
+            if self.is_disconnected() {
+                return false;
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a receiver takes a message out.
+                let oper = Operation::hook(token);
+                self.drainers.watch(oper, cx);
+
+                // Has the channel become empty or disconnected just now?
+                if self.is_empty() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                cx.wait_until(None);
+                self.drainers.unwatch(oper);
+            });
+        }
+    }
+
     /// Returns `true` if the channel is empty.
     pub fn is_empty(&self) -> bool {
         let head = self.head.index.load(Ordering::SeqCst);
@@ -607,6 +927,7 @@ impl<'a, T> SelectHandle for Receiver<'a, T> {
     }
 
     fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.assert_not_head_owner();
         self.0.receivers.register(oper, cx);
         self.is_ready()
     }
@@ -620,10 +941,11 @@ impl<'a, T> SelectHandle for Receiver<'a, T> {
     }
 
     fn is_ready(&self) -> bool {
-        !self.0.is_empty() || self.0.is_disconnected()
+        self.0.is_recv_ready()
     }
 
     fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.assert_not_head_owner();
         self.0.receivers.watch(oper, cx);
         self.is_ready()
     }
@@ -635,6 +957,9 @@ impl<'a, T> SelectHandle for Receiver<'a, T> {
 
 impl<'a, T> SelectHandle for Sender<'a, T> {
     fn try_select(&self, token: &mut Token) -> bool {
+        // Sending into an unbounded channel never blocks, so a send operation is always selected
+        // on the first attempt, before anything gets registered. A selection containing one
+        // therefore never parks the thread, and registering is a no-op.
         self.0.start_send(token)
     }
 
@@ -0,0 +1,265 @@
+//! Channel that receives messages from two other receivers.
+//!
+//! Messages cannot be sent into this kind of channel. The merged receiver is disconnected once
+//! both inputs are disconnected and empty.
+//!
+//! Blocking operations wait for either input to become ready using the same mechanism as
+//! `Select::ready`, which only observes readiness and never takes part in a rendezvous. Messages
+//! are then taken with `try_recv`, so inputs of any flavor can be merged, including zero-capacity
+//! channels.
+
+use std::cmp;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use channel::Receiver;
+use context::Context;
+use err::{RecvTimeoutError, TryRecvError};
+use select::{Operation, Select, SelectHandle, Token};
+use utils::{BoxedCallback, Spinlock};
+
+/// The token type for the merge flavor.
+///
+/// It is `true` if a message was taken and `false` if the channel is disconnected.
+pub type MergeToken = bool;
+
+/// Channel that receives messages from two other receivers.
+pub struct Channel<T> {
+    /// The input receivers.
+    inputs: [Receiver<T>; 2],
+
+    /// Set for each input once it is known to be empty and disconnected.
+    disconnected: [AtomicBool; 2],
+
+    /// The input to try first in the next receive operation.
+    next: AtomicUsize,
+
+    /// Messages taken by select operations but not read yet.
+    ///
+    /// Every message is pushed by `try_select` and popped by the following `read` on the same
+    /// thread, so the queue never runs empty on `read`.
+    selected: Spinlock<VecDeque<T>>,
+}
+
+impl<T> Channel<T> {
+    /// Creates a channel that receives messages from `a` and `b`.
+    pub fn new(a: Receiver<T>, b: Receiver<T>) -> Self {
+        Channel {
+            inputs: [a, b],
+            disconnected: [AtomicBool::new(false), AtomicBool::new(false)],
+            next: AtomicUsize::new(0),
+            selected: Spinlock::new(VecDeque::new()),
+        }
+    }
+
+    /// Returns `true` if input `i` is known to be disconnected.
+    fn is_input_disconnected(&self, i: usize) -> bool {
+        self.disconnected[i].load(Ordering::SeqCst)
+    }
+
+    /// Attempts to receive a message without blocking.
+    ///
+    /// The inputs are tried in alternating order so that neither of them gets starved.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+
+        for k in 0..2 {
+            let i = (start + k) % 2;
+            if self.is_input_disconnected(i) {
+                continue;
+            }
+
+            match self.inputs[i].try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    self.disconnected[i].store(true, Ordering::SeqCst)
+                }
+            }
+        }
+
+        if self.is_disconnected() {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        loop {
+            match self.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            // Wait until one of the inputs that are still connected becomes ready.
+            let mut sel = Select::new();
+            for i in 0..2 {
+                if !self.is_input_disconnected(i) {
+                    sel.recv(&self.inputs[i]);
+                }
+            }
+
+            match deadline {
+                None => {
+                    sel.ready();
+                }
+                Some(d) => {
+                    let now = Instant::now();
+                    if now >= d {
+                        return Err(RecvTimeoutError::Timeout);
+                    }
+                    let _ = sel.ready_timeout(d - now);
+                }
+            }
+        }
+    }
+
+    /// Reads a message from the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if token.merge {
+            Ok(self.selected.lock().pop_front().unwrap())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Returns `true` if both inputs are disconnected and empty.
+    pub fn is_disconnected(&self) -> bool {
+        self.is_input_disconnected(0) && self.is_input_disconnected(1)
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inputs.iter().all(|r| r.is_empty())
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        false
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.inputs.iter().map(|r| r.len()).sum()
+    }
+
+    /// Returns the sum of the exact lengths of both inputs.
+    pub fn len_exact(&self) -> usize {
+        self.inputs.iter().map(|r| r.len_exact()).sum()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Registers an observer that is notified every time a message is taken out of either input.
+    pub fn watch_drain(&self, oper: Operation, cx: &Context) {
+        for r in &self.inputs {
+            r.watch_drain(oper, cx);
+        }
+    }
+
+    /// Unregisters an observer registered by `watch_drain`.
+    pub fn unwatch_drain(&self, oper: Operation) {
+        for r in &self.inputs {
+            r.unwatch_drain(oper);
+        }
+    }
+
+    /// Returns the time since a message was last received from either input.
+    pub fn idle_duration(&self) -> Duration {
+        cmp::min(
+            self.inputs[0].idle_duration(),
+            self.inputs[1].idle_duration(),
+        )
+    }
+
+    /// Registers a callback to run once both inputs are disconnected.
+    ///
+    /// The callback is boxed because inputs may be merged channels themselves, so a generic
+    /// callback type would keep growing with every level of recursion.
+    pub fn on_disconnect_once(&self, f: BoxedCallback) {
+        let remaining = Arc::new(AtomicUsize::new(2));
+        let f = Arc::new(Mutex::new(Some(f)));
+
+        for r in &self.inputs {
+            let remaining = remaining.clone();
+            let f = f.clone();
+            r.on_disconnect_once(move || {
+                if remaining.fetch_sub(1, Ordering::AcqRel) == 1 {
+                    let f = f.lock().unwrap().take();
+                    if let Some(mut f) = f {
+                        f();
+                    }
+                }
+            });
+        }
+    }
+
+    /// Returns the input receivers.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn inputs(&self) -> &[Receiver<T>; 2] {
+        &self.inputs
+    }
+}
+
+impl<T> SelectHandle for Channel<T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        match self.try_recv() {
+            Ok(msg) => {
+                self.selected.lock().push_back(msg);
+                token.merge = true;
+                true
+            }
+            Err(TryRecvError::Disconnected) => {
+                token.merge = false;
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.watch(oper, cx)
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.unwatch(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        // A disconnected input is always ready, so it must be skipped once known, or else a
+        // blocking operation would keep waking up while the other input is empty.
+        self.is_disconnected()
+            || (0..2).any(|i| !self.is_input_disconnected(i) && self.inputs[i].is_ready())
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        for i in 0..2 {
+            if !self.is_input_disconnected(i) {
+                self.inputs[i].watch(oper, cx);
+            }
+        }
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        for r in &self.inputs {
+            r.unwatch(oper);
+        }
+    }
+}
@@ -1,17 +1,29 @@
 //! Channel flavors.
 //!
-//! There are six flavors:
+//! There are twelve flavors:
 //!
 //! 1. `after` - Channel that delivers a message after a certain amount of time.
 //! 2. `array` - Bounded channel based on a preallocated array.
-//! 3. `list` - Unbounded channel implemented as a linked list.
-//! 4. `never` - Channel that never delivers messages.
-//! 5. `tick` - Channel that delivers messages periodically.
-//! 6. `zero` - Zero-capacity channel.
+//! 3. `coalesce` - Unbounded channel that keeps only the latest message for each key.
+//! 4. `heap` - Bounded channel that receives messages in priority order.
+//! 5. `list` - Unbounded channel implemented as a linked list.
+//! 6. `merge` - Channel that receives messages from two other receivers.
+//! 7. `never` - Channel that never delivers messages.
+//! 8. `oneshot` - Single-use channel that carries at most one message.
+//! 9. `priority` - Bounded channel with a priority lane for urgent messages.
+//! 10. `ready` - Channel that always delivers a clone of the same message.
+//! 11. `tick` - Channel that delivers messages periodically.
+//! 12. `zero` - Zero-capacity channel.
 
 pub mod after;
 pub mod array;
+pub mod coalesce;
+pub mod heap;
 pub mod list;
+pub mod merge;
 pub mod never;
+pub mod oneshot;
+pub mod priority;
+pub mod ready;
 pub mod tick;
 pub mod zero;
@@ -0,0 +1,437 @@
+//! Single-use channel that carries at most one message.
+//!
+//! This flavor has a capacity of one, but unlike the array flavor, its single slot can be used
+//! only once. After a message has been sent, further sends fail, and after it has been received,
+//! the channel becomes disconnected.
+
+use std::cell::UnsafeCell;
+#[cfg(all(unix, feature = "os_poll"))]
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crossbeam_utils::Backoff;
+
+use context::Context;
+use err::{RecvTimeoutError, SendOnceError, SendTimeoutError, TryRecvError, TrySendError};
+use select::{Operation, SelectHandle, Selected, Token};
+use waker::SyncWaker;
+
+// The phase of the slot. Each phase is followed by the one with the next integer value.
+//
+// * No message has been sent yet.
+const EMPTY: usize = 0;
+// * A sender has claimed the slot and is writing the message into it.
+const WRITING: usize = 1;
+// * The message has been written and can be received.
+const WRITTEN: usize = 2;
+// * A receiver has claimed the message and is reading it.
+const READING: usize = 3;
+// * The message has been received.
+const READ: usize = 4;
+
+// The mark bit, which indicates that all senders or all receivers have been dropped.
+const MARK_BIT: usize = 8;
+
+/// The token type for the oneshot flavor.
+///
+/// It is `true` if a slot was claimed and `false` if the channel is disconnected.
+pub type OneshotToken = bool;
+
+/// Single-use channel that carries at most one message.
+pub struct Channel<T> {
+    /// The phase of the slot, together with the mark bit.
+    state: AtomicUsize,
+
+    /// The message.
+    msg: UnsafeCell<Option<T>>,
+
+    /// Receivers waiting while the channel is empty and not disconnected.
+    receivers: SyncWaker,
+}
+
+impl<T> Channel<T> {
+    /// Creates a new oneshot channel.
+    pub fn new() -> Self {
+        Channel {
+            state: AtomicUsize::new(EMPTY),
+            msg: UnsafeCell::new(None),
+            receivers: SyncWaker::new(),
+        }
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub fn receiver(&self) -> Receiver<'_, T> {
+        Receiver(self)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub fn sender(&self) -> Sender<'_, T> {
+        Sender(self)
+    }
+
+    /// Attempts to claim the slot for sending a message.
+    ///
+    /// This operation is always ready: it either claims the slot or fails because the slot was
+    /// already used or the channel is disconnected.
+    fn start_send(&self, token: &mut Token) -> bool {
+        token.oneshot = self
+            .state
+            .compare_exchange(EMPTY, WRITING, Ordering::SeqCst, Ordering::Relaxed)
+            .is_ok();
+        true
+    }
+
+    /// Writes a message into the channel.
+    pub unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        // If the slot was not claimed, it is used up or the channel is disconnected.
+        if !token.oneshot {
+            return Err(msg);
+        }
+
+        // Write the message and move from `WRITING` to `WRITTEN`, preserving the mark bit.
+        *self.msg.get() = Some(msg);
+        self.state.fetch_add(1, Ordering::SeqCst);
+
+        // Wake a sleeping receiver.
+        self.receivers.notify();
+        Ok(())
+    }
+
+    /// Attempts to claim the message for receiving.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        let backoff = Backoff::new();
+        let mut state = self.state.load(Ordering::SeqCst);
+
+        loop {
+            match state & !MARK_BIT {
+                EMPTY => {
+                    if state & MARK_BIT != 0 {
+                        // No message will ever be sent, so receive an error.
+                        token.oneshot = false;
+                        return true;
+                    } else {
+                        // Otherwise, the receive operation is not ready.
+                        return false;
+                    }
+                }
+                WRITING => {
+                    // Snooze because we need to wait for the message to get written.
+                    backoff.snooze();
+                    state = self.state.load(Ordering::SeqCst);
+                }
+                WRITTEN => {
+                    // Try moving from `WRITTEN` to `READING`.
+                    match self.state.compare_exchange_weak(
+                        state,
+                        state + 1,
+                        Ordering::SeqCst,
+                        Ordering::Relaxed,
+                    ) {
+                        Ok(_) => {
+                            token.oneshot = true;
+                            return true;
+                        }
+                        Err(s) => {
+                            state = s;
+                            backoff.spin();
+                        }
+                    }
+                }
+                READING | READ => {
+                    // The message has already been received, so receive an error.
+                    token.oneshot = false;
+                    return true;
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    /// Reads a message from the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if !token.oneshot {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        // Take the message and move from `READING` to `READ`, preserving the mark bit.
+        let msg = (*self.msg.get()).take().unwrap();
+        self.state.fetch_add(1, Ordering::SeqCst);
+
+        // The channel is now used up, so wake all other sleeping receivers.
+        self.receivers.disconnect();
+        Ok(msg)
+    }
+
+    /// Attempts to send a message into the channel.
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        let token = &mut Token::default();
+        assert!(self.start_send(token));
+        unsafe { self.write(token, msg).map_err(TrySendError::Disconnected) }
+    }
+
+    /// Sends a message into the channel.
+    pub fn send(&self, msg: T, _deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        let token = &mut Token::default();
+        assert!(self.start_send(token));
+        unsafe {
+            self.write(token, msg)
+                .map_err(SendTimeoutError::Disconnected)
+        }
+    }
+
+    /// Sends a message into the channel, reporting whether a failure is due to reuse.
+    pub fn send_once(&self, msg: T) -> Result<(), SendOnceError<T>> {
+        let token = &mut Token::default();
+
+        match self
+            .state
+            .compare_exchange(EMPTY, WRITING, Ordering::SeqCst, Ordering::Relaxed)
+        {
+            Ok(_) => {
+                token.oneshot = true;
+                unsafe {
+                    self.write(token, msg).ok().unwrap();
+                }
+                Ok(())
+            }
+            // The slot has been used, whether or not the channel is also disconnected.
+            Err(state) if state & !MARK_BIT != EMPTY => Err(SendOnceError::AlreadySent(msg)),
+            Err(_) => Err(SendOnceError::Disconnected(msg)),
+        }
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let token = &mut Token::default();
+
+        if self.start_recv(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            // Try receiving a message several times.
+            let backoff = Backoff::new();
+            loop {
+                if self.start_recv(token) {
+                    let res = unsafe { self.read(token) };
+                    return res.map_err(|_| RecvTimeoutError::Disconnected);
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a sender wakes us up.
+                let oper = Operation::hook(token);
+                self.receivers.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if self.receiver().is_ready() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.receivers.unregister(oper).unwrap();
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Returns the current number of messages inside the channel.
+    pub fn len(&self) -> usize {
+        match self.state.load(Ordering::SeqCst) & !MARK_BIT {
+            WRITING | WRITTEN => 1,
+            _ => 0,
+        }
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        Some(1)
+    }
+
+    /// Registers a file descriptor to be signalled whenever a receive operation may have become
+    /// ready.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn watch_fd(&self, fd: RawFd) {
+        self.receivers.watch_fd(fd);
+    }
+
+    /// Unregisters a file descriptor.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn unwatch_fd(&self, fd: RawFd) {
+        self.receivers.unwatch_fd(fd);
+    }
+
+    /// Lets the next notification signal the file descriptors again, after a receive operation
+    /// has found the channel empty.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn rearm_fds<F: FnOnce() -> bool>(&self, is_ready: F) {
+        self.receivers.rearm_fds(is_ready);
+    }
+
+    /// Disconnects the channel and wakes up all blocked receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        let state = self.state.fetch_or(MARK_BIT, Ordering::SeqCst);
+
+        if state & MARK_BIT == 0 {
+            self.receivers.disconnect();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Registers an observer that is notified every time a message is taken out of the channel.
+    pub fn watch_drain(&self, oper: Operation, cx: &Context) {
+        self.receivers.watch(oper, cx);
+    }
+
+    /// Unregisters an observer registered by `watch_drain`.
+    pub fn unwatch_drain(&self, oper: Operation) {
+        self.receivers.unwatch(oper);
+    }
+
+    /// Blocks the current thread until the channel is empty.
+    ///
+    /// Returns `false` if the channel got disconnected while it still held messages, in which case
+    /// they will never be received.
+    pub fn wait_empty(&self) -> bool {
+        let token = &mut Token::default();
+
+        loop {
+            if self.is_empty() {
+                return true;
+            }
+            if self.state.load(Ordering::SeqCst) & MARK_BIT != 0 {
+                return false;
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until the message is received. Taking the message wakes
+                // up everything registered with `receivers`.
+                let oper = Operation::hook(token);
+                self.receivers.watch(oper, cx);
+
+                // Has the channel become empty or disconnected just now?
+                if self.is_empty() || self.state.load(Ordering::SeqCst) & MARK_BIT != 0 {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                cx.wait_until(None);
+                self.receivers.unwatch(oper);
+            });
+        }
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.len() == 1
+    }
+}
+
+/// Receiver handle to a channel.
+pub struct Receiver<'a, T: 'a>(&'a Channel<T>);
+
+/// Sender handle to a channel.
+pub struct Sender<'a, T: 'a>(&'a Channel<T>);
+
+impl<'a, T> SelectHandle for Receiver<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        // Any state other than an empty connected channel means a receive will not block.
+        self.0.state.load(Ordering::SeqCst) != EMPTY
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.receivers.unwatch(oper);
+    }
+}
+
+impl<'a, T> SelectHandle for Sender<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_send(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    fn unregister(&self, _oper: Operation) {}
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn watch(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    fn unwatch(&self, _oper: Operation) {}
+}
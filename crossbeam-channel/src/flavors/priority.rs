@@ -0,0 +1,386 @@
+//! Bounded channel with a priority lane for urgent messages.
+//!
+//! Messages are kept in two queues protected by a lock. Normal messages are appended to the normal
+//! queue and urgent messages to the urgent queue. Receive operations take from the urgent queue
+//! first, so urgent messages cut the line while each queue stays in FIFO order. Both queues share
+//! the capacity of the channel.
+
+use std::collections::VecDeque;
+#[cfg(all(unix, feature = "os_poll"))]
+use std::os::unix::io::RawFd;
+use std::time::Instant;
+
+use crossbeam_utils::Backoff;
+
+use context::Context;
+use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use select::{Operation, SelectHandle, Selected, Token};
+use utils::Spinlock;
+use waker::SyncWaker;
+
+/// The token type for the priority flavor.
+///
+/// It is `true` if a message was reserved and `false` if the channel is disconnected.
+pub type PriorityToken = bool;
+
+/// Inner representation of a priority channel.
+struct Inner<T> {
+    /// Urgent messages, received before any normal message.
+    urgent: VecDeque<T>,
+
+    /// Normal messages.
+    normal: VecDeque<T>,
+
+    /// The number of messages reserved by receive operations but not read yet.
+    reserved: usize,
+
+    /// Equals `true` when the channel is disconnected.
+    is_disconnected: bool,
+}
+
+impl<T> Inner<T> {
+    /// Returns the number of messages in both queues.
+    fn len(&self) -> usize {
+        self.urgent.len() + self.normal.len()
+    }
+
+    /// Attempts to reserve a message for receiving.
+    fn reserve(&mut self, token: &mut Token) -> bool {
+        if self.len() > self.reserved {
+            self.reserved += 1;
+            token.priority = true;
+            true
+        } else if self.is_disconnected {
+            token.priority = false;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Takes the next message out of the queues, urgent messages first.
+    fn pop(&mut self) -> Option<T> {
+        self.urgent.pop_front().or_else(|| self.normal.pop_front())
+    }
+}
+
+/// Bounded channel with a priority lane for urgent messages.
+pub struct Channel<T> {
+    /// Inner representation of the channel.
+    inner: Spinlock<Inner<T>>,
+
+    /// The capacity shared by both queues.
+    cap: usize,
+
+    /// Senders waiting while the channel is full.
+    senders: SyncWaker,
+
+    /// Receivers waiting while the channel is empty and not disconnected.
+    receivers: SyncWaker,
+}
+
+impl<T> Channel<T> {
+    /// Creates a priority channel of capacity `cap`.
+    pub fn with_capacity(cap: usize) -> Self {
+        assert!(cap > 0, "capacity must be positive");
+
+        Channel {
+            inner: Spinlock::new(Inner {
+                urgent: VecDeque::with_capacity(cap),
+                normal: VecDeque::with_capacity(cap),
+                reserved: 0,
+                is_disconnected: false,
+            }),
+            cap,
+            senders: SyncWaker::new(),
+            receivers: SyncWaker::new(),
+        }
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub fn receiver(&self) -> Receiver<'_, T> {
+        Receiver(self)
+    }
+
+    /// Attempts to push a message into one of the queues.
+    ///
+    /// Returns the message back if the channel is full or disconnected.
+    fn push(&self, msg: T, urgent: bool) -> Result<(), TrySendError<T>> {
+        {
+            let mut inner = self.inner.lock();
+
+            if inner.is_disconnected {
+                return Err(TrySendError::Disconnected(msg));
+            }
+            if inner.len() == self.cap {
+                return Err(TrySendError::Full(msg));
+            }
+
+            if urgent {
+                inner.urgent.push_back(msg);
+            } else {
+                inner.normal.push_back(msg);
+            }
+        }
+
+        // Wake a sleeping receiver.
+        self.receivers.notify();
+        Ok(())
+    }
+
+    /// Attempts to send a message into the channel.
+    pub fn try_send(&self, msg: T, urgent: bool) -> Result<(), TrySendError<T>> {
+        self.push(msg, urgent)
+    }
+
+    /// Sends a message into the channel.
+    pub fn send(
+        &self,
+        mut msg: T,
+        urgent: bool,
+        deadline: Option<Instant>,
+    ) -> Result<(), SendTimeoutError<T>> {
+        let token = &mut Token::default();
+        loop {
+            // Try sending a message several times.
+            let backoff = Backoff::new();
+            loop {
+                match self.push(msg, urgent) {
+                    Ok(()) => return Ok(()),
+                    Err(TrySendError::Disconnected(m)) => {
+                        return Err(SendTimeoutError::Disconnected(m));
+                    }
+                    Err(TrySendError::Full(m)) => msg = m,
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(SendTimeoutError::Timeout(msg));
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a receiver wakes us up.
+                let oper = Operation::hook(token);
+                self.senders.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if !self.is_full() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.senders.unregister(oper).unwrap();
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Attempts to reserve a message for receiving.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        self.inner.lock().reserve(token)
+    }
+
+    /// Reads a message from the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if !token.priority {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        let msg = {
+            let mut inner = self.inner.lock();
+            inner.reserved -= 1;
+            inner.pop().unwrap()
+        };
+
+        // Wake a sleeping sender.
+        self.senders.notify();
+        Ok(msg)
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let token = &mut Token::default();
+
+        if self.start_recv(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            // Try receiving a message several times.
+            let backoff = Backoff::new();
+            loop {
+                if self.start_recv(token) {
+                    let res = unsafe { self.read(token) };
+                    return res.map_err(|_| RecvTimeoutError::Disconnected);
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a sender wakes us up.
+                let oper = Operation::hook(token);
+                self.receivers.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if self.receiver().is_ready() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.receivers.unregister(oper).unwrap();
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Returns the current number of messages inside the channel.
+    pub fn len(&self) -> usize {
+        self.inner.lock().len()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        Some(self.cap)
+    }
+
+    /// Registers a file descriptor to be signalled whenever a receive operation may have become
+    /// ready.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn watch_fd(&self, fd: RawFd) {
+        self.receivers.watch_fd(fd);
+    }
+
+    /// Unregisters a file descriptor.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn unwatch_fd(&self, fd: RawFd) {
+        self.receivers.unwatch_fd(fd);
+    }
+
+    /// Lets the next notification signal the file descriptors again, after a receive operation
+    /// has found the channel empty.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn rearm_fds<F: FnOnce() -> bool>(&self, is_ready: F) {
+        self.receivers.rearm_fds(is_ready);
+    }
+
+    /// Disconnects the channel and wakes up all blocked senders and receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        let was_connected = {
+            let mut inner = self.inner.lock();
+            !::std::mem::replace(&mut inner.is_disconnected, true)
+        };
+
+        if was_connected {
+            self.senders.disconnect();
+            self.receivers.disconnect();
+        }
+        was_connected
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.lock().is_disconnected
+    }
+
+    /// Registers an observer that is notified every time a message is taken out of the channel.
+    pub fn watch_drain(&self, oper: Operation, cx: &Context) {
+        self.senders.watch(oper, cx);
+    }
+
+    /// Unregisters an observer registered by `watch_drain`.
+    pub fn unwatch_drain(&self, oper: Operation) {
+        self.senders.unwatch(oper);
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.cap
+    }
+}
+
+/// Receiver handle to a channel.
+pub struct Receiver<'a, T: 'a>(&'a Channel<T>);
+
+impl<'a, T> SelectHandle for Receiver<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        let inner = self.0.inner.lock();
+        inner.len() > inner.reserved || inner.is_disconnected
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.receivers.unwatch(oper);
+    }
+}
@@ -0,0 +1,128 @@
+//! Channel that always delivers a clone of the same message.
+//!
+//! Messages cannot be sent into this kind of channel.
+
+use std::sync::Mutex;
+use std::time::Instant;
+
+use context::Context;
+use err::{RecvTimeoutError, TryRecvError};
+use select::{Operation, SelectHandle, Token};
+
+/// This flavor doesn't need a token.
+pub type ReadyToken = ();
+
+/// Channel that always delivers a clone of the same message.
+pub struct Channel<T> {
+    /// The message to deliver.
+    ///
+    /// It is cloned under a lock so that `T` does not need to be `Sync`.
+    msg: Mutex<T>,
+
+    /// Clones the message.
+    ///
+    /// This is a function pointer so that the receiver does not need a `T: Clone` bound.
+    clone: fn(&T) -> T,
+}
+
+impl<T> Channel<T> {
+    /// Creates a channel that always delivers a clone of `msg`.
+    #[inline]
+    pub fn new(msg: T) -> Self
+    where
+        T: Clone,
+    {
+        Channel {
+            msg: Mutex::new(msg),
+            clone: T::clone,
+        }
+    }
+
+    /// Returns a clone of the message.
+    #[inline]
+    fn message(&self) -> T {
+        // A panicking `clone` leaves the message intact, so poisoning can be ignored.
+        let msg = self.msg.lock().unwrap_or_else(|err| err.into_inner());
+        (self.clone)(&msg)
+    }
+
+    /// Attempts to receive a message without blocking.
+    #[inline]
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        Ok(self.message())
+    }
+
+    /// Receives a message from the channel.
+    #[inline]
+    pub fn recv(&self, _deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        Ok(self.message())
+    }
+
+    /// Reads a message from the channel.
+    #[inline]
+    pub unsafe fn read(&self, _token: &mut Token) -> Result<T, ()> {
+        Ok(self.message())
+    }
+
+    /// Returns `true` if the channel is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the channel is full.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        true
+    }
+
+    /// Returns the number of messages in the channel.
+    #[inline]
+    pub fn len(&self) -> usize {
+        1
+    }
+
+    /// Returns the capacity of the channel.
+    #[inline]
+    pub fn capacity(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+impl<T> SelectHandle for Channel<T> {
+    #[inline]
+    fn try_select(&self, _token: &mut Token) -> bool {
+        true
+    }
+
+    #[inline]
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    #[inline]
+    fn register(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    #[inline]
+    fn unregister(&self, _oper: Operation) {}
+
+    #[inline]
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    #[inline]
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn watch(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    #[inline]
+    fn unwatch(&self, _oper: Operation) {}
+}
@@ -2,6 +2,7 @@
 //!
 //! Messages cannot be sent into this kind of channel; they are materialized on demand.
 
+use std::cmp;
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -10,6 +11,7 @@ use crossbeam_utils::atomic::AtomicCell;
 use context::Context;
 use err::{RecvTimeoutError, TryRecvError};
 use select::{Operation, SelectHandle, Token};
+use utils;
 
 /// Result of a receive operation.
 pub type TickToken = Option<Instant>;
@@ -21,6 +23,34 @@ pub struct Channel {
 
     /// The time interval in which messages get delivered.
     duration: Duration,
+
+    /// The maximum random deviation from `duration` in microseconds.
+    jitter: u64,
+}
+
+/// Converts a number of microseconds into a `Duration`.
+fn micros(micros: u64) -> Duration {
+    Duration::new(micros / 1_000_000, (micros % 1_000_000) as u32 * 1_000)
+}
+
+/// Converts a `Duration` into a number of microseconds, rounding down.
+fn to_micros(dur: Duration) -> u64 {
+    dur.as_secs() * 1_000_000 + u64::from(dur.subsec_nanos() / 1_000)
+}
+
+/// Returns `dur` with a random offset of up to `jitter` microseconds in either direction.
+fn interval(dur: Duration, jitter: u64) -> Duration {
+    if jitter == 0 {
+        return dur;
+    }
+
+    // A uniformly distributed offset in `-jitter..=jitter`.
+    let r = utils::random(2 * jitter as usize + 1) as u64;
+    if r >= jitter {
+        dur + micros(r - jitter)
+    } else {
+        dur - micros(jitter - r)
+    }
 }
 
 impl Channel {
@@ -30,9 +60,30 @@ impl Channel {
         Channel {
             delivery_time: AtomicCell::new(Instant::now() + dur),
             duration: dur,
+            jitter: 0,
         }
     }
 
+    /// Creates a channel that delivers messages at randomly varying intervals.
+    ///
+    /// The jitter is truncated to whole microseconds and limited to `dur`, so that intervals are
+    /// never negative, and to about 35 minutes, so that it fits the random number generator.
+    pub fn with_jitter(dur: Duration, jitter: Duration) -> Self {
+        let jitter = cmp::min(to_micros(jitter), to_micros(dur));
+        let jitter = cmp::min(jitter, u64::from(u32::max_value() / 2));
+
+        Channel {
+            delivery_time: AtomicCell::new(Instant::now() + interval(dur, jitter)),
+            duration: dur,
+            jitter,
+        }
+    }
+
+    /// Returns the time until the next message, measured from the current one.
+    fn next_interval(&self) -> Duration {
+        interval(self.duration, self.jitter)
+    }
+
     /// Attempts to receive a message without blocking.
     #[inline]
     pub fn try_recv(&self) -> Result<Instant, TryRecvError> {
@@ -46,7 +97,7 @@ impl Channel {
 
             if self
                 .delivery_time
-                .compare_exchange(delivery_time, now + self.duration)
+                .compare_exchange(delivery_time, now + self.next_interval())
                 .is_ok()
             {
                 return Ok(delivery_time);
@@ -67,7 +118,7 @@ impl Channel {
                 if now >= delivery_time
                     && self
                         .delivery_time
-                        .compare_exchange(delivery_time, now + self.duration)
+                        .compare_exchange(delivery_time, now + self.next_interval())
                         .is_ok()
                 {
                     return Ok(delivery_time);
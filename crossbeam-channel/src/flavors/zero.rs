@@ -1,9 +1,19 @@
 //! Zero-capacity channel.
 //!
 //! This kind of channel is also known as *rendezvous* channel.
+//!
+//! A blocked operation is paired up with an operation on the other side by selecting its
+//! context, and it gives up after a timeout by selecting its own context with
+//! `Selected::Aborted`. Both are compare-and-swap operations on the same atomic, so exactly one
+//! of them succeeds. If the timeout loses the race, the blocked operation completes the handoff
+//! even though its deadline has passed. If the timeout wins, the other side never sees the
+//! operation and keeps looking for a different one. Either way, every message is handed off to
+//! exactly one receiver or stays with its sender.
 
 use std::cell::UnsafeCell;
 use std::marker::PhantomData;
+#[cfg(all(unix, feature = "os_poll"))]
+use std::os::unix::io::RawFd;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 
@@ -13,6 +23,8 @@ use context::Context;
 use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
 use select::{Operation, SelectHandle, Selected, Token};
 use utils::Spinlock;
+#[cfg(all(unix, feature = "os_poll"))]
+use waker::ReadyFds;
 use waker::Waker;
 
 /// A pointer to a packet.
@@ -84,6 +96,10 @@ pub struct Channel<T> {
     /// Inner representation of the channel.
     inner: Spinlock<Inner>,
 
+    /// File descriptors signalled when a sender starts waiting to be paired up.
+    #[cfg(all(unix, feature = "os_poll"))]
+    ready_fds: ReadyFds,
+
     /// Indicates that dropping a `Channel<T>` may drop values of type `T`.
     _marker: PhantomData<T>,
 }
@@ -97,17 +113,19 @@ impl<T> Channel<T> {
                 receivers: Waker::new(),
                 is_disconnected: false,
             }),
+            #[cfg(all(unix, feature = "os_poll"))]
+            ready_fds: ReadyFds::new(),
             _marker: PhantomData,
         }
     }
 
     /// Returns a receiver handle to the channel.
-    pub fn receiver(&self) -> Receiver<T> {
+    pub fn receiver(&self) -> Receiver<'_, T> {
         Receiver(self)
     }
 
     /// Returns a sender handle to the channel.
-    pub fn sender(&self) -> Sender<T> {
+    pub fn sender(&self) -> Sender<'_, T> {
         Sender(self)
     }
 
@@ -231,6 +249,9 @@ impl<T> Channel<T> {
             inner.receivers.notify();
             drop(inner);
 
+            #[cfg(all(unix, feature = "os_poll"))]
+            self.ready_fds.notify();
+
             // Block the current thread.
             let sel = cx.wait_until(deadline);
 
@@ -322,6 +343,25 @@ impl<T> Channel<T> {
         })
     }
 
+    /// Registers a file descriptor to be signalled whenever a receive operation may have become
+    /// ready.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn watch_fd(&self, fd: RawFd) {
+        self.ready_fds.watch(fd);
+    }
+
+    /// Unregisters a file descriptor.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn unwatch_fd(&self, fd: RawFd) {
+        self.ready_fds.unwatch(fd);
+    }
+
+    /// Lets the next sender that starts waiting signal the file descriptors again.
+    #[cfg(all(unix, feature = "os_poll"))]
+    pub fn rearm_fds<F: FnOnce() -> bool>(&self, is_ready: F) {
+        self.ready_fds.rearm(is_ready);
+    }
+
     /// Disconnects the channel and wakes up all blocked senders and receivers.
     ///
     /// Returns `true` if this call disconnected the channel.
@@ -332,6 +372,10 @@ impl<T> Channel<T> {
             inner.is_disconnected = true;
             inner.senders.disconnect();
             inner.receivers.disconnect();
+            drop(inner);
+
+            #[cfg(all(unix, feature = "os_poll"))]
+            self.ready_fds.signal_all();
             true
         } else {
             false
@@ -377,6 +421,10 @@ impl<'a, T> SelectHandle for Receiver<'a, T> {
     fn register(&self, oper: Operation, cx: &Context) -> bool {
         let packet = Box::into_raw(Packet::<T>::empty_on_heap());
 
+        // Registration and the readiness check happen under the same lock. If an operation on
+        // the other side registers concurrently, either it sees our entry and pairs up with us,
+        // or we see its entry here and retry, so both sides can never block waiting for each
+        // other.
         let mut inner = self.0.inner.lock();
         inner
             .receivers
@@ -399,6 +447,8 @@ impl<'a, T> SelectHandle for Receiver<'a, T> {
     }
 
     fn is_ready(&self) -> bool {
+        // There is no buffer, so a receive operation is ready only if a sender is blocked
+        // waiting to be paired up.
         let inner = self.0.inner.lock();
         inner.senders.can_select() || inner.is_disconnected
     }
@@ -427,12 +477,19 @@ impl<'a, T> SelectHandle for Sender<'a, T> {
     fn register(&self, oper: Operation, cx: &Context) -> bool {
         let packet = Box::into_raw(Packet::<T>::empty_on_heap());
 
-        let mut inner = self.0.inner.lock();
-        inner
-            .senders
-            .register_with_packet(oper, packet as usize, cx);
-        inner.receivers.notify();
-        inner.receivers.can_select() || inner.is_disconnected
+        // See `Receiver::register` for why a concurrent select on the other side can't be missed.
+        let ready = {
+            let mut inner = self.0.inner.lock();
+            inner
+                .senders
+                .register_with_packet(oper, packet as usize, cx);
+            inner.receivers.notify();
+            inner.receivers.can_select() || inner.is_disconnected
+        };
+
+        #[cfg(all(unix, feature = "os_poll"))]
+        self.0.ready_fds.notify();
+        ready
     }
 
     fn unregister(&self, oper: Operation) {
@@ -449,6 +506,8 @@ impl<'a, T> SelectHandle for Sender<'a, T> {
     }
 
     fn is_ready(&self) -> bool {
+        // There is no buffer, so a send operation is ready only if a receiver is blocked
+        // waiting to be paired up.
         let inner = self.0.inner.lock();
         inner.receivers.can_select() || inner.is_disconnected
     }
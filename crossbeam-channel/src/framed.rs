@@ -0,0 +1,149 @@
+//! Receiver adapter that reassembles length-prefixed frames out of byte chunks.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use channel::Receiver;
+use err::RecvTimeoutError;
+
+/// The size of the length prefix in bytes.
+const PREFIX_LEN: usize = 4;
+
+/// A receiver that reassembles length-prefixed frames from a channel of byte chunks.
+///
+/// Each frame on the wire is a 4-byte big-endian length followed by that many bytes of payload.
+/// Frames may be split across chunks arbitrarily, and a single chunk may contain several frames.
+/// Bytes received but not yet returned as part of a frame are kept in an internal buffer, so no
+/// data is lost when a call times out.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use crossbeam_channel::{unbounded, FramedReceiver};
+///
+/// let (s, r) = unbounded();
+/// let mut r = FramedReceiver::new(r);
+///
+/// // A frame of length 3 split across two chunks.
+/// s.send(vec![0, 0, 0, 3, b'a']).unwrap();
+/// assert!(r.recv_frame_timeout(Duration::from_millis(10)).is_err());
+///
+/// s.send(vec![b'b', b'c']).unwrap();
+/// assert_eq!(r.recv_frame_timeout(Duration::from_millis(10)), Ok(b"abc".to_vec()));
+/// ```
+pub struct FramedReceiver<T> {
+    /// The channel of byte chunks.
+    receiver: Receiver<T>,
+
+    /// Bytes received but not yet returned as part of a frame.
+    buffer: Vec<u8>,
+}
+
+impl<T: AsRef<[u8]>> FramedReceiver<T> {
+    /// Creates a framed receiver reading chunks from `receiver`.
+    pub fn new(receiver: Receiver<T>) -> FramedReceiver<T> {
+        FramedReceiver {
+            receiver,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Takes a complete frame out of the buffer, if there is one.
+    fn take_frame(&mut self) -> Option<Vec<u8>> {
+        if self.buffer.len() < PREFIX_LEN {
+            return None;
+        }
+
+        let len = self.buffer[..PREFIX_LEN]
+            .iter()
+            .fold(0usize, |len, &b| len << 8 | b as usize);
+        if self.buffer.len() < PREFIX_LEN + len {
+            return None;
+        }
+
+        let rest = self.buffer.split_off(PREFIX_LEN + len);
+        let mut frame = ::std::mem::replace(&mut self.buffer, rest);
+        frame.drain(..PREFIX_LEN);
+        Some(frame)
+    }
+
+    /// Waits for a complete frame for a limited time and returns its payload.
+    ///
+    /// If the buffer already holds a complete frame, it is returned without receiving anything.
+    /// Otherwise, chunks are received and appended to the buffer until a frame is complete.
+    ///
+    /// If the timeout elapses first, an error is returned and the partial frame stays in the
+    /// buffer, to be completed by a later call. If the channel is empty and disconnected, an error
+    /// is returned as well, and the incomplete bytes remain available through [`buffered`].
+    ///
+    /// [`buffered`]: struct.FramedReceiver.html#method.buffered
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, FramedReceiver, RecvTimeoutError};
+    ///
+    /// let (s, r) = unbounded();
+    /// let mut r = FramedReceiver::new(r);
+    ///
+    /// s.send(vec![0, 0, 0, 1, 7, 0, 0]).unwrap();
+    /// assert_eq!(r.recv_frame_timeout(Duration::from_millis(10)), Ok(vec![7]));
+    ///
+    /// // The start of the next frame is kept.
+    /// assert_eq!(
+    ///     r.recv_frame_timeout(Duration::from_millis(10)),
+    ///     Err(RecvTimeoutError::Timeout),
+    /// );
+    /// assert_eq!(r.buffered(), [0, 0]);
+    ///
+    /// drop(s);
+    /// assert_eq!(
+    ///     r.recv_frame_timeout(Duration::from_millis(10)),
+    ///     Err(RecvTimeoutError::Disconnected),
+    /// );
+    /// ```
+    pub fn recv_frame_timeout(&mut self, timeout: Duration) -> Result<Vec<u8>, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(frame) = self.take_frame() {
+                return Ok(frame);
+            }
+
+            let now = Instant::now();
+            let remaining = if now < deadline {
+                deadline - now
+            } else {
+                Duration::from_secs(0)
+            };
+
+            let chunk = self.receiver.recv_timeout(remaining)?;
+            self.buffer.extend_from_slice(chunk.as_ref());
+        }
+    }
+
+    /// Returns the bytes received but not yet returned as part of a frame.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Returns a reference to the underlying receiver.
+    pub fn get_ref(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+
+    /// Returns the underlying receiver and the buffered bytes.
+    pub fn into_inner(self) -> (Receiver<T>, Vec<u8>) {
+        (self.receiver, self.buffer)
+    }
+}
+
+impl<T> fmt::Debug for FramedReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FramedReceiver")
+            .field("buffered", &self.buffer.len())
+            .finish()
+    }
+}
@@ -347,17 +347,62 @@
 #![warn(missing_docs)]
 #![warn(missing_debug_implementations)]
 
+#[cfg(feature = "arena")]
+extern crate bumpalo;
 extern crate crossbeam_utils;
+#[cfg(feature = "async-bridge")]
+extern crate futures;
+#[cfg(all(unix, feature = "signal"))]
+extern crate libc;
+#[cfg(feature = "tracing")]
+extern crate tracing;
 
+// Declared first so that its macros are visible in all other modules.
+#[macro_use]
+mod trace;
+
+mod ack;
+mod adaptive;
+#[cfg(feature = "async-bridge")]
+mod async_bridge;
+mod atomic_instant;
+mod cancel;
 mod channel;
+mod channel_scope;
 mod context;
 mod counter;
+#[cfg(feature = "deadlock_detection")]
+mod deadlock;
 mod err;
 mod flavors;
+mod framed;
+mod local;
+mod multi;
+mod overflow;
+mod pool;
+mod profiling;
+mod pump;
+#[cfg(feature = "replay")]
+mod replay;
+mod ring;
+mod rpc;
+mod scoped;
 mod select;
+mod select_builder;
+mod select_fixed;
 mod select_macro;
+mod select_set;
+#[cfg(all(unix, feature = "signal"))]
+mod signal;
+mod spsc;
+mod stash;
+mod swappable;
+mod tagged;
+mod tee;
+mod throttled;
 mod utils;
 mod waker;
+mod watermark;
 
 /// Crate internals used by the `select!` macro.
 #[doc(hidden)]
@@ -366,13 +411,88 @@ pub mod internal {
     pub use select::{select, select_timeout, try_select};
 }
 
-pub use channel::{after, never, tick};
-pub use channel::{bounded, unbounded};
-pub use channel::{IntoIter, Iter, TryIter};
-pub use channel::{Receiver, Sender};
+pub use ack::{ack_channel, ack_channel_with_redelivery, AckReceiver, Delivery, Redelivery};
+
+pub use adaptive::BackoffReceiver;
+
+pub use atomic_instant::AtomicInstant;
+
+pub use cancel::{cancel_token, CancelToken, Canceller};
+
+pub use channel::bounded_in_slice;
+pub use channel::coalescing_channel;
+pub use channel::{after, merge, never, ready, tick, tick_jittered};
+pub use channel::{bounded, bounded_named, bounded_pow2, oneshot, unbounded, unbounded_named};
+pub use channel::{bounded_checked, BoundedBuilder};
+pub use channel::{bounded_priority, priority_channel, PrioritySender};
+pub use channel::{bounded_with_drop_handler, unbounded_with_drop_handler};
+pub use channel::{bounded_with_strategy, ParkStrategy};
+pub use channel::{ChannelKind, Contiguous, Decision, Poll, Receiver, Sender, Slot};
+pub use channel::{Drain, IntoIter, Iter, TryIter};
+pub use channel::{RecvState, SendState, Steal};
+
+pub use channel_scope::{channel_scope, ChannelScope, ScopeReceiver};
+
+pub use flavors::array::Slot as ArraySlot;
+
+pub use framed::FramedReceiver;
+
+pub use local::{local_channel, LocalReceiver, LocalSender};
+
+pub use multi::MultiChannel;
+
+pub use overflow::{bounded_with_overflow, OverflowSender};
+
+pub use pool::{pipe_with_pool, Pool};
+
+pub use profiling::{ProfilingReceiver, WaitStats};
+
+pub use pump::Pump;
+
+pub use ring::{ring_channel, RingSender};
+
+pub use rpc::{rpc, Responder, RpcClient, RpcServer};
+
+pub use scoped::{scoped_channel, ScopedReceiver, ScopedSender};
+
+pub use spsc::{spsc_bounded, SpscIter, SpscReceiver, SpscSender};
+
+pub use stash::StashReceiver;
+
+pub use swappable::{swappable, SwapHandle, SwapMode, SwappableReceiver};
+
+pub use tagged::{tagged_unbounded, SenderTag, TaggedSender};
+
+pub use tee::{tee, tee_bounded, tee_lossy};
+
+pub use throttled::ThrottledSender;
+
+#[cfg(all(unix, feature = "signal"))]
+pub use signal::signal_channel;
+
+#[cfg(feature = "async-bridge")]
+pub use async_bridge::{block_on_recv, receiver_from_stream, spawn_forwarder};
+
+#[cfg(feature = "deadlock_detection")]
+pub use deadlock::{blocked_threads, BlockedThread};
+
+#[cfg(feature = "replay")]
+pub use replay::{start_recording, start_replay, stop_recording, stop_replay};
+#[cfg(feature = "replay")]
+pub use replay::{ReplayEvent, ReplayOp};
 
-pub use select::{Select, SelectedOperation};
+pub use select::select_fairness_test;
+pub use select::{select_try, Phase, RecvOrClosed, Select, SelectedOperation};
+pub use select_builder::SelectBuilder;
+pub use select_fixed::{select2, select2_timeout, try_select2, Either};
+pub use select_fixed::{select3, select3_timeout, try_select3, Either3};
+pub use select_fixed::{select4, select4_timeout, try_select4, Either4};
+pub use select_set::{SelectSet, SetOperation};
 
+pub use err::CallError;
+pub use err::CapacityError;
+pub use err::{PeekError, TryPeekError};
 pub use err::{ReadyTimeoutError, SelectTimeoutError, TryReadyError, TrySelectError};
-pub use err::{RecvError, RecvTimeoutError, TryRecvError};
-pub use err::{SendError, SendTimeoutError, TrySendError};
+pub use err::{RecvCancelError, RecvError, RecvSpinError, RecvTimeoutError, TryRecvError};
+pub use err::{ReserveSlotError, TryReserveSlotError};
+pub use err::{SendError, SendOnceError, SendTimeoutError, TrySendError};
@@ -0,0 +1,239 @@
+//! Single-threaded channels for messages that are not `Send`.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
+
+use err::{RecvError, SendError, TryRecvError};
+
+/// Shared state of a local channel.
+struct Inner<T> {
+    /// Messages in the channel.
+    queue: VecDeque<T>,
+
+    /// The number of live senders.
+    senders: usize,
+
+    /// The number of live receivers.
+    receivers: usize,
+}
+
+/// Creates a channel of unbounded capacity that can only be used within a single thread.
+///
+/// Unlike channels created by [`unbounded`], this channel does not require messages to be
+/// [`Send`] and does not use atomic operations. In turn, its [`LocalSender`] and
+/// [`LocalReceiver`] cannot be sent to other threads.
+///
+/// Since no other thread can send a message while the current thread is waiting, receive
+/// operations never block. A receive from an empty channel simply fails.
+///
+/// [`unbounded`]: fn.unbounded.html
+/// [`Send`]: https://doc.rust-lang.org/std/marker/trait.Send.html
+/// [`LocalSender`]: struct.LocalSender.html
+/// [`LocalReceiver`]: struct.LocalReceiver.html
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use crossbeam_channel::local_channel;
+///
+/// let (s, r) = local_channel();
+///
+/// // `Rc` is not `Send`, but it can still be passed through a local channel.
+/// s.send(Rc::new(5)).unwrap();
+/// assert_eq!(*r.recv().unwrap(), 5);
+/// ```
+pub fn local_channel<T>() -> (LocalSender<T>, LocalReceiver<T>) {
+    let inner = Rc::new(RefCell::new(Inner {
+        queue: VecDeque::new(),
+        senders: 1,
+        receivers: 1,
+    }));
+    let s = LocalSender {
+        inner: inner.clone(),
+    };
+    let r = LocalReceiver { inner };
+    (s, r)
+}
+
+/// The sending side of a local channel.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::local_channel;
+///
+/// let (s1, r) = local_channel();
+/// let s2 = s1.clone();
+///
+/// s1.send(1).unwrap();
+/// s2.send(2).unwrap();
+///
+/// assert_eq!(r.recv(), Ok(1));
+/// assert_eq!(r.recv(), Ok(2));
+/// ```
+pub struct LocalSender<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+impl<T> LocalSender<T> {
+    /// Sends a message into the channel.
+    ///
+    /// This method never blocks. If all receivers have been dropped, an error is returned that
+    /// contains the original message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{local_channel, SendError};
+    ///
+    /// let (s, r) = local_channel();
+    /// assert_eq!(s.send(1), Ok(()));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.send(2), Err(SendError(2)));
+    /// ```
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        let mut inner = self.inner.borrow_mut();
+
+        if inner.receivers == 0 {
+            Err(SendError(msg))
+        } else {
+            inner.queue.push_back(msg);
+            Ok(())
+        }
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.borrow().queue.is_empty()
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.inner.borrow().queue.len()
+    }
+}
+
+impl<T> Drop for LocalSender<T> {
+    fn drop(&mut self) {
+        self.inner.borrow_mut().senders -= 1;
+    }
+}
+
+impl<T> Clone for LocalSender<T> {
+    fn clone(&self) -> Self {
+        self.inner.borrow_mut().senders += 1;
+        LocalSender {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for LocalSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("LocalSender { .. }")
+    }
+}
+
+/// The receiving side of a local channel.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{local_channel, TryRecvError};
+///
+/// let (s, r) = local_channel();
+/// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+///
+/// s.send(1).unwrap();
+/// assert_eq!(r.try_recv(), Ok(1));
+///
+/// drop(s);
+/// assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+/// ```
+pub struct LocalReceiver<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+impl<T> LocalReceiver<T> {
+    /// Attempts to receive a message from the channel.
+    ///
+    /// If the channel is empty, this method returns [`TryRecvError::Empty`], or
+    /// [`TryRecvError::Disconnected`] if all senders have been dropped.
+    ///
+    /// [`TryRecvError::Empty`]: enum.TryRecvError.html#variant.Empty
+    /// [`TryRecvError::Disconnected`]: enum.TryRecvError.html#variant.Disconnected
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut inner = self.inner.borrow_mut();
+
+        match inner.queue.pop_front() {
+            Some(msg) => Ok(msg),
+            None if inner.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Receives a message from the channel.
+    ///
+    /// Waiting for a message would block the only thread that could send it, so this method
+    /// returns an error if the channel is empty, whether it is disconnected or not.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{local_channel, RecvError};
+    ///
+    /// let (s, r) = local_channel();
+    /// assert_eq!(r.recv(), Err(RecvError));
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(r.recv(), Ok(1));
+    /// ```
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.try_recv().map_err(|_| RecvError)
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.borrow().queue.is_empty()
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.inner.borrow().queue.len()
+    }
+}
+
+impl<T> Drop for LocalReceiver<T> {
+    fn drop(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.receivers -= 1;
+
+        // Nobody can receive the remaining messages anymore, so drop them right away. They are
+        // dropped outside the borrow because their destructors might use the channel.
+        if inner.receivers == 0 {
+            let queue = mem::replace(&mut inner.queue, VecDeque::new());
+            drop(inner);
+            drop(queue);
+        }
+    }
+}
+
+impl<T> Clone for LocalReceiver<T> {
+    fn clone(&self) -> Self {
+        self.inner.borrow_mut().receivers += 1;
+        LocalReceiver {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for LocalReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("LocalReceiver { .. }")
+    }
+}
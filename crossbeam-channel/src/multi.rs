@@ -0,0 +1,155 @@
+//! Aggregate backlog of a group of channels.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use channel::Receiver;
+use context::Context;
+use select::{Operation, Selected, Token};
+
+/// A group of channels whose buffered messages are counted together.
+///
+/// This is useful for backpressure across several channels, for example in a load balancer that
+/// wants to stop accepting work while its workers' queues hold too many messages in total.
+///
+/// The total is the sum of [`Receiver::len`] over all channels in the group. The lengths are read
+/// one after another, not at a single instant, so the total is only a snapshot that may be stale by
+/// the time it is used.
+///
+/// [`Receiver::len`]: struct.Receiver.html#method.len
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{unbounded, MultiChannel};
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+///
+/// for i in 0..10 {
+///     s1.send(i).unwrap();
+///     s2.send(i).unwrap();
+/// }
+///
+/// let group = MultiChannel::new(&[&r1, &r2]);
+/// assert_eq!(group.total_len(), 20);
+///
+/// let (w1, w2) = (r1.clone(), r2.clone());
+/// thread::spawn(move || for _ in w1.iter() {});
+/// thread::spawn(move || for _ in w2.iter() {});
+///
+/// group.wait_total_below(5);
+/// # drop((s1, s2));
+/// ```
+pub struct MultiChannel<'a, T: 'a> {
+    /// The channels in the group.
+    receivers: Vec<&'a Receiver<T>>,
+}
+
+impl<'a, T> MultiChannel<'a, T> {
+    /// Creates a group of the given channels.
+    pub fn new(receivers: &[&'a Receiver<T>]) -> MultiChannel<'a, T> {
+        MultiChannel {
+            receivers: receivers.to_vec(),
+        }
+    }
+
+    /// Returns the total number of messages in all channels of the group.
+    pub fn total_len(&self) -> usize {
+        self.receivers.iter().map(|r| r.len()).sum()
+    }
+
+    /// Blocks the current thread until the total number of messages drops below `threshold`.
+    ///
+    /// The thread is parked while waiting and woken up every time a message is taken out of any
+    /// channel in the group. This only guarantees the total was momentarily below `threshold`:
+    /// senders may push it back up right after this method returns. Since the lengths are read one
+    /// after another, the observed total may also never have been the exact total at any single
+    /// instant while messages are being sent and received concurrently.
+    ///
+    /// Channels created by [`after`], [`tick`], [`never`], and [`ready`] don't notify the waiting
+    /// thread, so changes in their lengths are only noticed when another channel wakes it up. A
+    /// `threshold` of zero is never reached, so the call blocks forever.
+    ///
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
+    /// [`ready`]: fn.ready.html
+    pub fn wait_total_below(&self, threshold: usize) {
+        self.wait_below(threshold, None);
+    }
+
+    /// Waits until the total number of messages drops below `threshold`, but only for a limited
+    /// time.
+    ///
+    /// Returns `true` if the total was observed below `threshold`, and `false` if the timeout
+    /// elapsed first. Otherwise, this method behaves just like [`wait_total_below`].
+    ///
+    /// [`wait_total_below`]: struct.MultiChannel.html#method.wait_total_below
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{bounded, MultiChannel};
+    ///
+    /// let (s1, r1) = bounded(4);
+    /// let (s2, r2) = bounded(4);
+    /// s1.send(1).unwrap();
+    /// s2.send(2).unwrap();
+    ///
+    /// let group = MultiChannel::new(&[&r1, &r2]);
+    /// assert!(!group.wait_total_below_timeout(2, Duration::from_millis(100)));
+    ///
+    /// r1.recv().unwrap();
+    /// assert!(group.wait_total_below_timeout(2, Duration::from_millis(100)));
+    /// # drop((s1, s2));
+    /// ```
+    pub fn wait_total_below_timeout(&self, threshold: usize, timeout: Duration) -> bool {
+        self.wait_below(threshold, Some(Instant::now() + timeout))
+    }
+
+    /// Blocks until the total number of messages drops below `threshold` or the deadline passes.
+    fn wait_below(&self, threshold: usize, deadline: Option<Instant>) -> bool {
+        let token = &mut Token::default();
+
+        loop {
+            if self.total_len() < threshold {
+                return true;
+            }
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return false;
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a message is taken out of any channel.
+                let oper = Operation::hook(token);
+                for r in &self.receivers {
+                    r.watch_drain(oper, cx);
+                }
+
+                // Has the total dropped below the threshold just now?
+                if self.total_len() < threshold {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                cx.wait_until(deadline);
+                for r in &self.receivers {
+                    r.unwatch_drain(oper);
+                }
+            });
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for MultiChannel<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MultiChannel")
+            .field("channels", &self.receivers.len())
+            .finish()
+    }
+}
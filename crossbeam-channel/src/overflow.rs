@@ -0,0 +1,201 @@
+//! Bounded channel that spills into an unbounded buffer instead of blocking the producer.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use channel::{bounded, unbounded, Receiver, Sender};
+use err::{SendError, TrySendError};
+
+/// A sender that never blocks and spills messages into an overflow buffer while the channel is
+/// full.
+///
+/// Created by [`bounded_with_overflow`]. See its documentation for more.
+///
+/// [`bounded_with_overflow`]: fn.bounded_with_overflow.html
+pub struct OverflowSender<T> {
+    /// Sends messages directly into the bounded channel.
+    sender: Sender<T>,
+
+    /// Sends messages into the overflow buffer.
+    overflow: Sender<T>,
+
+    /// The number of messages sent into the overflow buffer but not yet moved into the bounded
+    /// channel.
+    pending: Arc<AtomicUsize>,
+}
+
+/// Creates a bounded channel whose sender spills into an unbounded overflow buffer when full.
+///
+/// Sending through the returned [`OverflowSender`] never blocks and never drops a message. While
+/// the bounded channel has room, messages go straight into it. When it is full, they are pushed
+/// into an unbounded overflow buffer instead, and a background thread moves them into the bounded
+/// channel as the receiver makes room. Memory use stays bounded in the steady state, while bursts
+/// of any size are absorbed by the overflow buffer.
+///
+/// Messages sent by a single thread are received in the order they were sent. As long as the
+/// overflow buffer holds any messages, new messages are appended to it rather than sent directly
+/// into the bounded channel, so they cannot overtake the earlier ones.
+///
+/// The receiver gets disconnected once all senders are dropped and the overflow buffer has been
+/// moved into the bounded channel. If the receiver is dropped, messages still in the overflow
+/// buffer are dropped as well.
+///
+/// [`OverflowSender`]: struct.OverflowSender.html
+///
+/// # Panics
+///
+/// Panics if the capacity is zero or too large, just like [`bounded`].
+///
+/// [`bounded`]: fn.bounded.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::bounded_with_overflow;
+///
+/// let (s, r) = bounded_with_overflow(2);
+///
+/// // None of these block, even though the channel holds only two messages.
+/// for i in 0..5 {
+///     s.send(i).unwrap();
+/// }
+/// drop(s);
+///
+/// assert_eq!(r.iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+/// ```
+pub fn bounded_with_overflow<T>(cap: usize) -> (OverflowSender<T>, Receiver<T>)
+where
+    T: Send + 'static,
+{
+    assert!(cap > 0, "capacity must be positive");
+
+    let (s, r) = bounded(cap);
+    let (overflow_s, overflow_r) = unbounded();
+    let pending = Arc::new(AtomicUsize::new(0));
+
+    spawn_drain(overflow_r, s.clone(), pending.clone());
+
+    let sender = OverflowSender {
+        sender: s,
+        overflow: overflow_s,
+        pending,
+    };
+    (sender, r)
+}
+
+/// Spawns a thread moving messages from the overflow buffer into the bounded channel.
+///
+/// The thread exits once all overflow senders are dropped and the buffer is empty, or once the
+/// receiver of the bounded channel is dropped.
+fn spawn_drain<T>(overflow: Receiver<T>, sender: Sender<T>, pending: Arc<AtomicUsize>)
+where
+    T: Send + 'static,
+{
+    thread::Builder::new()
+        .name("crossbeam-overflow".to_string())
+        .spawn(move || {
+            for msg in overflow.iter() {
+                if sender.send(msg).is_err() {
+                    return;
+                }
+
+                // Decrement only after the message is in the bounded channel, so that senders keep
+                // using the overflow buffer until all earlier messages are delivered.
+                pending.fetch_sub(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+}
+
+impl<T> OverflowSender<T> {
+    /// Sends a message without blocking.
+    ///
+    /// The message goes into the bounded channel if it has room and the overflow buffer is empty.
+    /// Otherwise, it is pushed into the overflow buffer.
+    ///
+    /// If the receiver has been dropped, an error is returned containing the message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::bounded_with_overflow;
+    ///
+    /// let (s, r) = bounded_with_overflow(1);
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    ///
+    /// assert_eq!(r.recv(), Ok(1));
+    /// assert_eq!(r.recv(), Ok(2));
+    /// ```
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        let msg = if self.pending.load(Ordering::SeqCst) == 0 {
+            match self.sender.try_send(msg) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Disconnected(msg)) => return Err(SendError(msg)),
+                Err(TrySendError::Full(msg)) => msg,
+            }
+        } else {
+            msg
+        };
+
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        let res = self.overflow.send(msg);
+        if res.is_err() {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+        }
+        res
+    }
+
+    /// Returns the number of messages in the overflow buffer.
+    ///
+    /// This counts messages that were sent while the channel was full and have not been moved
+    /// into the bounded channel yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::bounded_with_overflow;
+    ///
+    /// let (s, r) = bounded_with_overflow(1);
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// assert_eq!(s.overflow_len(), 1);
+    /// # drop(r);
+    /// ```
+    pub fn overflow_len(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of messages in the bounded channel.
+    pub fn len(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// Returns `true` if both the bounded channel and the overflow buffer are empty.
+    pub fn is_empty(&self) -> bool {
+        self.sender.is_empty() && self.overflow_len() == 0
+    }
+
+    /// Returns the capacity of the bounded channel.
+    pub fn capacity(&self) -> usize {
+        self.sender.capacity().unwrap()
+    }
+}
+
+impl<T> Clone for OverflowSender<T> {
+    fn clone(&self) -> Self {
+        OverflowSender {
+            sender: self.sender.clone(),
+            overflow: self.overflow.clone(),
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for OverflowSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("OverflowSender { .. }")
+    }
+}
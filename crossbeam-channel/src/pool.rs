@@ -0,0 +1,154 @@
+//! Channel paired with a pool of reusable items, such as buffers.
+
+use std::fmt;
+use std::sync::Arc;
+
+use channel::{bounded, Receiver, Sender};
+
+/// Creates a channel of bounded capacity together with a pool of reusable items.
+///
+/// This is useful when sending items that are expensive to allocate, such as `Vec<u8>` buffers.
+/// The producer gets an item from the pool with [`Pool::take`], fills it, and sends it into the
+/// channel. The consumer receives the item, processes it, and gives it back with
+/// [`Pool::recycle`], so that the producer can reuse it instead of allocating a new one.
+///
+/// The channel is created just like with [`bounded`]. The pool creates new items by calling
+/// `make` whenever it has no idle items. It retains at most `cap` idle items, or one item if
+/// `cap` is zero, and drops items recycled beyond that, so the pool cannot grow without bound.
+///
+/// Recycled items are handed out again as they are, so the consumer or the producer should clear
+/// them before reuse.
+///
+/// [`bounded`]: fn.bounded.html
+/// [`Pool::take`]: struct.Pool.html#method.take
+/// [`Pool::recycle`]: struct.Pool.html#method.recycle
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::pipe_with_pool;
+///
+/// let (s, r, pool) = pipe_with_pool(4, || Vec::with_capacity(1024));
+/// let producer_pool = pool.clone();
+///
+/// let producer = thread::spawn(move || {
+///     for i in 0..100u8 {
+///         let mut buf = producer_pool.take();
+///         buf.clear();
+///         buf.extend_from_slice(&[i; 16]);
+///         s.send(buf).unwrap();
+///     }
+/// });
+///
+/// let mut total = 0;
+/// for buf in r.iter() {
+///     total += buf.len();
+///     pool.recycle(buf);
+/// }
+///
+/// producer.join().unwrap();
+/// assert_eq!(total, 1600);
+/// ```
+pub fn pipe_with_pool<T, F>(cap: usize, make: F) -> (Sender<T>, Receiver<T>, Pool<T>)
+where
+    F: Fn() -> T + Send + Sync + 'static,
+{
+    let (s, r) = bounded(cap);
+    let (free_s, free_r) = bounded(if cap == 0 { 1 } else { cap });
+    let pool = Pool {
+        free_s,
+        free_r,
+        make: Arc::new(make),
+    };
+    (s, r, pool)
+}
+
+/// A pool of reusable items paired with a channel.
+///
+/// Created by [`pipe_with_pool`]. See its documentation for more.
+///
+/// Clones of a pool share the same idle items.
+///
+/// [`pipe_with_pool`]: fn.pipe_with_pool.html
+pub struct Pool<T> {
+    /// The sending side of the list of idle items.
+    free_s: Sender<T>,
+
+    /// The receiving side of the list of idle items.
+    free_r: Receiver<T>,
+
+    /// Creates a new item when there are no idle items.
+    make: Arc<Fn() -> T + Send + Sync>,
+}
+
+impl<T> Pool<T> {
+    /// Takes an idle item out of the pool, or creates a new one if there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::pipe_with_pool;
+    ///
+    /// let (_s, _r, pool) = pipe_with_pool(1, || vec![0u8; 4]);
+    ///
+    /// let mut buf = pool.take();
+    /// buf[0] = 1;
+    /// pool.recycle(buf);
+    ///
+    /// // The recycled item is handed out again.
+    /// assert_eq!(pool.take(), [1, 0, 0, 0]);
+    /// assert_eq!(pool.take(), [0, 0, 0, 0]);
+    /// ```
+    pub fn take(&self) -> T {
+        match self.free_r.try_recv() {
+            Ok(item) => item,
+            Err(_) => (self.make)(),
+        }
+    }
+
+    /// Gives an item back to the pool so that it can be reused.
+    ///
+    /// If the pool already holds as many idle items as it can retain, the item is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::pipe_with_pool;
+    ///
+    /// let (_s, _r, pool) = pipe_with_pool(1, Vec::<u8>::new);
+    ///
+    /// pool.recycle(Vec::new());
+    /// pool.recycle(Vec::new());
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    pub fn recycle(&self, item: T) {
+        let _ = self.free_s.try_send(item);
+    }
+
+    /// Returns the number of idle items in the pool.
+    pub fn len(&self) -> usize {
+        self.free_r.len()
+    }
+
+    /// Returns `true` if the pool has no idle items.
+    pub fn is_empty(&self) -> bool {
+        self.free_r.is_empty()
+    }
+}
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Self {
+        Pool {
+            free_s: self.free_s.clone(),
+            free_r: self.free_r.clone(),
+            make: self.make.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Pool { .. }")
+    }
+}
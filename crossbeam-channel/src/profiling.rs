@@ -0,0 +1,302 @@
+//! Receiver wrapper that collects wait time statistics for profiling.
+
+use std::fmt;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use channel::Receiver;
+use err::{RecvError, RecvTimeoutError, TryRecvError};
+
+/// The number of histogram buckets.
+///
+/// Bucket 0 counts waits shorter than a microsecond, and bucket `i` counts waits of at least
+/// `2^(i - 1)` and less than `2^i` microseconds. The last bucket also counts all longer waits.
+const BUCKETS: usize = 32;
+
+/// A receiver that records how long each receive operation waits for a message.
+///
+/// Wait times are collected into a histogram with power-of-two buckets, so recording one costs
+/// a clock read and a few atomic increments, and the memory used stays constant over the
+/// lifetime of the receiver. Statistics are shared by clones of a `ProfilingReceiver`.
+///
+/// Only receive operations made through this wrapper are recorded, so it can be dropped in during
+/// a load test and removed afterwards without affecting the channel itself.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{unbounded, ProfilingReceiver};
+///
+/// let (s, r) = unbounded();
+/// let r = ProfilingReceiver::new(r);
+///
+/// s.send(1).unwrap();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(10));
+///     s.send(2).unwrap();
+/// });
+///
+/// r.recv().unwrap();
+/// r.recv().unwrap();
+///
+/// let stats = r.stats();
+/// assert_eq!(stats.count(), 2);
+/// assert_eq!(stats.blocked(), 1);
+/// assert!(stats.percentile(0.99).unwrap() >= Duration::from_millis(10));
+/// ```
+pub struct ProfilingReceiver<T> {
+    /// The underlying receiver.
+    receiver: Receiver<T>,
+
+    /// The statistics, shared by all clones.
+    stats: Arc<Histogram>,
+}
+
+/// Counters updated by receive operations.
+struct Histogram {
+    /// The number of receive operations that had to wait for a message.
+    blocked: AtomicUsize,
+
+    /// The number of receive operations that timed out.
+    timeouts: AtomicUsize,
+
+    /// The number of messages received in each wait time bucket.
+    buckets: [AtomicUsize; BUCKETS],
+}
+
+impl Histogram {
+    /// Creates an empty histogram.
+    fn new() -> Histogram {
+        Histogram {
+            blocked: AtomicUsize::new(0),
+            timeouts: AtomicUsize::new(0),
+            // `AtomicUsize` is not `Copy`, so the array cannot be created with `[x; BUCKETS]`.
+            buckets: unsafe { mem::zeroed() },
+        }
+    }
+
+    /// Records a message received after waiting for `wait`.
+    fn record(&self, wait: Duration) {
+        let micros = wait.as_secs() * 1_000_000 + u64::from(wait.subsec_nanos() / 1_000);
+        let bucket = (64 - micros.leading_zeros()) as usize;
+        let bucket = if bucket < BUCKETS {
+            bucket
+        } else {
+            BUCKETS - 1
+        };
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<T> ProfilingReceiver<T> {
+    /// Creates a profiling receiver wrapping `receiver`.
+    pub fn new(receiver: Receiver<T>) -> ProfilingReceiver<T> {
+        ProfilingReceiver {
+            receiver,
+            stats: Arc::new(Histogram::new()),
+        }
+    }
+
+    /// Blocks the current thread until a message is received, and records how long it waited.
+    ///
+    /// This method behaves just like [`Receiver::recv`].
+    ///
+    /// [`Receiver::recv`]: struct.Receiver.html#method.recv
+    pub fn recv(&self) -> Result<T, RecvError> {
+        match self.receiver.try_recv() {
+            Ok(msg) => {
+                self.stats.record(Duration::from_secs(0));
+                return Ok(msg);
+            }
+            Err(TryRecvError::Disconnected) => return Err(RecvError),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        self.stats.blocked.fetch_add(1, Ordering::Relaxed);
+        let start = Instant::now();
+        let msg = self.receiver.recv()?;
+        self.stats.record(start.elapsed());
+        Ok(msg)
+    }
+
+    /// Waits for a message to be received for a limited time, and records how long it waited.
+    ///
+    /// This method behaves just like [`Receiver::recv_timeout`]. Operations that time out are
+    /// counted separately and do not contribute to the wait time histogram.
+    ///
+    /// [`Receiver::recv_timeout`]: struct.Receiver.html#method.recv_timeout
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        match self.receiver.try_recv() {
+            Ok(msg) => {
+                self.stats.record(Duration::from_secs(0));
+                return Ok(msg);
+            }
+            Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        self.stats.blocked.fetch_add(1, Ordering::Relaxed);
+        let start = Instant::now();
+        match self.receiver.recv_timeout(timeout) {
+            Ok(msg) => {
+                self.stats.record(start.elapsed());
+                Ok(msg)
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                self.stats.timeouts.fetch_add(1, Ordering::Relaxed);
+                Err(RecvTimeoutError::Timeout)
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(RecvTimeoutError::Disconnected),
+        }
+    }
+
+    /// Attempts to receive a message without blocking.
+    ///
+    /// This method behaves just like [`Receiver::try_recv`]. A received message is recorded as
+    /// received without waiting.
+    ///
+    /// [`Receiver::try_recv`]: struct.Receiver.html#method.try_recv
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let msg = self.receiver.try_recv()?;
+        self.stats.record(Duration::from_secs(0));
+        Ok(msg)
+    }
+
+    /// Returns a snapshot of the statistics collected so far.
+    pub fn stats(&self) -> WaitStats {
+        let mut buckets = [0; BUCKETS];
+        for (b, counter) in buckets.iter_mut().zip(self.stats.buckets.iter()) {
+            *b = counter.load(Ordering::Relaxed);
+        }
+
+        WaitStats {
+            blocked: self.stats.blocked.load(Ordering::Relaxed),
+            timeouts: self.stats.timeouts.load(Ordering::Relaxed),
+            buckets,
+        }
+    }
+
+    /// Returns a reference to the underlying receiver.
+    pub fn get_ref(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+
+    /// Returns the underlying receiver.
+    pub fn into_inner(self) -> Receiver<T> {
+        self.receiver
+    }
+}
+
+impl<T> Clone for ProfilingReceiver<T> {
+    fn clone(&self) -> Self {
+        ProfilingReceiver {
+            receiver: self.receiver.clone(),
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for ProfilingReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ProfilingReceiver { .. }")
+    }
+}
+
+/// Wait time statistics collected by a [`ProfilingReceiver`].
+///
+/// [`ProfilingReceiver`]: struct.ProfilingReceiver.html
+#[derive(Clone, Copy)]
+pub struct WaitStats {
+    /// The number of receive operations that had to wait for a message.
+    blocked: usize,
+
+    /// The number of receive operations that timed out.
+    timeouts: usize,
+
+    /// The number of messages received in each wait time bucket.
+    buckets: [usize; BUCKETS],
+}
+
+impl WaitStats {
+    /// Returns the number of messages received.
+    pub fn count(&self) -> usize {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns the number of receive operations that found the channel empty and had to wait.
+    pub fn blocked(&self) -> usize {
+        self.blocked
+    }
+
+    /// Returns the number of receive operations that timed out.
+    pub fn timeouts(&self) -> usize {
+        self.timeouts
+    }
+
+    /// Returns an upper bound on the wait time of the given fraction of received messages.
+    ///
+    /// For example, `percentile(0.99)` returns a duration that at least 99% of messages were
+    /// received within. Wait times are collected into power-of-two buckets, so the result is the
+    /// upper bound of a bucket and may overestimate the exact percentile by up to a factor of two.
+    /// Wait times beyond the last bucket, which is about 36 minutes, are not told apart.
+    ///
+    /// Returns `None` if no messages have been received.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is not between 0 and 1.
+    pub fn percentile(&self, q: f64) -> Option<Duration> {
+        assert!(q >= 0.0 && q <= 1.0, "percentile must be between 0 and 1");
+
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+
+        // The number of messages that must be covered, rounded up and at least one.
+        let target = ((count as f64 * q).ceil() as usize).max(1);
+
+        let mut seen = 0;
+        for (i, &n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= target {
+                let micros = 1u64 << i;
+                return Some(Duration::new(
+                    micros / 1_000_000,
+                    (micros % 1_000_000) as u32 * 1_000,
+                ));
+            }
+        }
+        unreachable!()
+    }
+
+    /// Returns an upper bound on the median wait time.
+    ///
+    /// This is the same as `percentile(0.5)`.
+    pub fn p50(&self) -> Option<Duration> {
+        self.percentile(0.5)
+    }
+
+    /// Returns an upper bound on the 99th percentile of wait times.
+    ///
+    /// This is the same as `percentile(0.99)`.
+    pub fn p99(&self) -> Option<Duration> {
+        self.percentile(0.99)
+    }
+}
+
+impl fmt::Debug for WaitStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WaitStats")
+            .field("count", &self.count())
+            .field("blocked", &self.blocked)
+            .field("timeouts", &self.timeouts)
+            .field("p50", &self.p50())
+            .field("p99", &self.p99())
+            .finish()
+    }
+}
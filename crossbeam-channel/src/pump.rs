@@ -0,0 +1,138 @@
+//! Forwarding messages from one channel into another, driven by the caller.
+
+use std::fmt;
+
+use channel::{Receiver, Sender};
+
+/// Forwards messages from a receiver into a sender, transforming each one on the way.
+///
+/// Created by [`Receiver::pipe_to`]. See its documentation for more.
+///
+/// A pump never spawns threads. The caller drives it by calling [`step`] or [`run`] on whatever
+/// thread it likes.
+///
+/// Once the pump finishes, it drops both its receiver and its sender. Dropping the sender lets
+/// the next stage see the disconnection after it has received all forwarded messages, and
+/// dropping the receiver lets the previous stage see that nobody is listening anymore. The same
+/// happens when the pump itself is dropped.
+///
+/// [`Receiver::pipe_to`]: struct.Receiver.html#method.pipe_to
+/// [`step`]: struct.Pump.html#method.step
+/// [`run`]: struct.Pump.html#method.run
+pub struct Pump<T, U, F> {
+    /// The source, or `None` once the pump has finished.
+    input: Option<Receiver<T>>,
+
+    /// The destination, or `None` once the pump has finished.
+    output: Option<Sender<U>>,
+
+    /// The transformation applied to each message.
+    f: F,
+}
+
+impl<T, U, F> Pump<T, U, F>
+where
+    F: FnMut(T) -> U,
+{
+    /// Creates a pump forwarding messages from `input` into `output`.
+    pub(crate) fn new(input: Receiver<T>, output: Sender<U>, f: F) -> Pump<T, U, F> {
+        Pump {
+            input: Some(input),
+            output: Some(output),
+            f,
+        }
+    }
+
+    /// Drops both channel handles, which propagates the disconnection in both directions.
+    fn finish(&mut self) {
+        self.input = None;
+        self.output = None;
+    }
+
+    /// Forwards a single message, blocking until it is received and sent.
+    ///
+    /// Returns `true` if a message was forwarded. Returns `false` if the pump has finished, which
+    /// happens once the source is empty and disconnected, or once the destination is disconnected.
+    /// In the latter case, the message being forwarded is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// let mut pump = r1.pipe_to(s2, |x: i32| x * 2);
+    ///
+    /// s1.send(1).unwrap();
+    /// assert!(pump.step());
+    /// assert_eq!(r2.recv(), Ok(2));
+    ///
+    /// drop(s1);
+    /// assert!(!pump.step());
+    ///
+    /// // The output got disconnected.
+    /// assert!(r2.recv().is_err());
+    /// ```
+    pub fn step(&mut self) -> bool {
+        let msg = match self.input.as_ref().map(|r| r.recv()) {
+            Some(Ok(msg)) => msg,
+            Some(Err(_)) | None => {
+                self.finish();
+                return false;
+            }
+        };
+
+        let msg = (self.f)(msg);
+        match self.output.as_ref().map(|s| s.send(msg)) {
+            Some(Ok(())) => true,
+            Some(Err(_)) | None => {
+                self.finish();
+                false
+            }
+        }
+    }
+
+    /// Forwards messages until the pump finishes, and returns how many were forwarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// let mut pump = r1.pipe_to(s2, |x: i32| x.to_string());
+    ///
+    /// let stage = thread::spawn(move || pump.run());
+    ///
+    /// for i in 0..3 {
+    ///     s1.send(i).unwrap();
+    /// }
+    /// drop(s1);
+    ///
+    /// assert_eq!(r2.iter().collect::<Vec<_>>(), ["0", "1", "2"]);
+    /// assert_eq!(stage.join().unwrap(), 3);
+    /// ```
+    pub fn run(&mut self) -> usize {
+        let mut count = 0;
+        while self.step() {
+            count += 1;
+        }
+        count
+    }
+
+    /// Returns `true` if the pump has finished.
+    pub fn is_finished(&self) -> bool {
+        self.input.is_none()
+    }
+}
+
+impl<T, U, F> fmt::Debug for Pump<T, U, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pump")
+            .field("finished", &self.input.is_none())
+            .finish()
+    }
+}
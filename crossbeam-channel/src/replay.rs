@@ -0,0 +1,464 @@
+//! Recording and replaying the order of channel operations, used for debugging.
+//!
+//! This module is only compiled with the `replay` feature.
+//!
+//! Channels created while recording or replaying get an identifier, numbered in creation order
+//! from the moment recording or replaying started. While recording, each send or receive is
+//! appended to a global log at the point where it succeeds. While replaying, a global coordinator
+//! tracks the progress of every event in a recorded schedule. An operation that appears among the
+//! pending events is held back until it is the first one, at which point it claims the event and
+//! proceeds. The event is done once the operation succeeds, or pending again if it fails.
+
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+/// The kind of a recorded channel operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayOp {
+    /// A message was sent into the channel.
+    Send,
+
+    /// A message was received from the channel.
+    Recv,
+}
+
+/// A successful channel operation, as recorded by [`start_recording`].
+///
+/// [`start_recording`]: fn.start_recording.html
+#[derive(Debug, Clone)]
+pub struct ReplayEvent {
+    /// Time since recording started.
+    elapsed: Duration,
+
+    /// Identifier of the channel.
+    channel: usize,
+
+    /// The kind of operation.
+    op: ReplayOp,
+
+    /// The thread that performed the operation.
+    thread: ThreadId,
+
+    /// The name of the thread that performed the operation.
+    thread_name: Option<String>,
+}
+
+impl ReplayEvent {
+    /// Returns the time between the start of recording and this operation.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the identifier of the channel.
+    ///
+    /// Channels are numbered in the order they were created, starting from zero when recording
+    /// started.
+    pub fn channel(&self) -> usize {
+        self.channel
+    }
+
+    /// Returns the kind of operation.
+    pub fn op(&self) -> ReplayOp {
+        self.op
+    }
+
+    /// Returns the thread that performed the operation.
+    pub fn thread(&self) -> ThreadId {
+        self.thread
+    }
+
+    /// Returns the name of the thread that performed the operation, if it had one.
+    pub fn thread_name(&self) -> Option<&str> {
+        self.thread_name.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Returns `true` if this is an operation of the given kind on the given channel by the current
+    /// thread.
+    ///
+    /// Thread identifiers differ between runs, so threads are told apart only by name.
+    fn matches(&self, channel: usize, op: ReplayOp) -> bool {
+        self.channel == channel
+            && self.op == op
+            && match self.thread_name {
+                None => true,
+                Some(ref name) => thread::current().name() == Some(&**name),
+            }
+    }
+}
+
+/// What the coordinator is doing.
+enum Mode {
+    /// Operations are neither recorded nor replayed.
+    Off,
+
+    /// Successful operations are appended to the log.
+    Record {
+        start: Instant,
+        log: Vec<ReplayEvent>,
+        next_channel: usize,
+    },
+
+    /// Operations are held back until they are next in the schedule.
+    Replay {
+        schedule: Vec<(ReplayEvent, Progress)>,
+        next: usize,
+        next_channel: usize,
+    },
+}
+
+/// The progress of an event in a replayed schedule.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Progress {
+    /// No operation has claimed the event yet.
+    Pending,
+
+    /// An operation has claimed the event and hasn't completed yet.
+    Claimed,
+
+    /// An operation has completed the event.
+    Done,
+}
+
+/// The global coordinator.
+struct Coordinator {
+    /// The current mode.
+    mode: Mutex<Mode>,
+
+    /// Signalled whenever the replay cursor moves or the mode changes.
+    turn: Condvar,
+}
+
+/// Returns the global coordinator, initializing it if necessary.
+fn coordinator() -> &'static Coordinator {
+    static COORDINATOR: AtomicPtr<Coordinator> = AtomicPtr::new(ptr::null_mut());
+
+    let mut c = COORDINATOR.load(Ordering::Acquire);
+
+    if c.is_null() {
+        let new = Box::into_raw(Box::new(Coordinator {
+            mode: Mutex::new(Mode::Off),
+            turn: Condvar::new(),
+        }));
+
+        // Install the new coordinator, unless another thread has beaten us to it.
+        c = match COORDINATOR.compare_exchange(c, new, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => new,
+            Err(c) => {
+                unsafe { drop(Box::from_raw(new)) };
+                c
+            }
+        };
+    }
+
+    // The coordinator is never deallocated.
+    unsafe { &*c }
+}
+
+/// Sets the mode and wakes up all operations waiting for their turn.
+fn set_mode(mode: Mode) -> Mode {
+    let c = coordinator();
+    let old = match c.mode.lock() {
+        Ok(mut m) => ::std::mem::replace(&mut *m, mode),
+        Err(_) => Mode::Off,
+    };
+    c.turn.notify_all();
+    old
+}
+
+/// Returns a fresh channel identifier, or `None` if not recording or replaying.
+pub(crate) fn next_channel_id() -> Option<usize> {
+    let mut mode = coordinator().mode.lock().ok()?;
+    match *mode {
+        Mode::Off => None,
+        Mode::Record {
+            ref mut next_channel,
+            ..
+        }
+        | Mode::Replay {
+            ref mut next_channel,
+            ..
+        } => {
+            *next_channel += 1;
+            Some(*next_channel - 1)
+        }
+    }
+}
+
+/// Waits until the operation is the first pending event in the replayed schedule and claims it.
+///
+/// Returns `true` if the operation may proceed and `false` if the deadline was reached first. The
+/// deadline is computed by calling `deadline` the first time the operation has to wait.
+/// Operations are never held back while not replaying, on channels without an identifier, or if
+/// they don't appear among the pending events. An operation that proceeds must be followed up by
+/// `complete`.
+pub(crate) fn wait_turn<F>(channel: Option<usize>, op: ReplayOp, deadline: F) -> bool
+where
+    F: FnOnce() -> Option<Instant>,
+{
+    let channel = match channel {
+        Some(channel) => channel,
+        None => return true,
+    };
+    let c = coordinator();
+    let mut mode = match c.mode.lock() {
+        Ok(mode) => mode,
+        Err(_) => return true,
+    };
+    let mut make_deadline = Some(deadline);
+    let mut when = None;
+
+    loop {
+        match *mode {
+            Mode::Replay {
+                ref mut schedule,
+                next,
+                ..
+            } => {
+                let mut pending = schedule[next..]
+                    .iter_mut()
+                    .filter(|e| e.1 == Progress::Pending);
+                match pending.next() {
+                    None => return true,
+                    Some(first) => {
+                        if first.0.matches(channel, op) {
+                            first.1 = Progress::Claimed;
+                            return true;
+                        }
+                    }
+                }
+                if !pending.any(|e| e.0.matches(channel, op)) {
+                    return true;
+                }
+            }
+            _ => return true,
+        }
+
+        // The operation has to wait, so compute the deadline if it hasn't been yet.
+        let deadline = *when.get_or_insert_with(|| make_deadline.take().unwrap()());
+        mode = match deadline {
+            None => match c.turn.wait(mode) {
+                Ok(mode) => mode,
+                Err(_) => return true,
+            },
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return false;
+                }
+                match c.turn.wait_timeout(mode, deadline - now) {
+                    Ok((mode, _)) => mode,
+                    Err(_) => return true,
+                }
+            }
+        };
+    }
+}
+
+/// Completes an operation at the point where it succeeds or fails.
+///
+/// While recording, a successful operation is appended to the log. While replaying, a successful
+/// operation marks the event it claimed in `wait_turn` as done, and a failed one makes the event
+/// pending again so that a later attempt can claim it. Operations that complete without having
+/// waited for their turn, such as those selected by `Select`, are never held back, but complete
+/// the first pending event they match so that the schedule doesn't stall on them.
+pub(crate) fn complete(channel: Option<usize>, op: ReplayOp, success: bool) {
+    let channel = match channel {
+        Some(channel) => channel,
+        None => return,
+    };
+    let c = coordinator();
+    let mut mode = match c.mode.lock() {
+        Ok(mode) => mode,
+        Err(_) => return,
+    };
+
+    match *mode {
+        Mode::Off => {}
+        Mode::Record {
+            start, ref mut log, ..
+        } => {
+            if success {
+                let thread = thread::current();
+                log.push(ReplayEvent {
+                    elapsed: start.elapsed(),
+                    channel,
+                    op,
+                    thread: thread.id(),
+                    thread_name: thread.name().map(|s| s.to_string()),
+                });
+            }
+        }
+        Mode::Replay {
+            ref mut schedule,
+            ref mut next,
+            ..
+        } => {
+            let index = {
+                let rest = &schedule[*next..];
+                let find = |progress| {
+                    rest.iter()
+                        .position(|e| e.1 == progress && e.0.matches(channel, op))
+                };
+                match find(Progress::Claimed) {
+                    Some(i) => Some(i),
+                    None if success => find(Progress::Pending),
+                    None => None,
+                }
+            };
+
+            if let Some(i) = index {
+                let event = &mut schedule[*next + i];
+                event.1 = if success {
+                    Progress::Done
+                } else {
+                    Progress::Pending
+                };
+
+                while *next < schedule.len() && schedule[*next].1 == Progress::Done {
+                    *next += 1;
+                }
+                c.turn.notify_all();
+            }
+        }
+    }
+}
+
+/// Starts recording successful channel operations, discarding any previous recording or replay.
+///
+/// This is a debugging aid, available with the `replay` feature. While recording, every message
+/// sent or received through [`Sender::send`], [`Sender::try_send`], [`Sender::send_timeout`],
+/// [`Receiver::recv`], [`Receiver::try_recv`], [`Receiver::recv_timeout`], the iterators built
+/// on them, or a completed [`Select`] operation, is logged together with the channel identifier,
+/// the thread, and the time since recording started. Operations are logged at the point where they
+/// succeed, so the log reflects the order in which messages actually moved through the channels.
+///
+/// Only channels created after this call are tracked, and they are numbered in the order they are
+/// created. Operations on older channels and operations on channels created by [`after`],
+/// [`tick`], [`never`], [`ready`], and [`merge`] are not logged.
+///
+/// Call [`stop_recording`] to get the log, and pass it to [`start_replay`] in a later run to
+/// reproduce the same order of operations.
+///
+/// [`Sender::send`]: struct.Sender.html#method.send
+/// [`Sender::try_send`]: struct.Sender.html#method.try_send
+/// [`Sender::send_timeout`]: struct.Sender.html#method.send_timeout
+/// [`Receiver::recv`]: struct.Receiver.html#method.recv
+/// [`Receiver::try_recv`]: struct.Receiver.html#method.try_recv
+/// [`Receiver::recv_timeout`]: struct.Receiver.html#method.recv_timeout
+/// [`Select`]: struct.Select.html
+/// [`after`]: fn.after.html
+/// [`tick`]: fn.tick.html
+/// [`never`]: fn.never.html
+/// [`ready`]: fn.ready.html
+/// [`merge`]: fn.merge.html
+/// [`stop_recording`]: fn.stop_recording.html
+/// [`start_replay`]: fn.start_replay.html
+pub fn start_recording() {
+    set_mode(Mode::Record {
+        start: Instant::now(),
+        log: Vec::new(),
+        next_channel: 0,
+    });
+}
+
+/// Stops recording and returns the recorded operations in the order they completed.
+///
+/// Returns an empty log if recording was not in progress.
+pub fn stop_recording() -> Vec<ReplayEvent> {
+    match set_mode(Mode::Off) {
+        Mode::Record { log, .. } => log,
+        _ => Vec::new(),
+    }
+}
+
+/// Starts replaying a schedule recorded by [`start_recording`].
+///
+/// Until [`stop_replay`] is called, every logged kind of operation on a channel that appears among
+/// the pending events of the schedule waits until it is the first pending event. Blocking
+/// operations wait for their turn, operations with a timeout give up when it elapses, and `try_`
+/// operations fail right away if it is not their turn yet. Operations that don't appear among the
+/// pending events proceed freely, and so does everything once the schedule is exhausted.
+///
+/// An operation whose turn has come claims its event, which lets the next event proceed, but the
+/// event only counts as replayed once the operation succeeds. If the operation fails instead, for
+/// example because a `try_` operation found the channel empty or a timeout elapsed, the event
+/// becomes pending again and is claimed by the next attempt. Operations completed through
+/// [`Select`] are never held back, but they count as the first pending event they match.
+///
+/// Channels are matched by the order in which they are created after this call, and threads by
+/// their names, because thread identifiers differ between runs. Operations by unnamed threads are
+/// told apart only by channel and kind, so threads that share a channel should be named.
+///
+/// For the replay to be faithful, the program has to create its channels in the same order as in
+/// the recorded run, and each thread has to produce the same messages in the same order, without
+/// depending on timing or on the interleaving of threads beyond what the schedule fixes. If the
+/// program diverges from the schedule, replay can stall because an operation waits for a turn that
+/// never comes, until [`stop_replay`] is called.
+///
+/// [`start_recording`]: fn.start_recording.html
+/// [`stop_replay`]: fn.stop_replay.html
+/// [`Select`]: struct.Select.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{start_recording, start_replay, stop_recording, stop_replay, unbounded};
+///
+/// fn run() -> Vec<&'static str> {
+///     let (s, r) = unbounded();
+///
+///     let handles: Vec<_> = ["a", "b"]
+///         .iter()
+///         .map(|&name| {
+///             let s = s.clone();
+///             thread::Builder::new()
+///                 .name(name.to_string())
+///                 .spawn(move || s.send(name).unwrap())
+///                 .unwrap()
+///         })
+///         .collect();
+///     for h in handles {
+///         h.join().unwrap();
+///     }
+///
+///     r.try_iter().collect()
+/// }
+///
+/// start_recording();
+/// let recorded = run();
+/// let log = stop_recording();
+/// assert_eq!(log.len(), 4);
+///
+/// // The threads send in the same order as in the recorded run.
+/// start_replay(log);
+/// assert_eq!(run(), recorded);
+/// assert!(stop_replay().is_empty());
+/// ```
+pub fn start_replay(schedule: Vec<ReplayEvent>) {
+    set_mode(Mode::Replay {
+        schedule: schedule
+            .into_iter()
+            .map(|e| (e, Progress::Pending))
+            .collect(),
+        next: 0,
+        next_channel: 0,
+    });
+}
+
+/// Stops replaying and releases all operations waiting for their turn.
+///
+/// Returns the events of the schedule that were not replayed.
+pub fn stop_replay() -> Vec<ReplayEvent> {
+    match set_mode(Mode::Off) {
+        Mode::Replay { schedule, .. } => schedule
+            .into_iter()
+            .filter(|e| e.1 != Progress::Done)
+            .map(|e| e.0)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
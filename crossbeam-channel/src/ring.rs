@@ -0,0 +1,152 @@
+//! Bounded channel whose sender overwrites the oldest message instead of blocking.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use channel::{bounded, Receiver, Sender};
+use err::{SendError, TryRecvError, TrySendError};
+
+/// A sender that never blocks and evicts the oldest message while the channel is full.
+///
+/// Created by [`ring_channel`]. See its documentation for more.
+///
+/// [`ring_channel`]: fn.ring_channel.html
+pub struct RingSender<T> {
+    /// Sends messages into the bounded channel.
+    sender: Sender<T>,
+
+    /// Serializes sends, so that a slot freed by an eviction is taken by the evicting sender.
+    lock: Arc<Mutex<()>>,
+}
+
+/// Creates a bounded channel that keeps only the most recent messages.
+///
+/// Sending through the returned [`RingSender`] never blocks and never fails because the channel
+/// is full. When it is full, the oldest message still in the channel is removed to make room and
+/// returned to the sender, so the channel always holds the `cap` most recently sent messages that
+/// have not been received yet. This suits telemetry and similar streams where stale samples are
+/// worthless once newer ones arrive.
+///
+/// **Messages are lost by design.** A slow receiver misses every message that was evicted before
+/// it got to it, and it cannot tell how many were evicted. Retained messages are received in the
+/// order they were sent.
+///
+/// Senders obtained through [`Receiver::make_sender`] are plain senders: they block or fail when
+/// the channel is full, like those of [`bounded`].
+///
+/// [`RingSender`]: struct.RingSender.html
+/// [`Receiver::make_sender`]: struct.Receiver.html#method.make_sender
+/// [`bounded`]: fn.bounded.html
+///
+/// # Panics
+///
+/// Panics if the capacity is zero or too large, just like [`bounded`].
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::ring_channel;
+///
+/// let (s, r) = ring_channel(2);
+///
+/// assert_eq!(s.send(1), Ok(None));
+/// assert_eq!(s.send(2), Ok(None));
+///
+/// // The channel is full, so the oldest message makes room.
+/// assert_eq!(s.send(3), Ok(Some(1)));
+///
+/// assert_eq!(r.recv(), Ok(2));
+/// assert_eq!(r.recv(), Ok(3));
+/// ```
+pub fn ring_channel<T>(cap: usize) -> (RingSender<T>, Receiver<T>) {
+    assert!(cap > 0, "capacity must be positive");
+
+    let (s, r) = bounded(cap);
+    let sender = RingSender {
+        sender: s,
+        lock: Arc::new(Mutex::new(())),
+    };
+    (sender, r)
+}
+
+impl<T> RingSender<T> {
+    /// Sends a message without blocking, evicting the oldest message if the channel is full.
+    ///
+    /// Returns the evicted message, or `None` if the channel had room. If all receivers have been
+    /// dropped, an error is returned containing the message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{ring_channel, SendError};
+    ///
+    /// let (s, r) = ring_channel(1);
+    /// assert_eq!(s.send(1), Ok(None));
+    /// assert_eq!(s.send(2), Ok(Some(1)));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.send(3), Err(SendError(3)));
+    /// ```
+    pub fn send(&self, mut msg: T) -> Result<Option<T>, SendError<T>> {
+        let _guard = self.lock.lock().unwrap();
+        let mut evicted = None;
+
+        loop {
+            msg = match self.sender.try_send(msg) {
+                Ok(()) => return Ok(evicted),
+                Err(TrySendError::Disconnected(msg)) => return Err(SendError(msg)),
+                Err(TrySendError::Full(msg)) => msg,
+            };
+
+            // Evict the oldest message through a temporary receiver, which is unavailable if all
+            // receivers have been dropped.
+            let r = match self.sender.make_receiver() {
+                Some(r) => r,
+                None => return Err(SendError(msg)),
+            };
+            match r.try_recv() {
+                // Only a plain sender can refill the slot before we take it, in which case we
+                // evict again and keep the newer of the two evicted messages.
+                Ok(old) => evicted = Some(old),
+                // A receiver has just made room.
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => return Err(SendError(msg)),
+            }
+        }
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.sender.is_empty()
+    }
+
+    /// Returns `true` if the channel is full, so the next send evicts a message.
+    pub fn is_full(&self) -> bool {
+        self.sender.is_full()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> usize {
+        self.sender.capacity().unwrap()
+    }
+}
+
+impl<T> Clone for RingSender<T> {
+    fn clone(&self) -> Self {
+        RingSender {
+            sender: self.sender.clone(),
+            lock: self.lock.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for RingSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("RingSender { .. }")
+    }
+}
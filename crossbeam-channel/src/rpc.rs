@@ -0,0 +1,182 @@
+//! Request/response calls over channels.
+
+use std::fmt;
+use std::time::Duration;
+
+use channel::{bounded, oneshot, Receiver, Sender};
+use err::{CallError, RecvError, RecvTimeoutError, SendError, TryRecvError};
+
+/// A request paired with the channel its reply is sent into.
+type Call<Req, Resp> = (Req, Responder<Resp>);
+
+/// Creates a channel for request/response calls.
+///
+/// Each [`RpcClient::call`] sends a request together with a fresh [`oneshot`] reply channel and
+/// blocks until the reply arrives. A server receives the request together with a [`Responder`],
+/// which sends the reply back to the calling client. Requests go through a zero-capacity channel,
+/// so a call blocks until a server takes its request.
+///
+/// Both sides can be cloned. Each request is received by exactly one server, and its reply goes
+/// back to exactly the client that made the call.
+///
+/// [`RpcClient::call`]: struct.RpcClient.html#method.call
+/// [`oneshot`]: fn.oneshot.html
+/// [`Responder`]: struct.Responder.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::rpc;
+///
+/// let (client, server) = rpc();
+///
+/// thread::spawn(move || {
+///     while let Ok((x, responder)) = server.recv() {
+///         responder.respond(x * 2);
+///     }
+/// });
+///
+/// assert_eq!(client.call(1), Ok(2));
+/// assert_eq!(client.call(21), Ok(42));
+/// ```
+pub fn rpc<Req, Resp>() -> (RpcClient<Req, Resp>, RpcServer<Req, Resp>) {
+    let (s, r) = bounded(0);
+    (RpcClient { requests: s }, RpcServer { requests: r })
+}
+
+/// The calling side of a channel created by [`rpc`].
+///
+/// [`rpc`]: fn.rpc.html
+pub struct RpcClient<Req, Resp> {
+    /// The channel requests are sent into.
+    requests: Sender<Call<Req, Resp>>,
+}
+
+impl<Req, Resp> RpcClient<Req, Resp> {
+    /// Sends a request and blocks the current thread until the reply arrives.
+    ///
+    /// If all servers have been dropped, the request is returned in a
+    /// [`CallError::Disconnected`]. If the server that received the request drops its
+    /// [`Responder`] without replying, a [`CallError::NoReply`] is returned.
+    ///
+    /// [`CallError::Disconnected`]: enum.CallError.html#variant.Disconnected
+    /// [`CallError::NoReply`]: enum.CallError.html#variant.NoReply
+    /// [`Responder`]: struct.Responder.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{rpc, CallError};
+    ///
+    /// let (client, server) = rpc::<i32, i32>();
+    ///
+    /// thread::spawn(move || {
+    ///     // Drop the responder without replying.
+    ///     let _ = server.recv();
+    /// });
+    ///
+    /// assert_eq!(client.call(1), Err(CallError::NoReply));
+    /// assert_eq!(client.call(2), Err(CallError::Disconnected(2)));
+    /// ```
+    pub fn call(&self, req: Req) -> Result<Resp, CallError<Req>> {
+        let (s, r) = oneshot();
+        let responder = Responder { reply: s };
+
+        if let Err(SendError((req, _))) = self.requests.send((req, responder)) {
+            return Err(CallError::Disconnected(req));
+        }
+        r.recv().map_err(|_| CallError::NoReply)
+    }
+}
+
+impl<Req, Resp> Clone for RpcClient<Req, Resp> {
+    fn clone(&self) -> Self {
+        RpcClient {
+            requests: self.requests.clone(),
+        }
+    }
+}
+
+impl<Req, Resp> fmt::Debug for RpcClient<Req, Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("RpcClient { .. }")
+    }
+}
+
+/// The serving side of a channel created by [`rpc`].
+///
+/// [`rpc`]: fn.rpc.html
+pub struct RpcServer<Req, Resp> {
+    /// The channel requests are received from.
+    requests: Receiver<Call<Req, Resp>>,
+}
+
+impl<Req, Resp> RpcServer<Req, Resp> {
+    /// Attempts to receive a request without blocking.
+    ///
+    /// Since requests go through a zero-capacity channel, this only succeeds if a client is
+    /// blocked in a call at the same time.
+    pub fn try_recv(&self) -> Result<(Req, Responder<Resp>), TryRecvError> {
+        self.requests.try_recv()
+    }
+
+    /// Blocks the current thread until a request is received.
+    ///
+    /// If all clients have been dropped, this call returns an error.
+    pub fn recv(&self) -> Result<(Req, Responder<Resp>), RecvError> {
+        self.requests.recv()
+    }
+
+    /// Waits for a request to be received, but only for a limited time.
+    pub fn recv_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<(Req, Responder<Resp>), RecvTimeoutError> {
+        self.requests.recv_timeout(timeout)
+    }
+}
+
+impl<Req, Resp> Clone for RpcServer<Req, Resp> {
+    fn clone(&self) -> Self {
+        RpcServer {
+            requests: self.requests.clone(),
+        }
+    }
+}
+
+impl<Req, Resp> fmt::Debug for RpcServer<Req, Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("RpcServer { .. }")
+    }
+}
+
+/// Sends the reply to a request received by an [`RpcServer`].
+///
+/// Dropping a responder without replying makes the call fail with [`CallError::NoReply`].
+///
+/// [`RpcServer`]: struct.RpcServer.html
+/// [`CallError::NoReply`]: enum.CallError.html#variant.NoReply
+pub struct Responder<Resp> {
+    /// The channel the reply is sent into.
+    reply: Sender<Resp>,
+}
+
+impl<Resp> Responder<Resp> {
+    /// Sends the reply back to the client that made the call.
+    ///
+    /// The client stays blocked in [`RpcClient::call`] until the reply arrives, and the reply
+    /// channel has room for it, so this never blocks.
+    ///
+    /// [`RpcClient::call`]: struct.RpcClient.html#method.call
+    pub fn respond(self, resp: Resp) {
+        let _ = self.reply.send(resp);
+    }
+}
+
+impl<Resp> fmt::Debug for Responder<Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Responder { .. }")
+    }
+}
@@ -0,0 +1,168 @@
+//! Channels for passing borrowed data between scoped threads.
+
+use std::fmt;
+use std::time::Duration;
+
+use channel::{unbounded, Iter, Receiver, Sender, TryIter};
+use err::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+
+/// Creates a channel of unbounded capacity for sending references that borrow from a scope.
+///
+/// The channel carries `&'env T` rather than owned messages, so threads spawned inside a scope can
+/// hand out pieces of data owned outside of it without wrapping the data in an `Arc`. All
+/// lifetimes are checked at compile time: both sides of the channel are invariant in `'env`, so
+/// only references that outlive the channel can be sent into it, and received references can't
+/// be kept past `'env`.
+///
+/// This is meant to be used with scoped threads, such as those spawned by
+/// `crossbeam_utils::thread::scope`. Threads spawned with `std::thread::spawn` require `'static`
+/// data, in which case there is nothing to borrow and [`unbounded`] works just as well.
+///
+/// [`unbounded`]: fn.unbounded.html
+///
+/// # Examples
+///
+/// ```
+/// extern crate crossbeam_channel;
+/// extern crate crossbeam_utils;
+/// # fn main() {
+/// use crossbeam_channel::scoped_channel;
+/// use crossbeam_utils::thread::scope;
+///
+/// let words = vec![String::from("foo"), String::from("bar"), String::from("baz")];
+/// let (s, r) = scoped_channel::<String>();
+///
+/// let total = scope(|scope| {
+///     let worker = scope.spawn(move |_| r.iter().map(|w| w.len()).sum::<usize>());
+///
+///     for w in &words {
+///         s.send(w).unwrap();
+///     }
+///     drop(s);
+///
+///     worker.join().unwrap()
+/// })
+/// .unwrap();
+///
+/// assert_eq!(total, 9);
+/// # }
+/// ```
+pub fn scoped_channel<'env, T: ?Sized>() -> (ScopedSender<'env, T>, ScopedReceiver<'env, T>) {
+    let (sender, receiver) = unbounded();
+    (ScopedSender { sender }, ScopedReceiver { receiver })
+}
+
+/// The sending side of a channel created by [`scoped_channel`].
+///
+/// [`scoped_channel`]: fn.scoped_channel.html
+pub struct ScopedSender<'env, T: 'env + ?Sized> {
+    /// The underlying sender.
+    ///
+    /// `Sender` is invariant in its message type, which makes this type invariant in `'env`.
+    sender: Sender<&'env T>,
+}
+
+impl<'env, T: ?Sized> ScopedSender<'env, T> {
+    /// Sends a reference into the channel.
+    ///
+    /// If all receivers have been dropped, an error is returned that contains the reference.
+    pub fn send(&self, msg: &'env T) -> Result<(), SendError<&'env T>> {
+        self.sender.send(msg)
+    }
+
+    /// Returns the underlying sender.
+    pub fn sender(&self) -> &Sender<&'env T> {
+        &self.sender
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.sender.is_empty()
+    }
+
+    /// Returns the number of references in the channel.
+    pub fn len(&self) -> usize {
+        self.sender.len()
+    }
+}
+
+impl<'env, T: ?Sized> Clone for ScopedSender<'env, T> {
+    fn clone(&self) -> Self {
+        ScopedSender {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<'env, T: ?Sized> fmt::Debug for ScopedSender<'env, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ScopedSender { .. }")
+    }
+}
+
+/// The receiving side of a channel created by [`scoped_channel`].
+///
+/// [`scoped_channel`]: fn.scoped_channel.html
+pub struct ScopedReceiver<'env, T: 'env + ?Sized> {
+    /// The underlying receiver.
+    ///
+    /// `Receiver` is invariant in its message type, which makes this type invariant in `'env`.
+    receiver: Receiver<&'env T>,
+}
+
+impl<'env, T: ?Sized> ScopedReceiver<'env, T> {
+    /// Attempts to receive a reference from the channel without blocking.
+    pub fn try_recv(&self) -> Result<&'env T, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// Blocks the current thread until a reference is received or the channel is empty and
+    /// disconnected.
+    pub fn recv(&self) -> Result<&'env T, RecvError> {
+        self.receiver.recv()
+    }
+
+    /// Waits for a reference to be received from the channel, but only for a limited time.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<&'env T, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// A blocking iterator over references in the channel.
+    pub fn iter(&self) -> Iter<'_, &'env T> {
+        self.receiver.iter()
+    }
+
+    /// A non-blocking iterator over references in the channel.
+    pub fn try_iter(&self) -> TryIter<'_, &'env T> {
+        self.receiver.try_iter()
+    }
+
+    /// Returns the underlying receiver.
+    pub fn receiver(&self) -> &Receiver<&'env T> {
+        &self.receiver
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+
+    /// Returns the number of references in the channel.
+    pub fn len(&self) -> usize {
+        self.receiver.len()
+    }
+}
+
+impl<'env, T: ?Sized> Clone for ScopedReceiver<'env, T> {
+    fn clone(&self) -> Self {
+        ScopedReceiver {
+            receiver: self.receiver.clone(),
+        }
+    }
+}
+
+impl<'env, T: ?Sized> fmt::Debug for ScopedReceiver<'env, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ScopedReceiver { .. }")
+    }
+}
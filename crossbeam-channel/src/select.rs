@@ -1,8 +1,10 @@
 //! Interface to the select mechanism.
 
+use std::cmp;
 use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use crossbeam_utils::Backoff;
@@ -23,8 +25,14 @@ use utils;
 pub struct Token {
     pub after: flavors::after::AfterToken,
     pub array: flavors::array::ArrayToken,
+    pub coalesce: flavors::coalesce::CoalesceToken,
+    pub heap: flavors::heap::HeapToken,
     pub list: flavors::list::ListToken,
+    pub merge: flavors::merge::MergeToken,
     pub never: flavors::never::NeverToken,
+    pub oneshot: flavors::oneshot::OneshotToken,
+    pub priority: flavors::priority::PriorityToken,
+    pub ready: flavors::ready::ReadyToken,
     pub tick: flavors::tick::TickToken,
     pub zero: flavors::zero::ZeroToken,
 }
@@ -47,6 +55,15 @@ impl Operation {
         assert!(val > 2);
         Operation(val)
     }
+
+    /// Creates an operation identifier from the address of a context.
+    ///
+    /// Unlike `hook`, the identifier stays unique after the current function returns, as long as
+    /// the context is alive. This suits registrations that outlive the call that made them.
+    #[inline]
+    pub fn from_context(cx: &Context) -> Operation {
+        Operation(cx.addr())
+    }
 }
 
 /// Current state of a select or a blocking operation.
@@ -153,6 +170,10 @@ impl<'a, T: SelectHandle> SelectHandle for &'a T {
     }
 }
 
+/// An operation participating in select: its handle, its index, and the address of the sender or
+/// receiver it was added with.
+pub type HandleEntry<'a> = (&'a SelectHandle, usize, *const u8);
+
 /// Determines when a select operation should time out.
 #[derive(Clone, Copy, Eq, PartialEq)]
 enum Timeout {
@@ -166,13 +187,47 @@ enum Timeout {
     At(Instant),
 }
 
+/// Determines how long a select operation keeps retrying operations before blocking.
+#[derive(Clone, Copy)]
+enum Spin {
+    /// Retry the given number of times.
+    Count(usize),
+
+    /// Retry until the given amount of time has elapsed.
+    For(Duration),
+}
+
+/// Spin durations of this many seconds or more are too long to turn into a deadline.
+///
+/// Adding such a duration to the current time could overflow, and `Instant::checked_add` is not
+/// available on the oldest supported Rust. Spinning that long is no different from spinning
+/// without a deadline anyway.
+const MAX_SPIN_SECS: u64 = 1 << 32;
+
 /// Runs until one of the operations is selected, potentially blocking the current thread.
 ///
+/// Before blocking, the operations are retried while spinning, for as long as `spin` allows.
+///
 /// Successful receive operations will have to be followed up by `channel::read()` and successful
 /// send operations by `channel::write()`.
 fn run_select(
-    handles: &mut [(&SelectHandle, usize, *const u8)],
+    handles: &mut [HandleEntry],
     timeout: Timeout,
+    spin: Spin,
+) -> Option<(Token, usize, *const u8)> {
+    // Shuffle the operations for fairness.
+    utils::shuffle(handles);
+
+    run_select_in_order(handles, timeout, spin)
+}
+
+/// Runs until one of the operations is selected, like `run_select`, but without shuffling.
+///
+/// Whenever several operations are ready, the one that comes first in `handles` is selected.
+fn run_select_in_order(
+    handles: &mut [HandleEntry],
+    timeout: Timeout,
+    spin: Spin,
 ) -> Option<(Token, usize, *const u8)> {
     if handles.is_empty() {
         // Wait until the timeout and return.
@@ -189,9 +244,6 @@ fn run_select(
         }
     }
 
-    // Shuffle the operations for fairness.
-    utils::shuffle(handles);
-
     // Create a token, which serves as a temporary variable that gets initialized in this function
     // and is later used by a call to `channel::read()` or `channel::write()` that completes the
     // selected operation.
@@ -204,7 +256,55 @@ fn run_select(
         }
     }
 
+    // If blocking is allowed, spin for a while and retry the operations before blocking.
+    if timeout != Timeout::Now {
+        let backoff = Backoff::new();
+        let (retries, spin_deadline) = match spin {
+            Spin::Count(count) => (count, None),
+            Spin::For(duration) => {
+                let deadline = if duration.as_secs() < MAX_SPIN_SECS {
+                    Some(Instant::now() + duration)
+                } else {
+                    None
+                };
+                (usize::max_value(), deadline)
+            }
+        };
+
+        for _ in 0..retries {
+            if let Some(d) = spin_deadline {
+                if Instant::now() >= d {
+                    break;
+                }
+            }
+            backoff.spin();
+
+            for &(handle, i, ptr) in handles.iter() {
+                if handle.try_select(&mut token) {
+                    return Some((token, i, ptr));
+                }
+            }
+
+            // Check for timeout after every sweep so that spinning never outlasts the deadline,
+            // however many times we are allowed to spin.
+            if let Timeout::At(when) = timeout {
+                if Instant::now() >= when {
+                    return None;
+                }
+            }
+        }
+    }
+
     loop {
+        // Check for timeout before every attempt to block. If operations keep looking ready during
+        // registration without any of them being selected, the thread never blocks, and this is
+        // where the deadline is enforced.
+        if let Timeout::At(when) = timeout {
+            if Instant::now() >= when {
+                return None;
+            }
+        }
+
         // Prepare for blocking.
         let res = Context::with(|cx| {
             let mut sel = Selected::Waiting;
@@ -216,11 +316,22 @@ fn run_select(
             }
 
             // Register all operations.
+            //
+            // No operation may become ready, for example because its channel gets disconnected,
+            // without this thread noticing, so there is nothing to count here:
+            //
+            // * If the operation became ready before it was registered, `register` sees that and
+            //   returns `true`, which aborts the selection.
+            // * If it becomes ready after it was registered, the channel selects this context
+            //   (disconnection selects `Selected::Disconnected`), so `cx.selected()` below or
+            //   `cx.wait_until()` returns.
+            //
+            // In both cases the loop below retries all operations, and the ready one gets selected.
             for (handle, i, _) in handles.iter_mut() {
                 registered_count += 1;
 
-                // If registration returns `false`, that means the operation has just become ready.
-                if handle.register(Operation::hook::<&SelectHandle>(handle), cx) {
+                // If registration returns `true`, that means the operation has just become ready.
+                if handle.register(Operation::hook(handle), cx) {
                     // Try aborting select.
                     sel = match cx.try_select(Selected::Aborted) {
                         Ok(()) => {
@@ -259,7 +370,7 @@ fn run_select(
 
             // Unregister all registered operations.
             for (handle, _, _) in handles.iter_mut().take(registered_count) {
-                handle.unregister(Operation::hook::<&SelectHandle>(handle));
+                handle.unregister(Operation::hook(handle));
             }
 
             match sel {
@@ -279,7 +390,7 @@ fn run_select(
                     // Find the selected operation.
                     for (handle, i, ptr) in handles.iter_mut() {
                         // Is this the selected operation?
-                        if sel == Selected::Operation(Operation::hook::<&SelectHandle>(handle)) {
+                        if sel == Selected::Operation(Operation::hook(handle)) {
                             // Try selecting this operation.
                             if handle.accept(&mut token, cx) {
                                 return Some((*i, *ptr));
@@ -304,20 +415,16 @@ fn run_select(
             }
         }
 
-        match timeout {
-            Timeout::Now => return None,
-            Timeout::Never => {}
-            Timeout::At(when) => {
-                if Instant::now() >= when {
-                    return None;
-                }
-            }
+        if let Timeout::Now = timeout {
+            return None;
         }
     }
 }
 
 /// Runs until one of the operations becomes ready, potentially blocking the current thread.
-fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout) -> Option<usize> {
+///
+/// Unless `biased` is set, the operations are shuffled first for fairness.
+fn run_ready(handles: &mut [HandleEntry], timeout: Timeout, biased: bool) -> Option<usize> {
     if handles.is_empty() {
         // Wait until the timeout and return.
         match timeout {
@@ -334,7 +441,9 @@ fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout
     }
 
     // Shuffle the operations for fairness.
-    utils::shuffle(handles);
+    if !biased {
+        utils::shuffle(handles);
+    }
 
     loop {
         let backoff = Backoff::new();
@@ -372,9 +481,10 @@ fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout
             // Begin watching all operations.
             for (handle, _, _) in handles.iter_mut() {
                 registered_count += 1;
-                let oper = Operation::hook::<&SelectHandle>(handle);
+                let oper = Operation::hook(handle);
 
-                // If registration returns `false`, that means the operation has just become ready.
+                // If registration returns `true`, that means the operation has just become ready.
+                // See `run_select` for why no readiness change can be missed.
                 if handle.watch(oper, cx) {
                     sel = match cx.try_select(Selected::Operation(oper)) {
                         Ok(()) => Selected::Operation(oper),
@@ -410,7 +520,7 @@ fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout
 
             // Unwatch all operations.
             for (handle, _, _) in handles.iter_mut().take(registered_count) {
-                handle.unwatch(Operation::hook::<&SelectHandle>(handle));
+                handle.unwatch(Operation::hook(handle));
             }
 
             match sel {
@@ -419,7 +529,7 @@ fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout
                 Selected::Disconnected => {}
                 Selected::Operation(_) => {
                     for (handle, i, _) in handles.iter_mut() {
-                        let oper = Operation::hook::<&SelectHandle>(handle);
+                        let oper = Operation::hook(handle);
                         if sel == Selected::Operation(oper) {
                             return Some(*i);
                         }
@@ -437,12 +547,28 @@ fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout
     }
 }
 
+/// Runs `run_select`, or `run_select_in_order` if `biased` is set.
+fn run_select_with(
+    handles: &mut [HandleEntry],
+    timeout: Timeout,
+    biased: bool,
+) -> Option<(Token, usize, *const u8)> {
+    if biased {
+        run_select_in_order(handles, timeout, Spin::Count(0))
+    } else {
+        run_select(handles, timeout, Spin::Count(0))
+    }
+}
+
 /// Attempts to select one of the operations without blocking.
+///
+/// If `biased` is set, the first ready operation in `handles` is selected.
 #[inline]
 pub fn try_select<'a>(
-    handles: &mut [(&'a SelectHandle, usize, *const u8)],
+    handles: &mut [HandleEntry<'a>],
+    biased: bool,
 ) -> Result<SelectedOperation<'a>, TrySelectError> {
-    match run_select(handles, Timeout::Now) {
+    match run_select_with(handles, Timeout::Now, biased) {
         None => Err(TrySelectError),
         Some((token, index, ptr)) => Ok(SelectedOperation {
             token,
@@ -454,13 +580,15 @@ pub fn try_select<'a>(
 }
 
 /// Blocks until one of the operations becomes ready and selects it.
+///
+/// If `biased` is set, the first ready operation in `handles` is selected.
 #[inline]
-pub fn select<'a>(handles: &mut [(&'a SelectHandle, usize, *const u8)]) -> SelectedOperation<'a> {
+pub fn select<'a>(handles: &mut [HandleEntry<'a>], biased: bool) -> SelectedOperation<'a> {
     if handles.is_empty() {
         panic!("no operations have been added to `Select`");
     }
 
-    let (token, index, ptr) = run_select(handles, Timeout::Never).unwrap();
+    let (token, index, ptr) = run_select_with(handles, Timeout::Never, biased).unwrap();
     SelectedOperation {
         token,
         index,
@@ -470,14 +598,17 @@ pub fn select<'a>(handles: &mut [(&'a SelectHandle, usize, *const u8)]) -> Selec
 }
 
 /// Blocks for a limited time until one of the operations becomes ready and selects it.
+///
+/// If `biased` is set, the first ready operation in `handles` is selected.
 #[inline]
 pub fn select_timeout<'a>(
-    handles: &mut [(&'a SelectHandle, usize, *const u8)],
+    handles: &mut [HandleEntry<'a>],
     timeout: Duration,
+    biased: bool,
 ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
     let timeout = Timeout::At(Instant::now() + timeout);
 
-    match run_select(handles, timeout) {
+    match run_select_with(handles, timeout, biased) {
         None => Err(SelectTimeoutError),
         Some((token, index, ptr)) => Ok(SelectedOperation {
             token,
@@ -488,6 +619,105 @@ pub fn select_timeout<'a>(
     }
 }
 
+/// Attempts to receive a message from one of the receivers without blocking.
+///
+/// This function does exactly one sweep over the receivers, in a random order, and returns the
+/// index of the first receiver that had a message ready, along with the message. If several
+/// receivers have a message ready, each of them is equally likely to be chosen. If
+/// no message is ready, `None` is returned. This function never blocks and never reads the clock,
+/// which makes it a building block for custom event loops.
+///
+/// Unlike [`Select::try_select`], this function does not consider a disconnected channel to be
+/// ready. Such channels are simply skipped.
+///
+/// [`Select::try_select`]: struct.Select.html#method.try_select
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{select_try, unbounded};
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+///
+/// assert_eq!(select_try(&[&r1, &r2]), None);
+///
+/// s2.send(10).unwrap();
+/// assert_eq!(select_try(&[&r1, &r2]), Some((1, 10)));
+/// # drop(s1);
+/// ```
+pub fn select_try<T>(receivers: &[&Receiver<T>]) -> Option<(usize, T)> {
+    // Shuffle the receivers for fairness. Starting at a random position and going around in
+    // order would favor receivers that come right after ones that aren't ready.
+    let mut order = (0..receivers.len()).collect::<Vec<_>>();
+    utils::shuffle(&mut order);
+
+    for index in order {
+        if let Ok(msg) = receivers[index].try_recv() {
+            return Some((index, msg));
+        }
+    }
+
+    None
+}
+
+/// Runs a number of selections over receive operations and counts how many times each one won.
+///
+/// A [`Select`] is built with one receive operation for each receiver in `receivers`, and then
+/// `iterations` selections are made with it, each receiving the message from the selected
+/// operation. The returned vector holds, for each receiver, the number of selections it won.
+///
+/// This is a diagnostic for checking that a set of channels is selected fairly, for example to
+/// catch accidental starvation after reordering channels. The selections go through the same code
+/// as [`Select::select`], so the counts reflect the random choice made among ready operations.
+///
+/// For the counts to be meaningful, every receiver must have a message ready on every iteration,
+/// so each channel should hold at least `iterations` messages. A disconnected channel is always
+/// ready too. If some receivers are not ready, they simply lose to the others, and if none of them
+/// are ready, this function blocks until one is.
+///
+/// [`Select`]: struct.Select.html
+/// [`Select::select`]: struct.Select.html#method.select
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{select_fairness_test, unbounded};
+///
+/// let (s1, r1) = unbounded::<()>();
+/// let (s2, r2) = unbounded::<()>();
+///
+/// // Disconnected channels are always ready.
+/// drop(s1);
+/// drop(s2);
+///
+/// let counts = select_fairness_test(&[&r1, &r2], 1000);
+/// assert_eq!(counts.iter().sum::<usize>(), 1000);
+/// assert!(counts.iter().all(|&c| c > 300));
+/// ```
+pub fn select_fairness_test<T>(receivers: &[&Receiver<T>], iterations: usize) -> Vec<usize> {
+    let mut counts = vec![0; receivers.len()];
+
+    // With no operations, a selection would block forever.
+    if receivers.is_empty() {
+        return counts;
+    }
+
+    let mut sel = Select::new();
+    for r in receivers {
+        sel.recv(r);
+    }
+
+    for _ in 0..iterations {
+        let oper = sel.select();
+        let index = oper.index();
+        let _ = oper.recv(receivers[index]);
+        counts[index] += 1;
+    }
+
+    counts
+}
+
 /// Selects from a set of channel operations.
 ///
 /// `Select` allows you to define a set of channel operations, wait until any one of them becomes
@@ -513,6 +743,34 @@ pub fn select_timeout<'a>(
 ///   so it's wise to use a retry loop. However, note that these methods might return with success
 ///   spuriously, so it's a good idea to always double check if the operation is really ready.
 ///
+/// # Threads
+///
+/// `Select` is [`Send`] and [`Sync`]. It holds no thread-local state: the current thread's
+/// context is looked up only for the duration of each call that selects or waits. A `Select` can
+/// therefore be built on one thread and used on another, or stored in a struct that is shared
+/// between threads.
+///
+/// However, every method that adds operations, selects, or waits takes `&mut self`, so the
+/// compiler rejects using one `Select` from several threads at the same time. To share one, wrap
+/// it in a [`Mutex`]. Usually it is simpler to keep a separate `Select` per thread: creating one
+/// is cheap, and an existing one can be reused for a new list of operations by calling [`clear`],
+/// which keeps its allocated storage. Use [`with_capacity`] to allocate storage for a known number
+/// of operations up front.
+///
+/// # Panic safety
+///
+/// Selection never calls user code while operations are registered with channels or while any
+/// internal lock is held, and internal locks are released by guards even during unwinding. A
+/// panic caught with [`catch_unwind`] therefore never leaves a stale registration behind or a
+/// channel locked, and the `Select` as well as the channels remain usable afterwards. In
+/// particular, a message that panics when dropped is dropped by the caller only after it has been
+/// fully removed from the channel.
+///
+/// The one exception is a [`SelectedOperation`] that is never completed. The channel has already
+/// reserved a slot or paired up with another thread for it, so dropping it panics, and dropping
+/// it while another panic is unwinding aborts the process. Complete the selected operation before
+/// running any code that might panic.
+///
 /// # Examples
 ///
 /// Use [`select`] to receive a message from a list of receivers:
@@ -571,12 +829,87 @@ pub fn select_timeout<'a>(
 /// [`try_ready`]: struct.Select.html#method.try_ready
 /// [`ready`]: struct.Select.html#method.ready
 /// [`ready_timeout`]: struct.Select.html#method.ready_timeout
+/// [`clear`]: struct.Select.html#method.clear
+/// [`with_capacity`]: struct.Select.html#method.with_capacity
+/// [`Send`]: https://doc.rust-lang.org/std/marker/trait.Send.html
+/// [`Sync`]: https://doc.rust-lang.org/std/marker/trait.Sync.html
+/// [`Mutex`]: https://doc.rust-lang.org/std/sync/struct.Mutex.html
+/// [`catch_unwind`]: https://doc.rust-lang.org/std/panic/fn.catch_unwind.html
+/// [`SelectedOperation`]: struct.SelectedOperation.html
 pub struct Select<'a> {
     /// A list of senders and receivers participating in selection.
-    handles: Vec<(&'a SelectHandle, usize, *const u8)>,
+    handles: Vec<HandleEntry<'a>>,
 
     /// The next index to assign to an operation.
     next_index: usize,
+
+    /// How long to keep attempting to select an operation before blocking.
+    spin: Spin,
+
+    /// The direction of each operation, by index.
+    directions: Vec<Direction>,
+
+    /// The direction that wins ties between ready operations, if any.
+    prefer: Option<Direction>,
+
+    /// Whether ties between ready operations are won by the one added first.
+    biased: bool,
+
+    /// The current phase of step-by-step selection with `poll_phase`.
+    phase: Phase,
+
+    /// Called when a subscribed operation may have become ready.
+    waker: Option<Arc<Fn() + Send + Sync>>,
+
+    /// The context operations are subscribed with, while in the `Parked` phase.
+    subscription: Option<Subscription>,
+}
+
+/// The context that `poll_phase` subscribes to operations with.
+///
+/// Dropping it cancels the subscription, so that the waker is never called afterwards. It doesn't
+/// borrow the channels, so `Select` needs no `Drop` of its own and can still be dropped after the
+/// channels it selects over. Subscriptions left in a channel this way are removed the next time
+/// the channel notifies its observers.
+struct Subscription {
+    cx: Context,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let _ = self.cx.try_select(Selected::Aborted);
+        self.cx.drop_callback();
+    }
+}
+
+/// The direction of an operation in `Select`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// A send operation.
+    Send,
+
+    /// A receive operation.
+    Recv,
+}
+
+/// The phase of a [`Select`] that is driven step by step with [`Select::poll_phase`].
+///
+/// [`Select`]: struct.Select.html
+/// [`Select::poll_phase`]: struct.Select.html#method.poll_phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// The next step checks whether any of the operations are ready.
+    Try,
+
+    /// None of the operations were ready, and the next step subscribes to all of them.
+    Subscribing,
+
+    /// The operations are subscribed to, and the next step checks whether any of the channels
+    /// notified the waker.
+    Parked,
+
+    /// The operation with the given index is ready.
+    Done(usize),
 }
 
 unsafe impl<'a> Send for Select<'a> {}
@@ -596,9 +929,156 @@ impl<'a> Select<'a> {
     /// assert!(sel.try_select().is_err());
     /// ```
     pub fn new() -> Select<'a> {
+        Select::with_options(Vec::with_capacity(4), Spin::Count(0), false)
+    }
+
+    /// Creates an empty list of channel operations that are tried in the order they are added.
+    ///
+    /// By default, operations are shuffled before every selection so that, when several of them
+    /// are ready, a random one is selected. A biased `Select` skips the shuffle and always selects
+    /// the first ready operation in the order they were added. The same applies to [`ready`] and
+    /// friends.
+    ///
+    /// This makes selection deterministic and expresses priority, but operations added later can
+    /// starve: they are never selected as long as an earlier operation keeps being ready. The
+    /// [`select_biased!`] macro is the macro counterpart of this constructor.
+    ///
+    /// [`ready`]: struct.Select.html#method.ready
+    /// [`select_biased!`]: macro.select_biased.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// s1.send(1).unwrap();
+    /// s2.send(2).unwrap();
+    ///
+    /// let mut sel = Select::new_biased();
+    /// let oper1 = sel.recv(&r1);
+    /// let _oper2 = sel.recv(&r2);
+    ///
+    /// // Both operations are ready, but the first one always wins.
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert_eq!(oper.recv(&r1), Ok(1));
+    /// ```
+    pub fn new_biased() -> Select<'a> {
+        Select::with_options(Vec::with_capacity(4), Spin::Count(0), true)
+    }
+
+    /// Creates an empty list of channel operations with storage for `cap` operations.
+    ///
+    /// Operations beyond `cap` can still be added, but may cause a reallocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let rs = (0..16).map(|_| unbounded::<i32>().1).collect::<Vec<_>>();
+    ///
+    /// let mut sel = Select::with_capacity(rs.len());
+    /// for r in &rs {
+    ///     sel.recv(r);
+    /// }
+    /// ```
+    pub fn with_capacity(cap: usize) -> Select<'a> {
+        Select::with_options(Vec::with_capacity(cap), Spin::Count(0), false)
+    }
+
+    /// Creates an empty list of channel operations that spins before blocking.
+    ///
+    /// When none of the operations are ready, [`select`] and [`select_timeout`] will retry all of
+    /// them `spin` more times, spinning in between, before blocking the current thread. This burns
+    /// a little more CPU time, but lowers latency when operations are expected to become ready
+    /// very soon.
+    ///
+    /// The timeout of [`select_timeout`] is checked after every retry, so it is respected no matter
+    /// how large `spin` is.
+    ///
+    /// [`select`]: struct.Select.html#method.select
+    /// [`select_timeout`]: struct.Select.html#method.select_timeout
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s, r) = unbounded();
+    /// thread::spawn(move || s.send(1).unwrap());
+    ///
+    /// let mut sel = Select::with_spin(100);
+    /// let oper1 = sel.recv(&r);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert_eq!(oper.recv(&r), Ok(1));
+    /// ```
+    pub fn with_spin(spin: usize) -> Select<'a> {
+        Select::with_options(Vec::with_capacity(4), Spin::Count(spin), false)
+    }
+
+    /// Creates an empty list of channel operations that spins for up to `duration` before
+    /// blocking.
+    ///
+    /// This is like [`with_spin`], except the limit is the time spent spinning rather than the
+    /// number of retries. When none of the operations are ready, [`select`] and
+    /// [`select_timeout`] keep retrying them until `duration` has elapsed, and only then block the
+    /// current thread.
+    ///
+    /// If operations are known to become ready within a short window, this avoids the cost of
+    /// parking and unparking the thread. The price is that the current thread keeps a CPU core
+    /// busy for the whole window whenever the operations take longer than that, so `duration`
+    /// should be kept short.
+    ///
+    /// The timeout of [`select_timeout`] takes precedence: the spinning stops once the timeout
+    /// has elapsed, even if `duration` hasn't.
+    ///
+    /// [`with_spin`]: struct.Select.html#method.with_spin
+    /// [`select`]: struct.Select.html#method.select
+    /// [`select_timeout`]: struct.Select.html#method.select_timeout
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s, r) = unbounded();
+    /// thread::spawn(move || s.send(1).unwrap());
+    ///
+    /// let mut sel = Select::with_spin_duration(Duration::from_millis(1));
+    /// let oper1 = sel.recv(&r);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert_eq!(oper.recv(&r), Ok(1));
+    /// ```
+    pub fn with_spin_duration(duration: Duration) -> Select<'a> {
+        Select::with_options(Vec::with_capacity(4), Spin::For(duration), false)
+    }
+
+    /// Creates a list of channel operations with the given handles and options, and everything
+    /// else in its initial state.
+    ///
+    /// All constructors go through this function, so that a new field only needs to be
+    /// initialized in one place.
+    fn with_options(handles: Vec<HandleEntry<'a>>, spin: Spin, biased: bool) -> Select<'a> {
         Select {
-            handles: Vec::with_capacity(4),
+            handles,
             next_index: 0,
+            spin,
+            directions: Vec::new(),
+            prefer: None,
+            biased,
+            phase: Phase::Try,
+            waker: None,
+            subscription: None,
         }
     }
 
@@ -606,6 +1086,13 @@ impl<'a> Select<'a> {
     ///
     /// Returns the index of the added operation.
     ///
+    /// The message is not passed here. Selecting an operation never touches a message, so the
+    /// message is moved only when the selected operation is completed with
+    /// [`SelectedOperation::send`], and a message that was not sent can be kept and offered again
+    /// in the next selection without cloning it.
+    ///
+    /// [`SelectedOperation::send`]: struct.SelectedOperation.html#method.send
+    ///
     /// # Examples
     ///
     /// ```
@@ -617,11 +1104,17 @@ impl<'a> Select<'a> {
     /// let mut sel = Select::new();
     /// let index = sel.send(&s);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index space is exhausted, i.e. after `usize::MAX` operations have been added
+    /// since the `Select` was created or last cleared.
     pub fn send<T>(&mut self, s: &'a Sender<T>) -> usize {
-        let i = self.next_index;
+        let i = self.take_index();
         let ptr = s as *const Sender<_> as *const u8;
+        self.reset_phase();
         self.handles.push((s, i, ptr));
-        self.next_index += 1;
+        self.directions.push(Direction::Send);
         i
     }
 
@@ -640,14 +1133,57 @@ impl<'a> Select<'a> {
     /// let mut sel = Select::new();
     /// let index = sel.recv(&r);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index space is exhausted, i.e. after `usize::MAX` operations have been added
+    /// since the `Select` was created or last cleared.
     pub fn recv<T>(&mut self, r: &'a Receiver<T>) -> usize {
-        let i = self.next_index;
+        let i = self.take_index();
         let ptr = r as *const Receiver<_> as *const u8;
+        self.reset_phase();
         self.handles.push((r, i, ptr));
-        self.next_index += 1;
+        self.directions.push(Direction::Recv);
         i
     }
 
+    /// Adds a receive operation that also completes when its channel is closed.
+    ///
+    /// Returns the index of the added operation.
+    ///
+    /// The operation becomes ready as soon as a message arrives or this particular channel gets
+    /// disconnected, even while other channels in the same select are still connected. Complete
+    /// it with [`SelectedOperation::recv_or_closed`] to find out which of the two happened.
+    ///
+    /// This is the same operation as [`recv`], with the closed event spelled out in the result.
+    ///
+    /// [`SelectedOperation::recv_or_closed`]: struct.SelectedOperation.html#method.recv_or_closed
+    /// [`recv`]: struct.Select.html#method.recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, RecvOrClosed, Select};
+    ///
+    /// let (s1, r1) = unbounded::<i32>();
+    /// let (s2, r2) = unbounded::<i32>();
+    ///
+    /// // The first upstream shuts down while the second one keeps going.
+    /// drop(s1);
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv_or_closed(&r1);
+    /// let oper2 = sel.recv_or_closed(&r2);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert_eq!(oper.recv_or_closed(&r1), RecvOrClosed::Closed);
+    /// # drop((s2, oper2));
+    /// ```
+    pub fn recv_or_closed<T>(&mut self, r: &'a Receiver<T>) -> usize {
+        self.recv(r)
+    }
+
     /// Clears the list of operations.
     ///
     /// Newly added operations will be assigned indices as usual, starting with 0.
@@ -670,10 +1206,88 @@ impl<'a> Select<'a> {
     /// assert_eq!(index, 0);
     /// ```
     pub fn clear(&mut self) {
+        self.reset_phase();
         self.handles.clear();
+        self.directions.clear();
         self.next_index = 0;
     }
 
+    /// Prefers send operations over receive operations when both are ready.
+    ///
+    /// This only breaks ties in [`try_select`], [`select`], and [`select_timeout`]: if a send
+    /// operation and a receive operation are ready at the same time, a send operation is selected.
+    /// If only operations in one direction are ready, one of them is selected regardless, and
+    /// ties among operations in the same direction are still broken randomly. A proxy can use it
+    /// to drain outbound messages before accepting new inbound ones.
+    ///
+    /// The preference replaces one set by [`prefer_recv`].
+    ///
+    /// [`try_select`]: struct.Select.html#method.try_select
+    /// [`select`]: struct.Select.html#method.select
+    /// [`select_timeout`]: struct.Select.html#method.select_timeout
+    /// [`prefer_recv`]: struct.Select.html#method.prefer_recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// s1.send(10).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&r1);
+    /// let oper2 = sel.send(&s2);
+    /// sel.prefer_send();
+    ///
+    /// // Both operations are ready, so the send operation is selected.
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper2);
+    /// oper.send(&s2, 20).unwrap();
+    /// assert_eq!(r2.recv(), Ok(20));
+    /// ```
+    pub fn prefer_send(&mut self) {
+        self.prefer = Some(Direction::Send);
+    }
+
+    /// Prefers receive operations over send operations when both are ready.
+    ///
+    /// This only breaks ties in [`try_select`], [`select`], and [`select_timeout`]: if a send
+    /// operation and a receive operation are ready at the same time, a receive operation is
+    /// selected. If only operations in one direction are ready, one of them is selected regardless,
+    /// and ties among operations in the same direction are still broken randomly.
+    ///
+    /// The preference replaces one set by [`prefer_send`].
+    ///
+    /// [`try_select`]: struct.Select.html#method.try_select
+    /// [`select`]: struct.Select.html#method.select
+    /// [`select_timeout`]: struct.Select.html#method.select_timeout
+    /// [`prefer_send`]: struct.Select.html#method.prefer_send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, _r2) = unbounded::<i32>();
+    /// s1.send(10).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// sel.send(&s2);
+    /// sel.prefer_recv();
+    ///
+    /// // Both operations are ready, so the receive operation is selected.
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert_eq!(oper.recv(&r1), Ok(10));
+    /// ```
+    pub fn prefer_recv(&mut self) {
+        self.prefer = Some(Direction::Recv);
+    }
+
     /// Disables an operation, preventing it from being selected.
     ///
     /// This is useful when an operation is selected because the channel got disconnected and we
@@ -720,7 +1334,14 @@ impl<'a> Select<'a> {
             .unwrap()
             .0;
 
-        self.handles.swap_remove(i);
+        self.reset_phase();
+
+        // A biased `Select` must keep the remaining operations in the order they were added.
+        if self.biased {
+            self.handles.remove(i);
+        } else {
+            self.handles.swap_remove(i);
+        }
     }
 
     /// Attempts to select one of the operations without blocking.
@@ -732,6 +1353,9 @@ impl<'a> Select<'a> {
     /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
     /// even when it will simply return an error because the channel is disconnected.
     ///
+    /// On a zero-capacity channel, a receive operation is ready only while a send operation is
+    /// blocked on the other side of the channel, and vice versa.
+    ///
     /// The selected operation must be completed with [`SelectedOperation::send`]
     /// or [`SelectedOperation::recv`].
     ///
@@ -766,7 +1390,15 @@ impl<'a> Select<'a> {
     /// }
     /// ```
     pub fn try_select(&mut self) -> Result<SelectedOperation<'a>, TrySelectError> {
-        try_select(&mut self.handles)
+        match self.run(Timeout::Now) {
+            None => Err(TrySelectError),
+            Some((token, index, ptr)) => Ok(SelectedOperation {
+                token,
+                index,
+                ptr,
+                _marker: PhantomData,
+            }),
+        }
     }
 
     /// Blocks until one of the operations becomes ready and selects it.
@@ -777,6 +1409,11 @@ impl<'a> Select<'a> {
     /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
     /// even when it will simply return an error because the channel is disconnected.
     ///
+    /// In particular, if every channel is already disconnected when this method is called, an
+    /// operation is selected by the first sweep over the operations and the current thread never
+    /// blocks. Completing that operation returns an error, which tells this case apart from one
+    /// where a message was sent or received.
+    ///
     /// The selected operation must be completed with [`SelectedOperation::send`]
     /// or [`SelectedOperation::recv`].
     ///
@@ -816,7 +1453,17 @@ impl<'a> Select<'a> {
     /// }
     /// ```
     pub fn select(&mut self) -> SelectedOperation<'a> {
-        select(&mut self.handles)
+        if self.handles.is_empty() {
+            panic!("no operations have been added to `Select`");
+        }
+
+        let (token, index, ptr) = self.run(Timeout::Never).unwrap();
+        SelectedOperation {
+            token,
+            index,
+            ptr,
+            _marker: PhantomData,
+        }
     }
 
     /// Blocks for a limited time until one of the operations becomes ready and selects it.
@@ -831,6 +1478,12 @@ impl<'a> Select<'a> {
     /// The selected operation must be completed with [`SelectedOperation::send`]
     /// or [`SelectedOperation::recv`].
     ///
+    /// If another thread pairs up with one of the blocked operations just as the timeout elapses,
+    /// the operation is selected rather than timing out. Either the other thread pairs up first
+    /// and the operation is returned, or the timeout wins and the other thread never sees the
+    /// operation, so a message handed off through a zero-capacity channel is never lost or
+    /// received twice.
+    ///
     /// [`SelectedOperation::send`]: struct.SelectedOperation.html#method.send
     /// [`SelectedOperation::recv`]: struct.SelectedOperation.html#method.recv
     ///
@@ -869,7 +1522,17 @@ impl<'a> Select<'a> {
         &mut self,
         timeout: Duration,
     ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
-        select_timeout(&mut self.handles, timeout)
+        let timeout = Timeout::At(Instant::now() + timeout);
+
+        match self.run(timeout) {
+            None => Err(SelectTimeoutError),
+            Some((token, index, ptr)) => Ok(SelectedOperation {
+                token,
+                index,
+                ptr,
+                _marker: PhantomData,
+            }),
+        }
     }
 
     /// Attempts to find a ready operation without blocking.
@@ -881,6 +1544,9 @@ impl<'a> Select<'a> {
     /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
     /// even when it will simply return an error because the channel is disconnected.
     ///
+    /// On a zero-capacity channel, a receive operation is ready only while a send operation is
+    /// blocked on the other side of the channel, and vice versa.
+    ///
     /// Note that this method might return with success spuriously, so it's a good idea to always
     /// double check if the operation is really ready.
     ///
@@ -909,7 +1575,7 @@ impl<'a> Select<'a> {
     /// }
     /// ```
     pub fn try_ready(&mut self) -> Result<usize, TryReadyError> {
-        match run_ready(&mut self.handles, Timeout::Now) {
+        match run_ready(&mut self.handles, Timeout::Now, self.biased) {
             None => Err(TryReadyError),
             Some(index) => Ok(index),
         }
@@ -962,7 +1628,7 @@ impl<'a> Select<'a> {
             panic!("no operations have been added to `Select`");
         }
 
-        run_ready(&mut self.handles, Timeout::Never).unwrap()
+        run_ready(&mut self.handles, Timeout::Never, self.biased).unwrap()
     }
 
     /// Blocks for a limited time until one of the operations becomes ready.
@@ -1008,19 +1674,262 @@ impl<'a> Select<'a> {
     pub fn ready_timeout(&mut self, timeout: Duration) -> Result<usize, ReadyTimeoutError> {
         let timeout = Timeout::At(Instant::now() + timeout);
 
-        match run_ready(&mut self.handles, timeout) {
+        match run_ready(&mut self.handles, timeout, self.biased) {
             None => Err(ReadyTimeoutError),
             Some(index) => Ok(index),
         }
     }
+
+    /// Sets the function called when an operation subscribed to by [`poll_phase`] may have become
+    /// ready.
+    ///
+    /// The waker runs on the thread that made the channel ready, possibly while a lock inside the
+    /// channel is held. It must not use any of the channels and should only schedule the task that
+    /// drives this `Select`, e.g. by pushing it onto a run queue or unparking a thread.
+    ///
+    /// The new waker is used from the next subscription on.
+    ///
+    /// [`poll_phase`]: struct.Select.html#method.poll_phase
+    pub fn set_waker<F>(&mut self, waker: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.waker = Some(Arc::new(waker));
+    }
+
+    /// Advances step-by-step selection by one step and returns the new phase.
+    ///
+    /// This is a manual alternative to [`ready`] for cooperative schedulers that must not block
+    /// the thread. Instead of parking the current thread, the blocking protocol is split into
+    /// steps, and the caller decides what to do in between:
+    ///
+    /// * In the [`Try`] phase, the operations are checked for readiness once. If one of them is
+    ///   ready, the new phase is [`Done`], otherwise it is [`Subscribing`].
+    /// * In the [`Subscribing`] phase, the operations are subscribed to, so that the [waker] is
+    ///   called when any of them may have become ready. If one became ready while subscribing, the
+    ///   new phase is [`Try`], otherwise it is [`Parked`].
+    /// * In the [`Parked`] phase, the phase stays [`Parked`] until a channel calls the waker, after
+    ///   which the subscriptions are dropped and the new phase is [`Try`].
+    /// * After [`Done`], the next step starts over with [`Try`].
+    ///
+    /// In manual mode, the caller is responsible for parking and waking: once the phase is
+    /// [`Parked`], there is no point in stepping again before the waker has been called. Wakeups
+    /// may be spurious, so an operation may turn out not to be ready in the following [`Try`]
+    /// phase. Without a waker, the [`Parked`] phase can still be polled.
+    ///
+    /// Just like with [`ready`], a [`Done`] operation is not selected, so it should be completed
+    /// with a non-blocking method like [`Receiver::try_recv`], and another thread may win the race
+    /// for it. Adding, disabling, or clearing operations resets the phase to [`Try`] and drops any
+    /// subscriptions. Dropping the `Select` cancels the subscriptions too, so the waker is not
+    /// called after that. The other methods of `Select` don't use or change the phase.
+    ///
+    /// [`ready`]: struct.Select.html#method.ready
+    /// [waker]: struct.Select.html#method.set_waker
+    /// [`Try`]: enum.Phase.html#variant.Try
+    /// [`Subscribing`]: enum.Phase.html#variant.Subscribing
+    /// [`Parked`]: enum.Phase.html#variant.Parked
+    /// [`Done`]: enum.Phase.html#variant.Done
+    /// [`Receiver::try_recv`]: struct.Receiver.html#method.try_recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    /// use crossbeam_channel::{unbounded, Phase, Select};
+    ///
+    /// let (s, r) = unbounded();
+    /// let woken = Arc::new(AtomicBool::new(false));
+    ///
+    /// let mut sel = Select::new();
+    /// let oper = sel.recv(&r);
+    /// let w = woken.clone();
+    /// sel.set_waker(move || w.store(true, Ordering::SeqCst));
+    ///
+    /// assert_eq!(sel.poll_phase(), Phase::Subscribing);
+    /// assert_eq!(sel.poll_phase(), Phase::Parked);
+    ///
+    /// // The scheduler runs other tasks until the waker is called.
+    /// assert_eq!(sel.poll_phase(), Phase::Parked);
+    /// s.send(1).unwrap();
+    /// assert!(woken.load(Ordering::SeqCst));
+    ///
+    /// assert_eq!(sel.poll_phase(), Phase::Try);
+    /// assert_eq!(sel.poll_phase(), Phase::Done(oper));
+    /// assert_eq!(r.try_recv(), Ok(1));
+    /// ```
+    pub fn poll_phase(&mut self) -> Phase {
+        self.phase = match self.phase {
+            Phase::Try | Phase::Done(_) => {
+                // Shuffle the operations for fairness.
+                if !self.biased {
+                    utils::shuffle(&mut self.handles);
+                }
+
+                match self
+                    .handles
+                    .iter()
+                    .find(|&&(handle, _, _)| handle.is_ready())
+                {
+                    Some(&(_, i, _)) => Phase::Done(i),
+                    None => Phase::Subscribing,
+                }
+            }
+            Phase::Subscribing => {
+                let waker = self.waker.clone();
+                let cx = Context::with_callback(move || {
+                    if let Some(waker) = waker {
+                        waker();
+                    }
+                });
+                let oper = Operation::from_context(&cx);
+
+                // Subscribe to all operations, unless one of them is already ready.
+                let mut registered_count = 0;
+                let mut is_ready = false;
+                for &(handle, _, _) in &self.handles {
+                    registered_count += 1;
+                    if handle.watch(oper, &cx) {
+                        is_ready = true;
+                        break;
+                    }
+                }
+
+                if is_ready {
+                    for &(handle, _, _) in self.handles.iter().take(registered_count) {
+                        handle.unwatch(oper);
+                    }
+                    Phase::Try
+                } else {
+                    self.subscription = Some(Subscription { cx });
+                    Phase::Parked
+                }
+            }
+            Phase::Parked => {
+                let notified = match self.subscription {
+                    Some(ref sub) => sub.cx.selected() != Selected::Waiting,
+                    None => true,
+                };
+
+                if notified {
+                    self.reset_phase();
+                    Phase::Try
+                } else {
+                    Phase::Parked
+                }
+            }
+        };
+        self.phase
+    }
+
+    /// Reserves the index for a new operation.
+    ///
+    /// Indices are never reused until the `Select` is cleared, even for disabled operations, so
+    /// adding and disabling operations in a loop could otherwise silently wrap around.
+    fn take_index(&mut self) -> usize {
+        let i = self.next_index;
+        self.next_index = i
+            .checked_add(1)
+            .expect("too many operations added to `Select`");
+        i
+    }
+
+    /// Drops the subscriptions made by `poll_phase`, if any, and resets the phase to `Try`.
+    fn reset_phase(&mut self) {
+        if let Some(sub) = self.subscription.take() {
+            let oper = Operation::from_context(&sub.cx);
+            for &(handle, _, _) in &self.handles {
+                handle.unwatch(oper);
+            }
+        }
+        self.phase = Phase::Try;
+    }
+
+    /// Blocks until `k` of the given receivers have each produced a message, and returns them.
+    ///
+    /// This is useful for quorum-style requests, where the same request is sent to several peers
+    /// and the caller proceeds once enough of them have responded.
+    ///
+    /// At most one message is received from each receiver. Every returned pair holds the position
+    /// of the receiver in `receivers` and the message received from it, in the order the messages
+    /// were received. If fewer than `k` receivers can produce a message because the others are
+    /// disconnected, all the messages that could be received are returned.
+    ///
+    /// The list of operations is cleared before and after the call, so any operations added
+    /// earlier are discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// let (s3, r3) = unbounded();
+    ///
+    /// thread::spawn(move || s2.send("b").unwrap());
+    /// thread::spawn(move || s3.send("c").unwrap());
+    /// # drop(s1);
+    ///
+    /// let mut sel = Select::new();
+    /// let mut responses = sel.recv_until(&[&r1, &r2, &r3], 2);
+    /// responses.sort();
+    /// assert_eq!(responses, [(1, "b"), (2, "c")]);
+    /// ```
+    pub fn recv_until<T>(&mut self, receivers: &[&'a Receiver<T>], k: usize) -> Vec<(usize, T)> {
+        self.clear();
+        for r in receivers {
+            self.recv(r);
+        }
+
+        let mut results = Vec::with_capacity(cmp::min(k, receivers.len()));
+        let mut remaining = receivers.len();
+
+        while results.len() < k && remaining > 0 {
+            let oper = self.select();
+            let index = oper.index();
+
+            // A receiver is done once it produces a message or gets disconnected.
+            if let Ok(msg) = oper.recv(receivers[index]) {
+                results.push((index, msg));
+            }
+            self.disable(index);
+            remaining -= 1;
+        }
+
+        self.clear();
+        results
+    }
+
+    /// Runs the selection, trying operations in the preferred direction first.
+    fn run(&mut self, timeout: Timeout) -> Option<(Token, usize, *const u8)> {
+        match self.prefer {
+            None if self.biased => run_select_in_order(&mut self.handles, timeout, self.spin),
+            None => run_select(&mut self.handles, timeout, self.spin),
+            Some(dir) => {
+                // Shuffle the operations for fairness within each direction, and then move the
+                // preferred ones to the front. Sorting is stable, so the shuffled order is kept.
+                if !self.biased {
+                    utils::shuffle(&mut self.handles);
+                }
+                let directions = &self.directions;
+                self.handles.sort_by_key(|&(_, i, _)| directions[i] != dir);
+                run_select_in_order(&mut self.handles, timeout, self.spin)
+            }
+        }
+    }
 }
 
 impl<'a> Clone for Select<'a> {
     fn clone(&self) -> Select<'a> {
-        Select {
-            handles: self.handles.clone(),
-            next_index: self.next_index,
-        }
+        // The clone starts over in the `Try` phase, without any subscriptions.
+        let mut sel = Select::with_options(self.handles.clone(), self.spin, self.biased);
+        sel.next_index = self.next_index;
+        sel.directions = self.directions.clone();
+        sel.prefer = self.prefer;
+        sel.waker = self.waker.clone();
+        sel
     }
 }
 
@@ -1096,6 +2005,9 @@ impl<'a> SelectedOperation<'a> {
     /// The passed [`Sender`] reference must be the same one that was used in [`Select::send`]
     /// when the operation was added.
     ///
+    /// This is the only point where the message is consumed. If the channel is disconnected, the
+    /// very same message is returned inside the error, without being cloned or dropped.
+    ///
     /// # Panics
     ///
     /// Panics if an incorrect [`Sender`] reference is passed.
@@ -1164,6 +2076,53 @@ impl<'a> SelectedOperation<'a> {
         mem::forget(self);
         res.map_err(|_| RecvError)
     }
+
+    /// Completes the receive operation, reporting whether a message arrived or the channel closed.
+    ///
+    /// This method behaves just like [`recv`], except a disconnected channel is reported as
+    /// [`RecvOrClosed::Closed`] rather than as an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect [`Receiver`] reference is passed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, RecvOrClosed, Select};
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv_or_closed(&r);
+    /// assert_eq!(sel.select().recv_or_closed(&r), RecvOrClosed::Message(1));
+    ///
+    /// drop(s);
+    /// assert_eq!(sel.select().recv_or_closed(&r), RecvOrClosed::Closed);
+    /// ```
+    ///
+    /// [`recv`]: struct.SelectedOperation.html#method.recv
+    /// [`RecvOrClosed::Closed`]: enum.RecvOrClosed.html#variant.Closed
+    /// [`Receiver`]: struct.Receiver.html
+    pub fn recv_or_closed<T>(self, r: &Receiver<T>) -> RecvOrClosed<T> {
+        match self.recv(r) {
+            Ok(msg) => RecvOrClosed::Message(msg),
+            Err(RecvError) => RecvOrClosed::Closed,
+        }
+    }
+}
+
+/// The outcome of a receive operation added by [`Select::recv_or_closed`].
+///
+/// [`Select::recv_or_closed`]: struct.Select.html#method.recv_or_closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecvOrClosed<T> {
+    /// A message was received.
+    Message(T),
+
+    /// The channel is empty and disconnected.
+    Closed,
 }
 
 impl<'a> fmt::Debug for SelectedOperation<'a> {
@@ -0,0 +1,214 @@
+//! A builder that collects select arms as closures and runs exactly one of them.
+
+use std::fmt;
+
+use channel::{Receiver, Sender};
+use err::{RecvError, SendError};
+use select::{Select, SelectedOperation};
+
+/// Builds a selection out of operations with their handlers, and runs it once.
+///
+/// This is a safe front-end to [`Select`] that resembles the [`select!`] macro. Each send or
+/// receive operation is added together with a closure that handles its result. When the
+/// selection is run with [`wait`], exactly one operation is executed and only its closure is
+/// called. Because the selected operation is always completed by the builder, it is impossible to
+/// forget to complete it, and all registrations with channels are cleaned up before the closure
+/// is called.
+///
+/// If a [`default`] handler is set, [`wait`] doesn't block: it calls the default handler if none
+/// of the operations are ready. Otherwise, [`wait`] blocks until one of the operations becomes
+/// ready. Among several ready operations, one is chosen randomly.
+///
+/// [`Select`]: struct.Select.html
+/// [`select!`]: macro.select.html
+/// [`wait`]: struct.SelectBuilder.html#method.wait
+/// [`default`]: struct.SelectBuilder.html#method.default
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{unbounded, SelectBuilder};
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+///
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(100));
+///     s1.send(10).unwrap();
+/// });
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(500));
+///     s2.send("hello").unwrap();
+/// });
+///
+/// let got = SelectBuilder::new()
+///     .recv(&r1, |msg| format!("r1: {:?}", msg))
+///     .recv(&r2, |msg| format!("r2: {:?}", msg))
+///     .wait();
+///
+/// assert_eq!(got, "r1: Ok(10)");
+/// ```
+pub struct SelectBuilder<'a, R> {
+    /// The list of operations.
+    sel: Select<'a>,
+
+    /// Handlers that complete the operations, in the order of operation indices.
+    arms: Vec<Box<FnMut(SelectedOperation<'a>) -> R + 'a>>,
+
+    /// The handler called when no operation is ready.
+    default: Option<Box<FnMut() -> R + 'a>>,
+}
+
+impl<'a, R> SelectBuilder<'a, R> {
+    /// Creates a builder with no operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::SelectBuilder;
+    ///
+    /// let res = SelectBuilder::new().default(|| "nothing to do").wait();
+    /// assert_eq!(res, "nothing to do");
+    /// ```
+    pub fn new() -> SelectBuilder<'a, R> {
+        SelectBuilder {
+            sel: Select::new(),
+            arms: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// Adds a receive operation with a handler.
+    ///
+    /// If the operation is selected, `f` is called with the result of the receive operation,
+    /// which is an error if the channel is empty and disconnected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, SelectBuilder};
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(7).unwrap();
+    ///
+    /// let msg = SelectBuilder::new().recv(&r, |msg| msg.unwrap()).wait();
+    /// assert_eq!(msg, 7);
+    /// ```
+    pub fn recv<T, F>(mut self, r: &'a Receiver<T>, f: F) -> SelectBuilder<'a, R>
+    where
+        F: FnOnce(Result<T, RecvError>) -> R + 'a,
+    {
+        let index = self.sel.recv(r);
+        debug_assert_eq!(index, self.arms.len());
+
+        let mut f = Some(f);
+        self.arms.push(Box::new(move |oper: SelectedOperation<'a>| {
+            let f = f.take().unwrap();
+            f(oper.recv(r))
+        }));
+        self
+    }
+
+    /// Adds a send operation with a handler.
+    ///
+    /// If the operation is selected, `msg` is sent and `f` is called with the result of the send
+    /// operation, which contains the message if the channel is disconnected. If the operation is
+    /// not selected, `msg` is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, SelectBuilder};
+    ///
+    /// let (s, r) = bounded(1);
+    ///
+    /// let sent = SelectBuilder::new().send(&s, 5, |res| res.is_ok()).wait();
+    /// assert!(sent);
+    /// assert_eq!(r.recv(), Ok(5));
+    /// ```
+    pub fn send<T, F>(mut self, s: &'a Sender<T>, msg: T, f: F) -> SelectBuilder<'a, R>
+    where
+        T: 'a,
+        F: FnOnce(Result<(), SendError<T>>) -> R + 'a,
+    {
+        let index = self.sel.send(s);
+        debug_assert_eq!(index, self.arms.len());
+
+        let mut f = Some(f);
+        let mut msg = Some(msg);
+        self.arms.push(Box::new(move |oper: SelectedOperation<'a>| {
+            let f = f.take().unwrap();
+            f(oper.send(s, msg.take().unwrap()))
+        }));
+        self
+    }
+
+    /// Sets a handler that is called if none of the operations are ready.
+    ///
+    /// With a default handler, [`wait`] never blocks. Setting it again replaces the previous
+    /// handler.
+    ///
+    /// [`wait`]: struct.SelectBuilder.html#method.wait
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, SelectBuilder};
+    ///
+    /// let (_s, r) = unbounded::<i32>();
+    ///
+    /// let res = SelectBuilder::new()
+    ///     .recv(&r, |_| "message")
+    ///     .default(|| "empty")
+    ///     .wait();
+    /// assert_eq!(res, "empty");
+    /// ```
+    pub fn default<F>(mut self, f: F) -> SelectBuilder<'a, R>
+    where
+        F: FnOnce() -> R + 'a,
+    {
+        let mut f = Some(f);
+        self.default = Some(Box::new(move || f.take().unwrap()()));
+        self
+    }
+
+    /// Runs the selection and returns the result of the handler that was called.
+    ///
+    /// Exactly one handler is called: that of the selected operation, or the default handler if
+    /// it is set and no operation is ready. Without a default handler, this method blocks until
+    /// an operation becomes ready.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no operations have been added and there is no default handler.
+    pub fn wait(mut self) -> R {
+        match self.default.take() {
+            Some(mut default) => match self.sel.try_select() {
+                Ok(oper) => {
+                    let index = oper.index();
+                    (self.arms[index])(oper)
+                }
+                Err(_) => default(),
+            },
+            None => {
+                let oper = self.sel.select();
+                let index = oper.index();
+                (self.arms[index])(oper)
+            }
+        }
+    }
+}
+
+impl<'a, R> Default for SelectBuilder<'a, R> {
+    fn default() -> SelectBuilder<'a, R> {
+        SelectBuilder::new()
+    }
+}
+
+impl<'a, R> fmt::Debug for SelectBuilder<'a, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("SelectBuilder { .. }")
+    }
+}
@@ -0,0 +1,266 @@
+//! Selection over a fixed number of receivers with a typed result.
+//!
+//! Handles are kept in an array on the stack and passed straight to the selection machinery used
+//! by `select!`, so these functions never allocate.
+
+use std::time::Duration;
+
+use channel::Receiver;
+use err::{RecvError, SelectTimeoutError, TrySelectError};
+use select::{self, HandleEntry, SelectedOperation};
+
+/// The result of [`select2`], [`try_select2`], or [`select2_timeout`].
+///
+/// Each variant holds the result of receiving from the corresponding receiver.
+///
+/// [`select2`]: fn.select2.html
+/// [`try_select2`]: fn.try_select2.html
+/// [`select2_timeout`]: fn.select2_timeout.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Either<A, B> {
+    /// The first receiver was selected.
+    First(A),
+
+    /// The second receiver was selected.
+    Second(B),
+}
+
+/// The result of [`select3`], [`try_select3`], or [`select3_timeout`].
+///
+/// Each variant holds the result of receiving from the corresponding receiver.
+///
+/// [`select3`]: fn.select3.html
+/// [`try_select3`]: fn.try_select3.html
+/// [`select3_timeout`]: fn.select3_timeout.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Either3<A, B, C> {
+    /// The first receiver was selected.
+    First(A),
+
+    /// The second receiver was selected.
+    Second(B),
+
+    /// The third receiver was selected.
+    Third(C),
+}
+
+/// The result of [`select4`], [`try_select4`], or [`select4_timeout`].
+///
+/// Each variant holds the result of receiving from the corresponding receiver.
+///
+/// [`select4`]: fn.select4.html
+/// [`try_select4`]: fn.try_select4.html
+/// [`select4_timeout`]: fn.select4_timeout.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Either4<A, B, C, D> {
+    /// The first receiver was selected.
+    First(A),
+
+    /// The second receiver was selected.
+    Second(B),
+
+    /// The third receiver was selected.
+    Third(C),
+
+    /// The fourth receiver was selected.
+    Fourth(D),
+}
+
+/// Returns the selection handle for `r` at position `index`.
+fn handle<T>(r: &Receiver<T>, index: usize) -> HandleEntry<'_> {
+    (r, index, r as *const Receiver<T> as *const u8)
+}
+
+/// Generates the blocking, non-blocking, and timeout variants for one arity.
+macro_rules! fixed_select {
+    (
+        $either:ident,
+        $(#[$select_meta:meta])* fn $select:ident,
+        $(#[$try_meta:meta])* fn $try_select:ident,
+        $(#[$timeout_meta:meta])* fn $select_timeout:ident,
+        $($r:ident: $t:ident => $variant:ident = $index:expr,)+
+    ) => {
+        $(#[$select_meta])*
+        pub fn $select<$($t),+>(
+            $($r: &Receiver<$t>),+
+        ) -> $either<$(Result<$t, RecvError>),+> {
+            let mut handles = [$(handle($r, $index)),+];
+            let oper = select::select(&mut handles, false);
+            complete!(oper, $either, $($r => $variant = $index,)+)
+        }
+
+        $(#[$try_meta])*
+        pub fn $try_select<$($t),+>(
+            $($r: &Receiver<$t>),+
+        ) -> Result<$either<$(Result<$t, RecvError>),+>, TrySelectError> {
+            let mut handles = [$(handle($r, $index)),+];
+            let oper = select::try_select(&mut handles, false)?;
+            Ok(complete!(oper, $either, $($r => $variant = $index,)+))
+        }
+
+        $(#[$timeout_meta])*
+        pub fn $select_timeout<$($t),+>(
+            $($r: &Receiver<$t>,)+
+            timeout: Duration,
+        ) -> Result<$either<$(Result<$t, RecvError>),+>, SelectTimeoutError> {
+            let mut handles = [$(handle($r, $index)),+];
+            let oper = select::select_timeout(&mut handles, timeout, false)?;
+            Ok(complete!(oper, $either, $($r => $variant = $index,)+))
+        }
+    };
+}
+
+/// Completes the selected receive operation and wraps its result into the matching variant.
+macro_rules! complete {
+    ($oper:ident, $either:ident, $($r:ident => $variant:ident = $index:expr,)+) => {{
+        let oper: SelectedOperation = $oper;
+        match oper.index() {
+            $(i if i == $index => $either::$variant(oper.recv($r)),)+
+            _ => unreachable!(),
+        }
+    }};
+}
+
+fixed_select! {
+    Either,
+
+    /// Blocks until one of two receivers becomes ready and receives from it.
+    ///
+    /// This is a shorthand for a [`Select`] over exactly two receivers. The handles are kept on
+    /// the stack, so no memory is allocated, and the result tells which receiver was selected
+    /// along with the result of receiving from it. If both receivers are ready, a random one is
+    /// selected.
+    ///
+    /// A receive operation is ready even when it will simply return an error because the channel
+    /// is disconnected.
+    ///
+    /// [`Select`]: struct.Select.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{select2, unbounded, Either};
+    ///
+    /// let (s1, r1) = unbounded::<i32>();
+    /// let (s2, r2) = unbounded::<&str>();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     s2.send("hello").unwrap();
+    /// });
+    ///
+    /// match select2(&r1, &r2) {
+    ///     Either::First(n) => panic!("unexpected {:?}", n),
+    ///     Either::Second(msg) => assert_eq!(msg, Ok("hello")),
+    /// }
+    /// # drop(s1);
+    /// ```
+    fn select2,
+
+    /// Attempts to receive from one of two receivers without blocking.
+    ///
+    /// This is just like [`select2`], except it returns an error if neither receiver is ready.
+    ///
+    /// [`select2`]: fn.select2.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{try_select2, unbounded, Either};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded::<()>();
+    ///
+    /// assert!(try_select2(&r1, &r2).is_err());
+    ///
+    /// s1.send(1).unwrap();
+    /// assert_eq!(try_select2(&r1, &r2), Ok(Either::First(Ok(1))));
+    /// # drop(s2);
+    /// ```
+    fn try_select2,
+
+    /// Blocks for a limited time until one of two receivers becomes ready and receives from it.
+    ///
+    /// This is just like [`select2`], except it returns an error if neither receiver becomes
+    /// ready for the specified duration.
+    ///
+    /// [`select2`]: fn.select2.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{select2_timeout, unbounded};
+    ///
+    /// let (s1, r1) = unbounded::<i32>();
+    /// let (s2, r2) = unbounded::<i32>();
+    ///
+    /// assert!(select2_timeout(&r1, &r2, Duration::from_millis(100)).is_err());
+    /// # drop((s1, s2));
+    /// ```
+    fn select2_timeout,
+
+    r1: T1 => First = 0,
+    r2: T2 => Second = 1,
+}
+
+fixed_select! {
+    Either3,
+
+    /// Blocks until one of three receivers becomes ready and receives from it.
+    ///
+    /// This is just like [`select2`], except it selects over three receivers.
+    ///
+    /// [`select2`]: fn.select2.html
+    fn select3,
+
+    /// Attempts to receive from one of three receivers without blocking.
+    ///
+    /// This is just like [`try_select2`], except it selects over three receivers.
+    ///
+    /// [`try_select2`]: fn.try_select2.html
+    fn try_select3,
+
+    /// Blocks for a limited time until one of three receivers becomes ready and receives from it.
+    ///
+    /// This is just like [`select2_timeout`], except it selects over three receivers.
+    ///
+    /// [`select2_timeout`]: fn.select2_timeout.html
+    fn select3_timeout,
+
+    r1: T1 => First = 0,
+    r2: T2 => Second = 1,
+    r3: T3 => Third = 2,
+}
+
+fixed_select! {
+    Either4,
+
+    /// Blocks until one of four receivers becomes ready and receives from it.
+    ///
+    /// This is just like [`select2`], except it selects over four receivers.
+    ///
+    /// [`select2`]: fn.select2.html
+    fn select4,
+
+    /// Attempts to receive from one of four receivers without blocking.
+    ///
+    /// This is just like [`try_select2`], except it selects over four receivers.
+    ///
+    /// [`try_select2`]: fn.try_select2.html
+    fn try_select4,
+
+    /// Blocks for a limited time until one of four receivers becomes ready and receives from it.
+    ///
+    /// This is just like [`select2_timeout`], except it selects over four receivers.
+    ///
+    /// [`select2_timeout`]: fn.select2_timeout.html
+    fn select4_timeout,
+
+    r1: T1 => First = 0,
+    r2: T2 => Second = 1,
+    r3: T3 => Third = 2,
+    r4: T4 => Fourth = 3,
+}
@@ -45,16 +45,21 @@ macro_rules! crossbeam_channel_delegate {
 ///
 /// If the parsing stage encounters a syntax error or the codegen stage ends up with too many
 /// cases to process, the macro fails with a compile-time error.
+///
+/// The `select_biased!` macro enters through `@biased`. Both macros share all stages, which carry
+/// a `$biased` flag that only decides whether the operations are shuffled before selection.
 #[doc(hidden)]
 #[macro_export(local_inner_macros)]
 macro_rules! crossbeam_channel_internal {
     // The list is empty. Now check the arguments of each processed case.
     (@list
+        $biased:tt
         ()
         ($($head:tt)*)
     ) => {
         crossbeam_channel_internal!(
             @case
+            $biased
             ($($head)*)
             ()
             ()
@@ -62,17 +67,20 @@ macro_rules! crossbeam_channel_internal {
     };
     // If necessary, insert an empty argument list after `default`.
     (@list
+        $biased:tt
         (default => $($tail:tt)*)
         ($($head:tt)*)
     ) => {
         crossbeam_channel_internal!(
             @list
+            $biased
             (default() => $($tail)*)
             ($($head)*)
         )
     };
     // But print an error if `default` is followed by a `->`.
     (@list
+        $biased:tt
         (default -> $($tail:tt)*)
         ($($head:tt)*)
     ) => {
@@ -82,6 +90,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Print an error if there's an `->` after the argument list in the default case.
     (@list
+        $biased:tt
         (default $args:tt -> $($tail:tt)*)
         ($($head:tt)*)
     ) => {
@@ -91,6 +100,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Print an error if there is a missing result in a recv case.
     (@list
+        $biased:tt
         (recv($($args:tt)*) => $($tail:tt)*)
         ($($head:tt)*)
     ) => {
@@ -100,6 +110,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Print an error if there is a missing result in a send case.
     (@list
+        $biased:tt
         (send($($args:tt)*) => $($tail:tt)*)
         ($($head:tt)*)
     ) => {
@@ -109,6 +120,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Make sure the arrow and the result are not repeated.
     (@list
+        $biased:tt
         ($case:ident $args:tt -> $res:tt -> $($tail:tt)*)
         ($($head:tt)*)
     ) => {
@@ -116,6 +128,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Print an error if there is a semicolon after the block.
     (@list
+        $biased:tt
         ($case:ident $args:tt $(-> $res:pat)* => $body:block; $($tail:tt)*)
         ($($head:tt)*)
     ) => {
@@ -125,50 +138,59 @@ macro_rules! crossbeam_channel_internal {
     };
     // The first case is separated by a comma.
     (@list
+        $biased:tt
         ($case:ident ($($args:tt)*) $(-> $res:pat)* => $body:expr, $($tail:tt)*)
         ($($head:tt)*)
     ) => {
         crossbeam_channel_internal!(
             @list
+            $biased
             ($($tail)*)
             ($($head)* $case ($($args)*) $(-> $res)* => { $body },)
         )
     };
     // Don't require a comma after the case if it has a proper block.
     (@list
+        $biased:tt
         ($case:ident ($($args:tt)*) $(-> $res:pat)* => $body:block $($tail:tt)*)
         ($($head:tt)*)
     ) => {
         crossbeam_channel_internal!(
             @list
+            $biased
             ($($tail)*)
             ($($head)* $case ($($args)*) $(-> $res)* => { $body },)
         )
     };
     // Only one case remains.
     (@list
+        $biased:tt
         ($case:ident ($($args:tt)*) $(-> $res:pat)* => $body:expr)
         ($($head:tt)*)
     ) => {
         crossbeam_channel_internal!(
             @list
+            $biased
             ()
             ($($head)* $case ($($args)*) $(-> $res)* => { $body },)
         )
     };
     // Accept a trailing comma at the end of the list.
     (@list
+        $biased:tt
         ($case:ident ($($args:tt)*) $(-> $res:pat)* => $body:expr,)
         ($($head:tt)*)
     ) => {
         crossbeam_channel_internal!(
             @list
+            $biased
             ()
             ($($head)* $case ($($args)*) $(-> $res)* => { $body },)
         )
     };
     // Diagnose and print an error.
     (@list
+        $biased:tt
         ($($tail:tt)*)
         ($($head:tt)*)
     ) => {
@@ -386,12 +408,14 @@ macro_rules! crossbeam_channel_internal {
 
     // Success! All cases were parsed.
     (@case
+        $biased:tt
         ()
         $cases:tt
         $default:tt
     ) => {
         crossbeam_channel_internal!(
             @init
+            $biased
             $cases
             $default
         )
@@ -399,12 +423,14 @@ macro_rules! crossbeam_channel_internal {
 
     // Check the format of a recv case.
     (@case
+        $biased:tt
         (recv($r:expr) -> $res:pat => $body:tt, $($tail:tt)*)
         ($($cases:tt)*)
         $default:tt
     ) => {
         crossbeam_channel_internal!(
             @case
+            $biased
             ($($tail)*)
             ($($cases)* recv($r) -> $res => $body,)
             $default
@@ -412,12 +438,14 @@ macro_rules! crossbeam_channel_internal {
     };
     // Allow trailing comma...
     (@case
+        $biased:tt
         (recv($r:expr,) -> $res:pat => $body:tt, $($tail:tt)*)
         ($($cases:tt)*)
         $default:tt
     ) => {
         crossbeam_channel_internal!(
             @case
+            $biased
             ($($tail)*)
             ($($cases)* recv($r) -> $res => $body,)
             $default
@@ -425,6 +453,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Print an error if the argument list is invalid.
     (@case
+        $biased:tt
         (recv($($args:tt)*) -> $res:pat => $body:tt, $($tail:tt)*)
         ($($cases:tt)*)
         $default:tt
@@ -439,6 +468,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Print an error if there is no argument list.
     (@case
+        $biased:tt
         (recv $t:tt $($tail:tt)*)
         ($($cases:tt)*)
         $default:tt
@@ -454,12 +484,14 @@ macro_rules! crossbeam_channel_internal {
 
     // Check the format of a send case.
     (@case
+        $biased:tt
         (send($s:expr, $m:expr) -> $res:pat => $body:tt, $($tail:tt)*)
         ($($cases:tt)*)
         $default:tt
     ) => {
         crossbeam_channel_internal!(
             @case
+            $biased
             ($($tail)*)
             ($($cases)* send($s, $m) -> $res => $body,)
             $default
@@ -467,12 +499,14 @@ macro_rules! crossbeam_channel_internal {
     };
     // Allow trailing comma...
     (@case
+        $biased:tt
         (send($s:expr, $m:expr,) -> $res:pat => $body:tt, $($tail:tt)*)
         ($($cases:tt)*)
         $default:tt
     ) => {
         crossbeam_channel_internal!(
             @case
+            $biased
             ($($tail)*)
             ($($cases)* send($s, $m) -> $res => $body,)
             $default
@@ -480,6 +514,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Print an error if the argument list is invalid.
     (@case
+        $biased:tt
         (send($($args:tt)*) -> $res:pat => $body:tt, $($tail:tt)*)
         ($($cases:tt)*)
         $default:tt
@@ -494,6 +529,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Print an error if there is no argument list.
     (@case
+        $biased:tt
         (send $t:tt $($tail:tt)*)
         ($($cases:tt)*)
         $default:tt
@@ -509,12 +545,14 @@ macro_rules! crossbeam_channel_internal {
 
     // Check the format of a default case.
     (@case
+        $biased:tt
         (default() => $body:tt, $($tail:tt)*)
         $cases:tt
         ()
     ) => {
         crossbeam_channel_internal!(
             @case
+            $biased
             ($($tail)*)
             $cases
             (default() => $body,)
@@ -522,12 +560,14 @@ macro_rules! crossbeam_channel_internal {
     };
     // Check the format of a default case with timeout.
     (@case
+        $biased:tt
         (default($timeout:expr) => $body:tt, $($tail:tt)*)
         $cases:tt
         ()
     ) => {
         crossbeam_channel_internal!(
             @case
+            $biased
             ($($tail)*)
             $cases
             (default($timeout) => $body,)
@@ -535,12 +575,14 @@ macro_rules! crossbeam_channel_internal {
     };
     // Allow trailing comma...
     (@case
+        $biased:tt
         (default($timeout:expr,) => $body:tt, $($tail:tt)*)
         $cases:tt
         ()
     ) => {
         crossbeam_channel_internal!(
             @case
+            $biased
             ($($tail)*)
             $cases
             (default($timeout) => $body,)
@@ -548,6 +590,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Check for duplicate default cases...
     (@case
+        $biased:tt
         (default $($tail:tt)*)
         $cases:tt
         ($($def:tt)+)
@@ -558,6 +601,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Print an error if the argument list is invalid.
     (@case
+        $biased:tt
         (default($($args:tt)*) => $body:tt, $($tail:tt)*)
         $cases:tt
         $default:tt
@@ -572,6 +616,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Print an error if there is an unexpected token after `default`.
     (@case
+        $biased:tt
         (default $($tail:tt)*)
         $cases:tt
         $default:tt
@@ -587,6 +632,7 @@ macro_rules! crossbeam_channel_internal {
 
     // The case was not consumed, therefore it must be invalid.
     (@case
+        $biased:tt
         ($case:ident $($tail:tt)*)
         $cases:tt
         $default:tt
@@ -602,6 +648,7 @@ macro_rules! crossbeam_channel_internal {
 
     // Optimize `select!` into `try_recv()`.
     (@init
+        $biased:tt
         (recv($r:expr) -> $res:pat => $recv_body:tt,)
         (default() => $default_body:tt,)
     ) => {{
@@ -623,6 +670,7 @@ macro_rules! crossbeam_channel_internal {
     }};
     // Optimize `select!` into `recv()`.
     (@init
+        $biased:tt
         (recv($r:expr) -> $res:pat => $body:tt,)
         ()
     ) => {{
@@ -637,6 +685,7 @@ macro_rules! crossbeam_channel_internal {
     }};
     // Optimize `select!` into `recv_timeout()`.
     (@init
+        $biased:tt
         (recv($r:expr) -> $res:pat => $recv_body:tt,)
         (default($timeout:expr) => $default_body:tt,)
     ) => {{
@@ -659,6 +708,7 @@ macro_rules! crossbeam_channel_internal {
 
     // // Optimize the non-blocking case with two receive operations.
     // (@init
+    //     $biased:tt
     //     (recv($r1:expr) -> $res1:pat => $recv_body1:tt,)
     //     (recv($r2:expr) -> $res2:pat => $recv_body2:tt,)
     //     (default() => $default_body:tt,)
@@ -679,6 +729,7 @@ macro_rules! crossbeam_channel_internal {
     // }};
     // // Optimize the blocking case with two receive operations.
     // (@init
+    //     $biased:tt
     //     (recv($r1:expr) -> $res1:pat => $body1:tt,)
     //     (recv($r2:expr) -> $res2:pat => $body2:tt,)
     //     ()
@@ -699,6 +750,7 @@ macro_rules! crossbeam_channel_internal {
     // }};
     // // Optimize the case with two receive operations and a timeout.
     // (@init
+    //     $biased:tt
     //     (recv($r1:expr) -> $res1:pat => $recv_body1:tt,)
     //     (recv($r2:expr) -> $res2:pat => $recv_body2:tt,)
     //     (default($timeout:expr) => $default_body:tt,)
@@ -720,6 +772,7 @@ macro_rules! crossbeam_channel_internal {
 
     // // Optimize `select!` into `try_send()`.
     // (@init
+    //     $biased:tt
     //     (send($s:expr, $m:expr) -> $res:pat => $send_body:tt,)
     //     (default() => $default_body:tt,)
     // ) => {{
@@ -732,6 +785,7 @@ macro_rules! crossbeam_channel_internal {
     // }};
     // // Optimize `select!` into `send()`.
     // (@init
+    //     $biased:tt
     //     (send($s:expr, $m:expr) -> $res:pat => $body:tt,)
     //     ()
     // ) => {{
@@ -744,6 +798,7 @@ macro_rules! crossbeam_channel_internal {
     // }};
     // // Optimize `select!` into `send_timeout()`.
     // (@init
+    //     $biased:tt
     //     (send($s:expr, $m:expr) -> $res:pat => $body:tt,)
     //     (default($timeout:expr) => $body:tt,)
     // ) => {{
@@ -757,6 +812,7 @@ macro_rules! crossbeam_channel_internal {
 
     // Create the list of handles and add operations to it.
     (@init
+        $biased:tt
         ($($cases:tt)*)
         $default:tt
     ) => {{
@@ -768,6 +824,7 @@ macro_rules! crossbeam_channel_internal {
 
         crossbeam_channel_internal!(
             @add
+            $biased
             _sel
             ($($cases)*)
             $default
@@ -819,6 +876,7 @@ macro_rules! crossbeam_channel_internal {
 
     // Run blocking selection.
     (@add
+        $biased:tt
         $sel:ident
         ()
         ()
@@ -826,7 +884,7 @@ macro_rules! crossbeam_channel_internal {
         $cases:tt
     ) => {{
         let _oper: $crate::SelectedOperation<'_> = {
-            let _oper = $crate::internal::select(&mut $sel);
+            let _oper = $crate::internal::select(&mut $sel, $biased);
 
             // Erase the lifetime so that `sel` can be dropped early even without NLL.
             #[allow(unsafe_code)]
@@ -842,6 +900,7 @@ macro_rules! crossbeam_channel_internal {
     }};
     // Run non-blocking selection.
     (@add
+        $biased:tt
         $sel:ident
         ()
         (default() => $body:tt,)
@@ -849,7 +908,7 @@ macro_rules! crossbeam_channel_internal {
         $cases:tt
     ) => {{
         let _oper: ::std::option::Option<$crate::SelectedOperation<'_>> = {
-            let _oper = $crate::internal::try_select(&mut $sel);
+            let _oper = $crate::internal::try_select(&mut $sel, $biased);
 
             // Erase the lifetime so that `sel` can be dropped early even without NLL.
             #[allow(unsafe_code)]
@@ -873,6 +932,7 @@ macro_rules! crossbeam_channel_internal {
     }};
     // Run selection with a timeout.
     (@add
+        $biased:tt
         $sel:ident
         ()
         (default($timeout:expr) => $body:tt,)
@@ -880,7 +940,7 @@ macro_rules! crossbeam_channel_internal {
         $cases:tt
     ) => {{
         let _oper: ::std::option::Option<$crate::SelectedOperation<'_>> = {
-            let _oper = $crate::internal::select_timeout(&mut $sel, $timeout);
+            let _oper = $crate::internal::select_timeout(&mut $sel, $timeout, $biased);
 
             // Erase the lifetime so that `sel` can be dropped early even without NLL.
             #[allow(unsafe_code)]
@@ -904,6 +964,7 @@ macro_rules! crossbeam_channel_internal {
     }};
     // Have we used up all labels?
     (@add
+        $biased:tt
         $sel:ident
         $input:tt
         $default:tt
@@ -914,6 +975,7 @@ macro_rules! crossbeam_channel_internal {
     };
     // Add a receive operation to `sel`.
     (@add
+        $biased:tt
         $sel:ident
         (recv($r:expr) -> $res:pat => $body:tt, $($tail:tt)*)
         $default:tt
@@ -936,6 +998,7 @@ macro_rules! crossbeam_channel_internal {
 
                 crossbeam_channel_internal!(
                     @add
+                    $biased
                     $sel
                     ($($tail)*)
                     $default
@@ -947,6 +1010,7 @@ macro_rules! crossbeam_channel_internal {
     }};
     // Add a send operation to `sel`.
     (@add
+        $biased:tt
         $sel:ident
         (send($s:expr, $m:expr) -> $res:pat => $body:tt, $($tail:tt)*)
         $default:tt
@@ -969,6 +1033,7 @@ macro_rules! crossbeam_channel_internal {
 
                 crossbeam_channel_internal!(
                     @add
+                    $biased
                     $sel
                     ($($tail)*)
                     $default
@@ -1032,6 +1097,27 @@ macro_rules! crossbeam_channel_internal {
         ))
     }};
 
+    // The entry points of `select_biased!`.
+    (@biased) => {
+        crossbeam_channel_delegate!(compile_error("empty `select_biased!` block"))
+    };
+    (@biased $($case:ident $(($($args:tt)*))* => $body:expr $(,)*)*) => {
+        crossbeam_channel_internal!(
+            @list
+            true
+            ($($case $(($($args)*))* => { $body },)*)
+            ()
+        )
+    };
+    (@biased $($tokens:tt)*) => {
+        crossbeam_channel_internal!(
+            @list
+            true
+            ($($tokens)*)
+            ()
+        )
+    };
+
     // Catches a bug within this macro (should not happen).
     (@$($tokens:tt)*) => {
         crossbeam_channel_delegate!(compile_error(
@@ -1049,6 +1135,7 @@ macro_rules! crossbeam_channel_internal {
     ($($case:ident $(($($args:tt)*))* => $body:expr $(,)*)*) => {
         crossbeam_channel_internal!(
             @list
+            false
             ($($case $(($($args)*))* => { $body },)*)
             ()
         )
@@ -1056,6 +1143,7 @@ macro_rules! crossbeam_channel_internal {
     ($($tokens:tt)*) => {
         crossbeam_channel_internal!(
             @list
+            false
             ($($tokens)*)
             ()
         )
@@ -1212,3 +1300,51 @@ macro_rules! select {
         )
     };
 }
+
+/// Selects from a set of channel operations, preferring the first ready one.
+///
+/// This macro has exactly the same syntax and behavior as [`select!`], except when multiple
+/// operations are ready at the same time. Instead of picking a random one, `select_biased!` picks
+/// the one written first. It is the macro counterpart of [`Select::new_biased`].
+///
+/// Biased selection is deterministic and slightly cheaper, which is useful when some operations
+/// matter more than others, for example when a shutdown signal should be handled before any
+/// remaining work. The downside is starvation: as long as an earlier operation keeps being ready,
+/// the operations after it are never selected. Use [`select!`] unless the order of the cases is
+/// meant to express priority.
+///
+/// [`select!`]: macro.select.html
+/// [`Select::new_biased`]: struct.Select.html#method.new_biased
+///
+/// # Examples
+///
+/// Handle a shutdown signal before any pending work:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use crossbeam_channel::unbounded;
+///
+/// let (work_s, work_r) = unbounded();
+/// let (stop_s, stop_r) = unbounded();
+///
+/// work_s.send(1).unwrap();
+/// stop_s.send(()).unwrap();
+///
+/// // Both operations are ready, but the first one always wins.
+/// select_biased! {
+///     recv(stop_r) -> msg => assert_eq!(msg, Ok(())),
+///     recv(work_r) -> _ => panic!(),
+/// }
+/// # }
+/// ```
+#[macro_export(local_inner_macros)]
+macro_rules! select_biased {
+    ($($tokens:tt)*) => {
+        crossbeam_channel_internal!(
+            @biased
+            $($tokens)*
+        )
+    };
+}
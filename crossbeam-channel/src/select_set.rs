@@ -0,0 +1,315 @@
+//! A reusable selection over a fixed set of channel operations.
+
+use std::any::Any;
+use std::fmt;
+use std::mem;
+use std::time::Duration;
+
+use channel::{Receiver, Sender};
+use err::{RecvError, SelectTimeoutError, SendError, TrySelectError};
+use select::{self, HandleEntry, SelectHandle, SelectedOperation};
+
+/// A channel handle owned by a [`SelectSet`].
+///
+/// [`SelectSet`]: struct.SelectSet.html
+trait Entry: SelectHandle + Send + Sync {
+    /// Returns the handle as an operation at position `index`, as passed to the selection
+    /// functions.
+    fn handle(&self, index: usize) -> HandleEntry<'_>;
+
+    /// Returns the handle as `Any` so that it can be downcast when completing an operation.
+    fn as_any(&self) -> &Any;
+}
+
+impl<T: Send + 'static> Entry for Receiver<T> {
+    fn handle(&self, index: usize) -> HandleEntry<'_> {
+        (self, index, self as *const Receiver<T> as *const u8)
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl<T: Send + 'static> Entry for Sender<T> {
+    fn handle(&self, index: usize) -> HandleEntry<'_> {
+        (self, index, self as *const Sender<T> as *const u8)
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+/// A fixed set of channel operations that can be selected over again and again.
+///
+/// [`Select`] borrows its senders and receivers and is typically built anew for every selection,
+/// which means collecting the list of operations each time. A `SelectSet` is the reusable
+/// counterpart for loops that select over the same operations forever: it holds clones of the
+/// senders and receivers, builds the list of operations once, and keeps reusing it.
+///
+/// Operations are added with [`add_recv`] and [`add_send`] and get indices in the order they were
+/// added, starting from zero. Selecting returns a [`SetOperation`], which must be completed by
+/// passing a sender or receiver of the same channel that was added.
+///
+/// When a selected operation fails because its channel is disconnected, the operation is removed
+/// from the set, so later selections skip it and never return its index again. The indices of the
+/// remaining operations don't change. Once all operations have been removed, [`select`] blocks
+/// forever, just like it does on an empty [`Select`].
+///
+/// [`Select`]: struct.Select.html
+/// [`add_recv`]: struct.SelectSet.html#method.add_recv
+/// [`add_send`]: struct.SelectSet.html#method.add_send
+/// [`SetOperation`]: struct.SetOperation.html
+/// [`select`]: struct.SelectSet.html#method.select
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{unbounded, SelectSet};
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+///
+/// thread::spawn(move || {
+///     for i in 0..3 {
+///         s1.send(i).unwrap();
+///     }
+/// });
+/// thread::spawn(move || {
+///     for i in 3..6 {
+///         s2.send(i).unwrap();
+///     }
+/// });
+///
+/// let mut set = SelectSet::new().add_recv(&r1).add_recv(&r2);
+/// let mut sum = 0;
+///
+/// // Both channels get disconnected after their messages, and are then skipped.
+/// while !set.is_empty() {
+///     let oper = set.select();
+///     let r = if oper.index() == 0 { &r1 } else { &r2 };
+///     if let Ok(msg) = oper.recv(r) {
+///         sum += msg;
+///     }
+/// }
+///
+/// assert_eq!(sum, 15);
+/// ```
+pub struct SelectSet {
+    /// The operations that haven't been removed, as passed to the selection functions.
+    ///
+    /// The references point into `entries`, which are boxed and never moved or dropped while
+    /// the set is alive. This field is declared first so that it gets dropped first.
+    handles: Vec<HandleEntry<'static>>,
+
+    /// The senders and receivers owned by the set, indexed by operation index.
+    entries: Vec<Box<Entry>>,
+}
+
+unsafe impl Send for SelectSet {}
+unsafe impl Sync for SelectSet {}
+
+impl SelectSet {
+    /// Creates an empty set of operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::SelectSet;
+    ///
+    /// let mut set = SelectSet::new();
+    /// assert!(set.try_select().is_err());
+    /// ```
+    pub fn new() -> SelectSet {
+        SelectSet {
+            handles: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds an entry to the set and returns it.
+    fn add(mut self, entry: Box<Entry>) -> SelectSet {
+        let index = self.entries.len();
+
+        // The entry is boxed and owned by the set, so it outlives the handle.
+        let handle =
+            unsafe { mem::transmute::<HandleEntry, HandleEntry<'static>>(entry.handle(index)) };
+
+        self.handles.push(handle);
+        self.entries.push(entry);
+        self
+    }
+
+    /// Adds a receive operation on a clone of `r`.
+    ///
+    /// The index of the operation equals the number of operations added before it.
+    pub fn add_recv<T: Send + 'static>(self, r: &Receiver<T>) -> SelectSet {
+        self.add(Box::new(r.clone()))
+    }
+
+    /// Adds a send operation on a clone of `s`.
+    ///
+    /// The index of the operation equals the number of operations added before it.
+    pub fn add_send<T: Send + 'static>(self, s: &Sender<T>) -> SelectSet {
+        self.add(Box::new(s.clone()))
+    }
+
+    /// Returns the number of operations that haven't been removed from the set.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Returns `true` if all operations have been removed from the set.
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Returns `true` if the operation at `index` was added and hasn't been removed.
+    pub fn contains(&self, index: usize) -> bool {
+        self.handles.iter().any(|&(_, i, _)| i == index)
+    }
+
+    /// Removes the operation at `index` so that it is skipped by later selections.
+    ///
+    /// The clone of the sender or receiver is dropped along with the set, not right away, so
+    /// removing an operation doesn't disconnect its channel.
+    pub fn remove(&mut self, index: usize) {
+        self.handles.retain(|&(_, i, _)| i != index);
+    }
+
+    /// Attempts to select one of the operations without blocking.
+    ///
+    /// This method behaves just like [`Select::try_select`].
+    ///
+    /// [`Select::try_select`]: struct.Select.html#method.try_select
+    pub fn try_select(&mut self) -> Result<SetOperation<'_>, TrySelectError> {
+        let oper = select::try_select(&mut self.handles, false)?;
+        Ok(SetOperation { oper, set: self })
+    }
+
+    /// Blocks until one of the operations becomes ready and selects it.
+    ///
+    /// This method behaves just like [`Select::select`].
+    ///
+    /// [`Select::select`]: struct.Select.html#method.select
+    pub fn select(&mut self) -> SetOperation<'_> {
+        let oper = select::select(&mut self.handles, false);
+        SetOperation { oper, set: self }
+    }
+
+    /// Blocks for a limited time until one of the operations becomes ready and selects it.
+    ///
+    /// This method behaves just like [`Select::select_timeout`].
+    ///
+    /// [`Select::select_timeout`]: struct.Select.html#method.select_timeout
+    pub fn select_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<SetOperation<'_>, SelectTimeoutError> {
+        let oper = select::select_timeout(&mut self.handles, timeout, false)?;
+        Ok(SetOperation { oper, set: self })
+    }
+}
+
+impl Default for SelectSet {
+    fn default() -> SelectSet {
+        SelectSet::new()
+    }
+}
+
+impl fmt::Debug for SelectSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SelectSet")
+            .field("len", &self.handles.len())
+            .field("added", &self.entries.len())
+            .finish()
+    }
+}
+
+/// An operation selected from a [`SelectSet`].
+///
+/// Just like [`SelectedOperation`], it must be completed with [`send`] or [`recv`], and dropping
+/// it without completing it panics.
+///
+/// [`SelectSet`]: struct.SelectSet.html
+/// [`SelectedOperation`]: struct.SelectedOperation.html
+/// [`send`]: struct.SetOperation.html#method.send
+/// [`recv`]: struct.SetOperation.html#method.recv
+pub struct SetOperation<'a> {
+    /// The operation selected over the handles owned by the set.
+    oper: SelectedOperation<'static>,
+
+    /// The set the operation was selected from.
+    set: &'a mut SelectSet,
+}
+
+impl<'a> SetOperation<'a> {
+    /// Returns the index of the selected operation.
+    pub fn index(&self) -> usize {
+        self.oper.index()
+    }
+
+    /// Completes the send operation.
+    ///
+    /// The passed [`Sender`] must belong to the same channel as the one passed to
+    /// [`SelectSet::add_send`]. If the channel is disconnected, the operation is removed from the
+    /// set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the selected operation is not a send operation on the channel of `s`.
+    ///
+    /// [`Sender`]: struct.Sender.html
+    /// [`SelectSet::add_send`]: struct.SelectSet.html#method.add_send
+    pub fn send<T: 'static>(self, s: &Sender<T>, msg: T) -> Result<(), SendError<T>> {
+        let index = self.oper.index();
+        let res = {
+            let held = self.set.entries[index].as_any().downcast_ref::<Sender<T>>();
+            match held {
+                Some(held) if held.identical_to(s) => self.oper.send(held, msg),
+                _ => panic!("passed a sender that wasn't selected"),
+            }
+        };
+        if res.is_err() {
+            self.set.remove(index);
+        }
+        res
+    }
+
+    /// Completes the receive operation.
+    ///
+    /// The passed [`Receiver`] must belong to the same channel as the one passed to
+    /// [`SelectSet::add_recv`]. If the channel is empty and disconnected, the operation is removed
+    /// from the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the selected operation is not a receive operation on the channel of `r`.
+    ///
+    /// [`Receiver`]: struct.Receiver.html
+    /// [`SelectSet::add_recv`]: struct.SelectSet.html#method.add_recv
+    pub fn recv<T: 'static>(self, r: &Receiver<T>) -> Result<T, RecvError> {
+        let index = self.oper.index();
+        let res = {
+            let held = self.set.entries[index]
+                .as_any()
+                .downcast_ref::<Receiver<T>>();
+            match held {
+                Some(held) if held.identical_to(r) => self.oper.recv(held),
+                _ => panic!("passed a receiver that wasn't selected"),
+            }
+        };
+        if res.is_err() {
+            self.set.remove(index);
+        }
+        res
+    }
+}
+
+impl<'a> fmt::Debug for SetOperation<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("SetOperation { .. }")
+    }
+}
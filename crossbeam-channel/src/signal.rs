@@ -0,0 +1,258 @@
+//! Channels that deliver OS signals.
+//!
+//! Signal handlers may only perform async-signal-safe operations, so the handler installed here
+//! just writes the signal number into a non-blocking pipe (the "self-pipe trick"). A background
+//! thread reads the pipe and sends a message into every channel registered for that signal. The
+//! returned receivers are ordinary channels, so they can be used in selection like any other.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::mem;
+use std::os::unix::io::FromRawFd;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use libc;
+
+use channel::{unbounded, Receiver, Sender};
+
+/// The write end of the self-pipe.
+static PIPE_WRITE: AtomicUsize = AtomicUsize::new(0);
+
+/// The global registry, allocated on first use.
+static REGISTRY: AtomicPtr<Mutex<Registry>> = AtomicPtr::new(ptr::null_mut());
+
+/// Channels registered for a single signal.
+struct Slot {
+    /// Senders feeding the receivers returned by `signal_channel`.
+    senders: Vec<Sender<()>>,
+
+    /// The action that was installed before ours.
+    old: libc::sigaction,
+}
+
+/// Registered signals and the state of the dispatch thread.
+struct Registry {
+    /// Channels for each signal with our handler installed.
+    slots: HashMap<libc::c_int, Slot>,
+
+    /// Equals `true` once the self-pipe is created and the dispatch thread is running.
+    started: bool,
+}
+
+/// Returns the global registry.
+fn registry() -> &'static Mutex<Registry> {
+    let mut p = REGISTRY.load(Ordering::Acquire);
+
+    if p.is_null() {
+        let new = Box::into_raw(Box::new(Mutex::new(Registry {
+            slots: HashMap::new(),
+            started: false,
+        })));
+
+        match REGISTRY.compare_exchange(ptr::null_mut(), new, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => p = new,
+            Err(current) => {
+                // Another thread won the race.
+                unsafe { drop(Box::from_raw(new)) };
+                p = current;
+            }
+        }
+    }
+
+    unsafe { &*p }
+}
+
+/// Returns a pointer to the current thread's `errno`.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "emscripten"))]
+unsafe fn errno_location() -> *mut libc::c_int {
+    libc::__errno_location()
+}
+
+/// Returns a pointer to the current thread's `errno`.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+))]
+unsafe fn errno_location() -> *mut libc::c_int {
+    libc::__error()
+}
+
+/// Returns a pointer to the current thread's `errno`.
+#[cfg(any(target_os = "openbsd", target_os = "netbsd"))]
+unsafe fn errno_location() -> *mut libc::c_int {
+    libc::__errno()
+}
+
+/// The signal handler.
+extern "C" fn handler(sig: libc::c_int) {
+    // Only async-signal-safe functions may be called here. The interrupted code must not observe
+    // a change of `errno` caused by a failed write, so it is saved and restored.
+    unsafe {
+        let errno = *errno_location();
+        let byte = sig as u8;
+        libc::write(
+            PIPE_WRITE.load(Ordering::Relaxed) as libc::c_int,
+            &byte as *const u8 as *const libc::c_void,
+            1,
+        );
+        *errno_location() = errno;
+    }
+}
+
+/// Creates the self-pipe and spawns the dispatch thread.
+fn start() -> io::Result<()> {
+    let mut fds = [0 as libc::c_int; 2];
+
+    unsafe {
+        if libc::pipe(fds.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        for &fd in &fds {
+            libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+        }
+
+        // The handler must never block, even if the pipe is full. Dropped bytes are fine in that
+        // case because signals of the same kind get coalesced by the OS anyway.
+        let flags = libc::fcntl(fds[1], libc::F_GETFL);
+        libc::fcntl(fds[1], libc::F_SETFL, flags | libc::O_NONBLOCK);
+    }
+
+    PIPE_WRITE.store(fds[1] as usize, Ordering::SeqCst);
+    let mut pipe = unsafe { File::from_raw_fd(fds[0]) };
+
+    thread::Builder::new()
+        .name("crossbeam-signal".to_string())
+        .spawn(move || {
+            let mut buf = [0u8; 64];
+            loop {
+                match pipe.read(&mut buf) {
+                    Ok(0) => return,
+                    Ok(n) => {
+                        for &sig in &buf[..n] {
+                            dispatch(sig as libc::c_int);
+                        }
+                    }
+                    Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(_) => return,
+                }
+            }
+        })?;
+
+    Ok(())
+}
+
+/// Sends a message into every channel registered for `sig`.
+///
+/// If all receivers for the signal have been dropped, the previous action is restored and the
+/// signal is raised again, as if our handler had never been installed.
+fn dispatch(sig: libc::c_int) {
+    let mut registry = registry().lock().unwrap();
+
+    let abandoned = match registry.slots.get_mut(&sig) {
+        None => return,
+        Some(slot) => {
+            slot.senders.retain(|s| s.send(()).is_ok());
+            slot.senders.is_empty()
+        }
+    };
+
+    if abandoned {
+        let slot = registry.slots.remove(&sig).unwrap();
+        unsafe {
+            libc::sigaction(sig, &slot.old, ptr::null_mut());
+            libc::raise(sig);
+        }
+    }
+}
+
+/// Installs our handler for `sig` and returns the previous action.
+fn install(sig: libc::c_int) -> io::Result<libc::sigaction> {
+    unsafe {
+        let mut new: libc::sigaction = mem::zeroed();
+        new.sa_sigaction = handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        new.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut new.sa_mask);
+
+        let mut old: libc::sigaction = mem::zeroed();
+        if libc::sigaction(sig, &new, &mut old) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(old)
+    }
+}
+
+/// Creates a receiver that gets a message each time the process receives the OS signal `sig`.
+///
+/// Signal numbers are those defined by the `libc` crate, for example `libc::SIGINT`. The receiver
+/// can be used in [`select!`] together with other channels, so that a blocking selection wakes up
+/// when the signal is delivered.
+///
+/// Each call creates an independent channel, and every channel registered for a signal receives a
+/// message when it is delivered. Clones of the returned receiver share the same channel, so only
+/// one of the threads selecting on it will get the message.
+///
+/// The first call for a signal replaces its handler. Once all receivers for the signal are
+/// dropped, the next delivery restores the previous handler and raises the signal again, so that
+/// it takes its usual effect. Note that the OS may merge signals that arrive in quick succession.
+///
+/// An error is returned if the handler cannot be installed, for example for `SIGKILL`.
+///
+/// This function is available with the `signal` feature on Unix platforms.
+///
+/// [`select!`]: macro.select.html
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # extern crate libc;
+/// # fn main() {
+/// use crossbeam_channel::{signal_channel, unbounded};
+///
+/// let (s, work) = unbounded::<String>();
+/// let ctrl_c = signal_channel(libc::SIGINT).unwrap();
+/// # drop(s);
+///
+/// loop {
+///     select! {
+///         recv(work) -> msg => match msg {
+///             Ok(job) => println!("working on {}", job),
+///             Err(_) => break,
+///         },
+///         recv(ctrl_c) -> _ => {
+///             println!("interrupted");
+///             break;
+///         }
+///     }
+/// }
+/// # }
+/// ```
+pub fn signal_channel(sig: libc::c_int) -> io::Result<Receiver<()>> {
+    let mut registry = registry().lock().unwrap();
+
+    if !registry.started {
+        start()?;
+        registry.started = true;
+    }
+
+    let slot = match registry.slots.entry(sig) {
+        Entry::Occupied(e) => e.into_mut(),
+        Entry::Vacant(e) => e.insert(Slot {
+            senders: Vec::new(),
+            old: install(sig)?,
+        }),
+    };
+
+    let (s, r) = unbounded();
+    slot.senders.push(s);
+    Ok(r)
+}
@@ -0,0 +1,472 @@
+//! Bounded channel for exactly one sender and one receiver.
+//!
+//! The buffer is a ring indexed by two counters that wrap around on overflow. Only the sender
+//! moves the tail and only the receiver moves the head, so neither side ever needs a CAS. Each side
+//! also caches the last value it has seen of the other side's counter and only loads it again when
+//! the cached value says that the buffer is full or empty.
+//!
+//! The length of the buffer is the capacity rounded up to a power of two, and a counter is mapped
+//! to a slot by masking. Since the number of slots divides the range of the counters, the slots
+//! stay consecutive when a counter wraps around.
+//!
+//! Memory orderings:
+//!   - Moving the head or the tail is `SeqCst`, and so is the load of the other counter when the
+//!     cached value is exhausted. This pairs with `SyncWaker`, whose flag is stored with `SeqCst`
+//!     after registering, so a blocked side cannot miss a move made before it went to sleep.
+//!   - A message is published by the `SeqCst` store of the tail and taken after loading the tail,
+//!     which is at least `Acquire`.
+
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossbeam_utils::{Backoff, CachePadded};
+
+use context::Context;
+use err::{RecvError, RecvTimeoutError, SendError, SendTimeoutError, TryRecvError, TrySendError};
+use select::{Operation, Selected, Token};
+use waker::SyncWaker;
+
+/// Creates a bounded channel for a single sender and a single receiver.
+///
+/// This is a specialized alternative to [`bounded`] for the common case of one producer thread
+/// talking to one consumer thread. Neither side ever competes with another thread of its own kind,
+/// so sending and receiving take a few loads and a store instead of compare-and-swap loops, which
+/// makes this channel considerably faster under load.
+///
+/// The returned [`SpscSender`] and [`SpscReceiver`] can't be cloned, and they can be moved to
+/// another thread but not shared between threads, so the compiler enforces that there is only ever
+/// one sender and one receiver. In turn, they can't be used with [`Select`] or [`select!`].
+///
+/// [`bounded`]: fn.bounded.html
+/// [`SpscSender`]: struct.SpscSender.html
+/// [`SpscReceiver`]: struct.SpscReceiver.html
+/// [`Select`]: struct.Select.html
+/// [`select!`]: macro.select.html
+///
+/// # Panics
+///
+/// Panics if the capacity is zero or too large.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::spsc_bounded;
+///
+/// let (s, r) = spsc_bounded(16);
+///
+/// thread::spawn(move || {
+///     for i in 0..100 {
+///         s.send(i).unwrap();
+///     }
+/// });
+///
+/// assert_eq!(r.iter().sum::<i32>(), 4950);
+/// ```
+pub fn spsc_bounded<T>(cap: usize) -> (SpscSender<T>, SpscReceiver<T>) {
+    assert!(cap > 0, "capacity must be positive");
+
+    // Allocate a buffer of `cap` slots, rounded up to a power of two.
+    let len = cap
+        .checked_next_power_of_two()
+        .expect("capacity is too large");
+    let buffer = {
+        let mut v = Vec::<T>::with_capacity(len);
+        let ptr = v.as_mut_ptr();
+        mem::forget(v);
+        ptr
+    };
+
+    let inner = Arc::new(Inner {
+        buffer,
+        cap,
+        mask: len - 1,
+        head: CachePadded::new(AtomicUsize::new(0)),
+        tail: CachePadded::new(AtomicUsize::new(0)),
+        is_disconnected: AtomicBool::new(false),
+        senders: SyncWaker::new(),
+        receivers: SyncWaker::new(),
+        _marker: PhantomData,
+    });
+
+    let s = SpscSender {
+        inner: inner.clone(),
+        head: Cell::new(0),
+    };
+    let r = SpscReceiver {
+        inner,
+        tail: Cell::new(0),
+    };
+    (s, r)
+}
+
+/// State shared by the sender and the receiver.
+struct Inner<T> {
+    /// The buffer holding slots.
+    buffer: *mut T,
+
+    /// The channel capacity.
+    cap: usize,
+
+    /// The length of the buffer minus one, which is a power of two minus one.
+    mask: usize,
+
+    /// The number of messages received so far, wrapping around on overflow.
+    head: CachePadded<AtomicUsize>,
+
+    /// The number of messages sent so far, wrapping around on overflow.
+    tail: CachePadded<AtomicUsize>,
+
+    /// Equals `true` once the sender or the receiver has been dropped.
+    is_disconnected: AtomicBool,
+
+    /// The sender waiting while the channel is full.
+    senders: SyncWaker,
+
+    /// The receiver waiting while the channel is empty and not disconnected.
+    receivers: SyncWaker,
+
+    /// Indicates that dropping an `Inner<T>` may drop messages of type `T`.
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> Inner<T> {
+    /// Returns the slot for the message with the given index.
+    fn slot(&self, index: usize) -> *mut T {
+        unsafe { self.buffer.add(index & self.mask) }
+    }
+
+    /// Returns the number of messages in the channel.
+    fn len(&self) -> usize {
+        loop {
+            // Load the tail, then load the head.
+            let tail = self.tail.load(Ordering::SeqCst);
+            let head = self.head.load(Ordering::SeqCst);
+
+            // If the tail didn't change, we've got consistent values to work with.
+            if self.tail.load(Ordering::SeqCst) == tail {
+                return tail.wrapping_sub(head);
+            }
+        }
+    }
+
+    /// Disconnects the channel and wakes up the other side.
+    fn disconnect(&self) {
+        self.is_disconnected.store(true, Ordering::SeqCst);
+        self.senders.disconnect();
+        self.receivers.disconnect();
+    }
+
+    /// Blocks the current thread on `waker` until it is woken up or the deadline is reached,
+    /// unless `is_ready` returns `true` after registering.
+    fn park<F: Fn() -> bool>(&self, waker: &SyncWaker, deadline: Option<Instant>, is_ready: F) {
+        Context::with(|cx| {
+            let mut token = Token::default();
+            let oper = Operation::hook(&mut token);
+            waker.register(oper, cx);
+
+            // Has the channel become ready just now?
+            if is_ready() || self.is_disconnected.load(Ordering::SeqCst) {
+                let _ = cx.try_select(Selected::Aborted);
+            }
+
+            cx.wait_until(deadline);
+            waker.unregister(oper);
+        });
+    }
+}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Relaxed);
+
+        unsafe {
+            // Drop all messages between head and tail.
+            let mut i = head;
+            while i != tail {
+                ptr::drop_in_place(self.slot(i));
+                i = i.wrapping_add(1);
+            }
+
+            // Finally, deallocate the buffer, but don't run any destructors.
+            Vec::from_raw_parts(self.buffer, 0, self.mask + 1);
+        }
+    }
+}
+
+/// The sending side of a channel created by [`spsc_bounded`].
+///
+/// [`spsc_bounded`]: fn.spsc_bounded.html
+pub struct SpscSender<T> {
+    /// The shared state.
+    inner: Arc<Inner<T>>,
+
+    /// The last seen head.
+    ///
+    /// Being a `Cell`, it also keeps the sender from being shared between threads.
+    head: Cell<usize>,
+}
+
+unsafe impl<T: Send> Send for SpscSender<T> {}
+
+impl<T> SpscSender<T> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// If the channel is full or the receiver has been dropped, an error is returned that
+    /// contains the original message.
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        let inner = &*self.inner;
+
+        if inner.is_disconnected.load(Ordering::SeqCst) {
+            return Err(TrySendError::Disconnected(msg));
+        }
+
+        let tail = inner.tail.load(Ordering::Relaxed);
+        if tail.wrapping_sub(self.head.get()) == inner.cap {
+            self.head.set(inner.head.load(Ordering::SeqCst));
+            if tail.wrapping_sub(self.head.get()) == inner.cap {
+                return Err(TrySendError::Full(msg));
+            }
+        }
+
+        unsafe {
+            ptr::write(inner.slot(tail), msg);
+        }
+        inner.tail.store(tail.wrapping_add(1), Ordering::SeqCst);
+        inner.receivers.notify();
+        Ok(())
+    }
+
+    /// Blocks the current thread until a message is sent or the receiver is dropped.
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.send_until(msg, None).map_err(|err| match err {
+            SendTimeoutError::Disconnected(msg) => SendError(msg),
+            SendTimeoutError::Timeout(_) => unreachable!(),
+        })
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.send_until(msg, Some(Instant::now() + timeout))
+    }
+
+    /// Sends a message, waiting until the deadline if the channel is full.
+    fn send_until(&self, mut msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        let backoff = Backoff::new();
+
+        loop {
+            match self.try_send(msg) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Disconnected(m)) => {
+                    return Err(SendTimeoutError::Disconnected(m))
+                }
+                Err(TrySendError::Full(m)) => msg = m,
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(SendTimeoutError::Timeout(msg));
+                }
+            }
+
+            if backoff.is_completed() {
+                let inner = &*self.inner;
+                inner.park(&inner.senders, deadline, || !self.is_full());
+            } else {
+                backoff.snooze();
+            }
+        }
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.len() == 0
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.inner.len() == self.inner.cap
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns the channel capacity.
+    pub fn capacity(&self) -> usize {
+        self.inner.cap
+    }
+}
+
+impl<T> Drop for SpscSender<T> {
+    fn drop(&mut self) {
+        self.inner.disconnect();
+    }
+}
+
+impl<T> fmt::Debug for SpscSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("SpscSender { .. }")
+    }
+}
+
+/// The receiving side of a channel created by [`spsc_bounded`].
+///
+/// [`spsc_bounded`]: fn.spsc_bounded.html
+pub struct SpscReceiver<T> {
+    /// The shared state.
+    inner: Arc<Inner<T>>,
+
+    /// The last seen tail.
+    ///
+    /// Being a `Cell`, it also keeps the receiver from being shared between threads.
+    tail: Cell<usize>,
+}
+
+unsafe impl<T: Send> Send for SpscReceiver<T> {}
+
+impl<T> SpscReceiver<T> {
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// If the channel is empty, this method returns [`TryRecvError::Empty`], or
+    /// [`TryRecvError::Disconnected`] if the sender has been dropped.
+    ///
+    /// [`TryRecvError::Empty`]: enum.TryRecvError.html#variant.Empty
+    /// [`TryRecvError::Disconnected`]: enum.TryRecvError.html#variant.Disconnected
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let inner = &*self.inner;
+
+        let head = inner.head.load(Ordering::Relaxed);
+        if head == self.tail.get() {
+            self.tail.set(inner.tail.load(Ordering::SeqCst));
+            if head == self.tail.get() {
+                // The sender may have sent a message right before disconnecting.
+                if inner.is_disconnected.load(Ordering::SeqCst) {
+                    self.tail.set(inner.tail.load(Ordering::SeqCst));
+                    if head == self.tail.get() {
+                        return Err(TryRecvError::Disconnected);
+                    }
+                } else {
+                    return Err(TryRecvError::Empty);
+                }
+            }
+        }
+
+        let msg = unsafe { ptr::read(inner.slot(head)) };
+        inner.head.store(head.wrapping_add(1), Ordering::SeqCst);
+        inner.senders.notify();
+        Ok(msg)
+    }
+
+    /// Blocks the current thread until a message is received or the channel is empty and
+    /// disconnected.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.recv_until(None).map_err(|_| RecvError)
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_until(Some(Instant::now() + timeout))
+    }
+
+    /// Receives a message, waiting until the deadline if the channel is empty.
+    fn recv_until(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let backoff = Backoff::new();
+
+        loop {
+            match self.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            if backoff.is_completed() {
+                let inner = &*self.inner;
+                inner.park(&inner.receivers, deadline, || !self.is_empty());
+            } else {
+                backoff.snooze();
+            }
+        }
+    }
+
+    /// A blocking iterator over messages in the channel.
+    ///
+    /// Each call to `next` blocks waiting for the next message and then returns it. However, if
+    /// the channel becomes empty and disconnected, it returns `None` without blocking.
+    pub fn iter(&self) -> SpscIter<'_, T> {
+        SpscIter { receiver: self }
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.len() == 0
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.inner.len() == self.inner.cap
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns the channel capacity.
+    pub fn capacity(&self) -> usize {
+        self.inner.cap
+    }
+}
+
+impl<T> Drop for SpscReceiver<T> {
+    fn drop(&mut self) {
+        self.inner.disconnect();
+    }
+}
+
+impl<T> fmt::Debug for SpscReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("SpscReceiver { .. }")
+    }
+}
+
+/// A blocking iterator over messages in a channel created by [`spsc_bounded`].
+///
+/// Created by [`SpscReceiver::iter`].
+///
+/// [`spsc_bounded`]: fn.spsc_bounded.html
+/// [`SpscReceiver::iter`]: struct.SpscReceiver.html#method.iter
+pub struct SpscIter<'a, T: 'a> {
+    receiver: &'a SpscReceiver<T>,
+}
+
+impl<'a, T> Iterator for SpscIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl<'a, T> fmt::Debug for SpscIter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("SpscIter { .. }")
+    }
+}
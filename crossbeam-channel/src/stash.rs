@@ -0,0 +1,162 @@
+//! Receiver adapter that waits for a matching message and keeps the others for later.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+use channel::Receiver;
+use err::{RecvError, RecvTimeoutError, TryRecvError};
+
+/// A receiver that can wait for a specific message while keeping the ones that arrive before it.
+///
+/// Channels have no way of putting a message back, so messages skipped by [`wait_for`] are kept
+/// in a stash inside this wrapper. Stashed messages are delivered before any new messages from
+/// the channel by subsequent receive operations, in the order they were received, so wrapping a
+/// receiver never loses or reorders messages apart from those taken out by [`wait_for`].
+///
+/// This is useful for correlating requests and responses, where the response with a particular id
+/// is awaited while responses to other requests keep arriving.
+///
+/// [`wait_for`]: struct.StashReceiver.html#method.wait_for
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{unbounded, StashReceiver};
+///
+/// let (s, r) = unbounded();
+/// let mut r = StashReceiver::new(r);
+///
+/// s.send((1, "first")).unwrap();
+/// s.send((2, "second")).unwrap();
+/// s.send((3, "third")).unwrap();
+///
+/// assert_eq!(r.wait_for(|&(id, _)| id == 2), Ok((2, "second")));
+///
+/// // The skipped message comes first.
+/// assert_eq!(r.recv(), Ok((1, "first")));
+/// assert_eq!(r.recv(), Ok((3, "third")));
+/// ```
+pub struct StashReceiver<T> {
+    /// The underlying receiver.
+    receiver: Receiver<T>,
+
+    /// Messages received from the channel but not returned yet.
+    stash: VecDeque<T>,
+}
+
+impl<T> StashReceiver<T> {
+    /// Creates a stashing receiver reading messages from `receiver`.
+    pub fn new(receiver: Receiver<T>) -> StashReceiver<T> {
+        StashReceiver {
+            receiver,
+            stash: VecDeque::new(),
+        }
+    }
+
+    /// Blocks the current thread until a message satisfying `pred` is received, and returns it.
+    ///
+    /// The stash is searched first, and the earliest matching message in it is returned. Otherwise,
+    /// messages are received from the channel, and those that don't satisfy `pred` are appended to
+    /// the stash.
+    ///
+    /// If the channel is empty and disconnected before a matching message arrives, an error is
+    /// returned. Any messages received in the meantime stay in the stash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{unbounded, StashReceiver};
+    ///
+    /// let (s, r) = unbounded();
+    /// let mut r = StashReceiver::new(r);
+    ///
+    /// thread::spawn(move || {
+    ///     for i in 0..5 {
+    ///         s.send(i).unwrap();
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(r.wait_for(|&x| x == 3), Ok(3));
+    /// assert_eq!(r.stashed(), 3);
+    /// ```
+    pub fn wait_for<F>(&mut self, mut pred: F) -> Result<T, RecvError>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        if let Some(i) = self.stash.iter().position(&mut pred) {
+            return Ok(self.stash.remove(i).unwrap());
+        }
+
+        loop {
+            let msg = self.receiver.recv()?;
+            if pred(&msg) {
+                return Ok(msg);
+            }
+            self.stash.push_back(msg);
+        }
+    }
+
+    /// Blocks the current thread until a message is received, taking stashed messages first.
+    ///
+    /// This method behaves just like [`Receiver::recv`], except it returns the earliest stashed
+    /// message if there is one.
+    ///
+    /// [`Receiver::recv`]: struct.Receiver.html#method.recv
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        match self.stash.pop_front() {
+            Some(msg) => Ok(msg),
+            None => self.receiver.recv(),
+        }
+    }
+
+    /// Waits for a message to be received for a limited time, taking stashed messages first.
+    ///
+    /// This method behaves just like [`Receiver::recv_timeout`], except it returns the earliest
+    /// stashed message if there is one.
+    ///
+    /// [`Receiver::recv_timeout`]: struct.Receiver.html#method.recv_timeout
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        match self.stash.pop_front() {
+            Some(msg) => Ok(msg),
+            None => self.receiver.recv_timeout(timeout),
+        }
+    }
+
+    /// Attempts to receive a message without blocking, taking stashed messages first.
+    ///
+    /// This method behaves just like [`Receiver::try_recv`], except it returns the earliest
+    /// stashed message if there is one.
+    ///
+    /// [`Receiver::try_recv`]: struct.Receiver.html#method.try_recv
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        match self.stash.pop_front() {
+            Some(msg) => Ok(msg),
+            None => self.receiver.try_recv(),
+        }
+    }
+
+    /// Returns the number of stashed messages.
+    pub fn stashed(&self) -> usize {
+        self.stash.len()
+    }
+
+    /// Returns a reference to the underlying receiver.
+    pub fn get_ref(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+
+    /// Returns the underlying receiver and the stashed messages.
+    pub fn into_inner(self) -> (Receiver<T>, VecDeque<T>) {
+        (self.receiver, self.stash)
+    }
+}
+
+impl<T> fmt::Debug for StashReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StashReceiver")
+            .field("stashed", &self.stash.len())
+            .finish()
+    }
+}
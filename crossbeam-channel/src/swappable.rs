@@ -0,0 +1,311 @@
+//! Receiver whose backing channel can be replaced while it is in use.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use channel::{bounded, Receiver, Sender};
+use err::{RecvError, TryRecvError};
+use select::Select;
+
+/// Creates a receiver whose backing channel can be swapped out, along with a handle for swapping.
+///
+/// The returned [`SwappableReceiver`] receives messages from `receiver` until [`SwapHandle::swap`]
+/// redirects it to another channel. The consumer keeps calling the same receive methods across the
+/// swap and doesn't need to know that it happened, which allows channels to be reconfigured
+/// without restarting the consumer. Receive operations blocked at the moment of the swap pick up
+/// the new channel right away, unless the old channel still has to be drained.
+///
+/// [`SwappableReceiver`]: struct.SwappableReceiver.html
+/// [`SwapHandle::swap`]: struct.SwapHandle.html#method.swap
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{swappable, unbounded, SwapMode};
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+/// let (r, handle) = swappable(r1);
+///
+/// s1.send(1).unwrap();
+/// s1.send(2).unwrap();
+/// s2.send(10).unwrap();
+/// assert_eq!(r.recv(), Ok(1));
+///
+/// // The message left in the first channel is received before switching.
+/// handle.swap(r2, SwapMode::Drain);
+/// assert_eq!(r.recv(), Ok(2));
+///
+/// // The first channel is drained until its senders are gone.
+/// drop(s1);
+/// assert_eq!(r.recv(), Ok(10));
+/// ```
+pub fn swappable<T>(receiver: Receiver<T>) -> (SwappableReceiver<T>, SwapHandle<T>) {
+    let shared = Arc::new(Shared {
+        pending: Mutex::new(Vec::new()),
+        is_pending: AtomicBool::new(false),
+    });
+    let (signal_s, signal_r) = bounded(1);
+
+    let r = SwappableReceiver {
+        shared: shared.clone(),
+        state: RefCell::new(State {
+            current: receiver,
+            draining: VecDeque::new(),
+            signal: Some(signal_r),
+        }),
+    };
+    let handle = SwapHandle {
+        shared,
+        signal: signal_s,
+    };
+    (r, handle)
+}
+
+/// What happens to messages still buffered in the old channel when a receiver is swapped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapMode {
+    /// Messages buffered in the old channel are received first, before any message from the new
+    /// channel.
+    ///
+    /// The old channel is drained until it is empty and disconnected, at which point it is
+    /// dropped. Until then, the receiver keeps waiting for messages from the old channel, even if
+    /// it is empty for a while, so no message sent into it is lost.
+    Drain,
+
+    /// The receiver switches to the new channel at once and drops the old one, along with any
+    /// old channels that are still being drained.
+    ///
+    /// Messages buffered in the old channel are not received. They are dropped with the channel
+    /// unless it has other receivers.
+    Discard,
+}
+
+/// State shared by the receiver and its swap handles.
+struct Shared<T> {
+    /// Swaps that the receiver hasn't applied yet, in order.
+    pending: Mutex<Vec<(Receiver<T>, SwapMode)>>,
+
+    /// Equals `true` if `pending` might not be empty.
+    is_pending: AtomicBool,
+}
+
+/// The channels a receiver reads from.
+struct State<T> {
+    /// The channel new messages are received from.
+    current: Receiver<T>,
+
+    /// Old channels with messages to be received before those in `current`.
+    draining: VecDeque<Receiver<T>>,
+
+    /// Woken up by swaps, or `None` once all swap handles have been dropped.
+    signal: Option<Receiver<()>>,
+}
+
+/// The outcome of a blocking wait.
+enum Wait<T> {
+    /// A message was received.
+    Message(T),
+
+    /// The channel being read from is empty and disconnected.
+    Disconnected,
+
+    /// Something may have been swapped.
+    Swapped,
+
+    /// All swap handles have been dropped.
+    HandlesDropped,
+}
+
+/// A receiver created by [`swappable`], whose backing channel can be replaced.
+///
+/// It can be moved to another thread, but not shared between threads.
+///
+/// [`swappable`]: fn.swappable.html
+pub struct SwappableReceiver<T> {
+    /// State shared with the swap handles.
+    shared: Arc<Shared<T>>,
+
+    /// The channels this receiver reads from.
+    state: RefCell<State<T>>,
+}
+
+impl<T> SwappableReceiver<T> {
+    /// Attempts to receive a message without blocking.
+    ///
+    /// Pending swaps are applied first. Then messages are taken from old channels that are being
+    /// drained, and finally from the current channel. If an old channel is empty but not
+    /// disconnected yet, [`TryRecvError::Empty`] is returned without looking at the channels after
+    /// it.
+    ///
+    /// [`TryRecvError::Empty`]: enum.TryRecvError.html#variant.Empty
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.state.borrow_mut();
+        self.apply_swaps(&mut state);
+
+        loop {
+            let res = match state.draining.front() {
+                None => break,
+                Some(r) => r.try_recv(),
+            };
+            match res {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Empty) => return Err(TryRecvError::Empty),
+                Err(TryRecvError::Disconnected) => {
+                    state.draining.pop_front();
+                }
+            }
+        }
+
+        state.current.try_recv()
+    }
+
+    /// Blocks the current thread until a message is received or the current channel is empty and
+    /// disconnected.
+    ///
+    /// Old channels that are being drained are waited on first, until each of them is empty and
+    /// disconnected.
+    ///
+    /// If the receiver is swapped while this method is blocked, it switches to the new channel
+    /// and keeps waiting there, once the channels it still has to drain are done.
+    ///
+    /// Note that an error is returned as soon as the current channel is disconnected, unless a
+    /// swap is already pending. The receiver doesn't wait for a swap that might come later.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        loop {
+            match self.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    if !self.shared.is_pending.load(Ordering::SeqCst) {
+                        return Err(RecvError);
+                    }
+                    continue;
+                }
+            }
+
+            let mut state = self.state.borrow_mut();
+            match self.wait(&state) {
+                Wait::Message(msg) => return Ok(msg),
+                Wait::Disconnected | Wait::Swapped => {}
+                // Nothing can be swapped anymore, so stop waiting for swaps.
+                Wait::HandlesDropped => state.signal = None,
+            }
+        }
+    }
+
+    /// Blocks until a message arrives in the channel being read from or a swap happens.
+    ///
+    /// The channel being read from is the first old channel that is still being drained, or the
+    /// current channel if there is none.
+    fn wait(&self, state: &State<T>) -> Wait<T> {
+        let receiver = state.draining.front().unwrap_or(&state.current);
+
+        let signal = match state.signal {
+            Some(ref signal) => signal,
+            None => {
+                return match receiver.recv() {
+                    Ok(msg) => Wait::Message(msg),
+                    Err(_) => Wait::Disconnected,
+                }
+            }
+        };
+
+        let mut sel = Select::new();
+        let index = sel.recv(receiver);
+        sel.recv(signal);
+
+        let oper = sel.select();
+        if oper.index() == index {
+            match oper.recv(receiver) {
+                Ok(msg) => Wait::Message(msg),
+                Err(_) => Wait::Disconnected,
+            }
+        } else {
+            match oper.recv(signal) {
+                Ok(()) => Wait::Swapped,
+                Err(_) => Wait::HandlesDropped,
+            }
+        }
+    }
+
+    /// Applies pending swaps in the order they were made.
+    fn apply_swaps(&self, state: &mut State<T>) {
+        if !self.shared.is_pending.swap(false, Ordering::SeqCst) {
+            return;
+        }
+
+        let pending = mem::replace(&mut *self.shared.pending.lock().unwrap(), Vec::new());
+        for (receiver, mode) in pending {
+            let old = mem::replace(&mut state.current, receiver);
+            match mode {
+                SwapMode::Drain => state.draining.push_back(old),
+                SwapMode::Discard => state.draining.clear(),
+            }
+        }
+    }
+
+    /// Returns `true` if no messages are ready in the old channels or the current channel.
+    ///
+    /// Pending swaps are not taken into account.
+    pub fn is_empty(&self) -> bool {
+        let state = self.state.borrow();
+        state.draining.iter().all(|r| r.is_empty()) && state.current.is_empty()
+    }
+}
+
+impl<T> fmt::Debug for SwappableReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("SwappableReceiver { .. }")
+    }
+}
+
+/// A handle for swapping the channel of a [`SwappableReceiver`].
+///
+/// [`SwappableReceiver`]: struct.SwappableReceiver.html
+pub struct SwapHandle<T> {
+    /// State shared with the receiver.
+    shared: Arc<Shared<T>>,
+
+    /// Wakes up the receiver after a swap.
+    signal: Sender<()>,
+}
+
+impl<T> SwapHandle<T> {
+    /// Redirects the receiver to a new channel.
+    ///
+    /// The receiver switches before its next receive operation, or right away if it is blocked.
+    /// A receive operation already under way completes on the old channel. `mode` decides
+    /// whether messages buffered in the old channel are still received first. See [`SwapMode`]
+    /// for details.
+    ///
+    /// Swaps made in a row are applied in order, so the receiver ends up on the last channel.
+    ///
+    /// [`SwapMode`]: enum.SwapMode.html
+    pub fn swap(&self, receiver: Receiver<T>, mode: SwapMode) {
+        self.shared.pending.lock().unwrap().push((receiver, mode));
+        self.shared.is_pending.store(true, Ordering::SeqCst);
+
+        // The receiver only needs one wakeup, no matter how many swaps are pending.
+        let _ = self.signal.try_send(());
+    }
+}
+
+impl<T> Clone for SwapHandle<T> {
+    fn clone(&self) -> Self {
+        SwapHandle {
+            shared: self.shared.clone(),
+            signal: self.signal.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for SwapHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("SwapHandle { .. }")
+    }
+}
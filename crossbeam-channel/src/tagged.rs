@@ -0,0 +1,161 @@
+//! Channel whose messages are tagged with the sender that sent them.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use channel::{unbounded, Receiver, Sender};
+use err::{SendError, SendTimeoutError, TrySendError};
+
+/// Identifies the [`TaggedSender`] a message was sent by.
+///
+/// Every tagged sender has its own tag, which stays the same for its whole lifetime. Tags are
+/// assigned in increasing order, starting from 0 for the sender returned by [`tagged_unbounded`],
+/// and a new one is assigned to each clone.
+///
+/// [`TaggedSender`]: struct.TaggedSender.html
+/// [`tagged_unbounded`]: fn.tagged_unbounded.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SenderTag(usize);
+
+impl SenderTag {
+    /// Returns the tag as a number.
+    pub fn id(&self) -> usize {
+        self.0
+    }
+}
+
+/// The sending side of a channel created by [`tagged_unbounded`].
+///
+/// [`tagged_unbounded`]: fn.tagged_unbounded.html
+pub struct TaggedSender<T> {
+    /// The underlying sender.
+    sender: Sender<(SenderTag, T)>,
+
+    /// The tag attached to every message sent by this sender.
+    tag: SenderTag,
+
+    /// The next tag to assign, shared by all clones.
+    next_tag: Arc<AtomicUsize>,
+}
+
+/// Creates an unbounded channel whose messages carry the tag of the sender that sent them.
+///
+/// Each [`TaggedSender`] has a distinct [`SenderTag`], and every message it sends is received
+/// paired with that tag. This tells producers apart, e.g. to route replies back to them, without
+/// putting an id into every message type. Cloning a sender assigns a new tag to the clone.
+///
+/// [`TaggedSender`]: struct.TaggedSender.html
+/// [`SenderTag`]: struct.SenderTag.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::tagged_unbounded;
+///
+/// let (s1, r) = tagged_unbounded();
+/// let s2 = s1.clone();
+/// let (t1, t2) = (s1.tag(), s2.tag());
+/// assert_ne!(t1, t2);
+///
+/// thread::spawn(move || s1.send("from the first sender").unwrap());
+/// thread::spawn(move || s2.send("from the second sender").unwrap());
+///
+/// for (tag, msg) in r.iter() {
+///     if tag == t1 {
+///         assert_eq!(msg, "from the first sender");
+///     } else {
+///         assert_eq!(tag, t2);
+///         assert_eq!(msg, "from the second sender");
+///     }
+/// }
+/// ```
+pub fn tagged_unbounded<T>() -> (TaggedSender<T>, Receiver<(SenderTag, T)>) {
+    let (s, r) = unbounded();
+    let s = TaggedSender {
+        sender: s,
+        tag: SenderTag(0),
+        next_tag: Arc::new(AtomicUsize::new(1)),
+    };
+    (s, r)
+}
+
+impl<T> TaggedSender<T> {
+    /// Returns the tag attached to messages sent by this sender.
+    pub fn tag(&self) -> SenderTag {
+        self.tag
+    }
+
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// This method behaves just like [`Sender::try_send`]. The returned error contains the
+    /// message without the tag.
+    ///
+    /// [`Sender::try_send`]: struct.Sender.html#method.try_send
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.sender
+            .try_send((self.tag, msg))
+            .map_err(|err| match err {
+                TrySendError::Full((_, msg)) => TrySendError::Full(msg),
+                TrySendError::Disconnected((_, msg)) => TrySendError::Disconnected(msg),
+            })
+    }
+
+    /// Sends a message into the channel, tagged with this sender's tag.
+    ///
+    /// This method behaves just like [`Sender::send`]. The returned error contains the message
+    /// without the tag.
+    ///
+    /// [`Sender::send`]: struct.Sender.html#method.send
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.sender
+            .send((self.tag, msg))
+            .map_err(|SendError((_, msg))| SendError(msg))
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    ///
+    /// This method behaves just like [`Sender::send_timeout`]. The returned error contains the
+    /// message without the tag.
+    ///
+    /// [`Sender::send_timeout`]: struct.Sender.html#method.send_timeout
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.sender
+            .send_timeout((self.tag, msg), timeout)
+            .map_err(|err| match err {
+                SendTimeoutError::Timeout((_, msg)) => SendTimeoutError::Timeout(msg),
+                SendTimeoutError::Disconnected((_, msg)) => SendTimeoutError::Disconnected(msg),
+            })
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.sender.is_empty()
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.sender.len()
+    }
+}
+
+impl<T> Clone for TaggedSender<T> {
+    /// Creates another sender for the same channel with a new tag.
+    fn clone(&self) -> Self {
+        TaggedSender {
+            sender: self.sender.clone(),
+            tag: SenderTag(self.next_tag.fetch_add(1, Ordering::Relaxed)),
+            next_tag: self.next_tag.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for TaggedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TaggedSender")
+            .field("tag", &self.tag.0)
+            .finish()
+    }
+}
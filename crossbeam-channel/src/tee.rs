@@ -0,0 +1,169 @@
+//! Duplicating a stream of messages into two receivers.
+
+use std::thread;
+
+use channel::{bounded, unbounded, Receiver, Sender};
+
+/// How the pump delivers messages into the outputs.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Delivery {
+    /// Block while an output is full.
+    Blocking,
+
+    /// Drop the message for an output that is full.
+    Lossy,
+}
+
+/// Spawns a thread forwarding every message from `input` into both `outputs`.
+///
+/// The thread exits once `input` is disconnected and empty, or once it finds both outputs dropped.
+fn spawn_pump<T>(input: Receiver<T>, a: Sender<T>, b: Sender<T>, delivery: Delivery)
+where
+    T: Clone + Send + 'static,
+{
+    let mut outputs = [Some(a), Some(b)];
+
+    thread::Builder::new()
+        .name("crossbeam-tee".to_string())
+        .spawn(move || {
+            for msg in input.iter() {
+                for slot in outputs.iter_mut() {
+                    let disconnected = match slot {
+                        None => continue,
+                        Some(s) => match delivery {
+                            Delivery::Blocking => s.send(msg.clone()).is_err(),
+                            Delivery::Lossy => match s.try_send(msg.clone()) {
+                                Ok(()) => false,
+                                Err(err) => err.is_disconnected(),
+                            },
+                        },
+                    };
+
+                    // Stop sending into outputs whose receivers are gone.
+                    if disconnected {
+                        *slot = None;
+                    }
+                }
+
+                if outputs.iter().all(|s| s.is_none()) {
+                    return;
+                }
+            }
+        })
+        .unwrap();
+}
+
+/// Duplicates a stream of messages into two receivers.
+///
+/// A pump thread receives every message from `input` and sends a clone of it into each of the
+/// returned receivers. The outputs are unbounded, so a slow consumer never holds back the other
+/// one or the source, and no message is ever dropped. Messages for a slow consumer pile up in
+/// memory instead. Use [`tee_bounded`] or [`tee_lossy`] to limit that.
+///
+/// The outputs get disconnected once `input` is disconnected and all of its messages have been
+/// forwarded. If one output is dropped, the other one keeps receiving messages. The pump thread
+/// exits when `input` is disconnected, or when a message arrives after both outputs have been
+/// dropped, which in turn drops `input`.
+///
+/// [`tee_bounded`]: fn.tee_bounded.html
+/// [`tee_lossy`]: fn.tee_lossy.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{tee, unbounded};
+///
+/// let (s, r) = unbounded();
+/// let (work, audit) = tee(r);
+///
+/// s.send("hello").unwrap();
+/// drop(s);
+///
+/// assert_eq!(work.iter().collect::<Vec<_>>(), ["hello"]);
+/// assert_eq!(audit.iter().collect::<Vec<_>>(), ["hello"]);
+/// ```
+pub fn tee<T>(input: Receiver<T>) -> (Receiver<T>, Receiver<T>)
+where
+    T: Clone + Send + 'static,
+{
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    spawn_pump(input, s1, s2, Delivery::Blocking);
+    (r1, r2)
+}
+
+/// Duplicates a stream of messages into two bounded receivers with backpressure.
+///
+/// This is just like [`tee`], except each output is a bounded channel of capacity `cap`. When one
+/// output is full, the pump thread blocks until its consumer catches up, so a slow consumer slows
+/// down the other consumer and, through `input`, the source as well. No message is ever dropped.
+///
+/// Because the outputs depend on each other, they should be consumed concurrently. Draining one
+/// output while the other one is left full deadlocks once the pump blocks.
+///
+/// [`tee`]: fn.tee.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{bounded, tee_bounded};
+///
+/// let (s, r) = bounded(0);
+/// let (work, audit) = tee_bounded(r, 1);
+///
+/// thread::spawn(move || {
+///     for i in 0..5 {
+///         s.send(i).unwrap();
+///     }
+/// });
+///
+/// let auditor = thread::spawn(move || audit.iter().collect::<Vec<_>>());
+///
+/// assert_eq!(work.iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+/// assert_eq!(auditor.join().unwrap(), [0, 1, 2, 3, 4]);
+/// ```
+pub fn tee_bounded<T>(input: Receiver<T>, cap: usize) -> (Receiver<T>, Receiver<T>)
+where
+    T: Clone + Send + 'static,
+{
+    let (s1, r1) = bounded(cap);
+    let (s2, r2) = bounded(cap);
+    spawn_pump(input, s1, s2, Delivery::Blocking);
+    (r1, r2)
+}
+
+/// Duplicates a stream of messages into two bounded receivers, dropping messages for a slow one.
+///
+/// This is just like [`tee`], except each output is a bounded channel of capacity `cap`. When one
+/// output is full, the message is dropped for that output only, so a slow consumer misses
+/// messages but never holds back the other consumer or the source.
+///
+/// [`tee`]: fn.tee.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{tee_lossy, unbounded};
+///
+/// let (s, r) = unbounded();
+/// let (work, audit) = tee_lossy(r, 2);
+///
+/// for i in 0..5 {
+///     s.send(i).unwrap();
+///     assert_eq!(work.recv(), Ok(i));
+/// }
+/// drop(s);
+///
+/// // Nobody read from `audit`, so it only got the first two messages.
+/// assert_eq!(audit.iter().collect::<Vec<_>>(), [0, 1]);
+/// ```
+pub fn tee_lossy<T>(input: Receiver<T>, cap: usize) -> (Receiver<T>, Receiver<T>)
+where
+    T: Clone + Send + 'static,
+{
+    let (s1, r1) = bounded(cap);
+    let (s2, r2) = bounded(cap);
+    spawn_pump(input, s1, s2, Delivery::Lossy);
+    (r1, r2)
+}
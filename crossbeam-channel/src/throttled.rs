@@ -0,0 +1,152 @@
+//! Sender wrapper that slows down producers as the channel fills up.
+
+use std::cmp;
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+use channel::Sender;
+use err::{SendError, SendTimeoutError, TrySendError};
+
+/// The delay in nanoseconds introduced by each message in the channel above the high watermark.
+const NANOS_PER_MESSAGE: u32 = 10_000;
+
+/// The longest delay before a send operation, unless set with `with_max_delay`.
+const DEFAULT_MAX_DELAY_MILLIS: u64 = 10;
+
+/// A sender that delays send operations while the channel holds too many messages.
+///
+/// Created by [`Sender::throttled`]. See its documentation for more.
+///
+/// [`Sender::throttled`]: struct.Sender.html#method.throttled
+pub struct ThrottledSender<T> {
+    /// The underlying sender.
+    sender: Sender<T>,
+
+    /// The number of messages above which send operations get delayed.
+    high_watermark: usize,
+
+    /// The longest delay before a send operation.
+    max_delay: Duration,
+}
+
+impl<T> ThrottledSender<T> {
+    /// Creates a throttled sender.
+    pub(crate) fn new(sender: Sender<T>, high_watermark: usize) -> ThrottledSender<T> {
+        ThrottledSender {
+            sender,
+            high_watermark,
+            max_delay: Duration::from_millis(DEFAULT_MAX_DELAY_MILLIS),
+        }
+    }
+
+    /// Sets the longest delay before a send operation.
+    ///
+    /// The delay grows with the number of messages above the high watermark, but never exceeds
+    /// `max_delay`, so a large backlog can't stall senders for long. The default is 10
+    /// milliseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// let s = s.throttled(0).with_max_delay(Duration::from_millis(1));
+    /// assert_eq!(s.max_delay(), Duration::from_millis(1));
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(r.recv(), Ok(1));
+    /// ```
+    pub fn with_max_delay(mut self, max_delay: Duration) -> ThrottledSender<T> {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sleeps for a duration proportional to the number of messages above the high watermark,
+    /// capped at the maximum delay.
+    fn throttle(&self) {
+        let len = self.sender.len();
+
+        if len > self.high_watermark {
+            let over = cmp::min(len - self.high_watermark, u32::max_value() as usize) as u32;
+            let delay = Duration::new(0, NANOS_PER_MESSAGE)
+                .checked_mul(over)
+                .map_or(self.max_delay, |d| cmp::min(d, self.max_delay));
+            thread::sleep(delay);
+        }
+    }
+
+    /// Sends a message into the channel, first sleeping if the channel is above the high
+    /// watermark.
+    ///
+    /// Apart from the delay, this method behaves just like [`Sender::send`].
+    ///
+    /// [`Sender::send`]: struct.Sender.html#method.send
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.throttle();
+        self.sender.send(msg)
+    }
+
+    /// Sends a message into the channel, first sleeping if the channel is above the high
+    /// watermark, and then waiting for at most `timeout` for the send to proceed.
+    ///
+    /// Apart from the delay, this method behaves just like [`Sender::send_timeout`].
+    ///
+    /// [`Sender::send_timeout`]: struct.Sender.html#method.send_timeout
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.throttle();
+        self.sender.send_timeout(msg, timeout)
+    }
+
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// This method is never delayed and behaves just like [`Sender::try_send`].
+    ///
+    /// [`Sender::try_send`]: struct.Sender.html#method.try_send
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.sender.try_send(msg)
+    }
+
+    /// Returns the high watermark.
+    pub fn high_watermark(&self) -> usize {
+        self.high_watermark
+    }
+
+    /// Returns the longest delay before a send operation.
+    pub fn max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    /// Returns the underlying sender.
+    pub fn sender(&self) -> &Sender<T> {
+        &self.sender
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.sender.is_empty()
+    }
+}
+
+impl<T> Clone for ThrottledSender<T> {
+    fn clone(&self) -> Self {
+        ThrottledSender {
+            sender: self.sender.clone(),
+            high_watermark: self.high_watermark,
+            max_delay: self.max_delay,
+        }
+    }
+}
+
+impl<T> fmt::Debug for ThrottledSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ThrottledSender { .. }")
+    }
+}
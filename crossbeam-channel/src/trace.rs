@@ -0,0 +1,33 @@
+//! Instrumentation with `tracing`.
+//!
+//! With the `tracing` feature enabled, these macros forward to `tracing` and record spans and
+//! events at the `TRACE` level under the `crossbeam_channel` target. Without it, they expand to
+//! nothing, so their arguments are never evaluated and instrumentation costs nothing.
+
+/// Enters a span that lasts until the end of the enclosing block.
+#[cfg(feature = "tracing")]
+macro_rules! trace_span {
+    ($($args:tt)*) => {
+        let _span = ::tracing::trace_span!(target: "crossbeam_channel", $($args)*).entered();
+    };
+}
+
+/// Enters a span that lasts until the end of the enclosing block.
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_span {
+    ($($args:tt)*) => {};
+}
+
+/// Records an event.
+#[cfg(feature = "tracing")]
+macro_rules! trace_event {
+    ($($args:tt)*) => {
+        ::tracing::trace!(target: "crossbeam_channel", $($args)*);
+    };
+}
+
+/// Records an event.
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_event {
+    ($($args:tt)*) => {};
+}
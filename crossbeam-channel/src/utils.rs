@@ -9,6 +9,58 @@ use std::time::{Duration, Instant};
 
 use crossbeam_utils::Backoff;
 
+thread_local! {
+    /// State of the thread-local random number generator.
+    static RNG: Cell<Wrapping<u32>> = Cell::new(Wrapping(1406868647));
+}
+
+/// Advances the random number generator and returns the next random number.
+fn next_u32(rng: &Cell<Wrapping<u32>>) -> u32 {
+    // This is the 32-bit variant of Xorshift.
+    //
+    // Source: https://en.wikipedia.org/wiki/Xorshift
+    let mut x = rng.get();
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    rng.set(x);
+    x.0
+}
+
+/// Advances the random number generator and returns a random number in the range `0..n`.
+///
+/// Every number in the range is equally likely, which matters for fairness of select.
+fn random_below(rng: &Cell<Wrapping<u32>>, n: usize) -> usize {
+    debug_assert!(n > 0 && n as u64 <= 1 << 32);
+    let n = n as u64;
+
+    // This is a fast alternative to `x % n` that maps `x` onto `0..n` by multiplying and taking
+    // the upper 32 bits. Some results are produced by one more `x` than others, so products whose
+    // lower 32 bits fall below `2^32 % n` are rejected to make the distribution exactly uniform.
+    // Rejection is rare, and the division computing the threshold is only needed when it might
+    // happen.
+    //
+    // Author: Daniel Lemire
+    // Source: https://arxiv.org/abs/1805.10941
+    let mut m = u64::from(next_u32(rng)) * n;
+    if (m as u32 as u64) < n {
+        let threshold = (1u64 << 32) % n;
+        while (m as u32 as u64) < threshold {
+            m = u64::from(next_u32(rng)) * n;
+        }
+    }
+    (m >> 32) as usize
+}
+
+/// Returns a random number in the range `0..n`.
+pub fn random(n: usize) -> usize {
+    if n <= 1 {
+        return 0;
+    }
+
+    RNG.try_with(|rng| random_below(rng, n)).unwrap_or(0)
+}
+
 /// Randomly shuffles a slice.
 pub fn shuffle<T>(v: &mut [T]) {
     let len = v.len();
@@ -16,35 +68,26 @@ pub fn shuffle<T>(v: &mut [T]) {
         return;
     }
 
-    thread_local! {
-        static RNG: Cell<Wrapping<u32>> = Cell::new(Wrapping(1406868647));
-    }
-
     let _ = RNG.try_with(|rng| {
         for i in 1..len {
-            // This is the 32-bit variant of Xorshift.
-            //
-            // Source: https://en.wikipedia.org/wiki/Xorshift
-            let mut x = rng.get();
-            x ^= x << 13;
-            x ^= x >> 17;
-            x ^= x << 5;
-            rng.set(x);
-
-            let x = x.0;
-            let n = i + 1;
-
-            // This is a fast alternative to `let j = x % n`.
-            //
-            // Author: Daniel Lemire
-            // Source: https://lemire.me/blog/2016/06/27/a-fast-alternative-to-the-modulo-reduction/
-            let j = ((x as u64).wrapping_mul(n as u64) >> 32) as u32 as usize;
-
+            let j = random_below(rng, i + 1);
             v.swap(i, j);
         }
     });
 }
 
+/// Returns a nonzero number that identifies the current thread among all running threads.
+///
+/// Unlike a `ThreadId`, it fits into an atomic integer.
+pub fn thread_token() -> usize {
+    thread_local! {
+        /// A variable whose address identifies the thread.
+        static TOKEN: u8 = 0;
+    }
+
+    TOKEN.with(|token| token as *const u8 as usize)
+}
+
 /// Sleeps until the deadline, or forever if the deadline isn't specified.
 pub fn sleep_until(deadline: Option<Instant>) {
     loop {
@@ -61,6 +104,12 @@ pub fn sleep_until(deadline: Option<Instant>) {
     }
 }
 
+/// A boxed callback that takes no arguments and can be sent to another thread.
+pub type BoxedCallback = Box<FnMut() + Send>;
+
+/// A boxed handler that receives messages left in a channel when the last receiver is dropped.
+pub type DropHandler<T> = Box<FnMut(T) + Send>;
+
 /// A simple spinlock.
 pub struct Spinlock<T> {
     flag: AtomicBool,
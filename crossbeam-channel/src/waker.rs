@@ -1,6 +1,15 @@
 //! Waking mechanism for threads blocked on channel operations.
 
+use std::collections::VecDeque;
+#[cfg(all(unix, feature = "os_poll"))]
+use std::fs::File;
+#[cfg(all(unix, feature = "os_poll"))]
+use std::io::{self, Write};
+#[cfg(all(unix, feature = "os_poll"))]
+use std::os::unix::io::{FromRawFd, IntoRawFd, RawFd};
 use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(all(unix, feature = "os_poll"))]
+use std::sync::RwLock;
 use std::thread::{self, ThreadId};
 
 use context::Context;
@@ -22,10 +31,10 @@ pub struct Entry {
 /// A queue of threads blocked on channel operations.
 ///
 /// This data structure is used by threads to register blocking operations and get woken up once
-/// an operation becomes ready.
+/// an operation becomes ready. Select operations are woken up in the order they were registered.
 pub struct Waker {
-    /// A list of select operations.
-    selectors: Vec<Entry>,
+    /// A queue of select operations.
+    selectors: VecDeque<Entry>,
 
     /// A list of operations waiting to be ready.
     observers: Vec<Entry>,
@@ -36,11 +45,17 @@ impl Waker {
     #[inline]
     pub fn new() -> Self {
         Waker {
-            selectors: Vec::new(),
+            selectors: VecDeque::new(),
             observers: Vec::new(),
         }
     }
 
+    /// Returns `true` if there is nothing to notify.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty() && self.observers.is_empty()
+    }
+
     /// Registers a select operation.
     #[inline]
     pub fn register(&mut self, oper: Operation, cx: &Context) {
@@ -50,13 +65,23 @@ impl Waker {
     /// Registers a select operation and a packet.
     #[inline]
     pub fn register_with_packet(&mut self, oper: Operation, packet: usize, cx: &Context) {
-        self.selectors.push(Entry {
+        self.selectors.push_back(Entry {
             oper,
             packet,
             cx: cx.clone(),
         });
     }
 
+    /// Registers a select operation at the front of the queue, so that it is woken up first.
+    #[inline]
+    pub fn register_front(&mut self, oper: Operation, cx: &Context) {
+        self.selectors.push_front(Entry {
+            oper,
+            packet: 0,
+            cx: cx.clone(),
+        });
+    }
+
     /// Unregisters a select operation.
     #[inline]
     pub fn unregister(&mut self, oper: Operation) -> Option<Entry> {
@@ -66,8 +91,7 @@ impl Waker {
             .enumerate()
             .find(|&(_, entry)| entry.oper == oper)
         {
-            let entry = self.selectors.remove(i);
-            Some(entry)
+            self.selectors.remove(i)
         } else {
             None
         }
@@ -96,7 +120,7 @@ impl Waker {
 
                         // Remove the entry from the queue to keep it clean and improve
                         // performance.
-                        entry = Some(self.selectors.remove(i));
+                        entry = self.selectors.remove(i);
                         break;
                     }
                 }
@@ -181,6 +205,10 @@ pub struct SyncWaker {
 
     /// `true` if the waker is empty.
     is_empty: AtomicBool,
+
+    /// File descriptors signalled when the channel becomes ready.
+    #[cfg(all(unix, feature = "os_poll"))]
+    fds: ReadyFds,
 }
 
 impl SyncWaker {
@@ -190,6 +218,8 @@ impl SyncWaker {
         SyncWaker {
             inner: Spinlock::new(Waker::new()),
             is_empty: AtomicBool::new(true),
+            #[cfg(all(unix, feature = "os_poll"))]
+            fds: ReadyFds::new(),
         }
     }
 
@@ -198,10 +228,15 @@ impl SyncWaker {
     pub fn register(&self, oper: Operation, cx: &Context) {
         let mut inner = self.inner.lock();
         inner.register(oper, cx);
-        self.is_empty.store(
-            inner.selectors.is_empty() && inner.observers.is_empty(),
-            Ordering::SeqCst,
-        );
+        self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
+    }
+
+    /// Registers the current thread with an operation at the front of the queue.
+    #[inline]
+    pub fn register_front(&self, oper: Operation, cx: &Context) {
+        let mut inner = self.inner.lock();
+        inner.register_front(oper, cx);
+        self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
     }
 
     /// Unregisters an operation previously registered by the current thread.
@@ -209,10 +244,7 @@ impl SyncWaker {
     pub fn unregister(&self, oper: Operation) -> Option<Entry> {
         let mut inner = self.inner.lock();
         let entry = inner.unregister(oper);
-        self.is_empty.store(
-            inner.selectors.is_empty() && inner.observers.is_empty(),
-            Ordering::SeqCst,
-        );
+        self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
         entry
     }
 
@@ -223,11 +255,25 @@ impl SyncWaker {
             let mut inner = self.inner.lock();
             inner.try_select();
             inner.notify();
-            self.is_empty.store(
-                inner.selectors.is_empty() && inner.observers.is_empty(),
-                Ordering::SeqCst,
-            );
+            self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
         }
+
+        #[cfg(all(unix, feature = "os_poll"))]
+        self.fds.notify();
+    }
+
+    /// Selects the operations of all threads (not the current one) and wakes them up.
+    #[inline]
+    pub fn notify_all(&self) {
+        if !self.is_empty.load(Ordering::SeqCst) {
+            let mut inner = self.inner.lock();
+            while inner.try_select().is_some() {}
+            inner.notify();
+            self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
+        }
+
+        #[cfg(all(unix, feature = "os_poll"))]
+        self.fds.notify();
     }
 
     /// Registers an operation waiting to be ready.
@@ -235,10 +281,7 @@ impl SyncWaker {
     pub fn watch(&self, oper: Operation, cx: &Context) {
         let mut inner = self.inner.lock();
         inner.watch(oper, cx);
-        self.is_empty.store(
-            inner.selectors.is_empty() && inner.observers.is_empty(),
-            Ordering::SeqCst,
-        );
+        self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
     }
 
     /// Unregisters an operation waiting to be ready.
@@ -246,21 +289,42 @@ impl SyncWaker {
     pub fn unwatch(&self, oper: Operation) {
         let mut inner = self.inner.lock();
         inner.unwatch(oper);
-        self.is_empty.store(
-            inner.selectors.is_empty() && inner.observers.is_empty(),
-            Ordering::SeqCst,
-        );
+        self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
+    }
+
+    /// Registers a file descriptor to be signalled when the channel becomes ready.
+    #[cfg(all(unix, feature = "os_poll"))]
+    #[inline]
+    pub fn watch_fd(&self, fd: RawFd) {
+        self.fds.watch(fd);
+    }
+
+    /// Unregisters a file descriptor.
+    #[cfg(all(unix, feature = "os_poll"))]
+    #[inline]
+    pub fn unwatch_fd(&self, fd: RawFd) {
+        self.fds.unwatch(fd);
+    }
+
+    /// Lets the next notification signal the file descriptors again, once a receive operation has
+    /// found the channel empty.
+    #[cfg(all(unix, feature = "os_poll"))]
+    #[inline]
+    pub fn rearm_fds<F: FnOnce() -> bool>(&self, is_ready: F) {
+        self.fds.rearm(is_ready);
     }
 
     /// Notifies all threads that the channel is disconnected.
     #[inline]
     pub fn disconnect(&self) {
-        let mut inner = self.inner.lock();
-        inner.disconnect();
-        self.is_empty.store(
-            inner.selectors.is_empty() && inner.observers.is_empty(),
-            Ordering::SeqCst,
-        );
+        {
+            let mut inner = self.inner.lock();
+            inner.disconnect();
+            self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
+        }
+
+        #[cfg(all(unix, feature = "os_poll"))]
+        self.fds.signal_all();
     }
 }
 
@@ -271,6 +335,110 @@ impl Drop for SyncWaker {
     }
 }
 
+/// File descriptors signalled when a channel becomes ready for receiving.
+///
+/// To avoid a system call on every send operation, the descriptors are signalled only on the
+/// transition from empty to ready: after a signal, the set is disarmed until a receive operation
+/// finds the channel empty and rearms it. Signals are sent without holding any spinlock.
+#[cfg(all(unix, feature = "os_poll"))]
+pub struct ReadyFds {
+    /// The registered descriptors.
+    fds: RwLock<Vec<RawFd>>,
+
+    /// Equals `true` if any descriptors are registered.
+    has_fds: AtomicBool,
+
+    /// Equals `true` if the next notification should signal the descriptors.
+    armed: AtomicBool,
+}
+
+#[cfg(all(unix, feature = "os_poll"))]
+impl ReadyFds {
+    /// Creates an empty, armed set of descriptors.
+    #[inline]
+    pub fn new() -> Self {
+        ReadyFds {
+            fds: RwLock::new(Vec::new()),
+            has_fds: AtomicBool::new(false),
+            armed: AtomicBool::new(true),
+        }
+    }
+
+    /// Registers a file descriptor.
+    pub fn watch(&self, fd: RawFd) {
+        let mut fds = self.fds.write().unwrap();
+        if !fds.contains(&fd) {
+            fds.push(fd);
+        }
+        self.has_fds.store(true, Ordering::SeqCst);
+    }
+
+    /// Unregisters a file descriptor.
+    pub fn unwatch(&self, fd: RawFd) {
+        let mut fds = self.fds.write().unwrap();
+        fds.retain(|&f| f != fd);
+        self.has_fds.store(!fds.is_empty(), Ordering::SeqCst);
+    }
+
+    /// Signals the descriptors if the set is armed, and disarms it.
+    #[inline]
+    pub fn notify(&self) {
+        if self.has_fds.load(Ordering::SeqCst) && self.armed.swap(false, Ordering::SeqCst) {
+            self.signal_all();
+        }
+    }
+
+    /// Rearms the set after a receive operation has found the channel empty.
+    ///
+    /// A notification that came in before rearming found the set disarmed and skipped the
+    /// descriptors. Rearming with `SeqCst` and then checking whether the channel is ready means
+    /// that either that check sees the new message, or the notification sees the set armed.
+    #[inline]
+    pub fn rearm<F: FnOnce() -> bool>(&self, is_ready: F) {
+        if self.has_fds.load(Ordering::SeqCst)
+            && !self.armed.swap(true, Ordering::SeqCst)
+            && is_ready()
+        {
+            self.notify();
+        }
+    }
+
+    /// Signals all descriptors, whether the set is armed or not.
+    pub fn signal_all(&self) {
+        for &fd in self.fds.read().unwrap().iter() {
+            signal_fd(fd);
+        }
+    }
+}
+
+/// Signals a file descriptor by writing to it.
+///
+/// The written value is an 8-byte integer equal to 1, which is what an `eventfd` expects. A write
+/// interrupted by a signal is retried. If the descriptor is non-blocking and full, the write fails
+/// with `EAGAIN`, which is fine because the descriptor is readable in that case. Other errors are
+/// ignored because there is nobody to report them to.
+#[cfg(all(unix, feature = "os_poll"))]
+pub fn signal_fd(fd: RawFd) {
+    let buf: [u8; 8] = if cfg!(target_endian = "little") {
+        [1, 0, 0, 0, 0, 0, 0, 0]
+    } else {
+        [0, 0, 0, 0, 0, 0, 0, 1]
+    };
+
+    // The descriptor is borrowed, so it must not be closed when the `File` goes away.
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    loop {
+        match file.write(&buf) {
+            // Nothing was written, so try again.
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            // `EAGAIN`: the descriptor is full, so it is readable already.
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+            _ => break,
+        }
+    }
+    let _ = file.into_raw_fd();
+}
+
 /// Returns the id of the current thread.
 #[inline]
 fn current_thread_id() -> ThreadId {
@@ -0,0 +1,165 @@
+//! Notifications when the number of messages in a channel reaches a threshold.
+
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::usize;
+
+use channel::{bounded, Sender};
+use utils::{BoxedCallback, Spinlock};
+
+/// The threshold value meaning that no watermark is set.
+const UNSET: usize = usize::MAX;
+
+/// A high watermark on the number of messages in a channel.
+///
+/// Send operations report the length of the channel after sending, and each time it reaches the
+/// threshold from below, a notifier thread is woken up to call the callback. Producers therefore
+/// never run the callback themselves and never wait for it.
+///
+/// The notifier thread is started the first time the watermark is set and lives as long as the
+/// watermark does. Setting the watermark again only swaps the callback.
+pub struct Watermark {
+    /// The threshold, or `UNSET`.
+    threshold: AtomicUsize,
+
+    /// Equals `true` while the length is known to be at or above the threshold.
+    above: AtomicBool,
+
+    /// The callback, shared with the notifier thread.
+    callback: Arc<Mutex<Callback>>,
+
+    /// Wakes up the notifier thread, or `None` if it hasn't been started yet.
+    ///
+    /// Dropping it makes the notifier thread exit.
+    notifier: Spinlock<Option<Sender<()>>>,
+}
+
+/// The callback called by the notifier thread.
+struct Callback {
+    /// The callback, or `None` if there is none or the notifier thread is running it.
+    f: Option<BoxedCallback>,
+
+    /// Incremented each time the callback is replaced or removed.
+    ///
+    /// The notifier thread puts the callback back after running it only if this hasn't changed.
+    version: usize,
+}
+
+impl Watermark {
+    /// Creates a watermark that is not set.
+    pub fn new() -> Watermark {
+        Watermark {
+            threshold: AtomicUsize::new(UNSET),
+            above: AtomicBool::new(false),
+            callback: Arc::new(Mutex::new(Callback {
+                f: None,
+                version: 0,
+            })),
+            notifier: Spinlock::new(None),
+        }
+    }
+
+    /// Sets the threshold and the callback, replacing the previous ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is the first time the watermark is set and the notifier thread can't be
+    /// spawned.
+    pub fn set<F>(&self, threshold: usize, cb: F)
+    where
+        F: Fn() + Send + 'static,
+    {
+        self.start_notifier();
+
+        // The old callback is dropped after the lock is released.
+        let old = self.replace_callback(Some(Box::new(cb)));
+        self.above.store(false, Ordering::SeqCst);
+        self.threshold.store(threshold, Ordering::SeqCst);
+        drop(old);
+    }
+
+    /// Removes the threshold and the callback.
+    ///
+    /// The notifier thread keeps running, so that setting the watermark again doesn't start
+    /// another one.
+    pub fn clear(&self) {
+        self.threshold.store(UNSET, Ordering::SeqCst);
+        drop(self.replace_callback(None));
+    }
+
+    /// Replaces the callback and returns the previous one.
+    fn replace_callback(&self, f: Option<BoxedCallback>) -> Option<BoxedCallback> {
+        let mut callback = self.callback.lock().unwrap();
+        callback.version = callback.version.wrapping_add(1);
+        mem::replace(&mut callback.f, f)
+    }
+
+    /// Starts the notifier thread, unless it is already running.
+    fn start_notifier(&self) {
+        if self.notifier.lock().is_some() {
+            return;
+        }
+
+        // Notifications are coalesced while the callback is running.
+        let (s, r) = bounded(1);
+        let callback = self.callback.clone();
+        thread::Builder::new()
+            .name("crossbeam-watermark".to_string())
+            .spawn(move || {
+                for () in r.iter() {
+                    // Take the callback out so that it doesn't run while the lock is held.
+                    let (version, f) = {
+                        let mut callback = callback.lock().unwrap();
+                        (callback.version, callback.f.take())
+                    };
+
+                    if let Some(mut f) = f {
+                        f();
+
+                        // Put the callback back, unless it was replaced or removed meanwhile.
+                        let stale = {
+                            let mut callback = callback.lock().unwrap();
+                            if callback.version == version {
+                                callback.f = Some(f);
+                                None
+                            } else {
+                                Some(f)
+                            }
+                        };
+                        drop(stale);
+                    }
+                }
+            })
+            .expect("failed to spawn the watermark notifier thread");
+
+        // If another thread has started a notifier meanwhile, this one exits right away because
+        // its sender is dropped.
+        let mut notifier = self.notifier.lock();
+        if notifier.is_none() {
+            *notifier = Some(s);
+        }
+    }
+
+    /// Checks the length of the channel after a send operation.
+    ///
+    /// The length is only computed if a watermark is set.
+    #[inline]
+    pub fn observe<F: FnOnce() -> usize>(&self, len: F) {
+        let threshold = self.threshold.load(Ordering::Relaxed);
+        if threshold == UNSET {
+            return;
+        }
+
+        if len() >= threshold {
+            if !self.above.swap(true, Ordering::SeqCst) {
+                if let Some(s) = self.notifier.lock().as_ref() {
+                    let _ = s.try_send(());
+                }
+            }
+        } else if self.above.load(Ordering::Relaxed) {
+            self.above.store(false, Ordering::SeqCst);
+        }
+    }
+}
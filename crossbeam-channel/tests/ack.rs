@@ -0,0 +1,200 @@
+//! Tests for `ack_channel`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{ack_channel, ack_channel_with_redelivery, Redelivery};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = ack_channel(1);
+    s.send(7).unwrap();
+
+    let d = r.try_recv().unwrap();
+    assert_eq!(*d, 7);
+    assert_eq!(r.outstanding(), 1);
+    assert_eq!(d.ack(), 7);
+    assert_eq!(r.outstanding(), 0);
+
+    assert_eq!(r.try_recv().unwrap_err(), TryRecvError::Empty);
+    assert_eq!(
+        r.recv_timeout(ms(50)).unwrap_err(),
+        RecvTimeoutError::Timeout
+    );
+}
+
+#[test]
+fn redelivery_front() {
+    let (s, r) = ack_channel(3);
+    for i in 0..3 {
+        s.send(i).unwrap();
+    }
+
+    drop(r.recv().unwrap());
+    assert_eq!(r.recv().unwrap().ack(), 0);
+    assert_eq!(r.recv().unwrap().ack(), 1);
+    assert_eq!(r.recv().unwrap().ack(), 2);
+}
+
+#[test]
+fn redelivery_back() {
+    let (s, r) = ack_channel_with_redelivery(3, Redelivery::Back);
+    for i in 0..3 {
+        s.send(i).unwrap();
+    }
+
+    drop(r.recv().unwrap());
+    assert_eq!(r.recv().unwrap().ack(), 1);
+    assert_eq!(r.recv().unwrap().ack(), 2);
+    assert_eq!(r.recv().unwrap().ack(), 0);
+}
+
+#[test]
+fn modified_message_is_redelivered() {
+    let (s, r) = ack_channel(1);
+    s.send(vec![1]).unwrap();
+
+    let mut d = r.recv().unwrap();
+    d.push(2);
+    drop(d);
+
+    assert_eq!(r.recv().unwrap().ack(), [1, 2]);
+}
+
+#[test]
+fn disconnect_waits_for_outstanding() {
+    let (s, r) = ack_channel(1);
+    s.send(1).unwrap();
+    drop(s);
+
+    let d = r.recv().unwrap();
+    assert_eq!(r.try_recv().unwrap_err(), TryRecvError::Empty);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            drop(d);
+        });
+
+        // Blocks until the delivery is dropped and redelivered.
+        let d = r.recv().unwrap();
+
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            d.ack();
+        });
+
+        // Blocks until the delivery is acknowledged.
+        assert_eq!(r.recv().unwrap_err(), RecvError);
+    })
+    .unwrap();
+
+    assert_eq!(r.try_recv().unwrap_err(), TryRecvError::Disconnected);
+    assert_eq!(
+        r.recv_timeout(ms(10)).unwrap_err(),
+        RecvTimeoutError::Disconnected
+    );
+}
+
+#[test]
+fn panicking_worker() {
+    let (s, r) = ack_channel(1);
+    s.send(1).unwrap();
+    drop(s);
+
+    let r2 = r.clone();
+    let res = thread::spawn(move || {
+        let d = r2.recv().unwrap();
+        if *d == 1 {
+            panic!("worker failed");
+        }
+        d.ack();
+    })
+    .join();
+    assert!(res.is_err());
+
+    assert_eq!(r.recv().unwrap().ack(), 1);
+    assert_eq!(r.recv().unwrap_err(), RecvError);
+}
+
+#[test]
+fn all_receivers_wake_up_on_disconnect() {
+    const THREADS: usize = 4;
+
+    let (s, r) = ack_channel::<i32>(1);
+    s.send(1).unwrap();
+    let d = r.recv().unwrap();
+    drop(s);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| assert_eq!(r.recv().unwrap_err(), RecvError));
+        }
+
+        thread::sleep(ms(100));
+        d.ack();
+    })
+    .unwrap();
+}
+
+#[test]
+fn at_least_once() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = ack_channel(16);
+    let acked = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+    let attempts = AtomicUsize::new(0);
+
+    // Silence the output of panicking workers.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                while let Ok(d) = r.recv() {
+                    // Every third attempt fails, either by dropping or by panicking.
+                    match attempts.fetch_add(1, Ordering::SeqCst) % 6 {
+                        0 => drop(d),
+                        3 => {
+                            let _ = panic::catch_unwind(panic::AssertUnwindSafe(move || {
+                                let _d = d;
+                                panic!("worker failed");
+                            }));
+                        }
+                        _ => {
+                            let i = d.ack();
+                            acked[i].fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    panic::set_hook(hook);
+
+    for a in &acked {
+        assert_eq!(a.load(Ordering::SeqCst), 1);
+    }
+    assert_eq!(r.outstanding(), 0);
+}
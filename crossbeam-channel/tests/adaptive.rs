@@ -0,0 +1,137 @@
+//! Tests for `BackoffReceiver`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, unbounded, BackoffReceiver};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let mut r = BackoffReceiver::new(r, ms(10), ms(100), 2);
+
+    s.send(7).unwrap();
+    assert_eq!(r.recv_adaptive(), Ok(7));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout_adaptive(), Err(RecvTimeoutError::Timeout));
+
+    drop(s);
+    assert_eq!(r.recv_adaptive(), Err(RecvError));
+    assert_eq!(
+        r.recv_timeout_adaptive(),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
+
+#[test]
+fn backs_off_and_caps() {
+    let (_s, r) = bounded::<()>(0);
+    let mut r = BackoffReceiver::new(r, ms(1), ms(20), 3);
+
+    let mut timeouts = vec![r.current_timeout()];
+    for _ in 0..5 {
+        assert_eq!(r.recv_timeout_adaptive(), Err(RecvTimeoutError::Timeout));
+        timeouts.push(r.current_timeout());
+    }
+    assert_eq!(timeouts, [ms(1), ms(3), ms(9), ms(20), ms(20), ms(20)]);
+
+    r.reset();
+    assert_eq!(r.current_timeout(), ms(1));
+}
+
+#[test]
+fn resets_on_success() {
+    let (s, r) = unbounded();
+    let mut r = BackoffReceiver::new(r, ms(1), ms(100), 2);
+
+    for _ in 0..4 {
+        assert_eq!(r.recv_timeout_adaptive(), Err(RecvTimeoutError::Timeout));
+    }
+    assert_eq!(r.current_timeout(), ms(16));
+
+    s.send(1).unwrap();
+    assert_eq!(r.recv_timeout_adaptive(), Ok(1));
+    assert_eq!(r.current_timeout(), ms(1));
+
+    // An empty `try_recv` doesn't back off, but a successful one resets.
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.current_timeout(), ms(1));
+    r.recv_timeout_adaptive().unwrap_err();
+    s.send(2).unwrap();
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(r.current_timeout(), ms(1));
+}
+
+#[test]
+fn factor_one() {
+    let (_s, r) = unbounded::<()>();
+    let mut r = BackoffReceiver::new(r, ms(5), ms(50), 1);
+
+    for _ in 0..3 {
+        assert_eq!(r.recv_timeout_adaptive(), Err(RecvTimeoutError::Timeout));
+        assert_eq!(r.current_timeout(), ms(5));
+    }
+}
+
+#[test]
+#[should_panic(expected = "minimum timeout is greater than the maximum timeout")]
+fn min_greater_than_max() {
+    let (_s, r) = unbounded::<()>();
+    BackoffReceiver::new(r, ms(10), ms(5), 2);
+}
+
+#[test]
+#[should_panic(expected = "minimum timeout must be positive")]
+fn zero_min() {
+    let (_s, r) = unbounded::<()>();
+    BackoffReceiver::new(r, ms(0), ms(5), 2);
+}
+
+#[test]
+#[should_panic(expected = "backoff factor must be positive")]
+fn zero_factor() {
+    let (_s, r) = unbounded::<()>();
+    BackoffReceiver::new(r, ms(1), ms(5), 0);
+}
+
+#[test]
+fn recv_adaptive_waits() {
+    let (s, r) = unbounded();
+    let mut r = BackoffReceiver::new(r, ms(1), ms(50), 2);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.send(7).unwrap();
+        });
+
+        let start = Instant::now();
+        assert_eq!(r.recv_adaptive(), Ok(7));
+        assert!(start.elapsed() >= ms(500));
+        assert!(start.elapsed() < ms(1000));
+
+        // The timeout was reset by the received message.
+        assert_eq!(r.current_timeout(), ms(1));
+        assert_eq!(r.recv_adaptive(), Err(RecvError));
+    })
+    .unwrap();
+}
+
+#[test]
+fn into_inner() {
+    let (s, r) = unbounded();
+    let r = BackoffReceiver::new(r, ms(1), ms(2), 2);
+
+    s.send(1).unwrap();
+    assert_eq!(r.get_ref().len(), 1);
+    assert_eq!(r.into_inner().recv(), Ok(1));
+}
@@ -0,0 +1,74 @@
+//! Tests for `Receiver::recv_into_arena`.
+
+#![cfg(feature = "arena")]
+
+extern crate bumpalo;
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use bumpalo::Bump;
+use crossbeam_channel::{bounded, unbounded, RecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = bounded(1);
+    let arena = Bump::new();
+
+    s.send(7).unwrap();
+    let msg = r.recv_into_arena(&arena).unwrap();
+    *msg += 1;
+    assert_eq!(*msg, 8);
+}
+
+#[test]
+fn batch() {
+    let (s, r) = unbounded();
+    let arena = Bump::new();
+
+    for i in 0..100 {
+        s.send([i; 16]).unwrap();
+    }
+    drop(s);
+
+    let mut msgs = Vec::new();
+    while let Ok(msg) = r.recv_into_arena(&arena) {
+        msgs.push(msg);
+    }
+
+    assert_eq!(msgs.len(), 100);
+    for (i, msg) in msgs.iter().enumerate() {
+        assert_eq!(**msg, [i; 16]);
+    }
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = bounded::<i32>(1);
+    let arena = Bump::new();
+
+    drop(s);
+    assert_eq!(r.recv_into_arena(&arena), Err(RecvError));
+}
+
+#[test]
+fn blocks() {
+    let (s, r) = bounded(0);
+    let arena = Bump::new();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(50));
+            s.send("hello").unwrap();
+        });
+        assert_eq!(r.recv_into_arena(&arena).map(|m| *m), Ok("hello"));
+    })
+    .unwrap();
+}
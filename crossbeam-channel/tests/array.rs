@@ -6,14 +6,21 @@ extern crate crossbeam_utils;
 extern crate rand;
 
 use std::any::Any;
-use std::sync::atomic::AtomicUsize;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
-use crossbeam_channel::{bounded, Receiver};
-use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
-use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
+use crossbeam_channel::{bounded, bounded_pow2, Decision, Receiver, Select};
+use crossbeam_channel::{bounded_in_slice, ArraySlot};
+use crossbeam_channel::{bounded_with_drop_handler, bounded_with_strategy, ParkStrategy};
+use crossbeam_channel::{CapacityError, TryPeekError, TryRecvError};
+use crossbeam_channel::{PeekError, RecvError, RecvSpinError, RecvState, RecvTimeoutError, Steal};
+use crossbeam_channel::{ReserveSlotError, TryReserveSlotError};
+use crossbeam_channel::{SendError, SendState, SendTimeoutError, TrySendError};
 use crossbeam_utils::thread::scope;
 use rand::{thread_rng, Rng};
 
@@ -43,6 +50,23 @@ fn capacity() {
     }
 }
 
+#[test]
+fn capacity_pow2() {
+    for &(min, cap) in &[
+        (0, 1),
+        (1, 1),
+        (2, 2),
+        (3, 4),
+        (5, 8),
+        (100, 128),
+        (128, 128),
+    ] {
+        let (s, r) = bounded_pow2::<()>(min);
+        assert_eq!(s.capacity(), Some(cap));
+        assert_eq!(r.capacity(), Some(cap));
+    }
+}
+
 #[test]
 fn len_empty_full() {
     let (s, r) = bounded(2);
@@ -102,6 +126,26 @@ fn try_recv() {
     .unwrap();
 }
 
+#[test]
+fn poll() {
+    let (s, r) = bounded(100);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.poll(), RecvState::Empty);
+            thread::sleep(ms(1500));
+            assert_eq!(r.poll(), RecvState::Ready(7));
+            thread::sleep(ms(500));
+            assert_eq!(r.poll(), RecvState::Disconnected);
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            s.send(7).unwrap();
+        });
+    })
+    .unwrap();
+}
+
 #[test]
 fn recv() {
     let (s, r) = bounded(100);
@@ -126,252 +170,396 @@ fn recv() {
 }
 
 #[test]
-fn recv_timeout() {
-    let (s, r) = bounded::<i32>(100);
+fn bounded_checked() {
+    let (s, r) = crossbeam_channel::bounded_checked(2).unwrap();
+    assert_eq!(s.capacity(), Some(2));
+    s.send(1).unwrap();
+    assert_eq!(r.recv(), Ok(1));
 
-    scope(|scope| {
-        scope.spawn(move |_| {
-            assert_eq!(r.recv_timeout(ms(1000)), Err(RecvTimeoutError::Timeout));
-            assert_eq!(r.recv_timeout(ms(1000)), Ok(7));
-            assert_eq!(
-                r.recv_timeout(ms(1000)),
-                Err(RecvTimeoutError::Disconnected)
-            );
-        });
-        scope.spawn(move |_| {
-            thread::sleep(ms(1500));
-            s.send(7).unwrap();
-        });
-    })
-    .unwrap();
+    let (s, _) = crossbeam_channel::bounded_checked::<i32>(0).unwrap();
+    assert_eq!(s.capacity(), Some(0));
+
+    for &cap in &[
+        usize::max_value(),
+        usize::max_value() / 2,
+        isize::max_value() as usize / 8,
+    ] {
+        assert_eq!(
+            crossbeam_channel::bounded_checked::<u64>(cap).err(),
+            Some(CapacityError)
+        );
+    }
 }
 
 #[test]
-fn try_send() {
-    let (s, r) = bounded(1);
+#[should_panic(expected = "capacity overflow")]
+fn bounded_too_large() {
+    bounded::<u64>(usize::max_value());
+}
+
+fn leak_slots<T: Default>(cap: usize) -> &'static mut [ArraySlot<T>] {
+    let buf = (0..cap).map(|_| ArraySlot::new()).collect::<Vec<_>>();
+    Box::leak(buf.into_boxed_slice())
+}
+
+#[test]
+fn in_slice() {
+    const COUNT: usize = 25_000;
+    const CAP: usize = 5;
+
+    let (s, r) = bounded_in_slice(leak_slots(CAP));
+    assert_eq!(s.capacity(), Some(CAP));
+
+    for i in 0..CAP {
+        s.send(i).unwrap();
+    }
+    assert_eq!(s.try_send(CAP), Err(TrySendError::Full(CAP)));
+    for i in 0..CAP {
+        assert_eq!(r.recv(), Ok(i));
+    }
 
     scope(|scope| {
-        scope.spawn(move |_| {
-            assert_eq!(s.try_send(1), Ok(()));
-            assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
-            thread::sleep(ms(1500));
-            assert_eq!(s.try_send(3), Ok(()));
-            thread::sleep(ms(500));
-            assert_eq!(s.try_send(4), Err(TrySendError::Disconnected(4)));
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                assert_eq!(r.recv(), Ok(i));
+            }
         });
-        scope.spawn(move |_| {
-            thread::sleep(ms(1000));
-            assert_eq!(r.try_recv(), Ok(1));
-            assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
-            assert_eq!(r.recv(), Ok(3));
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
         });
     })
     .unwrap();
 }
 
 #[test]
-fn send() {
+fn in_slice_drops_messages() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Default)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    // The default messages the slots were created with are dropped when the channel takes over.
+    let (s, r) = bounded_in_slice(leak_slots(4));
+    assert_eq!(DROPS.load(Ordering::SeqCst), 4);
+
+    for _ in 0..6 {
+        s.send(DropCounter).unwrap();
+        drop(r.recv().unwrap());
+    }
+    s.send(DropCounter).unwrap();
+    s.send(DropCounter).unwrap();
+    assert_eq!(DROPS.load(Ordering::SeqCst), 10);
+
+    // Messages left in the buffer are dropped along with the channel.
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 12);
+}
+
+#[test]
+#[should_panic(expected = "buffer must not be empty")]
+fn in_slice_empty() {
+    bounded_in_slice::<i32>(leak_slots(0));
+}
+
+#[test]
+fn recv_bounded_spins() {
     let (s, r) = bounded(1);
 
+    assert_eq!(r.recv_bounded_spins(10), Err(RecvSpinError::Exhausted));
+
     scope(|scope| {
         scope.spawn(|_| {
-            s.send(7).unwrap();
-            thread::sleep(ms(1000));
-            s.send(8).unwrap();
-            thread::sleep(ms(1000));
-            s.send(9).unwrap();
-            thread::sleep(ms(1000));
-            s.send(10).unwrap();
-        });
-        scope.spawn(|_| {
-            thread::sleep(ms(1500));
-            assert_eq!(r.recv(), Ok(7));
-            assert_eq!(r.recv(), Ok(8));
-            assert_eq!(r.recv(), Ok(9));
+            thread::sleep(ms(100));
+            s.send(1).unwrap();
         });
+
+        // Retrying never blocks, so it gives up well before the message arrives.
+        assert_eq!(r.recv_bounded_spins(10), Err(RecvSpinError::Exhausted));
+        assert_eq!(r.recv_timeout(ms(1000)), Ok(1));
     })
     .unwrap();
+
+    s.send(2).unwrap();
+    assert_eq!(r.recv_bounded_spins(0), Ok(2));
+    drop(s);
+    assert_eq!(r.recv_bounded_spins(10), Err(RecvSpinError::Disconnected));
 }
 
 #[test]
-fn send_timeout() {
-    let (s, r) = bounded(2);
+fn recv_timeout() {
+    let (s, r) = bounded::<i32>(100);
 
     scope(|scope| {
         scope.spawn(move |_| {
-            assert_eq!(s.send_timeout(1, ms(1000)), Ok(()));
-            assert_eq!(s.send_timeout(2, ms(1000)), Ok(()));
+            assert_eq!(r.recv_timeout(ms(1000)), Err(RecvTimeoutError::Timeout));
+            assert_eq!(r.recv_timeout(ms(1000)), Ok(7));
             assert_eq!(
-                s.send_timeout(3, ms(500)),
-                Err(SendTimeoutError::Timeout(3))
+                r.recv_timeout(ms(1000)),
+                Err(RecvTimeoutError::Disconnected)
             );
-            thread::sleep(ms(1000));
-            assert_eq!(s.send_timeout(4, ms(1000)), Ok(()));
-            thread::sleep(ms(1000));
-            assert_eq!(s.send(5), Err(SendError(5)));
         });
         scope.spawn(move |_| {
-            thread::sleep(ms(1000));
-            assert_eq!(r.recv(), Ok(1));
-            thread::sleep(ms(1000));
-            assert_eq!(r.recv(), Ok(2));
-            assert_eq!(r.recv(), Ok(4));
+            thread::sleep(ms(1500));
+            s.send(7).unwrap();
         });
     })
     .unwrap();
 }
 
 #[test]
-fn send_after_disconnect() {
-    let (s, r) = bounded(100);
-
-    s.send(1).unwrap();
-    s.send(2).unwrap();
-    s.send(3).unwrap();
-
-    drop(r);
+fn recv_with() {
+    let (s, r) = bounded(2);
 
-    assert_eq!(s.send(4), Err(SendError(4)));
-    assert_eq!(s.try_send(5), Err(TrySendError::Disconnected(5)));
-    assert_eq!(
-        s.send_timeout(6, ms(500)),
-        Err(SendTimeoutError::Disconnected(6))
-    );
-}
+    s.send(vec![1, 2, 3]).unwrap();
+    s.send(vec![4, 5]).unwrap();
 
-#[test]
-fn recv_after_disconnect() {
-    let (s, r) = bounded(100);
+    assert_eq!(r.recv_with(|v| v.len()), Ok(3));
+    assert_eq!(r.recv_with(|v| v[0]), Ok(4));
+    assert!(r.is_empty());
 
-    s.send(1).unwrap();
-    s.send(2).unwrap();
-    s.send(3).unwrap();
+    s.send(vec![6]).unwrap();
+    s.send(vec![7]).unwrap();
+    assert_eq!(s.try_send(vec![8]), Err(TrySendError::Full(vec![8])));
 
     drop(s);
-
-    assert_eq!(r.recv(), Ok(1));
-    assert_eq!(r.recv(), Ok(2));
-    assert_eq!(r.recv(), Ok(3));
-    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(r.recv_with(|v| v[0]), Ok(6));
+    assert_eq!(r.recv_with(|v| v[0]), Ok(7));
+    assert_eq!(r.recv_with(|v| v[0]), Err(RecvError));
 }
 
 #[test]
-fn len() {
-    const COUNT: usize = 25_000;
-    const CAP: usize = 1000;
-
-    let (s, r) = bounded(CAP);
-
-    assert_eq!(s.len(), 0);
-    assert_eq!(r.len(), 0);
+fn recv_with_panic() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
 
-    for _ in 0..CAP / 10 {
-        for i in 0..50 {
-            s.send(i).unwrap();
-            assert_eq!(s.len(), i + 1);
-        }
+    #[derive(Debug, PartialEq)]
+    struct DropCounter(i32);
 
-        for i in 0..50 {
-            r.recv().unwrap();
-            assert_eq!(r.len(), 50 - i - 1);
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
         }
     }
 
-    assert_eq!(s.len(), 0);
-    assert_eq!(r.len(), 0);
+    let (s, r) = bounded(1);
 
-    for i in 0..CAP {
-        s.send(i).unwrap();
-        assert_eq!(s.len(), i + 1);
-    }
+    s.send(DropCounter(1)).unwrap();
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _ = r.recv_with(|_| panic!("inspection failed"));
+    }));
+    assert!(res.is_err());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    // The slot has been released, so the channel is usable again.
+    assert!(r.is_empty());
+    s.send(DropCounter(2)).unwrap();
+    assert_eq!(r.recv_with(|m| m.0), Ok(2));
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+}
 
-    for _ in 0..CAP {
-        r.recv().unwrap();
+#[test]
+fn recv_with_empty() {
+    let (s, r) = bounded(3);
+
+    // Go around the buffer a few times.
+    for i in 0..10 {
+        s.send(i).unwrap();
+        s.send(i + 1).unwrap();
+        assert_eq!(r.recv_with_empty(), Ok((i, false)));
+        assert_eq!(r.recv_with_empty(), Ok((i + 1, true)));
     }
 
-    assert_eq!(s.len(), 0);
-    assert_eq!(r.len(), 0);
+    // Reserved slots count as messages behind the received one.
+    s.send(1).unwrap();
+    let slot = s.reserve().unwrap();
+    assert_eq!(r.recv_with_empty(), Ok((1, false)));
+    slot.send(2);
+    assert_eq!(r.recv_with_empty(), Ok((2, true)));
 
     scope(|scope| {
         scope.spawn(|_| {
-            for i in 0..COUNT {
-                assert_eq!(r.recv(), Ok(i));
-                let len = r.len();
-                assert!(len <= CAP);
-            }
-        });
-
-        scope.spawn(|_| {
-            for i in 0..COUNT {
-                s.send(i).unwrap();
-                let len = s.len();
-                assert!(len <= CAP);
-            }
+            thread::sleep(ms(100));
+            s.send(3).unwrap();
         });
+        assert_eq!(r.recv_with_empty(), Ok((3, true)));
     })
     .unwrap();
 
-    assert_eq!(s.len(), 0);
-    assert_eq!(r.len(), 0);
+    s.send(4).unwrap();
+    drop(s);
+    assert_eq!(r.recv_with_empty(), Ok((4, true)));
+    assert_eq!(r.recv_with_empty(), Err(RecvError));
 }
 
 #[test]
-fn disconnect_wakes_sender() {
-    let (s, r) = bounded(1);
+fn recv_peek_decide() {
+    let (s, r) = bounded(2);
 
     scope(|scope| {
-        scope.spawn(move |_| {
-            assert_eq!(s.send(()), Ok(()));
-            assert_eq!(s.send(()), Err(SendError(())));
-        });
-        scope.spawn(move |_| {
+        scope.spawn(|_| {
             thread::sleep(ms(1000));
-            drop(r);
+            s.send(1).unwrap();
+            s.send(2).unwrap();
+            s.send(3).unwrap();
         });
+
+        // The first call blocks until a message is sent.
+        assert_eq!(r.recv_peek_decide(|_| Decision::Leave), Ok(None));
+
+        let mut seen = Vec::new();
+        while seen.len() < 3 {
+            let res = r.recv_peek_decide(|&x| {
+                seen.push(x);
+                if seen.len() % 2 == 0 {
+                    Decision::Take
+                } else {
+                    Decision::Leave
+                }
+            });
+            assert!(res.is_ok());
+        }
+        assert_eq!(seen, [1, 1, 2]);
     })
     .unwrap();
+
+    assert_eq!(r.recv_peek_decide(|_| Decision::Take), Ok(Some(2)));
+    assert_eq!(r.recv_peek_decide(|_| Decision::Leave), Ok(None));
+    drop(s);
+    assert_eq!(r.recv_peek_decide(|_| Decision::Take), Ok(Some(3)));
+    assert_eq!(
+        r.recv_peek_decide(|_| Decision::Take),
+        Err(PeekError::Disconnected)
+    );
 }
 
 #[test]
-fn disconnect_wakes_receiver() {
-    let (s, r) = bounded::<()>(1);
+fn recv_peek_decide_panic() {
+    let (s, r) = bounded(1);
 
-    scope(|scope| {
-        scope.spawn(move |_| {
-            assert_eq!(r.recv(), Err(RecvError));
-        });
-        scope.spawn(move |_| {
-            thread::sleep(ms(1000));
-            drop(s);
-        });
-    })
-    .unwrap();
+    s.send(1).unwrap();
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _ = r.recv_peek_decide(|_| -> Decision { panic!("inspection failed") });
+    }));
+    assert!(res.is_err());
+
+    // The head has been unlocked and the message is still in the channel.
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
 }
 
 #[test]
-fn spsc() {
-    const COUNT: usize = 100_000;
-
+fn recv_peek_decide_locked_head() {
     let (s, r) = bounded(3);
-
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    let res = r.recv_peek_decide(|_| {
+        // Other receive operations see an empty channel while the head is locked.
+        assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+        select! {
+            recv(r) -> _ => panic!(),
+            default => {}
+        }
+        Decision::Leave
+    });
+    assert_eq!(res, Ok(None));
+    assert_eq!(r.try_recv(), Ok(1));
+}
+
+#[test]
+fn recv_peek_decide_locked_head_disconnected() {
+    let (s, r) = bounded(3);
+    s.send(1).unwrap();
+
     scope(|scope| {
-        scope.spawn(move |_| {
-            for i in 0..COUNT {
-                assert_eq!(r.recv(), Ok(i));
+        let res = r.recv_peek_decide(|_| {
+            let h = scope.spawn(|_| r.recv());
+            thread::sleep(ms(500));
+
+            // Once the channel is disconnected, other receive operations see it as empty and
+            // disconnected rather than wait for the head to be unlocked.
+            drop(s);
+            assert_eq!(h.join().unwrap(), Err(RecvError));
+            assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+            select! {
+                recv(r) -> msg => assert_eq!(msg, Err(RecvError)),
             }
-            assert_eq!(r.recv(), Err(RecvError));
+            Decision::Leave
         });
-        scope.spawn(move |_| {
-            for i in 0..COUNT {
-                s.send(i).unwrap();
+        assert_eq!(res, Ok(None));
+    })
+    .unwrap();
+
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn recv_peek_decide_wakes_receivers() {
+    let (s, r) = bounded(3);
+    for i in 0..3 {
+        s.send(i).unwrap();
+    }
+
+    scope(|scope| {
+        let mut handles = Vec::new();
+        let res = r.recv_peek_decide(|_| {
+            // These receivers block until the head is unlocked.
+            for _ in 0..2 {
+                handles.push(scope.spawn(|_| r.recv().unwrap()));
             }
+            thread::sleep(ms(500));
+            Decision::Take
         });
+        assert_eq!(res, Ok(Some(0)));
+
+        let mut received = handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>();
+        received.sort();
+        assert_eq!(received, [1, 2]);
     })
     .unwrap();
 }
 
 #[test]
-fn mpmc() {
+#[should_panic(expected = "cannot block on a channel while inspecting its front")]
+fn recv_peek_decide_blocking_recv() {
+    let (s, r) = bounded(3);
+    s.send(1).unwrap();
+
+    let _ = r.recv_peek_decide(|_| {
+        let _ = r.recv();
+        Decision::Leave
+    });
+}
+
+#[test]
+#[should_panic(expected = "cannot block on a channel while inspecting its front")]
+fn recv_peek_decide_blocking_select() {
+    let (s, r) = bounded(3);
+    s.send(1).unwrap();
+
+    let _ = r.recv_peek_decide(|_| {
+        let mut sel = Select::new();
+        sel.recv(&r);
+        let _ = sel.select();
+        Decision::Leave
+    });
+}
+
+#[test]
+fn recv_peek_decide_mpmc() {
     const COUNT: usize = 25_000;
     const THREADS: usize = 4;
 
@@ -381,19 +569,41 @@ fn mpmc() {
     scope(|scope| {
         for _ in 0..THREADS {
             scope.spawn(|_| {
-                for _ in 0..COUNT {
-                    let n = r.recv().unwrap();
-                    v[n].fetch_add(1, Ordering::SeqCst);
+                let mut rng = thread_rng();
+                loop {
+                    let decide = |_: &usize| {
+                        if rng.gen() {
+                            Decision::Take
+                        } else {
+                            Decision::Leave
+                        }
+                    };
+                    match r.recv_peek_decide(decide) {
+                        Ok(Some(n)) => {
+                            v[n].fetch_add(1, Ordering::SeqCst);
+                        }
+                        Ok(None) => {}
+                        Err(_) => break,
+                    }
                 }
             });
         }
         for _ in 0..THREADS {
             scope.spawn(|_| {
+                while let Ok(n) = r.recv() {
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            let s = s.clone();
+            scope.spawn(move |_| {
                 for i in 0..COUNT {
                     s.send(i).unwrap();
                 }
             });
         }
+        drop(s);
     })
     .unwrap();
 
@@ -403,257 +613,1364 @@ fn mpmc() {
 }
 
 #[test]
-fn stress_oneshot() {
-    const COUNT: usize = 10_000;
+fn recv_timeout_ready() {
+    let (s, r) = bounded(2);
+    s.send(7).unwrap();
+    s.send(8).unwrap();
 
-    for _ in 0..COUNT {
-        let (s, r) = bounded(1);
+    // Ready messages are received even if the timeout has already elapsed.
+    assert_eq!(r.recv_timeout(ms(0)), Ok(7));
+    assert_eq!(r.recv_timeout(ms(0)), Ok(8));
+    assert_eq!(r.recv_timeout(ms(0)), Err(RecvTimeoutError::Timeout));
 
-        scope(|scope| {
-            scope.spawn(|_| r.recv().unwrap());
-            scope.spawn(|_| s.send(0).unwrap());
-        })
-        .unwrap();
-    }
+    // A ready message is received without reading the clock. Adding this timeout to the current
+    // time would panic with an overflow.
+    let huge = Duration::new(u64::max_value(), 0);
+    s.send(9).unwrap();
+    assert_eq!(r.recv_timeout(huge), Ok(9));
+
+    drop(s);
+    assert_eq!(r.recv_timeout(ms(0)), Err(RecvTimeoutError::Disconnected));
+    assert_eq!(r.recv_timeout(huge), Err(RecvTimeoutError::Disconnected));
 }
 
 #[test]
-fn stress_iter() {
-    const COUNT: usize = 100_000;
-
-    let (request_s, request_r) = bounded(1);
-    let (response_s, response_r) = bounded(1);
+fn try_send() {
+    let (s, r) = bounded(1);
 
     scope(|scope| {
         scope.spawn(move |_| {
-            let mut count = 0;
-            loop {
-                for x in response_r.try_iter() {
-                    count += x;
-                    if count == COUNT {
-                        return;
-                    }
-                }
-                request_s.send(()).unwrap();
-            }
+            assert_eq!(s.try_send(1), Ok(()));
+            assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+            thread::sleep(ms(1500));
+            assert_eq!(s.try_send(3), Ok(()));
+            thread::sleep(ms(500));
+            assert_eq!(s.try_send(4), Err(TrySendError::Disconnected(4)));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            assert_eq!(r.try_recv(), Ok(1));
+            assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+            assert_eq!(r.recv(), Ok(3));
         });
+    })
+    .unwrap();
+}
 
-        for _ in request_r.iter() {
-            if response_s.send(1).is_err() {
-                break;
-            }
-        }
+#[test]
+fn offer_state() {
+    let (s, r) = bounded(1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(s.offer_state(1), SendState::Sent);
+            assert_eq!(s.offer_state(2), SendState::Full(2));
+            thread::sleep(ms(1500));
+            assert_eq!(s.offer_state(3), SendState::Sent);
+            thread::sleep(ms(500));
+            assert_eq!(s.offer_state(4), SendState::Disconnected(4));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Ok(3));
+        });
     })
     .unwrap();
 }
 
 #[test]
-fn stress_timeout_two_threads() {
-    const COUNT: usize = 100;
+fn offer() {
+    const COUNT: usize = 10_000;
 
-    let (s, r) = bounded(2);
+    let (s, r) = bounded(1);
+    assert_eq!(s.offer(1), Ok(()));
+    assert_eq!(s.offer(2), Err(TrySendError::Full(2)));
+    assert_eq!(r.recv(), Ok(1));
 
+    // A spinning receiver keeps up with offers often enough that some of them succeed.
+    let mut sent = 0;
     scope(|scope| {
         scope.spawn(|_| {
             for i in 0..COUNT {
-                if i % 2 == 0 {
-                    thread::sleep(ms(50));
-                }
-                loop {
-                    if let Ok(()) = s.send_timeout(i, ms(10)) {
-                        break;
-                    }
+                if s.offer(i).is_ok() {
+                    sent += 1;
                 }
             }
+            drop(s);
         });
+        scope.spawn(|_| while r.recv_bounded_spins(usize::max_value()).is_ok() {});
+    })
+    .unwrap();
+    assert!(sent > 0);
+}
+
+#[test]
+fn send() {
+    let (s, r) = bounded(1);
 
+    scope(|scope| {
         scope.spawn(|_| {
-            for i in 0..COUNT {
-                if i % 2 == 0 {
-                    thread::sleep(ms(50));
-                }
-                loop {
-                    if let Ok(x) = r.recv_timeout(ms(10)) {
-                        assert_eq!(x, i);
-                        break;
-                    }
-                }
-            }
+            s.send(7).unwrap();
+            thread::sleep(ms(1000));
+            s.send(8).unwrap();
+            thread::sleep(ms(1000));
+            s.send(9).unwrap();
+            thread::sleep(ms(1000));
+            s.send(10).unwrap();
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(1500));
+            assert_eq!(r.recv(), Ok(7));
+            assert_eq!(r.recv(), Ok(8));
+            assert_eq!(r.recv(), Ok(9));
         });
     })
     .unwrap();
 }
 
 #[test]
-fn drops() {
-    const RUNS: usize = 100;
-
-    static DROPS: AtomicUsize = AtomicUsize::new(0);
-
-    #[derive(Debug, PartialEq)]
-    struct DropCounter;
-
-    impl Drop for DropCounter {
-        fn drop(&mut self) {
-            DROPS.fetch_add(1, Ordering::SeqCst);
-        }
-    }
-
-    let mut rng = thread_rng();
+fn send_and_len() {
+    let (s, r) = bounded(3);
 
-    for _ in 0..RUNS {
-        let steps = rng.gen_range(0, 10_000);
-        let additional = rng.gen_range(0, 50);
+    assert_eq!(s.send_and_len(1), Ok(1));
+    assert_eq!(s.send_and_len(2), Ok(2));
+    assert_eq!(s.send_and_len(3), Ok(3));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(s.send_and_len(4), Ok(3));
 
-        DROPS.store(0, Ordering::SeqCst);
-        let (s, r) = bounded::<DropCounter>(50);
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(1000));
+            assert_eq!(r.recv(), Ok(2));
+        });
 
-        scope(|scope| {
-            scope.spawn(|_| {
-                for _ in 0..steps {
-                    r.recv().unwrap();
-                }
-            });
+        // The channel is full, so this blocks until a message is received.
+        assert_eq!(s.send_and_len(5), Ok(3));
+    })
+    .unwrap();
 
-            scope.spawn(|_| {
-                for _ in 0..steps {
-                    s.send(DropCounter).unwrap();
-                }
-            });
-        })
-        .unwrap();
+    drop(r);
+    assert_eq!(s.send_and_len(6), Err(SendError(6)));
+}
 
-        for _ in 0..additional {
-            s.send(DropCounter).unwrap();
-        }
+#[test]
+fn send_timeout() {
+    let (s, r) = bounded(2);
 
-        assert_eq!(DROPS.load(Ordering::SeqCst), steps);
-        drop(s);
-        drop(r);
-        assert_eq!(DROPS.load(Ordering::SeqCst), steps + additional);
-    }
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(s.send_timeout(1, ms(1000)), Ok(()));
+            assert_eq!(s.send_timeout(2, ms(1000)), Ok(()));
+            assert_eq!(
+                s.send_timeout(3, ms(500)),
+                Err(SendTimeoutError::Timeout(3))
+            );
+            thread::sleep(ms(1000));
+            assert_eq!(s.send_timeout(4, ms(1000)), Ok(()));
+            thread::sleep(ms(1000));
+            assert_eq!(s.send(5), Err(SendError(5)));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            assert_eq!(r.recv(), Ok(1));
+            thread::sleep(ms(1000));
+            assert_eq!(r.recv(), Ok(2));
+            assert_eq!(r.recv(), Ok(4));
+        });
+    })
+    .unwrap();
 }
 
 #[test]
-fn linearizable() {
-    const COUNT: usize = 25_000;
-    const THREADS: usize = 4;
+fn send_after_disconnect() {
+    let (s, r) = bounded(100);
 
-    let (s, r) = bounded(THREADS);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    s.send(3).unwrap();
+
+    drop(r);
+
+    assert_eq!(s.send(4), Err(SendError(4)));
+    assert_eq!(s.try_send(5), Err(TrySendError::Disconnected(5)));
+    assert_eq!(
+        s.send_timeout(6, ms(500)),
+        Err(SendTimeoutError::Disconnected(6))
+    );
+}
+
+#[test]
+fn recv_after_disconnect() {
+    let (s, r) = bounded(100);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    s.send(3).unwrap();
+
+    drop(s);
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv(), Ok(3));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn len() {
+    const COUNT: usize = 25_000;
+    const CAP: usize = 1000;
+
+    let (s, r) = bounded(CAP);
+
+    assert_eq!(s.len(), 0);
+    assert_eq!(r.len(), 0);
+
+    for _ in 0..CAP / 10 {
+        for i in 0..50 {
+            s.send(i).unwrap();
+            assert_eq!(s.len(), i + 1);
+        }
+
+        for i in 0..50 {
+            r.recv().unwrap();
+            assert_eq!(r.len(), 50 - i - 1);
+        }
+    }
+
+    assert_eq!(s.len(), 0);
+    assert_eq!(r.len(), 0);
+
+    for i in 0..CAP {
+        s.send(i).unwrap();
+        assert_eq!(s.len(), i + 1);
+    }
+
+    for _ in 0..CAP {
+        r.recv().unwrap();
+    }
+
+    assert_eq!(s.len(), 0);
+    assert_eq!(r.len(), 0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                assert_eq!(r.recv(), Ok(i));
+                let len = r.len();
+                assert!(len <= CAP);
+            }
+        });
+
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+                let len = s.len();
+                assert!(len <= CAP);
+            }
+        });
+    })
+    .unwrap();
+
+    assert_eq!(s.len(), 0);
+    assert_eq!(r.len(), 0);
+}
+
+#[test]
+fn drop_handler() {
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let h = handled.clone();
+    let (s, r) = bounded_with_drop_handler(2, move |msg| h.lock().unwrap().push(msg));
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.recv(), Ok(1));
+    s.send(3).unwrap();
+
+    scope(|scope| {
+        // A sender blocked on the full channel gets its message back.
+        scope.spawn(|_| assert_eq!(s.send(4), Err(SendError(4))));
+
+        thread::sleep(ms(500));
+        drop(r);
+        assert_eq!(*handled.lock().unwrap(), [2, 3]);
+    })
+    .unwrap();
+
+    assert_eq!(s.try_send(5), Err(TrySendError::Disconnected(5)));
+    drop(s);
+    assert_eq!(*handled.lock().unwrap(), [2, 3]);
+}
+
+#[test]
+fn drop_handler_skips_reserved() {
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let h = handled.clone();
+    let (s, r) = bounded_with_drop_handler(3, move |msg| h.lock().unwrap().push(msg));
+
+    // The first sender is stopped in the middle of writing its message.
+    let slot = s.try_reserve().unwrap();
+    s.send(2).unwrap();
+    s.send(3).unwrap();
+
+    // The messages behind the reserved slot are still handed off.
+    drop(r);
+    assert_eq!(*handled.lock().unwrap(), [2, 3]);
+
+    slot.send(1);
+    drop(s);
+    assert_eq!(*handled.lock().unwrap(), [2, 3]);
+}
+
+#[test]
+fn len_exact() {
+    const COUNT: usize = 25_000;
+    const CAP: usize = 100;
+
+    let (s, r) = bounded(CAP);
+    assert_eq!(r.len_exact(), 0);
+
+    // Reserved slots are counted by `len`, but not by `len_exact` until they are filled.
+    let slot = s.reserve().unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.len(), 2);
+    assert_eq!(r.len_exact(), 1);
+    slot.send(1);
+    assert_eq!(r.len_exact(), 2);
+
+    // Abandoned reservations are never counted.
+    drop(s.reserve().unwrap());
+    assert_eq!(r.len_exact(), 2);
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.len_exact(), 0);
+
+    // Wrap around the buffer a few times.
+    for i in 0..CAP * 3 {
+        s.send(i).unwrap();
+        assert_eq!(r.len_exact(), 1);
+        r.recv().unwrap();
+    }
+    for i in 0..CAP {
+        s.send(i).unwrap();
+        assert_eq!(r.len_exact(), i + 1);
+    }
+    for i in 0..CAP {
+        r.recv().unwrap();
+        assert_eq!(r.len_exact(), CAP - i - 1);
+    }
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                assert_eq!(r.recv(), Ok(i));
+                assert!(r.len_exact() <= CAP);
+            }
+        });
+
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+                assert!(r.len_exact() <= CAP);
+            }
+        });
+    })
+    .unwrap();
+
+    assert_eq!(r.len_exact(), 0);
+}
+
+#[test]
+fn len_exact_under_traffic() {
+    const THREADS: usize = 4;
+    const CAP: usize = 1000;
+
+    let (s, r) = bounded(CAP);
+    for i in 0..CAP / 2 {
+        s.send(i).unwrap();
+    }
+    let done = AtomicBool::new(false);
+
+    scope(|scope| {
+        // Keep the head and the tail moving all the time, so that scans of the buffer keep
+        // getting interrupted.
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                while !done.load(Ordering::SeqCst) {
+                    let _ = s.try_send(0);
+                    let _ = r.try_recv();
+                }
+            });
+        }
+
+        // The number of scans is capped, so this finishes anyway.
+        for _ in 0..1000 {
+            assert!(r.len_exact() <= CAP);
+        }
+        done.store(true, Ordering::SeqCst);
+    })
+    .unwrap();
+}
+
+#[test]
+fn disconnect_wakes_sender() {
+    let (s, r) = bounded(1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(s.send(()), Ok(()));
+            assert_eq!(s.send(()), Err(SendError(())));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            drop(r);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn disconnect_wakes_receiver() {
+    let (s, r) = bounded::<()>(1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn spsc() {
+    const COUNT: usize = 100_000;
+
+    let (s, r) = bounded(3);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                assert_eq!(r.recv(), Ok(i));
+            }
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded::<usize>(3);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let n = r.recv().unwrap();
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
+#[test]
+fn steal() {
+    let (s, r) = bounded(3);
+    assert_eq!(r.steal(), Steal::Empty);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.steal(), Steal::Success(1));
+
+    // A reservation at the front blocks stealing until it is filled.
+    let (s2, r2) = bounded(3);
+    let slot = s2.reserve().unwrap();
+    s2.send(2).unwrap();
+    assert_eq!(r2.steal(), Steal::Empty);
+    slot.send(1);
+    assert_eq!(r2.steal(), Steal::Success(1));
+    assert_eq!(r2.steal(), Steal::Success(2));
+
+    // An abandoned reservation is skipped.
+    drop(s2.reserve().unwrap());
+    s2.send(3).unwrap();
+    assert_eq!(r2.steal(), Steal::Success(3));
+    assert_eq!(r2.steal(), Steal::Empty);
+
+    drop(s);
+    assert_eq!(r.steal(), Steal::Success(2));
+    assert_eq!(r.steal(), Steal::Empty);
+}
+
+#[test]
+fn steal_locked_head() {
+    let (s, r) = bounded(3);
+    s.send(1).unwrap();
+
+    // The receiver holding the lock may leave the message in the channel.
+    let run = r.peek_contiguous().unwrap();
+    assert_eq!(r.steal(), Steal::Retry);
+    drop(run);
+    assert_eq!(r.steal(), Steal::Success(1));
+
+    s.send(2).unwrap();
+    let run = r.peek_contiguous().unwrap();
+    drop(s);
+    assert_eq!(r.steal(), Steal::Empty);
+    run.consume(1);
+    assert_eq!(r.steal(), Steal::Empty);
+}
+
+#[test]
+fn stress_steal() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded::<usize>(3);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+    let received = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                while received.load(Ordering::SeqCst) < COUNT {
+                    match r.steal() {
+                        Steal::Success(n) => {
+                            v[n].fetch_add(1, Ordering::SeqCst);
+                            received.fetch_add(1, Ordering::SeqCst);
+                        }
+                        Steal::Empty | Steal::Retry => thread::yield_now(),
+                    }
+                }
+            });
+        }
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), 1);
+    }
+}
+
+#[test]
+fn stress_oneshot() {
+    const COUNT: usize = 10_000;
+
+    for _ in 0..COUNT {
+        let (s, r) = bounded(1);
+
+        scope(|scope| {
+            scope.spawn(|_| r.recv().unwrap());
+            scope.spawn(|_| s.send(0).unwrap());
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn stress_iter() {
+    const COUNT: usize = 100_000;
+
+    let (request_s, request_r) = bounded(1);
+    let (response_s, response_r) = bounded(1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            let mut count = 0;
+            loop {
+                for x in response_r.try_iter() {
+                    count += x;
+                    if count == COUNT {
+                        return;
+                    }
+                }
+                request_s.send(()).unwrap();
+            }
+        });
+
+        for _ in request_r.iter() {
+            if response_s.send(1).is_err() {
+                break;
+            }
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn stress_timeout_two_threads() {
+    const COUNT: usize = 100;
+
+    let (s, r) = bounded(2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                if i % 2 == 0 {
+                    thread::sleep(ms(50));
+                }
+                loop {
+                    if let Ok(()) = s.send_timeout(i, ms(10)) {
+                        break;
+                    }
+                }
+            }
+        });
+
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                if i % 2 == 0 {
+                    thread::sleep(ms(50));
+                }
+                loop {
+                    if let Ok(x) = r.recv_timeout(ms(10)) {
+                        assert_eq!(x, i);
+                        break;
+                    }
+                }
+            }
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn stress_select_send_full() {
+    const COUNT: usize = 10_000;
+
+    let (s, r) = bounded(1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                select! {
+                    send(s, i) -> res => res.unwrap(),
+                }
+            }
+        });
+
+        for i in 0..COUNT {
+            if i % 100 == 0 {
+                thread::yield_now();
+            }
+            assert_eq!(r.recv(), Ok(i));
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn drops() {
+    const RUNS: usize = 100;
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let mut rng = thread_rng();
+
+    for _ in 0..RUNS {
+        let steps = rng.gen_range(0, 10_000);
+        let additional = rng.gen_range(0, 50);
+
+        DROPS.store(0, Ordering::SeqCst);
+        let (s, r) = bounded::<DropCounter>(50);
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                for _ in 0..steps {
+                    r.recv().unwrap();
+                }
+            });
+
+            scope.spawn(|_| {
+                for _ in 0..steps {
+                    s.send(DropCounter).unwrap();
+                }
+            });
+        })
+        .unwrap();
+
+        for _ in 0..additional {
+            s.send(DropCounter).unwrap();
+        }
+
+        assert_eq!(DROPS.load(Ordering::SeqCst), steps);
+        drop(s);
+        drop(r);
+        assert_eq!(DROPS.load(Ordering::SeqCst), steps + additional);
+    }
+}
+
+#[test]
+fn linearizable() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded(THREADS);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    s.send(0).unwrap();
+                    r.try_recv().unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn fairness() {
+    const COUNT: usize = 10_000;
+
+    let (s1, r1) = bounded::<()>(COUNT);
+    let (s2, r2) = bounded::<()>(COUNT);
+
+    for _ in 0..COUNT {
+        s1.send(()).unwrap();
+        s2.send(()).unwrap();
+    }
+
+    let mut hits = [0usize; 2];
+    for _ in 0..COUNT {
+        select! {
+            recv(r1) -> _  => hits[0] += 1,
+            recv(r2) -> _  => hits[1] += 1,
+        }
+    }
+    assert!(hits.iter().all(|x| *x >= COUNT / hits.len() / 2));
+}
+
+#[test]
+fn fairness_duplicates() {
+    const COUNT: usize = 10_000;
+
+    let (s, r) = bounded::<()>(COUNT);
+
+    for _ in 0..COUNT {
+        s.send(()).unwrap();
+    }
+
+    let mut hits = [0usize; 5];
+    for _ in 0..COUNT {
+        select! {
+            recv(r) -> _ => hits[0] += 1,
+            recv(r) -> _ => hits[1] += 1,
+            recv(r) -> _ => hits[2] += 1,
+            recv(r) -> _ => hits[3] += 1,
+            recv(r) -> _ => hits[4] += 1,
+        }
+    }
+    assert!(hits.iter().all(|x| *x >= COUNT / hits.len() / 2));
+}
+
+#[test]
+fn blocked_senders_skew() {
+    const PRODUCERS: usize = 32;
+    const COUNT: usize = PRODUCERS * 50;
+
+    let (s, r) = bounded::<usize>(1);
+
+    scope(|scope| {
+        for i in 0..PRODUCERS {
+            let s = s.clone();
+            scope.spawn(move |_| while s.send(i).is_ok() {});
+        }
+
+        // Let all producers block on the full channel.
+        thread::sleep(ms(100));
+
+        let mut hits = [0usize; PRODUCERS];
+        for _ in 0..COUNT {
+            hits[r.recv().unwrap()] += 1;
+            thread::sleep(Duration::new(0, 50_000));
+        }
+        drop(r);
+
+        // Woken senders that lose the slot keep their place at the front of the queue, so every
+        // producer gets roughly its share.
+        let min = *hits.iter().min().unwrap();
+        let max = *hits.iter().max().unwrap();
+        assert!(min >= COUNT / PRODUCERS / 4, "hits: {:?}", &hits[..]);
+        assert!(max <= COUNT / PRODUCERS * 4, "hits: {:?}", &hits[..]);
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_in_send() {
+    let (s, _r) = bounded(1);
+    s.send(()).unwrap();
+
+    #[allow(unreachable_code)]
+    {
+        select! {
+            send(s, panic!()) -> _ => panic!(),
+            default => {}
+        }
+    }
+
+    let (s, r) = bounded(2);
+    s.send(()).unwrap();
+
+    select! {
+        send(s, assert_eq!(r.recv(), Ok(()))) -> _ => {}
+    }
+}
+
+#[test]
+fn channel_through_channel() {
+    const COUNT: usize = 1000;
+
+    type T = Box<Any + Send>;
+
+    let (s, r) = bounded::<T>(1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            let mut s = s;
+
+            for _ in 0..COUNT {
+                let (new_s, new_r) = bounded(1);
+                let new_r: T = Box::new(Some(new_r));
+
+                s.send(new_r).unwrap();
+                s = new_s;
+            }
+        });
+
+        scope.spawn(move |_| {
+            let mut r = r;
+
+            for _ in 0..COUNT {
+                r = r
+                    .recv()
+                    .unwrap()
+                    .downcast_mut::<Option<Receiver<T>>>()
+                    .unwrap()
+                    .take()
+                    .unwrap()
+            }
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn park_strategies() {
+    // Spinning threads sharing a core only make progress when one of them gets preempted.
+    const COUNT: usize = 1000;
+
+    for &strategy in &[ParkStrategy::Spin, ParkStrategy::Yield, ParkStrategy::Block] {
+        let (s, r) = bounded_with_strategy(3, strategy);
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    assert_eq!(r.recv(), Ok(i));
+                }
+                assert_eq!(r.recv(), Err(RecvError));
+            });
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        })
+        .unwrap();
+
+        assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    }
+}
+
+#[test]
+fn park_strategies_timeout() {
+    for &strategy in &[ParkStrategy::Spin, ParkStrategy::Yield, ParkStrategy::Block] {
+        let (s, r) = bounded_with_strategy(1, strategy);
+
+        assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+        s.send(1).unwrap();
+        assert_eq!(
+            s.send_timeout(2, ms(100)),
+            Err(SendTimeoutError::Timeout(2))
+        );
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                thread::sleep(ms(100));
+                assert_eq!(r.recv(), Ok(1));
+            });
+
+            // The sender is woken up without a notification from the receiver.
+            assert_eq!(s.send_timeout(3, ms(1000)), Ok(()));
+        })
+        .unwrap();
+
+        drop(r);
+        assert_eq!(s.send(4), Err(SendError(4)));
+    }
+}
+
+#[test]
+fn park_strategy_zero_capacity() {
+    let (s, r) = bounded_with_strategy(0, ParkStrategy::Spin);
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(7).unwrap());
+        assert_eq!(r.recv(), Ok(7));
+    })
+    .unwrap();
+
+    assert_eq!(r.capacity(), Some(0));
+}
+
+#[test]
+fn wait_drained() {
+    let (s, r) = bounded(3);
+    assert!(s.wait_drained());
 
     scope(|scope| {
-        for _ in 0..THREADS {
-            scope.spawn(|_| {
-                for _ in 0..COUNT {
-                    s.send(0).unwrap();
-                    r.try_recv().unwrap();
-                }
-            });
+        scope.spawn(|_| {
+            for i in 0..10 {
+                s.send(i).unwrap();
+            }
+            assert!(s.wait_drained());
+            assert!(s.is_empty());
+        });
+
+        for i in 0..10 {
+            thread::sleep(ms(10));
+            assert_eq!(r.recv(), Ok(i));
         }
     })
     .unwrap();
+
+    drop(r);
+    assert!(s.wait_drained());
 }
 
 #[test]
-fn fairness() {
-    const COUNT: usize = 10_000;
-
-    let (s1, r1) = bounded::<()>(COUNT);
-    let (s2, r2) = bounded::<()>(COUNT);
+fn peek_contiguous() {
+    let (s, r) = bounded(4);
+    assert_eq!(r.peek_contiguous().err(), Some(TryPeekError::Empty));
 
-    for _ in 0..COUNT {
-        s1.send(()).unwrap();
-        s2.send(()).unwrap();
+    for i in 0..4 {
+        s.send(i).unwrap();
     }
+    assert_eq!(r.recv(), Ok(0));
+    assert_eq!(r.recv(), Ok(1));
+    s.send(4).unwrap();
 
-    let mut hits = [0usize; 2];
-    for _ in 0..COUNT {
-        select! {
-            recv(r1) -> _  => hits[0] += 1,
-            recv(r2) -> _  => hits[1] += 1,
-        }
-    }
-    assert!(hits.iter().all(|x| *x >= COUNT / hits.len() / 2));
+    // The run stops where the buffer wraps around.
+    let run = r.peek_contiguous().unwrap();
+    assert_eq!(run.len(), 2);
+    assert_eq!((run[0], run[1]), (2, 3));
+    assert_eq!(run.get(2), None);
+    run.consume(2);
+
+    let run = r.peek_contiguous().unwrap();
+    assert_eq!(run.len(), 1);
+    assert_eq!(run[0], 4);
+    drop(run);
+
+    // Dropping a run without consuming leaves the messages in the channel.
+    assert_eq!(r.len(), 1);
+    s.send(5).unwrap();
+    let run = r.peek_contiguous().unwrap();
+    assert_eq!(run.len(), 2);
+    run.consume(1);
+    assert_eq!(r.recv(), Ok(5));
+
+    drop(s);
+    assert_eq!(r.peek_contiguous().err(), Some(TryPeekError::Disconnected));
 }
 
 #[test]
-fn fairness_duplicates() {
+fn peek_contiguous_blocks_receivers() {
+    let (s, r) = bounded(4);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    scope(|scope| {
+        let run = r.peek_contiguous().unwrap();
+        scope.spawn(|_| {
+            // Waits until the run is consumed.
+            assert_eq!(r.recv(), Ok(2));
+        });
+
+        thread::sleep(ms(100));
+        assert_eq!(run[0], 1);
+        run.consume(1);
+    })
+    .unwrap();
+
+    s.send(3).unwrap();
+    assert_eq!(r.recv(), Ok(3));
+}
+
+#[test]
+fn peek_contiguous_locked_head() {
+    let (s, r) = bounded(4);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    // Other receive operations see an empty channel while the run exists.
+    let run = r.peek_contiguous().unwrap();
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.peek_contiguous().err(), Some(TryPeekError::Empty));
+    run.consume(1);
+
+    assert_eq!(r.try_recv(), Ok(2));
+}
+
+#[test]
+fn peek_contiguous_leaked() {
+    let (s, r) = bounded(4);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    // A leaked run keeps the front locked, which stalls other receivers until the channel is
+    // disconnected.
+    mem::forget(r.peek_contiguous().unwrap());
+
+    scope(|scope| {
+        let h = scope.spawn(|_| r.recv());
+        thread::sleep(ms(500));
+        drop(s);
+        assert_eq!(h.join().unwrap(), Err(RecvError));
+    })
+    .unwrap();
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(r.peek_contiguous().err(), Some(TryPeekError::Disconnected));
+}
+
+#[test]
+#[should_panic(expected = "cannot block on a channel while inspecting its front")]
+fn peek_contiguous_blocking_recv() {
+    let (s, r) = bounded(4);
+    s.send(1).unwrap();
+
+    let _run = r.peek_contiguous().unwrap();
+    let _ = r.recv();
+}
+
+#[test]
+fn peek_contiguous_stress() {
     const COUNT: usize = 10_000;
 
-    let (s, r) = bounded::<()>(COUNT);
+    let (s, r) = bounded(7);
 
-    for _ in 0..COUNT {
-        s.send(()).unwrap();
-    }
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
 
-    let mut hits = [0usize; 5];
-    for _ in 0..COUNT {
-        select! {
-            recv(r) -> _ => hits[0] += 1,
-            recv(r) -> _ => hits[1] += 1,
-            recv(r) -> _ => hits[2] += 1,
-            recv(r) -> _ => hits[3] += 1,
-            recv(r) -> _ => hits[4] += 1,
+        let mut next = 0;
+        while next < COUNT {
+            if let Ok(run) = r.peek_contiguous() {
+                let n = thread_rng().gen_range(0, run.len() + 1);
+                for i in 0..n {
+                    assert_eq!(run[i], next + i);
+                }
+                next += n;
+                run.consume(n);
+            }
         }
-    }
-    assert!(hits.iter().all(|x| *x >= COUNT / hits.len() / 2));
+    })
+    .unwrap();
+
+    assert!(r.is_empty());
 }
 
 #[test]
-fn recv_in_send() {
-    let (s, _r) = bounded(1);
-    s.send(()).unwrap();
+fn peek_contiguous_drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
 
-    #[allow(unreachable_code)]
-    {
-        select! {
-            send(s, panic!()) -> _ => panic!(),
-            default => {}
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
         }
     }
 
+    let (s, r) = bounded(4);
+    for _ in 0..3 {
+        s.send(DropCounter).unwrap();
+    }
+
+    r.peek_contiguous().unwrap().consume(2);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+
+    drop(r.peek_contiguous().unwrap());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+#[should_panic(expected = "cannot consume more messages than the run holds")]
+fn peek_contiguous_consume_too_many() {
     let (s, r) = bounded(2);
-    s.send(()).unwrap();
+    s.send(1).unwrap();
+    r.peek_contiguous().unwrap().consume(2);
+}
 
-    select! {
-        send(s, assert_eq!(r.recv(), Ok(()))) -> _ => {}
-    }
+#[test]
+fn reserve() {
+    let (s, r) = bounded(2);
+
+    let slot = s.reserve().unwrap();
+    assert_eq!(s.len(), 1);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    slot.send(1);
+    assert_eq!(r.try_recv(), Ok(1));
+
+    let slot1 = s.try_reserve().unwrap();
+    let slot2 = s.try_reserve().unwrap();
+    assert_eq!(s.try_reserve().err(), Some(TryReserveSlotError::Full));
+    assert_eq!(s.try_send(3), Err(TrySendError::Full(3)));
+
+    // Slots are received in the order they were reserved, not filled.
+    slot2.send(2);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    slot1.send(1);
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv(), Ok(2));
+
+    drop(r);
+    assert_eq!(
+        s.try_reserve().err(),
+        Some(TryReserveSlotError::Disconnected)
+    );
+    assert_eq!(s.reserve().err(), Some(ReserveSlotError::Disconnected));
 }
 
 #[test]
-fn channel_through_channel() {
-    const COUNT: usize = 1000;
+fn reserve_abandon() {
+    let (s, r) = bounded(2);
 
-    type T = Box<Any + Send>;
+    let slot = s.reserve().unwrap();
+    s.send(2).unwrap();
+    assert!(s.is_full());
 
-    let (s, r) = bounded::<T>(1);
+    // The abandoned slot is skipped and its capacity is freed.
+    drop(slot);
+    assert_eq!(r.recv(), Ok(2));
+    assert!(s.is_empty());
+    s.send(3).unwrap();
+    s.send(4).unwrap();
+    assert_eq!(r.recv(), Ok(3));
+    assert_eq!(r.recv(), Ok(4));
 
-    scope(|scope| {
-        scope.spawn(move |_| {
-            let mut s = s;
+    // Abandoned slots are skipped even by receivers that only look at the front of the channel.
+    drop(s.reserve().unwrap());
+    drop(s.reserve().unwrap());
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.peek_contiguous().err(), Some(TryPeekError::Empty));
+    assert!(s.is_empty());
+}
 
-            for _ in 0..COUNT {
-                let (new_s, new_r) = bounded(1);
-                let new_r: T = Box::new(Some(new_r));
+#[test]
+fn reserve_blocks_receivers() {
+    let (s, r) = bounded(3);
 
-                s.send(new_r).unwrap();
-                s = new_s;
-            }
+    scope(|scope| {
+        let slot = s.reserve().unwrap();
+        s.send(2).unwrap();
+
+        scope.spawn(|_| {
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Ok(2));
+            assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+            assert_eq!(r.recv(), Ok(4));
         });
 
-        scope.spawn(move |_| {
-            let mut r = r;
+        thread::sleep(ms(500));
+        slot.send(1);
 
-            for _ in 0..COUNT {
-                r = r
-                    .recv()
-                    .unwrap()
-                    .downcast_mut::<Option<Receiver<T>>>()
-                    .unwrap()
-                    .take()
-                    .unwrap()
-            }
+        // Abandoning a slot wakes up a receiver blocked on it.
+        thread::sleep(ms(300));
+        let slot = s.reserve().unwrap();
+        thread::sleep(ms(500));
+        drop(slot);
+        thread::sleep(ms(100));
+        s.send(4).unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn reserve_blocks_select() {
+    let (s, r) = bounded::<i32>(1);
+    let slot = s.reserve().unwrap();
+
+    // A receive operation waiting for the reserved slot is not ready.
+    let mut sel = crossbeam_channel::Select::new();
+    sel.recv(&r);
+    assert!(sel.select_timeout(ms(100)).is_err());
+
+    slot.send(1);
+    let oper = sel.select_timeout(ms(100)).unwrap();
+    assert_eq!(oper.recv(&r), Ok(1));
+}
+
+#[test]
+fn reserve_waits_for_room() {
+    let (s, r) = bounded(1);
+    s.send(1).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            assert_eq!(r.recv(), Ok(1));
         });
+
+        let slot = s.reserve().unwrap();
+        slot.send(2);
+    })
+    .unwrap();
+
+    assert_eq!(r.recv(), Ok(2));
+}
+
+#[test]
+fn reserve_stress() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded::<usize>(3);
+    let received = AtomicUsize::new(0);
+    let sent = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let s = s.clone();
+            let sent = &sent;
+            scope.spawn(move |_| {
+                let mut rng = thread_rng();
+                for i in 0..COUNT {
+                    match rng.gen_range(0, 3) {
+                        0 => s.send(i).unwrap(),
+                        1 => s.reserve().unwrap().send(i),
+                        _ => {
+                            drop(s.reserve().unwrap());
+                            continue;
+                        }
+                    }
+                    sent.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        drop(s);
+
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                let mut rng = thread_rng();
+                loop {
+                    match rng.gen_range(0, 3) {
+                        0 => match r.recv_timeout(ms(10)) {
+                            Ok(_) => {}
+                            Err(RecvTimeoutError::Timeout) => continue,
+                            Err(RecvTimeoutError::Disconnected) => break,
+                        },
+                        1 => select! {
+                            recv(r) -> msg => if msg.is_err() {
+                                break;
+                            },
+                        },
+                        _ => match r.peek_contiguous() {
+                            Ok(run) => run.consume(1),
+                            Err(TryPeekError::Empty) => continue,
+                            Err(TryPeekError::Disconnected) => break,
+                            Err(TryPeekError::Unsupported) => unreachable!(),
+                        },
+                    }
+                    received.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
     })
     .unwrap();
+
+    assert_eq!(sent.load(Ordering::SeqCst), received.load(Ordering::SeqCst));
+}
+
+#[test]
+fn reserve_drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = bounded(4);
+    s.reserve().unwrap().send(DropCounter);
+    drop(s.reserve().unwrap());
+    s.send(DropCounter).unwrap();
+
+    // Abandoned slots left in the channel hold nothing to drop.
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn reserve_unbounded() {
+    let (s, r) = crossbeam_channel::unbounded::<i32>();
+
+    assert_eq!(s.reserve().err(), Some(ReserveSlotError::Unsupported));
+    assert_eq!(s.try_reserve().err(), Some(TryReserveSlotError::Unsupported));
+    assert!(r.is_empty());
 }
@@ -0,0 +1,109 @@
+//! Tests for the bridges to `futures` streams and channels.
+
+#![cfg(feature = "async-bridge")]
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate futures;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{block_on_recv, receiver_from_stream, spawn_forwarder};
+use crossbeam_channel::{bounded, unbounded, RecvError, Select};
+use futures::sync::mpsc;
+use futures::{stream, Future, Sink, Stream};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn bridge_to_async() {
+    let (s, r) = bounded(1);
+    let mut stream = r.bridge_to_async();
+
+    let producer = thread::spawn(move || {
+        for i in 0..100 {
+            s.send(i).unwrap();
+        }
+    });
+
+    for i in 0..100 {
+        assert_eq!(block_on_recv(&mut stream), Ok(i));
+    }
+    assert_eq!(block_on_recv(&mut stream), Err(RecvError));
+    producer.join().unwrap();
+}
+
+#[test]
+fn forwarder_stops_when_async_receiver_dropped() {
+    let (s, r) = unbounded();
+    let (tx, rx) = mpsc::channel(0);
+    let forwarder = spawn_forwarder(r, tx);
+
+    s.send(1).unwrap();
+    assert_eq!(rx.wait().next(), Some(Ok(1)));
+
+    // The next message fails to be forwarded, which stops the thread.
+    s.send(2).unwrap();
+    forwarder.join().unwrap();
+    assert!(s.send(3).is_err());
+}
+
+#[test]
+fn block_on_recv_waits() {
+    let (tx, mut rx) = mpsc::channel(0);
+
+    thread::spawn(move || {
+        thread::sleep(ms(100));
+        let tx = tx.send(1).wait().unwrap();
+        thread::sleep(ms(100));
+        tx.send(2).wait().unwrap();
+    });
+
+    assert_eq!(block_on_recv(&mut rx), Ok(1));
+    assert_eq!(block_on_recv(&mut rx), Ok(2));
+    assert_eq!(block_on_recv(&mut rx), Err(RecvError));
+}
+
+#[test]
+fn receiver_from_stream_in_select() {
+    let (s, r1) = unbounded();
+    let r2 = receiver_from_stream(stream::iter_ok::<_, ()>(vec![10, 20]));
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv(&r2), Ok(10));
+
+    s.send(1).unwrap();
+    let mut got = Vec::new();
+    for _ in 0..2 {
+        let oper = sel.select();
+        match oper.index() {
+            i if i == oper1 => got.push(oper.recv(&r1).unwrap()),
+            i if i == oper2 => got.push(oper.recv(&r2).unwrap()),
+            _ => unreachable!(),
+        }
+    }
+    got.sort();
+    assert_eq!(got, [1, 20]);
+
+    // The stream has ended.
+    assert_eq!(r2.recv(), Err(RecvError));
+}
+
+#[test]
+fn receiver_from_stream_error() {
+    let items = vec![Ok(1), Err("boom"), Ok(2)];
+    let r = receiver_from_stream(stream::iter_result(items));
+
+    select! {
+        recv(r) -> msg => assert_eq!(msg, Ok(1)),
+    }
+    assert_eq!(r.recv(), Err(RecvError));
+}
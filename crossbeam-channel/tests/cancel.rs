@@ -0,0 +1,131 @@
+//! Tests for `Receiver::recv_cancellable`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, cancel_token, unbounded, RecvCancelError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let (token, canceller) = cancel_token();
+    assert!(!token.is_cancelled());
+
+    s.send(7).unwrap();
+    assert_eq!(r.recv_cancellable(&token), Ok(7));
+
+    canceller.cancel();
+    assert!(token.is_cancelled());
+    assert!(canceller.is_cancelled());
+    assert_eq!(r.recv_cancellable(&token), Err(RecvCancelError::Cancelled));
+}
+
+#[test]
+fn ready_message_after_cancel() {
+    let (s, r) = unbounded();
+    let (token, canceller) = cancel_token();
+    canceller.cancel();
+    canceller.cancel();
+
+    // The token stays cancelled, but ready messages are still received.
+    s.send(1).unwrap();
+    assert_eq!(r.recv_cancellable(&token), Ok(1));
+    assert_eq!(r.recv_cancellable(&token), Err(RecvCancelError::Cancelled));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = bounded::<i32>(1);
+    let (token, _canceller) = cancel_token();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            drop(s);
+        });
+
+        assert_eq!(
+            r.recv_cancellable(&token),
+            Err(RecvCancelError::Disconnected)
+        );
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_wakes_up() {
+    let (s, r) = bounded(0);
+    let (token, _canceller) = cancel_token();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.send(7).unwrap();
+        });
+
+        assert_eq!(r.recv_cancellable(&token), Ok(7));
+    })
+    .unwrap();
+}
+
+#[test]
+fn cancel_wakes_up() {
+    let (_s, r) = unbounded::<i32>();
+    let (token, canceller) = cancel_token();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            canceller.cancel();
+        });
+
+        let start = Instant::now();
+        assert_eq!(r.recv_cancellable(&token), Err(RecvCancelError::Cancelled));
+        assert!(start.elapsed() >= ms(50));
+        assert!(start.elapsed() < ms(5000));
+    })
+    .unwrap();
+}
+
+#[test]
+fn shared_token() {
+    const THREADS: usize = 4;
+
+    let (_s1, r1) = unbounded::<i32>();
+    let (_s2, r2) = bounded::<i32>(0);
+    let (token, canceller) = cancel_token();
+
+    scope(|scope| {
+        for i in 0..THREADS {
+            let r = if i % 2 == 0 { r1.clone() } else { r2.clone() };
+            let token = token.clone();
+            scope.spawn(move |_| {
+                assert_eq!(r.recv_cancellable(&token), Err(RecvCancelError::Cancelled));
+            });
+        }
+
+        thread::sleep(ms(100));
+        canceller.clone().cancel();
+    })
+    .unwrap();
+}
+
+#[test]
+fn drop_canceller() {
+    let (s, r) = unbounded();
+    let (token, canceller) = cancel_token();
+    drop(canceller);
+
+    // Dropping the canceller does not cancel the token.
+    assert!(!token.is_cancelled());
+    s.send(1).unwrap();
+    assert_eq!(r.recv_cancellable(&token), Ok(1));
+}
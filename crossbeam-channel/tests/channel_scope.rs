@@ -0,0 +1,107 @@
+//! Tests for `channel_scope`.
+
+extern crate crossbeam_channel;
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{channel_scope, unbounded, RecvCancelError, TryRecvError};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn blocked_recv_cancelled() {
+    let cancelled = AtomicUsize::new(0);
+
+    channel_scope(|scope| {
+        let (s, r) = scope.channel_bounded::<i32>(1);
+        let (s2, r2) = scope.channel_unbounded::<i32>();
+        let cancelled = &cancelled;
+
+        for r in vec![r.clone(), r] {
+            scope.spawn(move |_| {
+                assert_eq!(r.recv(), Err(RecvCancelError::Cancelled));
+                assert!(r.is_cancelled());
+                cancelled.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        scope.spawn(move |_| {
+            assert_eq!(r2.recv(), Err(RecvCancelError::Cancelled));
+            cancelled.fetch_add(1, Ordering::SeqCst);
+        });
+
+        thread::sleep(ms(100));
+        assert!(!scope.is_cancelled());
+        assert_eq!(cancelled.load(Ordering::SeqCst), 0);
+
+        // Keep the senders alive until the threads have been joined.
+        (s, s2)
+    })
+    .unwrap();
+
+    assert_eq!(cancelled.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn messages_received_before_cancellation() {
+    let received = AtomicUsize::new(0);
+
+    let s = channel_scope(|scope| {
+        let (s, r) = scope.channel_unbounded();
+        let received = &received;
+
+        for i in 0..10 {
+            s.send(i).unwrap();
+        }
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            while let Ok(_) = r.recv() {
+                received.fetch_add(1, Ordering::SeqCst);
+            }
+            assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+        });
+        s
+    })
+    .unwrap();
+
+    assert_eq!(received.load(Ordering::SeqCst), 10);
+    assert!(s.send(10).is_err());
+}
+
+#[test]
+fn disconnected() {
+    channel_scope(|scope| {
+        let (s, r) = unbounded::<i32>();
+        let r = scope.receiver(r);
+        drop(s);
+        assert_eq!(r.recv(), Err(RecvCancelError::Disconnected));
+    })
+    .unwrap();
+}
+
+#[test]
+fn cancelled_on_panic() {
+    let joined = AtomicUsize::new(0);
+
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        channel_scope(|scope| {
+            let (s, r) = scope.channel_bounded::<i32>(0);
+            let joined = &joined;
+
+            scope.spawn(move |_| {
+                assert_eq!(r.recv(), Err(RecvCancelError::Cancelled));
+                drop(s);
+                joined.fetch_add(1, Ordering::SeqCst);
+            });
+            panic!();
+        })
+    }));
+
+    // The panic is propagated after the thread has been joined.
+    assert!(res.is_err());
+    assert_eq!(joined.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,237 @@
+//! Tests for the coalescing channel flavor.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{coalescing_channel, ChannelKind};
+use crossbeam_channel::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = coalescing_channel();
+    s.send((1, 7)).unwrap();
+    assert_eq!(r.try_recv(), Ok((1, 7)));
+
+    s.send((1, 8)).unwrap();
+    assert_eq!(r.recv(), Ok((1, 8)));
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn capacity() {
+    let (s, r) = coalescing_channel::<i32, ()>();
+    assert_eq!(s.capacity(), None);
+    assert_eq!(r.capacity(), None);
+    assert_eq!(s.kind(), ChannelKind::Unbounded);
+    assert_eq!(r.kind(), ChannelKind::Unbounded);
+    assert!(!s.is_full());
+}
+
+#[test]
+fn coalesce() {
+    let (s, r) = coalescing_channel();
+    s.send(("a", 1)).unwrap();
+    s.send(("b", 1)).unwrap();
+    s.send(("a", 2)).unwrap();
+    s.send(("c", 1)).unwrap();
+    s.send(("b", 2)).unwrap();
+    s.send(("a", 3)).unwrap();
+    assert_eq!(r.len(), 3);
+
+    // Messages keep the place of their key's first send.
+    let v: Vec<_> = r.try_iter().collect();
+    assert_eq!(v, [("a", 3), ("b", 2), ("c", 1)]);
+    assert!(r.is_empty());
+}
+
+#[test]
+fn resend_after_recv() {
+    let (s, r) = coalescing_channel();
+    s.send(("a", 1)).unwrap();
+    s.send(("b", 1)).unwrap();
+    assert_eq!(r.recv(), Ok(("a", 1)));
+
+    // The key has been received, so it goes to the back of the queue.
+    s.send(("a", 2)).unwrap();
+    s.send(("b", 2)).unwrap();
+    assert_eq!(r.recv(), Ok(("b", 2)));
+    assert_eq!(r.recv(), Ok(("a", 2)));
+    assert!(r.is_empty());
+}
+
+#[test]
+fn hash_collisions() {
+    use std::hash::{Hash, Hasher};
+
+    // All keys hash equally, so only equality tells them apart.
+    #[derive(Debug, PartialEq, Eq)]
+    struct Key(u32);
+
+    impl Hash for Key {
+        fn hash<H: Hasher>(&self, _state: &mut H) {}
+    }
+
+    let (s, r) = coalescing_channel();
+    for round in 0..3 {
+        for k in 0..10 {
+            s.send((Key(k), round)).unwrap();
+        }
+    }
+    assert_eq!(r.len(), 10);
+
+    for k in 0..5 {
+        assert_eq!(r.recv(), Ok((Key(k), 2)));
+    }
+    s.send((Key(7), 3)).unwrap();
+    s.send((Key(0), 3)).unwrap();
+    for k in 5..10 {
+        let v = if k == 7 { 3 } else { 2 };
+        assert_eq!(r.recv(), Ok((Key(k), v)));
+    }
+    assert_eq!(r.recv(), Ok((Key(0), 3)));
+    assert!(r.is_empty());
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = coalescing_channel();
+    s.send((1, 1)).unwrap();
+    s.send((2, 2)).unwrap();
+    drop(s);
+
+    assert_eq!(r.recv(), Ok((1, 1)));
+    assert_eq!(r.recv(), Ok((2, 2)));
+    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+
+    let (s, r) = coalescing_channel();
+    drop(r);
+    assert_eq!(s.send((1, 1)), Err(SendError((1, 1))));
+    assert_eq!(s.try_send((2, 2)), Err(TrySendError::Disconnected((2, 2))));
+}
+
+#[test]
+fn recv_wakes_up() {
+    let (s, r) = coalescing_channel();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.send((1, 7)).unwrap();
+        });
+
+        assert_eq!(r.recv(), Ok((1, 7)));
+    })
+    .unwrap();
+}
+
+#[test]
+fn wait_drained() {
+    let (s, r) = coalescing_channel();
+    s.send((1, 1)).unwrap();
+    s.send((1, 2)).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            assert_eq!(r.recv(), Ok((1, 2)));
+        });
+
+        assert!(s.wait_drained());
+    })
+    .unwrap();
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = coalescing_channel();
+    let (_s2, r2) = coalescing_channel::<i32, i32>();
+    s1.send((1, 1)).unwrap();
+    s1.send((1, 5)).unwrap();
+
+    select! {
+        recv(r1) -> msg => assert_eq!(msg, Ok((1, 5))),
+        recv(r2) -> _ => panic!(),
+    }
+
+    select! {
+        send(s1, (2, 2)) -> res => assert_eq!(res, Ok(())),
+        default => panic!(),
+    }
+    assert_eq!(r1.recv(), Ok((2, 2)));
+}
+
+#[test]
+fn latest_value_wins() {
+    const COUNT: usize = 10_000;
+    const KEYS: usize = 10;
+
+    let (s, r) = coalescing_channel();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                s.send((i % KEYS, i)).unwrap();
+            }
+        });
+
+        let mut last = HashMap::new();
+        let mut done = 0;
+        while done < KEYS {
+            let (k, v) = r.recv().unwrap();
+            // Values of each key only move forward.
+            if let Some(&prev) = last.get(&k) {
+                assert!(v > prev);
+            }
+            last.insert(k, v);
+            if v >= COUNT - KEYS {
+                done += 1;
+            }
+        }
+    })
+    .unwrap();
+
+    assert!(r.is_empty());
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = coalescing_channel();
+    for i in 0..10 {
+        s.send((i % 3, DropCounter)).unwrap();
+    }
+    // Overwritten messages are dropped right away.
+    assert_eq!(DROPS.load(Ordering::SeqCst), 7);
+
+    drop(r.recv().unwrap());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 8);
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 10);
+}
@@ -0,0 +1,95 @@
+//! Tests for deadlock detection.
+
+#![cfg(feature = "deadlock_detection")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{blocked_threads, bounded, unbounded, Select};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn is_blocked(name: &str) -> bool {
+    blocked_threads()
+        .iter()
+        .any(|info| info.thread().name() == Some(name))
+}
+
+#[test]
+fn recv() {
+    let (s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope
+            .builder()
+            .name("deadlock-recv".to_string())
+            .spawn(|_| r.recv().unwrap())
+            .unwrap();
+
+        thread::sleep(ms(500));
+        assert!(is_blocked("deadlock-recv"));
+
+        s.send(1).unwrap();
+        thread::sleep(ms(500));
+        assert!(!is_blocked("deadlock-recv"));
+    })
+    .unwrap();
+}
+
+#[test]
+fn send_and_select() {
+    let (s1, r1) = bounded::<i32>(0);
+    let (s2, r2) = bounded::<i32>(0);
+
+    scope(|scope| {
+        scope
+            .builder()
+            .name("deadlock-send".to_string())
+            .spawn(|_| s1.send(1).unwrap())
+            .unwrap();
+        scope
+            .builder()
+            .name("deadlock-select".to_string())
+            .spawn(|_| {
+                let mut sel = Select::new();
+                sel.recv(&r2);
+                let oper = sel.select();
+                oper.recv(&r2).unwrap();
+            })
+            .unwrap();
+
+        thread::sleep(ms(500));
+        assert!(is_blocked("deadlock-send"));
+        assert!(is_blocked("deadlock-select"));
+
+        r1.recv().unwrap();
+        s2.send(2).unwrap();
+        thread::sleep(ms(500));
+        assert!(!is_blocked("deadlock-send"));
+        assert!(!is_blocked("deadlock-select"));
+    })
+    .unwrap();
+}
+
+#[test]
+fn timeout_not_tracked() {
+    let (_s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope
+            .builder()
+            .name("deadlock-timeout".to_string())
+            .spawn(|_| r.recv_timeout(ms(1000)).unwrap_err())
+            .unwrap();
+
+        thread::sleep(ms(500));
+        assert!(!is_blocked("deadlock-timeout"));
+    })
+    .unwrap();
+}
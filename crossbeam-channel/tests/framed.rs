@@ -0,0 +1,124 @@
+//! Tests for `FramedReceiver`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{unbounded, FramedReceiver, RecvTimeoutError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn frame(payload: &[u8]) -> Vec<u8> {
+    let len = payload.len() as u32;
+    let mut v = vec![
+        (len >> 24) as u8,
+        (len >> 16) as u8,
+        (len >> 8) as u8,
+        len as u8,
+    ];
+    v.extend_from_slice(payload);
+    v
+}
+
+#[test]
+fn whole_frames() {
+    let (s, r) = unbounded();
+    let mut r = FramedReceiver::new(r);
+
+    s.send(frame(b"hello")).unwrap();
+    s.send(frame(b"")).unwrap();
+    assert_eq!(r.recv_frame_timeout(ms(100)), Ok(b"hello".to_vec()));
+    assert_eq!(r.recv_frame_timeout(ms(100)), Ok(Vec::new()));
+    assert!(r.buffered().is_empty());
+}
+
+#[test]
+fn several_frames_in_one_chunk() {
+    let (s, r) = unbounded();
+    let mut r = FramedReceiver::new(r);
+
+    let mut chunk = frame(b"a");
+    chunk.extend(frame(b"bc"));
+    chunk.extend(frame(b"def"));
+    s.send(chunk).unwrap();
+    drop(s);
+
+    assert_eq!(r.recv_frame_timeout(ms(100)), Ok(b"a".to_vec()));
+    assert_eq!(r.recv_frame_timeout(ms(100)), Ok(b"bc".to_vec()));
+    assert_eq!(r.recv_frame_timeout(ms(100)), Ok(b"def".to_vec()));
+    assert_eq!(
+        r.recv_frame_timeout(ms(100)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
+
+#[test]
+fn partial_kept_across_timeout() {
+    let (s, r) = unbounded::<&'static [u8]>();
+    let mut r = FramedReceiver::new(r);
+
+    s.send(&[0, 0]).unwrap();
+    assert_eq!(r.recv_frame_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+    assert_eq!(r.buffered(), [0, 0]);
+
+    s.send(&[0, 2, b'x']).unwrap();
+    assert_eq!(r.recv_frame_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+    assert_eq!(r.buffered(), [0, 0, 0, 2, b'x']);
+
+    s.send(&[b'y', 0]).unwrap();
+    assert_eq!(r.recv_frame_timeout(ms(50)), Ok(b"xy".to_vec()));
+    assert_eq!(r.buffered(), [0]);
+
+    let (_, rest) = r.into_inner();
+    assert_eq!(rest, [0]);
+}
+
+#[test]
+fn disconnected_with_partial() {
+    let (s, r) = unbounded();
+    let mut r = FramedReceiver::new(r);
+
+    s.send(vec![0, 0, 0, 9, 1, 2]).unwrap();
+    drop(s);
+
+    assert_eq!(
+        r.recv_frame_timeout(ms(100)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+    assert_eq!(r.buffered(), [0, 0, 0, 9, 1, 2]);
+}
+
+#[test]
+fn byte_by_byte() {
+    const COUNT: usize = 100;
+
+    let (s, r) = unbounded();
+    let mut r = FramedReceiver::new(r);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                for b in frame(&vec![i as u8; i]) {
+                    s.send(vec![b]).unwrap();
+                }
+                if i % 10 == 0 {
+                    thread::sleep(ms(1));
+                }
+            }
+        });
+
+        for i in 0..COUNT {
+            assert_eq!(r.recv_frame_timeout(ms(5000)), Ok(vec![i as u8; i]));
+        }
+        assert_eq!(
+            r.recv_frame_timeout(ms(5000)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    })
+    .unwrap();
+}
@@ -0,0 +1,272 @@
+//! Tests for the heap channel flavor.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate rand;
+
+use std::cmp::Reverse;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{priority_channel, ChannelKind, Select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
+use crossbeam_utils::thread::scope;
+use rand::{thread_rng, Rng};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = priority_channel(1);
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+
+    s.send(8).unwrap();
+    assert_eq!(r.recv(), Ok(8));
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn capacity() {
+    for i in 1..10 {
+        let (s, r) = priority_channel::<()>(i);
+        assert_eq!(s.capacity(), Some(i));
+        assert_eq!(r.capacity(), Some(i));
+        assert_eq!(s.kind(), ChannelKind::Bounded);
+        assert_eq!(r.kind(), ChannelKind::Bounded);
+    }
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    priority_channel::<i32>(0);
+}
+
+#[test]
+fn priority_order() {
+    let (s, r) = priority_channel(10);
+    for &x in &[3, 1, 4, 1, 5, 9, 2, 6, 5, 3] {
+        s.send(x).unwrap();
+    }
+    assert!(s.is_full());
+    assert_eq!(r.len(), 10);
+
+    let v: Vec<_> = r.try_iter().collect();
+    assert_eq!(v, [9, 6, 5, 5, 4, 3, 3, 2, 1, 1]);
+    assert!(r.is_empty());
+}
+
+#[test]
+fn reverse_order() {
+    let (s, r) = priority_channel(4);
+    s.send(Reverse(2)).unwrap();
+    s.send(Reverse(3)).unwrap();
+    s.send(Reverse(1)).unwrap();
+
+    assert_eq!(r.recv(), Ok(Reverse(1)));
+    s.send(Reverse(0)).unwrap();
+    assert_eq!(r.recv(), Ok(Reverse(0)));
+    assert_eq!(r.recv(), Ok(Reverse(2)));
+    assert_eq!(r.recv(), Ok(Reverse(3)));
+}
+
+#[test]
+fn full() {
+    let (s, r) = priority_channel(2);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    assert_eq!(s.try_send(3), Err(TrySendError::Full(3)));
+    assert_eq!(s.send_timeout(3, ms(50)), Err(SendTimeoutError::Timeout(3)));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            assert_eq!(r.recv(), Ok(2));
+        });
+
+        // Blocks until a receiver frees a slot.
+        s.send(0).unwrap();
+    })
+    .unwrap();
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(0));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = priority_channel(2);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    drop(s);
+
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+
+    let (s, r) = priority_channel(2);
+    drop(r);
+    assert_eq!(s.send(1), Err(SendError(1)));
+    assert_eq!(s.try_send(2), Err(TrySendError::Disconnected(2)));
+}
+
+#[test]
+fn disconnect_wakes_receiver() {
+    let (s, r) = priority_channel::<i32>(1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn make_sender() {
+    let (s, r) = priority_channel(2);
+    let s2 = r.make_sender().unwrap();
+    assert!(s.identical_to(&s2));
+    assert_eq!(s.channel_ptr(), r.channel_ptr());
+
+    s2.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv(), Ok(1));
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = priority_channel(2);
+    let (s2, r2) = priority_channel::<i32>(1);
+    s1.send(1).unwrap();
+    s1.send(5).unwrap();
+
+    select! {
+        recv(r1) -> msg => assert_eq!(msg, Ok(5)),
+        recv(r2) -> _ => panic!(),
+    }
+
+    // The first channel has room for one message, the second for one more.
+    let mut sel = Select::new();
+    let oper1 = sel.send(&s1);
+    let oper2 = sel.send(&s2);
+    for x in 10..12 {
+        let oper = sel.select();
+        match oper.index() {
+            i if i == oper1 => oper.send(&s1, x).unwrap(),
+            i if i == oper2 => oper.send(&s2, x).unwrap(),
+            _ => unreachable!(),
+        }
+    }
+    assert!(sel.try_select().is_err());
+    assert!(s1.is_full());
+    assert!(s2.is_full());
+    assert!(r1.recv().unwrap() >= 10);
+}
+
+#[test]
+fn select_wakes_up() {
+    let (s, r) = priority_channel(1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.send(7).unwrap();
+        });
+
+        select! {
+            recv(r) -> msg => assert_eq!(msg, Ok(7)),
+            default(ms(1000)) => panic!(),
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn sorted_drain() {
+    const COUNT: usize = 1000;
+
+    let (s, r) = priority_channel(COUNT);
+    let mut rng = thread_rng();
+    let mut v: Vec<usize> = (0..COUNT).map(|_| rng.gen_range(0, 100)).collect();
+    for &x in &v {
+        s.send(x).unwrap();
+    }
+
+    v.sort_by(|a, b| b.cmp(a));
+    let got: Vec<_> = r.try_iter().collect();
+    assert_eq!(got, v);
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = priority_channel::<usize>(3);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let n = r.recv().unwrap();
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct DropCounter(usize);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = priority_channel(10);
+    for i in 0..10 {
+        s.send(DropCounter(i)).unwrap();
+    }
+    assert_eq!(r.recv().unwrap().0, 9);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 10);
+}
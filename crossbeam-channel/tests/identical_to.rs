@@ -1,8 +1,9 @@
 extern crate crossbeam_channel;
 
+use std::collections::HashSet;
 use std::time::Duration;
 
-use crossbeam_channel::{after, bounded, never, tick, unbounded};
+use crossbeam_channel::{after, bounded, never, oneshot, tick, unbounded};
 
 fn ms(ms: u64) -> Duration {
     Duration::from_millis(ms)
@@ -111,3 +112,55 @@ fn different_flavours_identical_to() {
     assert!(!s1.identical_to(&s2));
     assert!(!r1.identical_to(&r2));
 }
+
+#[test]
+fn channel_ptr() {
+    for &cap in &[0, 1] {
+        let (s, r) = bounded::<usize>(cap);
+        let (s2, r2) = bounded::<usize>(cap);
+        assert_eq!(s.channel_ptr(), r.channel_ptr());
+        assert_eq!(s.channel_ptr(), s.clone().channel_ptr());
+        assert_eq!(r.channel_ptr(), r.clone().channel_ptr());
+        assert_ne!(s.channel_ptr(), s2.channel_ptr());
+        assert_ne!(r.channel_ptr(), r2.channel_ptr());
+    }
+
+    let (s, r) = unbounded::<usize>();
+    assert_eq!(s.channel_ptr(), r.clone().channel_ptr());
+    let (s, r) = oneshot::<usize>();
+    assert_eq!(s.clone().channel_ptr(), r.channel_ptr());
+
+    let r = after(ms(50));
+    assert_eq!(r.channel_ptr(), r.clone().channel_ptr());
+    assert_ne!(r.channel_ptr(), after(ms(50)).channel_ptr());
+    let r = tick(ms(50));
+    assert_eq!(r.channel_ptr(), r.clone().channel_ptr());
+    assert_ne!(r.channel_ptr(), tick(ms(50)).channel_ptr());
+
+    assert_eq!(
+        never::<usize>().channel_ptr(),
+        never::<String>().channel_ptr()
+    );
+}
+
+#[test]
+fn channel_ptr_type_erased() {
+    let (s1, r1) = unbounded::<usize>();
+    let (s2, r2) = bounded::<String>(1);
+
+    // Deduplicate subscriptions without knowing their message types.
+    let mut seen = HashSet::new();
+    let mut kept = Vec::new();
+    for &(ptr, name) in &[
+        (s1.channel_ptr(), "s1"),
+        (r1.channel_ptr(), "r1"),
+        (s2.channel_ptr(), "s2"),
+        (r2.channel_ptr(), "r2"),
+    ] {
+        if seen.insert(ptr) {
+            kept.push(name);
+        }
+    }
+
+    assert_eq!(kept, ["s1", "s2"]);
+}
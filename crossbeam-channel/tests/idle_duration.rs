@@ -0,0 +1,114 @@
+//! Tests for `Receiver::idle_duration`.
+
+#[macro_use]
+extern crate crossbeam_channel;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, bounded_priority, never, oneshot, unbounded};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn since_first_call() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+    thread::sleep(ms(100));
+    r.recv().unwrap();
+    thread::sleep(ms(100));
+
+    // Tracking starts with the first call.
+    assert_eq!(r.idle_duration(), ms(0));
+    thread::sleep(ms(100));
+    assert!(r.idle_duration() >= ms(100));
+}
+
+#[test]
+fn reset_by_recv() {
+    let (s, r) = bounded(10);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    s.send(3).unwrap();
+    r.idle_duration();
+
+    thread::sleep(ms(100));
+    r.try_recv().unwrap();
+    assert!(r.idle_duration() < ms(100));
+
+    thread::sleep(ms(100));
+    r.recv().unwrap();
+    assert!(r.idle_duration() < ms(100));
+
+    thread::sleep(ms(100));
+    r.recv_timeout(ms(100)).unwrap();
+    assert!(r.idle_duration() < ms(100));
+}
+
+#[test]
+fn failed_recv() {
+    let (s, r) = unbounded::<i32>();
+    r.idle_duration();
+    thread::sleep(ms(100));
+
+    assert!(r.try_recv().is_err());
+    assert!(r.recv_timeout(ms(10)).is_err());
+    assert!(r.idle_duration() >= ms(100));
+
+    drop(s);
+    assert!(r.recv().is_err());
+    assert!(r.idle_duration() >= ms(100));
+}
+
+#[test]
+fn shared_by_receivers() {
+    let (s, r1) = unbounded();
+    let r2 = r1.clone();
+    s.send(1).unwrap();
+    r1.idle_duration();
+
+    thread::sleep(ms(100));
+    assert!(r1.idle_duration() >= ms(100));
+    assert!(r2.idle_duration() >= ms(100));
+
+    r2.recv().unwrap();
+    assert!(r1.idle_duration() < ms(100));
+    assert!(r2.idle_duration() < ms(100));
+}
+
+#[test]
+fn select() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+    r.idle_duration();
+    thread::sleep(ms(100));
+
+    select! {
+        recv(r) -> msg => assert_eq!(msg, Ok(1)),
+    }
+    assert!(r.idle_duration() < ms(100));
+}
+
+#[test]
+fn flavors() {
+    let (s, r) = oneshot();
+    s.send(1).unwrap();
+    r.idle_duration();
+    thread::sleep(ms(100));
+    r.recv().unwrap();
+    assert!(r.idle_duration() < ms(100));
+
+    let (s, r) = bounded_priority(1);
+    s.send_urgent(1).unwrap();
+    r.idle_duration();
+    thread::sleep(ms(100));
+    r.recv().unwrap();
+    assert!(r.idle_duration() < ms(100));
+
+    let r = after(ms(0));
+    thread::sleep(ms(100));
+    assert_eq!(r.idle_duration(), ms(0));
+    assert_eq!(never::<i32>().idle_duration(), ms(0));
+}
@@ -3,7 +3,10 @@
 extern crate crossbeam_channel;
 extern crate crossbeam_utils;
 
-use crossbeam_channel::unbounded;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded};
 use crossbeam_utils::thread::scope;
 
 #[test]
@@ -111,3 +114,87 @@ fn recv_into_iter_borrowed() {
     assert_eq!(iter.next().unwrap(), 2);
     assert_eq!(iter.next().is_none(), true);
 }
+
+#[test]
+fn recv_drain() {
+    let (s, r) = bounded(4);
+    for i in 0..3 {
+        s.send(i).unwrap();
+    }
+
+    let mut drain = r.drain();
+    assert_eq!(drain.len(), 3);
+    s.send(3).unwrap();
+
+    assert_eq!(drain.next(), Some(0));
+    assert_eq!(drain.len(), 2);
+    assert_eq!(drain.collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(r.try_recv(), Ok(3));
+    assert_eq!(r.drain().len(), 0);
+}
+
+#[test]
+fn recv_drain_competing() {
+    let (s, r) = unbounded();
+    for i in 0..3 {
+        s.send(i).unwrap();
+    }
+
+    // Another receiver takes messages after the snapshot, so the iterator stops early.
+    let mut drain = r.drain();
+    assert_eq!(r.try_recv(), Ok(0));
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(drain.next(), Some(2));
+    assert_eq!(drain.len(), 2);
+    assert_eq!(drain.next(), None);
+    assert_eq!(drain.len(), 0);
+}
+
+#[test]
+fn recv_drain_zero() {
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(1).unwrap());
+
+        // A blocked sender does not count as a message in the channel.
+        thread::sleep(Duration::from_millis(500));
+        assert_eq!(r.drain().len(), 0);
+        assert_eq!(r.drain().next(), None);
+        assert_eq!(r.recv(), Ok(1));
+    })
+    .unwrap();
+}
+
+#[test]
+fn drain_all() {
+    let (s, r) = unbounded();
+    for i in 0..100 {
+        s.send(i).unwrap();
+    }
+
+    // Senders are still alive, so only the buffered messages are taken.
+    let r2 = r.clone();
+    assert_eq!(r.drain_all(), (0..100).collect::<Vec<_>>());
+    assert!(s.send(100).is_ok());
+    assert_eq!(r2.try_recv(), Ok(100));
+
+    // Dropping the last receiver disconnects the channel.
+    s.send(101).unwrap();
+    assert_eq!(r2.drain_all(), [101]);
+    assert!(s.send(102).is_err());
+}
+
+#[test]
+fn drain_all_zero() {
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| assert!(s.send(1).is_err()));
+
+        // A blocked sender does not count as a message in the channel.
+        thread::sleep(Duration::from_millis(500));
+        assert_eq!(r.drain_all(), []);
+    })
+    .unwrap();
+}
@@ -0,0 +1,66 @@
+extern crate crossbeam_channel;
+
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, never, oneshot, tick, unbounded, ChannelKind};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn array_kind() {
+    let (s, r) = bounded::<usize>(1);
+    assert_eq!(s.kind(), ChannelKind::Bounded);
+    assert_eq!(r.kind(), ChannelKind::Bounded);
+}
+
+#[test]
+fn list_kind() {
+    let (s, r) = unbounded::<usize>();
+    assert_eq!(s.kind(), ChannelKind::Unbounded);
+    assert_eq!(r.kind(), ChannelKind::Unbounded);
+}
+
+#[test]
+fn zero_kind() {
+    let (s, r) = bounded::<usize>(0);
+    assert_eq!(s.kind(), ChannelKind::Rendezvous);
+    assert_eq!(r.kind(), ChannelKind::Rendezvous);
+}
+
+#[test]
+fn oneshot_kind() {
+    let (s, r) = oneshot::<usize>();
+    assert_eq!(s.kind(), ChannelKind::Bounded);
+    assert_eq!(r.kind(), ChannelKind::Bounded);
+}
+
+#[test]
+fn after_kind() {
+    assert_eq!(after(ms(50)).kind(), ChannelKind::Bounded);
+}
+
+#[test]
+fn tick_kind() {
+    assert_eq!(tick(ms(50)).kind(), ChannelKind::Bounded);
+}
+
+#[test]
+fn never_kind() {
+    assert_eq!(never::<usize>().kind(), ChannelKind::Rendezvous);
+}
+
+#[test]
+fn kind_agrees_with_capacity() {
+    for cap in 0..4 {
+        let (s, r) = bounded::<usize>(cap);
+        let expected = if cap == 0 {
+            ChannelKind::Rendezvous
+        } else {
+            ChannelKind::Bounded
+        };
+        assert_eq!(s.kind(), expected);
+        assert_eq!(r.kind(), expected);
+    }
+}
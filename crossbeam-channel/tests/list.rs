@@ -6,13 +6,15 @@ extern crate crossbeam_utils;
 extern crate rand;
 
 use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crossbeam_channel::{unbounded, Receiver};
-use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{unbounded, unbounded_with_drop_handler, Decision, Receiver, Select};
+use crossbeam_channel::{PeekError, RecvError, RecvTimeoutError, TryPeekError, TryRecvError};
 use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
 use crossbeam_utils::thread::scope;
 use rand::{thread_rng, Rng};
@@ -114,6 +116,28 @@ fn recv() {
     .unwrap();
 }
 
+#[test]
+fn recv_reporting() {
+    let (s, r) = unbounded();
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.recv_reporting(), Ok((1, false)));
+    assert_eq!(r.recv_reporting(), Ok((2, false)));
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv_reporting(), Ok((3, true)));
+            assert_eq!(r.recv_reporting(), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            s.send(3).unwrap();
+        });
+    })
+    .unwrap();
+}
+
 #[test]
 fn recv_timeout() {
     let (s, r) = unbounded::<i32>();
@@ -135,6 +159,433 @@ fn recv_timeout() {
     .unwrap();
 }
 
+#[test]
+fn recv_timeout_ready() {
+    let (s, r) = unbounded();
+    s.send(7).unwrap();
+    s.send(8).unwrap();
+
+    // Ready messages are received even if the timeout has already elapsed.
+    assert_eq!(r.recv_timeout(ms(0)), Ok(7));
+    assert_eq!(r.recv_timeout(ms(0)), Ok(8));
+    assert_eq!(r.recv_timeout(ms(0)), Err(RecvTimeoutError::Timeout));
+
+    // A ready message is received without reading the clock. Adding this timeout to the current
+    // time would panic with an overflow.
+    let huge = Duration::new(u64::max_value(), 0);
+    s.send(9).unwrap();
+    assert_eq!(r.recv_timeout(huge), Ok(9));
+
+    drop(s);
+    assert_eq!(r.recv_timeout(ms(0)), Err(RecvTimeoutError::Disconnected));
+    assert_eq!(r.recv_timeout(huge), Err(RecvTimeoutError::Disconnected));
+}
+
+#[test]
+fn recv_timeout_zero() {
+    let (_s, r) = unbounded::<i32>();
+
+    // A zero timeout makes a single attempt and never blocks.
+    let start = Instant::now();
+    for _ in 0..1000 {
+        assert_eq!(r.recv_timeout(ms(0)), Err(RecvTimeoutError::Timeout));
+    }
+    assert!(start.elapsed() < ms(500));
+}
+
+#[test]
+fn recv_timeout_detailed() {
+    let (s, r) = unbounded();
+
+    let start = Instant::now();
+    match r.recv_timeout_detailed(ms(100)) {
+        Err((RecvTimeoutError::Timeout, overshoot)) => {
+            // The overshoot is at most the time spent beyond the requested timeout.
+            assert!(start.elapsed() >= ms(100) + overshoot);
+            assert!(overshoot < ms(1000));
+        }
+        res => panic!("{:?}", res),
+    }
+
+    s.send(7).unwrap();
+    assert_eq!(r.recv_timeout_detailed(ms(100)), Ok(7));
+    match r.recv_timeout_detailed(ms(0)) {
+        Err((RecvTimeoutError::Timeout, overshoot)) => assert!(overshoot < ms(1000)),
+        res => panic!("{:?}", res),
+    }
+
+    drop(s);
+    assert_eq!(
+        r.recv_timeout_detailed(ms(100)),
+        Err((RecvTimeoutError::Disconnected, ms(0)))
+    );
+}
+
+#[test]
+fn recv_until_deadline() {
+    let (s, r) = unbounded();
+
+    let deadline = Instant::now() + ms(100);
+    assert_eq!(
+        r.recv_until_deadline(deadline),
+        Err(RecvTimeoutError::Timeout)
+    );
+    assert!(Instant::now() >= deadline);
+
+    // Once the deadline has passed, ready messages are left in the channel.
+    s.send(7).unwrap();
+    assert_eq!(
+        r.recv_until_deadline(deadline),
+        Err(RecvTimeoutError::Timeout)
+    );
+    assert_eq!(r.recv_until_deadline(Instant::now() + ms(1000)), Ok(7));
+
+    drop(s);
+    assert_eq!(
+        r.recv_until_deadline(Instant::now() + ms(1000)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
+
+#[test]
+fn recv_until_deadline_steady_stream() {
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            // Keep sending faster than the period, which would starve a loop around `recv_timeout`.
+            while s.send(()).is_ok() {
+                thread::sleep(ms(1));
+            }
+        });
+
+        let start = Instant::now();
+        let deadline = start + ms(200);
+        let mut received = 0;
+        while r.recv_until_deadline(deadline).is_ok() {
+            received += 1;
+        }
+
+        assert!(received > 0);
+        assert!(start.elapsed() < ms(1000));
+        drop(r);
+    })
+    .unwrap();
+}
+
+#[test]
+fn wait_closed() {
+    let (s, r) = unbounded::<i32>();
+    let r2 = r.clone();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            s.wait_closed();
+            assert!(s.send(1).is_err());
+        });
+        scope.spawn(|_| {
+            s.wait_closed();
+        });
+
+        thread::sleep(ms(100));
+        drop(r);
+        thread::sleep(ms(100));
+        drop(r2);
+    })
+    .unwrap();
+
+    // Returns immediately once the channel is closed.
+    s.wait_closed();
+}
+
+#[test]
+fn closed_deadline() {
+    let (s, r) = unbounded::<i32>();
+
+    let start = Instant::now();
+    assert!(!s.closed_deadline(start));
+    assert!(!s.closed_deadline(start + ms(100)));
+    assert!(start.elapsed() >= ms(100));
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            drop(r);
+        });
+
+        assert!(s.closed_deadline(Instant::now() + ms(1000)));
+    })
+    .unwrap();
+
+    assert!(s.closed_deadline(Instant::now()));
+}
+
+#[test]
+fn wait_drained() {
+    let (s, r) = unbounded();
+
+    // Returns immediately if the channel is already empty.
+    assert!(s.wait_drained());
+
+    for i in 0..10 {
+        s.send(i).unwrap();
+    }
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..10 {
+                thread::sleep(ms(10));
+                assert_eq!(r.recv(), Ok(i));
+            }
+        });
+
+        assert!(s.wait_drained());
+        assert!(s.is_empty());
+    })
+    .unwrap();
+}
+
+#[test]
+fn wait_drained_disconnected() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            assert_eq!(r.recv(), Ok(1));
+            drop(r);
+        });
+
+        // The last message is never received.
+        assert!(!s.wait_drained());
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_peek_decide() {
+    const COUNT: usize = 100;
+
+    let (s, r) = unbounded();
+    for i in 0..COUNT {
+        s.send(i).unwrap();
+    }
+
+    // Leave every message once before taking it, across several blocks.
+    for i in 0..COUNT {
+        assert_eq!(
+            r.recv_peek_decide(|&x| {
+                assert_eq!(x, i);
+                Decision::Leave
+            }),
+            Ok(None)
+        );
+        assert_eq!(r.len(), COUNT - i);
+        assert_eq!(r.recv_peek_decide(|_| Decision::Take), Ok(Some(i)));
+    }
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(1000));
+            s.send(COUNT).unwrap();
+        });
+
+        // This call blocks until a message is sent.
+        assert_eq!(r.recv_peek_decide(|_| Decision::Take), Ok(Some(COUNT)));
+    })
+    .unwrap();
+
+    drop(s);
+    assert_eq!(
+        r.recv_peek_decide(|_| Decision::Take),
+        Err(PeekError::Disconnected)
+    );
+}
+
+#[test]
+fn recv_peek_decide_panic() {
+    let (s, r) = unbounded();
+
+    s.send(1).unwrap();
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _ = r.recv_peek_decide(|_| -> Decision { panic!("inspection failed") });
+    }));
+    assert!(res.is_err());
+
+    // The head has been unlocked and the message is still in the channel.
+    s.send(2).unwrap();
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn recv_peek_decide_locked_head() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    let res = r.recv_peek_decide(|_| {
+        // Other receive operations see an empty channel while the head is locked.
+        assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+        select! {
+            recv(r) -> _ => panic!(),
+            default => {}
+        }
+        Decision::Leave
+    });
+    assert_eq!(res, Ok(None));
+    assert_eq!(r.try_recv(), Ok(1));
+}
+
+#[test]
+fn recv_peek_decide_locked_head_disconnected() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+
+    scope(|scope| {
+        let res = r.recv_peek_decide(|_| {
+            let h = scope.spawn(|_| r.recv());
+            thread::sleep(ms(500));
+
+            // Once the channel is disconnected, other receive operations see it as empty and
+            // disconnected rather than wait for the head to be unlocked.
+            drop(s);
+            assert_eq!(h.join().unwrap(), Err(RecvError));
+            assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+            select! {
+                recv(r) -> msg => assert_eq!(msg, Err(RecvError)),
+            }
+            Decision::Leave
+        });
+        assert_eq!(res, Ok(None));
+    })
+    .unwrap();
+
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn recv_peek_decide_wakes_receivers() {
+    let (s, r) = unbounded();
+    for i in 0..3 {
+        s.send(i).unwrap();
+    }
+
+    scope(|scope| {
+        let mut handles = Vec::new();
+        let res = r.recv_peek_decide(|_| {
+            // These receivers block until the head is unlocked.
+            for _ in 0..2 {
+                handles.push(scope.spawn(|_| r.recv().unwrap()));
+            }
+            thread::sleep(ms(500));
+            Decision::Take
+        });
+        assert_eq!(res, Ok(Some(0)));
+
+        let mut received = handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>();
+        received.sort();
+        assert_eq!(received, [1, 2]);
+    })
+    .unwrap();
+}
+
+#[test]
+#[should_panic(expected = "cannot block on a channel while inspecting its front")]
+fn recv_peek_decide_blocking_recv() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+
+    let _ = r.recv_peek_decide(|_| {
+        let _ = r.recv();
+        Decision::Leave
+    });
+}
+
+#[test]
+#[should_panic(expected = "cannot block on a channel while inspecting its front")]
+fn recv_peek_decide_blocking_select() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+
+    let _ = r.recv_peek_decide(|_| {
+        let mut sel = Select::new();
+        sel.recv(&r);
+        let _ = sel.select();
+        Decision::Leave
+    });
+}
+
+#[test]
+fn recv_peek_decide_mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = unbounded::<usize>();
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                let mut rng = thread_rng();
+                loop {
+                    let decide = |_: &usize| {
+                        if rng.gen() {
+                            Decision::Take
+                        } else {
+                            Decision::Leave
+                        }
+                    };
+                    match r.recv_peek_decide(decide) {
+                        Ok(Some(n)) => {
+                            v[n].fetch_add(1, Ordering::SeqCst);
+                        }
+                        Ok(None) => {}
+                        Err(_) => break,
+                    }
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                while let Ok(n) = r.recv() {
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            let s = s.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+        drop(s);
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
+#[test]
+fn peek_contiguous_unsupported() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+
+    assert_eq!(r.peek_contiguous().err(), Some(TryPeekError::Unsupported));
+    assert_eq!(r.try_recv(), Ok(1));
+}
+
 #[test]
 fn try_send() {
     let (s, r) = unbounded();
@@ -226,6 +677,71 @@ fn len() {
     assert_eq!(r.len(), 0);
 }
 
+#[test]
+fn len_exact() {
+    let (s, r) = unbounded();
+    assert_eq!(r.len_exact(), 0);
+
+    // Cross a few block boundaries.
+    for i in 0..100 {
+        s.send(i).unwrap();
+        assert_eq!(r.len_exact(), i + 1);
+    }
+
+    for i in 0..100 {
+        r.recv().unwrap();
+        assert_eq!(r.len_exact(), 100 - i - 1);
+    }
+}
+
+#[test]
+fn recv_with_empty() {
+    let (s, r) = unbounded();
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.recv_with_empty(), Ok((1, false)));
+    assert_eq!(r.recv_with_empty(), Ok((2, true)));
+
+    drop(s);
+    assert_eq!(r.recv_with_empty(), Err(RecvError));
+}
+
+#[test]
+fn drop_handler() {
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let h = handled.clone();
+    let (s, r) = unbounded_with_drop_handler(move |msg| h.lock().unwrap().push(msg));
+
+    // Cross a block boundary.
+    for i in 0..50 {
+        s.send(i).unwrap();
+    }
+    for i in 0..10 {
+        assert_eq!(r.recv(), Ok(i));
+    }
+
+    // Only the last receiver hands the messages over.
+    let r2 = r.clone();
+    drop(r);
+    assert!(handled.lock().unwrap().is_empty());
+    drop(r2);
+    assert_eq!(*handled.lock().unwrap(), (10..50).collect::<Vec<_>>());
+
+    assert_eq!(s.send(50), Err(SendError(50)));
+    drop(s);
+    assert_eq!(handled.lock().unwrap().len(), 40);
+
+    // The handler also runs if all senders are gone already.
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let h = handled.clone();
+    let (s, r) = unbounded_with_drop_handler(move |msg| h.lock().unwrap().push(msg));
+    s.send(1).unwrap();
+    drop(s);
+    drop(r);
+    assert_eq!(*handled.lock().unwrap(), [1]);
+}
+
 #[test]
 fn disconnect_wakes_receiver() {
     let (s, r) = unbounded::<()>();
@@ -444,6 +960,66 @@ fn linearizable() {
     .unwrap();
 }
 
+#[test]
+fn try_send_never_full() {
+    const COUNT: usize = 100_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let s = s.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    assert_eq!(s.try_send(i), Ok(()));
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(r.len(), COUNT * THREADS);
+    assert!(!s.is_full());
+
+    drop(r);
+    assert_eq!(s.try_send(0), Err(TrySendError::Disconnected(0)));
+}
+
+#[test]
+fn clone_drop_no_spurious_disconnect() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 10_000;
+
+    let (s, r) = unbounded::<()>();
+    let done = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let mut s = s.clone();
+            let done = &done;
+            scope.spawn(move |_| {
+                for _ in 0..COUNT {
+                    let c = s.clone();
+                    drop(s);
+                    s = c;
+                }
+                done.fetch_add(1, Ordering::SeqCst);
+                drop(s);
+            });
+        }
+        drop(s);
+
+        loop {
+            if r.try_recv() == Err(TryRecvError::Disconnected) {
+                assert_eq!(done.load(Ordering::SeqCst), THREADS);
+                break;
+            }
+        }
+    })
+    .unwrap();
+}
+
 #[test]
 fn fairness() {
     const COUNT: usize = 10_000;
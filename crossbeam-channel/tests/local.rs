@@ -0,0 +1,129 @@
+//! Tests for single-threaded local channels.
+
+extern crate crossbeam_channel;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crossbeam_channel::{local_channel, LocalSender};
+use crossbeam_channel::{RecvError, SendError, TryRecvError};
+
+#[test]
+fn smoke() {
+    let (s, r) = local_channel();
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+
+    s.send(8).unwrap();
+    assert_eq!(r.recv(), Ok(8));
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn not_send() {
+    let (s, r) = local_channel();
+    let msg = Rc::new(RefCell::new(1));
+
+    s.send(msg.clone()).unwrap();
+    *r.recv().unwrap().borrow_mut() += 1;
+    assert_eq!(*msg.borrow(), 2);
+}
+
+#[test]
+fn len_empty() {
+    let (s, r) = local_channel();
+
+    assert_eq!(s.len(), 0);
+    assert!(s.is_empty());
+    assert_eq!(r.len(), 0);
+    assert!(r.is_empty());
+
+    s.send(()).unwrap();
+    s.send(()).unwrap();
+
+    assert_eq!(s.len(), 2);
+    assert!(!s.is_empty());
+    assert_eq!(r.len(), 2);
+    assert!(!r.is_empty());
+
+    r.recv().unwrap();
+
+    assert_eq!(s.len(), 1);
+    assert_eq!(r.len(), 1);
+}
+
+#[test]
+fn fifo() {
+    let (s, r) = local_channel();
+    for i in 0..100 {
+        s.send(i).unwrap();
+    }
+    for i in 0..100 {
+        assert_eq!(r.recv(), Ok(i));
+    }
+}
+
+#[test]
+fn disconnect() {
+    let (s, r) = local_channel();
+    let s2 = s.clone();
+
+    s.send(1).unwrap();
+    drop(s);
+    s2.send(2).unwrap();
+    drop(s2);
+
+    // Remaining messages are still received after all senders are gone.
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(r.recv(), Err(RecvError));
+
+    let (s, r) = local_channel();
+    let r2 = r.clone();
+    drop(r);
+    assert_eq!(s.send(1), Ok(()));
+    assert_eq!(r2.recv(), Ok(1));
+    drop(r2);
+    assert_eq!(s.send(2), Err(SendError(2)));
+}
+
+#[test]
+fn drops() {
+    struct DropCounter(Rc<RefCell<usize>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            *self.0.borrow_mut() += 1;
+        }
+    }
+
+    let drops = Rc::new(RefCell::new(0));
+    let (s, r) = local_channel();
+
+    for _ in 0..10 {
+        s.send(DropCounter(drops.clone())).unwrap();
+    }
+    drop(r.recv().unwrap());
+    assert_eq!(*drops.borrow(), 1);
+
+    // Dropping the last receiver drops the remaining messages.
+    drop(r);
+    assert_eq!(*drops.borrow(), 10);
+    drop(s);
+}
+
+#[test]
+fn sender_in_message() {
+    struct Msg(LocalSender<Msg>);
+
+    let (s, r) = local_channel();
+
+    // A message holding a sender for its own channel is dropped along with the last receiver.
+    s.send(Msg(s.clone())).unwrap();
+    drop(r);
+    let Msg(s2) = s.send(Msg(s.clone())).unwrap_err().into_inner();
+    assert!(s2.send(Msg(s.clone())).is_err());
+}
@@ -0,0 +1,123 @@
+//! Tests for creating senders from receivers and vice versa.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, never, oneshot, tick, unbounded};
+use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{RecvError, SendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn check_make_sender(s: Sender<i32>, r: Receiver<i32>) {
+    let s2 = r.make_sender().unwrap();
+    assert!(s.identical_to(&s2));
+
+    // The new sender delivers to the original receiver.
+    scope(|scope| {
+        scope.spawn(|_| s2.send(1).unwrap());
+        assert_eq!(r.recv(), Ok(1));
+    })
+    .unwrap();
+
+    // The new sender keeps the channel connected.
+    drop(s);
+    scope(|scope| {
+        scope.spawn(|_| s2.send(2).unwrap());
+        assert_eq!(r.recv(), Ok(2));
+    })
+    .unwrap();
+
+    drop(s2);
+    assert_eq!(r.recv(), Err(RecvError));
+    assert!(r.make_sender().is_none());
+}
+
+fn check_make_receiver(s: Sender<i32>, r: Receiver<i32>) {
+    let r2 = s.make_receiver().unwrap();
+    assert!(r.identical_to(&r2));
+
+    // The new receiver gets messages from the original sender.
+    scope(|scope| {
+        scope.spawn(|_| s.send(1).unwrap());
+        assert_eq!(r2.recv(), Ok(1));
+    })
+    .unwrap();
+
+    // The new receiver keeps the channel connected.
+    drop(r);
+    scope(|scope| {
+        scope.spawn(|_| s.send(2).unwrap());
+        assert_eq!(r2.recv(), Ok(2));
+    })
+    .unwrap();
+
+    drop(r2);
+    assert_eq!(s.send(3), Err(SendError(3)));
+    assert!(s.make_receiver().is_none());
+}
+
+#[test]
+fn array() {
+    let (s, r) = bounded(1);
+    check_make_sender(s, r);
+    let (s, r) = bounded(1);
+    check_make_receiver(s, r);
+}
+
+#[test]
+fn list() {
+    let (s, r) = unbounded();
+    check_make_sender(s, r);
+    let (s, r) = unbounded();
+    check_make_receiver(s, r);
+}
+
+#[test]
+fn zero() {
+    let (s, r) = bounded(0);
+    check_make_sender(s, r);
+    let (s, r) = bounded(0);
+    check_make_receiver(s, r);
+}
+
+#[test]
+fn oneshot_channel() {
+    let (s, r) = oneshot::<i32>();
+    let s2 = r.make_sender().unwrap();
+    s2.send(1).unwrap();
+    assert_eq!(s.send(2), Err(SendError(2)));
+
+    let r2 = s.make_receiver().unwrap();
+    assert_eq!(r2.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn no_senders() {
+    assert!(after(ms(50)).make_sender().is_none());
+    assert!(tick(ms(50)).make_sender().is_none());
+    assert!(never::<i32>().make_sender().is_none());
+}
+
+#[test]
+fn disconnect_wakes_receiver() {
+    let (s, r) = unbounded::<()>();
+    let s2 = r.make_sender().unwrap();
+    drop(s);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            drop(s2);
+        });
+        assert_eq!(r.recv(), Err(RecvError));
+    })
+    .unwrap();
+}
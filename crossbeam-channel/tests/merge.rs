@@ -0,0 +1,255 @@
+//! Tests for receivers created by `merge`.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, merge, never, unbounded, ChannelKind};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = bounded(1);
+    let r = merge(r1, r2);
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    s1.send(1).unwrap();
+    assert_eq!(r.try_recv(), Ok(1));
+    s2.send(2).unwrap();
+    assert_eq!(r.recv(), Ok(2));
+
+    assert_eq!(r.kind(), ChannelKind::Unbounded);
+    assert_eq!(r.capacity(), None);
+    assert!(r.make_sender().is_none());
+}
+
+#[test]
+fn len_empty_full() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let r = merge(r1, r2);
+
+    assert!(r.is_empty());
+    s1.send(()).unwrap();
+    s2.send(()).unwrap();
+    s2.send(()).unwrap();
+    assert_eq!(r.len(), 3);
+    assert!(!r.is_empty());
+    assert!(!r.is_full());
+}
+
+#[test]
+fn disconnect_when_both_done() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let r = merge(r1, r2);
+
+    s1.send(1).unwrap();
+    drop(s1);
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+
+    s2.send(2).unwrap();
+    drop(s2);
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn recv_wakes_up() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded::<i32>();
+    let r = merge(r1, r2);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            s1.send(7).unwrap();
+            thread::sleep(ms(100));
+            drop(s1);
+            drop(s2);
+        });
+
+        assert_eq!(r.recv(), Ok(7));
+        assert_eq!(r.recv(), Err(RecvError));
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_with_one_disconnected() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded();
+    let r = merge(r1, r2);
+    drop(s1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            s2.send(3).unwrap();
+        });
+
+        assert_eq!(r.recv_timeout(ms(1000)), Ok(3));
+    })
+    .unwrap();
+}
+
+#[test]
+fn zero_capacity_inputs() {
+    let (s1, r1) = bounded(0);
+    let (s2, r2) = bounded(0);
+    let r = merge(r1, r2);
+
+    scope(|scope| {
+        scope.spawn(move |_| s1.send(1).unwrap());
+        scope.spawn(move |_| s2.send(2).unwrap());
+
+        let mut msgs = vec![r.recv().unwrap(), r.recv().unwrap()];
+        msgs.sort();
+        assert_eq!(msgs, [1, 2]);
+        assert_eq!(r.recv(), Err(RecvError));
+    })
+    .unwrap();
+}
+
+#[test]
+fn in_select() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (s3, r3) = unbounded::<i32>();
+    let r = merge(r1, r2);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            s2.send(5).unwrap();
+        });
+
+        select! {
+            recv(r) -> msg => assert_eq!(msg, Ok(5)),
+            recv(r3) -> _ => panic!(),
+            recv(never::<i32>()) -> _ => panic!(),
+            default(ms(1000)) => panic!(),
+        }
+    })
+    .unwrap();
+
+    drop(s1);
+    drop(s3);
+    select! {
+        recv(r) -> msg => assert_eq!(msg, Err(RecvError)),
+        default(ms(1000)) => panic!(),
+    }
+}
+
+#[test]
+fn in_select_zero_capacity() {
+    let (s, r1) = bounded(0);
+    let (_s2, r2) = unbounded::<i32>();
+    let r = merge(r1, r2);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            s.send(9).unwrap();
+        });
+
+        select! {
+            recv(r) -> msg => assert_eq!(msg, Ok(9)),
+            default(ms(1000)) => panic!(),
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn fairness() {
+    const COUNT: usize = 10_000;
+
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    for _ in 0..COUNT {
+        s1.send(0).unwrap();
+        s2.send(1).unwrap();
+    }
+    let r = merge(r1, r2);
+
+    let mut hits = [0usize; 2];
+    for _ in 0..COUNT {
+        hits[r.recv().unwrap()] += 1;
+    }
+    assert!(hits.iter().all(|x| *x >= COUNT / hits.len() / 2));
+}
+
+#[test]
+fn nested() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (s3, r3) = unbounded();
+    let r = merge(merge(r1, r2), r3);
+
+    s3.send(3).unwrap();
+    s1.send(1).unwrap();
+    s2.send(2).unwrap();
+    drop((s1, s2, s3));
+
+    let mut msgs: Vec<i32> = r.iter().collect();
+    msgs.sort();
+    assert_eq!(msgs, [1, 2, 3]);
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s1, r1) = bounded::<usize>(3);
+    let (s2, r2) = unbounded();
+    let r = merge(r1, r2);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let r = r.clone();
+            let v = &v;
+            scope.spawn(move |_| {
+                for _ in 0..COUNT {
+                    let n = select! {
+                        recv(r) -> msg => msg.unwrap(),
+                    };
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            let s1 = s1.clone();
+            let s2 = s2.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    if i % 2 == 0 {
+                        s1.send(i).unwrap();
+                    } else {
+                        s2.send(i).unwrap();
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
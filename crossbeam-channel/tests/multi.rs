@@ -0,0 +1,116 @@
+//! Tests for `MultiChannel`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, merge, never, oneshot, priority_channel, unbounded};
+use crossbeam_channel::{MultiChannel, Receiver};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn total_len() {
+    let (s1, r1) = bounded(5);
+    let (s2, r2) = unbounded();
+    let r3 = never();
+
+    let group = MultiChannel::new(&[&r1, &r2, &r3]);
+    assert_eq!(group.total_len(), 0);
+    assert!(group.wait_total_below_timeout(1, ms(0)));
+
+    s1.send(1).unwrap();
+    s2.send(2).unwrap();
+    s2.send(3).unwrap();
+    assert_eq!(group.total_len(), 3);
+    assert!(!group.wait_total_below_timeout(3, ms(0)));
+    assert!(group.wait_total_below_timeout(4, ms(0)));
+}
+
+#[test]
+fn timeout() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+
+    let group = MultiChannel::new(&[&r]);
+    let start = Instant::now();
+    assert!(!group.wait_total_below_timeout(1, ms(100)));
+    assert!(start.elapsed() >= ms(100));
+
+    // A threshold of zero is never reached.
+    r.recv().unwrap();
+    assert!(!group.wait_total_below_timeout(0, ms(50)));
+}
+
+#[test]
+fn woken_by_any_flavor() {
+    let (s1, r1) = bounded(4);
+    let (s2, r2) = unbounded();
+    let (s3, r3) = oneshot();
+    let (s4, r4) = priority_channel(4);
+    let (s5, r5) = unbounded();
+    let r5 = merge(r5, never());
+
+    s1.send(1).unwrap();
+    s2.send(2).unwrap();
+    s3.send(3).unwrap();
+    s4.send(4).unwrap();
+    s5.send(5).unwrap();
+
+    let receivers = [&r1, &r2, &r3, &r4, &r5];
+    for (i, r) in receivers.iter().enumerate() {
+        let group = MultiChannel::new(&receivers);
+        assert_eq!(group.total_len(), receivers.len() - i);
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                thread::sleep(ms(100));
+                r.recv().unwrap();
+            });
+
+            // Only a receive operation on `r` can make the total drop.
+            let start = Instant::now();
+            group.wait_total_below(receivers.len() - i);
+            assert!(start.elapsed() >= ms(50));
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn drain_barrier() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+    const THRESHOLD: usize = 10;
+
+    let chans = (0..THREADS).map(|_| unbounded()).collect::<Vec<_>>();
+    let receivers = chans
+        .iter()
+        .map(|&(_, ref r)| r)
+        .collect::<Vec<&Receiver<usize>>>();
+    let group = MultiChannel::new(&receivers);
+
+    for i in 0..COUNT {
+        chans[i % THREADS].0.send(i).unwrap();
+    }
+
+    scope(|scope| {
+        for r in &receivers {
+            scope.spawn(move |_| {
+                for _ in 0..COUNT / THREADS {
+                    r.recv().unwrap();
+                }
+            });
+        }
+
+        group.wait_total_below(THRESHOLD);
+    })
+    .unwrap();
+
+    assert_eq!(group.total_len(), 0);
+}
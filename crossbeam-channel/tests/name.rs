@@ -0,0 +1,109 @@
+//! Tests for named channels.
+
+extern crate crossbeam_channel;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crossbeam_channel::{after, never, tick};
+use crossbeam_channel::{bounded, bounded_named, unbounded, unbounded_named};
+use crossbeam_channel::{BoundedBuilder, CapacityError, ParkStrategy};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn array_name() {
+    let (s, r) = bounded_named::<usize>(1, "array");
+    assert_eq!(s.name(), Some("array"));
+    assert_eq!(r.name(), Some("array"));
+    assert_eq!(s.capacity(), Some(1));
+}
+
+#[test]
+fn list_name() {
+    let (s, r) = unbounded_named::<usize>("list");
+    assert_eq!(s.name(), Some("list"));
+    assert_eq!(r.name(), Some("list"));
+}
+
+#[test]
+fn zero_name() {
+    let (s, r) = bounded_named::<usize>(0, "zero");
+    assert_eq!(s.name(), Some("zero"));
+    assert_eq!(r.name(), Some("zero"));
+    assert_eq!(s.capacity(), Some(0));
+}
+
+#[test]
+fn builder_name() {
+    let persisted = Arc::new(Mutex::new(Vec::new()));
+    let p = persisted.clone();
+
+    let (s, r) = BoundedBuilder::new(2)
+        .name("builder")
+        .strategy(ParkStrategy::Spin)
+        .drop_handler(move |msg| p.lock().unwrap().push(msg))
+        .build();
+    assert_eq!(s.name(), Some("builder"));
+    assert_eq!(r.name(), Some("builder"));
+    assert_eq!(s.capacity(), Some(2));
+
+    s.send(1).unwrap();
+    drop(r);
+    assert_eq!(*persisted.lock().unwrap(), [1]);
+
+    let (s, r) = BoundedBuilder::<usize>::new(0)
+        .name("zero")
+        .strategy(ParkStrategy::Yield)
+        .try_build()
+        .unwrap();
+    assert_eq!(s.name(), Some("zero"));
+    assert_eq!(r.name(), Some("zero"));
+    assert_eq!(s.capacity(), Some(0));
+
+    let res = BoundedBuilder::<u64>::new(usize::max_value())
+        .name("huge")
+        .try_build();
+    assert_eq!(res.err(), Some(CapacityError));
+}
+
+#[test]
+fn unnamed() {
+    let (s, r) = bounded::<usize>(1);
+    assert_eq!(s.name(), None);
+    assert_eq!(r.name(), None);
+
+    let (s, r) = unbounded::<usize>();
+    assert_eq!(s.name(), None);
+    assert_eq!(r.name(), None);
+
+    assert_eq!(after(ms(50)).name(), None);
+    assert_eq!(tick(ms(50)).name(), None);
+    assert_eq!(never::<usize>().name(), None);
+}
+
+#[test]
+fn shared() {
+    let (s, r) = unbounded_named::<usize>("shared");
+    let name = String::from("shared");
+
+    // The name is shared by clones and by handles created from the other side.
+    assert_eq!(s.clone().name(), Some(&name[..]));
+    assert_eq!(r.clone().name(), Some(&name[..]));
+    assert_eq!(r.make_sender().unwrap().name(), Some(&name[..]));
+    assert_eq!(s.make_receiver().unwrap().name(), Some(&name[..]));
+    assert_eq!(s.name().unwrap().as_ptr(), r.name().unwrap().as_ptr());
+}
+
+#[test]
+fn debug() {
+    let (s, r) = bounded_named::<usize>(1, "jobs");
+    assert_eq!(format!("{:?}", s), "Sender { name: \"jobs\", .. }");
+    assert_eq!(format!("{:?}", r), "Receiver { name: \"jobs\", .. }");
+
+    let (s, r) = bounded::<usize>(1);
+    assert_eq!(format!("{:?}", s), "Sender { .. }");
+    assert_eq!(format!("{:?}", r), "Receiver { .. }");
+}
@@ -0,0 +1,119 @@
+//! Tests for `Receiver::on_disconnect_once`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crossbeam_channel::{bounded, merge, never, unbounded};
+use crossbeam_utils::thread::scope;
+
+fn increment(count: &Arc<AtomicUsize>) -> impl FnOnce() + Send + 'static {
+    let count = count.clone();
+    move || {
+        count.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn last_sender() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let (s, r) = unbounded::<i32>();
+    let s2 = s.clone();
+
+    r.on_disconnect_once(increment(&count));
+    r.on_disconnect_once(increment(&count));
+    drop(s);
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+
+    drop(s2);
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    drop(r);
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn last_receiver() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let (s, r) = bounded::<i32>(1);
+
+    r.clone().on_disconnect_once(increment(&count));
+    drop(r);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    drop(s);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn already_disconnected() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let (s, r) = bounded::<i32>(0);
+    drop(s);
+
+    r.on_disconnect_once(increment(&count));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn runs_on_disconnecting_thread() {
+    let (s, r) = unbounded::<i32>();
+    let (id_s, id_r) = unbounded();
+
+    r.on_disconnect_once(move || id_s.send(thread::current().id()).unwrap());
+    let id = thread::spawn(move || {
+        drop(s);
+        thread::current().id()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(id_r.recv(), Ok(id));
+}
+
+#[test]
+fn race() {
+    const RUNS: usize = 1000;
+
+    for _ in 0..RUNS {
+        let count = Arc::new(AtomicUsize::new(0));
+        let (s, r) = unbounded::<i32>();
+        let r2 = r.clone();
+        let f = increment(&count);
+
+        scope(|scope| {
+            scope.spawn(move |_| drop(s));
+            scope.spawn(move |_| drop(r));
+            scope.spawn(move |_| r2.on_disconnect_once(f));
+        })
+        .unwrap();
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
+
+#[test]
+fn merged() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(1);
+    let r = merge(r1, r2);
+
+    r.on_disconnect_once(increment(&count));
+    drop(s1);
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+    drop(s2);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn never_disconnected() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let r = never::<i32>();
+
+    r.on_disconnect_once(increment(&count));
+    drop(r);
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,316 @@
+//! Tests for the oneshot channel flavor.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, oneshot};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{SendError, SendOnceError, SendTimeoutError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = oneshot();
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn capacity() {
+    let (s, r) = oneshot::<()>();
+    assert_eq!(s.capacity(), Some(1));
+    assert_eq!(r.capacity(), Some(1));
+}
+
+#[test]
+fn len_empty_full() {
+    let (s, r) = oneshot();
+
+    assert_eq!(s.len(), 0);
+    assert_eq!(s.is_empty(), true);
+    assert_eq!(s.is_full(), false);
+    assert_eq!(r.len(), 0);
+    assert_eq!(r.is_empty(), true);
+    assert_eq!(r.is_full(), false);
+
+    s.send(()).unwrap();
+
+    assert_eq!(s.len(), 1);
+    assert_eq!(s.is_empty(), false);
+    assert_eq!(s.is_full(), true);
+    assert_eq!(r.len(), 1);
+    assert_eq!(r.is_empty(), false);
+    assert_eq!(r.is_full(), true);
+
+    r.recv().unwrap();
+
+    assert_eq!(s.len(), 0);
+    assert_eq!(s.is_empty(), true);
+    assert_eq!(s.is_full(), false);
+    assert_eq!(r.len(), 0);
+    assert_eq!(r.is_empty(), true);
+    assert_eq!(r.is_full(), false);
+}
+
+#[test]
+fn double_send() {
+    let (s, r) = oneshot();
+    assert_eq!(s.send(1), Ok(()));
+    assert_eq!(s.send(2), Err(SendError(2)));
+    assert_eq!(s.try_send(3), Err(TrySendError::Disconnected(3)));
+    assert_eq!(
+        s.send_timeout(4, ms(100)),
+        Err(SendTimeoutError::Disconnected(4))
+    );
+
+    // The first message is still delivered.
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(s.send(5), Err(SendError(5)));
+}
+
+#[test]
+fn send_once() {
+    let (s, r) = oneshot();
+    assert_eq!(s.send_once(1), Ok(()));
+    assert_eq!(s.send_once(2), Err(SendOnceError::AlreadySent(2)));
+    assert!(s.send_once(3).unwrap_err().is_already_sent());
+
+    // Reuse is reported even after the receiver is gone.
+    assert_eq!(r.recv(), Ok(1));
+    drop(r);
+    assert_eq!(s.send_once(4), Err(SendOnceError::AlreadySent(4)));
+
+    // An unused channel with no receiver is disconnected.
+    let (s, r) = oneshot::<i32>();
+    drop(r);
+    assert_eq!(s.send_once(5), Err(SendOnceError::Disconnected(5)));
+    assert_eq!(s.send_once(6).unwrap_err().into_inner(), 6);
+
+    // Other flavors only ever report disconnection.
+    let (s, r) = bounded(1);
+    assert_eq!(s.send_once(7), Ok(()));
+    drop(r);
+    assert_eq!(s.send_once(8), Err(SendOnceError::Disconnected(8)));
+}
+
+#[test]
+fn double_recv() {
+    let (s, r) = oneshot();
+    s.send(1).unwrap();
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Disconnected));
+
+    // The sender is still alive, but the channel is used up.
+    drop(s);
+}
+
+#[test]
+fn recv() {
+    let (s, r) = oneshot();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv(), Ok(7));
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            s.send(7).unwrap();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_timeout() {
+    let (s, r) = oneshot::<i32>();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv_timeout(ms(1000)), Err(RecvTimeoutError::Timeout));
+            assert_eq!(r.recv_timeout(ms(1000)), Ok(7));
+            assert_eq!(
+                r.recv_timeout(ms(1000)),
+                Err(RecvTimeoutError::Disconnected)
+            );
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1500));
+            s.send(7).unwrap();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_after_disconnect() {
+    let (s, r) = oneshot::<i32>();
+    drop(s);
+    assert_eq!(r.recv(), Err(RecvError));
+
+    let (s, r) = oneshot();
+    s.send(1).unwrap();
+    drop(s);
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn send_after_disconnect() {
+    let (s, r) = oneshot();
+    drop(r);
+    assert_eq!(s.send(1), Err(SendError(1)));
+}
+
+#[test]
+fn disconnect_wakes_receiver() {
+    let (s, r) = oneshot::<()>();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn receive_wakes_other_receivers() {
+    let (s, r) = oneshot();
+    let received = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..4 {
+            let r = r.clone();
+            let received = &received;
+            scope.spawn(move |_| {
+                if r.recv().is_ok() {
+                    received.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        thread::sleep(ms(500));
+        s.send(7).unwrap();
+    })
+    .unwrap();
+
+    assert_eq!(received.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn select() {
+    let (s, r) = oneshot();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            select! {
+                recv(r) -> v => assert_eq!(v, Ok(7)),
+            }
+            select! {
+                recv(r) -> v => assert_eq!(v, Err(RecvError)),
+            }
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            select! {
+                send(s, 7) -> res => assert_eq!(res, Ok(())),
+            }
+            select! {
+                send(s, 8) -> res => assert_eq!(res, Err(SendError(8))),
+            }
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn stress() {
+    const COUNT: usize = 1000;
+
+    for i in 0..COUNT {
+        let (s, r) = oneshot();
+        let senders = AtomicUsize::new(0);
+
+        scope(|scope| {
+            for _ in 0..2 {
+                let s = s.clone();
+                let senders = &senders;
+                scope.spawn(move |_| {
+                    if s.send(i).is_ok() {
+                        senders.fetch_add(1, Ordering::SeqCst);
+                    }
+                });
+            }
+            assert_eq!(r.recv(), Ok(i));
+            assert_eq!(r.recv(), Err(RecvError));
+        })
+        .unwrap();
+
+        assert_eq!(senders.load(Ordering::SeqCst), 1);
+    }
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = oneshot();
+    s.send(DropCounter).unwrap();
+    assert!(s.send(DropCounter).is_err());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn wait_drained() {
+    let (s, r) = oneshot();
+    assert!(s.wait_drained());
+    s.send(1).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            assert_eq!(r.recv(), Ok(1));
+        });
+
+        assert!(s.wait_drained());
+    })
+    .unwrap();
+
+    let (s, r) = oneshot();
+    s.send(2).unwrap();
+    drop(r);
+    assert!(!s.wait_drained());
+}
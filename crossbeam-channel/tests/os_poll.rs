@@ -0,0 +1,198 @@
+//! Tests for signalling file descriptors when channels become ready.
+
+#![cfg(all(unix, feature = "os_poll"))]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, never, oneshot, tick, unbounded};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Creates a pair of connected sockets, where the first one is signalled and the second one is
+/// polled.
+fn socket_pair() -> (UnixStream, UnixStream) {
+    let (a, b) = UnixStream::pair().unwrap();
+    a.set_nonblocking(true).unwrap();
+    b.set_nonblocking(true).unwrap();
+    (a, b)
+}
+
+/// Reads all pending signals and returns how many bytes were read.
+fn drain(sock: &mut UnixStream) -> usize {
+    let mut buf = [0u8; 64];
+    let mut total = 0;
+    loop {
+        match sock.read(&mut buf) {
+            Ok(n) => total += n,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => return total,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+fn check(s: Sender<i32>, r: Receiver<i32>) {
+    let (a, mut b) = socket_pair();
+
+    assert!(r.register_ready_fd(a.as_raw_fd()));
+    assert_eq!(drain(&mut b), 0);
+
+    s.try_send(1).unwrap();
+    assert!(drain(&mut b) > 0);
+    assert_eq!(r.try_recv(), Ok(1));
+
+    // Disconnection is signalled as well.
+    drop(s);
+    assert!(drain(&mut b) > 0);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+
+    r.unregister_ready_fd(a.as_raw_fd());
+}
+
+#[test]
+fn array() {
+    let (s, r) = bounded(10);
+    check(s, r);
+}
+
+#[test]
+fn list() {
+    let (s, r) = unbounded();
+    check(s, r);
+}
+
+#[test]
+fn oneshot_channel() {
+    let (s, r) = oneshot();
+    check(s, r);
+}
+
+#[test]
+fn zero() {
+    let (s, r) = bounded(0);
+    let (a, mut b) = socket_pair();
+    assert!(r.register_ready_fd(a.as_raw_fd()));
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(1).unwrap());
+
+        // A blocked sender makes the channel ready for receiving.
+        while drain(&mut b) == 0 {
+            thread::sleep(ms(10));
+        }
+        assert_eq!(r.recv(), Ok(1));
+    })
+    .unwrap();
+
+    r.unregister_ready_fd(a.as_raw_fd());
+}
+
+#[test]
+fn ready_before_registration() {
+    let (s, r) = unbounded();
+    let (a, mut b) = socket_pair();
+
+    s.send(1).unwrap();
+    assert!(r.register_ready_fd(a.as_raw_fd()));
+    assert!(drain(&mut b) > 0);
+    r.unregister_ready_fd(a.as_raw_fd());
+}
+
+#[test]
+fn unregister() {
+    let (s, r) = unbounded();
+    let (a, mut b) = socket_pair();
+
+    assert!(r.register_ready_fd(a.as_raw_fd()));
+    r.unregister_ready_fd(a.as_raw_fd());
+
+    s.send(1).unwrap();
+    assert_eq!(drain(&mut b), 0);
+}
+
+#[test]
+fn unsupported() {
+    let (a, _b) = socket_pair();
+    assert!(!after(ms(50)).register_ready_fd(a.as_raw_fd()));
+    assert!(!tick(ms(50)).register_ready_fd(a.as_raw_fd()));
+    assert!(!never::<i32>().register_ready_fd(a.as_raw_fd()));
+}
+
+#[test]
+fn other_thread() {
+    let (s, r) = unbounded();
+    let (a, mut b) = socket_pair();
+    assert!(r.register_ready_fd(a.as_raw_fd()));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.send(1).unwrap();
+        });
+
+        while drain(&mut b) == 0 {
+            thread::sleep(ms(10));
+        }
+        assert_eq!(r.try_recv(), Ok(1));
+    })
+    .unwrap();
+
+    r.unregister_ready_fd(a.as_raw_fd());
+}
+
+#[test]
+fn signalled_once_until_empty() {
+    let (s, r) = bounded(10);
+    let (a, mut b) = socket_pair();
+    assert!(r.register_ready_fd(a.as_raw_fd()));
+
+    // Only the first message into an empty channel signals the descriptor.
+    for i in 0..3 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(drain(&mut b), 8);
+
+    // The descriptor is not signalled again until the channel is found empty.
+    assert_eq!(r.try_recv(), Ok(0));
+    s.send(3).unwrap();
+    assert_eq!(drain(&mut b), 0);
+    for i in 1..4 {
+        assert_eq!(r.try_recv(), Ok(i));
+    }
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    s.send(4).unwrap();
+    assert_eq!(drain(&mut b), 8);
+
+    r.unregister_ready_fd(a.as_raw_fd());
+}
+
+#[test]
+fn full_descriptor() {
+    let (s, r) = unbounded();
+    let (a, mut b) = socket_pair();
+    assert!(r.register_ready_fd(a.as_raw_fd()));
+
+    // Fill the socket so that signalling it fails with `EAGAIN`.
+    let mut writer = a.try_clone().unwrap();
+    while writer.write(&[0; 1024]).is_ok() {}
+
+    for i in 0..10 {
+        s.send(i).unwrap();
+        assert_eq!(r.try_recv(), Ok(i));
+        assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    }
+    assert!(drain(&mut b) > 0);
+
+    r.unregister_ready_fd(a.as_raw_fd());
+}
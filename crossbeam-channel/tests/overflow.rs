@@ -0,0 +1,116 @@
+//! Tests for channels created by `bounded_with_overflow`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded_with_overflow, RecvTimeoutError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = bounded_with_overflow(1);
+    s.send(7).unwrap();
+    assert_eq!(s.overflow_len(), 0);
+    assert_eq!(r.recv(), Ok(7));
+}
+
+#[test]
+fn never_blocks() {
+    let (s, r) = bounded_with_overflow(2);
+
+    for i in 0..10 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(s.capacity(), 2);
+    assert_eq!(s.overflow_len(), 8);
+    assert!(!s.is_empty());
+
+    for i in 0..10 {
+        assert_eq!(r.recv(), Ok(i));
+    }
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn fifo_after_draining() {
+    let (s, r) = bounded_with_overflow(1);
+
+    s.send(0).unwrap();
+    s.send(1).unwrap();
+    assert_eq!(r.recv(), Ok(0));
+
+    // Message 1 may still be in the overflow buffer, so message 2 must not overtake it.
+    s.send(2).unwrap();
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+}
+
+#[test]
+fn disconnect() {
+    let (s, r) = bounded_with_overflow(1);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    drop(s);
+
+    // Buffered messages are delivered before the disconnection.
+    assert_eq!(r.iter().collect::<Vec<_>>(), [1, 2]);
+}
+
+#[test]
+fn receiver_dropped() {
+    let (s, r) = bounded_with_overflow(1);
+    s.send(1).unwrap();
+    drop(r);
+
+    assert!(s.send(2).is_err());
+    thread::sleep(ms(50));
+    assert!(s.send(3).is_err());
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    bounded_with_overflow::<i32>(0);
+}
+
+#[test]
+fn mpsc() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded_with_overflow::<usize>(3);
+
+    scope(|scope| {
+        for t in 0..THREADS {
+            let s = s.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(t * COUNT + i).unwrap();
+                }
+            });
+        }
+        drop(s);
+
+        // Messages from each thread are received in order.
+        let mut last = vec![None; THREADS];
+        for msg in r.iter() {
+            let t = msg / COUNT;
+            assert!(last[t] < Some(msg));
+            last[t] = Some(msg);
+        }
+        assert_eq!(
+            last,
+            (0..THREADS)
+                .map(|t| Some((t + 1) * COUNT - 1))
+                .collect::<Vec<_>>()
+        );
+    })
+    .unwrap();
+}
@@ -0,0 +1,148 @@
+//! Tests for `Receiver::poll_recv`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, never, unbounded, Poll, Receiver, RecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Returns a counter and a callback that increments it.
+fn counter() -> (Arc<AtomicUsize>, impl FnOnce() + Send + 'static) {
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+    (count, move || {
+        c.fetch_add(1, Ordering::SeqCst);
+    })
+}
+
+#[test]
+fn ready() {
+    let (s, r) = unbounded();
+    s.send(7).unwrap();
+
+    let (count, f) = counter();
+    assert_eq!(r.poll_recv(f), Poll::Ready(Ok(7)));
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+
+    drop(s);
+    let (count, f) = counter();
+    assert_eq!(r.poll_recv(f), Poll::Ready(Err(RecvError)));
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn woken_by_send() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = bounded(2);
+
+    for &(ref s, ref r) in &[(s1, r1), (s2, r2)] {
+        let (count, f) = counter();
+        assert_eq!(r.poll_recv(f), Poll::Pending);
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        s.send(1).unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        // The callback is consumed by the first notification.
+        s.send(2).unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        let (count, f) = counter();
+        assert_eq!(r.poll_recv(f), Poll::Ready(Ok(1)));
+        assert_eq!(r.poll_recv(|| {}), Poll::Ready(Ok(2)));
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+    }
+}
+
+#[test]
+fn woken_by_blocked_sender() {
+    let (s, r) = bounded(0);
+    let (count, f) = counter();
+
+    scope(|scope| {
+        assert_eq!(r.poll_recv(f), Poll::Pending);
+
+        scope.spawn(|_| s.send(1).unwrap());
+
+        while count.load(Ordering::SeqCst) == 0 {
+            thread::sleep(ms(10));
+        }
+        assert_eq!(r.recv(), Ok(1));
+    })
+    .unwrap();
+}
+
+#[test]
+fn woken_by_disconnect() {
+    let (s, r) = unbounded::<i32>();
+    let (count, f) = counter();
+
+    assert_eq!(r.poll_recv(f), Poll::Pending);
+    drop(s);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(r.poll_recv(|| {}), Poll::Ready(Err(RecvError)));
+}
+
+#[test]
+fn timers() {
+    let r = never::<i32>();
+    let (count, f) = counter();
+    assert_eq!(r.poll_recv(f), Poll::Pending);
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+
+    let r = after(ms(50));
+    let (count, f) = counter();
+    assert_eq!(r.poll_recv(f), Poll::Pending);
+    thread::sleep(ms(100));
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+
+    let (count, f) = counter();
+    match r.poll_recv(f) {
+        Poll::Ready(Ok(_)) => {}
+        p => panic!("{:?}", p),
+    }
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn event_loop() {
+    const COUNT: usize = 10_000;
+
+    let (s, r) = bounded(3);
+
+    fn poll(r: &Receiver<usize>) -> Poll<Result<usize, RecvError>> {
+        let t = thread::current();
+        r.poll_recv(move || t.unpark())
+    }
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+
+        let mut next = 0;
+        loop {
+            match poll(&r) {
+                Poll::Ready(Ok(i)) => {
+                    assert_eq!(i, next);
+                    next += 1;
+                }
+                Poll::Ready(Err(RecvError)) => break,
+                Poll::Pending => thread::park(),
+            }
+        }
+        assert_eq!(next, COUNT);
+    })
+    .unwrap();
+}
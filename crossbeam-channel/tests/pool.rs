@@ -0,0 +1,114 @@
+//! Tests for channels paired with a pool of reusable items.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crossbeam_channel::pipe_with_pool;
+use crossbeam_utils::thread::scope;
+
+#[test]
+fn smoke() {
+    let (s, r, pool) = pipe_with_pool(1, || vec![0u8; 8]);
+
+    let buf = pool.take();
+    let ptr = buf.as_ptr();
+    s.send(buf).unwrap();
+
+    let buf = r.recv().unwrap();
+    pool.recycle(buf);
+    assert_eq!(pool.len(), 1);
+
+    // The same allocation is reused.
+    let buf = pool.take();
+    assert_eq!(buf.as_ptr(), ptr);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn channel_capacity() {
+    let (s, r, _pool) = pipe_with_pool(3, Vec::<u8>::new);
+    assert_eq!(s.capacity(), Some(3));
+    assert_eq!(r.capacity(), Some(3));
+
+    let (s, _r, pool) = pipe_with_pool(0, Vec::<u8>::new);
+    assert_eq!(s.capacity(), Some(0));
+    pool.recycle(Vec::new());
+    assert_eq!(pool.len(), 1);
+}
+
+#[test]
+fn retains_at_most_cap() {
+    let made = Arc::new(AtomicUsize::new(0));
+    let (_s, _r, pool) = {
+        let made = made.clone();
+        pipe_with_pool(2, move || {
+            made.fetch_add(1, Ordering::SeqCst);
+            String::new()
+        })
+    };
+
+    let items = (0..5).map(|_| pool.take()).collect::<Vec<_>>();
+    assert_eq!(made.load(Ordering::SeqCst), 5);
+
+    for item in items {
+        pool.recycle(item);
+    }
+    assert_eq!(pool.len(), 2);
+
+    pool.take();
+    pool.take();
+    assert_eq!(made.load(Ordering::SeqCst), 5);
+    pool.take();
+    assert_eq!(made.load(Ordering::SeqCst), 6);
+}
+
+#[test]
+fn clones_share_items() {
+    let (_s, _r, pool) = pipe_with_pool(4, || 0);
+    let pool2 = pool.clone();
+
+    pool.recycle(7);
+    assert_eq!(pool2.len(), 1);
+    assert_eq!(pool2.take(), 7);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn producer_consumer() {
+    const COUNT: usize = 10_000;
+    const CAP: usize = 4;
+
+    let made = Arc::new(AtomicUsize::new(0));
+    let (s, r, pool) = {
+        let made = made.clone();
+        pipe_with_pool(CAP, move || {
+            made.fetch_add(1, Ordering::SeqCst);
+            Vec::with_capacity(64)
+        })
+    };
+
+    scope(|scope| {
+        let pool2 = pool.clone();
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                let mut buf = pool2.take();
+                buf.clear();
+                buf.push(i);
+                s.send(buf).unwrap();
+            }
+        });
+
+        for i in 0..COUNT {
+            let buf = r.recv().unwrap();
+            assert_eq!(buf, [i]);
+            pool.recycle(buf);
+        }
+    })
+    .unwrap();
+
+    // Items in circulation: up to `CAP` in the channel, plus one held by each side.
+    assert!(made.load(Ordering::SeqCst) <= CAP + 2);
+}
@@ -0,0 +1,279 @@
+//! Tests for the priority channel flavor.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded_priority, ChannelKind};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = bounded_priority(1);
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+
+    s.send_urgent(8).unwrap();
+    assert_eq!(r.recv(), Ok(8));
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn capacity() {
+    for i in 1..10 {
+        let (s, r) = bounded_priority::<()>(i);
+        assert_eq!(s.capacity(), Some(i));
+        assert_eq!(r.capacity(), Some(i));
+        assert_eq!(r.kind(), ChannelKind::Bounded);
+    }
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    bounded_priority::<()>(0);
+}
+
+#[test]
+fn len_empty_full() {
+    let (s, r) = bounded_priority(2);
+
+    assert_eq!(s.len(), 0);
+    assert_eq!(s.is_empty(), true);
+    assert_eq!(s.is_full(), false);
+    assert_eq!(r.len(), 0);
+
+    s.send(()).unwrap();
+    s.send_urgent(()).unwrap();
+
+    assert_eq!(s.len(), 2);
+    assert_eq!(s.is_empty(), false);
+    assert_eq!(s.is_full(), true);
+    assert_eq!(r.len(), 2);
+    assert_eq!(r.is_full(), true);
+
+    r.recv().unwrap();
+
+    assert_eq!(s.len(), 1);
+    assert_eq!(s.is_full(), false);
+    assert_eq!(r.len(), 1);
+}
+
+#[test]
+fn urgent_first() {
+    let (s, r) = bounded_priority(10);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    s.send_urgent(10).unwrap();
+    s.send(3).unwrap();
+    s.send_urgent(20).unwrap();
+
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [10, 20, 1, 2, 3]);
+}
+
+#[test]
+fn shared_capacity() {
+    let (s, r) = bounded_priority(2);
+
+    s.send(1).unwrap();
+    s.send_urgent(2).unwrap();
+    assert_eq!(s.try_send(3), Err(TrySendError::Full(3)));
+    assert_eq!(s.try_send_urgent(4), Err(TrySendError::Full(4)));
+    assert_eq!(
+        s.send_timeout(5, ms(100)),
+        Err(SendTimeoutError::Timeout(5))
+    );
+    assert_eq!(
+        s.send_urgent_timeout(6, ms(100)),
+        Err(SendTimeoutError::Timeout(6))
+    );
+
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(s.try_send_urgent(7), Ok(()));
+    assert_eq!(r.recv(), Ok(7));
+    assert_eq!(r.recv(), Ok(1));
+}
+
+#[test]
+fn send_blocks_until_recv() {
+    let (s, r) = bounded_priority(1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            s.send(1).unwrap();
+            s.send_urgent(2).unwrap();
+            s.send(3).unwrap();
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            assert_eq!(r.recv(), Ok(1));
+            thread::sleep(ms(500));
+            assert_eq!(r.recv(), Ok(2));
+            assert_eq!(r.recv(), Ok(3));
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_after_disconnect() {
+    let (s, r) = bounded_priority(10);
+    s.send(1).unwrap();
+    s.send_urgent(2).unwrap();
+    drop(s);
+
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn send_after_disconnect() {
+    let (s, r) = bounded_priority(10);
+    drop(r);
+    assert_eq!(s.send(1), Err(SendError(1)));
+    assert_eq!(s.send_urgent(2), Err(SendError(2)));
+    assert_eq!(s.try_send(3), Err(TrySendError::Disconnected(3)));
+}
+
+#[test]
+fn disconnect_wakes_sender() {
+    let (s, r) = bounded_priority(1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(s.send(1), Ok(()));
+            assert_eq!(s.send_urgent(2), Err(SendError(2)));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            drop(r);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn disconnect_wakes_receiver() {
+    let (s, r) = bounded_priority::<()>(1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn make_sender() {
+    let (s, r) = bounded_priority::<i32>(1);
+    assert!(r.make_sender().is_none());
+    assert!(r.identical_to(&r.clone()));
+    drop(s);
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = bounded_priority(10);
+    let (_s2, r2) = bounded_priority::<i32>(10);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..3 {
+                select! {
+                    recv(r1) -> v => assert_eq!(v, Ok(if i == 0 { 9 } else { i - 1 })),
+                    recv(r2) -> _ => panic!(),
+                }
+            }
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s1.send(0).unwrap();
+            s1.send(1).unwrap();
+            s1.send_urgent(9).unwrap();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded_priority::<usize>(3);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let n = r.recv().unwrap();
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for t in 0..THREADS {
+            let s = &s;
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    if t % 2 == 0 {
+                        s.send(i).unwrap();
+                    } else {
+                        s.send_urgent(i).unwrap();
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = bounded_priority(10);
+    s.send(DropCounter).unwrap();
+    s.send_urgent(DropCounter).unwrap();
+    s.send(DropCounter).unwrap();
+    drop(r.recv().unwrap());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+}
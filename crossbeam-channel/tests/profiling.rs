@@ -0,0 +1,109 @@
+//! Tests for `ProfilingReceiver`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{unbounded, ProfilingReceiver, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn empty() {
+    let (_s, r) = unbounded::<i32>();
+    let r = ProfilingReceiver::new(r);
+
+    let stats = r.stats();
+    assert_eq!(stats.count(), 0);
+    assert_eq!(stats.blocked(), 0);
+    assert_eq!(stats.p50(), None);
+    assert_eq!(stats.p99(), None);
+}
+
+#[test]
+fn ready_messages() {
+    let (s, r) = unbounded();
+    let r = ProfilingReceiver::new(r);
+
+    for i in 0..10 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.recv(), Ok(0));
+    assert_eq!(r.recv_timeout(ms(10)), Ok(1));
+    assert_eq!(r.try_recv(), Ok(2));
+
+    let stats = r.stats();
+    assert_eq!(stats.count(), 3);
+    assert_eq!(stats.blocked(), 0);
+    assert!(stats.p99().unwrap() <= Duration::new(0, 1_000));
+}
+
+#[test]
+fn percentiles() {
+    let (s, r) = unbounded();
+    let r = ProfilingReceiver::new(r);
+
+    for i in 0..99 {
+        s.send(i).unwrap();
+        r.recv().unwrap();
+    }
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.send(99).unwrap();
+        });
+        r.recv().unwrap();
+    })
+    .unwrap();
+
+    let stats = r.stats();
+    assert_eq!(stats.count(), 100);
+    assert_eq!(stats.blocked(), 1);
+    assert!(stats.p50().unwrap() < ms(1));
+    assert!(stats.p99().unwrap() < ms(1));
+    assert!(stats.percentile(1.0).unwrap() >= ms(50));
+}
+
+#[test]
+fn timeouts() {
+    let (s, r) = unbounded::<i32>();
+    let r = ProfilingReceiver::new(r);
+
+    assert_eq!(r.recv_timeout(ms(10)), Err(RecvTimeoutError::Timeout));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    drop(s);
+    assert_eq!(r.recv_timeout(ms(10)), Err(RecvTimeoutError::Disconnected));
+
+    let stats = r.stats();
+    assert_eq!(stats.count(), 0);
+    assert_eq!(stats.blocked(), 1);
+    assert_eq!(stats.timeouts(), 1);
+}
+
+#[test]
+fn shared_by_clones() {
+    let (s, r) = unbounded();
+    let r1 = ProfilingReceiver::new(r);
+    let r2 = r1.clone();
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    r1.recv().unwrap();
+    r2.recv().unwrap();
+
+    assert_eq!(r1.stats().count(), 2);
+    assert_eq!(r2.stats().count(), 2);
+}
+
+#[test]
+#[should_panic(expected = "percentile must be between 0 and 1")]
+fn invalid_percentile() {
+    let (_s, r) = unbounded::<i32>();
+    ProfilingReceiver::new(r).stats().percentile(1.5);
+}
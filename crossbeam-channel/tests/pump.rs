@@ -0,0 +1,118 @@
+//! Tests for `Receiver::pipe_to`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, RecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let mut pump = r1.pipe_to(s2, |x: i32| x + 1);
+
+    s1.send(1).unwrap();
+    assert!(pump.step());
+    assert_eq!(r2.recv(), Ok(2));
+    assert!(!pump.is_finished());
+}
+
+#[test]
+fn source_disconnects() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let mut pump = r1.pipe_to(s2, |x: i32| x);
+
+    s1.send(1).unwrap();
+    s1.send(2).unwrap();
+    drop(s1);
+
+    assert_eq!(pump.run(), 2);
+    assert!(pump.is_finished());
+    assert!(!pump.step());
+
+    assert_eq!(r2.recv(), Ok(1));
+    assert_eq!(r2.recv(), Ok(2));
+    assert_eq!(r2.recv(), Err(RecvError));
+}
+
+#[test]
+fn output_disconnects() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded::<i32>();
+    let mut pump = r1.pipe_to(s2, |x: i32| x);
+
+    drop(r2);
+    s1.send(1).unwrap();
+    assert!(!pump.step());
+    assert!(pump.is_finished());
+
+    // The source is dropped as well, so upstream stages stop.
+    assert_eq!(s1.try_send(2), Err(TrySendError::Disconnected(2)));
+}
+
+#[test]
+fn dropped_pump_disconnects() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+    let pump = r1.pipe_to(s2, |x| x);
+
+    drop(pump);
+    assert!(s1.send(1).is_err());
+    assert_eq!(r2.recv(), Err(RecvError));
+}
+
+#[test]
+fn pipeline() {
+    const COUNT: usize = 1000;
+
+    let (s1, r1) = bounded(1);
+    let (s2, r2) = bounded(0);
+    let (s3, r3) = bounded(1);
+
+    let mut a = r1.pipe_to(s2, |x: usize| x * 2);
+    let mut b = r2.pipe_to(s3, |x: usize| x + 1);
+
+    scope(|scope| {
+        scope.spawn(move |_| a.run());
+        scope.spawn(move |_| b.run());
+
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s1.send(i).unwrap();
+            }
+        });
+
+        for i in 0..COUNT {
+            assert_eq!(r3.recv(), Ok(i * 2 + 1));
+        }
+        assert_eq!(r3.recv(), Err(RecvError));
+    })
+    .unwrap();
+}
+
+#[test]
+fn blocked_step() {
+    let (s1, r1) = bounded(0);
+    let (s2, r2) = unbounded();
+    let mut pump = r1.pipe_to(s2, |x: i32| x);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(50));
+            s1.send(7).unwrap();
+        });
+        assert!(pump.step());
+    })
+    .unwrap();
+
+    assert_eq!(r2.recv(), Ok(7));
+}
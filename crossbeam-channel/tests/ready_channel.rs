@@ -0,0 +1,194 @@
+//! Tests for the ready channel flavor.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crossbeam_channel::{ready, unbounded, Select};
+use crossbeam_channel::{ChannelKind, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let r = ready(7);
+    assert_eq!(r.try_recv(), Ok(7));
+    assert_eq!(r.recv(), Ok(7));
+    assert_eq!(r.recv_timeout(ms(100)), Ok(7));
+    assert_eq!(r.try_recv(), Ok(7));
+}
+
+#[test]
+fn clones() {
+    let r = ready(vec![1, 2, 3]);
+    let mut a = r.recv().unwrap();
+    a.push(4);
+    assert_eq!(r.recv(), Ok(vec![1, 2, 3]));
+}
+
+#[test]
+fn capacity() {
+    let r = ready(());
+    assert_eq!(r.capacity(), Some(1));
+    assert_eq!(r.len(), 1);
+    assert_eq!(r.is_empty(), false);
+    assert_eq!(r.is_full(), true);
+    assert_eq!(r.kind(), ChannelKind::Bounded);
+    assert_eq!(r.name(), None);
+    assert!(r.make_sender().is_none());
+}
+
+#[test]
+fn never_disconnects() {
+    let r = ready(1);
+    let r2 = r.clone();
+    drop(r);
+
+    for _ in 0..10 {
+        assert_eq!(r2.try_recv(), Ok(1));
+        assert_ne!(r2.try_recv(), Err(TryRecvError::Disconnected));
+        assert_ne!(r2.recv_timeout(ms(10)), Err(RecvTimeoutError::Disconnected));
+    }
+    assert_eq!(r2.iter().take(5).collect::<Vec<_>>(), [1; 5]);
+}
+
+#[test]
+fn identical_to() {
+    let r = ready(1);
+    assert!(r.identical_to(&r.clone()));
+    assert!(!r.identical_to(&ready(1)));
+}
+
+#[test]
+fn select_when_others_empty() {
+    let (_s, r) = unbounded::<i32>();
+    let fallback = ready(0);
+
+    for _ in 0..100 {
+        select! {
+            recv(r) -> _ => panic!(),
+            recv(fallback) -> msg => assert_eq!(msg, Ok(0)),
+        }
+    }
+}
+
+#[test]
+fn select_fairness() {
+    const COUNT: usize = 10_000;
+
+    let (s, r) = unbounded();
+    let fallback = ready(0);
+    let mut hits = [0usize; 2];
+
+    for i in 1..COUNT {
+        s.send(i).unwrap();
+
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r);
+        let oper2 = sel.recv(&fallback);
+        let oper = sel.select();
+        match oper.index() {
+            ix if ix == oper1 => {
+                assert_eq!(oper.recv(&r), Ok(i));
+                hits[0] += 1;
+            }
+            ix if ix == oper2 => {
+                assert_eq!(oper.recv(&fallback), Ok(0));
+                assert_eq!(r.recv(), Ok(i));
+                hits[1] += 1;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // Both ready operations get chosen a fair share of the time.
+    assert!(hits[0] > COUNT / 4);
+    assert!(hits[1] > COUNT / 4);
+}
+
+#[test]
+fn select_biased_fallback() {
+    let (s, r) = unbounded();
+    let fallback = ready(0);
+
+    for i in 1..100 {
+        s.send(i).unwrap();
+        select_biased! {
+            recv(r) -> msg => assert_eq!(msg, Ok(i)),
+            recv(fallback) -> _ => panic!(),
+        }
+        select_biased! {
+            recv(r) -> _ => panic!(),
+            recv(fallback) -> msg => assert_eq!(msg, Ok(0)),
+        }
+
+        s.send(i).unwrap();
+        let mut sel = Select::new_biased();
+        let oper1 = sel.recv(&r);
+        let oper2 = sel.recv(&fallback);
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv(&r), Ok(i));
+
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper2);
+        assert_eq!(oper.recv(&fallback), Ok(0));
+    }
+}
+
+#[test]
+fn not_sync() {
+    let r = ready(Cell::new(3));
+
+    scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|_| {
+                for _ in 0..100 {
+                    assert_eq!(r.recv().unwrap().get(), 3);
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn select_default_not_taken() {
+    let r = ready(5);
+    select! {
+        recv(r) -> msg => assert_eq!(msg, Ok(5)),
+        default => panic!(),
+    }
+}
+
+#[test]
+fn mpmc() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 10_000;
+
+    let r = ready(String::from("msg"));
+    let received = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let r = r.clone();
+            let received = &received;
+            scope.spawn(move |_| {
+                for _ in 0..COUNT {
+                    assert_eq!(r.recv().unwrap(), "msg");
+                    received.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(received.load(Ordering::SeqCst), THREADS * COUNT);
+}
@@ -0,0 +1,249 @@
+//! Tests for recording and replaying the order of channel operations.
+
+#![cfg(feature = "replay")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::{Mutex, MutexGuard, Once, ONCE_INIT};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, Receiver, Select, Sender};
+use crossbeam_channel::{start_recording, start_replay, stop_recording, stop_replay, ReplayOp};
+use crossbeam_channel::{RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Serializes tests, since they share the global coordinator.
+fn serialize() -> MutexGuard<'static, ()> {
+    static INIT: Once = ONCE_INIT;
+    static mut LOCK: *const Mutex<()> = 0 as *const Mutex<()>;
+
+    unsafe {
+        INIT.call_once(|| LOCK = Box::into_raw(Box::new(Mutex::new(()))));
+        match (*LOCK).lock() {
+            Ok(guard) => guard,
+            Err(err) => err.into_inner(),
+        }
+    }
+}
+
+/// Sends the thread's name from a few named threads and collects the order of arrival.
+fn race(s: &Sender<String>, r: &Receiver<String>) -> Vec<String> {
+    scope(|scope| {
+        for name in &["a", "b", "c", "d"] {
+            let s = s.clone();
+            scope
+                .builder()
+                .name(name.to_string())
+                .spawn(move |_| {
+                    for _ in 0..3 {
+                        s.send(name.to_string()).unwrap();
+                    }
+                })
+                .unwrap();
+        }
+    })
+    .unwrap();
+
+    r.try_iter().collect()
+}
+
+#[test]
+fn records_operations() {
+    let _guard = serialize();
+
+    let (s0, r0) = unbounded::<i32>();
+    start_recording();
+    let (s1, r1) = unbounded();
+    let (s2, r2) = bounded(1);
+
+    s0.send(0).unwrap();
+    r0.recv().unwrap();
+    s1.send(1).unwrap();
+    s2.try_send(2).unwrap();
+    assert_eq!(r2.recv_timeout(ms(100)), Ok(2));
+    assert_eq!(r1.try_recv(), Ok(1));
+    assert_eq!(r1.try_recv(), Err(TryRecvError::Empty));
+
+    let log = stop_recording();
+    let ops: Vec<_> = log.iter().map(|e| (e.channel(), e.op())).collect();
+    assert_eq!(
+        ops,
+        [
+            (0, ReplayOp::Send),
+            (1, ReplayOp::Send),
+            (1, ReplayOp::Recv),
+            (0, ReplayOp::Recv),
+        ]
+    );
+    assert!(log.iter().all(|e| e.thread() == thread::current().id()));
+    assert!(log.windows(2).all(|w| w[0].elapsed() <= w[1].elapsed()));
+
+    // Nothing is recorded once recording stops.
+    s1.send(3).unwrap();
+    assert!(stop_recording().is_empty());
+}
+
+#[test]
+fn send_and_len() {
+    let _guard = serialize();
+
+    start_recording();
+    let (s, r) = unbounded();
+    assert_eq!(s.send_and_len(1), Ok(1));
+    r.recv().unwrap();
+    let log = stop_recording();
+    let ops: Vec<_> = log.iter().map(|e| e.op()).collect();
+    assert_eq!(ops, [ReplayOp::Send, ReplayOp::Recv]);
+
+    // The receive waits for its turn until the send is replayed.
+    start_replay(log);
+    let (s, r) = unbounded();
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(s.send_and_len(1), Ok(1));
+    assert_eq!(r.try_recv(), Ok(1));
+    assert!(stop_replay().is_empty());
+}
+
+#[test]
+fn replays_interleaving() {
+    let _guard = serialize();
+
+    start_recording();
+    let (s, r) = unbounded();
+    let recorded = race(&s, &r);
+    let log = stop_recording();
+    assert_eq!(log.len(), 24);
+    assert_eq!(log[0].thread_name(), Some(&*recorded[0]));
+
+    for _ in 0..5 {
+        start_replay(log.clone());
+        let (s, r) = unbounded();
+        assert_eq!(race(&s, &r), recorded);
+        assert!(stop_replay().is_empty());
+    }
+}
+
+#[test]
+fn try_ops_fail_out_of_turn() {
+    let _guard = serialize();
+
+    start_recording();
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    s1.send(1).unwrap();
+    s2.send(2).unwrap();
+    r1.recv().unwrap();
+    r2.recv().unwrap();
+    let log = stop_recording();
+
+    start_replay(log);
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    s1.send(1).unwrap();
+    assert_eq!(r1.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r1.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+    s2.send(2).unwrap();
+    assert_eq!(r1.try_recv(), Ok(1));
+    assert_eq!(r2.try_recv(), Ok(2));
+    assert!(stop_replay().is_empty());
+}
+
+#[test]
+fn failed_ops_keep_their_turn() {
+    let _guard = serialize();
+
+    start_recording();
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+    r.recv().unwrap();
+    let log = stop_recording();
+
+    start_replay(log);
+    let (s, r) = unbounded();
+    drop(r);
+
+    // The send fails, so its event is not replayed.
+    assert!(s.send(1).is_err());
+    assert_eq!(stop_replay().len(), 2);
+}
+
+#[test]
+fn select_ops() {
+    let _guard = serialize();
+
+    fn run() {
+        let (s, r) = unbounded();
+
+        let mut sel = Select::new();
+        sel.send(&s);
+        let oper = sel.select();
+        assert_eq!(oper.send(&s, 1), Ok(()));
+
+        let mut sel = Select::new();
+        sel.recv(&r);
+        let oper = sel.select();
+        assert_eq!(oper.recv(&r), Ok(1));
+    }
+
+    start_recording();
+    run();
+    let log = stop_recording();
+    let ops: Vec<_> = log.iter().map(|e| (e.channel(), e.op())).collect();
+    assert_eq!(ops, [(0, ReplayOp::Send), (0, ReplayOp::Recv)]);
+
+    // Selected operations are not held back, but they complete their events.
+    start_replay(log);
+    run();
+    assert!(stop_replay().is_empty());
+}
+
+#[test]
+fn stop_replay_releases_waiters() {
+    let _guard = serialize();
+
+    start_recording();
+    let (s1, _r1) = unbounded();
+    let (s2, _r2) = unbounded();
+    s1.send(1).unwrap();
+    s2.send(2).unwrap();
+    let log = stop_recording();
+
+    start_replay(log);
+    let (_s1, _r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            assert!(r2.is_empty());
+            assert_eq!(stop_replay().len(), 2);
+        });
+
+        // This send waits for a send on the first channel that never happens.
+        s2.send(2).unwrap();
+    })
+    .unwrap();
+    assert_eq!(r2.try_recv(), Ok(2));
+}
+
+#[test]
+fn untracked_channels() {
+    let _guard = serialize();
+
+    let (s, r) = unbounded();
+    start_replay(Vec::new());
+    s.send(1).unwrap();
+    assert_eq!(r.recv(), Ok(1));
+    assert!(stop_replay().is_empty());
+
+    start_recording();
+    s.send(2).unwrap();
+    assert_eq!(r.recv(), Ok(2));
+    assert!(stop_recording().is_empty());
+}
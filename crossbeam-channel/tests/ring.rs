@@ -0,0 +1,140 @@
+//! Tests for `ring_channel`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{ring_channel, RecvError, SendError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = ring_channel(1);
+    assert_eq!(s.send(7), Ok(None));
+    assert_eq!(r.try_recv(), Ok(7));
+
+    assert_eq!(s.send(8), Ok(None));
+    assert_eq!(r.recv(), Ok(8));
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    ring_channel::<i32>(0);
+}
+
+#[test]
+fn evicts_oldest() {
+    let (s, r) = ring_channel(3);
+    assert_eq!(s.capacity(), 3);
+
+    for i in 0..3 {
+        assert_eq!(s.send(i), Ok(None));
+    }
+    assert!(s.is_full());
+
+    for i in 3..10 {
+        assert_eq!(s.send(i), Ok(Some(i - 3)));
+    }
+    assert_eq!(s.len(), 3);
+
+    assert_eq!(r.recv(), Ok(7));
+    assert_eq!(s.send(10), Ok(None));
+    assert_eq!(s.send(11), Ok(Some(8)));
+
+    let v: Vec<_> = r.try_iter().collect();
+    assert_eq!(v, [9, 10, 11]);
+    assert!(s.is_empty());
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = ring_channel(1);
+    s.send(1).unwrap();
+    drop(s);
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+
+    let (s, r) = ring_channel(1);
+    s.send(1).unwrap();
+    drop(r);
+    assert_eq!(s.send(2), Err(SendError(2)));
+}
+
+#[test]
+fn recv_wakes_up() {
+    let (s, r) = ring_channel(2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.send(7).unwrap();
+        });
+
+        assert_eq!(r.recv(), Ok(7));
+    })
+    .unwrap();
+}
+
+#[test]
+fn plain_sender() {
+    let (s, r) = ring_channel(2);
+    let plain = r.make_sender().unwrap();
+
+    plain.send(1).unwrap();
+    plain.send(2).unwrap();
+    assert!(plain.try_send(3).is_err());
+
+    assert_eq!(s.send(3), Ok(Some(1)));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv(), Ok(3));
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = ring_channel::<usize>(3);
+    let received = AtomicUsize::new(0);
+    let evicted = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let s = s.clone();
+            let evicted = &evicted;
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    if s.send(i).unwrap().is_some() {
+                        evicted.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            });
+        }
+        drop(s);
+
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in r.iter() {
+                    received.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    // Every message is either received or evicted, exactly once.
+    assert_eq!(
+        received.load(Ordering::SeqCst) + evicted.load(Ordering::SeqCst),
+        COUNT * THREADS
+    );
+}
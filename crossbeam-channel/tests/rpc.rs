@@ -0,0 +1,103 @@
+//! Tests for `rpc`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::time::Duration;
+
+use crossbeam_channel::{rpc, CallError, RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (client, server) = rpc();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let (req, responder) = server.recv().unwrap();
+            assert_eq!(req, "ping");
+            responder.respond("pong");
+        });
+
+        assert_eq!(client.call("ping"), Ok("pong"));
+    })
+    .unwrap();
+}
+
+#[test]
+fn server_disconnected() {
+    let (client, server) = rpc::<i32, i32>();
+    drop(server);
+
+    let err = client.call(1).unwrap_err();
+    assert!(err.is_disconnected());
+    assert_eq!(err.into_inner(), Some(1));
+}
+
+#[test]
+fn no_reply() {
+    let (client, server) = rpc::<i32, i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let (req, responder) = server.recv().unwrap();
+            assert_eq!(req, 1);
+            drop(responder);
+        });
+
+        let err = client.call(1).unwrap_err();
+        assert!(err.is_no_reply());
+        assert_eq!(err, CallError::NoReply);
+        assert_eq!(err.into_inner(), None);
+    })
+    .unwrap();
+}
+
+#[test]
+fn client_disconnected() {
+    let (client, server) = rpc::<i32, i32>();
+
+    assert_eq!(server.try_recv().err(), Some(TryRecvError::Empty));
+    assert_eq!(
+        server.recv_timeout(ms(50)).err(),
+        Some(RecvTimeoutError::Timeout)
+    );
+
+    drop(client);
+    assert_eq!(server.recv().err(), Some(RecvError));
+}
+
+#[test]
+fn many_clients_and_servers() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 200;
+
+    let (client, server) = rpc();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let server = server.clone();
+            scope.spawn(move |_| {
+                while let Ok((x, responder)) = server.recv() {
+                    responder.respond(x * 2);
+                }
+            });
+        }
+
+        for t in 0..THREADS {
+            let client = client.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    let x = t * COUNT + i;
+                    assert_eq!(client.call(x), Ok(x * 2));
+                }
+            });
+        }
+        drop(client);
+    })
+    .unwrap();
+}
@@ -0,0 +1,110 @@
+//! Tests for channels created by `scoped_channel`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{scoped_channel, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let data = vec![1, 2, 3];
+    let (s, r) = scoped_channel::<i32>();
+
+    s.send(&data[0]).unwrap();
+    s.send(&data[2]).unwrap();
+    assert_eq!(r.len(), 2);
+    assert_eq!(r.recv(), Ok(&1));
+    assert_eq!(r.try_recv(), Ok(&3));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+
+    drop(s);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn unsized_data() {
+    let text = String::from("hello world");
+    let (s, r) = scoped_channel::<str>();
+
+    for word in text.split(' ') {
+        s.send(word).unwrap();
+    }
+    drop(s);
+    assert_eq!(r.iter().collect::<Vec<_>>(), ["hello", "world"]);
+}
+
+#[test]
+fn workers() {
+    const COUNT: usize = 1000;
+    const THREADS: usize = 4;
+
+    let data = (0..COUNT).collect::<Vec<usize>>();
+    let (s, r) = scoped_channel::<usize>();
+    let (done_s, done_r) = scoped_channel::<usize>();
+
+    let sums = scope(|scope| {
+        let handles = (0..THREADS)
+            .map(|_| {
+                let r = r.clone();
+                let done_s = done_s.clone();
+                scope.spawn(move |_| {
+                    let mut sum = 0;
+                    for x in r.iter() {
+                        sum += *x;
+                        done_s.send(x).unwrap();
+                    }
+                    sum
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for x in &data {
+            s.send(x).unwrap();
+        }
+        drop(s);
+
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+    })
+    .unwrap();
+
+    assert_eq!(sums.iter().sum::<usize>(), COUNT * (COUNT - 1) / 2);
+
+    // Every received reference still points into `data`.
+    drop(done_s);
+    let mut seen = done_r
+        .try_iter()
+        .map(|x| x as *const usize)
+        .collect::<Vec<_>>();
+    seen.sort();
+    let expected = data.iter().map(|x| x as *const usize).collect::<Vec<_>>();
+    assert_eq!(seen, expected);
+}
+
+#[test]
+fn disconnect_on_receiver_drop() {
+    let data = 7;
+    let (s, r) = scoped_channel::<i32>();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            drop(r);
+        });
+        assert!(s.send(&data).is_ok());
+        thread::sleep(ms(500));
+        assert_eq!(s.send(&data).unwrap_err().into_inner(), &7);
+    })
+    .unwrap();
+}
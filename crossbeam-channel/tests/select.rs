@@ -5,10 +5,16 @@ extern crate crossbeam_utils;
 
 use std::any::Any;
 use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crossbeam_channel::{after, bounded, tick, unbounded, Receiver, Select, TryRecvError};
+use crossbeam_channel::select_fairness_test;
+use crossbeam_channel::TryRecvError;
+use crossbeam_channel::{after, bounded, never, select_try, tick, unbounded};
+use crossbeam_channel::{Phase, Receiver, RecvError, RecvOrClosed, Select, SendError};
 use crossbeam_utils::thread::scope;
 
 fn ms(ms: u64) -> Duration {
@@ -134,6 +140,73 @@ fn disconnected() {
     .unwrap();
 }
 
+#[test]
+fn disconnect_wakes_blocked() {
+    let channels = vec![unbounded::<i32>(), bounded(1), bounded(0)];
+
+    // Dropping the last sender wakes a select blocked on receiving.
+    for (s1, r1) in channels {
+        let (_s2, r2) = unbounded::<i32>();
+        let s3 = s1.clone();
+
+        scope(|scope| {
+            scope.spawn(move |_| {
+                thread::sleep(ms(500));
+                drop(s1);
+                thread::sleep(ms(500));
+                drop(s3);
+            });
+
+            let start = Instant::now();
+            let mut sel = Select::new();
+            let oper1 = sel.recv(&r1);
+            let oper2 = sel.recv(&r2);
+            let oper = sel.select();
+            match oper.index() {
+                i if i == oper1 => assert!(oper.recv(&r1).is_err()),
+                i if i == oper2 => panic!(),
+                _ => unreachable!(),
+            }
+            assert!(start.elapsed() >= ms(1000));
+        })
+        .unwrap();
+    }
+
+    let channels = vec![bounded::<i32>(1), bounded(0)];
+
+    // Dropping the last receiver wakes a select blocked on sending.
+    for (s1, r1) in channels {
+        let (s2, _r2) = bounded::<i32>(0);
+        let r3 = r1.clone();
+
+        if s1.capacity() == Some(1) {
+            s1.send(0).unwrap();
+        }
+
+        scope(|scope| {
+            scope.spawn(move |_| {
+                thread::sleep(ms(500));
+                drop(r1);
+                thread::sleep(ms(500));
+                drop(r3);
+            });
+
+            let start = Instant::now();
+            let mut sel = Select::new();
+            let oper1 = sel.send(&s1);
+            let oper2 = sel.send(&s2);
+            let oper = sel.select();
+            match oper.index() {
+                i if i == oper1 => assert!(oper.send(&s1, 1).is_err()),
+                i if i == oper2 => panic!(),
+                _ => unreachable!(),
+            }
+            assert!(start.elapsed() >= ms(1000));
+        })
+        .unwrap();
+    }
+}
+
 #[test]
 fn default() {
     let (s1, r1) = unbounded::<i32>();
@@ -1151,6 +1224,143 @@ fn fairness1() {
     assert!(hits.iter().all(|x| x.get() >= COUNT / hits.len() / 2));
 }
 
+#[test]
+fn select_try_sweep() {
+    let (s1, r1) = unbounded::<usize>();
+    let (s2, r2) = bounded::<usize>(1);
+    let (s3, r3) = bounded::<usize>(0);
+
+    assert_eq!(select_try(&[&r1, &r2, &r3]), None);
+    assert_eq!(select_try::<usize>(&[]), None);
+
+    s2.send(2).unwrap();
+    assert_eq!(select_try(&[&r1, &r2, &r3]), Some((1, 2)));
+    assert_eq!(select_try(&[&r1, &r2, &r3]), None);
+
+    // Disconnected channels are skipped.
+    drop(s1);
+    drop(s3);
+    assert_eq!(select_try(&[&r1, &r2, &r3]), None);
+    s2.send(4).unwrap();
+    assert_eq!(select_try(&[&r1, &r2, &r3]), Some((1, 4)));
+}
+
+#[test]
+fn select_try_fairness() {
+    const COUNT: usize = 10_000;
+
+    let (s1, r1) = unbounded::<()>();
+    let (s2, r2) = unbounded::<()>();
+    let (s3, r3) = unbounded::<()>();
+
+    for _ in 0..COUNT {
+        s1.send(()).unwrap();
+        s2.send(()).unwrap();
+        s3.send(()).unwrap();
+    }
+
+    let mut hits = [0usize; 3];
+    for _ in 0..COUNT {
+        let (i, ()) = select_try(&[&r1, &r2, &r3]).unwrap();
+        hits[i] += 1;
+    }
+    assert!(hits.iter().all(|x| *x >= COUNT / hits.len() / 2));
+}
+
+#[test]
+fn select_try_uniform_among_ready() {
+    const COUNT: usize = 20_000;
+
+    let (s1, r1) = unbounded::<()>();
+    let (s2, r2) = unbounded::<()>();
+    let (_s3, r3) = unbounded::<()>();
+
+    for _ in 0..COUNT {
+        s1.send(()).unwrap();
+        s2.send(()).unwrap();
+    }
+
+    // Only the first two receivers are ready, and they must win equally often.
+    let mut hits = [0usize; 3];
+    for _ in 0..COUNT {
+        let (i, ()) = select_try(&[&r1, &r2, &r3]).unwrap();
+        hits[i] += 1;
+    }
+    assert_eq!(hits[2], 0);
+    for &h in &hits[..2] {
+        let diff = (h as f64 - COUNT as f64 / 2.0).abs();
+        assert!(diff < COUNT as f64 * 0.05, "hits = {:?}", hits);
+    }
+}
+
+#[test]
+fn fairness_uniform() {
+    const COUNT: usize = 20_000;
+
+    // Counts that don't divide `2^32`, where a plain modulo reduction is biased.
+    for &n in &[3, 5, 7, 11] {
+        let chans = (0..n).map(|_| unbounded::<()>()).collect::<Vec<_>>();
+        for &(ref s, _) in &chans {
+            for _ in 0..COUNT * 2 {
+                s.send(()).unwrap();
+            }
+        }
+        let receivers = chans.iter().map(|&(_, ref r)| r).collect::<Vec<_>>();
+
+        let mut try_hits = vec![0usize; n];
+        let mut sel_hits = vec![0usize; n];
+        for _ in 0..COUNT * n {
+            let (i, ()) = select_try(&receivers).unwrap();
+            try_hits[i] += 1;
+
+            let mut sel = Select::new();
+            for r in &receivers {
+                sel.recv(r);
+            }
+            let oper = sel.select();
+            let i = oper.index();
+            oper.recv(receivers[i]).unwrap();
+            sel_hits[i] += 1;
+        }
+
+        // Each count has a standard deviation of about `sqrt(COUNT)`, so 5% is many of those.
+        for hits in &[try_hits, sel_hits] {
+            for &h in hits {
+                let diff = (h as f64 - COUNT as f64).abs();
+                assert!(diff < COUNT as f64 * 0.05, "n = {}, hits = {:?}", n, hits);
+            }
+        }
+    }
+}
+
+#[test]
+fn select_fairness_test_counts() {
+    const COUNT: usize = 10_000;
+
+    assert_eq!(select_fairness_test::<()>(&[], COUNT), Vec::<usize>::new());
+
+    let (s1, r1) = unbounded::<()>();
+    let (s2, r2) = unbounded::<()>();
+    let (s3, r3) = bounded::<()>(COUNT);
+
+    for _ in 0..COUNT {
+        s1.send(()).unwrap();
+        s2.send(()).unwrap();
+        s3.send(()).unwrap();
+    }
+
+    let hits = select_fairness_test(&[&r1, &r2, &r3], COUNT);
+    assert_eq!(hits.iter().sum::<usize>(), COUNT);
+    assert!(hits.iter().all(|x| *x >= COUNT / hits.len() / 2));
+    assert_eq!(r1.len() + r2.len() + r3.len(), COUNT * 2);
+
+    // A receiver that is never ready always loses.
+    let (_s4, r4) = unbounded::<()>();
+    drop(s1);
+    let hits = select_fairness_test(&[&r1, &r4], COUNT);
+    assert_eq!(hits, [COUNT, 0]);
+}
+
 #[test]
 fn fairness2() {
     const COUNT: usize = 10_000;
@@ -1302,3 +1512,667 @@ fn reuse() {
     })
     .unwrap();
 }
+
+#[test]
+fn with_spin() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s1.send(1).unwrap();
+        });
+
+        let mut sel = Select::with_spin(1000);
+        let oper1 = sel.recv(&r1);
+        let oper2 = sel.recv(&r2);
+        let oper = sel.select();
+        match oper.index() {
+            i if i == oper1 => assert_eq!(oper.recv(&r1), Ok(1)),
+            i if i == oper2 => panic!(),
+            _ => unreachable!(),
+        }
+
+        s2.send(2).unwrap();
+
+        let oper = sel.select();
+        match oper.index() {
+            i if i == oper1 => panic!(),
+            i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(2)),
+            _ => unreachable!(),
+        }
+
+        let now = Instant::now();
+        assert!(sel.select_timeout(ms(100)).is_err());
+        assert!(now.elapsed() >= ms(100));
+    })
+    .unwrap();
+}
+
+#[test]
+fn with_spin_duration() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s1.send(1).unwrap();
+        });
+
+        // The thread spins for a while and then blocks until the message arrives.
+        let mut sel = Select::with_spin_duration(ms(50));
+        let oper1 = sel.recv(&r1);
+        let oper2 = sel.recv(&r2);
+        let oper = sel.select();
+        match oper.index() {
+            i if i == oper1 => assert_eq!(oper.recv(&r1), Ok(1)),
+            i if i == oper2 => panic!(),
+            _ => unreachable!(),
+        }
+
+        s2.send(2).unwrap();
+
+        let oper = sel.select();
+        match oper.index() {
+            i if i == oper1 => panic!(),
+            i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(2)),
+            _ => unreachable!(),
+        }
+    })
+    .unwrap();
+
+    // The spin window is capped by the timeout.
+    let mut sel = Select::with_spin_duration(Duration::from_secs(10));
+    sel.recv(&r1);
+    sel.recv(&r2);
+
+    let now = Instant::now();
+    assert!(sel.select_timeout(ms(100)).is_err());
+    let elapsed = now.elapsed();
+    assert!(elapsed >= ms(100));
+    assert!(elapsed < ms(2000));
+
+    // A spin window too long to be a deadline doesn't overflow.
+    let mut sel = Select::with_spin_duration(Duration::new(u64::max_value(), 0));
+    sel.recv(&r1);
+    sel.recv(&r2);
+    assert!(sel.select_timeout(ms(100)).is_err());
+}
+
+#[test]
+fn send_sync() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    let (s, r) = unbounded::<i32>();
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r);
+    assert_send_sync(&sel);
+
+    let r = &r;
+    scope(|scope| {
+        // Build on this thread, select on another.
+        scope.spawn(move |_| {
+            let oper = sel.select();
+            assert_eq!(oper.index(), oper1);
+            assert_eq!(oper.recv(r), Ok(1));
+        });
+        thread::sleep(ms(100));
+        s.send(1).unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn shared_with_mutex() {
+    use std::sync::Mutex;
+
+    let (s, r) = unbounded::<i32>();
+    let sel = {
+        let mut sel = Select::new();
+        sel.recv(&r);
+        Mutex::new(sel)
+    };
+
+    for i in 0..4 {
+        s.send(i).unwrap();
+    }
+
+    scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|_| {
+                let oper = sel.lock().unwrap().select();
+                assert!(oper.recv(&r).is_ok());
+            });
+        }
+    })
+    .unwrap();
+    assert!(r.is_empty());
+}
+
+#[test]
+fn clear_reuse() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+    let mut sel = Select::with_capacity(2);
+
+    s1.send(1).unwrap();
+    s2.send(2).unwrap();
+
+    for (r, msg) in [(&r1, 1), (&r2, 2)].iter() {
+        sel.clear();
+        let oper1 = sel.recv(r);
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv(r), Ok(*msg));
+    }
+}
+
+#[test]
+fn disconnect_during_registration() {
+    const COUNT: usize = 1000;
+
+    for i in 0..COUNT {
+        let (s1, r1) = unbounded::<i32>();
+        let (s2, r2) = bounded::<i32>(0);
+        let (s3, r3) = bounded::<i32>(1);
+
+        scope(|scope| {
+            // Disconnect all channels while the other thread is registering its operations.
+            scope.spawn(move |_| {
+                for _ in 0..i % 8 {
+                    thread::yield_now();
+                }
+                drop(s1);
+                drop(s2);
+                drop(s3);
+            });
+
+            let start = Instant::now();
+            let mut sel = Select::new();
+            let oper1 = sel.recv(&r1);
+            let oper2 = sel.recv(&r2);
+            let oper3 = sel.recv(&r3);
+            let oper = sel.select();
+            match oper.index() {
+                ix if ix == oper1 => assert!(oper.recv(&r1).is_err()),
+                ix if ix == oper2 => assert!(oper.recv(&r2).is_err()),
+                ix if ix == oper3 => assert!(oper.recv(&r3).is_err()),
+                _ => unreachable!(),
+            }
+            assert!(start.elapsed() < Duration::from_secs(5));
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn recv_until() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (s3, r3) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            s1.send(1).unwrap();
+            s1.send(10).unwrap();
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s3.send(3).unwrap();
+        });
+
+        let mut sel = Select::new();
+        let mut res = sel.recv_until(&[&r1, &r2, &r3], 2);
+        res.sort();
+
+        // Only one message is taken from each receiver.
+        assert_eq!(res, [(0, 1), (2, 3)]);
+        assert_eq!(r1.recv(), Ok(10));
+    })
+    .unwrap();
+
+    drop(s2);
+}
+
+#[test]
+fn recv_until_disconnected() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded::<i32>();
+
+    s1.send(1).unwrap();
+    drop(s1);
+    drop(s2);
+
+    let mut sel = Select::new();
+    assert_eq!(sel.recv_until(&[&r1, &r2], 2), [(0, 1)]);
+    assert_eq!(sel.recv_until(&[&r1, &r2], 0), []);
+    assert_eq!(sel.recv_until::<i32>(&[], 1), []);
+}
+
+#[test]
+fn panicking_drop() {
+    struct PanicOnDrop(i32);
+
+    impl Drop for PanicOnDrop {
+        fn drop(&mut self) {
+            if !thread::panicking() {
+                panic!("dropped {}", self.0);
+            }
+        }
+    }
+
+    let (s1, r1) = unbounded::<PanicOnDrop>();
+    let (s2, r2) = bounded::<i32>(0);
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+
+    for i in 0..10 {
+        s1.send(PanicOnDrop(i)).unwrap();
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            let oper = sel.select();
+            assert_eq!(oper.index(), oper1);
+            // The message is dropped right after being received.
+            oper.recv(&r1).unwrap();
+        }));
+        assert!(res.is_err());
+        assert!(r1.is_empty());
+    }
+
+    // Registrations made by the panicking selections are gone, so a rendezvous still pairs up.
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s2.send(7).unwrap();
+        });
+
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper2);
+        assert_eq!(oper.recv(&r2), Ok(7));
+    })
+    .unwrap();
+
+    // The channel itself is still usable.
+    s1.send(PanicOnDrop(10)).unwrap();
+    assert_eq!(r1.len(), 1);
+    ::std::mem::forget(r1.recv().unwrap());
+}
+
+#[test]
+fn unbounded_send_never_parks() {
+    let (s1, r1) = unbounded();
+    let (_s2, r2) = bounded::<i32>(0);
+    let (_s3, r3) = unbounded::<i32>();
+
+    let mut sel = Select::new();
+    let oper1 = sel.send(&s1);
+    sel.recv(&r2);
+    sel.recv(&r3);
+
+    // The send operation is ready right away every time, even with other operations never ready.
+    for i in 0..1000 {
+        let oper = sel.try_select().unwrap();
+        assert_eq!(oper.index(), oper1);
+        oper.send(&s1, i).unwrap();
+    }
+    assert_eq!(r1.len(), 1000);
+
+    let oper = sel.select_timeout(ms(0)).unwrap();
+    assert_eq!(oper.index(), oper1);
+    oper.send(&s1, 1000).unwrap();
+
+    // Disconnection makes it ready as well, with an error.
+    drop(r1);
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.index(), oper1);
+    assert!(oper.send(&s1, 0).is_err());
+}
+
+#[test]
+fn all_disconnected_before_select() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(1);
+    let (s3, r3) = bounded::<i32>(0);
+    let (s4, r4) = bounded::<i32>(0);
+    drop(s1);
+    drop(s2);
+    drop(s3);
+    drop(r4);
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+    let oper3 = sel.recv(&r3);
+    let oper4 = sel.send(&s4);
+
+    // Every call selects an operation right away, and completing it returns an error.
+    let start = Instant::now();
+    for _ in 0..100 {
+        let oper = sel.select();
+        match oper.index() {
+            i if i == oper1 => assert_eq!(oper.recv(&r1), Err(RecvError)),
+            i if i == oper2 => assert_eq!(oper.recv(&r2), Err(RecvError)),
+            i if i == oper3 => assert_eq!(oper.recv(&r3), Err(RecvError)),
+            i if i == oper4 => assert_eq!(oper.send(&s4, 4), Err(SendError(4))),
+            _ => unreachable!(),
+        }
+        sel.ready();
+    }
+    assert!(start.elapsed() < ms(1000));
+}
+
+#[test]
+fn spinning_select_times_out() {
+    let (_s1, r1) = bounded::<i32>(0);
+    let (_s2, r2) = unbounded::<i32>();
+    let r3 = never::<i32>();
+
+    // None of the operations ever becomes ready, and none of the channels gets disconnected.
+    for &spin in &[0, 100, usize::max_value()] {
+        let mut sel = Select::with_spin(spin);
+        sel.recv(&r1);
+        sel.recv(&r2);
+        sel.recv(&r3);
+
+        let start = Instant::now();
+        assert!(sel.select_timeout(ms(100)).is_err());
+        let elapsed = start.elapsed();
+        assert!(elapsed >= ms(100));
+        assert!(elapsed < ms(2000));
+    }
+}
+
+#[test]
+fn send_keeps_move_only_message() {
+    use std::rc::Rc;
+
+    /// A large message that can't be cloned and counts how many times it was dropped.
+    struct Payload {
+        data: Box<[u8; 4096]>,
+        drops: Rc<Cell<usize>>,
+    }
+
+    impl Drop for Payload {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    let drops = Rc::new(Cell::new(0));
+    let mut msg = Payload {
+        data: Box::new([7; 4096]),
+        drops: drops.clone(),
+    };
+    let addr = &*msg.data as *const [u8; 4096];
+
+    let (s1, r1) = bounded(0);
+    let (s2, r2) = bounded(1);
+    drop(r1);
+    s2.send(Payload {
+        data: Box::new([0; 4096]),
+        drops: drops.clone(),
+    })
+    .unwrap();
+
+    // Only the disconnected channel is ready, so every attempt hands the message back.
+    for _ in 0..10 {
+        let mut sel = Select::new();
+        let oper1 = sel.send(&s1);
+        sel.send(&s2);
+
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper1);
+        msg = match oper.send(&s1, msg) {
+            Ok(()) => panic!(),
+            Err(SendError(msg)) => msg,
+        };
+        assert_eq!(&*msg.data as *const [u8; 4096], addr);
+        assert_eq!(drops.get(), 0);
+    }
+
+    // Make room in the second channel and send the same message there.
+    drop(r2.recv().unwrap());
+    assert_eq!(drops.get(), 1);
+
+    let mut sel = Select::new();
+    let oper2 = sel.send(&s2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert!(oper.send(&s2, msg).is_ok());
+
+    let msg = r2.recv().unwrap();
+    assert_eq!(&*msg.data as *const [u8; 4096], addr);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn prefer_direction() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+    let (s3, r3) = unbounded::<i32>();
+
+    let mut sel = Select::new();
+    let recv1 = sel.recv(&r1);
+    let send2 = sel.send(&s2);
+    let send3 = sel.send(&s3);
+
+    // Receiving is ready every time, and so is sending, since the channels are unbounded.
+    sel.prefer_send();
+    let mut sent = [0, 0];
+    for msg in 0..100 {
+        s1.send(msg).unwrap();
+
+        let oper = sel.select();
+        match oper.index() {
+            i if i == send2 => {
+                oper.send(&s2, msg).unwrap();
+                sent[0] += 1;
+            }
+            i if i == send3 => {
+                oper.send(&s3, msg).unwrap();
+                sent[1] += 1;
+            }
+            _ => panic!("a receive operation was selected"),
+        }
+    }
+    // Ties between the send operations are still broken randomly.
+    assert!(sent[0] > 0 && sent[1] > 0);
+
+    sel.prefer_recv();
+    for _ in 0..100 {
+        let oper = sel.try_select().unwrap();
+        assert_eq!(oper.index(), recv1);
+        oper.recv(&r1).unwrap();
+    }
+
+    // With nothing to receive, a send operation is selected regardless of the preference.
+    assert!(r1.is_empty());
+    let oper = sel.select_timeout(ms(100)).unwrap();
+    assert_ne!(oper.index(), recv1);
+    match oper.index() {
+        i if i == send2 => oper.send(&s2, 0).unwrap(),
+        _ => oper.send(&s3, 0).unwrap(),
+    }
+    assert_eq!(r2.len() + r3.len(), 101);
+}
+
+#[test]
+fn biased() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+    let (s3, r3) = unbounded::<i32>();
+
+    let mut sel = Select::new_biased();
+    let recv1 = sel.recv(&r1);
+    let send2 = sel.send(&s2);
+    let _send3 = sel.send(&s3);
+
+    // Every operation is ready, but the first one always wins.
+    for msg in 0..100 {
+        s1.send(msg).unwrap();
+        assert_eq!(sel.ready(), recv1);
+
+        let oper = sel.select();
+        assert_eq!(oper.index(), recv1);
+        assert_eq!(oper.recv(&r1), Ok(msg));
+    }
+
+    // With nothing to receive, the first send operation wins, even with a preference.
+    sel.prefer_send();
+    for msg in 0..100 {
+        let oper = sel.try_select().unwrap();
+        assert_eq!(oper.index(), send2);
+        oper.send(&s2, msg).unwrap();
+    }
+    assert_eq!(r2.len(), 100);
+    assert!(r3.is_empty());
+
+    // Disabling an operation keeps the others in order.
+    let mut sel = Select::new_biased();
+    let send1 = sel.send(&s1);
+    let send2 = sel.send(&s2);
+    let send3 = sel.send(&s3);
+    sel.disable(send1);
+    for _ in 0..10 {
+        assert_eq!(sel.ready(), send2);
+    }
+    sel.disable(send2);
+    assert_eq!(sel.ready(), send3);
+}
+
+#[test]
+fn poll_phase() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(0);
+    let (s3, r3) = unbounded::<i32>();
+    let wakeups = Arc::new(AtomicUsize::new(0));
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+    let w = wakeups.clone();
+    sel.set_waker(move || {
+        w.fetch_add(1, Ordering::SeqCst);
+    });
+
+    // A ready operation is found without subscribing.
+    s1.send(1).unwrap();
+    assert_eq!(sel.poll_phase(), Phase::Done(oper1));
+    assert_eq!(r1.try_recv(), Ok(1));
+
+    assert_eq!(sel.poll_phase(), Phase::Subscribing);
+    assert_eq!(sel.poll_phase(), Phase::Parked);
+    for _ in 0..10 {
+        assert_eq!(sel.poll_phase(), Phase::Parked);
+    }
+    assert_eq!(wakeups.load(Ordering::SeqCst), 0);
+
+    scope(|scope| {
+        scope.spawn(|_| s2.send(2).unwrap());
+
+        while wakeups.load(Ordering::SeqCst) == 0 {
+            thread::sleep(ms(10));
+        }
+        assert_eq!(sel.poll_phase(), Phase::Try);
+        assert_eq!(sel.poll_phase(), Phase::Done(oper2));
+        assert_eq!(r2.try_recv(), Ok(2));
+    })
+    .unwrap();
+    assert_eq!(wakeups.load(Ordering::SeqCst), 1);
+
+    // An operation that becomes ready before subscribing is tried again.
+    assert_eq!(sel.poll_phase(), Phase::Subscribing);
+    s1.send(3).unwrap();
+    assert_eq!(sel.poll_phase(), Phase::Try);
+    assert_eq!(sel.poll_phase(), Phase::Done(oper1));
+    assert_eq!(r1.try_recv(), Ok(3));
+
+    // Adding an operation drops the subscriptions.
+    assert_eq!(sel.poll_phase(), Phase::Subscribing);
+    assert_eq!(sel.poll_phase(), Phase::Parked);
+    let oper3 = sel.recv(&r3);
+    s1.send(4).unwrap();
+    assert_eq!(wakeups.load(Ordering::SeqCst), 1);
+    s3.send(5).unwrap();
+    sel.disable(oper1);
+    assert_eq!(sel.poll_phase(), Phase::Done(oper3));
+    drop(sel);
+    assert_eq!(r3.try_recv(), Ok(5));
+}
+
+#[test]
+fn poll_phase_drop() {
+    let wakeups = Arc::new(AtomicUsize::new(0));
+
+    // The `Select` may outlive the channels it selects over.
+    let mut sel = Select::new();
+    let (s, r) = unbounded::<i32>();
+    sel.recv(&r);
+    let w = wakeups.clone();
+    sel.set_waker(move || {
+        w.fetch_add(1, Ordering::SeqCst);
+    });
+
+    assert_eq!(sel.poll_phase(), Phase::Subscribing);
+    assert_eq!(sel.poll_phase(), Phase::Parked);
+
+    // Dropping the `Select` cancels the subscription and drops the waker.
+    drop(sel);
+    assert_eq!(Arc::strong_count(&wakeups), 1);
+    s.send(1).unwrap();
+    assert_eq!(wakeups.load(Ordering::SeqCst), 0);
+    assert_eq!(r.try_recv(), Ok(1));
+}
+
+#[test]
+fn recv_or_closed() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(0);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            s1.send(1).unwrap();
+            drop(s1);
+            thread::sleep(ms(100));
+            s2.send(2).unwrap();
+        });
+
+        let mut closed = false;
+        let mut received = vec![];
+        while received.len() < 2 {
+            let mut sel = Select::new();
+            let oper1 = if closed {
+                None
+            } else {
+                Some(sel.recv_or_closed(&r1))
+            };
+            let oper2 = sel.recv_or_closed(&r2);
+
+            let oper = sel.select();
+            match oper.index() {
+                i if Some(i) == oper1 => match oper.recv_or_closed(&r1) {
+                    RecvOrClosed::Message(x) => received.push(x),
+                    RecvOrClosed::Closed => {
+                        // The other channel is still connected.
+                        assert!(!closed);
+                        closed = true;
+                    }
+                },
+                i if i == oper2 => match oper.recv_or_closed(&r2) {
+                    RecvOrClosed::Message(x) => {
+                        // The closed event was seen before the delayed message arrived.
+                        assert!(closed);
+                        received.push(x);
+                    }
+                    RecvOrClosed::Closed => panic!(),
+                },
+                _ => unreachable!(),
+            }
+        }
+        assert_eq!(received, [1, 2]);
+    })
+    .unwrap();
+}
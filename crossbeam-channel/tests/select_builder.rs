@@ -0,0 +1,170 @@
+//! Tests for the `SelectBuilder` struct.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::cell::Cell;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, SelectBuilder};
+use crossbeam_channel::{RecvError, SendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<&str>();
+
+    s1.send(1).unwrap();
+    let res = SelectBuilder::new()
+        .recv(&r1, |msg| msg.map(|n| n.to_string()))
+        .recv(&r2, |msg| msg.map(|s| s.to_string()))
+        .wait();
+    assert_eq!(res, Ok("1".to_string()));
+
+    s2.send("two").unwrap();
+    let res = SelectBuilder::new()
+        .recv(&r1, |msg| msg.map(|n| n.to_string()))
+        .recv(&r2, |msg| msg.map(|s| s.to_string()))
+        .wait();
+    assert_eq!(res, Ok("two".to_string()));
+}
+
+#[test]
+fn only_winner_runs() {
+    let (s, r) = bounded(1);
+    let (_s2, r2) = unbounded::<i32>();
+    let calls = Cell::new(0);
+
+    s.send(7).unwrap();
+    let res = SelectBuilder::new()
+        .recv(&r2, |_| {
+            calls.set(calls.get() + 10);
+            0
+        })
+        .recv(&r, |msg| {
+            calls.set(calls.get() + 1);
+            msg.unwrap()
+        })
+        .default(|| {
+            calls.set(calls.get() + 100);
+            -1
+        })
+        .wait();
+
+    assert_eq!(res, 7);
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn default() {
+    let (s, r) = unbounded::<i32>();
+    let (s2, _r2) = bounded::<i32>(0);
+
+    let res = SelectBuilder::new()
+        .recv(&r, |_| "recv")
+        .send(&s2, 1, |_| "send")
+        .default(|| "default")
+        .wait();
+    assert_eq!(res, "default");
+
+    s.send(1).unwrap();
+    let res = SelectBuilder::new()
+        .recv(&r, |_| "recv")
+        .send(&s2, 1, |_| "send")
+        .default(|| "default")
+        .wait();
+    assert_eq!(res, "recv");
+
+    let res = SelectBuilder::<&str>::new().default(|| "default").wait();
+    assert_eq!(res, "default");
+}
+
+#[test]
+fn blocking() {
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.send(5).unwrap();
+        });
+
+        let res = SelectBuilder::new().recv(&r, |msg| msg).wait();
+        assert_eq!(res, Ok(5));
+    })
+    .unwrap();
+}
+
+#[test]
+fn send() {
+    let (s, r) = bounded(1);
+
+    let res = SelectBuilder::new().send(&s, 1, |res| res).wait();
+    assert_eq!(res, Ok(()));
+    assert_eq!(r.try_recv(), Ok(1));
+
+    drop(r);
+    let res = SelectBuilder::new().send(&s, 2, |res| res).wait();
+    assert_eq!(res, Err(SendError(2)));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = unbounded::<i32>();
+    drop(s);
+
+    let res = SelectBuilder::new()
+        .recv(&r, |msg| msg)
+        .default(|| Ok(0))
+        .wait();
+    assert_eq!(res, Err(RecvError));
+}
+
+#[test]
+fn unselected_message_dropped() {
+    let (s, _r) = bounded::<String>(0);
+    let (s2, r2) = unbounded();
+    s2.send(()).unwrap();
+
+    let msg = "not sent".to_string();
+    let res = SelectBuilder::new()
+        .send(&s, msg, |_| false)
+        .recv(&r2, |_| true)
+        .wait();
+    assert!(res);
+}
+
+#[test]
+#[should_panic(expected = "no operations have been added to `Select`")]
+fn empty() {
+    SelectBuilder::<()>::new().wait();
+}
+
+#[test]
+fn fairness() {
+    const COUNT: usize = 10_000;
+
+    let (s1, r1) = unbounded::<()>();
+    let (s2, r2) = unbounded::<()>();
+    let hits = [Cell::new(0usize), Cell::new(0usize)];
+
+    for _ in 0..COUNT {
+        s1.send(()).unwrap();
+        s2.send(()).unwrap();
+    }
+
+    for _ in 0..COUNT {
+        SelectBuilder::new()
+            .recv(&r1, |_| hits[0].set(hits[0].get() + 1))
+            .recv(&r2, |_| hits[1].set(hits[1].get() + 1))
+            .wait();
+    }
+
+    assert!(hits.iter().all(|x| x.get() >= COUNT / hits.len() / 2));
+}
@@ -0,0 +1,135 @@
+//! Tests for `select2`, `select3`, `select4`, and their variants.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, never, unbounded, RecvError};
+use crossbeam_channel::{select2, select2_timeout, try_select2, Either};
+use crossbeam_channel::{select3, select3_timeout, try_select3, Either3};
+use crossbeam_channel::{select4, select4_timeout, try_select4, Either4};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<&str>();
+
+    s1.send(1).unwrap();
+    assert_eq!(select2(&r1, &r2), Either::First(Ok(1)));
+
+    s2.send("two").unwrap();
+    assert_eq!(select2(&r1, &r2), Either::Second(Ok("two")));
+}
+
+#[test]
+fn disconnected() {
+    let (s1, r1) = unbounded::<i32>();
+    let (_s2, r2) = unbounded::<i32>();
+
+    drop(s1);
+    assert_eq!(select2(&r1, &r2), Either::First(Err(RecvError)));
+    assert_eq!(try_select2(&r1, &r2), Ok(Either::First(Err(RecvError))));
+}
+
+#[test]
+fn try_select() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+    let (s3, r3) = unbounded::<i32>();
+    let (s4, r4) = unbounded::<i32>();
+
+    assert!(try_select2(&r1, &r2).is_err());
+    assert!(try_select3(&r1, &r2, &r3).is_err());
+    assert!(try_select4(&r1, &r2, &r3, &r4).is_err());
+
+    s3.send(3).unwrap();
+    assert_eq!(try_select3(&r1, &r2, &r3), Ok(Either3::Third(Ok(3))));
+
+    s4.send(4).unwrap();
+    assert_eq!(try_select4(&r1, &r2, &r3, &r4), Ok(Either4::Fourth(Ok(4))));
+
+    drop((s1, s2));
+}
+
+#[test]
+fn timeout() {
+    let (s1, r1) = unbounded::<i32>();
+    let r2 = never::<i32>();
+    let r3 = never::<i32>();
+    let r4 = never::<i32>();
+
+    let start = Instant::now();
+    assert!(select2_timeout(&r1, &r2, ms(50)).is_err());
+    assert!(select3_timeout(&r1, &r2, &r3, ms(50)).is_err());
+    assert!(select4_timeout(&r1, &r2, &r3, &r4, ms(50)).is_err());
+    assert!(start.elapsed() >= ms(150));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(50));
+            s1.send(1).unwrap();
+        });
+        assert_eq!(
+            select4_timeout(&r1, &r2, &r3, &r4, ms(1000)),
+            Ok(Either4::First(Ok(1)))
+        );
+    })
+    .unwrap();
+}
+
+#[test]
+fn blocking() {
+    let (s1, r1) = bounded::<i32>(0);
+    let (s2, r2) = bounded::<u8>(0);
+    let (s3, r3) = bounded::<()>(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(50));
+            s2.send(2).unwrap();
+            thread::sleep(ms(50));
+            s3.send(()).unwrap();
+        });
+
+        assert_eq!(select3(&r1, &r2, &r3), Either3::Second(Ok(2)));
+        assert_eq!(select3(&r1, &r2, &r3), Either3::Third(Ok(())));
+    })
+    .unwrap();
+
+    drop(s1);
+}
+
+#[test]
+fn fairness() {
+    const COUNT: usize = 10_000;
+
+    let (s1, r1) = unbounded::<()>();
+    let (s2, r2) = unbounded::<()>();
+    let (s3, r3) = unbounded::<()>();
+    let (s4, r4) = unbounded::<()>();
+
+    for _ in 0..COUNT {
+        s1.send(()).unwrap();
+        s2.send(()).unwrap();
+        s3.send(()).unwrap();
+        s4.send(()).unwrap();
+    }
+
+    let mut hits = [0usize; 4];
+    for _ in 0..COUNT {
+        match select4(&r1, &r2, &r3, &r4) {
+            Either4::First(_) => hits[0] += 1,
+            Either4::Second(_) => hits[1] += 1,
+            Either4::Third(_) => hits[2] += 1,
+            Either4::Fourth(_) => hits[3] += 1,
+        }
+    }
+    assert!(hits.iter().all(|x| *x >= COUNT / 4 / 2));
+}
@@ -1438,3 +1438,74 @@ fn disconnect_wakes_receiver() {
     })
     .unwrap();
 }
+
+#[test]
+fn biased() {
+    const COUNT: usize = 1000;
+
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+    let (s3, r3) = bounded::<i32>(COUNT);
+
+    for _ in 0..COUNT {
+        s1.send(1).unwrap();
+        s2.send(2).unwrap();
+    }
+
+    // All operations are ready, but the one written first always wins.
+    for _ in 0..COUNT {
+        select_biased! {
+            recv(r2) -> msg => assert_eq!(msg, Ok(2)),
+            recv(r1) -> _ => panic!(),
+            send(s3, 3) -> _ => panic!(),
+        }
+    }
+    assert_eq!(r2.try_recv(), Err(TryRecvError::Empty));
+
+    for _ in 0..COUNT {
+        select_biased! {
+            send(s3, 3) -> res => assert_eq!(res, Ok(())),
+            recv(r1) -> _ => panic!(),
+            default => panic!(),
+        }
+    }
+    assert_eq!(r3.len(), COUNT);
+
+    // The channel is now full, so the next operation wins.
+    for _ in 0..COUNT {
+        select_biased! {
+            send(s3, 3) -> _ => panic!(),
+            recv(r1) -> msg => assert_eq!(msg, Ok(1)),
+            recv(r3) -> _ => panic!(),
+            default(ms(1000)) => panic!(),
+        }
+    }
+    assert_eq!(r1.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn biased_blocking() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s2.send(2).unwrap();
+        });
+
+        // Only the second operation becomes ready, so it is selected.
+        select_biased! {
+            recv(r1) -> _ => panic!(),
+            recv(r2) -> msg => assert_eq!(msg, Ok(2)),
+        }
+    })
+    .unwrap();
+
+    drop(s1);
+    select_biased! {
+        recv(r1) -> msg => assert_eq!(msg, Err(RecvError)),
+        recv(r2) -> _ => panic!(),
+        default => panic!(),
+    }
+}
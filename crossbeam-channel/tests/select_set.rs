@@ -0,0 +1,187 @@
+//! Tests for `SelectSet`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, SelectSet};
+use crossbeam_channel::{RecvError, SendError, TrySelectError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let mut set = SelectSet::new().add_recv(&r1).add_recv(&r2);
+    assert_eq!(set.len(), 2);
+
+    s1.send(1).unwrap();
+    let oper = set.select();
+    assert_eq!(oper.index(), 0);
+    assert_eq!(oper.recv(&r1), Ok(1));
+
+    s2.send(2).unwrap();
+    let oper = set.select();
+    assert_eq!(oper.index(), 1);
+    assert_eq!(oper.recv(&r2), Ok(2));
+
+    assert!(set.try_select().is_err());
+    assert!(set.select_timeout(ms(50)).is_err());
+}
+
+#[test]
+fn reuse() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = bounded(0);
+    let mut set = SelectSet::new().add_recv(&r1).add_recv(&r2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..1000 {
+                s1.send(i).unwrap();
+            }
+        });
+        scope.spawn(|_| {
+            for i in 0..1000 {
+                s2.send(i).unwrap();
+            }
+        });
+
+        let mut count = [0; 2];
+        for _ in 0..2000 {
+            let oper = set.select();
+            let i = oper.index();
+            let r = if i == 0 { &r1 } else { &r2 };
+            assert_eq!(oper.recv(r), Ok(count[i]));
+            count[i] += 1;
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn send() {
+    let (s1, r1) = bounded(1);
+    let (s2, r2) = unbounded::<i32>();
+    let mut set = SelectSet::new().add_send(&s1).add_recv(&r2);
+
+    let oper = set.select();
+    assert_eq!(oper.index(), 0);
+    assert_eq!(oper.send(&s1, 10), Ok(()));
+
+    assert!(set.try_select().is_err());
+    assert_eq!(r1.recv(), Ok(10));
+
+    let oper = set.select();
+    assert_eq!(oper.index(), 0);
+    assert_eq!(oper.send(&s1, 20), Ok(()));
+    assert_eq!(r1.try_recv(), Ok(20));
+
+    drop(s2);
+}
+
+#[test]
+fn skip_disconnected() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (s3, r3) = bounded::<i32>(0);
+    let mut set = SelectSet::new().add_recv(&r1).add_recv(&r2).add_send(&s3);
+
+    s1.send(1).unwrap();
+    drop(s1);
+    drop(r3);
+
+    let mut got = Vec::new();
+    while set.len() > 1 {
+        let oper = set.try_select().unwrap();
+        match oper.index() {
+            0 => got.extend(oper.recv(&r1)),
+            2 => assert_eq!(oper.send(&s3, 3), Err(SendError(3))),
+            _ => panic!(),
+        }
+    }
+
+    // The first channel got disconnected only after its message was received.
+    assert_eq!(got, [1]);
+    assert!(!set.contains(0));
+    assert!(set.contains(1));
+    assert!(!set.contains(2));
+
+    // Indices of the remaining operations stay the same.
+    s2.send(2).unwrap();
+    let oper = set.select();
+    assert_eq!(oper.index(), 1);
+    assert_eq!(oper.recv(&r2), Ok(2));
+
+    drop(s2);
+    let oper = set.select();
+    assert_eq!(oper.recv(&r2), Err(RecvError));
+    assert!(set.is_empty());
+    assert_eq!(set.try_select().err(), Some(TrySelectError));
+}
+
+#[test]
+fn remove() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let mut set = SelectSet::new().add_recv(&r1).add_recv(&r2);
+
+    s1.send(1).unwrap();
+    set.remove(0);
+    assert_eq!(set.len(), 1);
+    assert!(set.try_select().is_err());
+
+    s2.send(2).unwrap();
+    let oper = set.select();
+    assert_eq!(oper.index(), 1);
+    assert_eq!(oper.recv(&r2), Ok(2));
+    assert_eq!(r1.try_recv(), Ok(1));
+}
+
+#[test]
+fn holds_clones() {
+    let (s, r) = bounded::<i32>(0);
+    let (s2, r2) = unbounded();
+    let mut set = SelectSet::new().add_send(&s).add_recv(&r2);
+
+    // The set keeps the sending side connected.
+    drop(s);
+    assert!(r.recv_timeout(ms(50)).unwrap_err().is_timeout());
+
+    // Any receiver of the same channel completes the operation.
+    let r3 = r2.clone();
+    s2.send(5).unwrap();
+    let oper = set.try_select().unwrap();
+    assert_eq!(oper.index(), 1);
+    assert_eq!(oper.recv(&r3), Ok(5));
+
+    drop(set);
+    assert!(r.recv_timeout(ms(50)).unwrap_err().is_disconnected());
+}
+
+#[test]
+fn blocking() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded(0);
+    let mut set = SelectSet::new().add_recv(&r1).add_recv(&r2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s2.send(7).unwrap();
+        });
+
+        let oper = set.select();
+        assert_eq!(oper.index(), 1);
+        assert_eq!(oper.recv(&r2), Ok(7));
+    })
+    .unwrap();
+
+    drop(s1);
+}
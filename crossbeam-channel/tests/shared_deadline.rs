@@ -0,0 +1,139 @@
+//! Tests for `Receiver::recv_with_shared_deadline`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, unbounded, AtomicInstant, RecvTimeoutError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let deadline = AtomicInstant::new(Instant::now() + ms(1000));
+
+    s.send(7).unwrap();
+    assert_eq!(r.recv_with_shared_deadline(&deadline), Ok(7));
+
+    deadline.store(Instant::now() + ms(50));
+    let start = Instant::now();
+    assert_eq!(
+        r.recv_with_shared_deadline(&deadline),
+        Err(RecvTimeoutError::Timeout)
+    );
+    assert!(start.elapsed() >= ms(40));
+}
+
+#[test]
+fn load_store() {
+    let now = Instant::now();
+    let deadline = AtomicInstant::new(now + ms(100));
+    assert_eq!(deadline.load(), now + ms(100));
+
+    deadline.store(now + ms(200));
+    assert_eq!(deadline.load(), now + ms(200));
+
+    // Deadlines in the past are clamped, but stay in the past.
+    deadline.store(now - ms(100));
+    assert!(deadline.load() <= Instant::now());
+}
+
+#[test]
+fn already_passed() {
+    let (s, r) = unbounded::<i32>();
+    let deadline = AtomicInstant::new(Instant::now());
+
+    assert_eq!(
+        r.recv_with_shared_deadline(&deadline),
+        Err(RecvTimeoutError::Timeout)
+    );
+
+    // A ready message is still received.
+    s.send(1).unwrap();
+    assert_eq!(r.recv_with_shared_deadline(&deadline), Ok(1));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = bounded::<i32>(1);
+    let deadline = AtomicInstant::new(Instant::now() + ms(10_000));
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            drop(s);
+        });
+
+        assert_eq!(
+            r.recv_with_shared_deadline(&deadline),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    })
+    .unwrap();
+}
+
+#[test]
+fn shorten() {
+    let (_s, r) = unbounded::<i32>();
+    let deadline = AtomicInstant::new(Instant::now() + ms(10_000));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            deadline.store(Instant::now());
+        });
+
+        let start = Instant::now();
+        assert_eq!(
+            r.recv_with_shared_deadline(&deadline),
+            Err(RecvTimeoutError::Timeout)
+        );
+        assert!(start.elapsed() < ms(5000));
+    })
+    .unwrap();
+}
+
+#[test]
+fn extend() {
+    let (s, r) = bounded(0);
+    let deadline = AtomicInstant::new(Instant::now() + ms(100));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(50));
+            deadline.store(Instant::now() + ms(10_000));
+            thread::sleep(ms(200));
+            s.send(7).unwrap();
+        });
+
+        // The message arrives after the original deadline.
+        assert_eq!(r.recv_with_shared_deadline(&deadline), Ok(7));
+    })
+    .unwrap();
+}
+
+#[test]
+fn wake() {
+    let (s, r) = unbounded();
+    let deadline = AtomicInstant::new(Instant::now() + ms(10_000));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            // Extending the deadline alone doesn't wake the receiver, but `wake` does.
+            deadline.store(Instant::now() + ms(20_000));
+            deadline.wake();
+            s.send(7).unwrap();
+        });
+
+        assert_eq!(r.recv_with_shared_deadline(&deadline), Ok(7));
+        assert!(deadline.load() > Instant::now() + ms(15_000));
+    })
+    .unwrap();
+}
@@ -0,0 +1,121 @@
+//! Tests for channels that deliver OS signals.
+//!
+//! Every test uses a different signal because tests run concurrently in the same process.
+
+#![cfg(all(unix, feature = "signal"))]
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate libc;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{never, signal_channel, unbounded, RecvTimeoutError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn send_signal(sig: libc::c_int) {
+    unsafe {
+        assert_eq!(libc::kill(libc::getpid(), sig), 0);
+    }
+}
+
+#[test]
+fn smoke() {
+    let r = signal_channel(libc::SIGUSR1).unwrap();
+    assert!(r.try_recv().is_err());
+
+    send_signal(libc::SIGUSR1);
+    assert_eq!(r.recv_timeout(ms(1000)), Ok(()));
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn select_wakes_up() {
+    let r = signal_channel(libc::SIGUSR2).unwrap();
+    let (_s, work) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            send_signal(libc::SIGUSR2);
+        });
+
+        select! {
+            recv(work) -> _ => panic!(),
+            recv(never::<()>()) -> _ => panic!(),
+            recv(r) -> msg => assert_eq!(msg, Ok(())),
+            default(ms(1000)) => panic!(),
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn every_channel_receives() {
+    let r1 = signal_channel(libc::SIGURG).unwrap();
+    let r2 = signal_channel(libc::SIGURG).unwrap();
+
+    send_signal(libc::SIGURG);
+    assert_eq!(r1.recv_timeout(ms(1000)), Ok(()));
+    assert_eq!(r2.recv_timeout(ms(1000)), Ok(()));
+}
+
+#[test]
+fn shared_between_threads() {
+    const THREADS: usize = 4;
+
+    let r = signal_channel(libc::SIGALRM).unwrap();
+    let hits = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let r = r.clone();
+            let hits = &hits;
+            scope.spawn(move |_| {
+                select! {
+                    recv(r) -> msg => assert_eq!(msg, Ok(())),
+                    default(ms(5000)) => panic!(),
+                }
+                hits.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+
+        // Signals sent in quick succession may be merged, so send them one at a time.
+        for i in 0..THREADS {
+            send_signal(libc::SIGALRM);
+            while hits.load(Ordering::SeqCst) == i {
+                thread::sleep(ms(1));
+            }
+        }
+    })
+    .unwrap();
+
+    assert_eq!(hits.load(Ordering::SeqCst), THREADS);
+}
+
+#[test]
+fn reregister_after_drop() {
+    // `SIGWINCH` is ignored by default, so raising it again after the receiver is dropped is
+    // harmless.
+    let r = signal_channel(libc::SIGWINCH).unwrap();
+    drop(r);
+    send_signal(libc::SIGWINCH);
+    thread::sleep(ms(50));
+
+    let r = signal_channel(libc::SIGWINCH).unwrap();
+    send_signal(libc::SIGWINCH);
+    assert_eq!(r.recv_timeout(ms(1000)), Ok(()));
+}
+
+#[test]
+fn uncatchable() {
+    assert!(signal_channel(libc::SIGKILL).is_err());
+    assert!(signal_channel(libc::SIGSTOP).is_err());
+}
@@ -0,0 +1,87 @@
+//! Tests for `StashReceiver`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, StashReceiver};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let mut r = StashReceiver::new(r);
+
+    s.send(1).unwrap();
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(10)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn stash_order() {
+    let (s, r) = unbounded();
+    let mut r = StashReceiver::new(r);
+
+    for i in 0..10 {
+        s.send(i).unwrap();
+    }
+
+    assert_eq!(r.wait_for(|&x| x == 5), Ok(5));
+    assert_eq!(r.stashed(), 5);
+
+    // Matching messages in the stash are found without touching the channel.
+    assert_eq!(r.wait_for(|&x| x == 2), Ok(2));
+    assert_eq!(r.stashed(), 4);
+
+    // Stashed messages come first, then the rest of the channel.
+    assert_eq!(r.recv(), Ok(0));
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.recv_timeout(ms(10)), Ok(3));
+    assert_eq!(r.recv(), Ok(4));
+    assert_eq!(r.recv(), Ok(6));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = unbounded();
+    let mut r = StashReceiver::new(r);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    drop(s);
+
+    assert_eq!(r.wait_for(|&x| x == 3), Err(RecvError));
+    assert_eq!(r.stashed(), 2);
+
+    let (r, stash) = r.into_inner();
+    assert_eq!(stash, [1, 2]);
+    assert!(r.is_empty());
+}
+
+#[test]
+fn blocks() {
+    let (s, r) = bounded(0);
+    let mut r = StashReceiver::new(r);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..3 {
+                thread::sleep(ms(10));
+                s.send(i).unwrap();
+            }
+        });
+
+        assert_eq!(r.wait_for(|&x| x == 2), Ok(2));
+        assert_eq!(r.recv(), Ok(0));
+        assert_eq!(r.recv(), Ok(1));
+    })
+    .unwrap();
+}
@@ -0,0 +1,213 @@
+//! Tests for `swappable` receivers.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, swappable, unbounded, SwapMode};
+use crossbeam_channel::{RecvError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let (r, _handle) = swappable(r);
+
+    assert!(r.is_empty());
+    s.send(7).unwrap();
+    assert!(!r.is_empty());
+    assert_eq!(r.try_recv(), Ok(7));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    s.send(8).unwrap();
+    assert_eq!(r.recv(), Ok(8));
+
+    drop(s);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn drain() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (r, handle) = swappable(r1);
+
+    s1.send(1).unwrap();
+    s1.send(2).unwrap();
+    s2.send(10).unwrap();
+
+    handle.swap(r2, SwapMode::Drain);
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+
+    // The old channel is still drained while it is empty but not disconnected.
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    s1.send(3).unwrap();
+    assert_eq!(r.recv(), Ok(3));
+
+    // The old channel is dropped once it is found empty and disconnected.
+    drop(s1);
+    assert_eq!(r.recv(), Ok(10));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn discard() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (r, handle) = swappable(r1);
+
+    s1.send(1).unwrap();
+    s2.send(10).unwrap();
+
+    handle.swap(r2, SwapMode::Discard);
+    assert_eq!(r.recv(), Ok(10));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert!(s1.send(2).is_err());
+}
+
+#[test]
+fn swaps_in_a_row() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (s3, r3) = unbounded();
+    let (s4, r4) = unbounded();
+    let (r, handle) = swappable(r1);
+
+    s1.send(1).unwrap();
+    s2.send(2).unwrap();
+    s3.send(3).unwrap();
+    s4.send(4).unwrap();
+
+    handle.swap(r2, SwapMode::Drain);
+    handle.swap(r3, SwapMode::Drain);
+    drop(s1);
+    drop(s2);
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+
+    // Discarding also drops the channel that was still being drained.
+    let handle2 = handle.clone();
+    handle2.swap(r4, SwapMode::Discard);
+    assert_eq!(r.recv(), Ok(4));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert!(s3.send(5).is_err());
+}
+
+#[test]
+fn blocked_recv_switches() {
+    let (_s1, r1) = bounded::<i32>(0);
+    let (s2, r2) = bounded(0);
+    let (r, handle) = swappable(r1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv(), Ok(7));
+        });
+
+        thread::sleep(ms(500));
+        handle.swap(r2, SwapMode::Discard);
+        s2.send(7).unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn blocked_recv_drains() {
+    let (s1, r1) = bounded(0);
+    let (s2, r2) = bounded(0);
+    let (r, handle) = swappable(r1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Ok(2));
+        });
+
+        // The blocked receiver keeps waiting on the old channel until it is disconnected.
+        thread::sleep(ms(500));
+        handle.swap(r2, SwapMode::Drain);
+        thread::sleep(ms(500));
+        s1.send(1).unwrap();
+        drop(s1);
+        s2.send(2).unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn swap_disconnected_channel() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded();
+    let (r, handle) = swappable(r1);
+
+    drop(s1);
+    s2.send(1).unwrap();
+
+    // A pending swap is applied even though the current channel is disconnected.
+    handle.swap(r2, SwapMode::Drain);
+    assert_eq!(r.recv(), Ok(1));
+
+    drop(s2);
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn handles_dropped() {
+    let (s, r) = unbounded();
+    let (r, handle) = swappable(r);
+    drop(handle);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+
+        thread::sleep(ms(500));
+        s.send(1).unwrap();
+        thread::sleep(ms(500));
+        drop(s);
+    })
+    .unwrap();
+}
+
+#[test]
+fn stress() {
+    const COUNT: usize = 10_000;
+    const CHANNELS: usize = 10;
+
+    let mut chans = (0..CHANNELS).map(|_| bounded(5));
+    let (mut s, r) = chans.next().unwrap();
+    let (r, handle) = swappable(r);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT * CHANNELS {
+                assert_eq!(r.recv(), Ok(i));
+            }
+        });
+
+        // Without draining, a message could be lost in the old channel.
+        for c in 0..CHANNELS {
+            for i in 0..COUNT {
+                s.send(c * COUNT + i).unwrap();
+            }
+            if let Some((next_s, next_r)) = chans.next() {
+                handle.swap(next_r, SwapMode::Drain);
+
+                // Dropping the old sender lets the receiver move on. Doing it before the swap
+                // could leave the receiver with a disconnected channel and nothing to switch to.
+                s = next_s;
+            }
+        }
+    })
+    .unwrap();
+}
@@ -0,0 +1,96 @@
+//! Tests for `tagged_unbounded`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crossbeam_channel::{tagged_unbounded, SendError, SendTimeoutError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = tagged_unbounded();
+    assert_eq!(s.tag().id(), 0);
+
+    s.send(1).unwrap();
+    s.try_send(2).unwrap();
+    s.send_timeout(3, ms(100)).unwrap();
+    assert_eq!(s.len(), 3);
+
+    assert_eq!(r.recv(), Ok((s.tag(), 1)));
+    assert_eq!(r.recv(), Ok((s.tag(), 2)));
+    assert_eq!(r.recv(), Ok((s.tag(), 3)));
+    assert!(s.is_empty());
+}
+
+#[test]
+fn clones_get_new_tags() {
+    let (s1, r) = tagged_unbounded::<i32>();
+    let s2 = s1.clone();
+    let s3 = s2.clone();
+
+    assert_eq!(s1.tag().id(), 0);
+    assert_eq!(s2.tag().id(), 1);
+    assert_eq!(s3.tag().id(), 2);
+
+    // Tags are never reused, even after a sender is dropped.
+    drop(s3);
+    let s4 = s1.clone();
+    assert_eq!(s4.tag().id(), 3);
+
+    s4.send(4).unwrap();
+    s1.send(1).unwrap();
+    assert_eq!(r.recv(), Ok((s4.tag(), 4)));
+    assert_eq!(r.recv(), Ok((s1.tag(), 1)));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = tagged_unbounded();
+    drop(r);
+
+    assert_eq!(s.send(1), Err(SendError(1)));
+    assert_eq!(s.try_send(2), Err(TrySendError::Disconnected(2)));
+    assert_eq!(
+        s.send_timeout(3, ms(100)),
+        Err(SendTimeoutError::Disconnected(3))
+    );
+}
+
+#[test]
+fn many_producers() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 1000;
+
+    let (s, r) = tagged_unbounded();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let s = s.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+        drop(s);
+
+        // Each producer's messages arrive in order under its own tag.
+        let mut next = HashMap::new();
+        for (tag, msg) in r.iter() {
+            let expected = next.entry(tag).or_insert(0);
+            assert_eq!(msg, *expected);
+            *expected += 1;
+        }
+
+        assert_eq!(next.len(), THREADS);
+        assert!(next.values().all(|&n| n == COUNT));
+    })
+    .unwrap();
+}
@@ -0,0 +1,162 @@
+//! Tests for `tee`, `tee_bounded`, and `tee_lossy`.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, tee, tee_bounded, tee_lossy, unbounded};
+use crossbeam_channel::{RecvTimeoutError, SendTimeoutError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    const COUNT: usize = 1000;
+
+    let (s, r) = unbounded();
+    let (r1, r2) = tee(r);
+
+    for i in 0..COUNT {
+        s.send(i).unwrap();
+    }
+    drop(s);
+
+    assert_eq!(
+        r1.iter().collect::<Vec<_>>(),
+        (0..COUNT).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        r2.iter().collect::<Vec<_>>(),
+        (0..COUNT).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn unbounded_never_blocks_source() {
+    let (s, r) = bounded(0);
+    let (r1, r2) = tee(r);
+
+    for i in 0..100 {
+        s.send_timeout(i, ms(1000)).unwrap();
+    }
+    assert_eq!(r1.recv(), Ok(0));
+    drop(r2);
+}
+
+#[test]
+fn one_output_dropped() {
+    let (s, r) = unbounded();
+    let (r1, r2) = tee(r);
+    drop(r2);
+
+    for i in 0..10 {
+        s.send(i).unwrap();
+    }
+    drop(s);
+
+    assert_eq!(r1.iter().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn both_outputs_dropped() {
+    let (s, r) = bounded(0);
+    let (r1, r2) = tee(r);
+    drop(r1);
+    drop(r2);
+
+    // The pump notices on the first message and then drops the input.
+    s.send(1).unwrap();
+    loop {
+        match s.send_timeout(2, ms(1000)) {
+            Ok(()) => {}
+            Err(SendTimeoutError::Disconnected(2)) => break,
+            Err(err) => panic!("{:?}", err),
+        }
+    }
+}
+
+#[test]
+fn bounded_backpressure() {
+    let (s, r) = bounded(0);
+    let (fast, slow) = tee_bounded(r, 2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..10 {
+                assert_eq!(fast.recv(), Ok(i));
+            }
+        });
+
+        // The slow output holds two messages and the pump holds the third one.
+        for i in 0..3 {
+            s.send_timeout(i, ms(1000)).unwrap();
+        }
+        assert_eq!(
+            s.send_timeout(3, ms(100)),
+            Err(SendTimeoutError::Timeout(3))
+        );
+        assert_eq!(slow.len(), 2);
+
+        // Once the slow consumer catches up, the source gets going again.
+        scope.spawn(|_| {
+            for i in 0..10 {
+                assert_eq!(slow.recv(), Ok(i));
+            }
+        });
+        for i in 3..10 {
+            s.send_timeout(i, ms(1000)).unwrap();
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn lossy_drops_for_slow_output() {
+    const COUNT: usize = 100;
+
+    let (s, r) = unbounded();
+    let (fast, slow) = tee_lossy(r, 5);
+
+    for i in 0..COUNT {
+        s.send(i).unwrap();
+        assert_eq!(fast.recv_timeout(ms(1000)), Ok(i));
+    }
+    drop(s);
+
+    assert_eq!(
+        fast.recv_timeout(ms(1000)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+    assert_eq!(slow.iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn lossy_keeps_up() {
+    const COUNT: usize = 1000;
+
+    let (s, r) = bounded(0);
+    let (r1, r2) = tee_lossy(r, 1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+                thread::yield_now();
+            }
+        });
+
+        // Whatever gets through is in order.
+        for r in vec![r1, r2] {
+            scope.spawn(move |_| {
+                let v = r.iter().collect::<Vec<_>>();
+                assert!(v.windows(2).all(|w| w[0] < w[1]));
+            });
+        }
+    })
+    .unwrap();
+}
@@ -0,0 +1,126 @@
+//! Tests for throttled senders.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, unbounded};
+use crossbeam_channel::{SendError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = bounded(10);
+    let s = s.throttled(5);
+    assert_eq!(s.high_watermark(), 5);
+
+    s.send(1).unwrap();
+    s.try_send(2).unwrap();
+    s.send_timeout(3, ms(100)).unwrap();
+    assert_eq!(s.len(), 3);
+    assert!(!s.is_empty());
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv(), Ok(3));
+}
+
+#[test]
+fn below_watermark() {
+    let (s, r) = unbounded();
+    let s = s.throttled(1000);
+
+    let start = Instant::now();
+    for i in 0..1000 {
+        s.send(i).unwrap();
+    }
+    assert!(start.elapsed() < ms(1000));
+    assert_eq!(r.len(), 1000);
+}
+
+#[test]
+fn above_watermark() {
+    let (s, r) = unbounded();
+    let s = s.throttled(0);
+
+    for i in 0..1000 {
+        s.try_send(i).unwrap();
+    }
+
+    // With 1000 messages above the watermark, sending is delayed by a noticeable amount.
+    let start = Instant::now();
+    s.send(1000).unwrap();
+    assert!(start.elapsed() >= ms(5));
+    assert_eq!(r.len(), 1001);
+}
+
+#[test]
+fn max_delay() {
+    let (s, r) = unbounded();
+    let s = s.throttled(0);
+    assert_eq!(s.max_delay(), ms(10));
+
+    // Uncapped, a million messages above the watermark would delay sending by 10 seconds.
+    for i in 0..1_000_000 {
+        s.try_send(i).unwrap();
+    }
+
+    let start = Instant::now();
+    s.send(0).unwrap();
+    let elapsed = start.elapsed();
+    assert!(elapsed >= ms(10));
+    assert!(elapsed < ms(1000));
+
+    let s = s.with_max_delay(ms(200));
+    assert_eq!(s.max_delay(), ms(200));
+    let start = Instant::now();
+    s.send(0).unwrap();
+    let elapsed = start.elapsed();
+    assert!(elapsed >= ms(200));
+    assert!(elapsed < ms(2000));
+
+    assert_eq!(r.len(), 1_000_002);
+}
+
+#[test]
+fn try_send_not_delayed() {
+    let (s, _r) = bounded(1);
+    let s = s.throttled(0);
+
+    s.try_send(1).unwrap();
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = bounded(10);
+    let s = s.throttled(0);
+    drop(r);
+    assert_eq!(s.send(1), Err(SendError(1)));
+}
+
+#[test]
+fn slow_consumer() {
+    const COUNT: usize = 1000;
+
+    let (s, r) = bounded(100);
+    let s = s.throttled(10);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+
+        for i in 0..COUNT {
+            assert_eq!(r.recv(), Ok(i));
+        }
+    })
+    .unwrap();
+}
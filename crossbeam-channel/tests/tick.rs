@@ -10,7 +10,7 @@ use std::sync::atomic::Ordering;
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crossbeam_channel::{after, tick, Select, TryRecvError};
+use crossbeam_channel::{after, tick, tick_jittered, Select, TryRecvError};
 use crossbeam_utils::thread::scope;
 
 fn ms(ms: u64) -> Duration {
@@ -351,3 +351,47 @@ fn fairness_duplicates() {
         assert!(hits.iter().all(|x| *x >= COUNT / hits.len() / 2));
     }
 }
+
+#[test]
+fn jittered_intervals() {
+    let r = tick_jittered(ms(20), ms(10));
+
+    let mut prev = r.recv().unwrap();
+    let mut min = ms(1000);
+    let mut max = ms(0);
+
+    for _ in 0..20 {
+        let t = r.recv().unwrap();
+        let interval = t - prev;
+        min = min.min(interval);
+        max = max.max(interval);
+        prev = t;
+    }
+
+    // Each interval is within the jitter, plus some slack for scheduling.
+    assert!(min >= ms(10));
+    assert!(max < ms(60));
+
+    // With 20 uniform samples, the intervals are very unlikely to be all the same.
+    assert!(max - min >= ms(2));
+}
+
+#[test]
+fn jitter_limited_to_duration() {
+    let start = Instant::now();
+    let r = tick_jittered(ms(50), ms(1000));
+
+    let t = r.recv().unwrap();
+    assert!(start <= t);
+    assert!(start + ms(150) > t);
+}
+
+#[test]
+fn zero_jitter() {
+    let start = Instant::now();
+    let r = tick_jittered(ms(50), ms(0));
+
+    let t = r.recv().unwrap();
+    assert!(start + ms(50) <= t);
+    assert!(start + ms(100) > t);
+}
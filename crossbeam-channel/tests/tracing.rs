@@ -0,0 +1,142 @@
+//! Tests for the `tracing` instrumentation.
+
+#![cfg(feature = "tracing")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate tracing;
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded_named, unbounded};
+use crossbeam_utils::thread::scope;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Records span names with their fields and event messages, in order.
+#[derive(Clone, Default)]
+struct Recorder {
+    log: Arc<Mutex<Vec<String>>>,
+    next_id: Arc<AtomicUsize>,
+}
+
+/// Formats all fields as `name=value`.
+struct Fields(Vec<String>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &fmt::Debug) {
+        if field.name() == "message" {
+            self.0.push(format!("{:?}", value));
+        } else {
+            self.0.push(format!("{}={:?}", field.name(), value));
+        }
+    }
+}
+
+impl Recorder {
+    fn log(&self) -> Vec<String> {
+        self.log.lock().unwrap().clone()
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "crossbeam_channel"
+    }
+
+    fn new_span(&self, span: &Attributes) -> Id {
+        let mut fields = Fields(vec![span.metadata().name().to_string()]);
+        span.record(&mut fields);
+        self.log.lock().unwrap().push(fields.0.join(" "));
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) as u64 + 1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event) {
+        let mut fields = Fields(vec![]);
+        event.record(&mut fields);
+        self.log.lock().unwrap().push(fields.0.join(" "));
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[test]
+fn blocking_recv() {
+    let recorder = Recorder::default();
+    let (s, r) = bounded_named(1, "jobs");
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        scope(|scope| {
+            scope.spawn(|_| {
+                thread::sleep(ms(100));
+                s.send(7).unwrap();
+            });
+
+            assert_eq!(r.recv(), Ok(7));
+        })
+        .unwrap();
+    });
+
+    let log = recorder.log();
+    assert_eq!(log[0], "recv name=\"jobs\" len=0");
+    assert!(log.iter().any(|l| l.starts_with("parking")));
+    let woke = log.iter().find(|l| l.starts_with("woke up")).unwrap();
+    assert!(woke.contains("waited="));
+}
+
+#[test]
+fn ready_recv_does_not_park() {
+    let recorder = Recorder::default();
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        assert_eq!(r.recv(), Ok(1));
+    });
+
+    assert_eq!(recorder.log(), ["recv name=\"\" len=1"]);
+}
+
+#[test]
+fn send_and_len() {
+    let recorder = Recorder::default();
+    let (s, _r) = bounded_named(2, "jobs");
+    s.send(1).unwrap();
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        assert_eq!(s.send_and_len(2), Ok(2));
+    });
+
+    assert_eq!(recorder.log(), ["send name=\"jobs\" len=1"]);
+}
+
+#[test]
+fn disconnect() {
+    let recorder = Recorder::default();
+    let (s, r) = bounded_named::<i32>(1, "events");
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        drop(s);
+        drop(r);
+    });
+
+    assert_eq!(
+        recorder.log(),
+        ["all senders dropped, disconnected name=\"events\""]
+    );
+}
@@ -0,0 +1,161 @@
+//! Tests for `Receiver::set_high_watermark`.
+
+extern crate crossbeam_channel;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, bounded_priority, never, unbounded, Receiver};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Sets a watermark on `r` and returns a receiver getting a message for each callback call.
+fn watch<T>(r: &Receiver<T>, threshold: usize) -> Receiver<()> {
+    let (s, calls) = unbounded();
+    r.set_high_watermark(threshold, move || s.send(()).unwrap());
+    calls
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let calls = watch(&r, 3);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert!(calls.recv_timeout(ms(100)).is_err());
+
+    s.send(3).unwrap();
+    assert_eq!(calls.recv_timeout(ms(1000)), Ok(()));
+}
+
+#[test]
+fn once_per_crossing() {
+    let (s, r) = unbounded();
+    let calls = watch(&r, 2);
+
+    for i in 0..10 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(calls.recv_timeout(ms(1000)), Ok(()));
+    assert!(calls.recv_timeout(ms(100)).is_err());
+}
+
+#[test]
+fn refires_after_draining() {
+    let (s, r) = unbounded();
+    let calls = watch(&r, 2);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(calls.recv_timeout(ms(1000)), Ok(()));
+
+    // The length is checked on sending, so the next send must find the channel below the
+    // threshold before the watermark can fire again.
+    r.recv().unwrap();
+    r.recv().unwrap();
+    s.send(3).unwrap();
+    assert!(calls.recv_timeout(ms(100)).is_err());
+
+    s.send(4).unwrap();
+    assert_eq!(calls.recv_timeout(ms(1000)), Ok(()));
+}
+
+#[test]
+fn clear() {
+    let (s, r) = unbounded();
+    let calls = watch(&r, 1);
+
+    r.clear_high_watermark();
+    s.send(1).unwrap();
+    assert!(calls.recv_timeout(ms(100)).is_err());
+}
+
+#[test]
+fn replace() {
+    let (s, r) = unbounded();
+    let first = watch(&r, 1);
+    let second = watch(&r, 2);
+
+    s.send(1).unwrap();
+    assert!(second.recv_timeout(ms(100)).is_err());
+
+    s.send(2).unwrap();
+    assert_eq!(second.recv_timeout(ms(1000)), Ok(()));
+    assert!(first.try_recv().is_err());
+}
+
+#[test]
+fn one_notifier_thread() {
+    let (s, r) = unbounded();
+
+    // Each callback reports the thread it runs on.
+    let (ids_s, ids) = unbounded();
+    let report = move || {
+        let ids_s = ids_s.clone();
+        move || ids_s.send(thread::current().id()).unwrap()
+    };
+
+    r.set_high_watermark(1, report());
+    s.send(1).unwrap();
+    let first = ids.recv_timeout(ms(1000)).unwrap();
+
+    // Replacing or clearing the watermark keeps the same notifier thread.
+    r.recv().unwrap();
+    r.clear_high_watermark();
+    r.set_high_watermark(1, report());
+    s.send(2).unwrap();
+    assert_eq!(ids.recv_timeout(ms(1000)), Ok(first));
+    assert_ne!(first, thread::current().id());
+}
+
+#[test]
+fn array() {
+    let (s, r) = bounded(4);
+    let calls = watch(&r, 4);
+
+    for i in 0..3 {
+        s.try_send(i).unwrap();
+    }
+    assert!(calls.recv_timeout(ms(100)).is_err());
+
+    s.send_timeout(3, ms(1000)).unwrap();
+    assert_eq!(calls.recv_timeout(ms(1000)), Ok(()));
+}
+
+#[test]
+fn priority() {
+    let (s, r) = bounded_priority(4);
+    let calls = watch(&r, 2);
+
+    s.send(1).unwrap();
+    assert!(calls.recv_timeout(ms(100)).is_err());
+
+    s.send_urgent(2).unwrap();
+    assert_eq!(calls.recv_timeout(ms(1000)), Ok(()));
+}
+
+#[test]
+fn cross_thread() {
+    let (s, r) = unbounded();
+    let calls = watch(&r, 100);
+
+    let t = thread::spawn(move || {
+        for i in 0..100 {
+            s.send(i).unwrap();
+        }
+    });
+    t.join().unwrap();
+
+    assert_eq!(calls.recv_timeout(ms(1000)), Ok(()));
+    assert_eq!(r.len(), 100);
+}
+
+#[test]
+fn ignored_by_never() {
+    let r = never::<i32>();
+    r.set_high_watermark(0, || panic!());
+    r.clear_high_watermark();
+}
@@ -11,8 +11,8 @@ use std::sync::atomic::Ordering;
 use std::thread;
 use std::time::Duration;
 
-use crossbeam_channel::{bounded, Receiver};
-use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{bounded, Decision, PeekError, Receiver, Select};
+use crossbeam_channel::{RecvError, RecvSpinError, RecvTimeoutError, TryRecvError};
 use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
 use crossbeam_utils::thread::scope;
 use rand::{thread_rng, Rng};
@@ -103,6 +103,60 @@ fn recv() {
     .unwrap();
 }
 
+#[test]
+fn recv_reporting() {
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            // No sender is waiting yet, so the receiver has to wait.
+            assert_eq!(r.recv_reporting(), Ok((7, true)));
+
+            // A sender is already waiting, so the message is received right away.
+            thread::sleep(ms(1000));
+            assert_eq!(r.recv_reporting(), Ok((8, false)));
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(1000));
+            s.send(7).unwrap();
+            s.send(8).unwrap();
+        });
+    })
+    .unwrap();
+
+    drop(s);
+    assert_eq!(r.recv_reporting(), Err(RecvError));
+}
+
+#[test]
+fn recv_bounded_spins() {
+    let (s, r) = bounded(0);
+
+    // Nobody is sending, so the budget runs out.
+    assert_eq!(r.recv_bounded_spins(0), Err(RecvSpinError::Exhausted));
+    assert_eq!(r.recv_bounded_spins(100), Err(RecvSpinError::Exhausted));
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(7).unwrap());
+
+        // A blocked sender is picked up while retrying.
+        loop {
+            match r.recv_bounded_spins(1000) {
+                Ok(msg) => {
+                    assert_eq!(msg, 7);
+                    break;
+                }
+                Err(RecvSpinError::Exhausted) => {}
+                Err(RecvSpinError::Disconnected) => panic!(),
+            }
+        }
+    })
+    .unwrap();
+
+    drop(s);
+    assert_eq!(r.recv_bounded_spins(100), Err(RecvSpinError::Disconnected));
+}
+
 #[test]
 fn recv_timeout() {
     let (s, r) = bounded::<i32>(0);
@@ -166,6 +220,25 @@ fn send() {
     .unwrap();
 }
 
+#[test]
+fn send_and_len() {
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(s.send_and_len(7), Ok(0));
+            assert_eq!(s.send_and_len(8), Ok(0));
+            assert_eq!(s.send_and_len(9), Err(SendError(9)));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            assert_eq!(r.recv(), Ok(7));
+            assert_eq!(r.recv(), Ok(8));
+        });
+    })
+    .unwrap();
+}
+
 #[test]
 fn send_timeout() {
     let (s, r) = bounded(0);
@@ -498,6 +571,123 @@ fn fairness_duplicates() {
     .unwrap();
 }
 
+#[test]
+fn select_rendezvous() {
+    const COUNT: usize = 10_000;
+
+    let (s, r) = bounded::<usize>(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                let mut sel = Select::new();
+                let oper1 = sel.recv(&r);
+                let oper = sel.select();
+                assert_eq!(oper.index(), oper1);
+                assert_eq!(oper.recv(&r), Ok(i));
+            }
+        });
+
+        for i in 0..COUNT {
+            let mut sel = Select::new();
+            let oper1 = sel.send(&s);
+            let oper = sel.select();
+            assert_eq!(oper.index(), oper1);
+            assert_eq!(oper.send(&s, i), Ok(()));
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn select_rendezvous_both_directions() {
+    const COUNT: usize = 10_000;
+
+    let (s, r) = bounded::<usize>(0);
+    let sent = AtomicUsize::new(0);
+    let received = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..2 {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    // Each thread offers both to send and to receive, so the two threads must pair
+                    // up with each other rather than block on the same side.
+                    let mut sel = Select::new();
+                    let oper1 = sel.send(&s);
+                    let oper2 = sel.recv(&r);
+                    let oper = sel.select();
+                    match oper.index() {
+                        i1 if i1 == oper1 => {
+                            oper.send(&s, i).unwrap();
+                            sent.fetch_add(1, Ordering::SeqCst);
+                        }
+                        i2 if i2 == oper2 => {
+                            oper.recv(&r).unwrap();
+                            received.fetch_add(1, Ordering::SeqCst);
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(sent.load(Ordering::SeqCst), COUNT);
+    assert_eq!(received.load(Ordering::SeqCst), COUNT);
+}
+
+#[test]
+fn try_select_rendezvous() {
+    let (s, r) = bounded::<i32>(0);
+
+    // Nobody is blocked on the other side, so neither operation is ready.
+    let mut sel = Select::new();
+    sel.recv(&r);
+    sel.send(&s);
+    assert!(sel.try_select().is_err());
+    assert!(sel.try_ready().is_err());
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(7).unwrap());
+
+        // A blocked sender makes the receive operation ready.
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r);
+        loop {
+            match sel.try_select() {
+                Err(_) => thread::sleep(ms(10)),
+                Ok(oper) => {
+                    assert_eq!(oper.index(), oper1);
+                    assert_eq!(oper.recv(&r), Ok(7));
+                    break;
+                }
+            }
+        }
+    })
+    .unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.recv(), Ok(8)));
+
+        // A blocked receiver makes the send operation ready.
+        let mut sel = Select::new();
+        let oper1 = sel.send(&s);
+        loop {
+            match sel.try_ready() {
+                Err(_) => thread::sleep(ms(10)),
+                Ok(i) => {
+                    assert_eq!(i, oper1);
+                    assert_eq!(s.try_send(8), Ok(()));
+                    break;
+                }
+            }
+        }
+    })
+    .unwrap();
+}
+
 #[test]
 fn recv_in_send() {
     let (s, r) = bounded(0);
@@ -557,3 +747,141 @@ fn channel_through_channel() {
     })
     .unwrap();
 }
+
+#[test]
+fn select_handoff_vs_timeout() {
+    const COUNT: usize = 10_000;
+
+    let (s, r) = bounded::<usize>(0);
+    let sent = AtomicUsize::new(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let mut sel = Select::new();
+            let oper = sel.send(&s);
+
+            for i in 0..COUNT {
+                // Timeouts are so short that they often race with the receiver's timeout.
+                if let Ok(op) = sel.select_timeout(Duration::new(0, 1_000)) {
+                    assert_eq!(op.index(), oper);
+                    op.send(&s, i).unwrap();
+                    sent.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        });
+
+        let mut sel = Select::new();
+        let oper = sel.recv(&r);
+
+        let mut received = vec![];
+        for _ in 0..COUNT {
+            if let Ok(op) = sel.select_timeout(Duration::new(0, 1_000)) {
+                assert_eq!(op.index(), oper);
+                received.push(op.recv(&r).unwrap());
+            }
+        }
+
+        // Wait for the sender to finish, then check that every completed send was received
+        // exactly once, in order.
+        while sent.load(Ordering::SeqCst) != received.len() {
+            match r.recv_timeout(ms(100)) {
+                Ok(msg) => received.push(msg),
+                Err(_) => break,
+            }
+        }
+        assert_eq!(sent.load(Ordering::SeqCst), received.len());
+        assert!(received.windows(2).all(|w| w[0] < w[1]));
+    })
+    .unwrap();
+}
+
+#[test]
+fn send_timeout_vs_select_recv_timeout() {
+    const COUNT: usize = 10_000;
+
+    let (s, r) = bounded::<usize>(0);
+    let sent = AtomicUsize::new(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                match s.send_timeout(i, Duration::new(0, 1_000)) {
+                    Ok(()) => {
+                        sent.fetch_add(1, Ordering::SeqCst);
+                    }
+                    // A message that was not handed off is returned to the sender.
+                    Err(SendTimeoutError::Timeout(msg)) => assert_eq!(msg, i),
+                    Err(SendTimeoutError::Disconnected(_)) => panic!(),
+                }
+            }
+        });
+
+        let mut sel = Select::new();
+        sel.recv(&r);
+
+        let mut received = 0;
+        for _ in 0..COUNT {
+            if let Ok(op) = sel.select_timeout(Duration::new(0, 1_000)) {
+                op.recv(&r).unwrap();
+                received += 1;
+            }
+        }
+        while sent.load(Ordering::SeqCst) != received {
+            match r.recv_timeout(ms(100)) {
+                Ok(_) => received += 1,
+                Err(_) => break,
+            }
+        }
+        assert_eq!(sent.load(Ordering::SeqCst), received);
+    })
+    .unwrap();
+}
+
+#[test]
+fn wait_drained() {
+    let (s, r) = bounded::<i32>(0);
+    assert!(s.wait_drained());
+
+    drop(r);
+    assert!(s.wait_drained());
+}
+
+#[test]
+fn recv_timeout_zero() {
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            // A zero timeout never waits for a sender, so these never pair up.
+            for _ in 0..1000 {
+                assert_eq!(r.recv_timeout(ms(0)), Err(RecvTimeoutError::Timeout));
+            }
+        });
+
+        for i in 0..1000 {
+            assert_eq!(s.try_send(i), Err(TrySendError::Full(i)));
+        }
+    })
+    .unwrap();
+
+    drop(s);
+    assert_eq!(r.recv_timeout(ms(0)), Err(RecvTimeoutError::Disconnected));
+}
+
+#[test]
+fn recv_peek_decide_unsupported() {
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(1));
+        thread::sleep(ms(500));
+
+        // There is no front to inspect, so the waiting sender is not paired up.
+        assert_eq!(
+            r.recv_peek_decide(|_| Decision::Take),
+            Err(PeekError::Unsupported)
+        );
+        assert_eq!(r.recv(), Ok(1));
+    })
+    .unwrap();
+}
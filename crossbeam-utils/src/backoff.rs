@@ -14,6 +14,17 @@ const YIELD_LIMIT: u32 = 10;
 /// mechanism. Each step of the back off procedure takes roughly twice as long as the previous
 /// step.
 ///
+/// There are two kinds of backing off:
+///
+/// * [`spin`] is cheap and only executes *YIELD* or *PAUSE* instructions. It should be used in
+///   lock-free loops that retry an operation because another thread made progress.
+/// * [`snooze`] should be used in blocking loops that wait for another thread to make progress.
+///   After a few steps it starts yielding the current thread to the OS scheduler.
+///
+/// Once [`is_completed`] returns `true`, backing off any further is not advised, and the thread
+/// should instead be blocked using a different synchronization mechanism, such as
+/// [`std::thread::park()`] or a [`Condvar`].
+///
 /// # Examples
 ///
 /// Backing off in a lock-free loop:
@@ -72,6 +83,8 @@ const YIELD_LIMIT: u32 = 10;
 /// }
 /// ```
 ///
+/// [`spin`]: struct.Backoff.html#method.spin
+/// [`snooze`]: struct.Backoff.html#method.snooze
 /// [`is_completed`]: struct.Backoff.html#method.is_completed
 /// [`std::thread::park()`]: https://doc.rust-lang.org/std/thread/fn.park.html
 /// [`Condvar`]: https://doc.rust-lang.org/std/sync/struct.Condvar.html
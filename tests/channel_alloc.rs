@@ -0,0 +1,59 @@
+//! Makes sure unbounded channels allocate their buffer lazily.
+//!
+//! This test lives in the root crate because `#[global_allocator]` requires Rust 1.28. It is the
+//! only test in this file so that no other test allocates while allocations are being counted.
+
+extern crate crossbeam;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam::channel::{unbounded, TryRecvError};
+
+struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocs() -> usize {
+    ALLOCS.load(Ordering::SeqCst)
+}
+
+#[test]
+fn unbounded_lazy_block() {
+    let (s, r) = unbounded::<u64>();
+
+    // Inspecting or receiving from a fresh channel doesn't allocate a block.
+    let before = allocs();
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.len(), 0);
+    assert!(r.is_empty());
+    assert_eq!(allocs(), before);
+
+    // The first send allocates the first block.
+    s.send(1).unwrap();
+    assert!(allocs() > before);
+
+    // Following sends reuse that block.
+    let before = allocs();
+    s.send(2).unwrap();
+    s.send(3).unwrap();
+    assert_eq!(allocs(), before);
+
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(r.try_recv(), Ok(3));
+}